@@ -1,18 +1,32 @@
-#[cfg(target_os = "windows")]
-use std::{env, path::Path, process::Command};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// The releases the prebuilt libraries are downloaded from with the `vendored` feature,
+/// one archive per target triple, `RTC_WRAPPER_URL` points to another archive.
+const RELEASES: &str = "https://github.com/mycrl/webrtc/releases/download";
 
-#[cfg(target_os = "windows")]
 fn main() {
-    let out_dir = env::var("OUT_DIR").unwrap();
-    let target_dir = Path::new(&out_dir)
-        .parent()
-        .unwrap()
-        .parent()
-        .unwrap()
-        .parent()
-        .unwrap()
-        .to_str()
-        .unwrap();
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=RTC_WRAPPER_DIR");
+    println!("cargo:rerun-if-env-changed=RTC_WRAPPER_URL");
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    // target/<profile>, where the executables are, so the library is found next to them.
+    let target_dir = out_dir.ancestors().nth(3).unwrap().to_path_buf();
+
+    if env::var_os("CARGO_FEATURE_VENDORED").is_some() && env::var_os("DOCS_RS").is_none() {
+        let lib_dir = match env::var_os("RTC_WRAPPER_DIR") {
+            Some(dir) => PathBuf::from(dir),
+            None => download(&out_dir),
+        };
+
+        copy_libraries(&lib_dir, &target_dir);
+        println!("cargo:rustc-link-search=all={}", lib_dir.display());
+    }
+
     //  let profile = env::var("PROFILE")
     //      .unwrap()
     //      .replace('r', "R")
@@ -25,15 +39,62 @@ fn main() {
     //      .arg(format!("{}|x64", profile))
     //      .output()
     //      .unwrap();
-    println!("cargo:rustc-link-search=all={}", target_dir);
+    println!("cargo:rustc-link-search=all={}", target_dir.display());
 }
 
-#[cfg(target_os = "macos")]
-fn main() {
-    println!("warn: not support the macos system!")
+/// Downloads and extracts the prebuilt rtc_wrapper and libwebrtc of the target, once per
+/// build directory, with the curl and tar shipped by every supported system.
+fn download(out_dir: &Path) -> PathBuf {
+    let target = env::var("TARGET").unwrap();
+    let lib_dir = out_dir.join(format!("rtc_wrapper-{}", target));
+    if lib_dir.exists() {
+        return lib_dir;
+    }
+
+    let url = env::var("RTC_WRAPPER_URL").unwrap_or_else(|_| {
+        let version = env::var("CARGO_PKG_VERSION").unwrap();
+        format!("{}/v{}/rtc_wrapper-{}.tar.gz", RELEASES, version, target)
+    });
+
+    let archive = out_dir.join(format!("rtc_wrapper-{}.tar.gz", target));
+    run(Command::new("curl").arg("-fsSL").arg("-o").arg(&archive).arg(&url), &url);
+
+    // extracted next to the final directory, so an interrupted build downloads again.
+    let partial = out_dir.join(format!("rtc_wrapper-{}.partial", target));
+    let _ = fs::remove_dir_all(&partial);
+    fs::create_dir_all(&partial).unwrap();
+    run(Command::new("tar").arg("-xzf").arg(&archive).arg("-C").arg(&partial), &url);
+    fs::rename(&partial, &lib_dir).unwrap();
+    let _ = fs::remove_file(&archive);
+    lib_dir
 }
 
-#[cfg(target_os = "linux")]
-fn main() {
-    println!("warn: not support the linux system!")
+fn run(command: &mut Command, url: &str) {
+    let status = command
+        .status()
+        .unwrap_or_else(|e| panic!("failed to run {:?}: {}", command.get_program(), e));
+    if !status.success() {
+        panic!(
+            "failed to fetch the prebuilt rtc_wrapper from {}, set RTC_WRAPPER_DIR to a directory holding it",
+            url
+        );
+    }
+}
+
+/// Copies the dynamic libraries beside the executables, where the loader of every system
+/// finds them.
+fn copy_libraries(lib_dir: &Path, target_dir: &Path) {
+    let entries = fs::read_dir(lib_dir)
+        .unwrap_or_else(|e| panic!("failed to read {}: {}", lib_dir.display(), e));
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let dynamic = matches!(
+            path.extension().and_then(|e| e.to_str()),
+            Some("dll" | "so" | "dylib")
+        );
+
+        if dynamic {
+            fs::copy(&path, target_dir.join(entry.file_name())).unwrap();
+        }
+    }
 }
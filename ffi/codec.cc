@@ -0,0 +1,462 @@
+#include "codec.h"
+#include "convert.h"
+
+#include "absl/strings/match.h"
+#include "api/video/encoded_image.h"
+#include "api/video/i420_buffer.h"
+#include "api/video_codecs/builtin_video_decoder_factory.h"
+#include "api/video_codecs/builtin_video_encoder_factory.h"
+#include "modules/video_coding/include/video_error_codes.h"
+#include "rtc_base/string_encode.h"
+
+static std::vector<webrtc::SdpVideoFormat> from_c(struct RTCVideoFormat* formats, size_t size)
+{
+    std::vector<webrtc::SdpVideoFormat> sdp_formats;
+    for (size_t i = 0; i < size; i++)
+    {
+        webrtc::SdpVideoFormat::Parameters parameters;
+        std::string fmtp = formats[i].parameters ? from_c(formats[i].parameters) : "";
+        for (auto& parameter : rtc::split(fmtp, ';'))
+        {
+            auto pair = rtc::split(parameter, '=');
+            if (pair.size() == 2)
+            {
+                parameters[std::string(pair[0])] = std::string(pair[1]);
+            }
+        }
+
+        sdp_formats.push_back(webrtc::SdpVideoFormat(from_c(formats[i].name), parameters));
+    }
+
+    return sdp_formats;
+}
+
+/*
+Finds the format of the application with the name of the negotiated format,
+the parameters are those of the application.
+*/
+static const webrtc::SdpVideoFormat* find_format(
+    const std::vector<webrtc::SdpVideoFormat>& formats,
+    const webrtc::SdpVideoFormat& format
+)
+{
+    for (auto& candidate : formats)
+    {
+        if (absl::EqualsIgnoreCase(candidate.name, format.name))
+        {
+            return &candidate;
+        }
+    }
+
+    return nullptr;
+}
+
+static std::vector<webrtc::SdpVideoFormat> merge_formats(
+    std::vector<webrtc::SdpVideoFormat> formats,
+    std::vector<webrtc::SdpVideoFormat> builtin
+)
+{
+    for (auto& format : builtin)
+    {
+        if (!find_format(formats, format))
+        {
+            formats.push_back(format);
+        }
+    }
+
+    return formats;
+}
+
+/*
+Calls a function of the application with a borrowed C copy of a format.
+*/
+static void* create_with(
+    const webrtc::SdpVideoFormat& format,
+    void* ctx,
+    void* (*create)(struct RTCVideoFormat* format, void* ctx)
+)
+{
+    std::string fmtp;
+    for (auto& parameter : format.parameters)
+    {
+        fmtp += (fmtp.empty() ? "" : ";") + parameter.first + "=" + parameter.second;
+    }
+
+    struct RTCVideoFormat c_format;
+    c_format.name = (char*)format.name.c_str();
+    c_format.parameters = fmtp.empty() ? NULL : (char*)fmtp.c_str();
+    return create(&c_format, ctx);
+}
+
+ExternalVideoEncoder::ExternalVideoEncoder(struct RTCVideoEncoderFactory factory, void* encoder)
+{
+    _factory = factory;
+    _encoder = encoder;
+}
+
+ExternalVideoEncoder::~ExternalVideoEncoder()
+{
+    _factory.release(_encoder);
+}
+
+int ExternalVideoEncoder::InitEncode(
+    const webrtc::VideoCodec* codec,
+    const webrtc::VideoEncoder::Settings& settings
+)
+{
+    auto ok = _factory.init(
+        _encoder,
+        codec->width,
+        codec->height,
+        codec->maxBitrate * 1000,
+        codec->maxFramerate
+    );
+
+    return ok ? WEBRTC_VIDEO_CODEC_OK : WEBRTC_VIDEO_CODEC_ERROR;
+}
+
+int32_t ExternalVideoEncoder::RegisterEncodeCompleteCallback(webrtc::EncodedImageCallback* callback)
+{
+    _callback = callback;
+    return WEBRTC_VIDEO_CODEC_OK;
+}
+
+int32_t ExternalVideoEncoder::Release()
+{
+    _callback = nullptr;
+    return WEBRTC_VIDEO_CODEC_OK;
+}
+
+struct EncodeContext {
+    webrtc::EncodedImageCallback* callback;
+    const webrtc::VideoFrame* frame;
+    int width;
+    int height;
+};
+
+static void on_encoded(struct RTCEncodedFrame* c_frame, void* ctx)
+{
+    auto context = (struct EncodeContext*)ctx;
+    webrtc::EncodedImage image;
+    image.SetEncodedData(webrtc::EncodedImageBuffer::Create(c_frame->data, c_frame->size));
+    image._encodedWidth = context->width;
+    image._encodedHeight = context->height;
+    image.SetTimestamp(context->frame->timestamp());
+    image.capture_time_ms_ = context->frame->render_time_ms();
+    image.rotation_ = context->frame->rotation();
+    image._frameType = c_frame->keyframe
+        ? webrtc::VideoFrameType::kVideoFrameKey
+        : webrtc::VideoFrameType::kVideoFrameDelta;
+
+    webrtc::CodecSpecificInfo info;
+    info.codecType = webrtc::kVideoCodecGeneric;
+    context->callback->OnEncodedImage(image, &info);
+}
+
+int32_t ExternalVideoEncoder::Encode(
+    const webrtc::VideoFrame& frame,
+    const std::vector<webrtc::VideoFrameType>* frame_types
+)
+{
+    if (!_callback)
+    {
+        return WEBRTC_VIDEO_CODEC_UNINITIALIZED;
+    }
+
+    auto buffer = frame.video_frame_buffer()->ToI420();
+    if (!buffer)
+    {
+        return WEBRTC_VIDEO_CODEC_ERROR;
+    }
+
+    bool keyframe = false;
+    if (frame_types)
+    {
+        for (auto type : *frame_types)
+        {
+            keyframe |= type == webrtc::VideoFrameType::kVideoFrameKey;
+        }
+    }
+
+    struct RTCVideoFrame c_frame;
+    c_frame.width = buffer->width();
+    c_frame.height = buffer->height();
+    c_frame.timestamp_us = frame.timestamp_us();
+    c_frame.rotation = (int)frame.rotation();
+    c_frame.data_y = buffer->DataY();
+    c_frame.stride_y = buffer->StrideY();
+    c_frame.data_u = buffer->DataU();
+    c_frame.stride_u = buffer->StrideU();
+    c_frame.data_v = buffer->DataV();
+    c_frame.stride_v = buffer->StrideV();
+
+    struct EncodeContext context = { _callback, &frame, buffer->width(), buffer->height() };
+    auto ok = _factory.encode(_encoder, &c_frame, keyframe, &context, on_encoded);
+    return ok ? WEBRTC_VIDEO_CODEC_OK : WEBRTC_VIDEO_CODEC_ERROR;
+}
+
+void ExternalVideoEncoder::SetRates(const webrtc::VideoEncoder::RateControlParameters& parameters)
+{
+    _factory.set_rates(_encoder, parameters.bitrate.get_sum_bps(), parameters.framerate_fps);
+}
+
+webrtc::VideoEncoder::EncoderInfo ExternalVideoEncoder::GetEncoderInfo() const
+{
+    webrtc::VideoEncoder::EncoderInfo info;
+    info.implementation_name = "external";
+    info.supports_native_handle = false;
+    return info;
+}
+
+PlayoutDelayEncoder::PlayoutDelayEncoder(
+    std::unique_ptr<webrtc::VideoEncoder> encoder,
+    struct RTCPlayoutDelay delay
+)
+{
+    _encoder = std::move(encoder);
+    _delay = delay;
+}
+
+void PlayoutDelayEncoder::SetFecControllerOverride(webrtc::FecControllerOverride* fec_controller_override)
+{
+    _encoder->SetFecControllerOverride(fec_controller_override);
+}
+
+int PlayoutDelayEncoder::InitEncode(
+    const webrtc::VideoCodec* codec,
+    const webrtc::VideoEncoder::Settings& settings
+)
+{
+    return _encoder->InitEncode(codec, settings);
+}
+
+int32_t PlayoutDelayEncoder::RegisterEncodeCompleteCallback(webrtc::EncodedImageCallback* callback)
+{
+    /* the frames go through this encoder, which sets their delay on the way. */
+    _callback = callback;
+    return _encoder->RegisterEncodeCompleteCallback(callback ? this : nullptr);
+}
+
+int32_t PlayoutDelayEncoder::Release()
+{
+    _callback = nullptr;
+    return _encoder->Release();
+}
+
+int32_t PlayoutDelayEncoder::Encode(
+    const webrtc::VideoFrame& frame,
+    const std::vector<webrtc::VideoFrameType>* frame_types
+)
+{
+    return _encoder->Encode(frame, frame_types);
+}
+
+void PlayoutDelayEncoder::SetRates(const webrtc::VideoEncoder::RateControlParameters& parameters)
+{
+    _encoder->SetRates(parameters);
+}
+
+void PlayoutDelayEncoder::OnPacketLossRateUpdate(float packet_loss_rate)
+{
+    _encoder->OnPacketLossRateUpdate(packet_loss_rate);
+}
+
+void PlayoutDelayEncoder::OnRttUpdate(int64_t rtt_ms)
+{
+    _encoder->OnRttUpdate(rtt_ms);
+}
+
+void PlayoutDelayEncoder::OnLossNotification(const webrtc::VideoEncoder::LossNotification& loss_notification)
+{
+    _encoder->OnLossNotification(loss_notification);
+}
+
+webrtc::VideoEncoder::EncoderInfo PlayoutDelayEncoder::GetEncoderInfo() const
+{
+    return _encoder->GetEncoderInfo();
+}
+
+webrtc::EncodedImageCallback::Result PlayoutDelayEncoder::OnEncodedImage(
+    const webrtc::EncodedImage& image,
+    const webrtc::CodecSpecificInfo* info
+)
+{
+    webrtc::EncodedImage delayed = image;
+    delayed.playout_delay_ = { _delay.min_ms, _delay.max_ms };
+    return _callback->OnEncodedImage(delayed, info);
+}
+
+void PlayoutDelayEncoder::OnDroppedFrame(webrtc::EncodedImageCallback::DropReason reason)
+{
+    _callback->OnDroppedFrame(reason);
+}
+
+PlayoutDelayEncoderFactory::PlayoutDelayEncoderFactory(
+    std::unique_ptr<webrtc::VideoEncoderFactory> factory,
+    struct RTCPlayoutDelay delay
+)
+{
+    _factory = std::move(factory);
+    _delay = delay;
+}
+
+std::vector<webrtc::SdpVideoFormat> PlayoutDelayEncoderFactory::GetSupportedFormats() const
+{
+    return _factory->GetSupportedFormats();
+}
+
+std::unique_ptr<webrtc::VideoEncoder> PlayoutDelayEncoderFactory::CreateVideoEncoder(
+    const webrtc::SdpVideoFormat& format
+)
+{
+    auto encoder = _factory->CreateVideoEncoder(format);
+    if (!encoder)
+    {
+        return nullptr;
+    }
+
+    return std::make_unique<PlayoutDelayEncoder>(std::move(encoder), _delay);
+}
+
+ExternalVideoDecoder::ExternalVideoDecoder(struct RTCVideoDecoderFactory factory, void* decoder)
+{
+    _factory = factory;
+    _decoder = decoder;
+}
+
+ExternalVideoDecoder::~ExternalVideoDecoder()
+{
+    _factory.release(_decoder);
+}
+
+bool ExternalVideoDecoder::Configure(const webrtc::VideoDecoder::Settings& settings)
+{
+    return true;
+}
+
+struct DecodeContext {
+    webrtc::DecodedImageCallback* callback;
+    const webrtc::EncodedImage* image;
+};
+
+static void on_decoded(struct RTCVideoFrame* c_frame, void* ctx)
+{
+    auto context = (struct DecodeContext*)ctx;
+    auto buffer = webrtc::I420Buffer::Copy(
+        c_frame->width,
+        c_frame->height,
+        c_frame->data_y,
+        c_frame->stride_y,
+        c_frame->data_u,
+        c_frame->stride_u,
+        c_frame->data_v,
+        c_frame->stride_v
+    );
+
+    auto frame = webrtc::VideoFrame::Builder()
+        .set_video_frame_buffer(buffer)
+        .set_timestamp_rtp(context->image->Timestamp())
+        .set_rotation(context->image->rotation_)
+        .build();
+    context->callback->Decoded(frame);
+}
+
+int32_t ExternalVideoDecoder::Decode(
+    const webrtc::EncodedImage& image,
+    bool missing_frames,
+    int64_t render_time_ms
+)
+{
+    if (!_callback)
+    {
+        return WEBRTC_VIDEO_CODEC_UNINITIALIZED;
+    }
+
+    struct RTCEncodedFrame c_frame;
+    c_frame.data = image.data();
+    c_frame.size = image.size();
+    c_frame.timestamp_us = render_time_ms * 1000;
+    c_frame.keyframe = image._frameType == webrtc::VideoFrameType::kVideoFrameKey;
+
+    struct DecodeContext context = { _callback, &image };
+    auto ok = _factory.decode(_decoder, &c_frame, &context, on_decoded);
+    return ok ? WEBRTC_VIDEO_CODEC_OK : WEBRTC_VIDEO_CODEC_ERROR;
+}
+
+int32_t ExternalVideoDecoder::RegisterDecodeCompleteCallback(webrtc::DecodedImageCallback* callback)
+{
+    _callback = callback;
+    return WEBRTC_VIDEO_CODEC_OK;
+}
+
+int32_t ExternalVideoDecoder::Release()
+{
+    _callback = nullptr;
+    return WEBRTC_VIDEO_CODEC_OK;
+}
+
+ExternalVideoEncoderFactory::ExternalVideoEncoderFactory(struct RTCVideoEncoderFactory* factory)
+{
+    _builtin = webrtc::CreateBuiltinVideoEncoderFactory();
+    _factory = *factory;
+    _formats = from_c(factory->formats, factory->formats_size);
+
+    /* the formats are copied, the application only keeps its context. */
+    _factory.formats = NULL;
+    _factory.formats_size = 0;
+}
+
+std::vector<webrtc::SdpVideoFormat> ExternalVideoEncoderFactory::GetSupportedFormats() const
+{
+    return merge_formats(_formats, _builtin->GetSupportedFormats());
+}
+
+std::unique_ptr<webrtc::VideoEncoder> ExternalVideoEncoderFactory::CreateVideoEncoder(
+    const webrtc::SdpVideoFormat& format
+)
+{
+    if (!find_format(_formats, format))
+    {
+        return _builtin->CreateVideoEncoder(format);
+    }
+
+    auto encoder = create_with(format, _factory.ctx, _factory.create);
+    if (!encoder)
+    {
+        return nullptr;
+    }
+
+    return std::make_unique<ExternalVideoEncoder>(_factory, encoder);
+}
+
+ExternalVideoDecoderFactory::ExternalVideoDecoderFactory(struct RTCVideoDecoderFactory* factory)
+{
+    _builtin = webrtc::CreateBuiltinVideoDecoderFactory();
+    _factory = *factory;
+    _formats = from_c(factory->formats, factory->formats_size);
+    _factory.formats = NULL;
+    _factory.formats_size = 0;
+}
+
+std::vector<webrtc::SdpVideoFormat> ExternalVideoDecoderFactory::GetSupportedFormats() const
+{
+    return merge_formats(_formats, _builtin->GetSupportedFormats());
+}
+
+std::unique_ptr<webrtc::VideoDecoder> ExternalVideoDecoderFactory::CreateVideoDecoder(
+    const webrtc::SdpVideoFormat& format
+)
+{
+    if (!find_format(_formats, format))
+    {
+        return _builtin->CreateVideoDecoder(format);
+    }
+
+    auto decoder = create_with(format, _factory.ctx, _factory.create);
+    if (!decoder)
+    {
+        return nullptr;
+    }
+
+    return std::make_unique<ExternalVideoDecoder>(_factory, decoder);
+}
@@ -0,0 +1,123 @@
+#pragma once
+
+#include <memory>
+#include <vector>
+#include "api/video_codecs/sdp_video_format.h"
+#include "api/video_codecs/video_decoder.h"
+#include "api/video_codecs/video_decoder_factory.h"
+#include "api/video_codecs/video_encoder.h"
+#include "api/video_codecs/video_encoder_factory.h"
+#include "ffi.h"
+
+/*
+An encoder implemented by the application, the frames are handed over in the I420 format
+and the encoded frames are packetized with the generic packetizer.
+*/
+class ExternalVideoEncoder: public webrtc::VideoEncoder
+{
+public:
+    ExternalVideoEncoder(struct RTCVideoEncoderFactory factory, void* encoder);
+    ~ExternalVideoEncoder();
+    int InitEncode(const webrtc::VideoCodec* codec, const webrtc::VideoEncoder::Settings& settings);
+    int32_t RegisterEncodeCompleteCallback(webrtc::EncodedImageCallback* callback);
+    int32_t Release();
+    int32_t Encode(
+        const webrtc::VideoFrame& frame,
+        const std::vector<webrtc::VideoFrameType>* frame_types
+    );
+    void SetRates(const webrtc::VideoEncoder::RateControlParameters& parameters);
+    webrtc::VideoEncoder::EncoderInfo GetEncoderInfo() const;
+private:
+    struct RTCVideoEncoderFactory _factory;
+    void* _encoder;
+    webrtc::EncodedImageCallback* _callback = nullptr;
+};
+
+class ExternalVideoDecoder: public webrtc::VideoDecoder
+{
+public:
+    ExternalVideoDecoder(struct RTCVideoDecoderFactory factory, void* decoder);
+    ~ExternalVideoDecoder();
+    bool Configure(const webrtc::VideoDecoder::Settings& settings);
+    int32_t Decode(const webrtc::EncodedImage& image, bool missing_frames, int64_t render_time_ms);
+    int32_t RegisterDecodeCompleteCallback(webrtc::DecodedImageCallback* callback);
+    int32_t Release();
+private:
+    struct RTCVideoDecoderFactory _factory;
+    void* _decoder;
+    webrtc::DecodedImageCallback* _callback = nullptr;
+};
+
+/*
+The builtin encoders followed by the ones of the application, whose formats take
+precedence over the builtin formats with the same name.
+*/
+class ExternalVideoEncoderFactory: public webrtc::VideoEncoderFactory
+{
+public:
+    ExternalVideoEncoderFactory(struct RTCVideoEncoderFactory* factory);
+    std::vector<webrtc::SdpVideoFormat> GetSupportedFormats() const;
+    std::unique_ptr<webrtc::VideoEncoder> CreateVideoEncoder(const webrtc::SdpVideoFormat& format);
+private:
+    std::unique_ptr<webrtc::VideoEncoderFactory> _builtin;
+    struct RTCVideoEncoderFactory _factory;
+    std::vector<webrtc::SdpVideoFormat> _formats;
+};
+
+/*
+Sets the playout delay of every frame of an encoder, which the playout-delay header extension
+carries to the receivers.
+*/
+class PlayoutDelayEncoder: public webrtc::VideoEncoder, public webrtc::EncodedImageCallback
+{
+public:
+    PlayoutDelayEncoder(std::unique_ptr<webrtc::VideoEncoder> encoder, struct RTCPlayoutDelay delay);
+    void SetFecControllerOverride(webrtc::FecControllerOverride* fec_controller_override);
+    int InitEncode(const webrtc::VideoCodec* codec, const webrtc::VideoEncoder::Settings& settings);
+    int32_t RegisterEncodeCompleteCallback(webrtc::EncodedImageCallback* callback);
+    int32_t Release();
+    int32_t Encode(
+        const webrtc::VideoFrame& frame,
+        const std::vector<webrtc::VideoFrameType>* frame_types
+    );
+    void SetRates(const webrtc::VideoEncoder::RateControlParameters& parameters);
+    void OnPacketLossRateUpdate(float packet_loss_rate);
+    void OnRttUpdate(int64_t rtt_ms);
+    void OnLossNotification(const webrtc::VideoEncoder::LossNotification& loss_notification);
+    webrtc::VideoEncoder::EncoderInfo GetEncoderInfo() const;
+    webrtc::EncodedImageCallback::Result OnEncodedImage(
+        const webrtc::EncodedImage& image,
+        const webrtc::CodecSpecificInfo* info
+    );
+    void OnDroppedFrame(webrtc::EncodedImageCallback::DropReason reason);
+private:
+    std::unique_ptr<webrtc::VideoEncoder> _encoder;
+    struct RTCPlayoutDelay _delay;
+    webrtc::EncodedImageCallback* _callback = nullptr;
+};
+
+/*
+The encoders of another factory, which set the playout delay of every frame.
+*/
+class PlayoutDelayEncoderFactory: public webrtc::VideoEncoderFactory
+{
+public:
+    PlayoutDelayEncoderFactory(std::unique_ptr<webrtc::VideoEncoderFactory> factory, struct RTCPlayoutDelay delay);
+    std::vector<webrtc::SdpVideoFormat> GetSupportedFormats() const;
+    std::unique_ptr<webrtc::VideoEncoder> CreateVideoEncoder(const webrtc::SdpVideoFormat& format);
+private:
+    std::unique_ptr<webrtc::VideoEncoderFactory> _factory;
+    struct RTCPlayoutDelay _delay;
+};
+
+class ExternalVideoDecoderFactory: public webrtc::VideoDecoderFactory
+{
+public:
+    ExternalVideoDecoderFactory(struct RTCVideoDecoderFactory* factory);
+    std::vector<webrtc::SdpVideoFormat> GetSupportedFormats() const;
+    std::unique_ptr<webrtc::VideoDecoder> CreateVideoDecoder(const webrtc::SdpVideoFormat& format);
+private:
+    std::unique_ptr<webrtc::VideoDecoderFactory> _builtin;
+    struct RTCVideoDecoderFactory _factory;
+    std::vector<webrtc::SdpVideoFormat> _formats;
+};
@@ -3,6 +3,8 @@
 #include <vector>
 #include <assert.h>
 #include "api/peer_connection_interface.h"
+#include "rtc_base/proxy_info.h"
+#include "rtc_base/logging.h"
 
 const std::string from_c(char* raw)
 {
@@ -61,6 +63,8 @@ webrtc::PeerConnectionInterface::RTCConfiguration from_c(struct RTCPeerConnectio
 	config.enable_dtls_srtp = true;
 	config.sdp_semantics = webrtc::SdpSemantics::kUnifiedPlan;
 	config.ice_candidate_pool_size = raw->ice_candidate_pool_size;
+	config.port_allocator_config.min_port = raw->port_range_min;
+	config.port_allocator_config.max_port = raw->port_range_max;
 
 	if (raw->ice_transport_policy)
 	{
@@ -80,10 +84,63 @@ webrtc::PeerConnectionInterface::RTCConfiguration from_c(struct RTCPeerConnectio
 	{
 		config.servers = from_c(raw->ice_servers, raw->ice_servers_size);
 	}
+
+	if (raw->continual_gathering_policy)
+	{
+		config.continual_gathering_policy = (Peer::ContinualGatheringPolicy)(raw->continual_gathering_policy - 1);
+	}
+
+	if (raw->tcp_candidate_policy)
+	{
+		config.tcp_candidate_policy = raw->tcp_candidate_policy == TCP_CANDIDATE_POLICY_DISABLED
+			? Peer::kTcpCandidatePolicyDisabled
+			: Peer::kTcpCandidatePolicyEnabled;
+	}
+
+	if (raw->stun_keepalive_interval)
+	{
+		config.stun_candidate_keepalive_interval = raw->stun_keepalive_interval;
+	}
+
+	if (raw->ice_check_interval)
+	{
+		config.stable_writable_connection_ping_interval_ms = raw->ice_check_interval;
+	}
+
+	if (raw->ice_receiving_timeout)
+	{
+		config.ice_connection_receiving_timeout = raw->ice_receiving_timeout;
+	}
+
+	if (raw->ice_inactive_timeout)
+	{
+		config.ice_inactive_timeout = raw->ice_inactive_timeout;
+	}
 	
 	return config;
 }
 
+rtc::ProxyInfo from_c(struct RTCProxy* raw)
+{
+	rtc::ProxyInfo proxy;
+	proxy.type = raw->type == PROXY_TYPE_SOCKS5 ? rtc::PROXY_SOCKS5 : rtc::PROXY_HTTPS;
+	proxy.address = rtc::SocketAddress(from_c(raw->hostname), raw->port);
+
+	if (raw->username)
+	{
+		proxy.username = from_c(raw->username);
+	}
+
+	if (raw->password)
+	{
+		rtc::InsecureCryptStringImpl password;
+		password.password() = from_c(raw->password);
+		proxy.password = rtc::CryptString(password);
+	}
+
+	return proxy;
+}
+
 const webrtc::IceCandidateInterface* from_c(struct RTCIceCandidate* ice_candidate)
 {
 	int index = ice_candidate->sdp_mline_index;
@@ -119,8 +176,28 @@ webrtc::SessionDescriptionInterface* from_c(struct RTCSessionDescription* desc)
 	return webrtc::CreateSessionDescription(type, sdp, nullptr);
 }
 
-struct RTCSessionDescription* into_c(webrtc::SessionDescriptionInterface* desc)
+webrtc::PeerConnectionInterface::RTCOfferAnswerOptions from_c(struct RTCOfferAnswerOptions* raw)
 {
+	webrtc::PeerConnectionInterface::RTCOfferAnswerOptions options;
+	if (raw == NULL)
+	{
+		return options;
+	}
+
+	options.ice_restart = raw->ice_restart;
+	options.voice_activity_detection = raw->voice_activity_detection;
+	options.offer_to_receive_audio = raw->offer_to_receive_audio;
+	options.offer_to_receive_video = raw->offer_to_receive_video;
+	return options;
+}
+
+struct RTCSessionDescription* into_c(const webrtc::SessionDescriptionInterface* desc)
+{
+	if (desc == nullptr)
+	{
+		return NULL;
+	}
+
 	auto c_desc = (struct RTCSessionDescription*)malloc(sizeof(struct RTCSessionDescription));
 	if (!c_desc)
 	{
@@ -129,14 +206,263 @@ struct RTCSessionDescription* into_c(webrtc::SessionDescriptionInterface* desc)
 
 	std::string sdp;
 	desc->ToString(&sdp);
-	c_desc->sdp = (char*)malloc(sizeof(char) * sdp.size());
+	c_desc->sdp = into_c(sdp);
 	if (!c_desc->sdp)
 	{
+		free(c_desc);
 		return NULL;
 	}
 
-    strcpy((char*)c_desc->sdp, sdp.c_str());
-	c_desc->type = (enum RTC_SESSION_DESCRIPTION_TYPE)(desc->GetType());
+	c_desc->type = (enum RTC_SESSION_DESCRIPTION_TYPE)((int)desc->GetType() + 1);
 
 	return c_desc;
-}
\ No newline at end of file
+}
+
+char* into_c(const std::string& str)
+{
+	char* c_str = (char*)malloc(sizeof(char) * (str.size() + 1));
+	if (!c_str)
+	{
+		return NULL;
+	}
+
+	memcpy(c_str, str.c_str(), str.size() + 1);
+	return c_str;
+}
+
+struct RTCPeerConnectionConfigure* into_c(const webrtc::PeerConnectionInterface::RTCConfiguration& config)
+{
+	auto c_config = (struct RTCPeerConnectionConfigure*)calloc(1, sizeof(struct RTCPeerConnectionConfigure));
+	if (!c_config)
+	{
+		return NULL;
+	}
+
+	c_config->bundle_policy = (enum BUNDLE_POLICY)(config.bundle_policy + 1);
+	c_config->ice_transport_policy = (enum ICE_TRANSPORT_POLICY)(config.type + 1);
+	c_config->rtcp_mux_policy = (enum RTCP_MUX_POLICY)(config.rtcp_mux_policy + 1);
+	c_config->ice_candidate_pool_size = config.ice_candidate_pool_size;
+	c_config->port_range_min = config.port_allocator_config.min_port;
+	c_config->port_range_max = config.port_allocator_config.max_port;
+	c_config->continual_gathering_policy = (enum CONTINUAL_GATHERING_POLICY)(config.continual_gathering_policy + 1);
+	c_config->tcp_candidate_policy = config.tcp_candidate_policy == webrtc::PeerConnectionInterface::kTcpCandidatePolicyDisabled
+		? TCP_CANDIDATE_POLICY_DISABLED
+		: TCP_CANDIDATE_POLICY_ENABLED;
+	c_config->stun_keepalive_interval = config.stun_candidate_keepalive_interval.value_or(0);
+	c_config->ice_check_interval = config.stable_writable_connection_ping_interval_ms.value_or(0);
+	c_config->ice_receiving_timeout = config.ice_connection_receiving_timeout.value_or(0);
+	c_config->ice_inactive_timeout = config.ice_inactive_timeout.value_or(0);
+
+	int size = (int)config.servers.size();
+	c_config->ice_servers = (struct RTCIceServer*)calloc(size, sizeof(struct RTCIceServer));
+	c_config->ice_servers_size = c_config->ice_servers ? size : 0;
+	for (int i = 0; i < c_config->ice_servers_size; i++)
+	{
+		auto& server = config.servers[i];
+		auto& c_server = c_config->ice_servers[i];
+		c_server.username = server.username.empty() ? NULL : into_c(server.username);
+		c_server.credential = server.password.empty() ? NULL : into_c(server.password);
+
+		auto urls = server.urls.empty() ? std::vector<std::string>{ server.uri } : server.urls;
+		c_server.urls = (char**)calloc(urls.size(), sizeof(char*));
+		c_server.urls_size = c_server.urls ? (int)urls.size() : 0;
+		for (int k = 0; k < c_server.urls_size; k++)
+		{
+			c_server.urls[k] = into_c(urls[k]);
+		}
+	}
+
+	return c_config;
+}
+
+void free_c(struct RTCPeerConnectionConfigure* config)
+{
+	for (int i = 0; i < config->ice_servers_size; i++)
+	{
+		auto& server = config->ice_servers[i];
+		for (int k = 0; k < server.urls_size; k++)
+		{
+			free(server.urls[k]);
+		}
+
+		free(server.urls);
+		free(server.username);
+		free(server.credential);
+	}
+
+	free(config->ice_servers);
+	free(config->peer_identity);
+	free(config);
+}
+
+enum CONNECTION_STATE into_c(webrtc::PeerConnectionInterface::PeerConnectionState state)
+{
+	/* the C enum does not follow the order of the native one. */
+	enum CONNECTION_STATE c_state;
+	switch (state)
+	{
+	case webrtc::PeerConnectionInterface::PeerConnectionState::kNew:
+		c_state = CONNECTION_STATE_NEW;
+		break;
+	case webrtc::PeerConnectionInterface::PeerConnectionState::kConnecting:
+		c_state = CONNECTION_STATE_CHECKING;
+		break;
+	case webrtc::PeerConnectionInterface::PeerConnectionState::kConnected:
+		c_state = CONNECTION_STATE_CONNECTED;
+		break;
+	case webrtc::PeerConnectionInterface::PeerConnectionState::kDisconnected:
+		c_state = CONNECTION_STATE_DISCONNECTED;
+		break;
+	case webrtc::PeerConnectionInterface::PeerConnectionState::kFailed:
+		c_state = CONNECTION_STATE_FAILED;
+		break;
+	default:
+		c_state = CONNECTION_STATE_CLOSED;
+		break;
+	}
+
+	return c_state;
+}
+
+rtc::LoggingSeverity from_c(enum LOG_SEVERITY severity)
+{
+	switch (severity)
+	{
+	case LOG_SEVERITY_VERBOSE:
+		return rtc::LS_VERBOSE;
+	case LOG_SEVERITY_INFO:
+		return rtc::LS_INFO;
+	case LOG_SEVERITY_WARNING:
+		return rtc::LS_WARNING;
+	case LOG_SEVERITY_ERROR:
+		return rtc::LS_ERROR;
+	default:
+		return rtc::LS_NONE;
+	}
+}
+
+enum LOG_SEVERITY into_c(rtc::LoggingSeverity severity)
+{
+	switch (severity)
+	{
+	case rtc::LS_SENSITIVE:
+	case rtc::LS_VERBOSE:
+		return LOG_SEVERITY_VERBOSE;
+	case rtc::LS_INFO:
+		return LOG_SEVERITY_INFO;
+	case rtc::LS_WARNING:
+		return LOG_SEVERITY_WARNING;
+	case rtc::LS_ERROR:
+		return LOG_SEVERITY_ERROR;
+	default:
+		return LOG_SEVERITY_NONE;
+	}
+}
+
+webrtc::RtpTransceiverDirection from_c(enum TRANSCEIVER_DIRECTION direction)
+{
+	switch (direction)
+	{
+	case TRANSCEIVER_DIRECTION_SENDRECV:
+		return webrtc::RtpTransceiverDirection::kSendRecv;
+	case TRANSCEIVER_DIRECTION_SENDONLY:
+		return webrtc::RtpTransceiverDirection::kSendOnly;
+	case TRANSCEIVER_DIRECTION_RECVONLY:
+		return webrtc::RtpTransceiverDirection::kRecvOnly;
+	case TRANSCEIVER_DIRECTION_INACTIVE:
+		return webrtc::RtpTransceiverDirection::kInactive;
+	default:
+		return webrtc::RtpTransceiverDirection::kStopped;
+	}
+}
+
+enum TRANSCEIVER_DIRECTION into_c(webrtc::RtpTransceiverDirection direction)
+{
+	switch (direction)
+	{
+	case webrtc::RtpTransceiverDirection::kSendRecv:
+		return TRANSCEIVER_DIRECTION_SENDRECV;
+	case webrtc::RtpTransceiverDirection::kSendOnly:
+		return TRANSCEIVER_DIRECTION_SENDONLY;
+	case webrtc::RtpTransceiverDirection::kRecvOnly:
+		return TRANSCEIVER_DIRECTION_RECVONLY;
+	case webrtc::RtpTransceiverDirection::kInactive:
+		return TRANSCEIVER_DIRECTION_INACTIVE;
+	default:
+		return TRANSCEIVER_DIRECTION_STOPPED;
+	}
+}
+
+enum MEDIA_TYPE into_c(cricket::MediaType kind)
+{
+	return kind == cricket::MediaType::MEDIA_TYPE_AUDIO
+		? MEDIA_TYPE_AUDIO
+		: MEDIA_TYPE_VIDEO;
+}
+
+cricket::MediaType from_c(enum MEDIA_TYPE kind)
+{
+	return kind == MEDIA_TYPE_AUDIO
+		? cricket::MediaType::MEDIA_TYPE_AUDIO
+		: cricket::MediaType::MEDIA_TYPE_VIDEO;
+}
+
+webrtc::Priority from_c(enum PRIORITY priority)
+{
+	switch (priority)
+	{
+	case PRIORITY_VERY_LOW:
+		return webrtc::Priority::kVeryLow;
+	case PRIORITY_MEDIUM:
+		return webrtc::Priority::kMedium;
+	case PRIORITY_HIGH:
+		return webrtc::Priority::kHigh;
+	default:
+		return webrtc::Priority::kLow;
+	}
+}
+
+webrtc::DegradationPreference from_c(enum DEGRADATION_PREFERENCE preference)
+{
+	switch (preference)
+	{
+	case DEGRADATION_PREFERENCE_DISABLED:
+		return webrtc::DegradationPreference::DISABLED;
+	case DEGRADATION_PREFERENCE_MAINTAIN_FRAMERATE:
+		return webrtc::DegradationPreference::MAINTAIN_FRAMERATE;
+	case DEGRADATION_PREFERENCE_MAINTAIN_RESOLUTION:
+		return webrtc::DegradationPreference::MAINTAIN_RESOLUTION;
+	default:
+		return webrtc::DegradationPreference::BALANCED;
+	}
+}
+
+enum PRIORITY into_c(webrtc::Priority priority)
+{
+	switch (priority)
+	{
+	case webrtc::Priority::kVeryLow:
+		return PRIORITY_VERY_LOW;
+	case webrtc::Priority::kMedium:
+		return PRIORITY_MEDIUM;
+	case webrtc::Priority::kHigh:
+		return PRIORITY_HIGH;
+	default:
+		return PRIORITY_LOW;
+	}
+}
+
+enum DEGRADATION_PREFERENCE into_c(webrtc::DegradationPreference preference)
+{
+	switch (preference)
+	{
+	case webrtc::DegradationPreference::DISABLED:
+		return DEGRADATION_PREFERENCE_DISABLED;
+	case webrtc::DegradationPreference::MAINTAIN_FRAMERATE:
+		return DEGRADATION_PREFERENCE_MAINTAIN_FRAMERATE;
+	case webrtc::DegradationPreference::MAINTAIN_RESOLUTION:
+		return DEGRADATION_PREFERENCE_MAINTAIN_RESOLUTION;
+	default:
+		return DEGRADATION_PREFERENCE_BALANCED;
+	}
+}
@@ -4,6 +4,8 @@
 #include <string>
 #include <vector>
 #include "api/peer_connection_interface.h"
+#include "rtc_base/proxy_info.h"
+#include "rtc_base/logging.h"
 
 /*
 * c type to c++ type
@@ -13,10 +15,30 @@ std::vector<std::string> from_c(char** raw, int size);
 webrtc::PeerConnectionInterface::IceServer from_c(struct RTCIceServer raw);
 webrtc::PeerConnectionInterface::IceServers from_c(struct RTCIceServer* raw, int size);
 webrtc::PeerConnectionInterface::RTCConfiguration from_c(struct RTCPeerConnectionConfigure* raw);
+rtc::ProxyInfo from_c(struct RTCProxy* raw);
 const webrtc::IceCandidateInterface* from_c(struct RTCIceCandidate* candidate);
 webrtc::SessionDescriptionInterface* from_c(struct RTCSessionDescription* desc);
+webrtc::PeerConnectionInterface::RTCOfferAnswerOptions from_c(struct RTCOfferAnswerOptions* raw);
+rtc::LoggingSeverity from_c(enum LOG_SEVERITY severity);
+webrtc::RtpTransceiverDirection from_c(enum TRANSCEIVER_DIRECTION direction);
+cricket::MediaType from_c(enum MEDIA_TYPE kind);
+webrtc::Priority from_c(enum PRIORITY priority);
+webrtc::DegradationPreference from_c(enum DEGRADATION_PREFERENCE preference);
 
 /*
 * c++ type to c type
 */
-struct RTCSessionDescription* into_c(webrtc::SessionDescriptionInterface* raw);
\ No newline at end of file
+char* into_c(const std::string& str);
+struct RTCSessionDescription* into_c(const webrtc::SessionDescriptionInterface* raw);
+struct RTCPeerConnectionConfigure* into_c(const webrtc::PeerConnectionInterface::RTCConfiguration& config);
+enum CONNECTION_STATE into_c(webrtc::PeerConnectionInterface::PeerConnectionState state);
+enum LOG_SEVERITY into_c(rtc::LoggingSeverity severity);
+enum TRANSCEIVER_DIRECTION into_c(webrtc::RtpTransceiverDirection direction);
+enum MEDIA_TYPE into_c(cricket::MediaType kind);
+enum PRIORITY into_c(webrtc::Priority priority);
+enum DEGRADATION_PREFERENCE into_c(webrtc::DegradationPreference preference);
+
+/*
+* free c type
+*/
+void free_c(struct RTCPeerConnectionConfigure* config);
\ No newline at end of file
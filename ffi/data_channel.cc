@@ -0,0 +1,155 @@
+#include "data_channel.h"
+#include "convert.h"
+
+static enum DATA_CHANNEL_STATE into_c(webrtc::DataChannelInterface::DataState state)
+{
+    switch (state)
+    {
+    case webrtc::DataChannelInterface::kConnecting:
+        return DATA_CHANNEL_STATE_CONNECTING;
+    case webrtc::DataChannelInterface::kOpen:
+        return DATA_CHANNEL_STATE_OPEN;
+    case webrtc::DataChannelInterface::kClosing:
+        return DATA_CHANNEL_STATE_CLOSING;
+    default:
+        return DATA_CHANNEL_STATE_CLOSED;
+    }
+}
+
+DataChannelObserver::DataChannelObserver(
+    rtc::scoped_refptr<webrtc::DataChannelInterface> channel,
+    void* ctx,
+    void (*on_message)(uint8_t* data, size_t size, bool binary, void* ctx),
+    void (*on_state_change)(enum DATA_CHANNEL_STATE state, void* ctx),
+    void (*on_buffered_amount_change)(uint64_t buffered_amount, void* ctx)
+)
+{
+    _channel = channel;
+    _ctx = ctx;
+    _on_message = on_message;
+    _on_state_change = on_state_change;
+    _on_buffered_amount_change = on_buffered_amount_change;
+}
+
+void DataChannelObserver::OnStateChange()
+{
+    _on_state_change(into_c(_channel->state()), _ctx);
+}
+
+void DataChannelObserver::OnMessage(const webrtc::DataBuffer& buffer)
+{
+    _on_message((uint8_t*)buffer.data.data(), buffer.data.size(), buffer.binary, _ctx);
+}
+
+void DataChannelObserver::OnBufferedAmountChange(uint64_t sent_data_size)
+{
+    _on_buffered_amount_change(_channel->buffered_amount(), _ctx);
+}
+
+struct RTCDataChannel* into_c(rtc::scoped_refptr<webrtc::DataChannelInterface> channel)
+{
+    struct RTCDataChannelRef* c_channel = new RTCDataChannelRef();
+    c_channel->channel = channel;
+    c_channel->_label = channel->label();
+
+    c_channel->id = channel->id();
+    c_channel->label = (char*)c_channel->_label.c_str();
+    c_channel->ordered = channel->ordered();
+    c_channel->reliable = channel->reliable();
+    return c_channel;
+}
+
+void rtc_free_data_channel(struct RTCDataChannel* channel)
+{
+    rtc_remove_data_channel_observer(channel);
+    delete static_cast<RTCDataChannelRef*>(channel);
+}
+
+enum DATA_CHANNEL_STATE rtc_get_data_channel_state(struct RTCDataChannel* c_channel)
+{
+    return into_c(static_cast<RTCDataChannelRef*>(c_channel)->channel->state());
+}
+
+uint64_t rtc_get_data_channel_buffered_amount(struct RTCDataChannel* c_channel)
+{
+    return static_cast<RTCDataChannelRef*>(c_channel)->channel->buffered_amount();
+}
+
+enum PRIORITY rtc_get_data_channel_priority(struct RTCDataChannel* c_channel)
+{
+    return into_c(static_cast<RTCDataChannelRef*>(c_channel)->channel->priority());
+}
+
+void rtc_set_data_channel_observer(
+    struct RTCDataChannel* c_channel,
+    void* ctx,
+    void (*on_message)(uint8_t* data, size_t size, bool binary, void* ctx),
+    void (*on_state_change)(enum DATA_CHANNEL_STATE state, void* ctx),
+    void (*on_buffered_amount_change)(uint64_t buffered_amount, void* ctx)
+)
+{
+    auto channel = static_cast<RTCDataChannelRef*>(c_channel);
+    rtc_remove_data_channel_observer(c_channel);
+    channel->observer = std::make_unique<DataChannelObserver>(
+        channel->channel,
+        ctx,
+        on_message,
+        on_state_change,
+        on_buffered_amount_change
+    );
+
+    channel->channel->RegisterObserver(channel->observer.get());
+}
+
+void rtc_remove_data_channel_observer(struct RTCDataChannel* c_channel)
+{
+    auto channel = static_cast<RTCDataChannelRef*>(c_channel);
+    if (channel->observer)
+    {
+        /* the observer is unregistered on the signaling thread, no event is fired once it returns. */
+        channel->channel->UnregisterObserver();
+        channel->observer = nullptr;
+    }
+}
+
+bool rtc_send_data_channel_message(struct RTCDataChannel* c_channel, uint8_t* data, size_t size, bool binary)
+{
+    auto channel = static_cast<RTCDataChannelRef*>(c_channel)->channel;
+    return channel->Send(webrtc::DataBuffer(rtc::CopyOnWriteBuffer(data, size), binary));
+}
+
+void rtc_close_data_channel(struct RTCDataChannel* c_channel)
+{
+    static_cast<RTCDataChannelRef*>(c_channel)->channel->Close();
+}
+
+struct RTCDataChannel* rtc_create_data_channel(
+    struct RTCPeerConnection* rtc,
+    char* label,
+    struct RTCDataChannelInit* c_init,
+    struct RTCError* error
+)
+{
+    webrtc::DataChannelInit init;
+    init.ordered = c_init->ordered;
+    init.priority = from_c(c_init->priority);
+    if (c_init->max_retransmit_time >= 0)
+    {
+        init.maxRetransmitTime = c_init->max_retransmit_time;
+    }
+
+    if (c_init->max_retransmits >= 0)
+    {
+        init.maxRetransmits = c_init->max_retransmits;
+    }
+
+    auto result = rtc->peer_connection->CreateDataChannelOrError(from_c(label), &init);
+    if (!result.ok())
+    {
+        error->code = (int)result.error().type();
+        error->message = into_c(std::string(result.error().message()));
+        return NULL;
+    }
+
+    return into_c(result.MoveValue());
+}
@@ -0,0 +1,42 @@
+#pragma once
+
+#include <memory>
+#include <string>
+#include "api/data_channel_interface.h"
+#include "ffi.h"
+
+/*
+Forwards the events of a data channel to the handlers registered by rust.
+*/
+class DataChannelObserver: public webrtc::DataChannelObserver
+{
+public:
+    DataChannelObserver(
+        rtc::scoped_refptr<webrtc::DataChannelInterface> channel,
+        void* ctx,
+        void (*on_message)(uint8_t* data, size_t size, bool binary, void* ctx),
+        void (*on_state_change)(enum DATA_CHANNEL_STATE state, void* ctx),
+        void (*on_buffered_amount_change)(uint64_t buffered_amount, void* ctx)
+    );
+    void OnStateChange() override;
+    void OnMessage(const webrtc::DataBuffer& buffer) override;
+    void OnBufferedAmountChange(uint64_t sent_data_size) override;
+private:
+    rtc::scoped_refptr<webrtc::DataChannelInterface> _channel;
+    void* _ctx;
+    void (*_on_message)(uint8_t* data, size_t size, bool binary, void* ctx);
+    void (*_on_state_change)(enum DATA_CHANNEL_STATE state, void* ctx);
+    void (*_on_buffered_amount_change)(uint64_t buffered_amount, void* ctx);
+};
+
+/*
+A RTCDataChannel handed over to rust, it keeps the native channel alive
+and owns the strings pointed to by the C struct until it is freed.
+*/
+struct RTCDataChannelRef: public RTCDataChannel {
+    rtc::scoped_refptr<webrtc::DataChannelInterface> channel;
+    std::string _label;
+    std::unique_ptr<DataChannelObserver> observer;
+};
+
+struct RTCDataChannel* into_c(rtc::scoped_refptr<webrtc::DataChannelInterface> channel);
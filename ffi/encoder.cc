@@ -0,0 +1,223 @@
+#include "encoder.h"
+
+#include "api/audio_codecs/opus/audio_encoder_opus.h"
+#include "api/video/i420_buffer.h"
+#include "modules/video_coding/codecs/vp8/include/vp8.h"
+
+/* libopus and the RTP clock of Opus always run at 48kHz. */
+const int OPUS_SAMPLE_RATE = 48000;
+
+VideoEncoder::VideoEncoder(int bitrate, int frame_rate)
+{
+    _bitrate = bitrate;
+    _frame_rate = frame_rate;
+}
+
+bool VideoEncoder::Initialize(int width, int height)
+{
+    webrtc::VideoCodec settings;
+    settings.codecType = webrtc::kVideoCodecVP8;
+    settings.width = width;
+    settings.height = height;
+    settings.startBitrate = _bitrate / 1000;
+    settings.maxBitrate = _bitrate / 1000;
+    settings.minBitrate = 30;
+    settings.maxFramerate = _frame_rate;
+    settings.qpMax = 56;
+    *(settings.VP8()) = webrtc::VideoEncoder::GetDefaultVp8Settings();
+
+    _encoder = webrtc::VP8Encoder::Create();
+    auto capabilities = webrtc::VideoEncoder::Capabilities(false);
+    auto result = _encoder->InitEncode(
+        &settings,
+        webrtc::VideoEncoder::Settings(capabilities, 1, 1200)
+    );
+
+    if (result != WEBRTC_VIDEO_CODEC_OK)
+    {
+        _encoder = nullptr;
+        return false;
+    }
+
+    webrtc::VideoBitrateAllocation allocation;
+    allocation.SetBitrate(0, 0, _bitrate);
+    _encoder->SetRates(webrtc::VideoEncoder::RateControlParameters(allocation, _frame_rate));
+    _encoder->RegisterEncodeCompleteCallback(this);
+    _width = width;
+    _height = height;
+    return true;
+}
+
+bool VideoEncoder::Encode(
+    struct RTCVideoFrame* c_frame,
+    bool keyframe,
+    void* ctx,
+    void (*callback)(struct RTCEncodedFrame* frame, void* ctx)
+)
+{
+    /* the first frame of a new resolution is always a keyframe. */
+    if (!_encoder || c_frame->width != _width || c_frame->height != _height)
+    {
+        if (!Initialize(c_frame->width, c_frame->height))
+        {
+            return false;
+        }
+
+        keyframe = true;
+    }
+
+    auto buffer = webrtc::I420Buffer::Copy(
+        c_frame->width,
+        c_frame->height,
+        c_frame->data_y,
+        c_frame->stride_y,
+        c_frame->data_u,
+        c_frame->stride_u,
+        c_frame->data_v,
+        c_frame->stride_v
+    );
+
+    auto frame = webrtc::VideoFrame::Builder()
+        .set_video_frame_buffer(buffer)
+        .set_timestamp_us(c_frame->timestamp_us)
+        .set_timestamp_rtp((uint32_t)(c_frame->timestamp_us * 90 / 1000))
+        .build();
+    std::vector<webrtc::VideoFrameType> types{
+        keyframe ? webrtc::VideoFrameType::kVideoFrameKey : webrtc::VideoFrameType::kVideoFrameDelta
+    };
+
+    /* libvpx encodes synchronously, the callback is only valid for this call. */
+    _timestamp_us = c_frame->timestamp_us;
+    _callback = callback;
+    _ctx = ctx;
+    auto result = _encoder->Encode(frame, &types);
+    _callback = NULL;
+    _ctx = NULL;
+    return result == WEBRTC_VIDEO_CODEC_OK;
+}
+
+webrtc::EncodedImageCallback::Result VideoEncoder::OnEncodedImage(
+    const webrtc::EncodedImage& image,
+    const webrtc::CodecSpecificInfo* info
+)
+{
+    if (_callback != NULL)
+    {
+        struct RTCEncodedFrame c_frame;
+        c_frame.data = image.data();
+        c_frame.size = image.size();
+        c_frame.timestamp_us = _timestamp_us;
+        c_frame.keyframe = image._frameType == webrtc::VideoFrameType::kVideoFrameKey;
+        _callback(&c_frame, _ctx);
+    }
+
+    return Result(Result::OK);
+}
+
+AudioEncoder::AudioEncoder(int channels, int bitrate)
+{
+    webrtc::AudioEncoderOpusConfig config;
+    config.num_channels = channels;
+    config.bitrate_bps = bitrate;
+    config.frame_size_ms = 20;
+    config.application = channels > 1
+        ? webrtc::AudioEncoderOpusConfig::ApplicationMode::kAudio
+        : webrtc::AudioEncoderOpusConfig::ApplicationMode::kVoip;
+    _encoder = webrtc::AudioEncoderOpus::MakeAudioEncoder(config, 111);
+    _channels = channels;
+}
+
+bool AudioEncoder::Encode(
+    struct RTCAudioFrame* c_frame,
+    void* ctx,
+    void (*callback)(struct RTCEncodedFrame* frame, void* ctx)
+)
+{
+    if (!_encoder || c_frame->channels != _channels)
+    {
+        return false;
+    }
+
+    /* the frames of a sink keep their rate, a change of rate drops the pending samples. */
+    if (c_frame->sample_rate != _sample_rate)
+    {
+        _sample_rate = c_frame->sample_rate;
+        _pending.clear();
+    }
+
+    _pending.insert(
+        _pending.end(),
+        c_frame->data,
+        c_frame->data + c_frame->frames * c_frame->channels
+    );
+
+    size_t chunk = _sample_rate / 100 * _channels;
+    size_t output = OPUS_SAMPLE_RATE / 100 * _channels;
+    std::vector<int16_t> resampled(output);
+    _resampler.InitializeIfNeeded(_sample_rate, OPUS_SAMPLE_RATE, _channels);
+
+    size_t offset = 0;
+    for (; offset + chunk <= _pending.size(); offset += chunk)
+    {
+        _resampler.Resample(_pending.data() + offset, chunk, resampled.data(), output);
+
+        rtc::Buffer encoded;
+        auto info = _encoder->Encode(_samples, resampled, &encoded);
+        _samples += OPUS_SAMPLE_RATE / 100;
+        if (info.encoded_bytes == 0)
+        {
+            continue;
+        }
+
+        struct RTCEncodedFrame c_encoded;
+        c_encoded.data = encoded.data();
+        c_encoded.size = encoded.size();
+        c_encoded.timestamp_us = (int64_t)info.encoded_timestamp * 1000000 / OPUS_SAMPLE_RATE;
+        c_encoded.keyframe = true;
+        callback(&c_encoded, ctx);
+    }
+
+    _pending.erase(_pending.begin(), _pending.begin() + offset);
+    return true;
+}
+
+struct RTCVideoEncoder* rtc_create_video_encoder(int bitrate, int frame_rate)
+{
+    return (struct RTCVideoEncoder*)new VideoEncoder(bitrate, frame_rate);
+}
+
+bool rtc_video_encoder_encode(
+    struct RTCVideoEncoder* encoder,
+    struct RTCVideoFrame* frame,
+    bool keyframe,
+    void* ctx,
+    void (*callback)(struct RTCEncodedFrame* frame, void* ctx)
+)
+{
+    return ((VideoEncoder*)encoder)->Encode(frame, keyframe, ctx, callback);
+}
+
+void rtc_free_video_encoder(struct RTCVideoEncoder* encoder)
+{
+    delete (VideoEncoder*)encoder;
+}
+
+struct RTCAudioEncoder* rtc_create_audio_encoder(int channels, int bitrate)
+{
+    return (struct RTCAudioEncoder*)new AudioEncoder(channels, bitrate);
+}
+
+bool rtc_audio_encoder_encode(
+    struct RTCAudioEncoder* encoder,
+    struct RTCAudioFrame* frame,
+    void* ctx,
+    void (*callback)(struct RTCEncodedFrame* frame, void* ctx)
+)
+{
+    return ((AudioEncoder*)encoder)->Encode(frame, ctx, callback);
+}
+
+void rtc_free_audio_encoder(struct RTCAudioEncoder* encoder)
+{
+    delete (AudioEncoder*)encoder;
+}
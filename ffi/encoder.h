@@ -0,0 +1,61 @@
+#pragma once
+
+#include <memory>
+#include <vector>
+#include "api/audio_codecs/audio_encoder.h"
+#include "api/video_codecs/video_encoder.h"
+#include "common_audio/resampler/include/push_resampler.h"
+#include "ffi.h"
+
+/*
+A VP8 encoder of the frames delivered by a video sink, the encoder is
+initialized again whenever the resolution of the frames changes.
+*/
+class VideoEncoder: public webrtc::EncodedImageCallback
+{
+public:
+    VideoEncoder(int bitrate, int frame_rate);
+    bool Encode(
+        struct RTCVideoFrame* frame,
+        bool keyframe,
+        void* ctx,
+        void (*callback)(struct RTCEncodedFrame* frame, void* ctx)
+    );
+    Result OnEncodedImage(
+        const webrtc::EncodedImage& image,
+        const webrtc::CodecSpecificInfo* info
+    );
+private:
+    bool Initialize(int width, int height);
+
+    std::unique_ptr<webrtc::VideoEncoder> _encoder;
+    int _bitrate;
+    int _frame_rate;
+    int _width = 0;
+    int _height = 0;
+    int64_t _timestamp_us = 0;
+    void (*_callback)(struct RTCEncodedFrame* frame, void* ctx) = NULL;
+    void* _ctx = NULL;
+};
+
+/*
+An Opus encoder of the frames delivered by an audio sink, the samples are resampled
+to 48kHz and encoded by chunks of 10ms into packets of 20ms.
+*/
+class AudioEncoder
+{
+public:
+    AudioEncoder(int channels, int bitrate);
+    bool Encode(
+        struct RTCAudioFrame* frame,
+        void* ctx,
+        void (*callback)(struct RTCEncodedFrame* frame, void* ctx)
+    );
+private:
+    std::unique_ptr<webrtc::AudioEncoder> _encoder;
+    webrtc::PushResampler<int16_t> _resampler;
+    int _channels;
+    int _sample_rate = 0;
+    uint32_t _samples = 0;
+    std::vector<int16_t> _pending;
+};
@@ -2,57 +2,222 @@
 #include "convert.h"
 #include "observer.h"
 #include "promisify.h"
+#include "network.h"
+#include "socket.h"
+#include "media_stream_track.h"
+#include "runtime.h"
 
-#include "api/create_peerconnection_factory.h"
 #include "api/peer_connection_interface.h"
-#include "api/audio_codecs/builtin_audio_decoder_factory.h"
-#include "api/audio_codecs/builtin_audio_encoder_factory.h"
-#include "api/video_codecs/builtin_video_decoder_factory.h"
-#include "api/video_codecs/builtin_video_encoder_factory.h"
+#include "p2p/base/basic_packet_socket_factory.h"
+#include "p2p/client/basic_port_allocator.h"
 
-struct RTCPeerConnection* create_rtc_peer_connection(struct RTCPeerConnectionConfigure* c_config) 
+struct RTCPeerConnection* create_rtc_peer_connection(
+    struct RTCRuntime* runtime,
+    struct RTCPeerConnectionConfigure* c_config
+) 
 {
     struct RTCPeerConnection* rtc = new RTCPeerConnection();
-    auto peer_factory = webrtc::CreatePeerConnectionFactory(
-        nullptr /* network_thread */,
-        nullptr /* worker_thread */,
-        nullptr /* signaling_thread */,
-        nullptr /* default_adm */,
-        webrtc::CreateBuiltinAudioEncoderFactory(),
-        webrtc::CreateBuiltinAudioDecoderFactory(),
-        webrtc::CreateBuiltinVideoEncoderFactory(),
-        webrtc::CreateBuiltinVideoDecoderFactory(),
-        nullptr /* audio_mixer */,
-        nullptr /* audio_processing */
-    );
+    rtc->runtime = runtime;
 
-    if (!peer_factory) 
+    // the port allocator is owned by the wrapper rather than the factory,
+    // so that the network interfaces it gathers on can be filtered.
+    auto socket_server = runtime->network_thread->socketserver();
+    runtime->network_thread->Invoke<void>(RTC_FROM_HERE, [&]()
     {
-        return NULL;
+        NetworkFilter filter;
+        filter.allowed = from_c(c_config->allowed_interfaces, c_config->allowed_interfaces_size);
+        filter.denied = from_c(c_config->denied_interfaces, c_config->denied_interfaces_size);
+        filter.ipv6 = !c_config->disable_ipv6;
+        filter.link_local = !c_config->disable_link_local;
+        filter.vpn = !c_config->disable_vpn;
+
+        rtc->network_manager = std::make_unique<FilteredNetworkManager>(
+            socket_server,
+            filter,
+            c_config->mdns_obfuscation
+        );
+    });
+
+    if (c_config->socket_factory)
+    {
+        rtc->socket_factory = std::make_unique<ExternalPacketSocketFactory>(
+            socket_server,
+            c_config->socket_factory
+        );
+    }
+    else
+    {
+        rtc->socket_factory = std::make_unique<rtc::BasicPacketSocketFactory>(socket_server);
     }
 
     rtc->observer = std::make_shared<Observer>();
-    rtc->peer_connection = peer_factory->CreatePeerConnection(
+    webrtc::PeerConnectionDependencies dependencies(rtc->observer.get());
+    auto allocator = std::make_unique<PrewarmingPortAllocator>(
+        rtc->network_manager.get(),
+        rtc->socket_factory.get()
+    );
+
+    if (c_config->proxy)
+    {
+        auto user_agent = c_config->proxy->user_agent ? from_c(c_config->proxy->user_agent) : "";
+        allocator->set_proxy(user_agent, from_c(c_config->proxy));
+    }
+
+    // the passive candidates are the listening ports, they belong to the allocator
+    // rather than to the configuration.
+    if (c_config->tcp_candidate_policy == TCP_CANDIDATE_POLICY_ACTIVE_ONLY)
+    {
+        allocator->set_allow_tcp_listen(false);
+    }
+
+    rtc->port_allocator = allocator.get();
+    dependencies.allocator = std::move(allocator);
+
+    auto result = runtime->factory->CreatePeerConnectionOrError(
         from_c(c_config),
-        nullptr,
-        nullptr,
-        rtc->observer.get()
+        std::move(dependencies)
     );
 
-    if (!rtc->peer_connection)
+    if (!result.ok())
     {
+        rtc_free_peer_connection(rtc);
         return NULL;
     }
 
+    rtc->peer_connection = result.MoveValue();
     return rtc;
 }
 
-void rtc_add_ice_candidate(
+bool rtc_add_ice_candidate(
     struct RTCPeerConnection* rtc, 
     struct RTCIceCandidate* icecandidate
 )
 {
-    rtc->peer_connection->AddIceCandidate(from_c(icecandidate));
+    std::unique_ptr<const webrtc::IceCandidateInterface> candidate(from_c(icecandidate));
+    return candidate && rtc->peer_connection->AddIceCandidate(candidate.get());
+}
+
+void rtc_add_ice_candidate_async(
+    struct RTCPeerConnection* rtc,
+    struct RTCIceCandidate* icecandidate,
+    void* ctx,
+    void (*callback)(struct RTCError* error, void* ctx)
+)
+{
+    rtc::scoped_refptr<SetDescPromisify> promisify = new rtc::RefCountedObject<SetDescPromisify>(ctx, callback);
+    std::unique_ptr<webrtc::IceCandidateInterface> candidate(
+        const_cast<webrtc::IceCandidateInterface*>(from_c(icecandidate)));
+    if (!candidate)
+    {
+        promisify->OnFailure(webrtc::RTCError(webrtc::RTCErrorType::SYNTAX_ERROR, "failed to parse the candidate"));
+        return;
+    }
+
+    rtc->peer_connection->AddIceCandidate(std::move(candidate), [promisify](webrtc::RTCError error) {
+        if (error.ok())
+        {
+            promisify->OnSuccess();
+        }
+        else
+        {
+            promisify->OnFailure(error);
+        }
+    });
+}
+
+bool rtc_add_track(
+    struct RTCPeerConnection* rtc,
+    struct MediaStreamTrack* track,
+    char** stream_ids,
+    int stream_ids_size
+)
+{
+    auto result = rtc->peer_connection->AddTrack(
+        static_cast<MediaStreamTrackRef*>(track)->track,
+        from_c(stream_ids, stream_ids_size)
+    );
+
+    return result.ok();
+}
+
+void rtc_on_icecandidate(
+    struct RTCPeerConnection* rtc,
+    void* ctx,
+    void (*handler)(struct RTCIceCandidate* candidate, void* ctx)
+)
+{
+    rtc->observer->set_icecandidate_handler(ctx, handler);
+}
+
+void rtc_on_negotiationneeded(
+    struct RTCPeerConnection* rtc,
+    void* ctx,
+    void (*handler)(void* ctx)
+)
+{
+    rtc->observer->set_renegotiationneeded_handler(ctx, handler);
+}
+
+void rtc_on_networkchanged(
+    struct RTCPeerConnection* rtc,
+    void* ctx,
+    void (*handler)(char** names, int size, void* ctx)
+)
+{
+    auto network_manager = static_cast<FilteredNetworkManager*>(rtc->network_manager.get());
+    rtc->runtime->network_thread->Invoke<void>(RTC_FROM_HERE, [&]()
+    {
+        network_manager->set_networks_changed_handler(ctx, handler);
+    });
+}
+
+bool rtc_prewarm(struct RTCPeerConnection* rtc, int size)
+{
+    auto filtered = rtc->runtime->network_thread->Invoke<bool>(RTC_FROM_HERE, [&]()
+    {
+        return rtc->port_allocator->has_candidate_filter();
+    });
+
+    if (filtered)
+    {
+        return false;
+    }
+
+    auto config = rtc->peer_connection->GetConfiguration();
+    config.ice_candidate_pool_size = size;
+    return rtc->peer_connection->SetConfiguration(config).ok();
+}
+
+void rtc_set_candidate_filter(
+    struct RTCPeerConnection* rtc,
+    void* ctx,
+    bool (*filter)(char* candidate, void* ctx)
+)
+{
+    rtc->runtime->network_thread->Invoke<void>(RTC_FROM_HERE, [&]()
+    {
+        rtc->port_allocator->set_candidate_filter(ctx, filter);
+    });
+
+    /* the pooled candidates were gathered without the filter, the pool is frozen once it is used. */
+    auto config = rtc->peer_connection->GetConfiguration();
+    if (filter && config.ice_candidate_pool_size > 0)
+    {
+        config.ice_candidate_pool_size = 0;
+        rtc->peer_connection->SetConfiguration(config);
+    }
+}
+
+void rtc_on_prewarmed(
+    struct RTCPeerConnection* rtc,
+    void* ctx,
+    void (*handler)(int candidates, void* ctx)
+)
+{
+    rtc->runtime->network_thread->Invoke<void>(RTC_FROM_HERE, [&]()
+    {
+        rtc->port_allocator->set_prewarmed_handler(ctx, handler);
+    });
 }
 
 void rtc_free(struct RTCSessionDescription* raw)
@@ -61,35 +226,322 @@ void rtc_free(struct RTCSessionDescription* raw)
     free(raw);
 }
 
+void rtc_close(struct RTCPeerConnection* rtc)
+{
+    rtc->peer_connection->Close();
+}
+
+void rtc_stop_senders(struct RTCPeerConnection* rtc)
+{
+    for (auto sender : rtc->peer_connection->GetSenders())
+    {
+        sender->SetTrack(nullptr);
+    }
+}
+
+void rtc_close_data_channels(struct RTCPeerConnection* rtc)
+{
+    rtc->observer->close_data_channels();
+}
+
+void rtc_free_peer_connection(struct RTCPeerConnection* rtc)
+{
+    /* the connection goes before the network manager, which lives on the network thread. */
+    rtc->peer_connection = nullptr;
+    rtc->runtime->network_thread->Invoke<void>(RTC_FROM_HERE, [&]()
+    {
+        rtc->network_manager = nullptr;
+    });
+
+    delete rtc;
+}
+
 void rtc_create_answer(
     struct RTCPeerConnection* rtc,
+    struct RTCOfferAnswerOptions* c_options,
     void* ctx,
-    void (*callback)(struct RTCSessionDescription* desc, void* ctx)
+    void (*callback)(struct RTCSessionDescription* desc, struct RTCError* error, void* ctx)
 )
 {
     auto promisify = new rtc::RefCountedObject<CreateDescPromisify>(ctx, callback);
-    auto options = webrtc::PeerConnectionInterface::RTCOfferAnswerOptions();
-    rtc->peer_connection->CreateAnswer(promisify, options);
+    rtc->peer_connection->CreateAnswer(promisify, from_c(c_options));
 }
 
 void rtc_create_offer(
     struct RTCPeerConnection* rtc,
+    struct RTCOfferAnswerOptions* c_options,
     void* ctx,
-    void (*callback)(struct RTCSessionDescription* desc, void* ctx)
+    void (*callback)(struct RTCSessionDescription* desc, struct RTCError* error, void* ctx)
 )
 {
     auto promisify = new rtc::RefCountedObject<CreateDescPromisify>(ctx, callback);
-    auto options = webrtc::PeerConnectionInterface::RTCOfferAnswerOptions();
-    rtc->peer_connection->CreateOffer(promisify, options);
+    rtc->peer_connection->CreateOffer(promisify, from_c(c_options));
 }
 
 void rtc_set_local_description(
     struct RTCPeerConnection* rtc, 
     struct RTCSessionDescription* c_desc, 
     void* ctx, 
-    void (*callback)(int res, void* ctx)
+    void (*callback)(struct RTCError* error, void* ctx)
 )
 {
-    auto promisify = new rtc::RefCountedObject<SetDescPromisify>(ctx, callback);
-    rtc->peer_connection->SetLocalDescription(promisify, from_c(c_desc));
+    rtc::scoped_refptr<SetDescPromisify> promisify = new rtc::RefCountedObject<SetDescPromisify>(ctx, callback);
+    auto desc = from_c(c_desc);
+    if (desc == nullptr)
+    {
+        promisify->OnFailure(webrtc::RTCError(webrtc::RTCErrorType::SYNTAX_ERROR, "failed to parse the session description"));
+        return;
+    }
+
+    rtc->peer_connection->SetLocalDescription(promisify.get(), desc);
+}
+
+void rtc_set_remote_description(
+    struct RTCPeerConnection* rtc, 
+    struct RTCSessionDescription* c_desc, 
+    void* ctx, 
+    void (*callback)(struct RTCError* error, void* ctx)
+)
+{
+    rtc::scoped_refptr<SetDescPromisify> promisify = new rtc::RefCountedObject<SetDescPromisify>(ctx, callback);
+    auto desc = from_c(c_desc);
+    if (desc == nullptr)
+    {
+        promisify->OnFailure(webrtc::RTCError(webrtc::RTCErrorType::SYNTAX_ERROR, "failed to parse the session description"));
+        return;
+    }
+
+    rtc->peer_connection->SetRemoteDescription(promisify.get(), desc);
+}
+
+void rtc_get_stats(
+    struct RTCPeerConnection* rtc,
+    void* ctx,
+    void (*callback)(const char* stats, void* ctx)
+)
+{
+    auto promisify = new rtc::RefCountedObject<GetStatsPromisify>(ctx, callback);
+    rtc->peer_connection->GetStats(promisify);
+}
+
+void rtc_set_senders_active(
+    struct RTCPeerConnection* rtc,
+    enum MEDIA_TYPE kind,
+    bool active
+)
+{
+    auto media_type = kind == MEDIA_TYPE_AUDIO
+        ? cricket::MediaType::MEDIA_TYPE_AUDIO
+        : cricket::MediaType::MEDIA_TYPE_VIDEO;
+    for (auto sender : rtc->peer_connection->GetSenders())
+    {
+        if (sender->media_type() != media_type)
+        {
+            continue;
+        }
+
+        auto parameters = sender->GetParameters();
+        for (auto& encoding : parameters.encodings)
+        {
+            encoding.active = active;
+        }
+
+        sender->SetParameters(parameters);
+    }
+}
+
+void rtc_set_bitrate(
+    struct RTCPeerConnection* rtc,
+    int min_bitrate_bps,
+    int start_bitrate_bps,
+    int max_bitrate_bps
+)
+{
+    webrtc::BitrateSettings settings;
+    if (min_bitrate_bps > 0)
+    {
+        settings.min_bitrate_bps = min_bitrate_bps;
+    }
+
+    if (start_bitrate_bps > 0)
+    {
+        settings.start_bitrate_bps = start_bitrate_bps;
+    }
+
+    if (max_bitrate_bps > 0)
+    {
+        settings.max_bitrate_bps = max_bitrate_bps;
+    }
+
+    rtc->peer_connection->SetBitrate(settings);
+}
+
+bool rtc_update_ice_server(
+    struct RTCPeerConnection* rtc,
+    struct RTCIceServer* server
+)
+{
+    auto config = rtc->peer_connection->GetConfiguration();
+    auto updated = from_c(*server);
+    bool found = false;
+    for (auto& current : config.servers)
+    {
+        if (current.urls == updated.urls)
+        {
+            current.username = updated.username;
+            current.password = updated.password;
+            found = true;
+        }
+    }
+
+    if (!found)
+    {
+        config.servers.push_back(updated);
+    }
+
+    return rtc->peer_connection->SetConfiguration(config).ok();
+}
+
+struct RTCPeerConnectionConfigure* rtc_get_configuration(struct RTCPeerConnection* rtc)
+{
+    return into_c(rtc->peer_connection->GetConfiguration());
+}
+
+void rtc_free_configuration(struct RTCPeerConnectionConfigure* config)
+{
+    free_c(config);
+}
+
+bool rtc_set_configuration(
+    struct RTCPeerConnection* rtc,
+    struct RTCPeerConnectionConfigure* c_config
+)
+{
+    return rtc->peer_connection->SetConfiguration(from_c(c_config)).ok();
+}
+
+bool rtc_get_sctp_transport(
+    struct RTCPeerConnection* rtc,
+    struct RTCSctpTransport* transport
+)
+{
+    auto sctp = rtc->peer_connection->GetSctpTransport();
+    if (!sctp)
+    {
+        return false;
+    }
+
+    auto information = sctp->Information();
+    transport->max_message_size = information.MaxMessageSize().value_or(0);
+    transport->max_channels = information.MaxChannels().value_or(0);
+    return true;
+}
+
+void rtc_restart_ice(struct RTCPeerConnection* rtc)
+{
+    rtc->peer_connection->RestartIce();
+}
+
+void rtc_on_signalingstatechange(
+    struct RTCPeerConnection* rtc,
+    void* ctx,
+    void (*handler)(enum SIGNALING_STATE state, void* ctx)
+)
+{
+    rtc->observer->set_signalingchange_handler(ctx, handler);
+}
+
+void rtc_on_iceconnectionstatechange(
+    struct RTCPeerConnection* rtc,
+    void* ctx,
+    void (*handler)(enum ICE_CONNECTION_STATE state, void* ctx)
+)
+{
+    rtc->observer->set_iceconnectionchange_handler(ctx, handler);
+}
+
+void rtc_on_icegatheringstatechange(
+    struct RTCPeerConnection* rtc,
+    void* ctx,
+    void (*handler)(enum ICE_GATHERING_STATE state, void* ctx)
+)
+{
+    rtc->observer->set_icegatheringchange_handler(ctx, handler);
+}
+
+void rtc_on_connectionstatechange(
+    struct RTCPeerConnection* rtc,
+    void* ctx,
+    void (*handler)(enum CONNECTION_STATE state, void* ctx)
+)
+{
+    rtc->observer->set_connectionstatechange_handler(ctx, handler);
+}
+
+void rtc_on_datachannel(
+    struct RTCPeerConnection* rtc,
+    void* ctx,
+    void (*handler)(struct RTCDataChannel* channel, void* ctx)
+)
+{
+    rtc->observer->set_datachannel_handler(ctx, handler);
+}
+
+void rtc_on_track(
+    struct RTCPeerConnection* rtc,
+    void* ctx,
+    void (*handler)(struct MediaStreamTrack* track, char** stream_ids, int stream_ids_size, void* ctx)
+)
+{
+    rtc->observer->set_track_handler(ctx, handler);
+}
+
+enum CONNECTION_STATE rtc_get_connection_state(struct RTCPeerConnection* rtc)
+{
+    return into_c(rtc->peer_connection->peer_connection_state());
+}
+
+enum ICE_CONNECTION_STATE rtc_get_ice_connection_state(struct RTCPeerConnection* rtc)
+{
+    return (enum ICE_CONNECTION_STATE)(rtc->peer_connection->ice_connection_state() + 1);
+}
+
+enum ICE_GATHERING_STATE rtc_get_ice_gathering_state(struct RTCPeerConnection* rtc)
+{
+    return (enum ICE_GATHERING_STATE)(rtc->peer_connection->ice_gathering_state() + 1);
+}
+
+enum SIGNALING_STATE rtc_get_signaling_state(struct RTCPeerConnection* rtc)
+{
+    return (enum SIGNALING_STATE)(rtc->peer_connection->signaling_state() + 1);
+}
+
+struct RTCSessionDescription* rtc_get_local_description(struct RTCPeerConnection* rtc)
+{
+    return into_c(rtc->peer_connection->local_description());
+}
+
+struct RTCSessionDescription* rtc_get_remote_description(struct RTCPeerConnection* rtc)
+{
+    return into_c(rtc->peer_connection->remote_description());
+}
+
+struct RTCSessionDescription* rtc_get_current_local_description(struct RTCPeerConnection* rtc)
+{
+    return into_c(rtc->peer_connection->current_local_description());
+}
+
+struct RTCSessionDescription* rtc_get_current_remote_description(struct RTCPeerConnection* rtc)
+{
+    return into_c(rtc->peer_connection->current_remote_description());
+}
+
+struct RTCSessionDescription* rtc_get_pending_local_description(struct RTCPeerConnection* rtc)
+{
+    return into_c(rtc->peer_connection->pending_local_description());
+}
+
+struct RTCSessionDescription* rtc_get_pending_remote_description(struct RTCPeerConnection* rtc)
+{
+    return into_c(rtc->peer_connection->pending_remote_description());
 }
@@ -4,7 +4,39 @@
 
 #include <cstdint>
 #include "api/peer_connection_interface.h"
-#include "observer.h"
+#include "rtc_base/thread.h"
+
+class Observer;
+class PrewarmingPortAllocator;
+struct RTCRuntime;
+struct RTCSink;
+struct RTCTrackObserver;
+struct RTCTransport;
+struct RTCTransportObserver;
+struct RTCVideoEncoder;
+struct RTCAudioEncoder;
+struct RTCRtpTap;
+struct RTCFrameTransform;
+struct RTCRtpTransceiver;
+struct RTCRtpSender;
+struct RTCRtpReceiver;
+struct RTCPacketSocket;
+
+/*
+RTCError
+
+An error reported by the native side, it is only valid for the duration of the callback it is passed to.
+*/
+extern "C" FFI_API struct RTCError {
+    /*
+    The value of webrtc::RTCErrorType, such as 5 for a syntax error or 6 for an invalid state.
+    */
+    int code;
+    /*
+    A human readable description of the error.
+    */
+    const char* message;
+};
 
 /*
 free c type
@@ -83,6 +115,44 @@ FFI_API enum RTCP_MUX_POLICY {
     RTCP_MUX_POLICY_REQUIRE,
 };
 
+/*
+Whether the candidates are gathered once, or gathered again whenever the network interfaces change.
+*/
+FFI_API enum CONTINUAL_GATHERING_POLICY {
+    /*
+    The candidates are gathered once, a connection whose interface goes away
+    fails unless ICE is restarted. This is the default value.
+    */
+    CONTINUAL_GATHERING_POLICY_GATHER_ONCE = 1,
+    /*
+    The gathering never ends, the candidates of a new interface are gathered and signaled,
+    and the candidates of a removed interface are dropped, so the connection can move
+    to another interface, such as from Wi-Fi to cellular, without an ICE restart.
+    */
+    CONTINUAL_GATHERING_POLICY_GATHER_CONTINUALLY,
+};
+
+/*
+The ICE-TCP host candidates gathered besides the UDP ones, which are the only way to connect
+directly when UDP is blocked, at the cost of head of line blocking.
+*/
+FFI_API enum TCP_CANDIDATE_POLICY {
+    /*
+    Both the active candidates, which connect to the remote peer, and the passive candidates,
+    which listen for it, are gathered. This is the default value.
+    */
+    TCP_CANDIDATE_POLICY_ENABLED = 1,
+    /*
+    Only the active candidates are gathered, no port is listened on, which suits a peer behind
+    a NAT whose passive candidates can not be reached anyway.
+    */
+    TCP_CANDIDATE_POLICY_ACTIVE_ONLY,
+    /*
+    No TCP host candidate is gathered, TURN over TCP is not affected.
+    */
+    TCP_CANDIDATE_POLICY_DISABLED,
+};
+
 /*
 RTCIceServer
 
@@ -110,6 +180,88 @@ extern "C" FFI_API struct RTCIceServer {
     char* username;
 };
 
+/*
+The protocol spoken with a proxy server.
+*/
+FFI_API enum PROXY_TYPE {
+    /*
+    An HTTP proxy, the connection is tunneled with the CONNECT method.
+    */
+    PROXY_TYPE_HTTPS = 1,
+    PROXY_TYPE_SOCKS5,
+};
+
+/*
+RTCProxy
+
+A proxy server the TCP based connections, such as TURN over TCP or TLS,
+are established through. UDP traffic never goes through the proxy.
+*/
+extern "C" FFI_API struct RTCProxy {
+    enum PROXY_TYPE type;
+    char* hostname;
+    int port;
+    char* username;
+    char* password;
+    /*
+    The user agent sent to HTTP proxies.
+    */
+    char* user_agent;
+};
+
+/*
+An IP address, such as "192.168.1.2" or "fe80::1", and a port.
+*/
+extern "C" FFI_API struct RTCSocketAddress {
+    char* ip;
+    int port;
+};
+
+/*
+RTCPacketSocketFactory
+
+The UDP sockets of a connection implemented by the application, such as over a VPN tunnel
+or a userspace network stack, every function is called on the network thread. The TCP
+sockets are not supported, the TCP candidates and the TURN servers over TCP are not gathered.
+*/
+extern "C" FFI_API struct RTCPacketSocketFactory {
+    void* ctx;
+    /*
+    Binds a socket to the address, on a port of the range unless both bounds are zero, and writes
+    the address it is bound to, whose ip is valid until the socket is closed. Returns the context
+    of the socket, or null.
+    The packets received by the socket are handed over with rtc_packet_socket_receive until
+    the socket is closed.
+    */
+    void* (*bind)(
+        struct RTCSocketAddress* address,
+        int min_port,
+        int max_port,
+        struct RTCPacketSocket* socket,
+        struct RTCSocketAddress* bound,
+        void* ctx
+    );
+    /*
+    Sends a datagram, returns false when it is dropped.
+    */
+    bool (*send_to)(void* socket, const uint8_t* data, size_t size, struct RTCSocketAddress* to);
+    /*
+    Closes the socket, rtc_packet_socket_receive must not be called for it once this returns.
+    */
+    void (*close)(void* socket);
+};
+
+/*
+Hands a datagram received by a socket of an RTCPacketSocketFactory over to the connection,
+from any thread, the data is copied.
+*/
+extern "C" FFI_API void rtc_packet_socket_receive(
+    struct RTCPacketSocket* socket,
+    const uint8_t* data,
+    size_t size,
+    struct RTCSocketAddress* from
+);
+
 /*
 RTCPeerConnection
 
@@ -137,8 +289,231 @@ extern "C" FFI_API struct RTCPeerConnectionConfigure {
     for inspection when RTCPeerConnection.setLocalDescription() is called.
     */
     int ice_candidate_pool_size;
+    /*
+    The range of local UDP and TCP ports the port allocator may bind,
+    zero for both bounds means any port.
+    */
+    int port_range_min;
+    int port_range_max;
+    /*
+    The names of the network interfaces used for gathering candidates,
+    when set, any other interface is ignored.
+    */
+    char** allowed_interfaces;
+    int allowed_interfaces_size;
+    /*
+    The names of the network interfaces never used for gathering candidates.
+    */
+    char** denied_interfaces;
+    int denied_interfaces_size;
+    /*
+    The proxy used for the TCP based connections.
+    */
+    struct RTCProxy* proxy;
+    enum CONTINUAL_GATHERING_POLICY continual_gathering_policy;
+    /*
+    The interval in milliseconds of the STUN binding requests sent to the STUN servers,
+    which keep the NAT bindings of the server reflexive candidates open.
+    */
+    int stun_keepalive_interval;
+    /*
+    The interval in milliseconds of the connectivity checks on a stable candidate pair,
+    which double as the consent freshness checks and keep the NAT bindings of the pair open.
+    */
+    int ice_check_interval;
+    /*
+    The time in milliseconds without any packet after which a candidate pair is no longer receiving.
+    */
+    int ice_receiving_timeout;
+    /*
+    The time in milliseconds without an answer to the checks after which a candidate pair
+    is considered lost, once every pair is lost the connection fails.
+    Zero for any of the intervals and timeouts above keeps the default of libwebrtc.
+    */
+    int ice_inactive_timeout;
+    enum TCP_CANDIDATE_POLICY tcp_candidate_policy;
+    /*
+    Replaces the addresses of the host candidates by random ".local" names, which are only
+    resolved with multicast DNS on the local network, so the private addresses are not exposed
+    to the remote peer. The host candidates can then only connect within the local network,
+    and a peer which can not resolve the names only connects through the other candidates.
+    */
+    bool mdns_obfuscation;
+    /*
+    Ignores the IPv6 addresses of the network interfaces.
+    */
+    bool disable_ipv6;
+    /*
+    Ignores the link-local addresses, 169.254.0.0/16 and fe80::/10.
+    */
+    bool disable_link_local;
+    /*
+    Ignores the network interfaces which libwebrtc recognizes as VPNs.
+    */
+    bool disable_vpn;
+    /*
+    The UDP sockets of the connection, or null for the sockets of the system.
+    The factory is copied, the context must outlive the connection.
+    */
+    struct RTCPacketSocketFactory* socket_factory;
+};
+
+/*
+RTCRuntimeConfigure
+
+The threads of a runtime, the network thread is always dedicated.
+*/
+/*
+A video format, the name of a codec such as "H265" and its format specific parameters.
+*/
+extern "C" FFI_API struct RTCVideoFormat {
+    char* name;
+    /*
+    The parameters of the "a=fmtp" attribute, such as "profile-id=1", or null.
+    */
+    char* parameters;
+};
+
+/*
+RTCVideoEncoderFactory
+
+Video encoders implemented by the application, every function is called with the context
+returned by create, on the encoder queue of the stream. The formats are offered along with
+the builtin ones, and take precedence over a builtin format with the same name.
+*/
+extern "C" FFI_API struct RTCVideoEncoderFactory {
+    struct RTCVideoFormat* formats;
+    size_t formats_size;
+    void* ctx;
+    /*
+    Creates an encoder for one of the formats, returns its context or null.
+    */
+    void* (*create)(struct RTCVideoFormat* format, void* ctx);
+    /*
+    Prepares the encoder for the resolution, the bitrate in bits per second and the frame rate.
+    */
+    bool (*init)(void* encoder, int width, int height, int max_bitrate, int max_frame_rate);
+    /*
+    Encodes a frame, the callback is called synchronously for the encoded frame, if any.
+    */
+    bool (*encode)(
+        void* encoder,
+        struct RTCVideoFrame* frame,
+        bool keyframe,
+        void* ctx,
+        void (*callback)(struct RTCEncodedFrame* frame, void* ctx)
+    );
+    /*
+    Updates the target bitrate in bits per second and the frame rate.
+    */
+    void (*set_rates)(void* encoder, int bitrate, double frame_rate);
+    void (*release)(void* encoder);
+};
+
+/*
+RTCVideoDecoderFactory
+
+Video decoders implemented by the application, every function is called with the context
+returned by create, on the decoder thread of the stream.
+*/
+extern "C" FFI_API struct RTCVideoDecoderFactory {
+    struct RTCVideoFormat* formats;
+    size_t formats_size;
+    void* ctx;
+    /*
+    Creates a decoder for one of the formats, returns its context or null.
+    */
+    void* (*create)(struct RTCVideoFormat* format, void* ctx);
+    /*
+    Decodes a frame, the callback is called synchronously for the decoded frame, if any.
+    */
+    bool (*decode)(
+        void* decoder,
+        struct RTCEncodedFrame* frame,
+        void* ctx,
+        void (*callback)(struct RTCVideoFrame* frame, void* ctx)
+    );
+    void (*release)(void* decoder);
+};
+
+/*
+The bounds of the buffering of the receivers in milliseconds, carried with every video frame
+by the playout-delay header extension, up to 40950.
+*/
+extern "C" FFI_API struct RTCPlayoutDelay {
+    int min_ms;
+    int max_ms;
+};
+
+extern "C" FFI_API struct RTCRuntimeConfigure {
+    /*
+    The number of dedicated threads between 1 and 3, with a single thread every task runs on
+    the network thread, with two the signaling thread is shared with the worker thread.
+    */
+    int threads;
+    /*
+    The threads are named after the prefix followed by their role, such as "webrtc-network",
+    the prefix defaults to "webrtc" when it is null.
+    */
+    char* thread_name_prefix;
+    /*
+    The video encoders implemented by the application, or null.
+    The formats are copied, the context must outlive the runtime.
+    */
+    struct RTCVideoEncoderFactory* video_encoder_factory;
+    /*
+    The video decoders implemented by the application, or null.
+    The formats are copied, the context must outlive the runtime.
+    */
+    struct RTCVideoDecoderFactory* video_decoder_factory;
+    /*
+    The field trials of the factory, such as "WebRTC-Video-Pacing/factor:1.1/", or null.
+    */
+    char* field_trials;
+    /*
+    Creates the factory without the audio and video engines, the connections of the runtime
+    only carry data channels, and the codec factories are ignored.
+    */
+    bool data_only;
+    /*
+    The playout delay of the frames of every video sender of the runtime, or null.
+    */
+    struct RTCPlayoutDelay* playout_delay;
+};
+
+/*
+Returns a newly-created runtime, which owns the threads and the factory shared by
+the connections created on it, or null when the factory could not be created.
+*/
+extern "C" FFI_API struct RTCRuntime* create_rtc_runtime(struct RTCRuntimeConfigure* config);
+
+/*
+Frees a runtime created by create_rtc_runtime, every connection created on it must be freed.
+*/
+extern "C" FFI_API void rtc_free_runtime(struct RTCRuntime* runtime);
+
+/*
+The libwebrtc the library was built against, as given to the build by the LIBWEBRTC_MILESTONE
+and LIBWEBRTC_COMMIT definitions.
+*/
+extern "C" FFI_API struct RTCVersion {
+    /*
+    The milestone of the branch, such as 96, zero when it is not known.
+    */
+    int milestone;
+    /*
+    The commit hash, an empty string when it is not known, the string is static.
+    */
+    const char* commit;
 };
 
+extern "C" FFI_API void rtc_get_version(struct RTCVersion* version);
+
+/*
+Returns the resident memory of the process in bytes, or -1 where the system does not report it.
+*/
+extern "C" FFI_API int64_t rtc_get_resident_memory();
+
 /*
 RTCPeerConnection
 
@@ -149,6 +524,13 @@ the connection, and close the connection once it's no longer needed.
 extern "C" FFI_API struct RTCPeerConnection {
     rtc::scoped_refptr<webrtc::PeerConnectionInterface> peer_connection;
     std::shared_ptr<Observer> observer;
+    struct RTCRuntime* runtime;
+    std::unique_ptr<rtc::NetworkManager> network_manager;
+    std::unique_ptr<rtc::PacketSocketFactory> socket_factory;
+    /*
+    Owned by the connection.
+    */
+    PrewarmingPortAllocator* port_allocator;
 };
 
 /*
@@ -156,6 +538,7 @@ Returns a newly-created RTCPeerConnection, which represents a
 connection between the local device and a remote peer.
 */
 extern "C" FFI_API struct RTCPeerConnection* create_rtc_peer_connection(
+    struct RTCRuntime* runtime,
     struct RTCPeerConnectionConfigure* config
 );
 
@@ -212,12 +595,92 @@ an a-line value of end-of-candidates.
 During negotiation, your app will likely receive many candidates which you'll deliver to
 the ICE agent in this way, allowing it to build up a list of potential connection methods.
 This is covered in more detail in the articles WebRTC connectivity and Signaling and video calling.
+
+Returns false when the candidate can not be parsed, or can not be added, such as before
+the remote description is set.
 */
-extern "C" FFI_API void rtc_add_ice_candidate(
+extern "C" FFI_API bool rtc_add_ice_candidate(
     struct RTCPeerConnection* peer,
     struct RTCIceCandidate* icecandidate
 );
 
+/*
+Adds a candidate as rtc_add_ice_candidate does, the callback is called once the candidate
+is added, with a null error, or with the reason it was rejected. The candidate is copied.
+*/
+extern "C" FFI_API void rtc_add_ice_candidate_async(
+    struct RTCPeerConnection* peer,
+    struct RTCIceCandidate* icecandidate,
+    void* ctx,
+    void (*callback)(struct RTCError* error, void* ctx)
+);
+
+/*
+An icecandidate event is sent to an RTCPeerConnection when an RTCIceCandidate has been
+identified and added to the local peer by a call to RTCPeerConnection.setLocalDescription().
+
+The candidate is only valid for the duration of the handler.
+*/
+extern "C" FFI_API void rtc_on_icecandidate(
+    struct RTCPeerConnection* peer,
+    void* ctx,
+    void (*handler)(struct RTCIceCandidate* candidate, void* ctx)
+);
+
+/*
+A negotiationneeded event is sent to the RTCPeerConnection when a change occurs which
+requires session negotiation. This negotiation should be carried out as the offerer,
+because some session changes cannot be negotiated as the answerer.
+*/
+extern "C" FFI_API void rtc_on_negotiationneeded(
+    struct RTCPeerConnection* peer,
+    void* ctx,
+    void (*handler)(void* ctx)
+);
+
+/*
+A networkchanged event is sent when a network interface used for gathering candidates appears
+or goes away, with the names of the current interfaces. The handler is called on the network thread,
+the names are only valid for the duration of the handler.
+*/
+extern "C" FFI_API void rtc_on_networkchanged(
+    struct RTCPeerConnection* peer,
+    void* ctx,
+    void (*handler)(char** names, int size, void* ctx)
+);
+
+/*
+Starts gathering the candidates of a pool of ICE sessions, as the ice_candidate_pool_size of the
+configuration does, before the first offer. Returns false once the local description is set,
+the pool can no longer change, or while a candidate filter is set.
+*/
+extern "C" FFI_API bool rtc_prewarm(struct RTCPeerConnection* peer, int size);
+
+/*
+Sets the filter deciding which gathered local candidates are used, a rejected candidate is neither
+signaled nor used for the connectivity checks, null removes the filter. The filter is called on
+the network thread with the candidate, which is only valid for the duration of the call, and the
+gathering of every connection of the runtime waits for it.
+
+The filter applies to the candidates gathered from then on, the candidate pool is emptied, and
+no candidate is pooled while it is set.
+*/
+extern "C" FFI_API void rtc_set_candidate_filter(
+    struct RTCPeerConnection* peer,
+    void* ctx,
+    bool (*filter)(char* candidate, void* ctx)
+);
+
+/*
+A prewarmed event is sent once the sessions of the pool have gathered every candidate, with the
+number of candidates gathered. The handler is called on the network thread.
+*/
+extern "C" FFI_API void rtc_on_prewarmed(
+    struct RTCPeerConnection* peer,
+    void* ctx,
+    void (*handler)(int candidates, void* ctx)
+);
+
 /*
 MediaStreamTrack
 
@@ -275,90 +738,384 @@ extern "C" FFI_API struct MediaStreamTrack {
     int frame_rate;
 };
 
-extern "C" FFI_API struct MediaStreamTrackFrame {
-    char* buf;
-    int64_t len;
-};
+/*
+Releases a track handed over by the track event.
+*/
+extern "C" FFI_API void rtc_free_media_stream_track(struct MediaStreamTrack* track);
 
-extern "C" FFI_API void media_stream_track_write_frame(
-    struct MediaStreamTrack* track,
-    struct MediaStreamTrackFrame frame
-);
+/*
+Enables or disables a track, a disabled video track sends or renders black frames
+and a disabled audio track silence.
+*/
+extern "C" FFI_API void media_stream_track_set_enabled(struct MediaStreamTrack* track, bool enabled);
 
-extern "C" FFI_API void media_stream_track_on_frame(
-    struct MediaStreamTrack* track,
-    void (*callback)(struct MediaStreamTrackFrame frame)
-);
+/*
+Whether the track is enabled, the enabled field is only the state when the track was handed over.
+*/
+extern "C" FFI_API bool media_stream_track_get_enabled(struct MediaStreamTrack* track);
+
+FFI_API enum TRACK_EVENT {
+    /*
+    The source of the track is temporarily unable to provide media, such as when
+    the remote peer stops sending.
+    */
+    TRACK_EVENT_MUTE = 1,
+    /*
+    The source of the track provides media again.
+    */
+    TRACK_EVENT_UNMUTE,
+    /*
+    The track will never provide media again, such as when its transceiver is stopped.
+    */
+    TRACK_EVENT_ENDED,
+};
 
 /*
-The RTCPeerConnection method addTrack() adds a new media track to the set of tracks
-which will be transmitted to the other peer.
+Observes a track, the handler is called on the signaling thread when the track is muted,
+unmuted or ended, until the observer is removed.
 */
-extern "C" FFI_API void rtc_add_track(
-    struct RTCPeerConnection* peer,
-    struct MediaStreamTrack* track
+extern "C" FFI_API struct RTCTrackObserver* media_stream_track_add_observer(
+    struct MediaStreamTrack* track,
+    void* ctx,
+    void (*handler)(enum TRACK_EVENT event, void* ctx)
 );
 
 /*
-The RTCPeerConnection.close() method closes the current peer connection.
-
-Calling this method terminates the RTCPeerConnection's ICE agent, ending any ongoing ICE processing
-and any active streams. This also releases any resources in use by the ICE agent,
-including TURN permissions. All RTCRtpSender objects are considered to be stopped once this
-returns (they may still be in the process of stopping, but for all intents and purposes, they're stopped).
+Removes and frees an observer, the handler is no longer called once this returns.
 */
-extern "C" FFI_API void rtc_close(struct RTCPeerConnection* peer);
+extern "C" FFI_API void media_stream_track_remove_observer(struct RTCTrackObserver* observer);
 
 /*
-An enum describing the session description's type.
+RTCVideoFrame
+
+A decoded video frame in the I420 format, the planes are only valid for the duration
+of the handler it is passed to.
 */
-FFI_API enum RTC_SESSION_DESCRIPTION_TYPE {
+extern "C" FFI_API struct RTCVideoFrame {
+    int width;
+    int height;
     /*
-    The session description object describes the initial proposal in an offer/answer exchange.
-    The session negotiation process begins with an offer being sent from the caller to the callee.
+    The render time of the frame, in microseconds of the monotonic clock of libwebrtc.
     */
-    RTC_SESSION_DESCRIPTION_TYPE_OFFER = 1,
+    int64_t timestamp_us;
     /*
-    Description must be treated as an SDP answer, but not a final answer.
+    The clockwise rotation to apply before rendering, in degrees.
     */
-    RTC_SESSION_DESCRIPTION_TYPE_PRANSWER,
+    int rotation;
+    const uint8_t* data_y;
+    int stride_y;
+    const uint8_t* data_u;
+    int stride_u;
+    const uint8_t* data_v;
+    int stride_v;
+};
+
+/*
+RTCAudioFrame
+
+10ms of interleaved 16 bits samples, the samples are only valid for the duration
+of the handler it is passed to.
+*/
+extern "C" FFI_API struct RTCAudioFrame {
+    const int16_t* data;
+    int sample_rate;
+    int channels;
     /*
-    The SDP contained in the sdp property is the definitive choice in the exchange.
-    In other words, this session description describes the agreed-upon configuration,
-    and is being sent to finalize negotiation.
+    The number of samples per channel.
     */
-    RTC_SESSION_DESCRIPTION_TYPE_ANSWER,
+    int frames;
     /*
-    This special type with an empty session description is used to
-    roll back to the previous stable state.
+    The time the frame was delivered, in microseconds of the monotonic clock of libwebrtc.
     */
-    RTC_SESSION_DESCRIPTION_TYPE_ROLLBACK
+    int64_t timestamp_us;
 };
 
 /*
-The RTCSessionDescription interface describes one end of a connection¡ªor potential connection¡ªand
-how it's configured. Each RTCSessionDescription consists of a description type indicating which part
-of the offer/answer negotiation process it describes and of the SDP descriptor of the session.
+Attaches a sink to a video track, the handler receives every decoded frame on the
+thread delivering it until the sink is removed. Returns null if the track is not a video track.
 */
-extern "C" FFI_API struct RTCSessionDescription {
-    enum RTC_SESSION_DESCRIPTION_TYPE type;
-    /*
+extern "C" FFI_API struct RTCSink* media_stream_track_add_video_sink(
+    struct MediaStreamTrack* track,
+    void* ctx,
+    void (*handler)(struct RTCVideoFrame* frame, void* ctx)
+);
+
+/*
+Attaches a sink to an audio track, the handler receives every 10ms of audio on the
+thread delivering it until the sink is removed. Returns null if the track is not an audio track.
+*/
+extern "C" FFI_API struct RTCSink* media_stream_track_add_audio_sink(
+    struct MediaStreamTrack* track,
+    void* ctx,
+    void (*handler)(struct RTCAudioFrame* frame, void* ctx)
+);
+
+/*
+Detaches and frees a sink, the handler is no longer called once this returns.
+*/
+extern "C" FFI_API void media_stream_track_remove_sink(struct RTCSink* sink);
+
+/*
+RTCEncodedFrame
+
+An encoded frame, the data is only valid for the duration of the callback it is passed to.
+*/
+extern "C" FFI_API struct RTCEncodedFrame {
+    const uint8_t* data;
+    size_t size;
+    /*
+    The timestamp of the video frame it was encoded from,
+    or the time of the first sample since the audio encoder was created.
+    */
+    int64_t timestamp_us;
+    bool keyframe;
+};
+
+/*
+Creates a VP8 encoder targeting the bitrate in bits per second.
+*/
+extern "C" FFI_API struct RTCVideoEncoder* rtc_create_video_encoder(int bitrate, int frame_rate);
+
+/*
+Encodes a frame, the callback is called synchronously for the encoded frame, if any.
+*/
+extern "C" FFI_API bool rtc_video_encoder_encode(
+    struct RTCVideoEncoder* encoder,
+    struct RTCVideoFrame* frame,
+    bool keyframe,
+    void* ctx,
+    void (*callback)(struct RTCEncodedFrame* frame, void* ctx)
+);
+
+extern "C" FFI_API void rtc_free_video_encoder(struct RTCVideoEncoder* encoder);
+
+/*
+Creates an Opus encoder targeting the bitrate in bits per second, the encoded packets
+are 20ms long and sampled at 48kHz whatever the rate of the input.
+*/
+extern "C" FFI_API struct RTCAudioEncoder* rtc_create_audio_encoder(int channels, int bitrate);
+
+/*
+Encodes a frame, the callback is called synchronously for every packet completed by the frame.
+Returns false when the frame does not have the channels of the encoder.
+*/
+extern "C" FFI_API bool rtc_audio_encoder_encode(
+    struct RTCAudioEncoder* encoder,
+    struct RTCAudioFrame* frame,
+    void* ctx,
+    void (*callback)(struct RTCEncodedFrame* frame, void* ctx)
+);
+
+extern "C" FFI_API void rtc_free_audio_encoder(struct RTCAudioEncoder* encoder);
+
+/*
+Creates a local video track whose frames are written by media_stream_track_write_video_frame,
+the track must be released with rtc_free_media_stream_track.
+*/
+extern "C" FFI_API struct MediaStreamTrack* rtc_create_video_track(
+    struct RTCRuntime* runtime,
+    char* id
+);
+
+/*
+Creates a local audio track whose frames are written by media_stream_track_write_audio_frame,
+the track must be released with rtc_free_media_stream_track.
+*/
+extern "C" FFI_API struct MediaStreamTrack* rtc_create_audio_track(
+    struct RTCRuntime* runtime,
+    char* id
+);
+
+/*
+Delivers a frame to the senders of a local video track, the frame is copied and stamped
+with the current time. Returns false if the track is not a local video track.
+*/
+extern "C" FFI_API bool media_stream_track_write_video_frame(
+    struct MediaStreamTrack* track,
+    struct RTCVideoFrame* frame
+);
+
+/*
+Delivers 10ms of audio to the senders of a local audio track.
+Returns false if the track is not a local audio track.
+*/
+extern "C" FFI_API bool media_stream_track_write_audio_frame(
+    struct MediaStreamTrack* track,
+    struct RTCAudioFrame* frame
+);
+
+/*
+The RTCPeerConnection method addTrack() adds a new media track to the set of tracks
+which will be transmitted to the other peer, as part of the streams with the given ids,
+the remote peer groups the tracks of a stream, such as the audio and the video of a participant.
+*/
+extern "C" FFI_API bool rtc_add_track(
+    struct RTCPeerConnection* peer,
+    struct MediaStreamTrack* track,
+    char** stream_ids,
+    int stream_ids_size
+);
+
+FFI_API enum RTP_DIRECTION {
+    /*
+    The packet was received from the remote peer.
+    */
+    RTP_DIRECTION_INCOMING = 1,
+    /*
+    The packet is sent to the remote peer.
+    */
+    RTP_DIRECTION_OUTGOING,
+};
+
+/*
+RTCRtpPacket
+
+A serialized rtp or rtcp packet, the data is only valid for the duration of the handler it is passed to.
+*/
+extern "C" FFI_API struct RTCRtpPacket {
+    enum RTP_DIRECTION direction;
+    bool rtcp;
+    const uint8_t* data;
+    size_t size;
+};
+
+/*
+Attaches a tap to the transceiver with the given mid, the handler receives the rtp and rtcp packets
+of the transceiver on the network thread until the tap is removed. When intercepting, the outgoing
+rtp packets are not sent and the incoming rtp packets are not decoded, rtcp always flows.
+Returns null if there is no negotiated transceiver with this mid.
+*/
+extern "C" FFI_API struct RTCRtpTap* rtc_add_rtp_tap(
+    struct RTCPeerConnection* peer,
+    char* mid,
+    bool intercept,
+    void* ctx,
+    void (*handler)(struct RTCRtpPacket* packet, void* ctx)
+);
+
+/*
+Sends an rtp or rtcp packet on the transport of the tapped transceiver, the packet is sent as is,
+apart from the srtp protection. Returns false if the transport is gone.
+*/
+extern "C" FFI_API bool rtc_rtp_tap_send(
+    struct RTCRtpTap* tap,
+    const uint8_t* data,
+    size_t size,
+    bool rtcp
+);
+
+/*
+Detaches and frees a tap, the handler is no longer called once this returns.
+*/
+extern "C" FFI_API void rtc_remove_rtp_tap(struct RTCRtpTap* tap);
+
+/*
+The RTCPeerConnection.close() method closes the current peer connection.
+
+Calling this method terminates the RTCPeerConnection's ICE agent, ending any ongoing ICE processing
+and any active streams. This also releases any resources in use by the ICE agent,
+including TURN permissions. All RTCRtpSender objects are considered to be stopped once this
+returns (they may still be in the process of stopping, but for all intents and purposes, they're stopped).
+*/
+extern "C" FFI_API void rtc_close(struct RTCPeerConnection* peer);
+
+/*
+Stops sending on every RTCRtpSender of the connection by detaching its track,
+the transceivers are kept so no renegotiation is needed.
+*/
+extern "C" FFI_API void rtc_stop_senders(struct RTCPeerConnection* peer);
+
+/*
+Closes every RTCDataChannel the remote peer has opened on the connection.
+*/
+extern "C" FFI_API void rtc_close_data_channels(struct RTCPeerConnection* peer);
+
+/*
+Frees a connection created by create_rtc_peer_connection, the connection must be closed.
+*/
+extern "C" FFI_API void rtc_free_peer_connection(struct RTCPeerConnection* peer);
+
+/*
+An enum describing the session description's type.
+*/
+FFI_API enum RTC_SESSION_DESCRIPTION_TYPE {
+    /*
+    The session description object describes the initial proposal in an offer/answer exchange.
+    The session negotiation process begins with an offer being sent from the caller to the callee.
+    */
+    RTC_SESSION_DESCRIPTION_TYPE_OFFER = 1,
+    /*
+    Description must be treated as an SDP answer, but not a final answer.
+    */
+    RTC_SESSION_DESCRIPTION_TYPE_PRANSWER,
+    /*
+    The SDP contained in the sdp property is the definitive choice in the exchange.
+    In other words, this session description describes the agreed-upon configuration,
+    and is being sent to finalize negotiation.
+    */
+    RTC_SESSION_DESCRIPTION_TYPE_ANSWER,
+    /*
+    This special type with an empty session description is used to
+    roll back to the previous stable state.
+    */
+    RTC_SESSION_DESCRIPTION_TYPE_ROLLBACK
+};
+
+/*
+The RTCSessionDescription interface describes one end of a connection¡ªor potential connection¡ªand
+how it's configured. Each RTCSessionDescription consists of a description type indicating which part
+of the offer/answer negotiation process it describes and of the SDP descriptor of the session.
+*/
+extern "C" FFI_API struct RTCSessionDescription {
+    enum RTC_SESSION_DESCRIPTION_TYPE type;
+    /*
     A string containing the SDP describing the session.
     */
     const char* sdp;
 };
 
+/*
+RTCOfferAnswerOptions
+
+The options of createOffer() and createAnswer(), the legacy offerToReceive options only apply
+to offers, a value of -1 leaves the decision to the transceivers of the connection.
+A null options pointer uses the defaults.
+*/
+extern "C" FFI_API struct RTCOfferAnswerOptions {
+    /*
+    To restart ICE on an active connection, set this to true. This will cause the returned offer
+    to have different credentials than those already in place.
+    */
+    bool ice_restart;
+    /*
+    Enables voice activity detection, which lets the audio codecs skip the silences.
+    The default is true.
+    */
+    bool voice_activity_detection;
+    /*
+    Whether the remote peer is offered to send audio, regardless of the local audio tracks.
+    */
+    int offer_to_receive_audio;
+    /*
+    Whether the remote peer is offered to send video, regardless of the local video tracks.
+    */
+    int offer_to_receive_video;
+};
+
 /*
 The createAnswer() method on the RTCPeerConnection interface creates an SDP answer to an offer received
 from a remote peer during the offer/answer negotiation of a WebRTC connection. The answer contains
 information about any media already attached to the session, codecs and options supported by the browser,
 and any ICE candidates already gathered. The answer is delivered to the returned Promise, and should
 then be sent to the source of the offer to continue the negotiation process.
+
+The description handed to the callback belongs to the callback, it must be released with rtc_free.
 */
 extern "C" FFI_API void rtc_create_answer(
     struct RTCPeerConnection* peer,
+    struct RTCOfferAnswerOptions* options,
     void* ctx,
-    void (*callback)(struct RTCSessionDescription* desc, void* ctx)
+    void (*callback)(struct RTCSessionDescription* desc, struct RTCError* error, void* ctx)
 );
 
 /*
@@ -368,11 +1125,14 @@ about any MediaStreamTrack objects already attached to the WebRTC session, codec
 by the browser, and any candidates already gathered by the ICE agent, for the purpose of being sent
 over the signaling channel to a potential peer to request a connection or to update the configuration
 of an existing connection.
+
+The description handed to the callback belongs to the callback, it must be released with rtc_free.
 */
 extern "C" FFI_API void rtc_create_offer(
     struct RTCPeerConnection* peer,
+    struct RTCOfferAnswerOptions* options,
     void* ctx,
-    void (*callback)(struct RTCSessionDescription* desc, void* ctx)
+    void (*callback)(struct RTCSessionDescription* desc, struct RTCError* error, void* ctx)
 );
 
 /*
@@ -384,10 +1144,145 @@ and each peer connection can have up to a theoretical maximum of 65,534 data cha
 (the actual limit may vary from browser to browser).
 */
 extern "C" FFI_API struct RTCDataChannel {
-    char* id;
+    /*
+    Returns an ID number (between 0 and 65,534) which uniquely identifies the RTCDataChannel.
+    */
+    int id;
+    /*
+    Returns a string containing a name describing the data channel.
+    These labels are not required to be unique.
+    */
     char* label;
+    /*
+    Whether the messages are delivered in the order they were sent.
+    */
+    bool ordered;
+    /*
+    Whether the messages are retransmitted until they are delivered,
+    rather than within a limited number of retransmissions or a limited time.
+    */
+    bool reliable;
+};
+
+/*
+Releases a data channel handed over by the datachannel event.
+*/
+extern "C" FFI_API void rtc_free_data_channel(struct RTCDataChannel* channel);
+
+FFI_API enum DATA_CHANNEL_STATE {
+    /*
+    The user agent is attempting to establish the underlying data transport.
+    */
+    DATA_CHANNEL_STATE_CONNECTING = 1,
+    /*
+    The underlying data transport is established and communication is possible.
+    */
+    DATA_CHANNEL_STATE_OPEN,
+    /*
+    The process of closing down the underlying data transport has started.
+    */
+    DATA_CHANNEL_STATE_CLOSING,
+    /*
+    The underlying data transport has been closed or could not be established.
+    */
+    DATA_CHANNEL_STATE_CLOSED,
+};
+
+/*
+The read-only RTCDataChannel property readyState returns an enum of type RTCDataChannelState
+which indicates the state of the data channel's underlying data connection.
+*/
+extern "C" FFI_API enum DATA_CHANNEL_STATE rtc_get_data_channel_state(struct RTCDataChannel* channel);
+
+/*
+The read-only RTCDataChannel property bufferedAmount returns the number of bytes of data
+currently queued to be sent over the data channel.
+*/
+extern "C" FFI_API uint64_t rtc_get_data_channel_buffered_amount(struct RTCDataChannel* channel);
+
+/*
+The priority of a sender or a data channel, which the bandwidth allocator favors when the link
+is constrained, and which sets the DSCP marking of its packets when it is enabled.
+*/
+FFI_API enum PRIORITY {
+    PRIORITY_VERY_LOW = 1,
+    PRIORITY_LOW,
+    PRIORITY_MEDIUM,
+    PRIORITY_HIGH,
+};
+
+/*
+The priority of a data channel, as chosen by the peer which opened it.
+*/
+extern "C" FFI_API enum PRIORITY rtc_get_data_channel_priority(struct RTCDataChannel* channel);
+
+/*
+The options of a data channel opened by the local peer.
+*/
+extern "C" FFI_API struct RTCDataChannelInit {
+    /*
+    Whether the messages are delivered in the order they were sent.
+    */
+    bool ordered;
+    /*
+    The time in milliseconds during which a message is retransmitted, -1 for no limit.
+    */
+    int max_retransmit_time;
+    /*
+    The number of times a message is retransmitted, -1 for no limit.
+    */
+    int max_retransmits;
+    enum PRIORITY priority;
 };
 
+/*
+The createDataChannel() method on the RTCPeerConnection interface creates a new channel linked with
+the remote peer, over which any kind of data may be transmitted. Returns null on failure, in which
+case the error is written, its message must be released with rtc_free_string. The channel must be
+released with rtc_free_data_channel.
+*/
+extern "C" FFI_API struct RTCDataChannel* rtc_create_data_channel(
+    struct RTCPeerConnection* peer,
+    char* label,
+    struct RTCDataChannelInit* init,
+    struct RTCError* error
+);
+
+/*
+Registers the handlers of the events of a data channel, replacing the previous ones. The messages
+received before the handlers are registered are queued and delivered once they are.
+The handlers are called on the signaling thread, the data of a message is only valid for the duration of the handler.
+*/
+extern "C" FFI_API void rtc_set_data_channel_observer(
+    struct RTCDataChannel* channel,
+    void* ctx,
+    void (*on_message)(uint8_t* data, size_t size, bool binary, void* ctx),
+    void (*on_state_change)(enum DATA_CHANNEL_STATE state, void* ctx),
+    void (*on_buffered_amount_change)(uint64_t buffered_amount, void* ctx)
+);
+
+/*
+Removes the handlers of a data channel, no handler is called once it returns.
+*/
+extern "C" FFI_API void rtc_remove_data_channel_observer(struct RTCDataChannel* channel);
+
+/*
+The send() method of the RTCDataChannel interface sends data across the data channel to the remote peer.
+Returns false when the channel is not open or its send buffer is full.
+*/
+extern "C" FFI_API bool rtc_send_data_channel_message(
+    struct RTCDataChannel* channel,
+    uint8_t* data,
+    size_t size,
+    bool binary
+);
+
+/*
+The RTCDataChannel.close() method closes the RTCDataChannel. Either peer is permitted to call
+this method to initiate closure of the channel.
+*/
+extern "C" FFI_API void rtc_close_data_channel(struct RTCDataChannel* channel);
+
 /*
 The RTCPeerConnection method setLocalDescription() changes the local description associated with
 the connection. This description specifies the properties of the local end of the connection,
@@ -404,7 +1299,7 @@ extern "C" FFI_API void rtc_set_local_description(
     struct RTCPeerConnection* peer,
     struct RTCSessionDescription* desc,
     void* ctx,
-    void (*callback)(int res, void* ctx)
+    void (*callback)(struct RTCError* error, void* ctx)
 );
 
 /*
@@ -428,7 +1323,7 @@ extern "C" FFI_API void rtc_set_remote_description(
     struct RTCPeerConnection* peer,
     struct RTCSessionDescription* desc,
     void* ctx,
-    void (*callback)(int res, void* ctx)
+    void (*callback)(struct RTCError* error, void* ctx)
 );
 
 FFI_API enum CONNECTION_STATE {
@@ -448,15 +1343,890 @@ and is one of the string values: new, connecting, connected, disconnected, faile
 */
 extern "C" FFI_API void rtc_on_connectionstatechange(
     struct RTCPeerConnection* peer,
-    void (*handler)(enum CONNECTION_STATE state)
+    void* ctx,
+    void (*handler)(enum CONNECTION_STATE state, void* ctx)
 );
 
 /*
 A datachannel event is sent to an RTCPeerConnection instance when an RTCDataChannel has
 been added to the connection, as a result of the remote peer calling
 RTCPeerConnection.createDataChannel().
+
+The handler owns the channel, which must be released with rtc_free_data_channel.
 */
 extern "C" FFI_API void rtc_on_datachannel(
     struct RTCPeerConnection* peer,
-    void (*handler)(struct RTCDataChannel state)
+    void* ctx,
+    void (*handler)(struct RTCDataChannel* channel, void* ctx)
+);
+
+/*
+The track event is sent to the ontrack event handler on RTCPeerConnections after a new track
+has been added to an RTCRtpReceiver which is part of the connection.
+
+The handler owns the track, which must be released with rtc_free_media_stream_track,
+the ids of the remote streams of the track are only valid during the call.
+*/
+extern "C" FFI_API void rtc_on_track(
+    struct RTCPeerConnection* peer,
+    void* ctx,
+    void (*handler)(struct MediaStreamTrack* track, char** stream_ids, int stream_ids_size, void* ctx)
+);
+
+/*
+The RTCPeerConnection method getStats() returns a promise which resolves with data
+providing statistics about either the overall connection or about the specified MediaStreamTrack.
+
+The report is delivered as the JSON serialization of the RTCStatsReport,
+the string is only valid for the duration of the callback.
+*/
+extern "C" FFI_API void rtc_get_stats(
+    struct RTCPeerConnection* peer,
+    void* ctx,
+    void (*callback)(const char* stats, void* ctx)
+);
+
+/*
+The kind of media carried by a sender, receiver or track.
+*/
+FFI_API enum MEDIA_TYPE {
+    MEDIA_TYPE_AUDIO = 1,
+    MEDIA_TYPE_VIDEO,
+};
+
+/*
+Sets the active flag of every encoding of the senders carrying the given kind of media.
+An inactive encoding is not sent, but the sender and its track stay attached,
+so no renegotiation is required to resume it.
+*/
+extern "C" FFI_API void rtc_set_senders_active(
+    struct RTCPeerConnection* peer,
+    enum MEDIA_TYPE kind,
+    bool active
+);
+
+/*
+Sets the bitrate limits of the bandwidth estimation of the connection, in bits per second,
+the limits apply to the total outgoing bitrate of all senders.
+A value of zero leaves the corresponding limit unchanged.
+*/
+extern "C" FFI_API void rtc_set_bitrate(
+    struct RTCPeerConnection* peer,
+    int min_bitrate_bps,
+    int start_bitrate_bps,
+    int max_bitrate_bps
+);
+
+/*
+Replaces the username and credential of the configured ICE server with the same urls,
+the server is appended to the configuration when there is none.
+Returns false when the native configuration could not be updated.
+*/
+extern "C" FFI_API bool rtc_update_ice_server(
+    struct RTCPeerConnection* peer,
+    struct RTCIceServer* server
 );
+
+/*
+The RTCPeerConnection.getConfiguration() method returns an object which indicates the current
+configuration of the RTCPeerConnection on which the method is called.
+
+The returned configuration is owned by the native side and released with rtc_free_configuration.
+*/
+extern "C" FFI_API struct RTCPeerConnectionConfigure* rtc_get_configuration(
+    struct RTCPeerConnection* peer
+);
+
+extern "C" FFI_API void rtc_free_configuration(struct RTCPeerConnectionConfigure* config);
+
+/*
+The RTCPeerConnection.setConfiguration() method sets the current configuration of
+the RTCPeerConnection based on the values included in the specified object.
+Returns false when the configuration is rejected, such as when trying to change
+a property which can not be changed after the connection is created.
+*/
+extern "C" FFI_API bool rtc_set_configuration(
+    struct RTCPeerConnection* peer,
+    struct RTCPeerConnectionConfigure* config
+);
+
+/*
+The SCTP transport carrying the data channels of a connection.
+*/
+extern "C" FFI_API struct RTCSctpTransport {
+    /*
+    The largest message the data channels send, the smaller of the local limit and the limit
+    the remote peer announced in its description.
+    */
+    double max_message_size;
+    /*
+    The number of data channels which can be open at once, as negotiated when the association
+    was established.
+    */
+    int max_channels;
+};
+
+/*
+The RTCPeerConnection.sctp read-only property returns the RTCSctpTransport of the connection.
+Returns false while there is no SCTP transport, such as before a data channel is negotiated,
+and leaves the zero of a property which is not known yet, such as before the association is established.
+*/
+extern "C" FFI_API bool rtc_get_sctp_transport(
+    struct RTCPeerConnection* peer,
+    struct RTCSctpTransport* transport
+);
+
+FFI_API enum DTLS_TRANSPORT_STATE {
+    /*
+    The DTLS handshake has not started yet.
+    */
+    DTLS_TRANSPORT_STATE_NEW = 1,
+    /*
+    The DTLS handshake is underway.
+    */
+    DTLS_TRANSPORT_STATE_CONNECTING,
+    /*
+    The handshake has completed and the certificate of the remote peer matches its fingerprint.
+    */
+    DTLS_TRANSPORT_STATE_CONNECTED,
+    /*
+    The transport has been closed, such as when a close alert was received.
+    */
+    DTLS_TRANSPORT_STATE_CLOSED,
+    /*
+    The transport failed, such as when the handshake failed or the certificate of the remote peer
+    does not match its fingerprint.
+    */
+    DTLS_TRANSPORT_STATE_FAILED,
+};
+
+FFI_API enum ICE_TRANSPORT_STATE {
+    ICE_TRANSPORT_STATE_NEW = 1,
+    ICE_TRANSPORT_STATE_CHECKING,
+    ICE_TRANSPORT_STATE_CONNECTED,
+    ICE_TRANSPORT_STATE_COMPLETED,
+    ICE_TRANSPORT_STATE_DISCONNECTED,
+    ICE_TRANSPORT_STATE_FAILED,
+    ICE_TRANSPORT_STATE_CLOSED,
+};
+
+FFI_API enum TRANSPORT_ERROR {
+    /*
+    The DTLS handshake failed, such as when a fatal alert was received or the remote peer
+    did not answer the handshake.
+    */
+    TRANSPORT_ERROR_HANDSHAKE_FAILED = 1,
+    /*
+    The DTLS handshake failed because the peers have no cipher suite in common.
+    */
+    TRANSPORT_ERROR_INCOMPATIBLE_CIPHER_SUITE,
+    /*
+    The DTLS transport failed after the handshake, such as when the certificate of the remote
+    peer does not match the fingerprint of its description.
+    */
+    TRANSPORT_ERROR_DTLS_FAILED,
+};
+
+/*
+The RTCDtlsTransport of a media id, shared by the media sections bundled with it, along with
+its RTCIceTransport. Returns null when the media id has no transport, such as before the
+descriptions are set.
+
+The transport must be released with rtc_free_transport.
+*/
+extern "C" FFI_API struct RTCTransport* rtc_get_transport(struct RTCPeerConnection* peer, char* mid);
+
+extern "C" FFI_API void rtc_free_transport(struct RTCTransport* transport);
+
+/*
+The state of the DTLS transport.
+*/
+extern "C" FFI_API enum DTLS_TRANSPORT_STATE rtc_get_dtls_transport_state(struct RTCTransport* transport);
+
+/*
+The state of the ICE transport underneath the DTLS transport.
+*/
+extern "C" FFI_API enum ICE_TRANSPORT_STATE rtc_get_ice_transport_state(struct RTCTransport* transport);
+
+/*
+Observes a transport, the handlers are called on the network thread when the state of the DTLS
+or of the ICE transport changes, and when the DTLS transport fails, before the failed state,
+until the observer is removed.
+*/
+extern "C" FFI_API struct RTCTransportObserver* rtc_transport_add_observer(
+    struct RTCTransport* transport,
+    void* ctx,
+    void (*on_dtls_state_change)(enum DTLS_TRANSPORT_STATE state, void* ctx),
+    void (*on_ice_state_change)(enum ICE_TRANSPORT_STATE state, void* ctx),
+    void (*on_error)(enum TRANSPORT_ERROR error, void* ctx)
+);
+
+/*
+Removes and frees an observer, the handlers are no longer called once this returns.
+*/
+extern "C" FFI_API void rtc_transport_remove_observer(struct RTCTransportObserver* observer);
+
+/*
+The WebRTC API's RTCPeerConnection interface offers the restartIce() method to allow
+a web application to easily request that ICE candidate gathering be redone on both ends
+of the connection.
+*/
+extern "C" FFI_API void rtc_restart_ice(struct RTCPeerConnection* peer);
+
+/*
+Describes the state of the signaling process on the local end of the connection
+while connecting or reconnecting to another peer.
+*/
+FFI_API enum SIGNALING_STATE {
+    /*
+    There is no ongoing exchange of offer and answer underway.
+    */
+    SIGNALING_STATE_STABLE = 1,
+    /*
+    The local peer has called RTCPeerConnection.setLocalDescription(), passing in SDP representing an offer.
+    */
+    SIGNALING_STATE_HAVE_LOCAL_OFFER,
+    /*
+    The offer sent by the remote peer has been applied and an answer has been created
+    and applied by calling RTCPeerConnection.setLocalDescription() with a provisional answer.
+    */
+    SIGNALING_STATE_HAVE_LOCAL_PRANSWER,
+    /*
+    The remote peer has created an offer and used the signaling server to deliver it to the local peer,
+    which has set the offer as the remote description by calling RTCPeerConnection.setRemoteDescription().
+    */
+    SIGNALING_STATE_HAVE_REMOTE_OFFER,
+    /*
+    A provisional answer has been received and successfully applied in response to an offer
+    previously sent and established by calling setLocalDescription().
+    */
+    SIGNALING_STATE_HAVE_REMOTE_PRANSWER,
+    /*
+    The RTCPeerConnection has been closed.
+    */
+    SIGNALING_STATE_CLOSED,
+};
+
+/*
+Describes the state of the ICE agent associated with the connection.
+*/
+FFI_API enum ICE_CONNECTION_STATE {
+    /*
+    The ICE agent is gathering addresses or is waiting to be given remote candidates.
+    */
+    ICE_CONNECTION_STATE_NEW = 1,
+    /*
+    The ICE agent has been given one or more remote candidates and is checking pairs of local
+    and remote candidates against one another to try to find a compatible match.
+    */
+    ICE_CONNECTION_STATE_CHECKING,
+    /*
+    A usable pairing of local and remote candidates has been found for all components of the
+    connection, and the connection has been established.
+    */
+    ICE_CONNECTION_STATE_CONNECTED,
+    /*
+    The ICE agent has finished gathering candidates, has checked all pairs against one another,
+    and has found a connection for all components.
+    */
+    ICE_CONNECTION_STATE_COMPLETED,
+    /*
+    The ICE candidate has checked all candidates pairs against one another and has failed
+    to find compatible matches for all components of the connection.
+    */
+    ICE_CONNECTION_STATE_FAILED,
+    /*
+    Checks to ensure that components are still connected failed for at least one component.
+    */
+    ICE_CONNECTION_STATE_DISCONNECTED,
+    /*
+    The ICE agent for this RTCPeerConnection has shut down and is no longer handling requests.
+    */
+    ICE_CONNECTION_STATE_CLOSED,
+};
+
+/*
+Describes the ICE gathering state of the connection.
+*/
+FFI_API enum ICE_GATHERING_STATE {
+    /*
+    The peer connection was just created and hasn't done any networking yet.
+    */
+    ICE_GATHERING_STATE_NEW = 1,
+    /*
+    The ICE agent is in the process of gathering candidates for the connection.
+    */
+    ICE_GATHERING_STATE_GATHERING,
+    /*
+    The ICE agent has finished gathering candidates.
+    */
+    ICE_GATHERING_STATE_COMPLETE,
+};
+
+/*
+A signalingstatechange event is sent to an RTCPeerConnection to notify it that its signaling state
+has changed as a result of a call to setLocalDescription() or setRemoteDescription().
+*/
+extern "C" FFI_API void rtc_on_signalingstatechange(
+    struct RTCPeerConnection* peer,
+    void* ctx,
+    void (*handler)(enum SIGNALING_STATE state, void* ctx)
+);
+
+/*
+An iceconnectionstatechange event is sent to an RTCPeerConnection object each time
+the ICE connection state changes during the negotiation process.
+*/
+extern "C" FFI_API void rtc_on_iceconnectionstatechange(
+    struct RTCPeerConnection* peer,
+    void* ctx,
+    void (*handler)(enum ICE_CONNECTION_STATE state, void* ctx)
+);
+
+/*
+The icegatheringstatechange event is sent to the RTCPeerConnection when the state
+of the ICE candidate gathering process changes.
+*/
+extern "C" FFI_API void rtc_on_icegatheringstatechange(
+    struct RTCPeerConnection* peer,
+    void* ctx,
+    void (*handler)(enum ICE_GATHERING_STATE state, void* ctx)
+);
+
+/*
+The connectionState read-only property of the RTCPeerConnection interface indicates
+the current state of the peer connection.
+*/
+extern "C" FFI_API enum CONNECTION_STATE rtc_get_connection_state(struct RTCPeerConnection* peer);
+
+/*
+The iceConnectionState read-only property of the RTCPeerConnection interface returns a string
+which state of the ICE agent associated with the RTCPeerConnection.
+*/
+extern "C" FFI_API enum ICE_CONNECTION_STATE rtc_get_ice_connection_state(struct RTCPeerConnection* peer);
+
+/*
+The iceGatheringState read-only property of the RTCPeerConnection interface returns a string
+that describes the overall ICE gathering state for this connection.
+*/
+extern "C" FFI_API enum ICE_GATHERING_STATE rtc_get_ice_gathering_state(struct RTCPeerConnection* peer);
+
+/*
+The signalingState read-only property of the RTCPeerConnection interface returns a string value
+describing the state of the signaling process on the local end of the connection
+while connecting or reconnecting to another peer.
+*/
+extern "C" FFI_API enum SIGNALING_STATE rtc_get_signaling_state(struct RTCPeerConnection* peer);
+
+/*
+The read-only property RTCPeerConnection.localDescription returns an RTCSessionDescription
+describing the session for the local end of the connection. If it has not yet been set, this is null.
+
+It is the pending description when there is one, the current description otherwise.
+
+The description must be released with rtc_free.
+*/
+extern "C" FFI_API struct RTCSessionDescription* rtc_get_local_description(struct RTCPeerConnection* peer);
+
+/*
+The read-only property RTCPeerConnection.remoteDescription returns a RTCSessionDescription
+describing the session (which includes the configuration and media information) for the remote
+end of the connection. If this hasn't been set yet, this is null.
+
+It is the pending description when there is one, the current description otherwise.
+
+The description must be released with rtc_free.
+*/
+extern "C" FFI_API struct RTCSessionDescription* rtc_get_remote_description(struct RTCPeerConnection* peer);
+
+/*
+The read-only property RTCPeerConnection.currentLocalDescription returns an RTCSessionDescription
+object describing the local end of the connection as it was most recently successfully negotiated
+since the last time the RTCPeerConnection finished negotiating and connecting to a remote peer.
+
+The description must be released with rtc_free.
+*/
+extern "C" FFI_API struct RTCSessionDescription* rtc_get_current_local_description(struct RTCPeerConnection* peer);
+
+/*
+The read-only property RTCPeerConnection.currentRemoteDescription returns an RTCSessionDescription
+object describing the remote end of the connection as it was most recently successfully negotiated
+since the last time the RTCPeerConnection finished negotiating and connecting to a remote peer.
+
+The description must be released with rtc_free.
+*/
+extern "C" FFI_API struct RTCSessionDescription* rtc_get_current_remote_description(struct RTCPeerConnection* peer);
+
+/*
+The read-only property RTCPeerConnection.pendingLocalDescription returns an RTCSessionDescription
+object describing a pending configuration change for the local end of the connection.
+This does not describe the connection as it currently stands, but as it may exist in the near future.
+
+The description must be released with rtc_free.
+*/
+extern "C" FFI_API struct RTCSessionDescription* rtc_get_pending_local_description(struct RTCPeerConnection* peer);
+
+/*
+The read-only property RTCPeerConnection.pendingRemoteDescription returns an RTCSessionDescription
+object describing a pending configuration change for the remote end of the connection.
+This does not describe the connection as it currently stands, but as it may exist in the near future.
+
+The description must be released with rtc_free.
+*/
+extern "C" FFI_API struct RTCSessionDescription* rtc_get_pending_remote_description(struct RTCPeerConnection* peer);
+
+/*
+The codec of an encoded frame, which tells how much of the frame its packetizer reads.
+*/
+FFI_API enum FRAME_CODEC {
+    /*
+    The audio codecs and the video codecs packetized with the generic packetizer,
+    whose payload is opaque to the packetizer.
+    */
+    FRAME_CODEC_GENERIC = 1,
+    FRAME_CODEC_VP8,
+    FRAME_CODEC_VP9,
+    FRAME_CODEC_AV1,
+    FRAME_CODEC_H264,
+    /*
+    A video codec this library does not know the payload format of.
+    */
+    FRAME_CODEC_OTHER,
+};
+
+/*
+RTCTransformableFrame
+
+An encoded frame of a sender before packetization, or of a receiver after depacketization,
+the data is only valid for the duration of the handler it is passed to.
+*/
+extern "C" FFI_API struct RTCTransformableFrame {
+    enum MEDIA_TYPE kind;
+    /*
+    Outgoing for the frames of a sender, incoming for the frames of a receiver.
+    */
+    enum RTP_DIRECTION direction;
+    uint32_t ssrc;
+    uint8_t payload_type;
+    /*
+    The rtp timestamp of the frame, in the clock rate of its payload.
+    */
+    uint32_t timestamp;
+    /*
+    Always false for audio frames.
+    */
+    bool keyframe;
+    enum FRAME_CODEC codec;
+    const uint8_t* data;
+    size_t size;
+    /*
+    The native frame, only used by rtc_transformable_frame_set_data.
+    */
+    void* frame;
+};
+
+/*
+Replaces the payload of a frame being transformed, the data is copied.
+*/
+extern "C" FFI_API void rtc_transformable_frame_set_data(
+    struct RTCTransformableFrame* frame,
+    const uint8_t* data,
+    size_t size
+);
+
+/*
+Registers a transform on the sender (outgoing) or the receiver (incoming) of the transceiver
+with the given mid, the handler is called for every encoded frame on the thread delivering it,
+and the frame is dropped when it returns false. Returns null if there is no transceiver with this mid.
+*/
+extern "C" FFI_API struct RTCFrameTransform* rtc_add_frame_transform(
+    struct RTCPeerConnection* peer,
+    char* mid,
+    enum RTP_DIRECTION direction,
+    void* ctx,
+    bool (*handler)(struct RTCTransformableFrame* frame, void* ctx)
+);
+
+/*
+Removes the handler of a transform, the frames pass through unchanged once this returns.
+*/
+extern "C" FFI_API void rtc_remove_frame_transform(struct RTCFrameTransform* transform);
+
+FFI_API enum LOG_SEVERITY {
+    /*
+    The debugging messages, including the sensitive ones.
+    */
+    LOG_SEVERITY_VERBOSE = 1,
+    LOG_SEVERITY_INFO,
+    LOG_SEVERITY_WARNING,
+    LOG_SEVERITY_ERROR,
+    /*
+    No message is logged.
+    */
+    LOG_SEVERITY_NONE,
+};
+
+/*
+Forwards the messages of libwebrtc at or above the severity to the handler instead of stderr,
+the handler is called on the thread logging the message and replaces any previous handler.
+*/
+extern "C" FFI_API void rtc_set_log_handler(
+    enum LOG_SEVERITY severity,
+    void* ctx,
+    void (*handler)(enum LOG_SEVERITY severity, const char* message, void* ctx)
+);
+
+/*
+Changes the minimum severity of the messages logged by libwebrtc.
+*/
+extern "C" FFI_API void rtc_set_log_severity(enum LOG_SEVERITY severity);
+
+/*
+The preferred direction of a transceiver, or the direction negotiated for it.
+*/
+FFI_API enum TRANSCEIVER_DIRECTION {
+    TRANSCEIVER_DIRECTION_SENDRECV = 1,
+    TRANSCEIVER_DIRECTION_SENDONLY,
+    TRANSCEIVER_DIRECTION_RECVONLY,
+    TRANSCEIVER_DIRECTION_INACTIVE,
+    /*
+    The transceiver is stopped, it neither sends nor receives and will never be used again.
+    */
+    TRANSCEIVER_DIRECTION_STOPPED,
+};
+
+/*
+Returns the transceivers of the connection, the size of the array is written to size.
+Every handle must be released with rtc_free_transceiver, and the array with rtc_free_handles.
+*/
+extern "C" FFI_API struct RTCRtpTransceiver** rtc_get_transceivers(
+    struct RTCPeerConnection* peer,
+    size_t* size
+);
+
+/*
+Adds a transceiver without a track, such as to receive a kind of media without sending it,
+which fires the negotiationneeded event. Returns null on failure, in which case the error is
+written, its message must be released with rtc_free_string. The handle must be released with
+rtc_free_transceiver.
+*/
+extern "C" FFI_API struct RTCRtpTransceiver* rtc_add_transceiver(
+    struct RTCPeerConnection* peer,
+    enum MEDIA_TYPE kind,
+    enum TRANSCEIVER_DIRECTION direction,
+    struct RTCError* error
+);
+
+/*
+Returns the senders of the connection, released as the transceivers are.
+*/
+extern "C" FFI_API struct RTCRtpSender** rtc_get_senders(
+    struct RTCPeerConnection* peer,
+    size_t* size
+);
+
+/*
+Returns the receivers of the connection, released as the transceivers are.
+*/
+extern "C" FFI_API struct RTCRtpReceiver** rtc_get_receivers(
+    struct RTCPeerConnection* peer,
+    size_t* size
+);
+
+/*
+Frees an array of handles, the handles themselves are not released.
+*/
+extern "C" FFI_API void rtc_free_handles(void* handles);
+
+/*
+Returns the media id of a transceiver, or null until it is negotiated.
+The string must be released with rtc_free_string.
+*/
+extern "C" FFI_API char* rtc_transceiver_get_mid(struct RTCRtpTransceiver* transceiver);
+
+extern "C" FFI_API enum MEDIA_TYPE rtc_transceiver_get_kind(struct RTCRtpTransceiver* transceiver);
+
+/*
+The preferred direction of a transceiver, as used in the next offers and answers.
+*/
+extern "C" FFI_API enum TRANSCEIVER_DIRECTION rtc_transceiver_get_direction(
+    struct RTCRtpTransceiver* transceiver
+);
+
+/*
+The direction negotiated for a transceiver, or zero until it is negotiated.
+*/
+extern "C" FFI_API enum TRANSCEIVER_DIRECTION rtc_transceiver_get_current_direction(
+    struct RTCRtpTransceiver* transceiver
+);
+
+extern "C" FFI_API struct RTCRtpSender* rtc_transceiver_get_sender(struct RTCRtpTransceiver* transceiver);
+
+extern "C" FFI_API struct RTCRtpReceiver* rtc_transceiver_get_receiver(struct RTCRtpTransceiver* transceiver);
+
+/*
+Changes the preferred direction of a transceiver, which fires the negotiationneeded event,
+the direction is used by the media once it is negotiated. On failure the error is written,
+its message must be released with rtc_free_string.
+*/
+extern "C" FFI_API bool rtc_transceiver_set_direction(
+    struct RTCRtpTransceiver* transceiver,
+    enum TRANSCEIVER_DIRECTION direction,
+    struct RTCError* error
+);
+
+/*
+Stops a transceiver, its sender stops sending and its receiver ends its track at once,
+and the media section is rejected by the next negotiation. On failure the error is written,
+its message must be released with rtc_free_string.
+*/
+extern "C" FFI_API bool rtc_transceiver_stop(
+    struct RTCRtpTransceiver* transceiver,
+    struct RTCError* error
+);
+
+/*
+Offers an rtp header extension which is supported but not offered by default, such as
+abs-capture-time, in the next negotiations of a transceiver. On failure the error is written,
+its message must be released with rtc_free_string.
+*/
+extern "C" FFI_API bool rtc_transceiver_enable_header_extension(
+    struct RTCRtpTransceiver* transceiver,
+    const char* uri,
+    struct RTCError* error
+);
+
+extern "C" FFI_API void rtc_free_transceiver(struct RTCRtpTransceiver* transceiver);
+
+/*
+Returns the id of a sender, which must be released with rtc_free_string.
+*/
+extern "C" FFI_API char* rtc_sender_get_id(struct RTCRtpSender* sender);
+
+extern "C" FFI_API enum MEDIA_TYPE rtc_sender_get_kind(struct RTCRtpSender* sender);
+
+/*
+Returns the track sent by a sender, or null if it sends nothing.
+The track must be released with rtc_free_media_stream_track.
+*/
+extern "C" FFI_API struct MediaStreamTrack* rtc_sender_get_track(struct RTCRtpSender* sender);
+
+/*
+Returns the ssrcs of the encodings of a sender, one per simulcast layer, the retransmission
+ssrcs are not included. The size of the array is written to size, it must be released
+with rtc_free_ssrcs.
+*/
+extern "C" FFI_API uint32_t* rtc_sender_get_ssrcs(struct RTCRtpSender* sender, size_t* size);
+
+/*
+The priority of the encodings of a sender, low by default.
+*/
+extern "C" FFI_API enum PRIORITY rtc_sender_get_priority(struct RTCRtpSender* sender);
+
+/*
+Sets the priority of every encoding of a sender, both its share of the bandwidth and the DSCP
+marking of its packets. On failure the error is written, its message must be released
+with rtc_free_string.
+*/
+extern "C" FFI_API bool rtc_sender_set_priority(
+    struct RTCRtpSender* sender,
+    enum PRIORITY priority,
+    struct RTCError* error
+);
+
+/*
+What a video sender gives up when the CPU is overused or the bandwidth is too low to encode
+the frames at their resolution and frame rate.
+*/
+FFI_API enum DEGRADATION_PREFERENCE {
+    /*
+    The sender does not adapt, the frames are encoded as they are written, even when the
+    CPU is overused.
+    */
+    DEGRADATION_PREFERENCE_DISABLED = 1,
+    DEGRADATION_PREFERENCE_MAINTAIN_FRAMERATE,
+    DEGRADATION_PREFERENCE_MAINTAIN_RESOLUTION,
+    DEGRADATION_PREFERENCE_BALANCED,
+};
+
+/*
+Reads the degradation preference of a sender, returns false when it is not set, in which case
+libwebrtc maintains the frame rate of a camera and the resolution of a screen.
+*/
+extern "C" FFI_API bool rtc_sender_get_degradation_preference(
+    struct RTCRtpSender* sender,
+    enum DEGRADATION_PREFERENCE* preference
+);
+
+/*
+Sets the degradation preference of a sender, which turns the CPU overuse detection of its
+encoder off when it is disabled. On failure the error is written, its message must be released
+with rtc_free_string.
+*/
+extern "C" FFI_API bool rtc_sender_set_degradation_preference(
+    struct RTCRtpSender* sender,
+    enum DEGRADATION_PREFERENCE preference,
+    struct RTCError* error
+);
+
+/*
+The limits of the encodings of a sender, they are lowered further by the bandwidth estimation
+and the CPU adaptation of libwebrtc.
+*/
+extern "C" FFI_API struct RTCEncodingParameters {
+    /*
+    The bitrate of an encoding, in bits per second.
+    */
+    int max_bitrate_bps;
+    /*
+    The frame rate of an encoding, the frames written above it are dropped.
+    */
+    double max_framerate;
+    /*
+    The factor the width and the height of the frames are divided by before they are encoded.
+    */
+    double scale_resolution_down_by;
+};
+
+/*
+Reads the limits of the first encoding of a sender, leaves the zero of a limit which is not set.
+*/
+extern "C" FFI_API void rtc_sender_get_encoding_parameters(
+    struct RTCRtpSender* sender,
+    struct RTCEncodingParameters* parameters
+);
+
+/*
+Sets the limits of every encoding of a sender, a value of zero leaves the corresponding limit
+unchanged. The encoder is reconfigured without a renegotiation. On failure the error is written,
+its message must be released with rtc_free_string.
+*/
+extern "C" FFI_API bool rtc_sender_set_encoding_parameters(
+    struct RTCRtpSender* sender,
+    struct RTCEncodingParameters* parameters,
+    struct RTCError* error
+);
+
+/*
+Returns the first stream id of a sender, which is the sync group the remote peer plays its
+track in sync with, or null when the sender is part of no stream. It must be released with
+rtc_free_string.
+*/
+extern "C" FFI_API char* rtc_sender_get_sync_group(struct RTCRtpSender* sender);
+
+/*
+Replaces the streams of a sender, which fires the negotiationneeded event, the remote peer
+plays the tracks of the same first stream in sync once it is negotiated.
+*/
+extern "C" FFI_API void rtc_sender_set_streams(
+    struct RTCRtpSender* sender,
+    char** stream_ids,
+    int stream_ids_size
+);
+
+extern "C" FFI_API void rtc_free_sender(struct RTCRtpSender* sender);
+
+/*
+Returns the id of a receiver, which must be released with rtc_free_string.
+*/
+extern "C" FFI_API char* rtc_receiver_get_id(struct RTCRtpReceiver* receiver);
+
+extern "C" FFI_API enum MEDIA_TYPE rtc_receiver_get_kind(struct RTCRtpReceiver* receiver);
+
+/*
+Returns the track of a receiver, which must be released with rtc_free_media_stream_track.
+*/
+extern "C" FFI_API struct MediaStreamTrack* rtc_receiver_get_track(struct RTCRtpReceiver* receiver);
+
+/*
+Returns the first stream id of a receiver, from the remote description, which is the sync
+group its track is played in sync with, or null when it is part of no stream. It must be
+released with rtc_free_string.
+*/
+extern "C" FFI_API char* rtc_receiver_get_sync_group(struct RTCRtpReceiver* receiver);
+
+/*
+Returns the ssrcs signaled for a receiver, followed by those of the packets it recently
+received. The size of the array is written to size, it must be released with rtc_free_ssrcs.
+*/
+extern "C" FFI_API uint32_t* rtc_receiver_get_ssrcs(struct RTCRtpReceiver* receiver, size_t* size);
+
+/*
+Returns the estimated time in milliseconds between the capture of the latest packet of an ssrc
+of a receiver and its reception, from its abs-capture-time header extension, or -1 when the
+extension is not received or the clock of the sender is not estimated yet.
+*/
+extern "C" FFI_API int64_t rtc_receiver_get_capture_delay(struct RTCRtpReceiver* receiver, uint32_t ssrc);
+
+extern "C" FFI_API void rtc_free_receiver(struct RTCRtpReceiver* receiver);
+
+/*
+Frees a string returned by the native side.
+*/
+extern "C" FFI_API void rtc_free_string(char* string);
+
+/*
+Frees an array of ssrcs returned by the native side.
+*/
+extern "C" FFI_API void rtc_free_ssrcs(uint32_t* ssrcs);
+
+/*
+A codec supported by the senders or the receivers of a runtime.
+*/
+extern "C" FFI_API struct RTCRtpCodecCapability {
+    /*
+    The kind and the name of the codec, such as "video/AV1" or "audio/opus".
+    */
+    char* mime_type;
+    /*
+    The clock rate of the rtp timestamps, zero when unset.
+    */
+    int clock_rate;
+    /*
+    The number of audio channels, zero for the video codecs.
+    */
+    int channels;
+    /*
+    The format specific parameters of the codec, such as "minptime=10;useinbandfec=1",
+    null when there is none.
+    */
+    char* sdp_fmtp_line;
+};
+
+/*
+An rtp header extension supported by the senders or the receivers of a runtime.
+*/
+extern "C" FFI_API struct RTCRtpHeaderExtensionCapability {
+    char* uri;
+    /*
+    The id offered for the extension, zero when unset.
+    */
+    int preferred_id;
+};
+
+extern "C" FFI_API struct RTCRtpCapabilities {
+    struct RTCRtpCodecCapability* codecs;
+    size_t codecs_size;
+    struct RTCRtpHeaderExtensionCapability* header_extensions;
+    size_t header_extensions_size;
+};
+
+/*
+Returns the codecs and header extensions the senders of the runtime can send for the kind of
+media, which must be released with rtc_free_rtp_capabilities.
+*/
+extern "C" FFI_API struct RTCRtpCapabilities* rtc_get_rtp_sender_capabilities(
+    struct RTCRuntime* runtime,
+    enum MEDIA_TYPE kind
+);
+
+/*
+Returns the codecs and header extensions the receivers of the runtime can receive for the kind of
+media, which must be released with rtc_free_rtp_capabilities.
+*/
+extern "C" FFI_API struct RTCRtpCapabilities* rtc_get_rtp_receiver_capabilities(
+    struct RTCRuntime* runtime,
+    enum MEDIA_TYPE kind
+);
+
+extern "C" FFI_API void rtc_free_rtp_capabilities(struct RTCRtpCapabilities* capabilities);
@@ -0,0 +1,56 @@
+#include "logger.h"
+#include "convert.h"
+
+/* the sink is installed once for the process, the severity can be changed at any time. */
+static std::mutex sink_lock;
+static LogSink* sink = NULL;
+
+LogSink::LogSink(void* ctx, void (*handler)(enum LOG_SEVERITY severity, const char* message, void* ctx))
+{
+    _handler = handler;
+    _ctx = ctx;
+}
+
+void LogSink::OnLogMessage(const std::string& message)
+{
+    _handler(LOG_SEVERITY_INFO, message.c_str(), _ctx);
+}
+
+void LogSink::OnLogMessage(const std::string& message, rtc::LoggingSeverity severity)
+{
+    _handler(into_c(severity), message.c_str(), _ctx);
+}
+
+void rtc_set_log_handler(
+    enum LOG_SEVERITY severity,
+    void* ctx,
+    void (*handler)(enum LOG_SEVERITY severity, const char* message, void* ctx)
+)
+{
+    std::lock_guard<std::mutex> guard(sink_lock);
+    if (sink)
+    {
+        rtc::LogMessage::RemoveLogToStream(sink);
+        delete sink;
+    }
+
+    /* the messages are no longer printed to stderr once they are forwarded. */
+    rtc::LogMessage::LogToDebug(rtc::LS_NONE);
+    rtc::LogMessage::SetLogToStderr(false);
+    sink = new LogSink(ctx, handler);
+    rtc::LogMessage::AddLogToStream(sink, from_c(severity));
+}
+
+void rtc_set_log_severity(enum LOG_SEVERITY severity)
+{
+    std::lock_guard<std::mutex> guard(sink_lock);
+    if (sink)
+    {
+        rtc::LogMessage::RemoveLogToStream(sink);
+        rtc::LogMessage::AddLogToStream(sink, from_c(severity));
+    }
+    else
+    {
+        rtc::LogMessage::LogToDebug(from_c(severity));
+    }
+}
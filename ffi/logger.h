@@ -0,0 +1,20 @@
+#pragma once
+
+#include <mutex>
+#include "rtc_base/logging.h"
+#include "ffi.h"
+
+/*
+Forwards the messages of libwebrtc to a handler, the handler is called on the thread
+logging the message, the messages below the severity of the sink are filtered natively.
+*/
+class LogSink: public rtc::LogSink
+{
+public:
+    LogSink(void* ctx, void (*handler)(enum LOG_SEVERITY severity, const char* message, void* ctx));
+    void OnLogMessage(const std::string& message);
+    void OnLogMessage(const std::string& message, rtc::LoggingSeverity severity);
+private:
+    void (*_handler)(enum LOG_SEVERITY severity, const char* message, void* ctx);
+    void* _ctx;
+};
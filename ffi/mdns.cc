@@ -0,0 +1,300 @@
+#include <algorithm>
+#include <cctype>
+#include <vector>
+#include "mdns.h"
+
+#include "rtc_base/helpers.h"
+#include "rtc_base/logging.h"
+
+#ifdef _WIN32
+#include <ws2tcpip.h>
+#define close_socket closesocket
+#else
+#include <arpa/inet.h>
+#include <netinet/in.h>
+#include <sys/select.h>
+#include <sys/socket.h>
+#include <unistd.h>
+#define INVALID_SOCKET -1
+#define close_socket close
+#endif
+
+static const char* MDNS_GROUP = "224.0.0.251";
+static const uint16_t MDNS_PORT = 5353;
+static const uint16_t TYPE_A = 1;
+static const uint16_t TYPE_AAAA = 28;
+static const uint16_t TYPE_ANY = 255;
+/* the records of a name are unique, so the cache of the resolvers is flushed. */
+static const uint16_t CLASS_IN_FLUSH = 0x8001;
+static const uint32_t TTL = 120;
+
+static std::string lowercase(std::string name)
+{
+    std::transform(name.begin(), name.end(), name.begin(), [](unsigned char c)
+    {
+        return (char)std::tolower(c);
+    });
+
+    return name;
+}
+
+/*
+Reads a possibly compressed name, returns the offset after it or zero when it is malformed.
+*/
+static size_t read_name(const uint8_t* message, size_t size, size_t offset, std::string& name)
+{
+    size_t end = 0;
+    for (int jumps = 0; jumps < 16; )
+    {
+        if (offset >= size)
+        {
+            return 0;
+        }
+
+        uint8_t length = message[offset];
+        if (length == 0)
+        {
+            return end ? end : offset + 1;
+        }
+
+        if ((length & 0xC0) == 0xC0)
+        {
+            if (offset + 1 >= size)
+            {
+                return 0;
+            }
+
+            end = end ? end : offset + 2;
+            offset = ((length & 0x3F) << 8) | message[offset + 1];
+            jumps++;
+            continue;
+        }
+
+        if (offset + 1 + length > size)
+        {
+            return 0;
+        }
+
+        name += name.empty() ? "" : ".";
+        name.append((const char*)message + offset + 1, length);
+        offset += 1 + length;
+    }
+
+    return 0;
+}
+
+static void write_u16(std::vector<uint8_t>& buffer, uint16_t value)
+{
+    buffer.push_back(value >> 8);
+    buffer.push_back(value & 0xFF);
+}
+
+static void write_u32(std::vector<uint8_t>& buffer, uint32_t value)
+{
+    write_u16(buffer, value >> 16);
+    write_u16(buffer, value & 0xFFFF);
+}
+
+static void write_name(std::vector<uint8_t>& buffer, const std::string& name)
+{
+    size_t start = 0;
+    while (start < name.size())
+    {
+        size_t end = name.find('.', start);
+        end = end == std::string::npos ? name.size() : end;
+        buffer.push_back((uint8_t)(end - start));
+        buffer.insert(buffer.end(), name.begin() + start, name.begin() + end);
+        start = end + 1;
+    }
+
+    buffer.push_back(0);
+}
+
+std::unique_ptr<MdnsResponder> MdnsResponder::Create()
+{
+    mdns_socket_t fd = socket(AF_INET, SOCK_DGRAM, IPPROTO_UDP);
+    if (fd == INVALID_SOCKET)
+    {
+        return nullptr;
+    }
+
+    /* the port is usually shared with the mDNS service of the system. */
+    int reuse = 1;
+    setsockopt(fd, SOL_SOCKET, SO_REUSEADDR, (const char*)&reuse, sizeof(reuse));
+#ifdef SO_REUSEPORT
+    setsockopt(fd, SOL_SOCKET, SO_REUSEPORT, (const char*)&reuse, sizeof(reuse));
+#endif
+
+    struct sockaddr_in addr = {};
+    addr.sin_family = AF_INET;
+    addr.sin_port = htons(MDNS_PORT);
+    addr.sin_addr.s_addr = htonl(INADDR_ANY);
+
+    struct ip_mreq group = {};
+    inet_pton(AF_INET, MDNS_GROUP, &group.imr_multiaddr);
+    group.imr_interface.s_addr = htonl(INADDR_ANY);
+
+    if (bind(fd, (struct sockaddr*)&addr, sizeof(addr)) != 0 ||
+        setsockopt(fd, IPPROTO_IP, IP_ADD_MEMBERSHIP, (const char*)&group, sizeof(group)) != 0)
+    {
+        RTC_LOG(LS_WARNING) << "the mdns responder can not join the multicast group, "
+            << "the host candidates are not obfuscated";
+        close_socket(fd);
+        return nullptr;
+    }
+
+    return std::unique_ptr<MdnsResponder>(new MdnsResponder(fd));
+}
+
+MdnsResponder::MdnsResponder(mdns_socket_t socket)
+{
+    _socket = socket;
+    _running = true;
+    _thread = std::thread([this]()
+    {
+        this->run();
+    });
+}
+
+MdnsResponder::~MdnsResponder()
+{
+    _running = false;
+    _thread.join();
+    close_socket(_socket);
+}
+
+void MdnsResponder::CreateNameForAddress(const rtc::IPAddress& addr, NameCreatedCallback callback)
+{
+    std::string name;
+    {
+        std::lock_guard<std::mutex> guard(_mutex);
+        auto it = _names.find(addr);
+        if (it == _names.end())
+        {
+            it = _names.emplace(addr, rtc::CreateRandomUuid() + ".local").first;
+        }
+
+        name = it->second;
+    }
+
+    callback(addr, name);
+}
+
+void MdnsResponder::RemoveNameForAddress(const rtc::IPAddress& addr, NameRemovedCallback callback)
+{
+    bool removed;
+    {
+        std::lock_guard<std::mutex> guard(_mutex);
+        removed = _names.erase(addr) > 0;
+    }
+
+    callback(removed);
+}
+
+void MdnsResponder::run()
+{
+    uint8_t buffer[1500];
+    while (_running)
+    {
+        /* wakes up regularly to notice the responder is being destroyed. */
+        fd_set fds;
+        FD_ZERO(&fds);
+        FD_SET(_socket, &fds);
+        struct timeval timeout = { 0, 200 * 1000 };
+        if (select((int)_socket + 1, &fds, NULL, NULL, &timeout) <= 0)
+        {
+            continue;
+        }
+
+        struct sockaddr_in from = {};
+        socklen_t from_size = sizeof(from);
+        int size = recvfrom(_socket, (char*)buffer, sizeof(buffer), 0, (struct sockaddr*)&from, &from_size);
+        if (size > 0)
+        {
+            this->answer(buffer, (size_t)size, from);
+        }
+    }
+}
+
+void MdnsResponder::answer(const uint8_t* query, size_t size, const struct sockaddr_in& from)
+{
+    /* only the queries are answered, the responses of the other responders are ignored. */
+    if (size < 12 || (query[2] & 0x80))
+    {
+        return;
+    }
+
+    uint16_t questions = (query[4] << 8) | query[5];
+    std::vector<std::pair<std::string, rtc::IPAddress>> answers;
+    size_t offset = 12;
+    for (uint16_t i = 0; i < questions; i++)
+    {
+        std::string name;
+        offset = read_name(query, size, offset, name);
+        if (offset == 0 || offset + 4 > size)
+        {
+            return;
+        }
+
+        uint16_t type = (query[offset] << 8) | query[offset + 1];
+        offset += 4;
+
+        std::lock_guard<std::mutex> guard(_mutex);
+        for (auto& it : _names)
+        {
+            bool family = type == TYPE_ANY
+                || (type == TYPE_A && it.first.family() == AF_INET)
+                || (type == TYPE_AAAA && it.first.family() == AF_INET6);
+            if (family && lowercase(it.second) == lowercase(name))
+            {
+                answers.push_back({ it.second, it.first });
+            }
+        }
+    }
+
+    if (answers.empty())
+    {
+        return;
+    }
+
+    /* a query from another port than the mDNS port is a legacy unicast query, answered to its sender. */
+    bool legacy = ntohs(from.sin_port) != MDNS_PORT;
+    std::vector<uint8_t> response;
+    write_u16(response, legacy ? ((query[0] << 8) | query[1]) : 0);
+    write_u16(response, 0x8400);
+    write_u16(response, 0);
+    write_u16(response, (uint16_t)answers.size());
+    write_u16(response, 0);
+    write_u16(response, 0);
+    for (auto& answer : answers)
+    {
+        bool v4 = answer.second.family() == AF_INET;
+        write_name(response, answer.first);
+        write_u16(response, v4 ? TYPE_A : TYPE_AAAA);
+        write_u16(response, legacy ? 1 : CLASS_IN_FLUSH);
+        write_u32(response, TTL);
+        if (v4)
+        {
+            write_u16(response, 4);
+            auto ip = answer.second.ipv4_address();
+            auto bytes = (const uint8_t*)&ip;
+            response.insert(response.end(), bytes, bytes + 4);
+        }
+        else
+        {
+            write_u16(response, 16);
+            auto ip = answer.second.ipv6_address();
+            auto bytes = (const uint8_t*)&ip;
+            response.insert(response.end(), bytes, bytes + 16);
+        }
+    }
+
+    struct sockaddr_in to = from;
+    if (!legacy)
+    {
+        to.sin_port = htons(MDNS_PORT);
+        inet_pton(AF_INET, MDNS_GROUP, &to.sin_addr);
+    }
+
+    sendto(_socket, (const char*)response.data(), (int)response.size(), 0, (struct sockaddr*)&to, sizeof(to));
+}
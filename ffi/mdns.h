@@ -0,0 +1,44 @@
+#pragma once
+
+#include <atomic>
+#include <map>
+#include <mutex>
+#include <string>
+#include <thread>
+#include "rtc_base/ip_address.h"
+#include "rtc_base/mdns_responder_interface.h"
+
+#ifdef _WIN32
+#include <winsock2.h>
+typedef SOCKET mdns_socket_t;
+#else
+typedef int mdns_socket_t;
+#endif
+
+/*
+Obfuscates the addresses of the host candidates behind random ".local" names, and answers
+the multicast DNS queries of the remote peers for them, as described by the draft
+"Using Multicast DNS to protect privacy when exposing ICE candidates".
+
+Only the IPv4 multicast group is joined, the AAAA records of the IPv6 addresses are answered on it.
+*/
+class MdnsResponder: public webrtc::MdnsResponderInterface
+{
+public:
+    ~MdnsResponder();
+    /*
+    Joins the multicast group, returns null when the mDNS port can not be bound.
+    */
+    static std::unique_ptr<MdnsResponder> Create();
+    void CreateNameForAddress(const rtc::IPAddress& addr, NameCreatedCallback callback) override;
+    void RemoveNameForAddress(const rtc::IPAddress& addr, NameRemovedCallback callback) override;
+private:
+    MdnsResponder(mdns_socket_t socket);
+    void run();
+    void answer(const uint8_t* query, size_t size, const struct sockaddr_in& from);
+    std::mutex _mutex;
+    std::map<rtc::IPAddress, std::string> _names;
+    mdns_socket_t _socket;
+    std::atomic<bool> _running;
+    std::thread _thread;
+};
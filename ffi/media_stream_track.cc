@@ -0,0 +1,122 @@
+#include "media_stream_track.h"
+
+struct MediaStreamTrack* into_c(rtc::scoped_refptr<webrtc::MediaStreamTrackInterface> track)
+{
+    struct MediaStreamTrackRef* c_track = new MediaStreamTrackRef();
+    c_track->track = track;
+    c_track->_id = track->id();
+    c_track->_kind = track->kind();
+
+    c_track->id = (char*)c_track->_id.c_str();
+    c_track->kind = (char*)c_track->_kind.c_str();
+    c_track->label = (char*)c_track->_label.c_str();
+    c_track->enabled = track->enabled();
+    c_track->muted = false;
+    c_track->ready_state = track->state() == webrtc::MediaStreamTrackInterface::kLive;
+    c_track->remote = true;
+    c_track->width = 0;
+    c_track->height = 0;
+    c_track->frame_rate = 0;
+    return c_track;
+}
+
+void rtc_free_media_stream_track(struct MediaStreamTrack* track)
+{
+    delete static_cast<MediaStreamTrackRef*>(track);
+}
+
+void media_stream_track_set_enabled(struct MediaStreamTrack* c_track, bool enabled)
+{
+    auto track = static_cast<MediaStreamTrackRef*>(c_track)->track;
+    track->set_enabled(enabled);
+    c_track->enabled = enabled;
+}
+
+bool media_stream_track_get_enabled(struct MediaStreamTrack* c_track)
+{
+    return static_cast<MediaStreamTrackRef*>(c_track)->track->enabled();
+}
+
+static rtc::scoped_refptr<webrtc::MediaSourceInterface> get_source(
+    rtc::scoped_refptr<webrtc::MediaStreamTrackInterface> track
+)
+{
+    if (track->kind() == webrtc::MediaStreamTrackInterface::kVideoKind)
+    {
+        auto video = static_cast<webrtc::VideoTrackInterface*>(track.get());
+        return rtc::scoped_refptr<webrtc::MediaSourceInterface>(video->GetSource());
+    }
+    else
+    {
+        auto audio = static_cast<webrtc::AudioTrackInterface*>(track.get());
+        return rtc::scoped_refptr<webrtc::MediaSourceInterface>(audio->GetSource());
+    }
+}
+
+TrackObserver::TrackObserver(
+    rtc::scoped_refptr<webrtc::MediaStreamTrackInterface> track,
+    void* ctx,
+    void (*handler)(enum TRACK_EVENT event, void* ctx)
+)
+{
+    _track = track;
+    _source = get_source(track);
+    _handler = handler;
+    _ctx = ctx;
+    _muted = _source && _source->state() == webrtc::MediaSourceInterface::kMuted;
+    _ended = track->state() == webrtc::MediaStreamTrackInterface::kEnded;
+
+    /* the tracks and the sources of a connection are proxies, the observers are
+    registered and removed on the signaling thread, which calls them. */
+    _track->RegisterObserver(this);
+    if (_source)
+    {
+        _source->RegisterObserver(this);
+    }
+}
+
+TrackObserver::~TrackObserver()
+{
+    _track->UnregisterObserver(this);
+    if (_source)
+    {
+        _source->UnregisterObserver(this);
+    }
+}
+
+void TrackObserver::OnChanged()
+{
+    auto muted = _source && _source->state() == webrtc::MediaSourceInterface::kMuted;
+    if (muted != _muted)
+    {
+        _muted = muted;
+        _handler(muted ? TRACK_EVENT_MUTE : TRACK_EVENT_UNMUTE, _ctx);
+    }
+
+    if (!_ended && _track->state() == webrtc::MediaStreamTrackInterface::kEnded)
+    {
+        _ended = true;
+        _handler(TRACK_EVENT_ENDED, _ctx);
+    }
+}
+
+struct RTCTrackObserver* media_stream_track_add_observer(
+    struct MediaStreamTrack* c_track,
+    void* ctx,
+    void (*handler)(enum TRACK_EVENT event, void* ctx)
+)
+{
+    auto observer = new RTCTrackObserver();
+    observer->observer = std::make_unique<TrackObserver>(
+        static_cast<MediaStreamTrackRef*>(c_track)->track,
+        ctx,
+        handler
+    );
+
+    return observer;
+}
+
+void media_stream_track_remove_observer(struct RTCTrackObserver* observer)
+{
+    delete observer;
+}
@@ -0,0 +1,50 @@
+#pragma once
+
+#include <memory>
+#include <string>
+#include "api/media_stream_interface.h"
+#include "source.h"
+#include "ffi.h"
+
+/*
+A MediaStreamTrack handed over to rust, it keeps the native track alive
+and owns the strings pointed to by the C struct until it is freed.
+The source is only set on the local tracks created by rust.
+*/
+struct MediaStreamTrackRef: public MediaStreamTrack {
+    rtc::scoped_refptr<webrtc::MediaStreamTrackInterface> track;
+    std::string _id;
+    std::string _kind;
+    std::string _label;
+    rtc::scoped_refptr<VideoTrackSource> video_source;
+    rtc::scoped_refptr<AudioTrackSource> audio_source;
+};
+
+struct MediaStreamTrack* into_c(rtc::scoped_refptr<webrtc::MediaStreamTrackInterface> track);
+
+/*
+Observes a track and its source, the handler is called on the signaling thread when the
+source is muted or unmuted and when the track ends, until the observer is removed.
+*/
+class TrackObserver: public webrtc::ObserverInterface
+{
+public:
+    TrackObserver(
+        rtc::scoped_refptr<webrtc::MediaStreamTrackInterface> track,
+        void* ctx,
+        void (*handler)(enum TRACK_EVENT event, void* ctx)
+    );
+    ~TrackObserver();
+    void OnChanged();
+private:
+    rtc::scoped_refptr<webrtc::MediaStreamTrackInterface> _track;
+    rtc::scoped_refptr<webrtc::MediaSourceInterface> _source;
+    void (*_handler)(enum TRACK_EVENT event, void* ctx);
+    void* _ctx;
+    bool _muted;
+    bool _ended;
+};
+
+struct RTCTrackObserver {
+    std::unique_ptr<TrackObserver> observer;
+};
@@ -0,0 +1,416 @@
+#include <algorithm>
+#include "network.h"
+#include "pc/webrtc_sdp.h"
+
+FilteredNetworkManager::FilteredNetworkManager(
+    rtc::SocketFactory* socket_factory,
+    NetworkFilter filter,
+    bool mdns_obfuscation
+) : rtc::BasicNetworkManager(nullptr, socket_factory)
+{
+    this->_filter = filter;
+    if (mdns_obfuscation)
+    {
+        this->_mdns_responder = MdnsResponder::Create();
+    }
+
+    this->SignalNetworksChanged.connect(this, &FilteredNetworkManager::on_networks_changed);
+}
+
+std::vector<const rtc::Network*> FilteredNetworkManager::GetNetworks() const
+{
+    std::vector<const rtc::Network*> networks;
+    for (auto network : rtc::BasicNetworkManager::GetNetworks())
+    {
+        if (this->is_allowed(network))
+        {
+            networks.push_back(network);
+        }
+    }
+
+    return networks;
+}
+
+webrtc::MdnsResponderInterface* FilteredNetworkManager::GetMdnsResponder() const
+{
+    return this->_mdns_responder.get();
+}
+
+bool FilteredNetworkManager::is_allowed(const rtc::Network* network) const
+{
+    auto contains = [&](const std::vector<std::string>& names)
+    {
+        return std::find(names.begin(), names.end(), network->name()) != names.end();
+    };
+
+    if (contains(this->_filter.denied))
+    {
+        return false;
+    }
+
+    if (!this->_filter.ipv6 && network->prefix().family() == AF_INET6)
+    {
+        return false;
+    }
+
+    if (!this->_filter.link_local && rtc::IPIsLinkLocal(network->prefix()))
+    {
+        return false;
+    }
+
+    /* only the interfaces known to libwebrtc as VPNs are recognized. */
+    if (!this->_filter.vpn && network->type() == rtc::ADAPTER_TYPE_VPN)
+    {
+        return false;
+    }
+
+    return this->_filter.allowed.empty() || contains(this->_filter.allowed);
+}
+
+void FilteredNetworkManager::set_networks_changed_handler(
+    void* ctx,
+    void (*handler)(char** names, int size, void* ctx)
+)
+{
+    this->_on_networks_changed_handler = handler;
+    this->_on_networks_changed_ctx = ctx;
+}
+
+void FilteredNetworkManager::on_networks_changed()
+{
+    if (this->_on_networks_changed_handler == NULL) return;
+
+    std::vector<std::string> names;
+    for (auto network : this->GetNetworks())
+    {
+        names.push_back(network->name());
+    }
+
+    std::vector<char*> c_names;
+    for (auto& name : names)
+    {
+        c_names.push_back((char*)name.c_str());
+    }
+
+    this->_on_networks_changed_handler(c_names.data(), (int)c_names.size(), this->_on_networks_changed_ctx);
+}
+
+PrewarmingPortAllocator::PrewarmingPortAllocator(
+    rtc::NetworkManager* network_manager,
+    rtc::PacketSocketFactory* socket_factory
+) : cricket::BasicPortAllocator(network_manager, socket_factory)
+{
+}
+
+void PrewarmingPortAllocator::set_prewarmed_handler(
+    void* ctx,
+    void (*handler)(int candidates, void* ctx)
+)
+{
+    this->_on_prewarmed_handler = handler;
+    this->_on_prewarmed_ctx = ctx;
+}
+
+void PrewarmingPortAllocator::set_candidate_filter(
+    void* ctx,
+    bool (*filter)(char* candidate, void* ctx)
+)
+{
+    this->_candidate_filter = filter;
+    this->_candidate_filter_ctx = ctx;
+}
+
+bool PrewarmingPortAllocator::has_candidate_filter() const
+{
+    return this->_candidate_filter != NULL;
+}
+
+bool PrewarmingPortAllocator::accept_candidate(const cricket::Candidate& candidate)
+{
+    if (this->_candidate_filter == NULL) return true;
+
+    auto sdp = webrtc::SdpSerializeCandidate(candidate);
+    return this->_candidate_filter((char*)sdp.c_str(), this->_candidate_filter_ctx);
+}
+
+cricket::PortAllocatorSession* PrewarmingPortAllocator::CreateSessionInternal(
+    const std::string& content_name,
+    int component,
+    const std::string& ice_ufrag,
+    const std::string& ice_pwd
+)
+{
+    auto session = cricket::BasicPortAllocator::CreateSessionInternal(
+        content_name,
+        component,
+        ice_ufrag,
+        ice_pwd
+    );
+
+    /*
+    the filter is read when the candidates are gathered, so removing it lets the next
+    candidates of the session through.
+    */
+    if (this->has_candidate_filter() && !content_name.empty())
+    {
+        return new FilteringPortAllocatorSession(
+            std::unique_ptr<cricket::PortAllocatorSession>(session),
+            [this](const cricket::Candidate& candidate) { return this->accept_candidate(candidate); }
+        );
+    }
+
+    /* the sessions of the pool are the only ones created without a content name. */
+    if (content_name.empty())
+    {
+        this->_pending++;
+        session->SignalCandidatesAllocationDone.connect(this, &PrewarmingPortAllocator::on_allocation_done);
+    }
+
+    return session;
+}
+
+void PrewarmingPortAllocator::on_allocation_done(cricket::PortAllocatorSession* session)
+{
+    this->_candidates += (int)session->ReadyCandidates().size();
+    if (--this->_pending > 0) return;
+
+    auto candidates = this->_candidates;
+    this->_candidates = 0;
+    if (this->_on_prewarmed_handler == NULL) return;
+
+    this->_on_prewarmed_handler(candidates, this->_on_prewarmed_ctx);
+}
+
+FilteringPortAllocatorSession::FilteringPortAllocatorSession(
+    std::unique_ptr<cricket::PortAllocatorSession> session,
+    std::function<bool(const cricket::Candidate&)> accept
+) : cricket::PortAllocatorSession(
+        session->content_name(),
+        session->component(),
+        session->ice_ufrag(),
+        session->ice_pwd(),
+        session->flags()
+    )
+{
+    this->_session = std::move(session);
+    this->_accept = accept;
+    this->_session->SignalPortReady.connect(this, &FilteringPortAllocatorSession::on_port_ready);
+    this->_session->SignalPortsPruned.connect(this, &FilteringPortAllocatorSession::on_ports_pruned);
+    this->_session->SignalCandidatesReady.connect(this, &FilteringPortAllocatorSession::on_candidates_ready);
+    this->_session->SignalCandidatesRemoved.connect(this, &FilteringPortAllocatorSession::on_candidates_removed);
+    this->_session->SignalCandidateError.connect(this, &FilteringPortAllocatorSession::on_candidate_error);
+    this->_session->SignalCandidatesAllocationDone.connect(this, &FilteringPortAllocatorSession::on_allocation_done);
+    this->_session->SignalIceRegathering.connect(this, &FilteringPortAllocatorSession::on_ice_regathering);
+}
+
+void FilteringPortAllocatorSession::SetCandidateFilter(uint32_t filter)
+{
+    this->_session->SetCandidateFilter(filter);
+}
+
+void FilteringPortAllocatorSession::StartGettingPorts()
+{
+    this->_session->StartGettingPorts();
+}
+
+void FilteringPortAllocatorSession::StopGettingPorts()
+{
+    this->_session->StopGettingPorts();
+}
+
+void FilteringPortAllocatorSession::ClearGettingPorts()
+{
+    this->_session->ClearGettingPorts();
+}
+
+bool FilteringPortAllocatorSession::IsGettingPorts()
+{
+    return this->_session->IsGettingPorts();
+}
+
+bool FilteringPortAllocatorSession::IsCleared() const
+{
+    return this->_session->IsCleared();
+}
+
+bool FilteringPortAllocatorSession::IsStopped() const
+{
+    return this->_session->IsStopped();
+}
+
+void FilteringPortAllocatorSession::RegatherOnFailedNetworks()
+{
+    this->_session->RegatherOnFailedNetworks();
+}
+
+void FilteringPortAllocatorSession::GetCandidateStatsFromReadyPorts(
+    cricket::CandidateStatsList* candidate_stats_list
+) const
+{
+    cricket::CandidateStatsList stats;
+    this->_session->GetCandidateStatsFromReadyPorts(&stats);
+    for (auto& candidate_stats : stats)
+    {
+        if (this->_accept(candidate_stats.candidate()))
+        {
+            candidate_stats_list->push_back(std::move(candidate_stats));
+        }
+    }
+}
+
+void FilteringPortAllocatorSession::SetStunKeepaliveIntervalForReadyPorts(
+    const absl::optional<int>& stun_keepalive_interval
+)
+{
+    this->_session->SetStunKeepaliveIntervalForReadyPorts(stun_keepalive_interval);
+}
+
+std::vector<cricket::PortInterface*> FilteringPortAllocatorSession::ReadyPorts() const
+{
+    std::vector<cricket::PortInterface*> ports;
+    for (auto port : this->_session->ReadyPorts())
+    {
+        if (this->_ready.count(port) || this->is_accepted(port))
+        {
+            ports.push_back(port);
+        }
+    }
+
+    return ports;
+}
+
+std::vector<cricket::Candidate> FilteringPortAllocatorSession::ReadyCandidates() const
+{
+    return this->accepted(this->_session->ReadyCandidates());
+}
+
+bool FilteringPortAllocatorSession::CandidatesAllocationDone() const
+{
+    return this->_session->CandidatesAllocationDone();
+}
+
+bool FilteringPortAllocatorSession::PruneAllPorts()
+{
+    return this->_session->PruneAllPorts();
+}
+
+uint32_t FilteringPortAllocatorSession::generation()
+{
+    return this->_session->generation();
+}
+
+void FilteringPortAllocatorSession::set_generation(uint32_t generation)
+{
+    this->_session->set_generation(generation);
+}
+
+bool FilteringPortAllocatorSession::is_accepted(cricket::PortInterface* port) const
+{
+    auto& candidates = port->Candidates();
+    return std::any_of(candidates.begin(), candidates.end(), this->_accept);
+}
+
+std::vector<cricket::Candidate> FilteringPortAllocatorSession::accepted(
+    const std::vector<cricket::Candidate>& candidates
+) const
+{
+    std::vector<cricket::Candidate> accepted;
+    std::copy_if(candidates.begin(), candidates.end(), std::back_inserter(accepted), this->_accept);
+    return accepted;
+}
+
+void FilteringPortAllocatorSession::on_port_ready(
+    cricket::PortAllocatorSession* session,
+    cricket::PortInterface* port
+)
+{
+    if (!this->is_accepted(port))
+    {
+        this->_pending.insert(port);
+        return;
+    }
+
+    this->_ready.insert(port);
+    this->SignalPortReady(this, port);
+}
+
+void FilteringPortAllocatorSession::on_ports_pruned(
+    cricket::PortAllocatorSession* session,
+    const std::vector<cricket::PortInterface*>& ports
+)
+{
+    std::vector<cricket::PortInterface*> pruned;
+    for (auto port : ports)
+    {
+        this->_pending.erase(port);
+        if (this->_ready.erase(port))
+        {
+            pruned.push_back(port);
+        }
+    }
+
+    if (!pruned.empty())
+    {
+        this->SignalPortsPruned(this, pruned);
+    }
+}
+
+void FilteringPortAllocatorSession::on_candidates_ready(
+    cricket::PortAllocatorSession* session,
+    const std::vector<cricket::Candidate>& candidates
+)
+{
+    auto accepted = this->accepted(candidates);
+    if (accepted.empty()) return;
+
+    /* a port whose first candidates were rejected, such as the host candidate of a shared socket, becomes usable. */
+    for (auto iter = this->_pending.begin(); iter != this->_pending.end();)
+    {
+        auto port = *iter;
+        if (this->is_accepted(port))
+        {
+            iter = this->_pending.erase(iter);
+            this->_ready.insert(port);
+            this->SignalPortReady(this, port);
+        }
+        else
+        {
+            iter++;
+        }
+    }
+
+    this->SignalCandidatesReady(this, accepted);
+}
+
+void FilteringPortAllocatorSession::on_candidates_removed(
+    cricket::PortAllocatorSession* session,
+    const std::vector<cricket::Candidate>& candidates
+)
+{
+    auto accepted = this->accepted(candidates);
+    if (!accepted.empty())
+    {
+        this->SignalCandidatesRemoved(this, accepted);
+    }
+}
+
+void FilteringPortAllocatorSession::on_candidate_error(
+    cricket::PortAllocatorSession* session,
+    const cricket::IceCandidateErrorEvent& event
+)
+{
+    this->SignalCandidateError(this, event);
+}
+
+void FilteringPortAllocatorSession::on_allocation_done(cricket::PortAllocatorSession* session)
+{
+    this->SignalCandidatesAllocationDone(this);
+}
+
+void FilteringPortAllocatorSession::on_ice_regathering(
+    cricket::PortAllocatorSession* session,
+    cricket::IceRegatheringReason reason
+)
+{
+    this->SignalIceRegathering(this, reason);
+}
@@ -0,0 +1,146 @@
+#pragma once
+
+#include <functional>
+#include <memory>
+#include <set>
+#include <string>
+#include <vector>
+#include "p2p/base/port_allocator.h"
+#include "p2p/client/basic_port_allocator.h"
+#include "rtc_base/network.h"
+#include "rtc_base/third_party/sigslot/sigslot.h"
+#include "mdns.h"
+
+/*
+The network interfaces a connection may gather candidates on.
+*/
+struct NetworkFilter
+{
+    std::vector<std::string> allowed;
+    std::vector<std::string> denied;
+    bool ipv6;
+    bool link_local;
+    bool vpn;
+};
+
+/*
+A network manager which only exposes the network interfaces allowed by the configuration,
+the deny list takes precedence over the allow list, an empty allow list allows every interface.
+With an mDNS responder, the addresses of the host candidates are replaced by ".local" names.
+*/
+class FilteredNetworkManager: public rtc::BasicNetworkManager, public sigslot::has_slots<>
+{
+public:
+    FilteredNetworkManager(
+        rtc::SocketFactory* socket_factory,
+        NetworkFilter filter,
+        bool mdns_obfuscation
+    );
+    std::vector<const rtc::Network*> GetNetworks() const override;
+    webrtc::MdnsResponderInterface* GetMdnsResponder() const override;
+    /*
+    Must be called on the network thread, where the handler is called.
+    */
+    void set_networks_changed_handler(void* ctx, void (*handler)(char** names, int size, void* ctx));
+private:
+    bool is_allowed(const rtc::Network* network) const;
+    void on_networks_changed();
+    void (*_on_networks_changed_handler)(char** names, int size, void* ctx) = NULL;
+    void* _on_networks_changed_ctx = NULL;
+    NetworkFilter _filter;
+    std::unique_ptr<MdnsResponder> _mdns_responder;
+};
+
+/*
+A session which only signals the candidates accepted by a filter, a port is only signaled ready
+once one of its candidates is accepted, so the rejected candidates are neither signaled nor used
+by the connectivity checks.
+*/
+class FilteringPortAllocatorSession: public cricket::PortAllocatorSession, public sigslot::has_slots<>
+{
+public:
+    FilteringPortAllocatorSession(
+        std::unique_ptr<cricket::PortAllocatorSession> session,
+        std::function<bool(const cricket::Candidate&)> accept
+    );
+    void SetCandidateFilter(uint32_t filter) override;
+    void StartGettingPorts() override;
+    void StopGettingPorts() override;
+    void ClearGettingPorts() override;
+    bool IsGettingPorts() override;
+    bool IsCleared() const override;
+    bool IsStopped() const override;
+    void RegatherOnFailedNetworks() override;
+    void GetCandidateStatsFromReadyPorts(cricket::CandidateStatsList* candidate_stats_list) const override;
+    void SetStunKeepaliveIntervalForReadyPorts(const absl::optional<int>& stun_keepalive_interval) override;
+    std::vector<cricket::PortInterface*> ReadyPorts() const override;
+    std::vector<cricket::Candidate> ReadyCandidates() const override;
+    bool CandidatesAllocationDone() const override;
+    bool PruneAllPorts() override;
+    uint32_t generation() override;
+    void set_generation(uint32_t generation) override;
+private:
+    bool is_accepted(cricket::PortInterface* port) const;
+    void on_port_ready(cricket::PortAllocatorSession* session, cricket::PortInterface* port);
+    void on_ports_pruned(
+        cricket::PortAllocatorSession* session,
+        const std::vector<cricket::PortInterface*>& ports
+    );
+    void on_candidates_ready(
+        cricket::PortAllocatorSession* session,
+        const std::vector<cricket::Candidate>& candidates
+    );
+    void on_candidates_removed(
+        cricket::PortAllocatorSession* session,
+        const std::vector<cricket::Candidate>& candidates
+    );
+    void on_candidate_error(cricket::PortAllocatorSession* session, const cricket::IceCandidateErrorEvent& event);
+    void on_allocation_done(cricket::PortAllocatorSession* session);
+    void on_ice_regathering(cricket::PortAllocatorSession* session, cricket::IceRegatheringReason reason);
+    std::vector<cricket::Candidate> accepted(const std::vector<cricket::Candidate>& candidates) const;
+    std::unique_ptr<cricket::PortAllocatorSession> _session;
+    std::function<bool(const cricket::Candidate&)> _accept;
+    /*
+    The ports ready in the session, which are not signaled until one of their candidates is accepted.
+    */
+    std::set<cricket::PortInterface*> _pending;
+    std::set<cricket::PortInterface*> _ready;
+};
+
+/*
+A port allocator which reports when its pooled sessions, the ones gathering before the first
+offer, have gathered every candidate.
+
+While a candidate filter is set, the sessions it creates for the transports only use the
+accepted candidates.
+*/
+class PrewarmingPortAllocator: public cricket::BasicPortAllocator, public sigslot::has_slots<>
+{
+public:
+    PrewarmingPortAllocator(rtc::NetworkManager* network_manager, rtc::PacketSocketFactory* socket_factory);
+    /*
+    Must be called on the network thread, where the handler is called.
+    */
+    void set_prewarmed_handler(void* ctx, void (*handler)(int candidates, void* ctx));
+    /*
+    Must be called on the network thread, where the filter is called, null removes the filter.
+    */
+    void set_candidate_filter(void* ctx, bool (*filter)(char* candidate, void* ctx));
+    bool has_candidate_filter() const;
+protected:
+    cricket::PortAllocatorSession* CreateSessionInternal(
+        const std::string& content_name,
+        int component,
+        const std::string& ice_ufrag,
+        const std::string& ice_pwd
+    ) override;
+private:
+    void on_allocation_done(cricket::PortAllocatorSession* session);
+    bool accept_candidate(const cricket::Candidate& candidate);
+    void (*_on_prewarmed_handler)(int candidates, void* ctx) = NULL;
+    bool (*_candidate_filter)(char* candidate, void* ctx) = NULL;
+    void* _candidate_filter_ctx = NULL;
+    void* _on_prewarmed_ctx = NULL;
+    int _pending = 0;
+    int _candidates = 0;
+};
@@ -1,57 +1,153 @@
 #include "api/peer_connection_interface.h"
 #include "observer.h"
+#include "convert.h"
+#include "data_channel.h"
+#include "media_stream_track.h"
 
 void Observer::OnSignalingChange(webrtc::PeerConnectionInterface::SignalingState new_state)
 {
-	if (this->_on_connectionstatechange_handler == NULL) return;
-	this->_on_connectionstatechange_handler(new_state);
+	if (this->_on_signalingchange_handler == NULL) return;
+	this->_on_signalingchange_handler(
+		(enum SIGNALING_STATE)(new_state + 1),
+		this->_on_signalingchange_ctx
+	);
 }
 
 void Observer::OnDataChannel(rtc::scoped_refptr<webrtc::DataChannelInterface> data_channel)
 {
+	{
+		std::lock_guard<std::mutex> guard(this->_data_channels_mutex);
+		this->_data_channels.push_back(data_channel);
+	}
+
 	if (this->_on_datachannel_handler == NULL) return;
-	this->_on_datachannel_handler(data_channel);
+	this->_on_datachannel_handler(into_c(data_channel), this->_on_datachannel_ctx);
+}
+
+void Observer::close_data_channels()
+{
+	std::vector<rtc::scoped_refptr<webrtc::DataChannelInterface>> data_channels;
+	{
+		std::lock_guard<std::mutex> guard(this->_data_channels_mutex);
+		data_channels.swap(this->_data_channels);
+	}
+
+	for (auto data_channel : data_channels)
+	{
+		data_channel->Close();
+	}
 }
 
 void Observer::OnRenegotiationNeeded()
 {
 	if (this->_on_renegotiationneeded_handler == NULL) return;
-	this->_on_renegotiationneeded_handler();
+	this->_on_renegotiationneeded_handler(this->_on_renegotiationneeded_ctx);
+}
+
+void Observer::OnIceConnectionChange(webrtc::PeerConnectionInterface::IceConnectionState new_state)
+{
+	if (this->_on_iceconnectionchange_handler == NULL) return;
+	this->_on_iceconnectionchange_handler(
+		(enum ICE_CONNECTION_STATE)(new_state + 1),
+		this->_on_iceconnectionchange_ctx
+	);
 }
 
 void Observer::OnIceGatheringChange(webrtc::PeerConnectionInterface::IceGatheringState new_state)
 {
 	if (this->_on_icegatheringchange_handler == NULL) return;
-	this->_on_icegatheringchange_handler(new_state);
+	this->_on_icegatheringchange_handler(
+		(enum ICE_GATHERING_STATE)(new_state + 1),
+		this->_on_icegatheringchange_ctx
+	);
 }
 
 void Observer::OnIceCandidate(const webrtc::IceCandidateInterface* candidate)
 {
 	if (this->_on_icecandidate_handler == NULL) return;
-	this->_on_icecandidate_handler(candidate);
+
+	std::string sdp;
+	candidate->ToString(&sdp);
+	std::string sdp_mid = candidate->sdp_mid();
+
+	struct RTCIceCandidate c_candidate;
+	c_candidate.candidate = (char*)sdp.c_str();
+	c_candidate.sdp_mid = (char*)sdp_mid.c_str();
+	c_candidate.sdp_mline_index = candidate->sdp_mline_index();
+	this->_on_icecandidate_handler(&c_candidate, this->_on_icecandidate_ctx);
+}
+
+void Observer::OnConnectionChange(webrtc::PeerConnectionInterface::PeerConnectionState new_state)
+{
+	if (this->_on_connectionstatechange_handler == NULL) return;
+
+	this->_on_connectionstatechange_handler(into_c(new_state), this->_on_connectionstatechange_ctx);
+}
+
+void Observer::OnTrack(rtc::scoped_refptr<webrtc::RtpTransceiverInterface> transceiver)
+{
+	if (this->_on_track_handler == NULL) return;
+
+	// the strings are borrowed by the handler, which copies them.
+	auto stream_ids = transceiver->receiver()->stream_ids();
+	std::vector<char*> c_stream_ids;
+	for (auto& id : stream_ids)
+	{
+		c_stream_ids.push_back(const_cast<char*>(id.c_str()));
+	}
+
+	this->_on_track_handler(
+		into_c(transceiver->receiver()->track()),
+		c_stream_ids.data(),
+		(int)c_stream_ids.size(),
+		this->_on_track_ctx
+	);
 }
 
-void Observer::set_connectionstatechange_handler(void (*handler)(webrtc::PeerConnectionInterface::SignalingState new_state))
+void Observer::set_connectionstatechange_handler(void* ctx, void (*handler)(enum CONNECTION_STATE state, void* ctx))
 {
 	this->_on_connectionstatechange_handler = handler;
+	this->_on_connectionstatechange_ctx = ctx;
 }
 
-void Observer::set_datachannel_handler(void (*handler)(rtc::scoped_refptr<webrtc::DataChannelInterface> data_channel))
+void Observer::set_signalingchange_handler(void* ctx, void (*handler)(enum SIGNALING_STATE state, void* ctx))
+{
+	this->_on_signalingchange_handler = handler;
+	this->_on_signalingchange_ctx = ctx;
+}
+
+void Observer::set_datachannel_handler(void* ctx, void (*handler)(struct RTCDataChannel* channel, void* ctx))
 {
 	this->_on_datachannel_handler = handler;
+	this->_on_datachannel_ctx = ctx;
+}
+
+void Observer::set_track_handler(void* ctx, void (*handler)(struct MediaStreamTrack* track, char** stream_ids, int stream_ids_size, void* ctx))
+{
+	this->_on_track_handler = handler;
+	this->_on_track_ctx = ctx;
 }
 
-void Observer::set_renegotiationneeded_handler(void (*handler)())
+void Observer::set_renegotiationneeded_handler(void* ctx, void (*handler)(void* ctx))
 {
 	this->_on_renegotiationneeded_handler = handler;
+	this->_on_renegotiationneeded_ctx = ctx;
+}
+
+void Observer::set_iceconnectionchange_handler(void* ctx, void (*handler)(enum ICE_CONNECTION_STATE state, void* ctx))
+{
+	this->_on_iceconnectionchange_handler = handler;
+	this->_on_iceconnectionchange_ctx = ctx;
 }
 
-void Observer::set_icegatheringchange_handler(void (*handler)(webrtc::PeerConnectionInterface::IceGatheringState new_state))
+void Observer::set_icegatheringchange_handler(void* ctx, void (*handler)(enum ICE_GATHERING_STATE state, void* ctx))
 {
 	this->_on_icegatheringchange_handler = handler;
+	this->_on_icegatheringchange_ctx = ctx;
 }
 
-void Observer::set_icecandidate_handler(void (*handler)(const webrtc::IceCandidateInterface* candidate))
+void Observer::set_icecandidate_handler(void* ctx, void (*handler)(struct RTCIceCandidate* candidate, void* ctx))
 {
 	this->_on_icecandidate_handler = handler;
+	this->_on_icecandidate_ctx = ctx;
 }
@@ -1,6 +1,9 @@
 #pragma once
 
+#include <mutex>
+#include <vector>
 #include "api/peer_connection_interface.h"
+#include "ffi.h"
 
 class Observer: public webrtc::PeerConnectionObserver 
 {
@@ -11,18 +14,39 @@ public:
     void OnSignalingChange(webrtc::PeerConnectionInterface::SignalingState new_state);
     void OnDataChannel(rtc::scoped_refptr<webrtc::DataChannelInterface> data_channel);
     void OnRenegotiationNeeded();
+    void OnIceConnectionChange(webrtc::PeerConnectionInterface::IceConnectionState new_state);
     void OnIceGatheringChange(webrtc::PeerConnectionInterface::IceGatheringState new_state);
     void OnIceCandidate(const webrtc::IceCandidateInterface* candidate);
+    void OnConnectionChange(webrtc::PeerConnectionInterface::PeerConnectionState new_state);
+    void OnTrack(rtc::scoped_refptr<webrtc::RtpTransceiverInterface> transceiver);
 public:
-    void set_connectionstatechange_handler(void (*handler)(webrtc::PeerConnectionInterface::SignalingState new_state));
-    void set_datachannel_handler(void (*handler)(rtc::scoped_refptr<webrtc::DataChannelInterface> data_channel));
-    void set_renegotiationneeded_handler(void (*handler)());
-    void set_icegatheringchange_handler(void (*handler)(webrtc::PeerConnectionInterface::IceGatheringState new_state));
-    void set_icecandidate_handler(void (*handler)(const webrtc::IceCandidateInterface* candidate));
+    void set_connectionstatechange_handler(void* ctx, void (*handler)(enum CONNECTION_STATE state, void* ctx));
+    void set_signalingchange_handler(void* ctx, void (*handler)(enum SIGNALING_STATE state, void* ctx));
+    void set_datachannel_handler(void* ctx, void (*handler)(struct RTCDataChannel* channel, void* ctx));
+    void set_track_handler(void* ctx, void (*handler)(struct MediaStreamTrack* track, char** stream_ids, int stream_ids_size, void* ctx));
+    void set_renegotiationneeded_handler(void* ctx, void (*handler)(void* ctx));
+    void set_iceconnectionchange_handler(void* ctx, void (*handler)(enum ICE_CONNECTION_STATE state, void* ctx));
+    void set_icegatheringchange_handler(void* ctx, void (*handler)(enum ICE_GATHERING_STATE state, void* ctx));
+    void set_icecandidate_handler(void* ctx, void (*handler)(struct RTCIceCandidate* candidate, void* ctx));
+    /* closes every channel opened by the remote peer so far. */
+    void close_data_channels();
 private:
-    void (*_on_connectionstatechange_handler)(webrtc::PeerConnectionInterface::SignalingState new_state);
-    void (*_on_datachannel_handler)(rtc::scoped_refptr<webrtc::DataChannelInterface> data_channel);
-    void (*_on_renegotiationneeded_handler)();
-    void (*_on_icegatheringchange_handler)(webrtc::PeerConnectionInterface::IceGatheringState new_state);
-    void (*_on_icecandidate_handler)(const webrtc::IceCandidateInterface* candidate);
-};
\ No newline at end of file
+    std::mutex _data_channels_mutex;
+    std::vector<rtc::scoped_refptr<webrtc::DataChannelInterface>> _data_channels;
+    void (*_on_connectionstatechange_handler)(enum CONNECTION_STATE state, void* ctx) = NULL;
+    void* _on_connectionstatechange_ctx = NULL;
+    void (*_on_signalingchange_handler)(enum SIGNALING_STATE state, void* ctx) = NULL;
+    void* _on_signalingchange_ctx = NULL;
+    void (*_on_datachannel_handler)(struct RTCDataChannel* channel, void* ctx) = NULL;
+    void* _on_datachannel_ctx = NULL;
+    void (*_on_track_handler)(struct MediaStreamTrack* track, char** stream_ids, int stream_ids_size, void* ctx) = NULL;
+    void* _on_track_ctx = NULL;
+    void (*_on_renegotiationneeded_handler)(void* ctx) = NULL;
+    void* _on_renegotiationneeded_ctx = NULL;
+    void (*_on_iceconnectionchange_handler)(enum ICE_CONNECTION_STATE state, void* ctx) = NULL;
+    void* _on_iceconnectionchange_ctx = NULL;
+    void (*_on_icegatheringchange_handler)(enum ICE_GATHERING_STATE state, void* ctx) = NULL;
+    void* _on_icegatheringchange_ctx = NULL;
+    void (*_on_icecandidate_handler)(struct RTCIceCandidate* candidate, void* ctx) = NULL;
+    void* _on_icecandidate_ctx = NULL;
+};
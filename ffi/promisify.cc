@@ -2,46 +2,103 @@
 #include "promisify.h"
 #include "convert.h"
 
+static webrtc::RTCError abandoned()
+{
+    return webrtc::RTCError(
+        webrtc::RTCErrorType::INTERNAL_ERROR,
+        "the operation was abandoned by the connection"
+    );
+}
+
+static struct RTCError into_c(const webrtc::RTCError& error)
+{
+    struct RTCError c_error;
+    c_error.code = (int)error.type();
+    c_error.message = error.message();
+    return c_error;
+}
+
 CreateDescPromisify::CreateDescPromisify(
     void* ctx, 
-    void (*callback)(struct RTCSessionDescription* desc, void* ctx)
+    void (*callback)(struct RTCSessionDescription* desc, struct RTCError* error, void* ctx)
 )
 {
     this->_callback = callback;
     this->_ctx = ctx;
 }
 
+CreateDescPromisify::~CreateDescPromisify()
+{
+    if (!this->_called) this->OnFailure(abandoned());
+}
+
 void CreateDescPromisify::OnSuccess(webrtc::SessionDescriptionInterface* desc)
 {
-    printf("rtc_create_offer OnSuccess\n");
+    /* the observer owns the description, the callback receives a copy of it. */
+    std::unique_ptr<webrtc::SessionDescriptionInterface> owned(desc);
+    this->_called = true;
     if (this->_callback == NULL) return;
-    this->_callback(into_c(desc), this->_ctx);
+    this->_callback(into_c(desc), NULL, this->_ctx);
 }
 
-void CreateDescPromisify::OnFailure(const std::string& _error)
+void CreateDescPromisify::OnFailure(webrtc::RTCError error)
 {
-    printf("rtc_create_offer OnFailure\n");
+    this->_called = true;
     if (this->_callback == NULL) return;
-    this->_callback(nullptr, this->_ctx);
+
+    struct RTCError c_error = into_c(error);
+    this->_callback(NULL, &c_error, this->_ctx);
 }
 
 SetDescPromisify::SetDescPromisify(
     void* ctx, 
-    void (*callback)(int res, void* ctx)
+    void (*callback)(struct RTCError* error, void* ctx)
 )
 {
     this->_callback = callback;
     this->_ctx = ctx;
 }
 
+SetDescPromisify::~SetDescPromisify()
+{
+    if (!this->_called) this->OnFailure(abandoned());
+}
+
 void SetDescPromisify::OnSuccess()
 {
+    this->_called = true;
+    if (this->_callback == NULL) return;
+    this->_callback(NULL, this->_ctx);
+}
+
+void SetDescPromisify::OnFailure(webrtc::RTCError error)
+{
+    this->_called = true;
     if (this->_callback == NULL) return;
-    this->_callback(1, this->_ctx);
+
+    struct RTCError c_error = into_c(error);
+    this->_callback(&c_error, this->_ctx);
+}
+
+GetStatsPromisify::GetStatsPromisify(
+    void* ctx,
+    void (*callback)(const char* stats, void* ctx)
+)
+{
+    this->_callback = callback;
+    this->_ctx = ctx;
 }
 
-void SetDescPromisify::OnFailure(const std::string& _error)
+GetStatsPromisify::~GetStatsPromisify()
 {
+    /* a null report tells the caller that the connection is gone. */
+    if (!this->_called && this->_callback != NULL) this->_callback(NULL, this->_ctx);
+}
+
+void GetStatsPromisify::OnStatsDelivered(const rtc::scoped_refptr<const webrtc::RTCStatsReport>& report)
+{
+    this->_called = true;
     if (this->_callback == NULL) return;
-    this->_callback(0, this->_ctx);
-}
\ No newline at end of file
+    std::string json = report->ToJson();
+    this->_callback(json.c_str(), this->_ctx);
+}
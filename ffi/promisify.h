@@ -1,25 +1,48 @@
 #pragma once
 
 #include "api/peer_connection_interface.h"
+#include "api/stats/rtc_stats_collector_callback.h"
+#include "ffi.h"
+
+/*
+The callbacks own the context of the caller, so every promisify invokes its callback
+exactly once, a promisify released without a result fails with an internal error.
+*/
 
 class CreateDescPromisify: public webrtc::CreateSessionDescriptionObserver 
 {
 public:
-    CreateDescPromisify(void* ctx, void (*callback)(struct RTCSessionDescription* desc, void* ctx));
+    CreateDescPromisify(void* ctx, void (*callback)(struct RTCSessionDescription* desc, struct RTCError* error, void* ctx));
+    ~CreateDescPromisify();
     void OnSuccess(webrtc::SessionDescriptionInterface* desc);
-    void OnFailure(const std::string& error);
+    void OnFailure(webrtc::RTCError error);
 private:
-    void (*_callback)(struct RTCSessionDescription* desc, void* ctx);
+    void (*_callback)(struct RTCSessionDescription* desc, struct RTCError* error, void* ctx);
     void* _ctx;
+    bool _called = false;
 };
 
 class SetDescPromisify: public webrtc::SetSessionDescriptionObserver 
 {
 public:
-    SetDescPromisify(void* ctx, void (*callback)(int res, void* ctx));
+    SetDescPromisify(void* ctx, void (*callback)(struct RTCError* error, void* ctx));
+    ~SetDescPromisify();
     void OnSuccess();
-    void OnFailure(const std::string& error);
+    void OnFailure(webrtc::RTCError error);
+private:
+    void (*_callback)(struct RTCError* error, void* ctx);
+    void* _ctx;
+    bool _called = false;
+};
+
+class GetStatsPromisify: public webrtc::RTCStatsCollectorCallback
+{
+public:
+    GetStatsPromisify(void* ctx, void (*callback)(const char* stats, void* ctx));
+    ~GetStatsPromisify();
+    void OnStatsDelivered(const rtc::scoped_refptr<const webrtc::RTCStatsReport>& report);
 private:
-    void (*_callback)(int res, void* ctx);
+    void (*_callback)(const char* stats, void* ctx);
     void* _ctx;
-};
\ No newline at end of file
+    bool _called = false;
+};
@@ -0,0 +1,191 @@
+#include "rtp.h"
+#include "runtime.h"
+
+#include "call/rtp_demuxer.h"
+#include "modules/rtp_rtcp/source/rtp_packet_received.h"
+#include "pc/rtp_transceiver.h"
+
+RtpTap::RtpTap(
+    struct RTCRuntime* runtime,
+    cricket::BaseChannel* channel,
+    bool intercept,
+    void* ctx,
+    void (*handler)(struct RTCRtpPacket* packet, void* ctx)
+)
+{
+    this->runtime = runtime;
+    _channel = channel;
+    _network = NULL;
+    _intercept = intercept;
+    _handler = handler;
+    _ctx = ctx;
+}
+
+RtpTap::~RtpTap()
+{
+    runtime->network_thread->Invoke<void>(RTC_FROM_HERE, [&]()
+    {
+        Detach();
+    });
+}
+
+bool RtpTap::Attach()
+{
+    auto transport = _channel->rtp_transport();
+    if (!transport || _channel->content_name().empty())
+    {
+        return false;
+    }
+
+    _network = _channel->media_channel()->network_interface();
+    _channel->media_channel()->SetInterface(this);
+
+    webrtc::RtpDemuxerCriteria criteria;
+    criteria.mid = _channel->content_name();
+    transport->UnregisterRtpDemuxerSink(_channel);
+    transport->RegisterRtpDemuxerSink(criteria, this);
+    transport->SignalRtcpPacketReceived.connect(this, &RtpTap::OnRtcpPacketReceived);
+    return true;
+}
+
+void RtpTap::Detach()
+{
+    std::lock_guard<std::mutex> guard(_lock);
+    _handler = NULL;
+
+    auto transport = _channel->rtp_transport();
+    if (!_network || !transport)
+    {
+        return;
+    }
+
+    /* gives the packets back to the channel, which registers with the same criteria. */
+    transport->SignalRtcpPacketReceived.disconnect(this);
+    transport->UnregisterRtpDemuxerSink(this);
+    webrtc::RtpDemuxerCriteria criteria;
+    criteria.mid = _channel->content_name();
+    transport->RegisterRtpDemuxerSink(criteria, _channel);
+    _channel->media_channel()->SetInterface(_network);
+    _network = NULL;
+}
+
+void RtpTap::Deliver(enum RTP_DIRECTION direction, bool rtcp, const uint8_t* data, size_t size)
+{
+    std::lock_guard<std::mutex> guard(_lock);
+    if (!_handler)
+    {
+        return;
+    }
+
+    struct RTCRtpPacket packet;
+    packet.direction = direction;
+    packet.rtcp = rtcp;
+    packet.data = data;
+    packet.size = size;
+    _handler(&packet, _ctx);
+}
+
+bool RtpTap::Send(const uint8_t* data, size_t size, bool rtcp)
+{
+    return runtime->network_thread->Invoke<bool>(RTC_FROM_HERE, [&]()
+    {
+        if (!_network)
+        {
+            return false;
+        }
+
+        rtc::CopyOnWriteBuffer packet(data, size);
+        rtc::PacketOptions options;
+        return rtcp
+            ? _network->SendRtcp(&packet, options)
+            : _network->SendPacket(&packet, options);
+    });
+}
+
+bool RtpTap::SendPacket(rtc::CopyOnWriteBuffer* packet, const rtc::PacketOptions& options)
+{
+    Deliver(RTP_DIRECTION_OUTGOING, false, packet->cdata(), packet->size());
+
+    /* an intercepted packet is only handed to the tap, as if it was sent. */
+    return _intercept || _network->SendPacket(packet, options);
+}
+
+bool RtpTap::SendRtcp(rtc::CopyOnWriteBuffer* packet, const rtc::PacketOptions& options)
+{
+    Deliver(RTP_DIRECTION_OUTGOING, true, packet->cdata(), packet->size());
+
+    /* rtcp is always sent, the feedback of the receivers is needed by the remote peer. */
+    return _network->SendRtcp(packet, options);
+}
+
+int RtpTap::SetOption(SocketType type, rtc::Socket::Option opt, int option)
+{
+    return _network->SetOption(type, opt, option);
+}
+
+void RtpTap::OnRtpPacket(const webrtc::RtpPacketReceived& packet)
+{
+    Deliver(RTP_DIRECTION_INCOMING, false, packet.data(), packet.size());
+    if (!_intercept)
+    {
+        _channel->OnRtpPacket(packet);
+    }
+}
+
+void RtpTap::OnRtcpPacketReceived(rtc::CopyOnWriteBuffer* packet, int64_t packet_time_us)
+{
+    Deliver(RTP_DIRECTION_INCOMING, true, packet->cdata(), packet->size());
+}
+
+struct RTCRtpTap* rtc_add_rtp_tap(
+    struct RTCPeerConnection* rtc,
+    char* mid,
+    bool intercept,
+    void* ctx,
+    void (*handler)(struct RTCRtpPacket* packet, void* ctx)
+)
+{
+    cricket::BaseChannel* channel = NULL;
+    for (auto transceiver : rtc->peer_connection->GetTransceivers())
+    {
+        if (transceiver->mid() != std::string(mid))
+        {
+            continue;
+        }
+
+        /* the proxy handed out by the connection wraps the transceiver owning the channel. */
+        auto internal = static_cast<webrtc::RtpTransceiverProxyWithInternal<webrtc::RtpTransceiver>*>(
+            transceiver.get()
+        )->internal();
+        channel = static_cast<cricket::BaseChannel*>(internal->channel());
+    }
+
+    if (!channel)
+    {
+        return NULL;
+    }
+
+    auto tap = new RtpTap(rtc->runtime, channel, intercept, ctx, handler);
+    auto attached = rtc->runtime->network_thread->Invoke<bool>(RTC_FROM_HERE, [&]()
+    {
+        return tap->Attach();
+    });
+
+    if (!attached)
+    {
+        delete tap;
+        return NULL;
+    }
+
+    return (struct RTCRtpTap*)tap;
+}
+
+bool rtc_rtp_tap_send(struct RTCRtpTap* tap, const uint8_t* data, size_t size, bool rtcp)
+{
+    return ((RtpTap*)tap)->Send(data, size, rtcp);
+}
+
+void rtc_remove_rtp_tap(struct RTCRtpTap* tap)
+{
+    delete (RtpTap*)tap;
+}
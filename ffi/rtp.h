@@ -0,0 +1,56 @@
+#pragma once
+
+#include <mutex>
+#include "call/rtp_packet_sink_interface.h"
+#include "media/base/media_channel.h"
+#include "pc/channel.h"
+#include "rtc_base/third_party/sigslot/sigslot.h"
+#include "ffi.h"
+
+/*
+A tap on the transport of a transceiver, the handler receives the rtp and rtcp
+packets of the transceiver on the network thread until the tap is removed.
+
+The outgoing packets are observed by decorating the network interface of the media
+channel, the incoming rtp packets by taking the place of the channel in the demuxer
+of the transport, which requires the transceiver to be identified by its mid.
+*/
+class RtpTap: public cricket::MediaChannel::NetworkInterface,
+              public webrtc::RtpPacketSinkInterface,
+              public sigslot::has_slots<>
+{
+public:
+    RtpTap(
+        struct RTCRuntime* runtime,
+        cricket::BaseChannel* channel,
+        bool intercept,
+        void* ctx,
+        void (*handler)(struct RTCRtpPacket* packet, void* ctx)
+    );
+    ~RtpTap();
+
+    /* called on the network thread. */
+    bool Attach();
+    void Detach();
+
+    /* injects a packet as if it was sent by the media channel. */
+    bool Send(const uint8_t* data, size_t size, bool rtcp);
+
+    bool SendPacket(rtc::CopyOnWriteBuffer* packet, const rtc::PacketOptions& options);
+    bool SendRtcp(rtc::CopyOnWriteBuffer* packet, const rtc::PacketOptions& options);
+    int SetOption(SocketType type, rtc::Socket::Option opt, int option);
+    void OnRtpPacket(const webrtc::RtpPacketReceived& packet);
+    void OnRtcpPacketReceived(rtc::CopyOnWriteBuffer* packet, int64_t packet_time_us);
+
+    struct RTCRuntime* runtime;
+private:
+    void Deliver(enum RTP_DIRECTION direction, bool rtcp, const uint8_t* data, size_t size);
+
+    cricket::BaseChannel* _channel;
+    /* the interface the media channel was using before the tap was attached. */
+    cricket::MediaChannel::NetworkInterface* _network;
+    bool _intercept;
+    std::mutex _lock;
+    void (*_handler)(struct RTCRtpPacket* packet, void* ctx);
+    void* _ctx;
+};
@@ -0,0 +1,135 @@
+#include "runtime.h"
+#include "convert.h"
+#include "codec.h"
+
+#include "api/create_peerconnection_factory.h"
+#include "api/field_trials.h"
+#include "api/task_queue/default_task_queue_factory.h"
+#include "api/audio_codecs/builtin_audio_decoder_factory.h"
+#include "api/audio_codecs/builtin_audio_encoder_factory.h"
+#include "api/video_codecs/builtin_video_decoder_factory.h"
+#include "api/video_codecs/builtin_video_encoder_factory.h"
+#include "rtc_base/memory_usage.h"
+
+std::unique_ptr<rtc::Thread> create_thread(std::unique_ptr<rtc::Thread> thread, std::string name)
+{
+    thread->SetName(name, nullptr);
+    thread->Start();
+    return thread;
+}
+
+static rtc::scoped_refptr<webrtc::PeerConnectionFactoryInterface> create_media_factory(
+    struct RTCRuntimeConfigure* c_config,
+    struct RTCRuntime* runtime,
+    rtc::Thread* worker_thread,
+    rtc::Thread* signaling_thread,
+    std::unique_ptr<webrtc::FieldTrials> field_trials
+)
+{
+    std::unique_ptr<webrtc::VideoEncoderFactory> encoder_factory = c_config->video_encoder_factory
+        ? std::make_unique<ExternalVideoEncoderFactory>(c_config->video_encoder_factory)
+        : webrtc::CreateBuiltinVideoEncoderFactory();
+    if (c_config->playout_delay)
+    {
+        encoder_factory = std::make_unique<PlayoutDelayEncoderFactory>(
+            std::move(encoder_factory),
+            *c_config->playout_delay
+        );
+    }
+
+    return webrtc::CreatePeerConnectionFactory(
+        runtime->network_thread.get() /* network_thread */,
+        worker_thread /* worker_thread */,
+        signaling_thread /* signaling_thread */,
+        nullptr /* default_adm */,
+        webrtc::CreateBuiltinAudioEncoderFactory(),
+        webrtc::CreateBuiltinAudioDecoderFactory(),
+        std::move(encoder_factory),
+        c_config->video_decoder_factory
+            ? std::make_unique<ExternalVideoDecoderFactory>(c_config->video_decoder_factory)
+            : webrtc::CreateBuiltinVideoDecoderFactory(),
+        nullptr /* audio_mixer */,
+        nullptr /* audio_processing */,
+        nullptr /* audio_frame_processor */,
+        std::move(field_trials)
+    );
+}
+
+struct RTCRuntime* create_rtc_runtime(struct RTCRuntimeConfigure* c_config)
+{
+    auto prefix = c_config->thread_name_prefix ? from_c(c_config->thread_name_prefix) : "webrtc";
+    struct RTCRuntime* runtime = new RTCRuntime();
+    runtime->network_thread = create_thread(rtc::Thread::CreateWithSocketServer(), prefix + "-network");
+
+    /* the worker thread is the second one to be dedicated, the signaling thread the third. */
+    if (c_config->threads >= 2)
+    {
+        runtime->worker_thread = create_thread(rtc::Thread::Create(), prefix + "-worker");
+    }
+
+    if (c_config->threads >= 3)
+    {
+        runtime->signaling_thread = create_thread(rtc::Thread::Create(), prefix + "-signaling");
+    }
+
+    auto worker_thread = runtime->worker_thread
+        ? runtime->worker_thread.get()
+        : runtime->network_thread.get();
+    auto signaling_thread = runtime->signaling_thread
+        ? runtime->signaling_thread.get()
+        : worker_thread;
+    /* the trials of a runtime do not leak into the global ones of the other runtimes. */
+    auto field_trials = webrtc::FieldTrials::CreateNoGlobal(
+        c_config->field_trials ? from_c(c_config->field_trials) : "");
+
+    if (c_config->data_only)
+    {
+        /* without a media engine nor a call factory the connections skip the media entirely. */
+        webrtc::PeerConnectionFactoryDependencies dependencies;
+        dependencies.network_thread = runtime->network_thread.get();
+        dependencies.worker_thread = worker_thread;
+        dependencies.signaling_thread = signaling_thread;
+        dependencies.task_queue_factory = webrtc::CreateDefaultTaskQueueFactory(field_trials.get());
+        dependencies.trials = std::move(field_trials);
+        runtime->factory = webrtc::CreateModularPeerConnectionFactory(std::move(dependencies));
+    }
+    else
+    {
+        runtime->factory = create_media_factory(c_config, runtime, worker_thread, signaling_thread, std::move(field_trials));
+    }
+
+    if (!runtime->factory)
+    {
+        delete runtime;
+        return NULL;
+    }
+
+    return runtime;
+}
+
+/* defined by the build, such as /D LIBWEBRTC_MILESTONE=96 /D LIBWEBRTC_COMMIT=\"...\" */
+#ifndef LIBWEBRTC_MILESTONE
+#define LIBWEBRTC_MILESTONE 0
+#endif
+
+#ifndef LIBWEBRTC_COMMIT
+#define LIBWEBRTC_COMMIT ""
+#endif
+
+void rtc_get_version(struct RTCVersion* version)
+{
+    version->milestone = LIBWEBRTC_MILESTONE;
+    version->commit = LIBWEBRTC_COMMIT;
+}
+
+int64_t rtc_get_resident_memory()
+{
+    return rtc::GetProcessResidentSizeBytes();
+}
+
+void rtc_free_runtime(struct RTCRuntime* runtime)
+{
+    /* the factory goes before the threads it runs on. */
+    runtime->factory = nullptr;
+    delete runtime;
+}
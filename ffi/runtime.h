@@ -0,0 +1,17 @@
+#pragma once
+
+#include <memory>
+#include "api/peer_connection_interface.h"
+#include "rtc_base/thread.h"
+#include "ffi.h"
+
+/*
+The threads and the factory shared by every connection created on a runtime,
+the threads which are not dedicated run on one of the others.
+*/
+struct RTCRuntime {
+    std::unique_ptr<rtc::Thread> network_thread;
+    std::unique_ptr<rtc::Thread> worker_thread;
+    std::unique_ptr<rtc::Thread> signaling_thread;
+    rtc::scoped_refptr<webrtc::PeerConnectionFactoryInterface> factory;
+};
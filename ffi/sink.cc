@@ -0,0 +1,130 @@
+#include "sink.h"
+#include "media_stream_track.h"
+
+#include "rtc_base/time_utils.h"
+
+VideoSink::VideoSink(
+    rtc::scoped_refptr<webrtc::VideoTrackInterface> track,
+    void* ctx,
+    void (*handler)(struct RTCVideoFrame* frame, void* ctx)
+)
+{
+    _track = track;
+    _handler = handler;
+    _ctx = ctx;
+    _track->AddOrUpdateSink(this, rtc::VideoSinkWants());
+}
+
+VideoSink::~VideoSink()
+{
+    /* once removed the track no longer delivers frames to the sink. */
+    _track->RemoveSink(this);
+}
+
+void VideoSink::OnFrame(const webrtc::VideoFrame& frame)
+{
+    auto buffer = frame.video_frame_buffer()->ToI420();
+    if (!buffer)
+    {
+        return;
+    }
+
+    struct RTCVideoFrame c_frame;
+    c_frame.width = buffer->width();
+    c_frame.height = buffer->height();
+    c_frame.timestamp_us = frame.timestamp_us();
+    c_frame.rotation = (int)frame.rotation();
+    c_frame.data_y = buffer->DataY();
+    c_frame.stride_y = buffer->StrideY();
+    c_frame.data_u = buffer->DataU();
+    c_frame.stride_u = buffer->StrideU();
+    c_frame.data_v = buffer->DataV();
+    c_frame.stride_v = buffer->StrideV();
+    _handler(&c_frame, _ctx);
+}
+
+AudioSink::AudioSink(
+    rtc::scoped_refptr<webrtc::AudioTrackInterface> track,
+    void* ctx,
+    void (*handler)(struct RTCAudioFrame* frame, void* ctx)
+)
+{
+    _track = track;
+    _handler = handler;
+    _ctx = ctx;
+    _track->AddSink(this);
+}
+
+AudioSink::~AudioSink()
+{
+    _track->RemoveSink(this);
+}
+
+void AudioSink::OnData(
+    const void* audio_data,
+    int bits_per_sample,
+    int sample_rate,
+    size_t number_of_channels,
+    size_t number_of_frames
+)
+{
+    if (bits_per_sample != 16)
+    {
+        return;
+    }
+
+    struct RTCAudioFrame c_frame;
+    c_frame.data = (const int16_t*)audio_data;
+    c_frame.sample_rate = sample_rate;
+    c_frame.channels = (int)number_of_channels;
+    c_frame.frames = (int)number_of_frames;
+    c_frame.timestamp_us = rtc::TimeMicros();
+    _handler(&c_frame, _ctx);
+}
+
+struct RTCSink* media_stream_track_add_video_sink(
+    struct MediaStreamTrack* c_track,
+    void* ctx,
+    void (*handler)(struct RTCVideoFrame* frame, void* ctx)
+)
+{
+    auto track = static_cast<MediaStreamTrackRef*>(c_track)->track;
+    if (track->kind() != webrtc::MediaStreamTrackInterface::kVideoKind)
+    {
+        return NULL;
+    }
+
+    return new VideoSink(
+        rtc::scoped_refptr<webrtc::VideoTrackInterface>(
+            static_cast<webrtc::VideoTrackInterface*>(track.get())
+        ),
+        ctx,
+        handler
+    );
+}
+
+struct RTCSink* media_stream_track_add_audio_sink(
+    struct MediaStreamTrack* c_track,
+    void* ctx,
+    void (*handler)(struct RTCAudioFrame* frame, void* ctx)
+)
+{
+    auto track = static_cast<MediaStreamTrackRef*>(c_track)->track;
+    if (track->kind() != webrtc::MediaStreamTrackInterface::kAudioKind)
+    {
+        return NULL;
+    }
+
+    return new AudioSink(
+        rtc::scoped_refptr<webrtc::AudioTrackInterface>(
+            static_cast<webrtc::AudioTrackInterface*>(track.get())
+        ),
+        ctx,
+        handler
+    );
+}
+
+void media_stream_track_remove_sink(struct RTCSink* sink)
+{
+    delete sink;
+}
@@ -0,0 +1,52 @@
+#pragma once
+
+#include "api/media_stream_interface.h"
+#include "api/video/video_frame.h"
+#include "api/video/video_sink_interface.h"
+#include "ffi.h"
+
+/*
+A sink attached to a track, the handler receives every frame of the track
+on the thread delivering it until the sink is removed.
+*/
+struct RTCSink {
+    virtual ~RTCSink() {}
+};
+
+class VideoSink: public RTCSink, public rtc::VideoSinkInterface<webrtc::VideoFrame>
+{
+public:
+    VideoSink(
+        rtc::scoped_refptr<webrtc::VideoTrackInterface> track,
+        void* ctx,
+        void (*handler)(struct RTCVideoFrame* frame, void* ctx)
+    );
+    ~VideoSink();
+    void OnFrame(const webrtc::VideoFrame& frame);
+private:
+    rtc::scoped_refptr<webrtc::VideoTrackInterface> _track;
+    void (*_handler)(struct RTCVideoFrame* frame, void* ctx);
+    void* _ctx;
+};
+
+class AudioSink: public RTCSink, public webrtc::AudioTrackSinkInterface
+{
+public:
+    AudioSink(
+        rtc::scoped_refptr<webrtc::AudioTrackInterface> track,
+        void* ctx,
+        void (*handler)(struct RTCAudioFrame* frame, void* ctx)
+    );
+    ~AudioSink();
+    void OnData(
+        const void* audio_data,
+        int bits_per_sample,
+        int sample_rate,
+        size_t number_of_channels,
+        size_t number_of_frames
+    );
+private:
+    rtc::scoped_refptr<webrtc::AudioTrackInterface> _track;
+    void (*_handler)(struct RTCAudioFrame* frame, void* ctx);
+    void* _ctx;
+};
@@ -0,0 +1,206 @@
+#include "socket.h"
+#include "rtc_base/task_utils/to_queued_task.h"
+#include "rtc_base/time_utils.h"
+
+static rtc::SocketAddress from_c(struct RTCSocketAddress* address)
+{
+    return rtc::SocketAddress(address->ip ? address->ip : "", address->port);
+}
+
+/* the ip is borrowed from the string, which must outlive the call the address is passed to. */
+static struct RTCSocketAddress into_c(const rtc::SocketAddress& address, const std::string& ip)
+{
+    struct RTCSocketAddress c_address;
+    c_address.ip = const_cast<char*>(ip.c_str());
+    c_address.port = address.port();
+    return c_address;
+}
+
+ExternalPacketSocket::ExternalPacketSocket(
+    struct RTCPacketSocketFactory factory,
+    rtc::Thread* network_thread
+) : _factory(factory), _network_thread(network_thread)
+{
+}
+
+ExternalPacketSocket::~ExternalPacketSocket()
+{
+    this->Close();
+}
+
+bool ExternalPacketSocket::bind(const rtc::SocketAddress& address, uint16_t min_port, uint16_t max_port)
+{
+    auto ip = address.ipaddr().ToString();
+    auto c_address = into_c(address, ip);
+    struct RTCSocketAddress bound = {};
+    this->_socket = this->_factory.bind(
+        &c_address,
+        min_port,
+        max_port,
+        this,
+        &bound,
+        this->_factory.ctx
+    );
+
+    if (!this->_socket)
+    {
+        return false;
+    }
+
+    this->_local_address = from_c(&bound);
+    return true;
+}
+
+void ExternalPacketSocket::receive(std::vector<uint8_t> data, rtc::SocketAddress from)
+{
+    /* the task is dropped if the socket is destroyed before it runs. */
+    this->_network_thread->PostTask(webrtc::ToQueuedTask(
+        this->_safety.flag(),
+        [this, data = std::move(data), from]()
+        {
+            this->SignalReadPacket(
+                this,
+                reinterpret_cast<const char*>(data.data()),
+                data.size(),
+                from,
+                rtc::TimeMicros()
+            );
+        }));
+}
+
+rtc::SocketAddress ExternalPacketSocket::GetLocalAddress() const
+{
+    return this->_local_address;
+}
+
+rtc::SocketAddress ExternalPacketSocket::GetRemoteAddress() const
+{
+    return rtc::SocketAddress();
+}
+
+int ExternalPacketSocket::Send(const void* data, size_t size, const rtc::PacketOptions& options)
+{
+    /* the socket is not connected, every packet has a destination. */
+    this->_error = ENOTCONN;
+    return -1;
+}
+
+int ExternalPacketSocket::SendTo(
+    const void* data,
+    size_t size,
+    const rtc::SocketAddress& address,
+    const rtc::PacketOptions& options
+)
+{
+    if (!this->_socket)
+    {
+        this->_error = EBADF;
+        return -1;
+    }
+
+    auto ip = address.ipaddr().ToString();
+    auto c_address = into_c(address, ip);
+    if (!this->_factory.send_to(this->_socket, static_cast<const uint8_t*>(data), size, &c_address))
+    {
+        this->_error = EWOULDBLOCK;
+        return -1;
+    }
+
+    /* the congestion control pairs the sent packets with their feedback. */
+    rtc::SentPacket sent(options.packet_id, rtc::TimeMillis(), options.info_signaled_after_sent);
+    this->SignalSentPacket(this, sent);
+    return static_cast<int>(size);
+}
+
+int ExternalPacketSocket::Close()
+{
+    if (this->_socket)
+    {
+        this->_factory.close(this->_socket);
+        this->_socket = NULL;
+    }
+
+    return 0;
+}
+
+rtc::AsyncPacketSocket::State ExternalPacketSocket::GetState() const
+{
+    return this->_socket ? STATE_BOUND : STATE_CLOSED;
+}
+
+int ExternalPacketSocket::GetOption(rtc::Socket::Option option, int* value)
+{
+    return -1;
+}
+
+int ExternalPacketSocket::SetOption(rtc::Socket::Option option, int value)
+{
+    /* the buffers and the DSCP marking belong to the transport of the application. */
+    return 0;
+}
+
+int ExternalPacketSocket::GetError() const
+{
+    return this->_error;
+}
+
+void ExternalPacketSocket::SetError(int error)
+{
+    this->_error = error;
+}
+
+ExternalPacketSocketFactory::ExternalPacketSocketFactory(
+    rtc::SocketFactory* socket_factory,
+    struct RTCPacketSocketFactory* factory
+) : rtc::BasicPacketSocketFactory(socket_factory), _factory(*factory)
+{
+}
+
+rtc::AsyncPacketSocket* ExternalPacketSocketFactory::CreateUdpSocket(
+    const rtc::SocketAddress& address,
+    uint16_t min_port,
+    uint16_t max_port
+)
+{
+    auto socket = std::make_unique<ExternalPacketSocket>(this->_factory, rtc::Thread::Current());
+    if (!socket->bind(address, min_port, max_port))
+    {
+        return nullptr;
+    }
+
+    return socket.release();
+}
+
+rtc::AsyncPacketSocket* ExternalPacketSocketFactory::CreateServerTcpSocket(
+    const rtc::SocketAddress& local_address,
+    uint16_t min_port,
+    uint16_t max_port,
+    int opts
+)
+{
+    return nullptr;
+}
+
+rtc::AsyncPacketSocket* ExternalPacketSocketFactory::CreateClientTcpSocket(
+    const rtc::SocketAddress& local_address,
+    const rtc::SocketAddress& remote_address,
+    const rtc::ProxyInfo& proxy_info,
+    const std::string& user_agent,
+    const rtc::PacketSocketTcpOptions& tcp_options
+)
+{
+    return nullptr;
+}
+
+void rtc_packet_socket_receive(
+    struct RTCPacketSocket* socket,
+    const uint8_t* data,
+    size_t size,
+    struct RTCSocketAddress* from
+)
+{
+    static_cast<ExternalPacketSocket*>(socket)->receive(
+        std::vector<uint8_t>(data, data + size),
+        from_c(from)
+    );
+}
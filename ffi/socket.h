@@ -0,0 +1,78 @@
+#pragma once
+
+#include <vector>
+#include "p2p/base/basic_packet_socket_factory.h"
+#include "rtc_base/async_packet_socket.h"
+#include "rtc_base/task_utils/pending_task_safety_flag.h"
+#include "rtc_base/thread.h"
+#include "ffi.h"
+
+struct RTCPacketSocket {};
+
+/*
+A UDP socket implemented by the application, it is created, used and destroyed on the
+network thread, the received packets are posted to it from any thread.
+*/
+class ExternalPacketSocket: public rtc::AsyncPacketSocket, public RTCPacketSocket
+{
+public:
+    ExternalPacketSocket(struct RTCPacketSocketFactory factory, rtc::Thread* network_thread);
+    ~ExternalPacketSocket() override;
+    bool bind(const rtc::SocketAddress& address, uint16_t min_port, uint16_t max_port);
+    /*
+    Called from any thread.
+    */
+    void receive(std::vector<uint8_t> data, rtc::SocketAddress from);
+    rtc::SocketAddress GetLocalAddress() const override;
+    rtc::SocketAddress GetRemoteAddress() const override;
+    int Send(const void* data, size_t size, const rtc::PacketOptions& options) override;
+    int SendTo(
+        const void* data,
+        size_t size,
+        const rtc::SocketAddress& address,
+        const rtc::PacketOptions& options
+    ) override;
+    int Close() override;
+    State GetState() const override;
+    int GetOption(rtc::Socket::Option option, int* value) override;
+    int SetOption(rtc::Socket::Option option, int value) override;
+    int GetError() const override;
+    void SetError(int error) override;
+private:
+    struct RTCPacketSocketFactory _factory;
+    rtc::Thread* _network_thread;
+    void* _socket = NULL;
+    rtc::SocketAddress _local_address;
+    int _error = 0;
+    webrtc::ScopedTaskSafety _safety;
+};
+
+/*
+Creates the UDP sockets of a connection with the factory of the application, the TCP sockets
+are refused, and the names are still resolved by the system.
+*/
+class ExternalPacketSocketFactory: public rtc::BasicPacketSocketFactory
+{
+public:
+    ExternalPacketSocketFactory(rtc::SocketFactory* socket_factory, struct RTCPacketSocketFactory* factory);
+    rtc::AsyncPacketSocket* CreateUdpSocket(
+        const rtc::SocketAddress& address,
+        uint16_t min_port,
+        uint16_t max_port
+    ) override;
+    rtc::AsyncPacketSocket* CreateServerTcpSocket(
+        const rtc::SocketAddress& local_address,
+        uint16_t min_port,
+        uint16_t max_port,
+        int opts
+    ) override;
+    rtc::AsyncPacketSocket* CreateClientTcpSocket(
+        const rtc::SocketAddress& local_address,
+        const rtc::SocketAddress& remote_address,
+        const rtc::ProxyInfo& proxy_info,
+        const std::string& user_agent,
+        const rtc::PacketSocketTcpOptions& tcp_options
+    ) override;
+private:
+    struct RTCPacketSocketFactory _factory;
+};
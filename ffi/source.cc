@@ -0,0 +1,136 @@
+#include "source.h"
+#include "convert.h"
+#include "media_stream_track.h"
+#include "runtime.h"
+
+#include <algorithm>
+#include "api/video/i420_buffer.h"
+#include "rtc_base/time_utils.h"
+
+void VideoTrackSource::PushFrame(struct RTCVideoFrame* c_frame)
+{
+    auto buffer = webrtc::I420Buffer::Copy(
+        c_frame->width,
+        c_frame->height,
+        c_frame->data_y,
+        c_frame->stride_y,
+        c_frame->data_u,
+        c_frame->stride_u,
+        c_frame->data_v,
+        c_frame->stride_v
+    );
+
+    /* the frames are stamped when written, the encoders pace on the monotonic clock. */
+    OnFrame(webrtc::VideoFrame::Builder()
+        .set_video_frame_buffer(buffer)
+        .set_rotation((webrtc::VideoRotation)c_frame->rotation)
+        .set_timestamp_us(rtc::TimeMicros())
+        .build());
+}
+
+webrtc::MediaSourceInterface::SourceState VideoTrackSource::state() const
+{
+    return webrtc::MediaSourceInterface::kLive;
+}
+
+bool VideoTrackSource::remote() const
+{
+    return false;
+}
+
+bool VideoTrackSource::is_screencast() const
+{
+    return false;
+}
+
+absl::optional<bool> VideoTrackSource::needs_denoising() const
+{
+    return absl::nullopt;
+}
+
+void AudioTrackSource::PushFrame(struct RTCAudioFrame* c_frame)
+{
+    std::lock_guard<std::mutex> guard(_lock);
+    for (auto sink : _sinks)
+    {
+        sink->OnData(
+            c_frame->data,
+            16,
+            c_frame->sample_rate,
+            (size_t)c_frame->channels,
+            (size_t)c_frame->frames
+        );
+    }
+}
+
+webrtc::MediaSourceInterface::SourceState AudioTrackSource::state() const
+{
+    return webrtc::MediaSourceInterface::kLive;
+}
+
+bool AudioTrackSource::remote() const
+{
+    return false;
+}
+
+void AudioTrackSource::AddSink(webrtc::AudioTrackSinkInterface* sink)
+{
+    std::lock_guard<std::mutex> guard(_lock);
+    _sinks.push_back(sink);
+}
+
+void AudioTrackSource::RemoveSink(webrtc::AudioTrackSinkInterface* sink)
+{
+    std::lock_guard<std::mutex> guard(_lock);
+    _sinks.erase(std::remove(_sinks.begin(), _sinks.end(), sink), _sinks.end());
+}
+
+struct MediaStreamTrack* rtc_create_video_track(struct RTCRuntime* runtime, char* id)
+{
+    auto source = rtc::make_ref_counted<VideoTrackSource>();
+    auto track = runtime->factory->CreateVideoTrack(from_c(id), source.get());
+    auto c_track = static_cast<MediaStreamTrackRef*>(into_c(track));
+    c_track->video_source = source;
+    c_track->remote = false;
+    return c_track;
+}
+
+struct MediaStreamTrack* rtc_create_audio_track(struct RTCRuntime* runtime, char* id)
+{
+    auto source = rtc::make_ref_counted<AudioTrackSource>();
+    auto track = runtime->factory->CreateAudioTrack(from_c(id), source.get());
+    auto c_track = static_cast<MediaStreamTrackRef*>(into_c(track));
+    c_track->audio_source = source;
+    c_track->remote = false;
+    return c_track;
+}
+
+bool media_stream_track_write_video_frame(
+    struct MediaStreamTrack* c_track,
+    struct RTCVideoFrame* frame
+)
+{
+    auto track = static_cast<MediaStreamTrackRef*>(c_track);
+    if (!track->video_source)
+    {
+        return false;
+    }
+
+    track->video_source->PushFrame(frame);
+    return true;
+}
+
+bool media_stream_track_write_audio_frame(
+    struct MediaStreamTrack* c_track,
+    struct RTCAudioFrame* frame
+)
+{
+    auto track = static_cast<MediaStreamTrackRef*>(c_track);
+    if (!track->audio_source)
+    {
+        return false;
+    }
+
+    track->audio_source->PushFrame(frame);
+    return true;
+}
@@ -0,0 +1,39 @@
+#pragma once
+
+#include <mutex>
+#include <vector>
+#include "api/media_stream_interface.h"
+#include "api/notifier.h"
+#include "media/base/adapted_video_track_source.h"
+#include "ffi.h"
+
+/*
+The source of a local video track, the frames written by rust are
+delivered to the encoders of every sender of the track.
+*/
+class VideoTrackSource: public rtc::AdaptedVideoTrackSource
+{
+public:
+    void PushFrame(struct RTCVideoFrame* frame);
+    SourceState state() const;
+    bool remote() const;
+    bool is_screencast() const;
+    absl::optional<bool> needs_denoising() const;
+};
+
+/*
+The source of a local audio track, the samples written by rust are
+delivered to the sinks of the senders of the track.
+*/
+class AudioTrackSource: public webrtc::Notifier<webrtc::AudioSourceInterface>
+{
+public:
+    void PushFrame(struct RTCAudioFrame* frame);
+    SourceState state() const;
+    bool remote() const;
+    void AddSink(webrtc::AudioTrackSinkInterface* sink);
+    void RemoveSink(webrtc::AudioTrackSinkInterface* sink);
+private:
+    std::mutex _lock;
+    std::vector<webrtc::AudioTrackSinkInterface*> _sinks;
+};
@@ -0,0 +1,475 @@
+#include <algorithm>
+#include <vector>
+#include "system_wrappers/include/clock.h"
+#include "system_wrappers/include/ntp_time.h"
+#include "transceiver.h"
+#include "convert.h"
+#include "media_stream_track.h"
+#include "runtime.h"
+
+template<typename T> static T* into_array(std::vector<T> values, size_t* size)
+{
+    *size = values.size();
+    auto array = (T*)malloc(sizeof(T) * std::max(values.size(), (size_t)1));
+    std::copy(values.begin(), values.end(), array);
+    return array;
+}
+
+struct RTCRtpTransceiver** rtc_get_transceivers(struct RTCPeerConnection* rtc, size_t* size)
+{
+    std::vector<struct RTCRtpTransceiver*> handles;
+    for (auto transceiver : rtc->peer_connection->GetTransceivers())
+    {
+        handles.push_back(new RTCRtpTransceiver{ transceiver });
+    }
+
+    return into_array(handles, size);
+}
+
+struct RTCRtpSender** rtc_get_senders(struct RTCPeerConnection* rtc, size_t* size)
+{
+    std::vector<struct RTCRtpSender*> handles;
+    for (auto sender : rtc->peer_connection->GetSenders())
+    {
+        handles.push_back(new RTCRtpSender{ sender });
+    }
+
+    return into_array(handles, size);
+}
+
+struct RTCRtpReceiver** rtc_get_receivers(struct RTCPeerConnection* rtc, size_t* size)
+{
+    std::vector<struct RTCRtpReceiver*> handles;
+    for (auto receiver : rtc->peer_connection->GetReceivers())
+    {
+        handles.push_back(new RTCRtpReceiver{ receiver });
+    }
+
+    return into_array(handles, size);
+}
+
+void rtc_free_handles(void* handles)
+{
+    free(handles);
+}
+
+char* rtc_transceiver_get_mid(struct RTCRtpTransceiver* transceiver)
+{
+    auto mid = transceiver->transceiver->mid();
+    return mid ? into_c(mid.value()) : NULL;
+}
+
+enum MEDIA_TYPE rtc_transceiver_get_kind(struct RTCRtpTransceiver* transceiver)
+{
+    return into_c(transceiver->transceiver->media_type());
+}
+
+enum TRANSCEIVER_DIRECTION rtc_transceiver_get_direction(struct RTCRtpTransceiver* transceiver)
+{
+    return into_c(transceiver->transceiver->direction());
+}
+
+enum TRANSCEIVER_DIRECTION rtc_transceiver_get_current_direction(
+    struct RTCRtpTransceiver* transceiver
+)
+{
+    auto direction = transceiver->transceiver->current_direction();
+    return direction ? into_c(direction.value()) : (enum TRANSCEIVER_DIRECTION)0;
+}
+
+struct RTCRtpSender* rtc_transceiver_get_sender(struct RTCRtpTransceiver* transceiver)
+{
+    return new RTCRtpSender{ transceiver->transceiver->sender() };
+}
+
+struct RTCRtpReceiver* rtc_transceiver_get_receiver(struct RTCRtpTransceiver* transceiver)
+{
+    return new RTCRtpReceiver{ transceiver->transceiver->receiver() };
+}
+
+static bool into_c(const webrtc::RTCError& result, struct RTCError* error)
+{
+    if (result.ok())
+    {
+        return true;
+    }
+
+    error->code = (int)result.type();
+    error->message = into_c(std::string(result.message()));
+    return false;
+}
+
+struct RTCRtpTransceiver* rtc_add_transceiver(
+    struct RTCPeerConnection* rtc,
+    enum MEDIA_TYPE kind,
+    enum TRANSCEIVER_DIRECTION direction,
+    struct RTCError* error
+)
+{
+    webrtc::RtpTransceiverInit init;
+    init.direction = from_c(direction);
+
+    auto result = rtc->peer_connection->AddTransceiver(from_c(kind), init);
+    if (!into_c(result.error(), error))
+    {
+        return NULL;
+    }
+
+    return new RTCRtpTransceiver{ result.MoveValue() };
+}
+
+bool rtc_transceiver_set_direction(
+    struct RTCRtpTransceiver* transceiver,
+    enum TRANSCEIVER_DIRECTION direction,
+    struct RTCError* error
+)
+{
+    return into_c(transceiver->transceiver->SetDirectionWithError(from_c(direction)), error);
+}
+
+bool rtc_transceiver_stop(struct RTCRtpTransceiver* transceiver, struct RTCError* error)
+{
+    return into_c(transceiver->transceiver->StopStandard(), error);
+}
+
+bool rtc_transceiver_enable_header_extension(
+    struct RTCRtpTransceiver* transceiver,
+    const char* uri,
+    struct RTCError* error
+)
+{
+    auto extensions = transceiver->transceiver->GetHeaderExtensionsToNegotiate();
+    auto extension = std::find_if(
+        extensions.begin(),
+        extensions.end(),
+        [&](const webrtc::RtpHeaderExtensionCapability& extension) { return extension.uri == uri; }
+    );
+
+    if (extension == extensions.end())
+    {
+        return into_c(
+            webrtc::RTCError(
+                webrtc::RTCErrorType::UNSUPPORTED_PARAMETER,
+                "the header extension is not supported"
+            ),
+            error
+        );
+    }
+
+    extension->direction = webrtc::RtpTransceiverDirection::kSendRecv;
+    return into_c(transceiver->transceiver->SetHeaderExtensionsToNegotiate(extensions), error);
+}
+
+void rtc_free_transceiver(struct RTCRtpTransceiver* transceiver)
+{
+    delete transceiver;
+}
+
+char* rtc_sender_get_id(struct RTCRtpSender* sender)
+{
+    return into_c(sender->sender->id());
+}
+
+enum MEDIA_TYPE rtc_sender_get_kind(struct RTCRtpSender* sender)
+{
+    return into_c(sender->sender->media_type());
+}
+
+struct MediaStreamTrack* rtc_sender_get_track(struct RTCRtpSender* sender)
+{
+    auto track = sender->sender->track();
+    return track ? into_c(track) : NULL;
+}
+
+uint32_t* rtc_sender_get_ssrcs(struct RTCRtpSender* sender, size_t* size)
+{
+    std::vector<uint32_t> ssrcs;
+    for (auto& encoding : sender->sender->GetParameters().encodings)
+    {
+        if (encoding.ssrc)
+        {
+            ssrcs.push_back(encoding.ssrc.value());
+        }
+    }
+
+    return into_array(ssrcs, size);
+}
+
+/*
+The shares of the bandwidth of the priorities, relative to the low priority,
+as recommended by the webrtc priority control specification.
+*/
+static double bitrate_priority(webrtc::Priority priority)
+{
+    switch (priority)
+    {
+    case webrtc::Priority::kVeryLow:
+        return 0.5;
+    case webrtc::Priority::kMedium:
+        return 2.0;
+    case webrtc::Priority::kHigh:
+        return 4.0;
+    default:
+        return 1.0;
+    }
+}
+
+enum PRIORITY rtc_sender_get_priority(struct RTCRtpSender* sender)
+{
+    auto encodings = sender->sender->GetParameters().encodings;
+    return into_c(encodings.empty() ? webrtc::Priority::kLow : encodings[0].network_priority);
+}
+
+bool rtc_sender_set_priority(
+    struct RTCRtpSender* sender,
+    enum PRIORITY priority,
+    struct RTCError* error
+)
+{
+    auto parameters = sender->sender->GetParameters();
+    for (auto& encoding : parameters.encodings)
+    {
+        encoding.network_priority = from_c(priority);
+        encoding.bitrate_priority = bitrate_priority(from_c(priority));
+    }
+
+    return into_c(sender->sender->SetParameters(parameters), error);
+}
+
+bool rtc_sender_get_degradation_preference(
+    struct RTCRtpSender* sender,
+    enum DEGRADATION_PREFERENCE* preference
+)
+{
+    auto parameters = sender->sender->GetParameters();
+    if (!parameters.degradation_preference)
+    {
+        return false;
+    }
+
+    *preference = into_c(parameters.degradation_preference.value());
+    return true;
+}
+
+bool rtc_sender_set_degradation_preference(
+    struct RTCRtpSender* sender,
+    enum DEGRADATION_PREFERENCE preference,
+    struct RTCError* error
+)
+{
+    auto parameters = sender->sender->GetParameters();
+    parameters.degradation_preference = from_c(preference);
+    return into_c(sender->sender->SetParameters(parameters), error);
+}
+
+void rtc_sender_get_encoding_parameters(
+    struct RTCRtpSender* sender,
+    struct RTCEncodingParameters* parameters
+)
+{
+    auto encodings = sender->sender->GetParameters().encodings;
+    if (encodings.empty())
+    {
+        return;
+    }
+
+    auto& encoding = encodings[0];
+    parameters->max_bitrate_bps = encoding.max_bitrate_bps.value_or(0);
+    parameters->max_framerate = encoding.max_framerate.value_or(0);
+    parameters->scale_resolution_down_by = encoding.scale_resolution_down_by.value_or(0);
+}
+
+bool rtc_sender_set_encoding_parameters(
+    struct RTCRtpSender* sender,
+    struct RTCEncodingParameters* c_parameters,
+    struct RTCError* error
+)
+{
+    auto parameters = sender->sender->GetParameters();
+    for (auto& encoding : parameters.encodings)
+    {
+        if (c_parameters->max_bitrate_bps > 0)
+        {
+            encoding.max_bitrate_bps = c_parameters->max_bitrate_bps;
+        }
+
+        if (c_parameters->max_framerate > 0)
+        {
+            encoding.max_framerate = c_parameters->max_framerate;
+        }
+
+        if (c_parameters->scale_resolution_down_by > 0)
+        {
+            encoding.scale_resolution_down_by = c_parameters->scale_resolution_down_by;
+        }
+    }
+
+    return into_c(sender->sender->SetParameters(parameters), error);
+}
+
+char* rtc_sender_get_sync_group(struct RTCRtpSender* sender)
+{
+    auto stream_ids = sender->sender->stream_ids();
+    return stream_ids.empty() ? nullptr : into_c(stream_ids[0]);
+}
+
+void rtc_sender_set_streams(struct RTCRtpSender* sender, char** stream_ids, int stream_ids_size)
+{
+    sender->sender->SetStreams(std::vector<std::string>(stream_ids, stream_ids + stream_ids_size));
+}
+
+void rtc_free_sender(struct RTCRtpSender* sender)
+{
+    delete sender;
+}
+
+char* rtc_receiver_get_id(struct RTCRtpReceiver* receiver)
+{
+    return into_c(receiver->receiver->id());
+}
+
+enum MEDIA_TYPE rtc_receiver_get_kind(struct RTCRtpReceiver* receiver)
+{
+    return into_c(receiver->receiver->media_type());
+}
+
+struct MediaStreamTrack* rtc_receiver_get_track(struct RTCRtpReceiver* receiver)
+{
+    return into_c(receiver->receiver->track());
+}
+
+char* rtc_receiver_get_sync_group(struct RTCRtpReceiver* receiver)
+{
+    auto stream_ids = receiver->receiver->stream_ids();
+    return stream_ids.empty() ? nullptr : into_c(stream_ids[0]);
+}
+
+uint32_t* rtc_receiver_get_ssrcs(struct RTCRtpReceiver* receiver, size_t* size)
+{
+    std::vector<uint32_t> ssrcs;
+    for (auto& encoding : receiver->receiver->GetParameters().encodings)
+    {
+        if (encoding.ssrc)
+        {
+            ssrcs.push_back(encoding.ssrc.value());
+        }
+    }
+
+    /* the ssrcs of an unsignaled stream are only known from its packets. */
+    for (auto& source : receiver->receiver->GetSources())
+    {
+        if (source.source_type() == webrtc::RtpSourceType::SSRC &&
+            std::find(ssrcs.begin(), ssrcs.end(), source.source_id()) == ssrcs.end())
+        {
+            ssrcs.push_back(source.source_id());
+        }
+    }
+
+    return into_array(ssrcs, size);
+}
+
+int64_t rtc_receiver_get_capture_delay(struct RTCRtpReceiver* receiver, uint32_t ssrc)
+{
+    webrtc::Clock* clock = webrtc::Clock::GetRealTimeClock();
+    for (auto& source : receiver->receiver->GetSources())
+    {
+        auto capture_time = source.absolute_capture_time();
+        auto offset = source.local_capture_clock_offset();
+        if (source.source_type() != webrtc::RtpSourceType::SSRC ||
+            source.source_id() != ssrc ||
+            !capture_time ||
+            !offset)
+        {
+            continue;
+        }
+
+        /* the capture time is on the clock of the capturer, the offset brings it to the local clock. */
+        int64_t captured_ms = webrtc::NtpTime(capture_time->absolute_capture_timestamp).ToMs() + offset->ms();
+        int64_t received_ms = clock->CurrentNtpInMilliseconds() - (clock->TimeInMilliseconds() - source.timestamp().ms());
+        return std::max<int64_t>(received_ms - captured_ms, 0);
+    }
+
+    return -1;
+}
+
+void rtc_free_receiver(struct RTCRtpReceiver* receiver)
+{
+    delete receiver;
+}
+
+void rtc_free_string(char* string)
+{
+    free(string);
+}
+
+void rtc_free_ssrcs(uint32_t* ssrcs)
+{
+    free(ssrcs);
+}
+
+static struct RTCRtpCapabilities* into_c(const webrtc::RtpCapabilities& capabilities)
+{
+    auto c_capabilities = new RTCRtpCapabilities();
+    c_capabilities->codecs_size = capabilities.codecs.size();
+    c_capabilities->codecs = new RTCRtpCodecCapability[capabilities.codecs.size()];
+    for (size_t i = 0; i < capabilities.codecs.size(); i++)
+    {
+        auto& codec = capabilities.codecs[i];
+        std::string fmtp;
+        for (auto& parameter : codec.parameters)
+        {
+            fmtp += (fmtp.empty() ? "" : ";") + parameter.first + "=" + parameter.second;
+        }
+
+        c_capabilities->codecs[i].mime_type = into_c(codec.mime_type());
+        c_capabilities->codecs[i].clock_rate = codec.clock_rate.value_or(0);
+        c_capabilities->codecs[i].channels = codec.num_channels.value_or(0);
+        c_capabilities->codecs[i].sdp_fmtp_line = fmtp.empty() ? NULL : into_c(fmtp);
+    }
+
+    c_capabilities->header_extensions_size = capabilities.header_extensions.size();
+    c_capabilities->header_extensions =
+        new RTCRtpHeaderExtensionCapability[capabilities.header_extensions.size()];
+    for (size_t i = 0; i < capabilities.header_extensions.size(); i++)
+    {
+        auto& extension = capabilities.header_extensions[i];
+        c_capabilities->header_extensions[i].uri = into_c(extension.uri);
+        c_capabilities->header_extensions[i].preferred_id = extension.preferred_id.value_or(0);
+    }
+
+    return c_capabilities;
+}
+
+struct RTCRtpCapabilities* rtc_get_rtp_sender_capabilities(
+    struct RTCRuntime* runtime,
+    enum MEDIA_TYPE kind
+)
+{
+    return into_c(runtime->factory->GetRtpSenderCapabilities(from_c(kind)));
+}
+
+struct RTCRtpCapabilities* rtc_get_rtp_receiver_capabilities(
+    struct RTCRuntime* runtime,
+    enum MEDIA_TYPE kind
+)
+{
+    return into_c(runtime->factory->GetRtpReceiverCapabilities(from_c(kind)));
+}
+
+void rtc_free_rtp_capabilities(struct RTCRtpCapabilities* capabilities)
+{
+    for (size_t i = 0; i < capabilities->codecs_size; i++)
+    {
+        free(capabilities->codecs[i].mime_type);
+        free(capabilities->codecs[i].sdp_fmtp_line);
+    }
+
+    for (size_t i = 0; i < capabilities->header_extensions_size; i++)
+    {
+        free(capabilities->header_extensions[i].uri);
+    }
+
+    delete[] capabilities->codecs;
+    delete[] capabilities->header_extensions;
+    delete capabilities;
+}
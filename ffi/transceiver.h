@@ -0,0 +1,20 @@
+#pragma once
+
+#include "api/rtp_transceiver_interface.h"
+#include "ffi.h"
+
+/*
+The handles of the transceivers, senders and receivers handed over to rust,
+they keep the native objects alive until they are freed.
+*/
+struct RTCRtpTransceiver {
+    rtc::scoped_refptr<webrtc::RtpTransceiverInterface> transceiver;
+};
+
+struct RTCRtpSender {
+    rtc::scoped_refptr<webrtc::RtpSenderInterface> sender;
+};
+
+struct RTCRtpReceiver {
+    rtc::scoped_refptr<webrtc::RtpReceiverInterface> receiver;
+};
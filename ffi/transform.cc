@@ -0,0 +1,171 @@
+#include "transform.h"
+
+static enum FRAME_CODEC frame_codec(enum MEDIA_TYPE kind, webrtc::TransformableFrameInterface* frame)
+{
+    if (kind == MEDIA_TYPE_AUDIO)
+    {
+        return FRAME_CODEC_GENERIC;
+    }
+
+    auto metadata = static_cast<webrtc::TransformableVideoFrameInterface*>(frame)->GetMetadata();
+    switch (metadata.GetCodec())
+    {
+    case webrtc::kVideoCodecGeneric:
+        return FRAME_CODEC_GENERIC;
+    case webrtc::kVideoCodecVP8:
+        return FRAME_CODEC_VP8;
+    case webrtc::kVideoCodecVP9:
+        return FRAME_CODEC_VP9;
+    case webrtc::kVideoCodecAV1:
+        return FRAME_CODEC_AV1;
+    case webrtc::kVideoCodecH264:
+        return FRAME_CODEC_H264;
+    default:
+        return FRAME_CODEC_OTHER;
+    }
+}
+
+FrameTransformer::FrameTransformer(
+    enum MEDIA_TYPE kind,
+    enum RTP_DIRECTION direction,
+    void* ctx,
+    bool (*handler)(struct RTCTransformableFrame* frame, void* ctx)
+)
+{
+    _kind = kind;
+    _direction = direction;
+    _handler = handler;
+    _ctx = ctx;
+}
+
+rtc::scoped_refptr<webrtc::TransformedFrameCallback> FrameTransformer::Callback(uint32_t ssrc)
+{
+    std::lock_guard<std::mutex> guard(_lock);
+    auto iter = _sink_callbacks.find(ssrc);
+    return iter != _sink_callbacks.end() ? iter->second : _callback;
+}
+
+void FrameTransformer::Transform(std::unique_ptr<webrtc::TransformableFrameInterface> frame)
+{
+    auto callback = Callback(frame->GetSsrc());
+    if (!callback)
+    {
+        return;
+    }
+
+    {
+        std::lock_guard<std::mutex> guard(_lock);
+        if (_handler)
+        {
+            auto data = frame->GetData();
+
+            struct RTCTransformableFrame c_frame;
+            c_frame.kind = _kind;
+            c_frame.direction = _direction;
+            c_frame.ssrc = frame->GetSsrc();
+            c_frame.payload_type = frame->GetPayloadType();
+            c_frame.timestamp = frame->GetTimestamp();
+            c_frame.keyframe = _kind == MEDIA_TYPE_VIDEO
+                && static_cast<webrtc::TransformableVideoFrameInterface*>(frame.get())->IsKeyFrame();
+            c_frame.codec = frame_codec(_kind, frame.get());
+            c_frame.data = data.data();
+            c_frame.size = data.size();
+            c_frame.frame = frame.get();
+
+            /* a frame rejected by the handler is dropped, such as a frame which can not be decrypted. */
+            if (!_handler(&c_frame, _ctx))
+            {
+                return;
+            }
+        }
+    }
+
+    callback->OnTransformedFrame(std::move(frame));
+}
+
+void FrameTransformer::RegisterTransformedFrameCallback(
+    rtc::scoped_refptr<webrtc::TransformedFrameCallback> callback
+)
+{
+    std::lock_guard<std::mutex> guard(_lock);
+    _callback = callback;
+}
+
+void FrameTransformer::RegisterTransformedFrameSinkCallback(
+    rtc::scoped_refptr<webrtc::TransformedFrameCallback> callback,
+    uint32_t ssrc
+)
+{
+    std::lock_guard<std::mutex> guard(_lock);
+    _sink_callbacks[ssrc] = callback;
+}
+
+void FrameTransformer::UnregisterTransformedFrameCallback()
+{
+    std::lock_guard<std::mutex> guard(_lock);
+    _callback = nullptr;
+}
+
+void FrameTransformer::UnregisterTransformedFrameSinkCallback(uint32_t ssrc)
+{
+    std::lock_guard<std::mutex> guard(_lock);
+    _sink_callbacks.erase(ssrc);
+}
+
+void FrameTransformer::RemoveHandler()
+{
+    std::lock_guard<std::mutex> guard(_lock);
+    _handler = NULL;
+}
+
+void rtc_transformable_frame_set_data(
+    struct RTCTransformableFrame* frame,
+    const uint8_t* data,
+    size_t size
+)
+{
+    auto native = static_cast<webrtc::TransformableFrameInterface*>(frame->frame);
+    native->SetData(rtc::ArrayView<const uint8_t>(data, size));
+}
+
+struct RTCFrameTransform* rtc_add_frame_transform(
+    struct RTCPeerConnection* rtc,
+    char* mid,
+    enum RTP_DIRECTION direction,
+    void* ctx,
+    bool (*handler)(struct RTCTransformableFrame* frame, void* ctx)
+)
+{
+    for (auto transceiver : rtc->peer_connection->GetTransceivers())
+    {
+        if (transceiver->mid() != std::string(mid))
+        {
+            continue;
+        }
+
+        auto kind = transceiver->media_type() == cricket::MediaType::MEDIA_TYPE_AUDIO
+            ? MEDIA_TYPE_AUDIO
+            : MEDIA_TYPE_VIDEO;
+        auto transformer = rtc::make_ref_counted<FrameTransformer>(kind, direction, ctx, handler);
+        if (direction == RTP_DIRECTION_OUTGOING)
+        {
+            transceiver->sender()->SetEncoderToPacketizerFrameTransformer(transformer);
+        }
+        else
+        {
+            transceiver->receiver()->SetDepacketizerToDecoderFrameTransformer(transformer);
+        }
+
+        auto transform = new RTCFrameTransform();
+        transform->transformer = transformer;
+        return transform;
+    }
+
+    return NULL;
+}
+
+void rtc_remove_frame_transform(struct RTCFrameTransform* transform)
+{
+    transform->transformer->RemoveHandler();
+    delete transform;
+}
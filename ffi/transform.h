@@ -0,0 +1,49 @@
+#pragma once
+
+#include <map>
+#include <mutex>
+#include "api/frame_transformer_interface.h"
+#include "ffi.h"
+
+/*
+A transform of the encoded frames of a sender or a receiver, the handler rewrites the payload
+of every frame on the thread delivering it. Once the handler is removed the frames pass through
+unchanged, the transformer itself is kept by the sender or the receiver.
+*/
+class FrameTransformer: public webrtc::FrameTransformerInterface
+{
+public:
+    FrameTransformer(
+        enum MEDIA_TYPE kind,
+        enum RTP_DIRECTION direction,
+        void* ctx,
+        bool (*handler)(struct RTCTransformableFrame* frame, void* ctx)
+    );
+
+    void Transform(std::unique_ptr<webrtc::TransformableFrameInterface> frame);
+    void RegisterTransformedFrameCallback(rtc::scoped_refptr<webrtc::TransformedFrameCallback> callback);
+    void RegisterTransformedFrameSinkCallback(
+        rtc::scoped_refptr<webrtc::TransformedFrameCallback> callback,
+        uint32_t ssrc
+    );
+    void UnregisterTransformedFrameCallback();
+    void UnregisterTransformedFrameSinkCallback(uint32_t ssrc);
+    void RemoveHandler();
+private:
+    rtc::scoped_refptr<webrtc::TransformedFrameCallback> Callback(uint32_t ssrc);
+
+    enum MEDIA_TYPE _kind;
+    enum RTP_DIRECTION _direction;
+    std::mutex _lock;
+    rtc::scoped_refptr<webrtc::TransformedFrameCallback> _callback;
+    std::map<uint32_t, rtc::scoped_refptr<webrtc::TransformedFrameCallback>> _sink_callbacks;
+    bool (*_handler)(struct RTCTransformableFrame* frame, void* ctx);
+    void* _ctx;
+};
+
+/*
+The transformer handed over to rust, it keeps a reference until the handler is removed.
+*/
+struct RTCFrameTransform {
+    rtc::scoped_refptr<FrameTransformer> transformer;
+};
@@ -0,0 +1,162 @@
+#include "transport.h"
+#include "runtime.h"
+#include "pc/dtls_transport.h"
+
+static cricket::DtlsTransportInternal* get_internal(struct RTCTransport* c_transport)
+{
+    /* the interface of a connection is always implemented by webrtc::DtlsTransport. */
+    return static_cast<webrtc::DtlsTransport*>(c_transport->transport.get())->internal();
+}
+
+static enum DTLS_TRANSPORT_STATE into_c(cricket::DtlsTransportState state)
+{
+    switch (state)
+    {
+    case cricket::DTLS_TRANSPORT_CONNECTING:
+        return DTLS_TRANSPORT_STATE_CONNECTING;
+    case cricket::DTLS_TRANSPORT_CONNECTED:
+        return DTLS_TRANSPORT_STATE_CONNECTED;
+    case cricket::DTLS_TRANSPORT_CLOSED:
+        return DTLS_TRANSPORT_STATE_CLOSED;
+    case cricket::DTLS_TRANSPORT_FAILED:
+        return DTLS_TRANSPORT_STATE_FAILED;
+    default:
+        return DTLS_TRANSPORT_STATE_NEW;
+    }
+}
+
+static enum ICE_TRANSPORT_STATE into_c(webrtc::IceTransportState state)
+{
+    switch (state)
+    {
+    case webrtc::IceTransportState::kChecking:
+        return ICE_TRANSPORT_STATE_CHECKING;
+    case webrtc::IceTransportState::kConnected:
+        return ICE_TRANSPORT_STATE_CONNECTED;
+    case webrtc::IceTransportState::kCompleted:
+        return ICE_TRANSPORT_STATE_COMPLETED;
+    case webrtc::IceTransportState::kDisconnected:
+        return ICE_TRANSPORT_STATE_DISCONNECTED;
+    case webrtc::IceTransportState::kFailed:
+        return ICE_TRANSPORT_STATE_FAILED;
+    case webrtc::IceTransportState::kClosed:
+        return ICE_TRANSPORT_STATE_CLOSED;
+    default:
+        return ICE_TRANSPORT_STATE_NEW;
+    }
+}
+
+TransportObserver::TransportObserver(
+    cricket::DtlsTransportInternal* transport,
+    void* ctx,
+    void (*on_dtls_state_change)(enum DTLS_TRANSPORT_STATE state, void* ctx),
+    void (*on_ice_state_change)(enum ICE_TRANSPORT_STATE state, void* ctx),
+    void (*on_error)(enum TRANSPORT_ERROR error, void* ctx)
+)
+{
+    _ctx = ctx;
+    _on_dtls_state_change = on_dtls_state_change;
+    _on_ice_state_change = on_ice_state_change;
+    _on_error = on_error;
+
+    /* the slots are disconnected when the observer is destroyed. */
+    transport->SignalDtlsState.connect(this, &TransportObserver::on_dtls_state);
+    transport->SignalDtlsHandshakeError.connect(this, &TransportObserver::on_handshake_error);
+    transport->ice_transport()->SignalIceTransportStateChanged.connect(this, &TransportObserver::on_ice_state);
+}
+
+void TransportObserver::on_dtls_state(cricket::DtlsTransportInternal* transport, cricket::DtlsTransportState state)
+{
+    if (state == cricket::DTLS_TRANSPORT_FAILED && !_handshake_failed)
+    {
+        _on_error(TRANSPORT_ERROR_DTLS_FAILED, _ctx);
+    }
+
+    _on_dtls_state_change(into_c(state), _ctx);
+}
+
+void TransportObserver::on_ice_state(cricket::IceTransportInternal* transport)
+{
+    _on_ice_state_change(into_c(transport->GetIceTransportState()), _ctx);
+}
+
+void TransportObserver::on_handshake_error(rtc::SSLHandshakeError error)
+{
+    _handshake_failed = true;
+    _on_error(
+        error == rtc::SSLHandshakeError::INCOMPATIBLE_CIPHERSUITE
+            ? TRANSPORT_ERROR_INCOMPATIBLE_CIPHER_SUITE
+            : TRANSPORT_ERROR_HANDSHAKE_FAILED,
+        _ctx
+    );
+}
+
+struct RTCTransport* rtc_get_transport(struct RTCPeerConnection* rtc, char* mid)
+{
+    auto transport = rtc->peer_connection->LookupDtlsTransportByMid(std::string(mid));
+    if (!transport)
+    {
+        return nullptr;
+    }
+
+    auto c_transport = new RTCTransport();
+    c_transport->transport = transport;
+    c_transport->network_thread = rtc->runtime->network_thread.get();
+    return c_transport;
+}
+
+void rtc_free_transport(struct RTCTransport* transport)
+{
+    delete transport;
+}
+
+enum DTLS_TRANSPORT_STATE rtc_get_dtls_transport_state(struct RTCTransport* transport)
+{
+    return transport->network_thread->Invoke<enum DTLS_TRANSPORT_STATE>(RTC_FROM_HERE, [&]()
+    {
+        return into_c(get_internal(transport)->dtls_state());
+    });
+}
+
+enum ICE_TRANSPORT_STATE rtc_get_ice_transport_state(struct RTCTransport* transport)
+{
+    return transport->network_thread->Invoke<enum ICE_TRANSPORT_STATE>(RTC_FROM_HERE, [&]()
+    {
+        return into_c(get_internal(transport)->ice_transport()->GetIceTransportState());
+    });
+}
+
+struct RTCTransportObserver* rtc_transport_add_observer(
+    struct RTCTransport* transport,
+    void* ctx,
+    void (*on_dtls_state_change)(enum DTLS_TRANSPORT_STATE state, void* ctx),
+    void (*on_ice_state_change)(enum ICE_TRANSPORT_STATE state, void* ctx),
+    void (*on_error)(enum TRANSPORT_ERROR error, void* ctx)
+)
+{
+    auto observer = new RTCTransportObserver();
+    observer->network_thread = transport->network_thread;
+    transport->network_thread->Invoke<void>(RTC_FROM_HERE, [&]()
+    {
+        observer->observer = std::make_unique<TransportObserver>(
+            get_internal(transport),
+            ctx,
+            on_dtls_state_change,
+            on_ice_state_change,
+            on_error
+        );
+    });
+
+    return observer;
+}
+
+void rtc_transport_remove_observer(struct RTCTransportObserver* observer)
+{
+    /* the slots are disconnected on the network thread, no handler is called once this returns. */
+    observer->network_thread->Invoke<void>(RTC_FROM_HERE, [&]()
+    {
+        observer->observer = nullptr;
+    });
+
+    delete observer;
+}
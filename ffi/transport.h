@@ -0,0 +1,50 @@
+#pragma once
+
+#include <memory>
+#include "api/dtls_transport_interface.h"
+#include "p2p/base/dtls_transport_internal.h"
+#include "p2p/base/ice_transport_internal.h"
+#include "rtc_base/ssl_stream_adapter.h"
+#include "rtc_base/third_party/sigslot/sigslot.h"
+#include "rtc_base/thread.h"
+#include "ffi.h"
+
+/*
+Forwards the state changes and the errors of a DTLS transport and of its ICE transport
+to the handlers registered by rust, it is created and destroyed on the network thread,
+which fires the signals.
+*/
+class TransportObserver: public sigslot::has_slots<>
+{
+public:
+    TransportObserver(
+        cricket::DtlsTransportInternal* transport,
+        void* ctx,
+        void (*on_dtls_state_change)(enum DTLS_TRANSPORT_STATE state, void* ctx),
+        void (*on_ice_state_change)(enum ICE_TRANSPORT_STATE state, void* ctx),
+        void (*on_error)(enum TRANSPORT_ERROR error, void* ctx)
+    );
+private:
+    void on_dtls_state(cricket::DtlsTransportInternal* transport, cricket::DtlsTransportState state);
+    void on_ice_state(cricket::IceTransportInternal* transport);
+    void on_handshake_error(rtc::SSLHandshakeError error);
+    void* _ctx;
+    void (*_on_dtls_state_change)(enum DTLS_TRANSPORT_STATE state, void* ctx);
+    void (*_on_ice_state_change)(enum ICE_TRANSPORT_STATE state, void* ctx);
+    void (*_on_error)(enum TRANSPORT_ERROR error, void* ctx);
+    // a failure following a handshake error is already reported by the handshake error.
+    bool _handshake_failed = false;
+};
+
+/*
+The DTLS transport of a media id handed over to rust, it keeps the native transport alive.
+*/
+struct RTCTransport {
+    rtc::scoped_refptr<webrtc::DtlsTransportInterface> transport;
+    rtc::Thread* network_thread;
+};
+
+struct RTCTransportObserver {
+    std::unique_ptr<TransportObserver> observer;
+    rtc::Thread* network_thread;
+};
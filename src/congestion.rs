@@ -0,0 +1,109 @@
+use crate::field_trials::FieldTrials;
+use crate::BitrateSettings;
+
+use std::time::Duration;
+
+/// The behavior of the loss based bandwidth estimation, which lowers the
+/// estimate when packets are lost rather than only when the delay grows.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LossBasedBwe {
+    /// The default of the libwebrtc build.
+    #[default]
+    Default,
+    /// The second version of the estimator, which tells random losses apart from
+    /// congestion, so it backs off less on lossy wireless links.
+    Enabled,
+    /// Only the delay based estimation is used.
+    Disabled,
+}
+
+/// The tuning of the congestion controller and the pacer of every connection of a runtime.
+///
+/// The defaults of libwebrtc favor smoothness, a low latency deployment usually paces
+/// closer to the estimate and limits the queue of the pacer, see `low_latency`. The
+/// knobs are field trials, so they are experimental and may change between builds.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CongestionControlConfig {
+    /// The lower bound of the estimate in bits per second, the pacer never paces below it.
+    pub min_pacing_rate: Option<u32>,
+    /// The upper bound of the estimate in bits per second, the pacer never paces beyond it.
+    pub max_pacing_rate: Option<u32>,
+    /// The pacing rate relative to the estimate, 2.5 by default, a lower factor sends
+    /// smoother bursts at the cost of a longer queue after a keyframe.
+    pub pacing_factor: Option<f64>,
+    /// The longest time a packet waits in the queue of the pacer, the pacer sends faster
+    /// than the pacing rate to drain a longer queue.
+    pub max_pacing_delay: Option<Duration>,
+    /// Whether the estimate is probed with padding at the start of a call and when
+    /// the bitrate of the senders grows, `None` keeps the default of the build.
+    pub probing: Option<bool>,
+    pub loss_based_bwe: LossBasedBwe,
+}
+
+impl CongestionControlConfig {
+    /// Paces close to the estimate with a short queue, and backs off on congestion
+    /// rather than random losses, for interactive streams.
+    pub fn low_latency() -> Self {
+        Self {
+            pacing_factor: Some(1.1),
+            max_pacing_delay: Some(Duration::from_millis(100)),
+            loss_based_bwe: LossBasedBwe::Enabled,
+            ..Self::default()
+        }
+    }
+
+    /// Never probes the estimate with padding, for metered links where the probes cost
+    /// data, the estimate then only grows with the media which is sent.
+    pub fn metered() -> Self {
+        Self {
+            probing: Some(false),
+            ..Self::default()
+        }
+    }
+
+    /// The field trials of the knobs.
+    pub(crate) fn field_trials(&self) -> FieldTrials {
+        let mut trials = FieldTrials::new();
+        let mut pacing = Vec::new();
+        if let Some(factor) = self.pacing_factor {
+            pacing.push(("factor", factor.to_string()));
+        }
+
+        if let Some(delay) = self.max_pacing_delay {
+            pacing.push(("max_delay", format!("{}ms", delay.as_millis())));
+        }
+
+        if !pacing.is_empty() {
+            let pacing = pacing.iter().map(|(k, v)| (*k, v.as_str())).collect::<Vec<_>>();
+            trials = trials.parameters("WebRTC-Video-Pacing", &pacing);
+        }
+
+        // the scales of the initial and allocation probes, a zero scale skips the probe.
+        if self.probing == Some(false) {
+            trials = trials.parameters(
+                "WebRTC-Bwe-ProbingConfiguration",
+                &[("p1", "0"), ("p2", "0"), ("alloc_p1", "0"), ("alloc_p2", "0")],
+            );
+        }
+
+        match self.loss_based_bwe {
+            LossBasedBwe::Default => trials,
+            LossBasedBwe::Enabled => trials.parameters("WebRTC-Bwe-LossBasedBweV2", &[("Enabled", "true")]),
+            LossBasedBwe::Disabled => trials.parameters("WebRTC-Bwe-LossBasedBweV2", &[("Enabled", "false")]),
+        }
+    }
+
+    /// The bounds applied to every connection when it is created, the pacing rate
+    /// follows the estimate, so bounding the estimate bounds the pacer.
+    pub(crate) fn bitrate_settings(&self) -> Option<BitrateSettings> {
+        if self.min_pacing_rate.is_none() && self.max_pacing_rate.is_none() {
+            return None;
+        }
+
+        Some(BitrateSettings {
+            min_bitrate: self.min_pacing_rate,
+            start_bitrate: None,
+            max_bitrate: self.max_pacing_rate,
+        })
+    }
+}
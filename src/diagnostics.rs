@@ -0,0 +1,159 @@
+use crate::{RTCPeerConnection, RTCSessionDescription, Result};
+
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The number of events kept in the timeline of a connection.
+const TIMELINE_CAPACITY: usize = 1000;
+
+/// The number of stats reports kept in the history of a connection.
+const STATS_HISTORY_CAPACITY: usize = 60;
+
+/// Milliseconds since the unix epoch, the unit of the timestamps of the stats.
+fn now() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs_f64() * 1000.0)
+        .unwrap_or(0.0)
+}
+
+/// The latest entries recorded on a connection, the oldest ones are dropped.
+pub(crate) struct History<T> {
+    entries: Mutex<VecDeque<(f64, T)>>,
+    capacity: usize,
+}
+
+impl<T: Clone> History<T> {
+    fn new(capacity: usize) -> Self {
+        Self {
+            entries: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+        }
+    }
+
+    pub fn timeline() -> Self {
+        Self::new(TIMELINE_CAPACITY)
+    }
+
+    pub fn stats() -> Self {
+        Self::new(STATS_HISTORY_CAPACITY)
+    }
+
+    pub fn record(&self, value: T) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() == self.capacity {
+            entries.pop_front();
+        }
+
+        entries.push_back((now(), value));
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    fn entries(&self) -> Vec<(f64, T)> {
+        self.entries.lock().unwrap().iter().cloned().collect()
+    }
+}
+
+/// An event of the timeline, the name of the event and its value, such as
+/// "connectionstatechange" and "Connected".
+pub(crate) type TimelineEvent = (&'static str, String);
+
+fn description(desc: Option<RTCSessionDescription>) -> Value {
+    match desc {
+        None => Value::Null,
+        Some(desc) => json!({
+            "type": format!("{:?}", desc.get_type()),
+            "sdp": desc.get_sdp().unwrap_or_default(),
+        }),
+    }
+}
+
+/// The candidates of a description, which holds every candidate of the remote
+/// peer when they are not trickled.
+fn candidates(desc: &Value) -> Vec<&str> {
+    desc["sdp"]
+        .as_str()
+        .map(|sdp| {
+            sdp.lines()
+                .filter_map(|line| line.strip_prefix("a="))
+                .filter(|line| line.starts_with("candidate:"))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+impl RTCPeerConnection {
+    /// Produces a JSON document describing the connection, similar to chrome://webrtc-internals,
+    /// for attaching to bug reports.
+    ///
+    /// The document holds the configuration without the credentials of the ICE servers, the
+    /// states and descriptions, the local and remote candidates, a fresh stats report, the
+    /// latest reports read with `get_stats` while the stats history is enabled, see
+    /// `enable_stats_history`, and the timeline of the latest events.
+    pub async fn dump_diagnostics(&self) -> Result<Value> {
+        let configuration = self.get_configuration()?;
+        let local_description = description(self.local_description());
+        let remote_description = description(self.remote_description());
+        let fresh = match self.get_stats().with_json().await {
+            Ok((_, json)) => serde_json::from_str::<Value>(&json).unwrap_or(Value::Null),
+            Err(_) => Value::Null,
+        };
+
+        let timeline = self.inner.observer.timeline.entries();
+        let mut local_candidates = candidates(&local_description);
+        for (_, (event, candidate)) in &timeline {
+            if *event == "icecandidate" && !local_candidates.contains(&candidate.as_str()) {
+                local_candidates.push(candidate);
+            }
+        }
+
+        let stats_history = self
+            .inner
+            .observer
+            .stats_history
+            .entries()
+            .into_iter()
+            .map(|(timestamp, json)| {
+                json!({
+                    "timestamp": timestamp,
+                    "report": serde_json::from_str::<Value>(&json).unwrap_or(Value::Null),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Ok(json!({
+            "timestamp": now(),
+            "configuration": {
+                "bundlePolicy": configuration.bundle_policy.map(|p| format!("{:?}", p)),
+                "iceTransportPolicy": configuration.ice_transport_policy.map(|p| format!("{:?}", p)),
+                "rtcpMuxPolicy": configuration.rtcp_mux_policy.map(|p| format!("{:?}", p)),
+                "iceCandidatePoolSize": configuration.ice_candidate_pool_size,
+                "portRange": configuration.port_range,
+                "iceServers": configuration.ice_server_urls(),
+            },
+            "connectionState": format!("{:?}", self.connection_state()),
+            "iceConnectionState": format!("{:?}", self.ice_connection_state()),
+            "iceGatheringState": format!("{:?}", self.ice_gathering_state()),
+            "signalingState": format!("{:?}", self.signaling_state()),
+            "localCandidates": local_candidates,
+            "remoteCandidates": candidates(&remote_description),
+            "localDescription": local_description,
+            "remoteDescription": remote_description,
+            "stats": fresh,
+            "statsHistory": stats_history,
+            "events": timeline
+                .iter()
+                .map(|(timestamp, (event, value))| json!({
+                    "timestamp": timestamp,
+                    "event": event,
+                    "value": value,
+                }))
+                .collect::<Vec<_>>(),
+        }))
+    }
+}
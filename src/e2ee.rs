@@ -0,0 +1,595 @@
+use crate::{EncodedFrame, Error, FrameCodec, FrameTransform, RTCPeerConnection, Result, RtpDirection};
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes128Gcm, Nonce};
+use hkdf::Hkdf;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// `AES_128_GCM_SHA256_128`, the cipher suite of the frames.
+const CIPHER_SUITE: u16 = 0x0004;
+
+/// The size of the key and the nonce of AES-128-GCM.
+const KEY_SIZE: usize = 16;
+const NONCE_SIZE: usize = 12;
+
+/// The key and the salt derived from a base key, as described by RFC 9605.
+///
+/// The counter of the frames belongs to the key, so the frames sealed under a key by any
+/// encryptor, such as the audio and the video of a participant, never share a nonce.
+struct KeyMaterial {
+    cipher: Aes128Gcm,
+    salt: [u8; NONCE_SIZE],
+    counter: AtomicU64,
+}
+
+impl KeyMaterial {
+    fn derive(kid: u64, base_key: &[u8]) -> Self {
+        let mut info = Vec::with_capacity(33);
+        let secret = Hkdf::<Sha256>::new(None, base_key);
+        let label = |info: &mut Vec<u8>, label: &[u8]| {
+            info.clear();
+            info.extend_from_slice(label);
+            info.extend_from_slice(&kid.to_be_bytes());
+            info.extend_from_slice(&CIPHER_SUITE.to_be_bytes());
+        };
+
+        // the lengths are valid outputs of sha256, so the expansions can not fail.
+        let mut key = [0; KEY_SIZE];
+        label(&mut info, b"SFrame 1.0 Secret key ");
+        secret.expand(&info, &mut key).unwrap();
+
+        let mut salt = [0; NONCE_SIZE];
+        label(&mut info, b"SFrame 1.0 Secret salt ");
+        secret.expand(&info, &mut salt).unwrap();
+
+        Self {
+            cipher: Aes128Gcm::new(&key.into()),
+            counter: AtomicU64::new(0),
+            salt,
+        }
+    }
+
+    fn nonce(&self, counter: u64) -> [u8; NONCE_SIZE] {
+        let mut nonce = self.salt;
+        for (byte, ctr) in nonce[NONCE_SIZE - 8..].iter_mut().zip(counter.to_be_bytes()) {
+            *byte ^= ctr;
+        }
+
+        nonce
+    }
+}
+
+/// The shortest big endian encoding of a value, at least one byte.
+fn minimal_bytes(value: u64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let skip = (value.leading_zeros() / 8).min(7) as usize;
+    bytes[skip..].to_vec()
+}
+
+/// Encodes the header of a frame, the key id and the counter are
+/// stored in the config byte when they fit in 3 bits.
+fn encode_header(kid: u64, counter: u64) -> Vec<u8> {
+    let mut header = vec![0u8];
+    if kid < 8 {
+        header[0] |= (kid as u8) << 4;
+    } else {
+        let bytes = minimal_bytes(kid);
+        header[0] |= 0x80 | ((bytes.len() - 1) as u8) << 4;
+        header.extend(bytes);
+    }
+
+    if counter < 8 {
+        header[0] |= counter as u8;
+    } else {
+        let bytes = minimal_bytes(counter);
+        header[0] |= 0x08 | (bytes.len() - 1) as u8;
+        header.extend(bytes);
+    }
+
+    header
+}
+
+/// Decodes the header of a frame, returns the key id, the counter and the size of the header.
+fn decode_header(frame: &[u8]) -> Result<(u64, u64, usize)> {
+    let invalid = || Error::Decoding("the frame is too short for its sframe header".to_string());
+    let config = *frame.first().ok_or_else(invalid)?;
+    let mut offset = 1;
+    let mut read = |extended: bool, value: u8| -> Result<u64> {
+        if !extended {
+            return Ok(value as u64);
+        }
+
+        let size = value as usize + 1;
+        let bytes = frame.get(offset..offset + size).ok_or_else(invalid)?;
+        offset += size;
+        Ok(bytes.iter().fold(0, |value, byte| value << 8 | *byte as u64))
+    };
+
+    let kid = read(config & 0x80 != 0, (config >> 4) & 0x07)?;
+    let counter = read(config & 0x08 != 0, config & 0x07)?;
+    Ok((kid, counter, offset))
+}
+
+/// The size of the payload header of a VP8 frame which its packetizer reads, the frame tag
+/// followed by the start code and the dimensions of a keyframe, see RFC 6386.
+const VP8_KEYFRAME_HEADER_SIZE: usize = 10;
+const VP8_HEADER_SIZE: usize = 3;
+
+/// The part of a frame left in the clear for the packetizer, the part of it which is
+/// authenticated, and the rest, which is encrypted.
+struct FrameLayout<'a> {
+    clear: &'a [u8],
+    aad: &'a [u8],
+    protected: &'a [u8],
+}
+
+impl<'a> FrameLayout<'a> {
+    /// Splits a frame by its codec, the codecs whose packetizer parses the payload, apart
+    /// from the ones handled here, can not be protected.
+    fn split(codec: FrameCodec, keyframe: bool, data: &'a [u8]) -> Result<Self> {
+        let clear = match codec {
+            FrameCodec::Generic | FrameCodec::Vp9 => 0,
+            FrameCodec::Vp8 if keyframe => VP8_KEYFRAME_HEADER_SIZE.min(data.len()),
+            FrameCodec::Vp8 => VP8_HEADER_SIZE.min(data.len()),
+            FrameCodec::H264 => {
+                // the start codes and the parameter sets before the slice can be rewritten
+                // by the depacketizer of the receiver, so only the header of the slice
+                // is authenticated.
+                let clear = h264_slice_offset(data)
+                    .ok_or_else(|| Error::Encoding("the H264 frame has no slice".to_string()))?
+                    + 1;
+                return Ok(Self {
+                    clear: &data[..clear],
+                    aad: &data[clear - 1..clear],
+                    protected: &data[clear..],
+                });
+            }
+            FrameCodec::Av1 | FrameCodec::Other => {
+                return Err(Error::Encoding(format!(
+                    "the payload of {:?} frames is parsed by their packetizer",
+                    codec
+                )))
+            }
+        };
+
+        Ok(Self {
+            clear: &data[..clear],
+            aad: &data[..clear],
+            protected: &data[clear..],
+        })
+    }
+}
+
+/// The offset of the header of the first slice of an H264 frame in the Annex B format,
+/// the NAL units before it are the parameter sets and the supplemental information.
+fn h264_slice_offset(data: &[u8]) -> Option<usize> {
+    let mut index = 0;
+    while index + 3 <= data.len() {
+        if data[index..index + 3] == [0, 0, 1] {
+            let header = index + 3;
+            if let Some(kind) = data.get(header).map(|byte| byte & 0x1f) {
+                // a slice of a non IDR or of an IDR picture.
+                if kind == 1 || kind == 5 {
+                    return Some(header);
+                }
+            }
+
+            index = header;
+        } else {
+            index += 1;
+        }
+    }
+
+    None
+}
+
+/// Inserts the emulation prevention bytes of H264, so the encrypted part of a frame
+/// contains no start code for the packetizer to split it on.
+fn escape_h264(data: &[u8]) -> Vec<u8> {
+    let mut escaped = Vec::with_capacity(data.len() + data.len() / 64);
+    let mut zeros = 0;
+    for byte in data {
+        if zeros >= 2 && *byte <= 3 {
+            escaped.push(3);
+            zeros = 0;
+        }
+
+        escaped.push(*byte);
+        zeros = if *byte == 0 { zeros + 1 } else { 0 };
+    }
+
+    // trailing zeros would be taken for the beginning of the next start code.
+    if zeros >= 2 {
+        escaped.push(3);
+    }
+
+    escaped
+}
+
+fn unescape_h264(data: &[u8]) -> Vec<u8> {
+    let mut unescaped = Vec::with_capacity(data.len());
+    let mut zeros = 0;
+    for byte in data {
+        if zeros >= 2 && *byte == 3 {
+            zeros = 0;
+            continue;
+        }
+
+        unescaped.push(*byte);
+        zeros = if *byte == 0 { zeros + 1 } else { 0 };
+    }
+
+    unescaped
+}
+
+/// The keys of the participants of a session, indexed by key id.
+///
+/// A store is cheap to clone and shared by the encryptors and decryptors, the keys can be
+/// added and removed at any time, a frame protected with a removed key can no longer be decrypted.
+#[derive(Clone, Default)]
+pub struct KeyStore {
+    keys: Arc<RwLock<HashMap<u64, Arc<KeyMaterial>>>>,
+}
+
+impl KeyStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Derives the key and the salt of a key id from a base key, an existing key with the
+    /// same id is replaced, setting the same base key again keeps the counter of its frames.
+    ///
+    /// The counter starts at zero with every store, so a base key must not be reused with
+    /// another store, such as by a participant joining again, a new key is derived instead.
+    pub fn set_key(&self, kid: u64, base_key: &[u8]) {
+        let material = KeyMaterial::derive(kid, base_key);
+        let mut keys = self.keys.write().unwrap();
+        if keys.get(&kid).is_some_and(|existing| existing.salt == material.salt) {
+            return;
+        }
+
+        keys.insert(kid, Arc::new(material));
+    }
+
+    pub fn remove_key(&self, kid: u64) {
+        self.keys.write().unwrap().remove(&kid);
+    }
+
+    pub fn has_key(&self, kid: u64) -> bool {
+        self.keys.read().unwrap().contains_key(&kid)
+    }
+
+    fn get(&self, kid: u64) -> Option<Arc<KeyMaterial>> {
+        self.keys.read().unwrap().get(&kid).cloned()
+    }
+}
+
+/// Protects the frames of a participant with its current key.
+///
+/// The encryptor is cheap to clone, the clones share the key id, so a clone can rotate the
+/// key of the encryptor registered on a sender. The counter of the frames is kept with the
+/// key in the store, so several encryptors can use the same key.
+#[derive(Clone)]
+pub struct Encryptor {
+    keys: KeyStore,
+    kid: Arc<AtomicU64>,
+}
+
+impl Encryptor {
+    pub fn new(keys: KeyStore, kid: u64) -> Self {
+        Self {
+            keys,
+            kid: Arc::new(AtomicU64::new(kid)),
+        }
+    }
+
+    /// The key id of the next frames.
+    pub fn key_id(&self) -> u64 {
+        self.kid.load(Ordering::Relaxed)
+    }
+
+    /// Switches to another key of the store, the key is usually added to
+    /// the stores of the other participants before the switch.
+    pub fn rotate(&self, kid: u64) -> Result<()> {
+        if !self.keys.has_key(kid) {
+            return Err(Error::InvalidState(format!("the key {} is not in the store", kid)));
+        }
+
+        self.kid.store(kid, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Encrypts a payload into an sframe, the header followed by the ciphertext.
+    pub fn encrypt(&self, payload: &[u8]) -> Result<Vec<u8>> {
+        self.seal(&[], payload)
+    }
+
+    /// Encrypts an encoded frame in place, the payload header its packetizer reads is left
+    /// in the clear, such as the first bytes of a VP8 frame or the NAL unit headers of H264,
+    /// and is authenticated along with the sframe header. The AV1 frames and the frames of
+    /// an unknown codec can not be encrypted, their packetizer parses the whole payload.
+    pub fn encrypt_frame(&self, frame: &mut EncodedFrame) -> Result<()> {
+        let layout = FrameLayout::split(frame.codec, frame.keyframe, &frame.data)?;
+        let sealed = self.seal(layout.aad, layout.protected)?;
+        let mut data = layout.clear.to_vec();
+        if frame.codec == FrameCodec::H264 {
+            data.extend(escape_h264(&sealed));
+        } else {
+            data.extend(sealed);
+        }
+
+        frame.data = data;
+        Ok(())
+    }
+
+    /// Encrypts a payload into an sframe, the additional data is authenticated along with
+    /// the header but neither encrypted nor included in the sframe.
+    fn seal(&self, aad: &[u8], payload: &[u8]) -> Result<Vec<u8>> {
+        let kid = self.key_id();
+        let key = self
+            .keys
+            .get(kid)
+            .ok_or_else(|| Error::Encoding(format!("the key {} is not in the store", kid)))?;
+
+        let counter = key.counter.fetch_add(1, Ordering::Relaxed);
+        let mut frame = encode_header(kid, counter);
+        let ciphertext = key
+            .cipher
+            .encrypt(
+                Nonce::from_slice(&key.nonce(counter)),
+                Payload {
+                    msg: payload,
+                    aad: &[&frame, aad].concat(),
+                },
+            )
+            .map_err(|_| Error::Encoding("the frame can not be encrypted".to_string()))?;
+
+        frame.extend(ciphertext);
+        Ok(frame)
+    }
+}
+
+/// Decrypts the frames of any participant whose key is in the store.
+#[derive(Clone)]
+pub struct Decryptor {
+    keys: KeyStore,
+}
+
+impl Decryptor {
+    pub fn new(keys: KeyStore) -> Self {
+        Self { keys }
+    }
+
+    /// Decrypts an sframe, returns the key id it was protected with and the payload.
+    pub fn decrypt(&self, frame: &[u8]) -> Result<(u64, Vec<u8>)> {
+        self.open(&[], frame)
+    }
+
+    /// Decrypts an encoded frame encrypted by `Encryptor::encrypt_frame` in place,
+    /// returns the key id it was protected with.
+    pub fn decrypt_frame(&self, frame: &mut EncodedFrame) -> Result<u64> {
+        let layout = FrameLayout::split(frame.codec, frame.keyframe, &frame.data)?;
+        let (kid, payload) = if frame.codec == FrameCodec::H264 {
+            self.open(layout.aad, &unescape_h264(layout.protected))?
+        } else {
+            self.open(layout.aad, layout.protected)?
+        };
+
+        let mut data = layout.clear.to_vec();
+        data.extend(payload);
+        frame.data = data;
+        Ok(kid)
+    }
+
+    fn open(&self, aad: &[u8], frame: &[u8]) -> Result<(u64, Vec<u8>)> {
+        let (kid, counter, size) = decode_header(frame)?;
+        let key = self
+            .keys
+            .get(kid)
+            .ok_or_else(|| Error::Decoding(format!("the key {} is not in the store", kid)))?;
+
+        let (header, ciphertext) = frame.split_at(size);
+        let payload = key
+            .cipher
+            .decrypt(
+                Nonce::from_slice(&key.nonce(counter)),
+                Payload {
+                    msg: ciphertext,
+                    aad: &[header, aad].concat(),
+                },
+            )
+            .map_err(|_| Error::Decoding("the frame can not be decrypted".to_string()))?;
+
+        Ok((kid, payload))
+    }
+}
+
+/// Encrypts the frames of the sender of a transceiver until the transform is dropped,
+/// a frame which can not be encrypted, such as an AV1 frame, is dropped rather than sent
+/// in the clear, see `Encryptor::encrypt_frame`.
+pub fn encrypt_sender(
+    peer: &RTCPeerConnection,
+    mid: &str,
+    encryptor: Encryptor,
+) -> Result<FrameTransform> {
+    peer.add_frame_transform(mid, RtpDirection::Outgoing, move |frame| {
+        encryptor.encrypt_frame(frame).is_ok()
+    })
+}
+
+/// Decrypts the frames of the receiver of a transceiver until the transform is dropped,
+/// a frame which can not be decrypted, such as a frame of an unknown key, is dropped.
+pub fn decrypt_receiver(
+    peer: &RTCPeerConnection,
+    mid: &str,
+    decryptor: Decryptor,
+) -> Result<FrameTransform> {
+    peer.add_frame_transform(mid, RtpDirection::Incoming, move |frame| {
+        decryptor.decrypt_frame(frame).is_ok()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MediaType;
+
+    fn hex(value: &str) -> Vec<u8> {
+        (0..value.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&value[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn frame(codec: FrameCodec, keyframe: bool, data: &[u8]) -> EncodedFrame {
+        EncodedFrame {
+            kind: MediaType::Video,
+            direction: RtpDirection::Outgoing,
+            ssrc: 1,
+            payload_type: 96,
+            timestamp: 0,
+            keyframe,
+            codec,
+            data: data.to_vec(),
+        }
+    }
+
+    // RFC 9605 Appendix C.1.
+    #[test]
+    fn header_encoding() {
+        let vectors: &[(u64, u64, &str)] = &[
+            (0x0, 0x0, "00"),
+            (0x0, 0x7, "07"),
+            (0x0, 0x8, "0808"),
+            (0x0, 0xff, "08ff"),
+            (0x0, 0x100, "090100"),
+            (0x0, 0xffff, "09ffff"),
+            (0x0, 0xffffffffffffffff, "0fffffffffffffffff"),
+            (0x7, 0x0, "70"),
+            (0x8, 0x0, "8008"),
+            (0xff, 0xff, "88ffff"),
+            (0x100, 0x0, "900100"),
+            (0xffffffffffffffff, 0xffffffffffffffff, "ffffffffffffffffffffffffffffffffff"),
+        ];
+
+        for (kid, counter, header) in vectors {
+            let header = hex(header);
+            assert_eq!(encode_header(*kid, *counter), header);
+            assert_eq!(decode_header(&header).unwrap(), (*kid, *counter, header.len()));
+        }
+
+        assert!(decode_header(&hex("0901")).is_err());
+    }
+
+    // RFC 9605 Appendix C.4, AES_128_GCM_SHA256_128.
+    #[test]
+    fn key_derivation() {
+        let material = KeyMaterial::derive(0x123, &hex("000102030405060708090a0b0c0d0e0f"));
+        assert_eq!(material.salt.to_vec(), hex("75234edefe07819026751816"));
+        assert_eq!(material.nonce(0x4567).to_vec(), hex("75234edefe07819026755d71"));
+        assert_eq!(encode_header(0x123, 0x4567), hex("9901234567"));
+    }
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let keys = KeyStore::new();
+        keys.set_key(0x123, &hex("000102030405060708090a0b0c0d0e0f"));
+        let encryptor = Encryptor::new(keys.clone(), 0x123);
+        let decryptor = Decryptor::new(keys.clone());
+
+        let plaintext = b"draft-ietf-sframe-enc";
+        let metadata = b"IETF SFrame WG";
+        let sealed = encryptor.seal(metadata, plaintext).unwrap();
+        assert_eq!(sealed.len(), 1 + 2 + plaintext.len() + 16);
+        assert_eq!(decryptor.open(metadata, &sealed).unwrap(), (0x123, plaintext.to_vec()));
+        assert!(decryptor.open(b"IETF SFrame WH", &sealed).is_err());
+
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(decryptor.open(metadata, &tampered).is_err());
+
+        keys.remove_key(0x123);
+        assert!(decryptor.decrypt(&sealed).is_err());
+    }
+
+    #[test]
+    fn counter_is_shared_by_the_key() {
+        let keys = KeyStore::new();
+        keys.set_key(1, b"base key");
+        let audio = Encryptor::new(keys.clone(), 1);
+        let video = Encryptor::new(keys.clone(), 1);
+
+        let counter = |frame: &[u8]| decode_header(frame).unwrap().1;
+        assert_eq!(counter(&audio.encrypt(b"a").unwrap()), 0);
+        assert_eq!(counter(&video.encrypt(b"v").unwrap()), 1);
+
+        // setting the same key again keeps its counter, a new key starts over.
+        keys.set_key(1, b"base key");
+        assert_eq!(counter(&audio.encrypt(b"a").unwrap()), 2);
+        keys.set_key(1, b"another key");
+        assert_eq!(counter(&audio.encrypt(b"a").unwrap()), 0);
+    }
+
+    #[test]
+    fn vp8_header_stays_in_the_clear() {
+        let keys = KeyStore::new();
+        keys.set_key(0, b"base key");
+        let encryptor = Encryptor::new(keys.clone(), 0);
+        let decryptor = Decryptor::new(keys);
+
+        let data = (0..64).collect::<Vec<u8>>();
+        for (keyframe, clear) in [(true, 10), (false, 3)] {
+            let mut encrypted = frame(FrameCodec::Vp8, keyframe, &data);
+            encryptor.encrypt_frame(&mut encrypted).unwrap();
+            assert_eq!(encrypted.data[..clear], data[..clear]);
+            assert_ne!(encrypted.data[clear..clear + 8], data[clear..clear + 8]);
+
+            let mut tampered = encrypted.clone();
+            tampered.data[0] ^= 1;
+            assert!(decryptor.decrypt_frame(&mut tampered).is_err());
+
+            decryptor.decrypt_frame(&mut encrypted).unwrap();
+            assert_eq!(encrypted.data, data);
+        }
+    }
+
+    #[test]
+    fn h264_nal_headers_stay_in_the_clear() {
+        let keys = KeyStore::new();
+        keys.set_key(0, b"base key");
+        let encryptor = Encryptor::new(keys.clone(), 0);
+        let decryptor = Decryptor::new(keys);
+
+        // the parameter sets followed by an IDR slice full of zeros.
+        let mut data = hex("00000001674200290000000168ce3c80");
+        data.extend(hex("0000000165"));
+        data.extend([0; 256]);
+        let clear = 21;
+
+        for _ in 0..16 {
+            let mut encrypted = frame(FrameCodec::H264, true, &data);
+            encryptor.encrypt_frame(&mut encrypted).unwrap();
+            assert_eq!(encrypted.data[..clear], data[..clear]);
+            assert_eq!(h264_slice_offset(&encrypted.data), Some(clear - 1));
+            assert!(!encrypted.data[clear..].windows(3).any(|w| w == [0, 0, 1]));
+
+            decryptor.decrypt_frame(&mut encrypted).unwrap();
+            assert_eq!(encrypted.data, data);
+        }
+
+        let mut av1 = frame(FrameCodec::Av1, true, &data);
+        assert!(encryptor.encrypt_frame(&mut av1).is_err());
+    }
+
+    #[test]
+    fn h264_escaping() {
+        for data in [&[0u8, 0, 0, 1, 2, 3][..], &[0, 0], &[1, 0, 0, 3, 0], &[]] {
+            let escaped = escape_h264(data);
+            assert!(!escaped.windows(3).any(|w| w[0] == 0 && w[1] == 0 && w[2] < 3));
+            assert_eq!(unescape_h264(&escaped), data);
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use crate::ffi::raw;
+
+use libc::*;
+use std::ffi::CStr;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// The errors of the crate.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// An error reported by libwebrtc, the code is the value of `webrtc::RTCErrorType`.
+    #[error("native error {code}: {message}")]
+    NativeError { code: i32, message: String },
+    /// A session description or one of its lines could not be parsed.
+    #[error("invalid sdp: {0}")]
+    InvalidSdp(String),
+    /// The operation is not allowed in the current state of the connection,
+    /// such as setting an answer without a pending offer.
+    #[error("invalid state: {0}")]
+    InvalidState(String),
+    /// The url of an ICE server is malformed.
+    #[error("invalid ice server url: {0}")]
+    InvalidUrl(String),
+    /// The configuration is inconsistent, such as a TURN server without credentials.
+    #[error("invalid configuration: {0}")]
+    InvalidConfiguration(String),
+    /// An ICE candidate could not be parsed.
+    #[error("invalid candidate: {0}")]
+    InvalidCandidate(String),
+    /// Some of the candidates added at once were rejected, each along with its reason.
+    #[error("{} of the candidates were rejected", .0.len())]
+    CandidatesRejected(Vec<(crate::IceCandidate, Error)>),
+    /// The stats report of the native side could not be parsed.
+    #[error("invalid stats: {0}")]
+    InvalidStats(#[from] serde_json::Error),
+    /// A frame could not be encoded by the native encoders.
+    #[error("encoding failed: {0}")]
+    Encoding(String),
+    /// A media file could not be opened or decoded.
+    #[error("decoding failed: {0}")]
+    Decoding(String),
+    /// A frame could not be converted to or from the format of another library.
+    #[error("conversion failed: {0}")]
+    Conversion(String),
+    /// An audio device could not be opened or played.
+    #[error("audio device error: {0}")]
+    AudioDevice(String),
+    /// A message of the framing layer of a data channel is malformed, or exceeds
+    /// the size negotiated with the remote peer.
+    #[error("invalid frame: {0}")]
+    InvalidFrame(String),
+    /// A message of a typed data channel could not be encoded or decoded by its codec.
+    #[error("codec error: {0}")]
+    Codec(String),
+    /// Reading or writing a file failed.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// A request of the HTTP signaling failed, such as with a status other than a success.
+    #[error("http error: {0}")]
+    Http(String),
+    /// The operation did not complete in time.
+    #[error("the operation timed out")]
+    Timeout,
+    /// A phase of the establishment of the connection did not complete within its timeout.
+    #[error("the {0} timed out")]
+    EstablishmentTimeout(crate::EstablishmentPhase),
+    /// The connection has been closed, or could not be created.
+    #[error("the connection is closed")]
+    Closed,
+    /// The native library could not be loaded, or lacks a function, with the `dlopen` feature.
+    #[error("the native library could not be loaded: {0}")]
+    Library(String),
+    /// A string passed to the native side contains a nul byte.
+    #[error(transparent)]
+    NulInString(#[from] std::ffi::NulError),
+    /// A string returned by the native side is not valid UTF-8.
+    #[error(transparent)]
+    InvalidUtf8(#[from] std::str::Utf8Error),
+}
+
+/// `webrtc::RTCErrorType::SYNTAX_ERROR`
+const SYNTAX_ERROR: c_int = 5;
+
+/// `webrtc::RTCErrorType::INVALID_STATE`
+const INVALID_STATE: c_int = 6;
+
+impl Error {
+    /// Copies an error reported by the native side,
+    /// the syntax and state errors get their own variants.
+    pub(crate) fn from_raw(raw: *const raw::RTCError) -> Self {
+        let raw = unsafe { &*raw };
+        let message = if raw.message.is_null() {
+            String::new()
+        } else {
+            unsafe { CStr::from_ptr(raw.message) }.to_string_lossy().into_owned()
+        };
+
+        match raw.code {
+            SYNTAX_ERROR => Self::InvalidSdp(message),
+            INVALID_STATE => Self::InvalidState(message),
+            code => Self::NativeError { code, message },
+        }
+    }
+}
@@ -0,0 +1,305 @@
+use super::{raw, VideoFrame};
+use crate::Result;
+
+use libc::*;
+use std::ffi::{CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::slice;
+
+/// A video format, the name of a codec and its format specific parameters,
+/// as found in the "a=rtpmap" and "a=fmtp" attributes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VideoFormat {
+    /// The name of the codec, such as "H265", compared case insensitively.
+    pub name: String,
+    /// The parameters of the "a=fmtp" attribute, such as "profile-id=1".
+    pub parameters: Option<String>,
+}
+
+impl VideoFormat {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            parameters: None,
+        }
+    }
+
+    pub fn with_parameters(mut self, parameters: &str) -> Self {
+        self.parameters = Some(parameters.to_string());
+        self
+    }
+
+    fn from_raw(raw: *const raw::RTCVideoFormat) -> Self {
+        let format = unsafe { &*raw };
+        let string = |ptr: *const c_char| unsafe { CStr::from_ptr(ptr) }.to_string_lossy().into_owned();
+        Self {
+            name: string(format.name),
+            parameters: format.parameters.map(string),
+        }
+    }
+}
+
+/// The settings an encoder is initialized with, and initialized again with
+/// whenever the resolution of the stream changes.
+#[derive(Clone, Copy, Debug)]
+pub struct VideoEncoderSettings {
+    pub width: u32,
+    pub height: u32,
+    /// In bits per second.
+    pub max_bitrate: u32,
+    pub max_frame_rate: u32,
+}
+
+/// A frame produced by an external encoder, packetized with the generic packetizer.
+#[derive(Clone, Debug)]
+pub struct EncodedImage {
+    pub data: Vec<u8>,
+    pub keyframe: bool,
+}
+
+/// A video encoder implemented by the application.
+///
+/// The methods are called on the encoder queue of the stream, a panic or an error
+/// drops the frame, and libwebrtc then falls back to requesting a keyframe.
+pub trait ExternalVideoEncoder: Send {
+    fn init(&mut self, settings: VideoEncoderSettings) -> Result<()>;
+
+    /// Encodes an I420 frame, a keyframe must be produced when it is requested,
+    /// and no frame can be returned to skip the frame, such as to hold the bitrate.
+    fn encode(&mut self, frame: &VideoFrame, keyframe: bool) -> Result<Option<EncodedImage>>;
+
+    /// The target bitrate in bits per second and the frame rate, updated by the congestion control.
+    fn set_rates(&mut self, _bitrate: u32, _frame_rate: f64) {}
+}
+
+/// A video decoder implemented by the application.
+///
+/// The methods are called on the decoder thread of the stream, a panic or an error
+/// drops the frame, and libwebrtc then requests a keyframe from the remote peer.
+pub trait ExternalVideoDecoder: Send {
+    /// Decodes a frame into an I420 frame, no frame can be returned while
+    /// the decoder needs more data, the timestamp of the frame is ignored.
+    fn decode(&mut self, data: &[u8], keyframe: bool) -> Result<Option<VideoFrame>>;
+}
+
+/// Creates the external encoders of a runtime.
+///
+/// The formats are offered in the descriptions along with the builtin ones, and
+/// take precedence over a builtin format with the same name.
+pub trait ExternalVideoEncoderFactory: Send + Sync {
+    fn formats(&self) -> Vec<VideoFormat>;
+
+    /// Creates an encoder for one of the formats, once the format has been negotiated,
+    /// no encoder can be returned, in which case the stream is not sent.
+    fn create(&self, format: &VideoFormat) -> Option<Box<dyn ExternalVideoEncoder>>;
+}
+
+/// Creates the external decoders of a runtime.
+///
+/// The formats are offered in the descriptions along with the builtin ones, and
+/// take precedence over a builtin format with the same name.
+pub trait ExternalVideoDecoderFactory: Send + Sync {
+    fn formats(&self) -> Vec<VideoFormat>;
+
+    /// Creates a decoder for one of the formats, once the format has been negotiated.
+    fn create(&self, format: &VideoFormat) -> Option<Box<dyn ExternalVideoDecoder>>;
+}
+
+type EncoderFactory = Box<dyn ExternalVideoEncoderFactory>;
+type DecoderFactory = Box<dyn ExternalVideoDecoderFactory>;
+type Encoder = Box<dyn ExternalVideoEncoder>;
+type Decoder = Box<dyn ExternalVideoDecoder>;
+
+// the functions below are called by the native factories, a panic can not unwind into
+// the native side, so a panicking encoder or decoder fails the call instead.
+
+extern "C" fn create_encoder(format: *const raw::RTCVideoFormat, ctx: *mut c_void) -> *mut c_void {
+    let factory = unsafe { &*(ctx as *const EncoderFactory) };
+    catch_unwind(AssertUnwindSafe(|| factory.create(&VideoFormat::from_raw(format))))
+        .ok()
+        .flatten()
+        .map(|encoder| Box::into_raw(Box::new(encoder)) as *mut c_void)
+        .unwrap_or(std::ptr::null_mut())
+}
+
+extern "C" fn init_encoder(
+    encoder: *mut c_void,
+    width: c_int,
+    height: c_int,
+    max_bitrate: c_int,
+    max_frame_rate: c_int,
+) -> bool {
+    let encoder = unsafe { &mut *(encoder as *mut Encoder) };
+    let settings = VideoEncoderSettings {
+        width: width.max(0) as u32,
+        height: height.max(0) as u32,
+        max_bitrate: max_bitrate.max(0) as u32,
+        max_frame_rate: max_frame_rate.max(0) as u32,
+    };
+
+    matches!(catch_unwind(AssertUnwindSafe(|| encoder.init(settings))), Ok(Ok(())))
+}
+
+extern "C" fn encode(
+    encoder: *mut c_void,
+    frame: *const raw::RTCVideoFrame,
+    keyframe: bool,
+    ctx: *mut c_void,
+    callback: extern "C" fn(*const raw::RTCEncodedFrame, *mut c_void),
+) -> bool {
+    let encoder = unsafe { &mut *(encoder as *mut Encoder) };
+    let frame = VideoFrame::from_raw(frame);
+    match catch_unwind(AssertUnwindSafe(|| encoder.encode(&frame, keyframe))) {
+        Ok(Ok(Some(image))) => {
+            let encoded = raw::RTCEncodedFrame {
+                data: image.data.as_ptr(),
+                size: image.data.len(),
+                timestamp_us: frame.timestamp_us,
+                keyframe: image.keyframe,
+            };
+
+            callback(&encoded, ctx);
+            true
+        }
+        Ok(Ok(None)) => true,
+        _ => false,
+    }
+}
+
+extern "C" fn set_rates(encoder: *mut c_void, bitrate: c_int, frame_rate: c_double) {
+    let encoder = unsafe { &mut *(encoder as *mut Encoder) };
+    let _ = catch_unwind(AssertUnwindSafe(|| {
+        encoder.set_rates(bitrate.max(0) as u32, frame_rate)
+    }));
+}
+
+extern "C" fn release_encoder(encoder: *mut c_void) {
+    let _ = catch_unwind(|| drop(unsafe { Box::from_raw(encoder as *mut Encoder) }));
+}
+
+extern "C" fn create_decoder(format: *const raw::RTCVideoFormat, ctx: *mut c_void) -> *mut c_void {
+    let factory = unsafe { &*(ctx as *const DecoderFactory) };
+    catch_unwind(AssertUnwindSafe(|| factory.create(&VideoFormat::from_raw(format))))
+        .ok()
+        .flatten()
+        .map(|decoder| Box::into_raw(Box::new(decoder)) as *mut c_void)
+        .unwrap_or(std::ptr::null_mut())
+}
+
+extern "C" fn decode(
+    decoder: *mut c_void,
+    frame: *const raw::RTCEncodedFrame,
+    ctx: *mut c_void,
+    callback: extern "C" fn(*const raw::RTCVideoFrame, *mut c_void),
+) -> bool {
+    let decoder = unsafe { &mut *(decoder as *mut Decoder) };
+    let frame = unsafe { &*frame };
+    let data = unsafe { slice::from_raw_parts(frame.data, frame.size) };
+    match catch_unwind(AssertUnwindSafe(|| decoder.decode(data, frame.keyframe))) {
+        Ok(Ok(Some(decoded))) => {
+            callback(&decoded.as_raw(), ctx);
+            true
+        }
+        Ok(Ok(None)) => true,
+        _ => false,
+    }
+}
+
+extern "C" fn release_decoder(decoder: *mut c_void) {
+    let _ = catch_unwind(|| drop(unsafe { Box::from_raw(decoder as *mut Decoder) }));
+}
+
+/// The formats of a factory, borrowed by the native side while the runtime is created.
+struct RawFormats {
+    _strings: Vec<(CString, Option<CString>)>,
+    formats: Vec<raw::RTCVideoFormat>,
+}
+
+impl RawFormats {
+    fn new(formats: Vec<VideoFormat>) -> Result<Self> {
+        let strings = formats
+            .into_iter()
+            .map(|format| {
+                Ok((
+                    CString::new(format.name)?,
+                    format.parameters.map(CString::new).transpose()?,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let formats = strings
+            .iter()
+            .map(|(name, parameters)| raw::RTCVideoFormat {
+                name: name.as_ptr(),
+                parameters: parameters.as_ref().map(|p| p.as_ptr()),
+            })
+            .collect();
+
+        Ok(Self {
+            _strings: strings,
+            formats,
+        })
+    }
+}
+
+/// The external factories of a runtime, they are called by the native factories
+/// until the runtime is freed, so they are dropped after it.
+#[derive(Default)]
+pub(crate) struct ExternalFactories {
+    encoder: Option<Box<EncoderFactory>>,
+    decoder: Option<Box<DecoderFactory>>,
+}
+
+impl ExternalFactories {
+    pub fn set_encoder_factory(&mut self, factory: impl ExternalVideoEncoderFactory + 'static) {
+        self.encoder = Some(Box::new(Box::new(factory)));
+    }
+
+    pub fn set_decoder_factory(&mut self, factory: impl ExternalVideoDecoderFactory + 'static) {
+        self.decoder = Some(Box::new(Box::new(factory)));
+    }
+
+    /// Calls the function with the native descriptions of the factories, which
+    /// only borrow the formats for the duration of the call.
+    pub fn with_raw<T>(
+        &self,
+        f: impl FnOnce(Option<&raw::RTCVideoEncoderFactory>, Option<&raw::RTCVideoDecoderFactory>) -> T,
+    ) -> Result<T> {
+        let encoder_formats = self
+            .encoder
+            .as_ref()
+            .map(|factory| RawFormats::new(factory.formats()))
+            .transpose()?;
+        let decoder_formats = self
+            .decoder
+            .as_ref()
+            .map(|factory| RawFormats::new(factory.formats()))
+            .transpose()?;
+
+        let encoder = self.encoder.as_ref().zip(encoder_formats.as_ref()).map(|(factory, formats)| {
+            raw::RTCVideoEncoderFactory {
+                formats: formats.formats.as_ptr(),
+                formats_size: formats.formats.len(),
+                ctx: factory.as_ref() as *const EncoderFactory as *mut c_void,
+                create: create_encoder,
+                init: init_encoder,
+                encode,
+                set_rates,
+                release: release_encoder,
+            }
+        });
+
+        let decoder = self.decoder.as_ref().zip(decoder_formats.as_ref()).map(|(factory, formats)| {
+            raw::RTCVideoDecoderFactory {
+                formats: formats.formats.as_ptr(),
+                formats_size: formats.formats.len(),
+                ctx: factory.as_ref() as *const DecoderFactory as *mut c_void,
+                create: create_decoder,
+                decode,
+                release: release_decoder,
+            }
+        });
+
+        Ok(f(encoder.as_ref(), decoder.as_ref()))
+    }
+}
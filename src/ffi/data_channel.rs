@@ -0,0 +1,441 @@
+use super::transceiver::check;
+use super::{dispatcher, raw, DataChannelState, PeerConnection, Priority};
+use crate::{Error, Result};
+
+use futures::Stream;
+use libc::*;
+use std::collections::VecDeque;
+use std::ffi::{CStr, CString};
+use std::future::poll_fn;
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// A message received on a data channel.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DataChannelMessage {
+    pub data: Vec<u8>,
+    /// Whether the message was sent as binary data rather than as text.
+    pub binary: bool,
+}
+
+/// The options of a data channel opened by the local peer, see
+/// `RTCPeerConnection::create_data_channel`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DataChannelInit {
+    /// Whether the messages are delivered in the order they were sent, true by default.
+    pub ordered: bool,
+    /// How long a message is retransmitted, None retransmits it until it is delivered.
+    pub max_retransmit_time: Option<Duration>,
+    /// How many times a message is retransmitted, None retransmits it until it is delivered.
+    pub max_retransmits: Option<u16>,
+    /// The share of the bandwidth of the channel and the DSCP marking of its packets,
+    /// low by default.
+    pub priority: Priority,
+}
+
+impl Default for DataChannelInit {
+    fn default() -> Self {
+        Self {
+            ordered: true,
+            max_retransmit_time: None,
+            max_retransmits: None,
+            priority: Priority::default(),
+        }
+    }
+}
+
+/// RTCDataChannel
+///
+/// A data channel opened by either peer, it keeps the native channel alive until it is dropped.
+pub struct RTCDataChannel {
+    raw: *mut raw::RTCDataChannel,
+    id: i32,
+    label: String,
+    ordered: bool,
+    reliable: bool,
+    // the events of the channel, registered on the first use.
+    inbox: OnceLock<Arc<Inbox>>,
+    reading: AtomicBool,
+}
+
+// the native channel is reference counted and thread safe,
+// and the label is copied when the channel is received.
+unsafe impl Send for RTCDataChannel {}
+unsafe impl Sync for RTCDataChannel {}
+
+impl RTCDataChannel {
+    /// Takes the ownership of a channel handed over by the datachannel event.
+    pub(crate) fn from_raw(raw: *mut raw::RTCDataChannel) -> Self {
+        let channel = unsafe { &*raw };
+        let label = if channel.label.is_null() {
+            String::new()
+        } else {
+            unsafe { CStr::from_ptr(channel.label) }.to_string_lossy().into_owned()
+        };
+
+        Self {
+            id: channel.id,
+            ordered: channel.ordered,
+            reliable: channel.reliable,
+            inbox: OnceLock::new(),
+            reading: AtomicBool::new(false),
+            label,
+            raw,
+        }
+    }
+
+    pub(crate) fn create(peer: &Arc<PeerConnection>, label: &str, init: &DataChannelInit) -> Result<Self> {
+        let label = CString::new(label)?;
+        let init = raw::RTCDataChannelInit {
+            ordered: init.ordered,
+            max_retransmit_time: init
+                .max_retransmit_time
+                .map(|time| time.as_millis().min(c_int::MAX as u128) as c_int)
+                .unwrap_or(-1),
+            max_retransmits: init.max_retransmits.map(|count| count as c_int).unwrap_or(-1),
+            priority: init.priority,
+        };
+
+        let mut channel = std::ptr::null_mut();
+        check(|error| {
+            channel = unsafe { raw::rtc_create_data_channel(peer.raw, label.as_ptr(), &init, error) };
+            !channel.is_null()
+        })?;
+
+        Ok(Self::from_raw(channel))
+    }
+
+    /// An ID number (between 0 and 65,534) which uniquely identifies the channel.
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    /// A name describing the channel, labels are not required to be unique.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Whether the messages are delivered in the order they were sent.
+    pub fn ordered(&self) -> bool {
+        self.ordered
+    }
+
+    /// Whether the messages are retransmitted until they are delivered.
+    pub fn reliable(&self) -> bool {
+        self.reliable
+    }
+
+    /// The state of the underlying data connection.
+    pub fn ready_state(&self) -> DataChannelState {
+        unsafe { raw::rtc_get_data_channel_state(self.raw) }
+    }
+
+    /// The number of bytes currently queued to be sent over the channel.
+    pub fn buffered_amount(&self) -> u64 {
+        unsafe { raw::rtc_get_data_channel_buffered_amount(self.raw) }
+    }
+
+    /// The priority of the channel, as chosen by the peer which opened the channel,
+    /// see `DataChannelInit::priority`.
+    pub fn priority(&self) -> Priority {
+        unsafe { raw::rtc_get_data_channel_priority(self.raw) }
+    }
+
+    /// Sends a message to the remote peer, which fails when the channel is not open
+    /// or its send buffer is full, see `writable` to wait for room in the buffer.
+    pub fn send(&self, data: &[u8], binary: bool) -> Result<()> {
+        if unsafe { raw::rtc_send_data_channel_message(self.raw, data.as_ptr(), data.len(), binary) } {
+            Ok(())
+        } else {
+            Err(Error::InvalidState(format!("data channel {} can not send", self.label)))
+        }
+    }
+
+    /// Waits for the channel to be open with at most the given number of bytes in its send
+    /// buffer, which keeps a fast sender from filling the buffer. Fails once the channel is
+    /// closing.
+    pub async fn writable(&self, buffered_amount: u64) -> Result<()> {
+        poll_fn(|cx| self.poll_writable(cx, buffered_amount)).await.map_err(|_| Error::Closed)
+    }
+
+    /// Closes the channel, either peer may close it.
+    pub fn close(&self) {
+        unsafe { raw::rtc_close_data_channel(self.raw) }
+    }
+
+    /// The received messages, in order, the stream ends once the channel is closed and every
+    /// message has been read. The channel has a single reader at a time, the messages received
+    /// before the reader is taken are kept for it.
+    pub fn messages(self: Arc<Self>) -> Result<DataChannelMessages> {
+        if self.reading.swap(true, Ordering::SeqCst) {
+            return Err(Error::InvalidState(format!("data channel {} is already read", self.label)));
+        }
+
+        self.inbox();
+        Ok(DataChannelMessages { channel: self })
+    }
+
+    /// Turns the channel into a byte stream, so that the code written for a socket, such as
+    /// a tunnel or an HTTP client, runs over the channel unchanged.
+    ///
+    /// The written bytes are sent as binary messages of at most `DataChannelIo::MAX_MESSAGE_SIZE`
+    /// bytes, and the received messages are read back to back, whatever their size and type,
+    /// so both ends of the channel must use it as a byte stream. Only an ordered and reliable
+    /// channel keeps the bytes in order and complete.
+    pub fn into_async_io(self: Arc<Self>) -> Result<DataChannelIo> {
+        if !self.ordered || !self.reliable {
+            return Err(Error::InvalidState(format!(
+                "data channel {} is not ordered and reliable",
+                self.label
+            )));
+        }
+
+        Ok(DataChannelIo {
+            messages: self.messages()?,
+            offset: 0,
+        })
+    }
+
+    /// Registers the handlers of the channel on the first call.
+    fn inbox(&self) -> &Arc<Inbox> {
+        self.inbox.get_or_init(|| {
+            let inbox = Arc::new(Mutex::new(InboxState {
+                state: self.ready_state(),
+                received: VecDeque::new(),
+                reader: None,
+                writers: Vec::new(),
+            }));
+
+            unsafe {
+                raw::rtc_set_data_channel_observer(
+                    self.raw,
+                    Arc::as_ptr(&inbox) as *mut c_void,
+                    on_message,
+                    on_state_change,
+                    on_buffered_amount_change,
+                )
+            }
+
+            inbox
+        })
+    }
+
+    /// Registers the waker of a writer until the channel is open with at most the given
+    /// number of bytes in its send buffer, or fails once the channel is closing.
+    pub(crate) fn poll_writable(&self, cx: &mut Context<'_>, buffered_amount: u64) -> Poll<io::Result<()>> {
+        let mut inbox = self.inbox().lock().unwrap();
+        match inbox.state {
+            DataChannelState::Closing | DataChannelState::Closed => Poll::Ready(Err(io::ErrorKind::BrokenPipe.into())),
+            DataChannelState::Open if self.buffered_amount() <= buffered_amount => Poll::Ready(Ok(())),
+            _ => {
+                inbox.writers.push(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Drop for RTCDataChannel {
+    fn drop(&mut self) {
+        // no handler runs once they are removed, the dispatched jobs hold their own reference.
+        if self.inbox.get().is_some() {
+            unsafe { raw::rtc_remove_data_channel_observer(self.raw) }
+        }
+
+        unsafe { raw::rtc_free_data_channel(self.raw) }
+    }
+}
+
+struct InboxState {
+    state: DataChannelState,
+    received: VecDeque<DataChannelMessage>,
+    reader: Option<Waker>,
+    writers: Vec<Waker>,
+}
+
+impl InboxState {
+    fn wake_writers(&mut self) {
+        for waker in self.writers.drain(..) {
+            waker.wake();
+        }
+    }
+}
+
+/// The events of a channel, the context of the native handlers.
+type Inbox = Mutex<InboxState>;
+
+/// Takes a reference to the inbox from the context of a handler, which is alive for the
+/// duration of the handler, as the handlers are removed before the channel is freed.
+fn inbox_from_ctx(ctx: *mut c_void) -> Arc<Inbox> {
+    unsafe {
+        Arc::increment_strong_count(ctx as *const Inbox);
+        Arc::from_raw(ctx as *const Inbox)
+    }
+}
+
+// the events are copied on the signaling thread, and the wakers run on the dispatcher thread.
+
+extern "C" fn on_message(data: *const u8, size: usize, binary: bool, ctx: *mut c_void) {
+    let inbox = inbox_from_ctx(ctx);
+    let message = DataChannelMessage {
+        data: unsafe { std::slice::from_raw_parts(data, size) }.to_vec(),
+        binary,
+    };
+
+    dispatcher::dispatch(move || {
+        let mut inbox = inbox.lock().unwrap();
+        inbox.received.push_back(message);
+        if let Some(waker) = inbox.reader.take() {
+            waker.wake();
+        }
+    });
+}
+
+extern "C" fn on_state_change(state: DataChannelState, ctx: *mut c_void) {
+    let inbox = inbox_from_ctx(ctx);
+    dispatcher::dispatch(move || {
+        let mut inbox = inbox.lock().unwrap();
+        inbox.state = state;
+        inbox.wake_writers();
+        if let Some(waker) = inbox.reader.take() {
+            waker.wake();
+        }
+    });
+}
+
+extern "C" fn on_buffered_amount_change(_buffered_amount: u64, ctx: *mut c_void) {
+    let inbox = inbox_from_ctx(ctx);
+    dispatcher::dispatch(move || inbox.lock().unwrap().wake_writers());
+}
+
+/// DataChannelMessages
+///
+/// The reader of a channel, see `RTCDataChannel::messages`.
+pub struct DataChannelMessages {
+    channel: Arc<RTCDataChannel>,
+}
+
+impl DataChannelMessages {
+    /// The channel the messages are received on, which is also the one to send replies on.
+    pub fn channel(&self) -> &Arc<RTCDataChannel> {
+        &self.channel
+    }
+}
+
+impl Stream for DataChannelMessages {
+    type Item = DataChannelMessage;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut inbox = self.channel.inbox().lock().unwrap();
+        if let Some(message) = inbox.received.pop_front() {
+            return Poll::Ready(Some(message));
+        }
+
+        if inbox.state == DataChannelState::Closed {
+            return Poll::Ready(None);
+        }
+
+        inbox.reader = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl Drop for DataChannelMessages {
+    fn drop(&mut self) {
+        self.channel.reading.store(false, Ordering::SeqCst);
+    }
+}
+
+/// DataChannelIo
+///
+/// A data channel as a byte stream, see `RTCDataChannel::into_async_io`.
+///
+/// The writes wait for the channel to open, and wait while the send buffer of the channel
+/// is over `DataChannelIo::HIGH_WATER_MARK`, so a fast writer does not exhaust it. The reads
+/// end once the channel is closed and every received message has been read, and the shutdown
+/// closes the channel once its send buffer is empty.
+pub struct DataChannelIo {
+    messages: DataChannelMessages,
+    // the number of bytes already read from the front message.
+    offset: usize,
+}
+
+impl DataChannelIo {
+    /// The largest message sent, which every implementation is able to receive.
+    pub const MAX_MESSAGE_SIZE: usize = 16 * 1024;
+
+    /// The number of bytes in the send buffer beyond which the writes wait.
+    pub const HIGH_WATER_MARK: u64 = 1024 * 1024;
+
+    /// The channel the stream runs over.
+    pub fn channel(&self) -> &Arc<RTCDataChannel> {
+        self.messages.channel()
+    }
+}
+
+impl AsyncRead for DataChannelIo {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let this = &mut *self;
+        let mut inbox = this.messages.channel.inbox().lock().unwrap();
+        while buf.remaining() > 0 {
+            let Some(message) = inbox.received.front() else {
+                break;
+            };
+
+            let size = (message.data.len() - this.offset).min(buf.remaining());
+            buf.put_slice(&message.data[this.offset..this.offset + size]);
+            this.offset += size;
+            if this.offset == message.data.len() {
+                inbox.received.pop_front();
+                this.offset = 0;
+            }
+        }
+
+        // a closed channel with nothing left to read is the end of the stream.
+        let empty = buf.filled().is_empty() && buf.remaining() > 0;
+        if empty && inbox.state != DataChannelState::Closed {
+            inbox.reader = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for DataChannelIo {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        let channel = self.channel();
+        if let Err(e) = futures::ready!(channel.poll_writable(cx, Self::HIGH_WATER_MARK)) {
+            return Poll::Ready(Err(e));
+        }
+
+        let size = buf.len().min(Self::MAX_MESSAGE_SIZE);
+        match channel.send(&buf[..size], true) {
+            Ok(()) => Poll::Ready(Ok(size)),
+            Err(_) => Poll::Ready(Err(io::ErrorKind::BrokenPipe.into())),
+        }
+    }
+
+    /// Waits for the send buffer of the channel to be empty.
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.channel().poll_writable(cx, 0)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        // a channel which is already closing has nothing left to flush.
+        if futures::ready!(self.as_mut().poll_flush(cx)).is_ok() {
+            self.channel().close();
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
@@ -0,0 +1,86 @@
+use std::collections::BTreeMap;
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::{Duration, Instant};
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// A job to run at once, or once its deadline has passed.
+struct Queued {
+    deadline: Option<Instant>,
+    job: Job,
+}
+
+static DISPATCHER: OnceLock<Sender<Queued>> = OnceLock::new();
+
+fn queue(queued: Queued) {
+    let sender = DISPATCHER.get_or_init(|| {
+        let (sender, receiver) = channel::<Queued>();
+        thread::Builder::new()
+            .name("webrtc-dispatcher".to_string())
+            .spawn(move || {
+                // the delayed jobs by deadline, the sequence keeps the jobs of a deadline in order.
+                let mut timers = BTreeMap::<(Instant, u64), Job>::new();
+                let mut sequence = 0;
+                loop {
+                    let received = match timers.keys().next() {
+                        Some((deadline, _)) => {
+                            let timeout = deadline.saturating_duration_since(Instant::now());
+                            receiver.recv_timeout(timeout)
+                        }
+                        None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+                    };
+
+                    // the jobs catch their own panics, so the thread outlives them.
+                    match received {
+                        Ok(Queued { deadline: None, job }) => job(),
+                        Ok(Queued { deadline: Some(deadline), job }) => {
+                            sequence += 1;
+                            timers.insert((deadline, sequence), job);
+                        }
+                        Err(RecvTimeoutError::Timeout) => (),
+                        Err(RecvTimeoutError::Disconnected) => return,
+                    }
+
+                    let now = Instant::now();
+                    while let Some(entry) = timers.first_entry() {
+                        if entry.key().0 > now {
+                            break;
+                        }
+
+                        entry.remove()();
+                    }
+                }
+            })
+            .expect("the dispatcher thread can not be spawned");
+        sender
+    });
+
+    // the receiver lives as long as the thread, which never stops.
+    let _ = sender.send(queued);
+}
+
+/// Runs a job on the dispatcher thread, started on the first call and kept for the lifetime
+/// of the process. The jobs run one at a time, in the order they were queued, so the events
+/// of a connection are delivered in the order the native side fired them.
+///
+/// The native callbacks only copy their arguments and queue a job, so the code of the
+/// application, such as a listener or a waker, does not run on the threads of libwebrtc,
+/// and a slow listener can not stall the negotiation. The callbacks which must answer the
+/// native side at once, such as the candidate filter, the media sinks and the frame
+/// transforms, run on the threads of libwebrtc instead.
+pub(crate) fn dispatch(job: impl FnOnce() + Send + 'static) {
+    queue(Queued {
+        deadline: None,
+        job: Box::new(job),
+    });
+}
+
+/// Runs a job on the dispatcher thread once the delay has elapsed, such as a timeout.
+pub(crate) fn dispatch_after(delay: Duration, job: impl FnOnce() + Send + 'static) {
+    queue(Queued {
+        deadline: Some(Instant::now() + delay),
+        job: Box::new(job),
+    });
+}
@@ -0,0 +1,105 @@
+use super::{raw, AudioFrame, VideoFrame};
+use crate::{Error, Result};
+
+use libc::*;
+use std::slice;
+
+/// A frame produced by one of the encoders.
+pub(crate) struct EncodedFrame {
+    pub data: Vec<u8>,
+    /// The timestamp of the video frame it was encoded from,
+    /// or the time of the first sample since the audio encoder was created.
+    pub timestamp_us: i64,
+    pub keyframe: bool,
+}
+
+extern "C" fn on_encoded_frame(frame: *const raw::RTCEncodedFrame, ctx: *mut c_void) {
+    let frames = unsafe { &mut *(ctx as *mut Vec<EncodedFrame>) };
+    let frame = unsafe { &*frame };
+    frames.push(EncodedFrame {
+        data: unsafe { slice::from_raw_parts(frame.data, frame.size) }.to_vec(),
+        timestamp_us: frame.timestamp_us,
+        keyframe: frame.keyframe,
+    });
+}
+
+/// A VP8 encoder, the encoder follows the resolution of the frames.
+pub(crate) struct VideoEncoder {
+    raw: *const raw::RTCVideoEncoder,
+}
+
+// the native encoder is only used through `&mut self`.
+unsafe impl Send for VideoEncoder {}
+
+impl VideoEncoder {
+    pub fn new(bitrate: u32, frame_rate: u32) -> Self {
+        Self {
+            raw: unsafe { raw::rtc_create_video_encoder(bitrate as c_int, frame_rate as c_int) },
+        }
+    }
+
+    pub fn encode(&mut self, frame: &VideoFrame, keyframe: bool) -> Result<Vec<EncodedFrame>> {
+        let mut frames = Vec::new();
+        let raw_frame = frame.as_raw();
+        let ctx = &mut frames as *mut Vec<EncodedFrame> as *mut c_void;
+        if !unsafe { raw::rtc_video_encoder_encode(self.raw, &raw_frame, keyframe, ctx, on_encoded_frame) } {
+            return Err(Error::Encoding(format!(
+                "failed to encode a video frame of {}x{}",
+                frame.width, frame.height
+            )));
+        }
+
+        Ok(frames)
+    }
+}
+
+impl Drop for VideoEncoder {
+    fn drop(&mut self) {
+        unsafe { raw::rtc_free_video_encoder(self.raw) }
+    }
+}
+
+/// An Opus encoder producing packets of 20ms sampled at 48kHz.
+pub(crate) struct AudioEncoder {
+    raw: *const raw::RTCAudioEncoder,
+    channels: u32,
+}
+
+// the native encoder is only used through `&mut self`.
+unsafe impl Send for AudioEncoder {}
+
+impl AudioEncoder {
+    /// The rate of the encoded packets, whatever the rate of the input.
+    pub const SAMPLE_RATE: u32 = 48000;
+
+    pub fn new(channels: u32, bitrate: u32) -> Self {
+        Self {
+            raw: unsafe { raw::rtc_create_audio_encoder(channels as c_int, bitrate as c_int) },
+            channels,
+        }
+    }
+
+    pub fn channels(&self) -> u32 {
+        self.channels
+    }
+
+    pub fn encode(&mut self, frame: &AudioFrame) -> Result<Vec<EncodedFrame>> {
+        let mut frames = Vec::new();
+        let raw_frame = frame.as_raw();
+        let ctx = &mut frames as *mut Vec<EncodedFrame> as *mut c_void;
+        if !unsafe { raw::rtc_audio_encoder_encode(self.raw, &raw_frame, ctx, on_encoded_frame) } {
+            return Err(Error::Encoding(format!(
+                "failed to encode an audio frame of {} channels with an encoder of {}",
+                frame.channels, self.channels
+            )));
+        }
+
+        Ok(frames)
+    }
+}
+
+impl Drop for AudioEncoder {
+    fn drop(&mut self) {
+        unsafe { raw::rtc_free_audio_encoder(self.raw) }
+    }
+}
@@ -0,0 +1,181 @@
+use super::raw;
+
+use std::slice;
+
+/// A decoded video frame in the I420 format.
+///
+/// The planes are copied from the native frame and tightly packed, the Y plane is
+/// followed by the U and V planes of half the width and height.
+#[derive(Clone, Debug)]
+pub struct VideoFrame {
+    pub width: u32,
+    pub height: u32,
+    /// The render time of the frame, in microseconds of the monotonic clock of libwebrtc.
+    pub timestamp_us: i64,
+    /// The clockwise rotation to apply before rendering, in degrees.
+    pub rotation: u32,
+    pub data: Vec<u8>,
+}
+
+impl VideoFrame {
+    /// Copies a frame handed over by a video sink.
+    pub(crate) fn from_raw(raw: *const raw::RTCVideoFrame) -> Self {
+        let frame = unsafe { &*raw };
+        let width = frame.width.max(0) as usize;
+        let height = frame.height.max(0) as usize;
+        let (chroma_width, chroma_height) = (width.div_ceil(2), height.div_ceil(2));
+
+        let mut data = Vec::with_capacity(width * height + chroma_width * chroma_height * 2);
+        let planes = [
+            (frame.data_y, frame.stride_y, width, height),
+            (frame.data_u, frame.stride_u, chroma_width, chroma_height),
+            (frame.data_v, frame.stride_v, chroma_width, chroma_height),
+        ];
+
+        for (plane, stride, width, height) in planes {
+            for row in 0..height {
+                let line = unsafe { plane.add(row * stride as usize) };
+                data.extend_from_slice(unsafe { slice::from_raw_parts(line, width) });
+            }
+        }
+
+        Self {
+            width: width as u32,
+            height: height as u32,
+            timestamp_us: frame.timestamp_us,
+            rotation: frame.rotation.max(0) as u32,
+            data,
+        }
+    }
+
+    /// Borrows the frame for the native side, the planes must outlive the returned struct.
+    pub(crate) fn as_raw(&self) -> raw::RTCVideoFrame {
+        let (chroma_width, _) = self.chroma_size();
+        raw::RTCVideoFrame {
+            width: self.width as i32,
+            height: self.height as i32,
+            timestamp_us: self.timestamp_us,
+            rotation: self.rotation as i32,
+            data_y: self.y().as_ptr(),
+            stride_y: self.width as i32,
+            data_u: self.u().as_ptr(),
+            stride_u: chroma_width as i32,
+            data_v: self.v().as_ptr(),
+            stride_v: chroma_width as i32,
+        }
+    }
+
+    /// Converts the frame to packed 8 bits RGB, from the limited range of BT.601 which the
+    /// encoders of libwebrtc produce, the rotation is not applied.
+    pub fn to_rgb(&self) -> Vec<u8> {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let (chroma_width, _) = self.chroma_size();
+        let (y, u, v) = (self.y(), self.u(), self.v());
+
+        let mut rgb = Vec::with_capacity(width * height * 3);
+        for row in 0..height {
+            for column in 0..width {
+                let chroma = (row / 2) * chroma_width + column / 2;
+                let luma = (y[row * width + column] as f32 - 16.0) * 1.164;
+                let cb = u[chroma] as f32 - 128.0;
+                let cr = v[chroma] as f32 - 128.0;
+                rgb.extend([
+                    (luma + 1.596 * cr).round().clamp(0.0, 255.0) as u8,
+                    (luma - 0.392 * cb - 0.813 * cr).round().clamp(0.0, 255.0) as u8,
+                    (luma + 2.017 * cb).round().clamp(0.0, 255.0) as u8,
+                ]);
+            }
+        }
+
+        rgb
+    }
+
+    /// Converts the frame to an image with the rotation applied, so that a received frame
+    /// can be saved as a thumbnail or compared in a visual test.
+    ///
+    /// ```no_run
+    /// # fn example(frame: webrtc::VideoFrame) -> image::ImageResult<()> {
+    /// frame.to_image().save("frame.png")
+    /// # }
+    /// ```
+    #[cfg(feature = "image")]
+    pub fn to_image(&self) -> image::RgbImage {
+        use image::imageops;
+
+        let image = image::RgbImage::from_raw(self.width, self.height, self.to_rgb())
+            .expect("the rgb buffer matches the size of the frame");
+        match self.rotation {
+            90 => imageops::rotate90(&image),
+            180 => imageops::rotate180(&image),
+            270 => imageops::rotate270(&image),
+            _ => image,
+        }
+    }
+
+    pub fn y(&self) -> &[u8] {
+        &self.data[..self.luma_len()]
+    }
+
+    pub fn u(&self) -> &[u8] {
+        let start = self.luma_len();
+        &self.data[start..start + self.chroma_len()]
+    }
+
+    pub fn v(&self) -> &[u8] {
+        let start = self.luma_len() + self.chroma_len();
+        &self.data[start..start + self.chroma_len()]
+    }
+
+    fn chroma_size(&self) -> (usize, usize) {
+        ((self.width as usize).div_ceil(2), (self.height as usize).div_ceil(2))
+    }
+
+    fn luma_len(&self) -> usize {
+        self.width as usize * self.height as usize
+    }
+
+    fn chroma_len(&self) -> usize {
+        let (width, height) = self.chroma_size();
+        width * height
+    }
+}
+
+/// 10ms of interleaved 16 bits samples.
+#[derive(Clone, Debug)]
+pub struct AudioFrame {
+    pub sample_rate: u32,
+    pub channels: u32,
+    /// The time the frame was delivered, in microseconds of the monotonic clock of libwebrtc.
+    pub timestamp_us: i64,
+    pub data: Vec<i16>,
+}
+
+impl AudioFrame {
+    /// Copies a frame handed over by an audio sink.
+    pub(crate) fn from_raw(raw: *const raw::RTCAudioFrame) -> Self {
+        let frame = unsafe { &*raw };
+        let len = (frame.frames.max(0) * frame.channels.max(0)) as usize;
+        Self {
+            sample_rate: frame.sample_rate.max(0) as u32,
+            channels: frame.channels.max(0) as u32,
+            timestamp_us: frame.timestamp_us,
+            data: unsafe { slice::from_raw_parts(frame.data, len) }.to_vec(),
+        }
+    }
+
+    /// Borrows the frame for the native side, the samples must outlive the returned struct.
+    pub(crate) fn as_raw(&self) -> raw::RTCAudioFrame {
+        raw::RTCAudioFrame {
+            data: self.data.as_ptr(),
+            sample_rate: self.sample_rate as i32,
+            channels: self.channels as i32,
+            frames: self.frames() as i32,
+            timestamp_us: self.timestamp_us,
+        }
+    }
+
+    /// The number of samples per channel.
+    pub fn frames(&self) -> usize {
+        self.data.len() / self.channels.max(1) as usize
+    }
+}
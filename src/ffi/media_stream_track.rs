@@ -0,0 +1,353 @@
+use super::observer::Subscribers;
+use super::{dispatcher, raw, AudioFrame, TrackEvent, VideoFrame, WebRtcRuntime};
+use crate::{Error, Result};
+
+use futures::{Stream, StreamExt};
+
+use libc::*;
+use std::ffi::CStr;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+type Handler<T> = Box<dyn FnMut(Arc<T>) + Send>;
+
+/// The native sink of a track, detached when it is dropped.
+struct NativeSink(*const raw::RTCSink);
+
+// the native sink can be removed from any thread.
+unsafe impl Send for NativeSink {}
+
+impl Drop for NativeSink {
+    fn drop(&mut self) {
+        // the native side no longer calls the handler once the sink is removed.
+        unsafe { raw::media_stream_track_remove_sink(self.0) };
+    }
+}
+
+/// The sinks of a track for one kind of frames, a single native sink is attached
+/// while there is at least one handler, and every frame is copied once from the
+/// native side and shared by all the handlers.
+struct Sinks<T> {
+    handlers: Mutex<Vec<(u64, Handler<T>)>>,
+    native: Mutex<Option<NativeSink>>,
+    next_id: AtomicU64,
+}
+
+impl<T> Default for Sinks<T> {
+    fn default() -> Self {
+        Self {
+            handlers: Mutex::new(Vec::new()),
+            native: Mutex::new(None),
+            next_id: AtomicU64::new(0),
+        }
+    }
+}
+
+impl<T> Sinks<T> {
+    /// Adds a handler, `attach` attaches the native sink with the sinks as its context
+    /// if it is not attached yet, returns None if the native sink can not be attached.
+    fn add(
+        self: &Arc<Self>,
+        handler: Handler<T>,
+        attach: impl FnOnce(*mut c_void) -> *const raw::RTCSink,
+    ) -> Option<u64> {
+        let mut native = self.native.lock().unwrap();
+        if native.is_none() {
+            let raw = attach(Arc::as_ptr(self) as *mut c_void);
+            if raw.is_null() {
+                return None;
+            }
+
+            *native = Some(NativeSink(raw));
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.handlers().push((id, handler));
+        Some(id)
+    }
+
+    fn remove(&self, id: u64) {
+        let mut native = self.native.lock().unwrap();
+        let mut handlers = self.handlers();
+        handlers.retain(|(handler_id, _)| *handler_id != id);
+        let empty = handlers.is_empty();
+
+        // the native sink waits for the frame being delivered, which holds the handlers.
+        drop(handlers);
+        if empty {
+            native.take();
+        }
+    }
+
+    // a panicking handler only loses its frame, and can not poison the other ones.
+    fn emit(&self, frame: T) {
+        let frame = Arc::new(frame);
+        for (_, handler) in self.handlers().iter_mut() {
+            let _ = catch_unwind(AssertUnwindSafe(|| handler(frame.clone())));
+        }
+    }
+
+    fn handlers(&self) -> MutexGuard<'_, Vec<(u64, Handler<T>)>> {
+        self.handlers.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+// a panic can not unwind into the native side, and a sink has
+// no error stream to report to, so it only loses the frame.
+extern "C" fn on_video_frame(frame: *const raw::RTCVideoFrame, ctx: *mut c_void) {
+    let sinks = unsafe { &*(ctx as *const Sinks<VideoFrame>) };
+    let _ = catch_unwind(AssertUnwindSafe(|| sinks.emit(VideoFrame::from_raw(frame))));
+}
+
+extern "C" fn on_audio_frame(frame: *const raw::RTCAudioFrame, ctx: *mut c_void) {
+    let sinks = unsafe { &*(ctx as *const Sinks<AudioFrame>) };
+    let _ = catch_unwind(AssertUnwindSafe(|| sinks.emit(AudioFrame::from_raw(frame))));
+}
+
+enum Attached {
+    Video(Arc<Sinks<VideoFrame>>),
+    Audio(Arc<Sinks<AudioFrame>>),
+}
+
+/// MediaSink
+///
+/// A handler attached to a track, it is detached when the sink is dropped.
+///
+/// The handlers are called on the thread delivering the frames, one after the other,
+/// so a slow handler delays the other sinks of the track. A handler must not drop
+/// a sink of its own track.
+pub struct MediaSink {
+    id: u64,
+    attached: Attached,
+}
+
+impl Drop for MediaSink {
+    fn drop(&mut self) {
+        match &self.attached {
+            Attached::Video(sinks) => sinks.remove(self.id),
+            Attached::Audio(sinks) => sinks.remove(self.id),
+        }
+    }
+}
+
+// called on the signaling thread, the listeners are woken on the dispatcher thread.
+extern "C" fn on_track_event(event: TrackEvent, ctx: *mut c_void) {
+    let events = unsafe {
+        Arc::increment_strong_count(ctx as *const Subscribers<TrackEvent>);
+        Arc::from_raw(ctx as *const Subscribers<TrackEvent>)
+    };
+
+    dispatcher::dispatch(move || {
+        let _ = catch_unwind(AssertUnwindSafe(|| events.emit(event)));
+    });
+}
+
+/// The native observer of a track, removed when it is dropped.
+struct TrackObserver {
+    raw: *const raw::RTCTrackObserver,
+    events: Arc<Subscribers<TrackEvent>>,
+}
+
+impl Drop for TrackObserver {
+    fn drop(&mut self) {
+        unsafe { raw::media_stream_track_remove_observer(self.raw) }
+    }
+}
+
+/// MediaStreamTrack
+///
+/// A track received from the remote peer, or a local track created by a runtime,
+/// it keeps the native track alive until it is dropped.
+pub struct MediaStreamTrack {
+    pub(crate) raw: *mut raw::MediaStreamTrack,
+    id: String,
+    kind: String,
+    label: String,
+    stream_ids: Vec<String>,
+    // the source of a local track is created by the factory of the runtime.
+    runtime: Option<WebRtcRuntime>,
+    video_sinks: Arc<Sinks<VideoFrame>>,
+    audio_sinks: Arc<Sinks<AudioFrame>>,
+    observer: TrackObserver,
+}
+
+// the native track is reference counted and thread safe,
+// and the strings are copied when the track is received.
+unsafe impl Send for MediaStreamTrack {}
+unsafe impl Sync for MediaStreamTrack {}
+
+impl MediaStreamTrack {
+    /// Takes the ownership of a track handed over by the track event.
+    pub(crate) fn from_raw(raw: *mut raw::MediaStreamTrack) -> Self {
+        let track = unsafe { &*raw };
+        let to_string = |s: *const c_char| {
+            if s.is_null() {
+                String::new()
+            } else {
+                unsafe { CStr::from_ptr(s) }.to_string_lossy().into_owned()
+            }
+        };
+
+        let events = Arc::new(Subscribers::default());
+        let observer = TrackObserver {
+            raw: unsafe {
+                raw::media_stream_track_add_observer(
+                    raw,
+                    Arc::as_ptr(&events) as *mut c_void,
+                    on_track_event,
+                )
+            },
+            events,
+        };
+
+        Self {
+            id: to_string(track.id),
+            kind: to_string(track.kind),
+            label: to_string(track.label),
+            stream_ids: Vec::new(),
+            runtime: None,
+            video_sinks: Default::default(),
+            audio_sinks: Default::default(),
+            observer,
+            raw,
+        }
+    }
+
+    /// Takes the ownership of a local track created on a runtime.
+    pub(crate) fn from_local(raw: *mut raw::MediaStreamTrack, runtime: WebRtcRuntime) -> Self {
+        let mut track = Self::from_raw(raw);
+        track.runtime = Some(runtime);
+        track
+    }
+
+    /// Sets the ids of the remote streams announced in the track event.
+    pub(crate) fn with_stream_ids(mut self, stream_ids: Vec<String>) -> Self {
+        self.stream_ids = stream_ids;
+        self
+    }
+
+    /// A unique identifier (GUID) for the track.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// "audio" if the track is an audio track and "video" if it is a video track.
+    /// The ids of the streams of a remote track, as added by the remote peer, when the track
+    /// event fired. The tracks sharing a stream id belong together, such as the audio and the
+    /// video of a participant. Empty for a local track.
+    pub fn stream_ids(&self) -> &[String] {
+        &self.stream_ids
+    }
+
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// True if the track was created by a runtime, rather than received from a remote peer.
+    pub fn is_local(&self) -> bool {
+        self.runtime.is_some()
+    }
+
+    /// Whether the track is enabled, a disabled track carries black frames or silence.
+    pub fn enabled(&self) -> bool {
+        unsafe { raw::media_stream_track_get_enabled(self.raw) }
+    }
+
+    /// Enables or disables the track. Disabling a local track sends black frames or silence
+    /// to the remote peer, disabling a received track renders them to its sinks, the track
+    /// is not renegotiated either way.
+    pub fn set_enabled(&self, enabled: bool) {
+        unsafe { raw::media_stream_track_set_enabled(self.raw, enabled) }
+    }
+
+    /// Listens for the events of the track, which are only fired by the tracks
+    /// received from the remote peer.
+    pub fn on_event(&self) -> impl Stream<Item = TrackEvent> {
+        self.observer.events.subscribe()
+    }
+
+    /// The source of the track is temporarily unable to provide media,
+    /// such as when the remote peer stops sending.
+    pub fn on_mute(&self) -> impl Stream<Item = ()> {
+        self.on_event().filter_map(|event| async move { (event == TrackEvent::Mute).then_some(()) })
+    }
+
+    /// The source of the track provides media again.
+    pub fn on_unmute(&self) -> impl Stream<Item = ()> {
+        self.on_event().filter_map(|event| async move { (event == TrackEvent::Unmute).then_some(()) })
+    }
+
+    /// The track will never provide media again, such as when its transceiver is stopped.
+    pub fn on_ended(&self) -> impl Stream<Item = ()> {
+        self.on_event().filter_map(|event| async move { (event == TrackEvent::Ended).then_some(()) })
+    }
+
+    /// Sends a frame on a local video track, the frame is stamped with the time it is
+    /// written, so the caller paces the frames.
+    pub fn write_video_frame(&self, frame: &VideoFrame) -> Result<()> {
+        let raw_frame = frame.as_raw();
+        if unsafe { raw::media_stream_track_write_video_frame(self.raw, &raw_frame) } {
+            Ok(())
+        } else {
+            Err(Error::InvalidState(format!("the track {} is not a local video track", self.id)))
+        }
+    }
+
+    /// Sends 10ms of audio on a local audio track.
+    pub fn write_audio_frame(&self, frame: &AudioFrame) -> Result<()> {
+        let raw_frame = frame.as_raw();
+        if unsafe { raw::media_stream_track_write_audio_frame(self.raw, &raw_frame) } {
+            Ok(())
+        } else {
+            Err(Error::InvalidState(format!("the track {} is not a local audio track", self.id)))
+        }
+    }
+
+    /// Attaches a handler receiving every decoded frame of a video track,
+    /// returns None if the track is not a video track.
+    ///
+    /// Any number of handlers can be attached, such as a renderer and a recorder,
+    /// they share the frames, which are copied once from the native side.
+    pub fn add_video_sink(
+        &self,
+        handler: impl FnMut(Arc<VideoFrame>) + Send + 'static,
+    ) -> Option<MediaSink> {
+        let id = self.video_sinks.add(Box::new(handler), |ctx| unsafe {
+            raw::media_stream_track_add_video_sink(self.raw, ctx, on_video_frame)
+        })?;
+
+        Some(MediaSink {
+            attached: Attached::Video(self.video_sinks.clone()),
+            id,
+        })
+    }
+
+    /// Attaches a handler receiving every 10ms of audio of an audio track,
+    /// returns None if the track is not an audio track.
+    ///
+    /// Any number of handlers can be attached, they share the frames.
+    pub fn add_audio_sink(
+        &self,
+        handler: impl FnMut(Arc<AudioFrame>) + Send + 'static,
+    ) -> Option<MediaSink> {
+        let id = self.audio_sinks.add(Box::new(handler), |ctx| unsafe {
+            raw::media_stream_track_add_audio_sink(self.raw, ctx, on_audio_frame)
+        })?;
+
+        Some(MediaSink {
+            attached: Attached::Audio(self.audio_sinks.clone()),
+            id,
+        })
+    }
+}
+
+impl Drop for MediaStreamTrack {
+    fn drop(&mut self) {
+        unsafe { raw::rtc_free_media_stream_track(self.raw) }
+    }
+}
@@ -1,14 +1,91 @@
-mod raw;
+pub(crate) mod raw;
+mod codec;
+mod data_channel;
+mod dispatcher;
+pub(crate) mod encoder;
+mod frame;
+pub(crate) mod media_stream_track;
+pub(crate) mod observer;
+mod promise;
+mod rtp;
+mod runtime;
+mod socket;
+mod transceiver;
+mod transform;
+mod transport;
+
+use crate::ice::{Candidate, IceServerUrl, RelayProtocol};
+use crate::sdp::{self, SessionDescription};
+use crate::{Error, Result};
 
-use anyhow::{Result, anyhow};
 use libc::*;
-use std::ffi::CString;
+use std::ffi::{CStr, CString};
 use std::future::Future;
 use std::pin::Pin;
-use std::sync::atomic::{AtomicPtr, Ordering};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::task::*;
-use futures::task::AtomicWaker;
+use std::time::Duration;
+use futures::{Stream, StreamExt};
+use observer::Observer;
+use promise::Promise;
+use socket::ExternalSocketFactory;
+
+pub use raw::{
+    BundelPolicy,
+    ConnectionState,
+    ContinualGatheringPolicy,
+    DataChannelState,
+    DegradationPreference,
+    DtlsTransportState,
+    FrameCodec,
+    IceConnectionState,
+    IceGatheringState,
+    IceTransportPolicy,
+    IceTransportState,
+    LogSeverity,
+    MediaType,
+    Priority,
+    ProxyType,
+    RtcpMuxPolicy,
+    RtcSessionDescriptionType,
+    RtpDirection,
+    SignalingState,
+    TcpCandidatePolicy,
+    TrackEvent,
+    TransceiverDirection,
+    TransportError,
+};
+
+pub use codec::{
+    EncodedImage,
+    ExternalVideoDecoder,
+    ExternalVideoDecoderFactory,
+    ExternalVideoEncoder,
+    ExternalVideoEncoderFactory,
+    VideoEncoderSettings,
+    VideoFormat,
+};
+pub use data_channel::{DataChannelInit, DataChannelIo, DataChannelMessage, DataChannelMessages, RTCDataChannel};
+pub use frame::{AudioFrame, VideoFrame};
+pub use media_stream_track::{MediaSink, MediaStreamTrack};
+pub use observer::{CallbackPanic, CandidateFilter, PeerEvent};
+pub use rtp::{RtpPacket, RtpTap, RtpTapMode};
+pub use runtime::{PlayoutDelay, WebRtcRuntime, WebRtcRuntimeBuilder};
+pub use socket::{PacketReceiver, PacketSocket, PacketSocketFactory};
+#[cfg(feature = "dlopen")]
+pub use runtime::{is_library_loaded, load_library};
+pub use transceiver::{
+    EncodingParameters,
+    RtpCapabilities,
+    RtpCodecCapability,
+    RtpHeaderExtensionCapability,
+    RtpReceiver,
+    RtpSender,
+    RtpTransceiver,
+};
+pub use transform::{EncodedFrame, FrameTransform};
+pub use transport::{RTCDtlsTransport, RTCIceTransport, TransportEvent};
 
 #[derive(Default)]
 pub struct RTCIceServer {
@@ -19,22 +96,43 @@ pub struct RTCIceServer {
 }
 
 impl RTCIceServer {
-    pub fn set_credential(&mut self, credential: &str) {
-        self.credential = Some(CString::new(credential).unwrap());
+    pub fn set_credential(&mut self, credential: &str) -> Result<()> {
+        self.credential = Some(CString::new(credential)?);
+        Ok(())
     }
 
-    pub fn set_username(&mut self, username: &str) {
-        self.username = Some(CString::new(username).unwrap());
+    pub fn set_username(&mut self, username: &str) -> Result<()> {
+        self.username = Some(CString::new(username)?);
+        Ok(())
     }
 
-    pub fn set_urls(&mut self, urls: &[&str]) {
-        self.raw_urls = urls.iter().map(|url| CString::new(*url).unwrap()).collect();
+    /// The urls of the server, such as "stun:stun.l.google.com:19302" or
+    /// "turn:turn.example.com:3478?transport=tcp", every url is validated.
+    pub fn set_urls(&mut self, urls: &[&str]) -> Result<()> {
+        for url in urls {
+            url.parse::<IceServerUrl>()?;
+        }
+
+        self.raw_urls = urls
+            .iter()
+            .map(|url| CString::new(*url))
+            .collect::<Result<_, _>>()?;
         self.urls = Some(
             self.raw_urls
                 .iter()
                 .map(|url| url.as_c_str().as_ptr())
                 .collect(),
         );
+
+        Ok(())
+    }
+
+    /// The protocols the TURN urls of the server reach it over, in the order of the urls.
+    pub fn relay_protocols(&self) -> Vec<RelayProtocol> {
+        self.raw_urls
+            .iter()
+            .filter_map(|url| url.to_str().ok()?.parse::<IceServerUrl>().ok()?.relay_protocol())
+            .collect()
     }
 
     pub fn as_raw(&self) -> raw::RTCIceServer {
@@ -50,6 +148,123 @@ impl RTCIceServer {
     }
 }
 
+/// A builder of RTCIceServer which fails fast on a malformed url,
+/// or on a TURN server without credentials, instead of at connection time.
+#[derive(Clone, Debug, Default)]
+pub struct RTCIceServerBuilder {
+    urls: Vec<String>,
+    username: Option<String>,
+    credential: Option<String>,
+}
+
+impl RTCIceServerBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn url(mut self, url: &str) -> Self {
+        self.urls.push(url.to_string());
+        self
+    }
+
+    /// Adds a typed url, such as one made from a parsed url with a different transport.
+    pub fn ice_server_url(mut self, url: &IceServerUrl) -> Self {
+        self.urls.push(url.to_string());
+        self
+    }
+
+    /// Adds a TURN url reaching the server over the given protocol, see `IceServerUrl::turn`.
+    pub fn turn(self, host: &str, port: Option<u16>, protocol: RelayProtocol) -> Self {
+        self.ice_server_url(&IceServerUrl::turn(host, port, protocol))
+    }
+
+    pub fn username(mut self, username: &str) -> Self {
+        self.username = Some(username.to_string());
+        self
+    }
+
+    pub fn credential(mut self, credential: &str) -> Self {
+        self.credential = Some(credential.to_string());
+        self
+    }
+
+    pub fn build(self) -> Result<RTCIceServer> {
+        if self.urls.is_empty() {
+            return Err(Error::InvalidConfiguration("ice server without urls".to_string()));
+        }
+
+        for url in &self.urls {
+            let url: IceServerUrl = url.parse()?;
+            if url.scheme.is_turn() && (self.username.is_none() || self.credential.is_none()) {
+                return Err(Error::InvalidConfiguration(format!(
+                    "turn server without credentials: {}",
+                    url
+                )));
+            }
+        }
+
+        let mut server = RTCIceServer::default();
+        server.set_urls(&self.urls.iter().map(|url| url.as_str()).collect::<Vec<_>>())?;
+        if let Some(username) = &self.username {
+            server.set_username(username)?;
+        }
+
+        if let Some(credential) = &self.credential {
+            server.set_credential(credential)?;
+        }
+
+        Ok(server)
+    }
+}
+
+pub struct RTCProxy {
+    r#type: raw::ProxyType,
+    hostname: CString,
+    port: u16,
+    username: Option<CString>,
+    password: Option<CString>,
+    user_agent: Option<CString>,
+}
+
+impl RTCProxy {
+    pub fn new(r#type: raw::ProxyType, hostname: &str, port: u16) -> Result<Self> {
+        Ok(Self {
+            r#type,
+            hostname: CString::new(hostname)?,
+            port,
+            username: None,
+            password: None,
+            user_agent: None,
+        })
+    }
+
+    pub fn set_username(&mut self, username: &str) -> Result<()> {
+        self.username = Some(CString::new(username)?);
+        Ok(())
+    }
+
+    pub fn set_password(&mut self, password: &str) -> Result<()> {
+        self.password = Some(CString::new(password)?);
+        Ok(())
+    }
+
+    pub fn set_user_agent(&mut self, user_agent: &str) -> Result<()> {
+        self.user_agent = Some(CString::new(user_agent)?);
+        Ok(())
+    }
+
+    pub fn as_raw(&self) -> raw::RTCProxy {
+        raw::RTCProxy {
+            r#type: self.r#type,
+            hostname: self.hostname.as_c_str().as_ptr(),
+            port: self.port as c_int,
+            username: self.username.as_ref().map(|u| u.as_c_str().as_ptr()),
+            password: self.password.as_ref().map(|p| p.as_c_str().as_ptr()),
+            user_agent: self.user_agent.as_ref().map(|u| u.as_c_str().as_ptr()),
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct RTCConfiguration {
     pub bundle_policy: Option<raw::BundelPolicy>,
@@ -58,9 +273,65 @@ pub struct RTCConfiguration {
     pub rtcp_mux_policy: Option<raw::RtcpMuxPolicy>,
     pub ice_servers: Option<Vec<raw::RTCIceServer>>,
     pub ice_candidate_pool_size: Option<u8>,
+    pub port_range: Option<(u16, u16)>,
+    raw_ice_servers: Vec<RTCIceServer>,
+    allowed_interfaces: Option<Vec<*const c_char>>,
+    raw_allowed_interfaces: Vec<CString>,
+    denied_interfaces: Option<Vec<*const c_char>>,
+    raw_denied_interfaces: Vec<CString>,
+    proxy: Option<raw::RTCProxy>,
+    raw_proxy: Option<RTCProxy>,
+    socket_factory: Option<raw::RTCPacketSocketFactory>,
+    raw_socket_factory: Option<ExternalSocketFactory>,
+    pub continual_gathering_policy: Option<raw::ContinualGatheringPolicy>,
+    pub ice_keepalive: IceKeepalive,
+    pub tcp_candidate_policy: Option<raw::TcpCandidatePolicy>,
+    pub mdns_obfuscation: bool,
+    pub address_policy: AddressPolicy,
+    /// Kept on the rust side, the native configuration does not carry them.
+    pub establishment_timeouts: EstablishmentTimeouts,
+    /// The transceivers added once the connection is created, before any track,
+    /// see `RTCPeerConnection::add_transceiver`.
+    pub transceivers: Vec<(MediaType, TransceiverDirection)>,
 }
 
 impl RTCConfiguration {
+    /// A call with a single audio transceiver sending and receiving, on a single transport.
+    pub fn audio_call() -> Self {
+        Self {
+            transceivers: vec![(MediaType::Audio, TransceiverDirection::SendRecv)],
+            ..Self::single_transport()
+        }
+    }
+
+    /// A connection which only receives, such as the viewer of a broadcast, with a receive-only
+    /// audio and video transceiver, so the offer asks for both without any local track.
+    pub fn recv_only() -> Self {
+        Self {
+            transceivers: vec![
+                (MediaType::Audio, TransceiverDirection::RecvOnly),
+                (MediaType::Video, TransceiverDirection::RecvOnly),
+            ],
+            ..Self::single_transport()
+        }
+    }
+
+    /// A connection without media, which only carries data channels, a runtime built with
+    /// `WebRtcRuntimeBuilder::data_only` also skips the media engines.
+    pub fn data_only() -> Self {
+        Self::single_transport()
+    }
+
+    /// Every media section on one transport, with RTCP on the RTP port, which is what the
+    /// presets negotiate with the browsers and the media servers.
+    fn single_transport() -> Self {
+        Self {
+            bundle_policy: Some(raw::BundelPolicy::MaxBundle),
+            rtcp_mux_policy: Some(raw::RtcpMuxPolicy::Require),
+            ..Default::default()
+        }
+    }
+
     pub fn set_bundle_policy(&mut self, bundle_policy: raw::BundelPolicy) {
         self.bundle_policy = Some(bundle_policy);
     }
@@ -69,8 +340,9 @@ impl RTCConfiguration {
         self.ice_transport_policy = Some(ice_transport_policy);
     }
 
-    pub fn set_peer_identity(&mut self, peer_identity: &str) {
-        self.peer_identity = Some(CString::new(peer_identity).unwrap());
+    pub fn set_peer_identity(&mut self, peer_identity: &str) -> Result<()> {
+        self.peer_identity = Some(CString::new(peer_identity)?);
+        Ok(())
     }
 
     pub fn set_rtcp_mux_policy(&mut self, rtcp_mux_policy: raw::RtcpMuxPolicy) {
@@ -79,25 +351,464 @@ impl RTCConfiguration {
 
     pub fn set_ice_servers(&mut self, ice_servers: Vec<RTCIceServer>) {
         self.ice_servers = Some(ice_servers.iter().map(|i| i.as_raw()).collect());
+        self.raw_ice_servers = ice_servers;
     }
 
     pub fn set_ice_candidate_pool_size(&mut self, ice_candidate_pool_size: u8) {
         self.ice_candidate_pool_size = Some(ice_candidate_pool_size);
     }
 
+    /// Restricts the local ports used for ICE to the inclusive range `min..=max`,
+    /// so that the connection fits the ports opened on a firewall.
+    pub fn set_port_range(&mut self, min: u16, max: u16) {
+        self.port_range = Some((min, max));
+    }
+
+    /// Only gathers candidates on the network interfaces with the given names,
+    /// such as "eth0" or "Ethernet 2".
+    pub fn set_allowed_interfaces(&mut self, interfaces: &[&str]) -> Result<()> {
+        self.raw_allowed_interfaces = interfaces
+            .iter()
+            .map(|i| CString::new(*i))
+            .collect::<Result<_, _>>()?;
+        self.allowed_interfaces = Some(
+            self.raw_allowed_interfaces
+                .iter()
+                .map(|i| i.as_c_str().as_ptr())
+                .collect(),
+        );
+
+        Ok(())
+    }
+
+    /// Never gathers candidates on the network interfaces with the given names,
+    /// the deny list takes precedence over the allow list.
+    pub fn set_denied_interfaces(&mut self, interfaces: &[&str]) -> Result<()> {
+        self.raw_denied_interfaces = interfaces
+            .iter()
+            .map(|i| CString::new(*i))
+            .collect::<Result<_, _>>()?;
+        self.denied_interfaces = Some(
+            self.raw_denied_interfaces
+                .iter()
+                .map(|i| i.as_c_str().as_ptr())
+                .collect(),
+        );
+
+        Ok(())
+    }
+
+    /// Establishes the TCP based connections, such as TURN over TCP or TLS, through a proxy,
+    /// which is commonly the only way out of corporate networks.
+    pub fn set_proxy(&mut self, proxy: RTCProxy) {
+        self.proxy = Some(proxy.as_raw());
+        self.raw_proxy = Some(proxy);
+    }
+
+    /// Sends and receives the UDP packets of the connection through the sockets of the
+    /// application rather than those of the system, see `PacketSocketFactory`.
+    pub fn set_socket_factory(&mut self, factory: impl PacketSocketFactory + 'static) {
+        self.set_external_socket_factory(ExternalSocketFactory::new(factory));
+    }
+
+    fn set_external_socket_factory(&mut self, factory: ExternalSocketFactory) {
+        self.socket_factory = Some(factory.as_raw());
+        self.raw_socket_factory = Some(factory);
+    }
+
+    /// Keeps gathering candidates as the network interfaces change, see `on_network_changed`.
+    pub fn set_continual_gathering_policy(&mut self, policy: raw::ContinualGatheringPolicy) {
+        self.continual_gathering_policy = Some(policy);
+    }
+
+    pub fn set_ice_keepalive(&mut self, keepalive: IceKeepalive) {
+        self.ice_keepalive = keepalive;
+    }
+
+    /// Disables the TCP host candidates for the lowest latency, or only gathers the active ones.
+    pub fn set_tcp_candidate_policy(&mut self, policy: raw::TcpCandidatePolicy) {
+        self.tcp_candidate_policy = Some(policy);
+    }
+
+    /// Hides the private addresses of the host candidates behind ".local" names, as browsers do,
+    /// it is off by default, a server with a public address gains nothing from it and only
+    /// delays the connectivity checks of the remote peer by a multicast DNS lookup.
+    ///
+    /// The names are answered by a responder joined to the multicast group, when the mDNS port
+    /// can not be bound, the addresses are left as they are.
+    pub fn set_mdns_obfuscation(&mut self, enabled: bool) {
+        self.mdns_obfuscation = enabled;
+    }
+
+    pub fn set_address_policy(&mut self, policy: AddressPolicy) {
+        self.address_policy = policy;
+    }
+
+    pub fn set_establishment_timeouts(&mut self, timeouts: EstablishmentTimeouts) {
+        self.establishment_timeouts = timeouts;
+    }
+
+    /// The urls of the ICE servers, without their credentials.
+    pub(crate) fn ice_server_urls(&self) -> Vec<String> {
+        self.raw_ice_servers
+            .iter()
+            .flat_map(|server| server.raw_urls.iter())
+            .map(|url| url.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    /// Copies a configuration owned by the native side.
+    pub(crate) fn from_raw(raw: *const raw::RTCPeerConnectionConfigure) -> Result<Self> {
+        let raw = unsafe { &*raw };
+        let to_str = |s: *const c_char| unsafe { CStr::from_ptr(s) }.to_string_lossy();
+        let to_strs = |strs: Option<*const *const c_char>, size: c_int| match strs {
+            Some(strs) if size > 0 => unsafe { std::slice::from_raw_parts(strs, size as usize) }
+                .iter()
+                .map(|s| to_str(*s).into_owned())
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        let mut config = Self {
+            bundle_policy: raw.bundle_policy,
+            ice_transport_policy: raw.ice_transport_policy,
+            rtcp_mux_policy: raw.rtcp_mux_policy,
+            ice_candidate_pool_size: Some(raw.ice_candidate_pool_size as u8),
+            continual_gathering_policy: raw.continual_gathering_policy,
+            tcp_candidate_policy: raw.tcp_candidate_policy,
+            ice_keepalive: IceKeepalive {
+                stun_keepalive_interval: from_millis(raw.stun_keepalive_interval),
+                check_interval: from_millis(raw.ice_check_interval),
+                receiving_timeout: from_millis(raw.ice_receiving_timeout),
+                inactive_timeout: from_millis(raw.ice_inactive_timeout),
+            },
+            ..Default::default()
+        };
+
+        if let Some(peer_identity) = raw.peer_identity {
+            config.set_peer_identity(&to_str(peer_identity))?;
+        }
+
+        if raw.port_range_min != 0 || raw.port_range_max != 0 {
+            config.set_port_range(raw.port_range_min as u16, raw.port_range_max as u16);
+        }
+
+        if raw.allowed_interfaces.is_some() {
+            let interfaces = to_strs(raw.allowed_interfaces, raw.allowed_interfaces_size);
+            config.set_allowed_interfaces(&interfaces.iter().map(|i| i.as_str()).collect::<Vec<_>>())?;
+        }
+
+        if raw.denied_interfaces.is_some() {
+            let interfaces = to_strs(raw.denied_interfaces, raw.denied_interfaces_size);
+            config.set_denied_interfaces(&interfaces.iter().map(|i| i.as_str()).collect::<Vec<_>>())?;
+        }
+
+        if let Some(ice_servers) = raw.ice_servers {
+            let ice_servers = unsafe {
+                std::slice::from_raw_parts(ice_servers, raw.ice_servers_size as usize)
+            };
+
+            config.set_ice_servers(
+                ice_servers
+                    .iter()
+                    .map(|raw| {
+                        let mut server = RTCIceServer::default();
+                        let urls = to_strs(raw.urls, raw.urls_size);
+                        server.set_urls(&urls.iter().map(|u| u.as_str()).collect::<Vec<_>>())?;
+                        if let Some(username) = raw.username {
+                            server.set_username(&to_str(username))?;
+                        }
+
+                        if let Some(credential) = raw.credential {
+                            server.set_credential(&to_str(credential))?;
+                        }
+
+                        Ok(server)
+                    })
+                    .collect::<Result<_>>()?,
+            );
+        }
+
+        Ok(config)
+    }
+
     pub fn as_raw(&self) -> raw::RTCPeerConnectionConfigure {
         raw::RTCPeerConnectionConfigure {
             bundle_policy: self.bundle_policy,
             ice_transport_policy: self.ice_transport_policy,
             rtcp_mux_policy: self.rtcp_mux_policy,
             peer_identity: self.peer_identity.as_ref().map(|s| s.as_c_str().as_ptr()),
-            ice_candidate_pool_size: self.ice_candidate_pool_size.map(|i| i as c_int),
+            ice_candidate_pool_size: self.ice_candidate_pool_size.map(|i| i as c_int).unwrap_or(0),
             ice_servers: self.ice_servers.as_ref().map(|i| i.as_ptr()),
             ice_servers_size: match &self.ice_servers {
                 Some(i) => i.len() as c_int,
                 None => 0,
             },
+            port_range_min: self.port_range.map(|(min, _)| min as c_int).unwrap_or(0),
+            port_range_max: self.port_range.map(|(_, max)| max as c_int).unwrap_or(0),
+            allowed_interfaces: self.allowed_interfaces.as_ref().map(|i| i.as_ptr()),
+            allowed_interfaces_size: match &self.allowed_interfaces {
+                Some(i) => i.len() as c_int,
+                None => 0,
+            },
+            denied_interfaces: self.denied_interfaces.as_ref().map(|i| i.as_ptr()),
+            denied_interfaces_size: match &self.denied_interfaces {
+                Some(i) => i.len() as c_int,
+                None => 0,
+            },
+            proxy: self.proxy.as_ref().map(|p| p as *const raw::RTCProxy),
+            continual_gathering_policy: self.continual_gathering_policy,
+            stun_keepalive_interval: to_millis(self.ice_keepalive.stun_keepalive_interval),
+            ice_check_interval: to_millis(self.ice_keepalive.check_interval),
+            ice_receiving_timeout: to_millis(self.ice_keepalive.receiving_timeout),
+            ice_inactive_timeout: to_millis(self.ice_keepalive.inactive_timeout),
+            tcp_candidate_policy: self.tcp_candidate_policy,
+            mdns_obfuscation: self.mdns_obfuscation,
+            disable_ipv6: !self.address_policy.ipv6,
+            disable_link_local: !self.address_policy.link_local,
+            disable_vpn: !self.address_policy.vpn,
+            socket_factory: self.socket_factory.as_ref().map(|f| f as *const raw::RTCPacketSocketFactory),
+        }
+    }
+}
+
+/// A builder of RTCConfiguration which validates the combinations of options,
+/// such as a relay-only transport policy without any TURN server, instead of leaving
+/// the connection to fail at runtime. The setters of RTCConfiguration remain available,
+/// but they do not validate anything.
+#[derive(Default)]
+pub struct RTCConfigurationBuilder {
+    bundle_policy: Option<raw::BundelPolicy>,
+    ice_transport_policy: Option<raw::IceTransportPolicy>,
+    peer_identity: Option<String>,
+    rtcp_mux_policy: Option<raw::RtcpMuxPolicy>,
+    ice_servers: Vec<RTCIceServer>,
+    ice_candidate_pool_size: Option<usize>,
+    port_range: Option<(u16, u16)>,
+    allowed_interfaces: Vec<String>,
+    denied_interfaces: Vec<String>,
+    proxy: Option<RTCProxy>,
+    socket_factory: Option<ExternalSocketFactory>,
+    continual_gathering_policy: Option<raw::ContinualGatheringPolicy>,
+    ice_keepalive: IceKeepalive,
+    tcp_candidate_policy: Option<raw::TcpCandidatePolicy>,
+    mdns_obfuscation: bool,
+    address_policy: AddressPolicy,
+    establishment_timeouts: EstablishmentTimeouts,
+    transceivers: Vec<(MediaType, TransceiverDirection)>,
+}
+
+impl RTCConfigurationBuilder {
+    /// The largest pool of pre-gathered candidates, as allowed by the specification.
+    pub const MAX_ICE_CANDIDATE_POOL_SIZE: usize = u8::MAX as usize;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn bundle_policy(mut self, bundle_policy: raw::BundelPolicy) -> Self {
+        self.bundle_policy = Some(bundle_policy);
+        self
+    }
+
+    pub fn ice_transport_policy(mut self, ice_transport_policy: raw::IceTransportPolicy) -> Self {
+        self.ice_transport_policy = Some(ice_transport_policy);
+        self
+    }
+
+    pub fn peer_identity(mut self, peer_identity: &str) -> Self {
+        self.peer_identity = Some(peer_identity.to_string());
+        self
+    }
+
+    pub fn rtcp_mux_policy(mut self, rtcp_mux_policy: raw::RtcpMuxPolicy) -> Self {
+        self.rtcp_mux_policy = Some(rtcp_mux_policy);
+        self
+    }
+
+    /// Adds an ICE server, usually made with RTCIceServerBuilder.
+    pub fn ice_server(mut self, ice_server: RTCIceServer) -> Self {
+        self.ice_servers.push(ice_server);
+        self
+    }
+
+    pub fn ice_candidate_pool_size(mut self, ice_candidate_pool_size: usize) -> Self {
+        self.ice_candidate_pool_size = Some(ice_candidate_pool_size);
+        self
+    }
+
+    pub fn port_range(mut self, min: u16, max: u16) -> Self {
+        self.port_range = Some((min, max));
+        self
+    }
+
+    pub fn allowed_interfaces(mut self, interfaces: &[&str]) -> Self {
+        self.allowed_interfaces = interfaces.iter().map(|i| i.to_string()).collect();
+        self
+    }
+
+    pub fn denied_interfaces(mut self, interfaces: &[&str]) -> Self {
+        self.denied_interfaces = interfaces.iter().map(|i| i.to_string()).collect();
+        self
+    }
+
+    pub fn proxy(mut self, proxy: RTCProxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    pub fn socket_factory(mut self, factory: impl PacketSocketFactory + 'static) -> Self {
+        self.socket_factory = Some(ExternalSocketFactory::new(factory));
+        self
+    }
+
+    pub fn continual_gathering_policy(mut self, policy: raw::ContinualGatheringPolicy) -> Self {
+        self.continual_gathering_policy = Some(policy);
+        self
+    }
+
+    pub fn ice_keepalive(mut self, keepalive: IceKeepalive) -> Self {
+        self.ice_keepalive = keepalive;
+        self
+    }
+
+    pub fn tcp_candidate_policy(mut self, policy: raw::TcpCandidatePolicy) -> Self {
+        self.tcp_candidate_policy = Some(policy);
+        self
+    }
+
+    pub fn mdns_obfuscation(mut self, enabled: bool) -> Self {
+        self.mdns_obfuscation = enabled;
+        self
+    }
+
+    pub fn address_policy(mut self, policy: AddressPolicy) -> Self {
+        self.address_policy = policy;
+        self
+    }
+
+    pub fn establishment_timeouts(mut self, timeouts: EstablishmentTimeouts) -> Self {
+        self.establishment_timeouts = timeouts;
+        self
+    }
+
+    /// Adds a transceiver once the connection is created, the calls add up.
+    pub fn transceiver(mut self, kind: MediaType, direction: TransceiverDirection) -> Self {
+        self.transceivers.push((kind, direction));
+        self
+    }
+
+    pub fn build(self) -> Result<RTCConfiguration> {
+        let invalid = |reason: String| Err(Error::InvalidConfiguration(reason));
+
+        if let Some(size) = self.ice_candidate_pool_size {
+            if size > Self::MAX_ICE_CANDIDATE_POOL_SIZE {
+                return invalid(format!(
+                    "ice candidate pool size {} is over {}",
+                    size,
+                    Self::MAX_ICE_CANDIDATE_POOL_SIZE
+                ));
+            }
+        }
+
+        if let Some((min, max)) = self.port_range {
+            if min == 0 || min > max {
+                return invalid(format!("port range {}-{} is empty", min, max));
+            }
+        }
+
+        for phase in EstablishmentPhase::ALL {
+            if self.establishment_timeouts.get(phase) == Some(Duration::ZERO) {
+                return invalid(format!("the timeout of the {} is zero", phase));
+            }
+        }
+
+        let keepalive = &self.ice_keepalive;
+        for (name, duration) in keepalive.durations() {
+            if let Some(duration) = duration {
+                if duration.is_zero() || duration.as_millis() > c_int::MAX as u128 {
+                    return invalid(format!("the {} of {:?} is out of range", name, duration));
+                }
+            }
+        }
+
+        // a pair whose checks are further apart than its timeouts is lost between two checks.
+        if let Some(interval) = keepalive.check_interval {
+            let timeouts = [keepalive.receiving_timeout, keepalive.inactive_timeout];
+            if timeouts.into_iter().flatten().any(|timeout| timeout <= interval) {
+                return invalid(format!("the ice check interval of {:?} is not under the timeouts", interval));
+            }
+        }
+
+        // the proxy only carries TCP, which the sockets of the application do not.
+        if self.proxy.is_some() && self.socket_factory.is_some() {
+            return invalid("proxy with a packet socket factory, which only creates udp sockets".to_string());
+        }
+
+        let mut allowed = self.allowed_interfaces.iter();
+        if let Some(interface) = allowed.find(|i| self.denied_interfaces.contains(i)) {
+            return invalid(format!("interface {} is both allowed and denied", interface));
+        }
+
+        // a relay-only connection can not gather a single candidate without a TURN server.
+        if matches!(self.ice_transport_policy, Some(raw::IceTransportPolicy::Relay)) {
+            let mut urls = self.ice_servers.iter().flat_map(|server| &server.raw_urls);
+            let has_turn = urls.any(|url| {
+                url.to_str()
+                    .ok()
+                    .and_then(|url| url.parse::<IceServerUrl>().ok())
+                    .map(|url| url.scheme.is_turn())
+                    .unwrap_or(false)
+            });
+
+            if !has_turn {
+                return invalid("relay transport policy without a turn server".to_string());
+            }
+        }
+
+        let mut config = RTCConfiguration {
+            bundle_policy: self.bundle_policy,
+            ice_transport_policy: self.ice_transport_policy,
+            rtcp_mux_policy: self.rtcp_mux_policy,
+            ice_candidate_pool_size: self.ice_candidate_pool_size.map(|size| size as u8),
+            port_range: self.port_range,
+            continual_gathering_policy: self.continual_gathering_policy,
+            ice_keepalive: self.ice_keepalive,
+            tcp_candidate_policy: self.tcp_candidate_policy,
+            mdns_obfuscation: self.mdns_obfuscation,
+            address_policy: self.address_policy,
+            establishment_timeouts: self.establishment_timeouts,
+            transceivers: self.transceivers,
+            ..Default::default()
+        };
+
+        if let Some(peer_identity) = &self.peer_identity {
+            config.set_peer_identity(peer_identity)?;
+        }
+
+        if !self.allowed_interfaces.is_empty() {
+            let interfaces = self.allowed_interfaces.iter().map(|i| i.as_str()).collect::<Vec<_>>();
+            config.set_allowed_interfaces(&interfaces)?;
+        }
+
+        if !self.denied_interfaces.is_empty() {
+            let interfaces = self.denied_interfaces.iter().map(|i| i.as_str()).collect::<Vec<_>>();
+            config.set_denied_interfaces(&interfaces)?;
         }
+
+        if let Some(proxy) = self.proxy {
+            config.set_proxy(proxy);
+        }
+
+        if let Some(factory) = self.socket_factory {
+            config.set_external_socket_factory(factory);
+        }
+
+        if !self.ice_servers.is_empty() {
+            config.set_ice_servers(self.ice_servers);
+        }
+
+        Ok(config)
     }
 }
 
@@ -107,11 +818,43 @@ pub struct RTCSessionDescription {
 }
 
 impl RTCSessionDescription {
-    pub fn from_raw(raw: *const raw::RTCSessionDescription) -> Self {
+    /// Creates a description, such as an offer received from the remote peer.
+    pub fn new(r#type: raw::RtcSessionDescriptionType, sdp: &str) -> Result<Self> {
+        Ok(Self {
+            r#type,
+            sdp: CString::new(sdp)?,
+        })
+    }
+
+    /// An offer received from the remote peer.
+    pub fn offer(sdp: &str) -> Result<Self> {
+        Self::new(raw::RtcSessionDescriptionType::Offer, sdp)
+    }
+
+    /// An answer received from the remote peer.
+    pub fn answer(sdp: &str) -> Result<Self> {
+        Self::new(raw::RtcSessionDescriptionType::Answer, sdp)
+    }
+
+    /// A provisional answer, which a final answer replaces later.
+    pub fn pranswer(sdp: &str) -> Result<Self> {
+        Self::new(raw::RtcSessionDescriptionType::PrAnswer, sdp)
+    }
+
+    /// Rolls the pending offer back, to the previous stable state.
+    pub fn rollback() -> Self {
+        Self {
+            r#type: raw::RtcSessionDescriptionType::Rollback,
+            sdp: CString::default(),
+        }
+    }
+
+    /// Copies a description owned by the native side, which stays owned by the caller.
+    pub(crate) fn copy_from_raw(raw: *const raw::RTCSessionDescription) -> Self {
         let raw = unsafe { &*raw };
-        Self { 
-            r#type: raw.r#type, 
-            sdp: unsafe { CString::from_raw(raw.sdp as *mut c_char) }
+        Self {
+            r#type: raw.r#type,
+            sdp: unsafe { CStr::from_ptr(raw.sdp) }.to_owned(),
         }
     }
 
@@ -122,118 +865,1348 @@ impl RTCSessionDescription {
     pub fn get_sdp(&self) -> Result<&str> {
         Ok(self.sdp.to_str()?)
     }
-}
 
-pub struct RTCPeerConnection {
-    raw: *const raw::RTCPeerConnection,
-    config: Box<raw::RTCPeerConnectionConfigure>,
-}
+    /// Parses the description, edits it and returns the edited description of the same type,
+    /// such as an offer whose bandwidth is limited before it is set as the local description.
+    pub fn munge(&self, f: impl FnOnce(&mut SessionDescription)) -> Result<Self> {
+        let mut desc = self.get_sdp()?.parse::<SessionDescription>()?;
+        f(&mut desc);
+        Self::new(self.r#type, &desc.to_string())
+    }
 
-impl RTCPeerConnection {
-    pub fn new(config: &RTCConfiguration) -> Result<Self> {
-        let config = Box::new(config.as_raw());
-        let raw = unsafe { raw::create_rtc_peer_connection(config.as_ref()) };
-        if raw.is_null() {
-            return Err(anyhow!("crate RTCPeerConnection failed!"))
+    pub fn as_raw(&self) -> raw::RTCSessionDescription {
+        raw::RTCSessionDescription {
+            r#type: self.r#type,
+            sdp: self.sdp.as_c_str().as_ptr(),
         }
-
-        Ok(Self {
-            raw,
-            config,
-        })
     }
+}
 
-    pub fn create_offer(&self) -> CreateSessionDescription {
-        CreateSessionDescription::new(self.raw, CreateSessionDescriptionKind::Offer)
+/// The options of `create_offer_with` and `create_answer_with`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RTCOfferAnswerOptions {
+    /// Generates new ICE credentials, so that applying the offer restarts ICE.
+    pub ice_restart: bool,
+    /// Lets the audio codecs skip the silences, true by default.
+    pub voice_activity_detection: bool,
+    /// Legacy, offers to receive audio even without a local audio track,
+    /// `None` leaves it to the transceivers. Ignored by answers.
+    pub offer_to_receive_audio: Option<bool>,
+    /// Legacy, offers to receive video even without a local video track,
+    /// `None` leaves it to the transceivers. Ignored by answers.
+    pub offer_to_receive_video: Option<bool>,
+}
+
+impl Default for RTCOfferAnswerOptions {
+    fn default() -> Self {
+        Self {
+            ice_restart: false,
+            voice_activity_detection: true,
+            offer_to_receive_audio: None,
+            offer_to_receive_video: None,
+        }
     }
+}
 
-    pub fn create_answer(&self) -> CreateSessionDescription {
-        CreateSessionDescription::new(self.raw, CreateSessionDescriptionKind::Answer)
+impl RTCOfferAnswerOptions {
+    pub fn as_raw(&self) -> raw::RTCOfferAnswerOptions {
+        let offer_to_receive = |offer: Option<bool>| offer.map(|o| o as c_int).unwrap_or(-1);
+        raw::RTCOfferAnswerOptions {
+            ice_restart: self.ice_restart,
+            voice_activity_detection: self.voice_activity_detection,
+            offer_to_receive_audio: offer_to_receive(self.offer_to_receive_audio),
+            offer_to_receive_video: offer_to_receive(self.offer_to_receive_video),
+        }
     }
 }
 
-pub struct CreateSessionDescriptionContext {
-    callback: Box<dyn FnMut(*const raw::RTCSessionDescription)>,
+/// Bitrate limits of a connection in bits per second, `None` leaves it without a limit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BitrateSettings {
+    pub min_bitrate: Option<u32>,
+    pub start_bitrate: Option<u32>,
+    pub max_bitrate: Option<u32>,
 }
 
-#[derive(PartialEq, PartialOrd)]
-pub enum CreateSessionDescriptionKind {
-    Offer,
-    Answer,
+/// The phases of the establishment of a connection, which can be given a timeout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EstablishmentPhase {
+    /// From the start of the gathering to its completion.
+    IceGathering,
+    /// From the start of the connectivity checks to the first connected candidate pair.
+    IceConnection,
+    /// From the connection of ICE to the completion of the DTLS handshake.
+    DtlsHandshake,
 }
 
-pub struct CreateSessionDescription {
-    kind: CreateSessionDescriptionKind,
-    waker: Arc<AtomicWaker>,
-    peer: *const raw::RTCPeerConnection,
-    desc: Arc<AtomicPtr<raw::RTCSessionDescription>>,
-    begin: bool,
+impl EstablishmentPhase {
+    pub(crate) const ALL: [Self; 3] = [Self::IceGathering, Self::IceConnection, Self::DtlsHandshake];
+
+    pub(crate) fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl std::fmt::Display for EstablishmentPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::IceGathering => "ice gathering",
+            Self::IceConnection => "ice connectivity checks",
+            Self::DtlsHandshake => "dtls handshake",
+        })
+    }
+}
+
+/// The longest time each phase of the establishment may take, `None` waits for as long as
+/// libwebrtc does, which is several minutes when a TURN server is unreachable.
+///
+/// A phase which does not complete in time moves the connection to the `Failed` state,
+/// see `RTCPeerConnection::establishment_error`. The timeouts apply to every negotiation,
+/// including the ICE restarts.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EstablishmentTimeouts {
+    pub ice_gathering: Option<Duration>,
+    pub ice_connection: Option<Duration>,
+    pub dtls_handshake: Option<Duration>,
+}
+
+impl EstablishmentTimeouts {
+    pub(crate) fn get(&self, phase: EstablishmentPhase) -> Option<Duration> {
+        match phase {
+            EstablishmentPhase::IceGathering => self.ice_gathering,
+            EstablishmentPhase::IceConnection => self.ice_connection,
+            EstablishmentPhase::DtlsHandshake => self.dtls_handshake,
+        }
+    }
+}
+
+/// The keepalives of the connection, `None` keeps the default of libwebrtc.
+///
+/// A NAT drops an idle binding after its own timeout, as short as 30 seconds on aggressive
+/// ones, after which the connection silently stops receiving. The STUN keepalives refresh
+/// the bindings towards the STUN servers, while the connectivity checks of the selected pair,
+/// which are also the consent freshness checks of RFC 7675, refresh the binding of the pair
+/// itself, an interval well below the timeout of the NAT keeps an idle connection alive.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct IceKeepalive {
+    pub stun_keepalive_interval: Option<Duration>,
+    /// The interval of the checks on a stable candidate pair, 2.5 seconds by default.
+    pub check_interval: Option<Duration>,
+    /// The time without any packet after which a candidate pair is no longer receiving.
+    pub receiving_timeout: Option<Duration>,
+    /// The time without an answer to the checks after which a candidate pair is lost,
+    /// the consent of the remote peer is then considered expired.
+    pub inactive_timeout: Option<Duration>,
+}
+
+impl IceKeepalive {
+    fn durations(&self) -> [(&'static str, Option<Duration>); 4] {
+        [
+            ("stun keepalive interval", self.stun_keepalive_interval),
+            ("ice check interval", self.check_interval),
+            ("ice receiving timeout", self.receiving_timeout),
+            ("ice inactive timeout", self.inactive_timeout),
+        ]
+    }
+}
+
+/// The kinds of addresses the candidates are gathered on, all of them by default.
+///
+/// An interface which is up but does not route reliably, such as a flaky IPv6 tunnel, gets
+/// selected when its checks happen to succeed, and the connection then flaps between it and
+/// the others, leaving it out of the gathering keeps the connection on the stable interfaces.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AddressPolicy {
+    pub ipv6: bool,
+    /// The link-local addresses, 169.254.0.0/16 and fe80::/10, which only reach the local link.
+    pub link_local: bool,
+    /// The interfaces which libwebrtc recognizes as VPNs, which depends on the platform,
+    /// an interface not recognized as such can still be denied by its name.
+    pub vpn: bool,
+}
+
+impl Default for AddressPolicy {
+    fn default() -> Self {
+        Self {
+            ipv6: true,
+            link_local: true,
+            vpn: true,
+        }
+    }
+}
+
+/// The SCTP transport carrying the data channels of a connection.
+///
+/// libwebrtc sends messages of up to 256 KiB and opens up to 1024 streams, which the
+/// description can not change, so a larger limit is announced to the remote peer by
+/// editing the local description, see `MediaSection::set_max_message_size`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RTCSctpTransport {
+    /// The largest message the data channels send, the smaller of the local limit and the
+    /// limit of the remote peer, None until the remote description is set.
+    pub max_message_size: Option<u64>,
+    /// The number of data channels which can be open at once, None until the association
+    /// is established.
+    pub max_channels: Option<u32>,
+}
+
+impl RTCSctpTransport {
+    fn from_raw(raw: &raw::RTCSctpTransport) -> Self {
+        Self {
+            max_message_size: (raw.max_message_size > 0.0).then_some(raw.max_message_size as u64),
+            max_channels: (raw.max_channels > 0).then_some(raw.max_channels as u32),
+        }
+    }
+}
+
+fn from_millis(millis: c_int) -> Option<Duration> {
+    (millis > 0).then(|| Duration::from_millis(millis as u64))
+}
+
+fn to_millis(duration: Option<Duration>) -> c_int {
+    duration.map(|d| d.as_millis() as c_int).unwrap_or(0)
+}
+
+/// An owned copy of an ICE candidate, which can outlive the native callback that produced it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IceCandidate {
+    /// The candidate attribute, an empty string is the end-of-candidates marker.
+    pub candidate: String,
+    pub sdp_mid: String,
+    pub sdp_mline_index: i32,
+}
+
+impl IceCandidate {
+    /// The end-of-candidates marker, which applies to every media section, as the remote
+    /// peer sends it once its gathering is complete.
+    pub fn end_of_candidates() -> Self {
+        Self::default()
+    }
+
+    pub fn is_end_of_candidates(&self) -> bool {
+        self.candidate.is_empty()
+    }
+
+    pub(crate) fn from_raw(raw: *const raw::RTCIceCandidate) -> Self {
+        let raw = unsafe { &*raw };
+        let to_string = |s: *const c_char| {
+            if s.is_null() {
+                String::new()
+            } else {
+                unsafe { CStr::from_ptr(s) }.to_string_lossy().into_owned()
+            }
+        };
+
+        Self {
+            candidate: to_string(raw.candidate),
+            sdp_mid: to_string(raw.sdp_mid),
+            sdp_mline_index: raw.sdp_mline_index,
+        }
+    }
+}
+
+/// The native connection shared by the clones of a RTCPeerConnection,
+/// it is closed and freed once the last clone is dropped.
+pub(crate) struct PeerConnection {
+    pub raw: *const raw::RTCPeerConnection,
+    pub observer: Arc<Observer>,
+    // called by the native connection until it is freed.
+    _socket_factory: Option<ExternalSocketFactory>,
+    // the limits of the last set_bitrate, kept by probe_bitrate as libwebrtc replaces all of them.
+    bitrate: Mutex<BitrateSettings>,
+    // dropped after the native connection, which runs on its threads.
+    pub runtime: WebRtcRuntime,
+}
+
+// every method of the native connection is marshalled to its signaling thread,
+// so the connection can be used from any thread, and the observer only holds
+// thread safe state.
+unsafe impl Send for PeerConnection {}
+unsafe impl Sync for PeerConnection {}
+
+impl Drop for PeerConnection {
+    fn drop(&mut self) {
+        // closing is synchronous, no event is dispatched to the observer
+        // once it returns, so the observer can be dropped after it.
+        unsafe {
+            raw::rtc_close(self.raw);
+            raw::rtc_free_peer_connection(self.raw);
+        }
+
+        self.runtime.inner.connections.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// RTCPeerConnection
+///
+/// A handle to a connection between the local device and a remote peer.
+///
+/// The handle is `Send + Sync` and cheap to clone, the clones share the same native
+/// connection, which is closed when the last of them is dropped. Pending futures
+/// such as `create_offer` or `get_stats` hold a clone, so they keep the connection
+/// alive until they complete.
+///
+/// The callbacks of the native side are forwarded by an internal dispatcher thread, the
+/// events and the wakers of the futures run there, in the order they were fired, so the code
+/// of the application never runs on the signaling thread of libwebrtc. The media sinks, frame
+/// transforms and rtp taps are the exception, they are called on the media and network threads,
+/// for every frame or packet, and so is the candidate filter, called on the network thread for
+/// every gathered candidate, see `set_candidate_filter`.
+#[derive(Clone)]
+pub struct RTCPeerConnection {
+    pub(crate) inner: Arc<PeerConnection>,
+}
+
+/// Numbers the connections of the process in their tracing spans.
+static NEXT_PEER_ID: AtomicU64 = AtomicU64::new(0);
+
+impl RTCPeerConnection {
+    /// Creates a connection on the shared runtime, see `WebRtcRuntime::shared`.
+    pub fn new(config: &RTCConfiguration) -> Result<Self> {
+        Self::with_runtime(&WebRtcRuntime::shared()?, config)
+    }
+
+    /// Creates a connection running on the threads of the given runtime.
+    pub fn with_runtime(runtime: &WebRtcRuntime, config: &RTCConfiguration) -> Result<Self> {
+        let timeouts = config.establishment_timeouts;
+        let socket_factory = config.raw_socket_factory.clone();
+        let transceivers = config.transceivers.clone();
+        if !transceivers.is_empty() {
+            runtime.check_media()?;
+        }
+
+        // the native connection copies the configuration, it is only borrowed for the call.
+        let raw = unsafe { raw::create_rtc_peer_connection(runtime.inner.raw, &config.as_raw()) };
+        if raw.is_null() {
+            return Err(Error::Closed);
+        }
+
+        let id = NEXT_PEER_ID.fetch_add(1, Ordering::Relaxed);
+        runtime.inner.connections.fetch_add(1, Ordering::Relaxed);
+        let inner = Arc::new(PeerConnection {
+            observer: Arc::new(Observer::new(
+                tracing::info_span!("peer_connection", id),
+                timeouts,
+            )),
+            runtime: runtime.clone(),
+            _socket_factory: socket_factory,
+            bitrate: Mutex::new(BitrateSettings::default()),
+            raw,
+        });
+
+        let ctx = Arc::as_ptr(&inner.observer) as *mut c_void;
+        unsafe {
+            raw::rtc_on_icecandidate(raw, ctx, observer::on_ice_candidate);
+            raw::rtc_on_negotiationneeded(raw, ctx, observer::on_negotiation_needed);
+            raw::rtc_on_networkchanged(raw, ctx, observer::on_network_changed);
+            raw::rtc_on_prewarmed(raw, ctx, observer::on_prewarmed);
+            raw::rtc_on_signalingstatechange(raw, ctx, observer::on_signaling_state_change);
+            raw::rtc_on_iceconnectionstatechange(raw, ctx, observer::on_ice_connection_state_change);
+            raw::rtc_on_icegatheringstatechange(raw, ctx, observer::on_ice_gathering_state_change);
+            raw::rtc_on_connectionstatechange(raw, ctx, observer::on_connection_state_change);
+            raw::rtc_on_track(raw, ctx, observer::on_track);
+            raw::rtc_on_datachannel(raw, ctx, observer::on_data_channel);
+        }
+
+        let peer = Self { inner };
+        if let Some(bitrate) = &runtime.inner.bitrate {
+            peer.set_bitrate(bitrate);
+        }
+
+        for (kind, direction) in transceivers {
+            peer.add_transceiver(kind, direction)?;
+        }
+
+        Ok(peer)
+    }
+
+    /// The tracing span of the connection, its callbacks run in it and it can
+    /// be entered to attribute the native messages of a call to the connection.
+    pub fn span(&self) -> &tracing::Span {
+        &self.inner.observer.span
+    }
+
+    /// Closes the connection gracefully, all senders are stopped and the data channels
+    /// are closed before the connection itself, and the returned future completes once
+    /// the connection state has reached `Close`.
+    ///
+    /// The native connection is freed when the future completes if this is the last
+    /// handle to it, otherwise when the last clone is dropped.
+    pub async fn close(self) -> Result<()> {
+        let mut states = self.inner.observer.connection_state.subscribe();
+        unsafe {
+            raw::rtc_stop_senders(self.inner.raw);
+            raw::rtc_close_data_channels(self.inner.raw);
+            raw::rtc_close(self.inner.raw);
+        }
+
+        // the native connection may change its state without notifying the observer
+        // when it is closed, so the current state is checked before waiting on events.
+        while self.connection_state() != ConnectionState::Close {
+            if states.next().await.is_none() {
+                return Err(Error::Closed);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn create_offer(&self) -> CreateSessionDescription {
+        self.create_offer_with(&RTCOfferAnswerOptions::default())
+    }
+
+    pub fn create_answer(&self) -> CreateSessionDescription {
+        self.create_answer_with(&RTCOfferAnswerOptions::default())
+    }
+
+    /// The RTCPeerConnection method setLocalDescription() changes the local description
+    /// associated with the connection, usually the offer or the answer just created.
+    pub fn set_local_description(&self, desc: RTCSessionDescription) -> SetSessionDescription {
+        let event = ("setLocalDescription", format!("{:?}", desc.get_type()));
+        self.inner.observer.timeline.record(event);
+        SetSessionDescription::new(self.clone(), SetSessionDescriptionKind::Local, desc)
+    }
+
+    /// The RTCPeerConnection method setRemoteDescription() sets the specified session
+    /// description as the remote peer's current offer or answer.
+    pub fn set_remote_description(&self, desc: RTCSessionDescription) -> SetSessionDescription {
+        let event = ("setRemoteDescription", format!("{:?}", desc.get_type()));
+        self.inner.observer.timeline.record(event);
+        SetSessionDescription::new(self.clone(), SetSessionDescriptionKind::Remote, desc)
+    }
+
+    /// Checks a remote description before it is set, and reports the reason it would be refused
+    /// as `Error::InvalidSdp`, where setting it only fails with the opaque error of libwebrtc.
+    ///
+    /// The structure of the description is checked by `SessionDescription::validate`, against
+    /// the local offer for an answer, and every audio and video section must offer at least
+    /// one codec the connection can receive. Passing the checks does not guarantee that the
+    /// description is accepted.
+    pub fn validate_remote_description(&self, desc: &RTCSessionDescription) -> Result<()> {
+        use raw::RtcSessionDescriptionType as Type;
+
+        let r#type = desc.get_type();
+        if matches!(r#type, Type::Rollback) {
+            return Ok(());
+        }
+
+        let remote: SessionDescription = desc.get_sdp()?.parse()?;
+        let offer = if matches!(r#type, Type::Answer | Type::PrAnswer) {
+            let local = self
+                .local_description()
+                .filter(|local| matches!(local.get_type(), Type::Offer))
+                .ok_or_else(|| Error::InvalidState("an answer without a local offer".to_string()))?;
+            Some(local.get_sdp()?.parse::<SessionDescription>()?)
+        } else {
+            None
+        };
+
+        remote.validate(offer.as_ref())?;
+        for section in remote.media.iter().filter(|section| !section.is_rejected()) {
+            let kind = match section.kind.as_str() {
+                "audio" => MediaType::Audio,
+                "video" => MediaType::Video,
+                _ => continue,
+            };
+
+            let supported = RtpReceiver::capabilities(kind)?.codecs;
+            let codecs = section.media_codecs();
+            let is_supported = |name: &String| {
+                supported.iter().any(|codec| {
+                    let supported_name = codec.mime_type.split_once('/').map(|(_, name)| name);
+                    supported_name.is_some_and(|supported_name| supported_name.eq_ignore_ascii_case(name))
+                })
+            };
+
+            if !codecs.iter().any(is_supported) {
+                return Err(Error::InvalidSdp(format!(
+                    "none of the codecs of the {} section {} is supported: {}",
+                    section.kind,
+                    section.mid().unwrap_or_default(),
+                    codecs.join(", ")
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The RTCPeerConnection method addIceCandidate() adds a candidate trickled by the remote
+    /// peer. The end-of-candidates marker is ignored, libwebrtc keeps checking the candidates
+    /// it has.
+    ///
+    /// The candidates added before the remote description are queued, and applied once
+    /// `set_remote_description` completes, even if its future was dropped. A queued candidate
+    /// which is rejected then is only logged, and one whose ufrag is not in the applied
+    /// description, such as a candidate of the generation before an ICE restart, is dropped.
+    pub fn add_ice_candidate(&self, candidate: &IceCandidate) -> Result<()> {
+        if candidate.is_end_of_candidates() {
+            return Ok(());
+        }
+
+        {
+            // checked under the lock, so the candidate is not queued after the queue is applied.
+            let mut pending = self.inner.observer.pending_candidates.lock().unwrap();
+            if self.remote_description().is_none() {
+                pending.push(candidate.clone());
+                return Ok(());
+            }
+        }
+
+        self.add_pending_candidates();
+        self.apply_ice_candidate(candidate)
+    }
+
+    /// Tells the connection that the remote peer gathered all its candidates, such as when
+    /// the signaling channel carries the end-of-candidates marker. The marker is recorded in
+    /// the timeline of the connection, libwebrtc keeps checking the candidates it has.
+    pub fn add_end_of_candidates(&self) -> Result<()> {
+        self.inner.observer.timeline.record(("addEndOfCandidates", String::new()));
+        self.add_ice_candidate(&IceCandidate::end_of_candidates())
+    }
+
+    /// Applies the queued candidates, once the remote description is set.
+    fn add_pending_candidates(&self) {
+        let pending = std::mem::take(&mut *self.inner.observer.pending_candidates.lock().unwrap());
+        if pending.is_empty() {
+            return;
+        }
+
+        let ufrags = self.remote_ufrags();
+        for candidate in pending {
+            let ufrag = candidate.candidate.parse::<Candidate>().ok().and_then(|c| {
+                c.extension("ufrag").map(|ufrag| ufrag.to_string())
+            });
+
+            if let Some(ufrag) = ufrag.filter(|ufrag| !ufrags.is_empty() && !ufrags.contains(ufrag)) {
+                let _entered = self.inner.observer.span.enter();
+                tracing::debug!(ufrag, "queued candidate of a replaced description dropped");
+                continue;
+            }
+
+            if let Err(e) = self.apply_ice_candidate(&candidate) {
+                let _entered = self.inner.observer.span.enter();
+                tracing::warn!("queued candidate rejected: {}", e);
+            }
+        }
+    }
+
+    /// The ICE usernames of the remote description, of the session and of every section.
+    fn remote_ufrags(&self) -> Vec<String> {
+        let desc = match self.remote_description() {
+            Some(desc) => desc,
+            None => return Vec::new(),
+        };
+
+        match desc.get_sdp().map(|sdp| sdp.parse::<SessionDescription>()) {
+            Ok(Ok(desc)) => desc
+                .attributes("ice-ufrag")
+                .chain(desc.media.iter().flat_map(|section| section.attributes("ice-ufrag")))
+                .map(|ufrag| ufrag.to_string())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn apply_ice_candidate(&self, candidate: &IceCandidate) -> Result<()> {
+        let raw_candidate = CString::new(candidate.candidate.as_str())?;
+        let sdp_mid = CString::new(candidate.sdp_mid.as_str())?;
+        let raw = raw::RTCIceCandidate {
+            candidate: raw_candidate.as_ptr(),
+            sdp_mid: sdp_mid.as_ptr(),
+            sdp_mline_index: candidate.sdp_mline_index,
+        };
+
+        if unsafe { raw::rtc_add_ice_candidate(self.inner.raw, &raw) } {
+            Ok(())
+        } else {
+            Err(Error::InvalidCandidate(candidate.candidate.clone()))
+        }
+    }
+
+    /// Adds a batch of candidates trickled by the remote peer, and completes once every
+    /// candidate is added. The candidates which arrive before the remote description are
+    /// queued until it is set, the future only fails early when the connection is closed.
+    /// The candidates which are rejected are returned together in
+    /// `Error::CandidatesRejected`, the other ones are added.
+    pub async fn add_ice_candidates(&self, candidates: &[IceCandidate]) -> Result<()> {
+        let mut states = self.inner.observer.signaling_state.subscribe();
+        // the remote description is checked before every event, the event may be older.
+        while self.remote_description().is_none() {
+            if self.signaling_state() == SignalingState::Closed || states.next().await.is_none() {
+                return Err(Error::Closed);
+            }
+        }
+
+        let candidates = candidates
+            .iter()
+            .filter(|candidate| !candidate.is_end_of_candidates())
+            .collect::<Vec<_>>();
+        let results = futures::future::join_all(candidates.iter().map(|candidate| async move {
+            let promise = self.add_ice_candidate_async(candidate)?;
+            futures::future::poll_fn(|cx| promise.poll(cx)).await
+        }))
+        .await;
+
+        let rejected = candidates
+            .into_iter()
+            .zip(results)
+            .filter_map(|(candidate, result)| result.err().map(|e| (candidate.clone(), e)))
+            .collect::<Vec<_>>();
+        if rejected.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::CandidatesRejected(rejected))
+        }
+    }
+
+    fn add_ice_candidate_async(&self, candidate: &IceCandidate) -> Result<Arc<Promise<Result<()>>>> {
+        extern "C" fn callback(error: *const raw::RTCError, ctx: *mut c_void) {
+            unsafe {
+                Promise::resolve(ctx, "addicecandidate", || {
+                    if error.is_null() {
+                        Ok(())
+                    } else {
+                        Err(Error::from_raw(error))
+                    }
+                })
+            }
+        }
+
+        let raw_candidate = CString::new(candidate.candidate.as_str())?;
+        let sdp_mid = CString::new(candidate.sdp_mid.as_str())?;
+        let raw = raw::RTCIceCandidate {
+            candidate: raw_candidate.as_ptr(),
+            sdp_mid: sdp_mid.as_ptr(),
+            sdp_mline_index: candidate.sdp_mline_index,
+        };
+
+        let promise = Promise::new(self.inner.observer.clone());
+        unsafe { raw::rtc_add_ice_candidate_async(self.inner.raw, &raw, promise.as_ctx(), callback) };
+        Ok(promise)
+    }
+
+    /// Creates an offer with options, such as `ice_restart` to renegotiate the ICE
+    /// credentials of an active connection.
+    pub fn create_offer_with(&self, options: &RTCOfferAnswerOptions) -> CreateSessionDescription {
+        CreateSessionDescription::new(
+            self.clone(),
+            CreateSessionDescriptionKind::Offer,
+            options.as_raw(),
+        )
+    }
+
+    /// Creates an answer with options, the legacy offer to receive options are ignored.
+    pub fn create_answer_with(&self, options: &RTCOfferAnswerOptions) -> CreateSessionDescription {
+        CreateSessionDescription::new(
+            self.clone(),
+            CreateSessionDescriptionKind::Answer,
+            options.as_raw(),
+        )
+    }
+
+    /// The RTCPeerConnection method addTrack() adds a new media track to the set of tracks
+    /// which will be transmitted to the other peer, as part of the stream with the given id.
+    ///
+    /// The connection keeps the native track, adding a track usually fires the
+    /// negotiationneeded event.
+    pub fn add_track(&self, track: &MediaStreamTrack, stream_id: &str) -> Result<()> {
+        self.add_track_with_streams(track, &[stream_id])
+    }
+
+    /// Adds a track as part of several streams, or of none, the remote peer receives
+    /// the ids in the track event, see `MediaStreamTrack::stream_ids`.
+    pub fn add_track_with_streams(&self, track: &MediaStreamTrack, stream_ids: &[&str]) -> Result<()> {
+        self.inner.runtime.check_media()?;
+        let stream_ids = stream_ids
+            .iter()
+            .map(|id| CString::new(*id))
+            .collect::<Result<Vec<_>, _>>()?;
+        let raw_stream_ids = stream_ids.iter().map(|id| id.as_ptr()).collect::<Vec<_>>();
+        if unsafe {
+            raw::rtc_add_track(
+                self.inner.raw,
+                track.raw,
+                raw_stream_ids.as_ptr(),
+                raw_stream_ids.len() as c_int,
+            )
+        } {
+            Ok(())
+        } else {
+            Err(Error::InvalidState(format!("add track {} failed!", track.id())))
+        }
+    }
+
+    /// The transceivers of the connection, in the order of their media sections,
+    /// including the stopped ones until they are removed by a negotiation.
+    pub fn get_transceivers(&self) -> Vec<RtpTransceiver> {
+        transceiver::take_handles(&self.inner, raw::rtc_get_transceivers, RtpTransceiver::from_raw)
+    }
+
+    /// Adds a transceiver without a track, such as a receive-only one to receive a kind of
+    /// media without sending any, the connection then needs a negotiation.
+    pub fn add_transceiver(&self, kind: MediaType, direction: TransceiverDirection) -> Result<RtpTransceiver> {
+        self.inner.runtime.check_media()?;
+        RtpTransceiver::add(&self.inner, kind, direction)
+    }
+
+    /// The senders of the connection, one per transceiver.
+    pub fn get_senders(&self) -> Vec<RtpSender> {
+        transceiver::take_handles(&self.inner, raw::rtc_get_senders, RtpSender::from_raw)
+    }
+
+    /// The receivers of the connection, one per transceiver.
+    pub fn get_receivers(&self) -> Vec<RtpReceiver> {
+        transceiver::take_handles(&self.inner, raw::rtc_get_receivers, RtpReceiver::from_raw)
+    }
+
+    /// Taps the rtp and rtcp packets of the negotiated transceiver with the given media id,
+    /// such as the mid of the media sections of the session description.
+    ///
+    /// The incoming rtp packets are only delivered when the transceiver can be demultiplexed
+    /// by its mid, which is the case of every bundled session.
+    pub fn tap_rtp(&self, mid: &str, mode: RtpTapMode) -> Result<RtpTap> {
+        RtpTap::new(self.inner.clone(), mid, mode)
+    }
+
+    /// Registers a transform on the sender (outgoing) or the receiver (incoming) of the transceiver
+    /// with the given media id, the handler gets every encoded frame before packetization or after
+    /// depacketization, on the thread delivering it, and the frame is dropped when it returns false.
+    ///
+    /// A transceiver has a single transform per direction, registering another one replaces it.
+    pub fn add_frame_transform(
+        &self,
+        mid: &str,
+        direction: RtpDirection,
+        handler: impl FnMut(&mut EncodedFrame) -> bool + Send + 'static,
+    ) -> Result<FrameTransform> {
+        FrameTransform::new(self.inner.clone(), mid, direction, handler)
+    }
+
+    /// The icecandidate event is sent to an RTCPeerConnection when an RTCIceCandidate has
+    /// been identified and added to the local peer by a call to RTCPeerConnection.setLocalDescription(),
+    /// the candidates should be transmitted to the remote peer over the signaling channel.
+    pub fn on_ice_candidate(&self) -> impl Stream<Item = IceCandidate> {
+        self.inner.observer.ice_candidate.subscribe()
+    }
+
+    /// A negotiationneeded event is sent to the RTCPeerConnection when a change occurs which
+    /// requires session negotiation. This negotiation should be carried out as the offerer,
+    /// because some session changes cannot be negotiated as the answerer.
+    ///
+    /// This event is not dispatched if multiple changes occur at once,
+    /// adding a track or a data channel is the typical cause.
+    pub fn on_negotiation_needed(&self) -> impl Stream<Item = ()> {
+        self.inner.observer.negotiation_needed.subscribe()
+    }
+
+    /// A signalingstatechange event is sent to an RTCPeerConnection to notify it that its
+    /// signaling state has changed as a result of a call to setLocalDescription()
+    /// or setRemoteDescription().
+    pub fn on_signaling_state_change(&self) -> impl Stream<Item = SignalingState> {
+        self.inner.observer.signaling_state.subscribe()
+    }
+
+    /// An iceconnectionstatechange event is sent to an RTCPeerConnection object each time
+    /// the ICE connection state changes during the negotiation process.
+    pub fn on_ice_connection_state_change(&self) -> impl Stream<Item = IceConnectionState> {
+        self.inner.observer.ice_connection_state.subscribe()
+    }
+
+    /// The icegatheringstatechange event is sent to the RTCPeerConnection when the state
+    /// of the ICE candidate gathering process changes.
+    pub fn on_ice_gathering_state_change(&self) -> impl Stream<Item = IceGatheringState> {
+        self.inner.observer.ice_gathering_state.subscribe()
+    }
+
+    /// A networkchanged event is sent when a network interface used for gathering candidates
+    /// appears or goes away, with the names of the current interfaces.
+    ///
+    /// With the `GatherContinually` policy the connection follows the change by itself, the
+    /// candidates of a new interface are sent on `on_ice_candidate` and must be signaled to the
+    /// remote peer, so a call survives a handover such as from Wi-Fi to cellular. With the
+    /// default policy the event is the hint to call `restart_ice`.
+    pub fn on_network_changed(&self) -> impl Stream<Item = Vec<String>> {
+        self.inner.observer.network_changed.subscribe()
+    }
+
+    /// A prewarmed event is sent once the pool of the connection has gathered every candidate,
+    /// with the number of candidates gathered, see `prewarm`.
+    pub fn on_prewarmed(&self) -> impl Stream<Item = usize> {
+        self.inner.observer.prewarmed.subscribe()
+    }
+
+    /// The connectionstatechange event is sent to the RTCPeerConnection each time the
+    /// aggregate state of its ICE and DTLS transports changes.
+    pub fn on_connection_state_change(&self) -> impl Stream<Item = ConnectionState> {
+        self.inner.observer.connection_state.subscribe()
+    }
+
+    /// The track event is sent to the RTCPeerConnection after a new track has been added
+    /// to an RTCRtpReceiver which is part of the connection.
+    pub fn on_track(&self) -> impl Stream<Item = Arc<MediaStreamTrack>> {
+        self.inner.observer.track.subscribe()
+    }
+
+    /// The createDataChannel() method on the RTCPeerConnection interface creates a new channel
+    /// linked with the remote peer, over which any kind of data may be transmitted.
+    ///
+    /// The first channel of a connection fires the negotiationneeded event, the channel opens
+    /// once the SCTP transport is established.
+    pub fn create_data_channel(&self, label: &str, init: &DataChannelInit) -> Result<Arc<RTCDataChannel>> {
+        RTCDataChannel::create(&self.inner, label, init).map(Arc::new)
+    }
+
+    /// A datachannel event is sent to the RTCPeerConnection when an RTCDataChannel has been
+    /// added to the connection, as a result of the remote peer calling createDataChannel().
+    pub fn on_data_channel(&self) -> impl Stream<Item = Arc<RTCDataChannel>> {
+        self.inner.observer.data_channel.subscribe()
+    }
+
+    /// Every event of the connection in a single stream, so that an application can be
+    /// driven from one `select!` loop instead of a stream per event.
+    ///
+    /// The event specific streams keep working alongside, each subscriber receives its own copy.
+    pub fn events(&self) -> impl Stream<Item = PeerEvent> {
+        self.inner.observer.events.subscribe()
+    }
+
+    /// The current state of the connection, aggregated from its ICE and DTLS transports.
+    ///
+    /// A connection whose establishment timed out is failed, until it connects or is closed.
+    pub fn connection_state(&self) -> ConnectionState {
+        let state = unsafe { raw::rtc_get_connection_state(self.inner.raw) };
+        if state != ConnectionState::Close && self.inner.observer.timed_out().is_some() {
+            return ConnectionState::Failed;
+        }
+
+        state
+    }
+
+    /// The error of an establishment which timed out, None while every phase completed
+    /// in time, or when no timeout is configured.
+    pub fn establishment_error(&self) -> Option<Error> {
+        self.inner.observer.timed_out().map(Error::EstablishmentTimeout)
+    }
+
+    /// Waits for the connection to be connected, or for its establishment to fail,
+    /// such as when one of the establishment timeouts elapses.
+    pub async fn connected(&self) -> Result<()> {
+        let mut states = self.inner.observer.connection_state.subscribe();
+        loop {
+            // the current state is checked before every event, the event may be older.
+            match self.connection_state() {
+                ConnectionState::Connected => return Ok(()),
+                ConnectionState::Close => return Err(Error::Closed),
+                ConnectionState::Failed => {
+                    return Err(self.establishment_error().unwrap_or_else(|| {
+                        Error::InvalidState("the connection failed".to_string())
+                    }))
+                }
+                _ => (),
+            }
+
+            if states.next().await.is_none() {
+                return Err(Error::Closed);
+            }
+        }
+    }
+
+    /// Waits for the gathering of the local candidates to complete, the local description
+    /// then carries every candidate, for a signaling which does not trickle them.
+    pub async fn ice_gathering_complete(&self) -> Result<()> {
+        let mut states = self.inner.observer.ice_gathering_state.subscribe();
+        while self.ice_gathering_state() != IceGatheringState::Complete {
+            if self.signaling_state() == SignalingState::Closed || states.next().await.is_none() {
+                return Err(Error::Closed);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The current state of the ICE agent of the connection.
+    pub fn ice_connection_state(&self) -> IceConnectionState {
+        unsafe { raw::rtc_get_ice_connection_state(self.inner.raw) }
+    }
+
+    /// The current state of the ICE candidate gathering process.
+    pub fn ice_gathering_state(&self) -> IceGatheringState {
+        unsafe { raw::rtc_get_ice_gathering_state(self.inner.raw) }
+    }
+
+    /// The current state of the offer/answer exchange on the local end of the connection.
+    pub fn signaling_state(&self) -> SignalingState {
+        unsafe { raw::rtc_get_signaling_state(self.inner.raw) }
+    }
+
+    /// The description of the local end of the connection, the pending one when a negotiation
+    /// is underway, the current one otherwise. None if it has not been set yet.
+    pub fn local_description(&self) -> Option<RTCSessionDescription> {
+        self.description(raw::rtc_get_local_description)
+    }
+
+    /// The description of the remote end of the connection, the pending one when a negotiation
+    /// is underway, the current one otherwise. None if it has not been set yet.
+    pub fn remote_description(&self) -> Option<RTCSessionDescription> {
+        self.description(raw::rtc_get_remote_description)
+    }
+
+    /// The description of the local end as it was most recently successfully negotiated.
+    pub fn current_local_description(&self) -> Option<RTCSessionDescription> {
+        self.description(raw::rtc_get_current_local_description)
+    }
+
+    /// The description of the remote end as it was most recently successfully negotiated.
+    pub fn current_remote_description(&self) -> Option<RTCSessionDescription> {
+        self.description(raw::rtc_get_current_remote_description)
+    }
+
+    /// The local description of the negotiation underway, None if there is none.
+    pub fn pending_local_description(&self) -> Option<RTCSessionDescription> {
+        self.description(raw::rtc_get_pending_local_description)
+    }
+
+    /// The remote description of the negotiation underway, None if there is none.
+    pub fn pending_remote_description(&self) -> Option<RTCSessionDescription> {
+        self.description(raw::rtc_get_pending_remote_description)
+    }
+
+    /// The RTCP canonical name of the local end, shared by every stream sent by the connection,
+    /// read from the local description. None until the local description is set, or when
+    /// it does not send anything.
+    pub fn local_cname(&self) -> Option<String> {
+        let local = self.local_description()?;
+        let desc = local.get_sdp().ok()?.parse::<SessionDescription>().ok()?;
+        desc.media
+            .iter()
+            .find_map(|section| section.cname().map(str::to_string))
+    }
+
+    /// The DTLS transport of a media id, along with its ICE transport, None when the media id
+    /// has no transport, such as before the descriptions are set. The media sections bundled
+    /// together share the transport of the first one.
+    pub fn dtls_transport(&self, mid: &str) -> Option<RTCDtlsTransport> {
+        let mid = CString::new(mid).ok()?;
+        let raw = unsafe { raw::rtc_get_transport(self.inner.raw, mid.as_ptr()) };
+        (!raw.is_null()).then(|| RTCDtlsTransport::from_raw(raw, self.inner.runtime.clone()))
+    }
+
+    /// The SCTP transport of the data channels, None until a data channel is negotiated.
+    pub fn sctp(&self) -> Option<RTCSctpTransport> {
+        let mut raw = raw::RTCSctpTransport::default();
+        unsafe { raw::rtc_get_sctp_transport(self.inner.raw, &mut raw) }
+            .then(|| RTCSctpTransport::from_raw(&raw))
+    }
+
+    /// The largest message the remote peer receives, as announced by the "a=max-message-size"
+    /// attribute of its description, so a sender can size its chunks. None until a remote
+    /// description with a data section is set.
+    ///
+    /// A peer which announces no limit gets the 64 KiB of RFC 8841, and one which announces
+    /// a limit of zero, which accepts messages of any size, gets `u64::MAX`.
+    pub fn remote_max_message_size(&self) -> Option<u64> {
+        let remote = self.remote_description()?;
+        let desc = remote.get_sdp().ok()?.parse::<SessionDescription>().ok()?;
+        let section = desc.media.iter().find(|section| section.kind == "application")?;
+        Some(match section.max_message_size() {
+            Some(0) => u64::MAX,
+            Some(size) => size,
+            None => sdp::DEFAULT_MAX_MESSAGE_SIZE,
+        })
+    }
+
+    fn description(
+        &self,
+        get: unsafe extern "C" fn(*const raw::RTCPeerConnection) -> *const raw::RTCSessionDescription,
+    ) -> Option<RTCSessionDescription> {
+        let raw = unsafe { get(self.inner.raw) };
+        if raw.is_null() {
+            return None;
+        }
+
+        let desc = RTCSessionDescription::copy_from_raw(raw);
+        unsafe { raw::rtc_free(raw) };
+        Some(desc)
+    }
+
+    /// Panics caught in the callbacks of the native side, such as a panicking candidate filter.
+    pub fn on_error(&self) -> impl Stream<Item = CallbackPanic> {
+        self.inner.observer.errors.subscribe()
+    }
+
+    /// Installs a predicate deciding which gathered local candidates are used.
+    ///
+    /// The port allocator drops the rejected candidates, they are neither used for the
+    /// connectivity checks nor emitted by `on_ice_candidate`, and the descriptions created by
+    /// `create_offer` and `create_answer` leave out the ones gathered before the filter was
+    /// installed. Passing `None` removes the filter. Installing a filter empties the candidate
+    /// pool, see `prewarm`.
+    ///
+    /// The filter runs on the network thread of the runtime, which every connection of the
+    /// runtime shares, so it must be quick and must not block.
+    pub fn set_candidate_filter(&self, filter: Option<CandidateFilter>) {
+        let installed = filter.is_some();
+        self.inner.observer.set_candidate_filter(filter);
+
+        let ctx = Arc::as_ptr(&self.inner.observer) as *mut c_void;
+        let filter = if installed {
+            Some(observer::on_filter_candidate as extern "C" fn(*mut c_char, *mut c_void) -> bool)
+        } else {
+            None
+        };
+
+        unsafe { raw::rtc_set_candidate_filter(self.inner.raw, ctx, filter) }
+    }
+
+    /// Activates or pauses every encoding of the senders carrying `kind` media,
+    /// the transceivers and the negotiated session are left untouched.
+    pub fn set_senders_active(&self, kind: MediaType, active: bool) {
+        unsafe { raw::rtc_set_senders_active(self.inner.raw, kind, active) }
+    }
+
+    /// The RTCPeerConnection.getConfiguration() method returns an object which indicates the current
+    /// configuration of the RTCPeerConnection on which the method is called.
+    ///
+    /// The network interfaces, the address policy, the proxy, the passive TCP candidates and the
+    /// mDNS obfuscation belong to the port allocator, they are not part of the returned configuration.
+    pub fn get_configuration(&self) -> Result<RTCConfiguration> {
+        let raw = unsafe { raw::rtc_get_configuration(self.inner.raw) };
+        if raw.is_null() {
+            return Err(Error::Closed);
+        }
+
+        let config = RTCConfiguration::from_raw(raw);
+        unsafe { raw::rtc_free_configuration(raw) };
+        config
+    }
+
+    /// The RTCPeerConnection.setConfiguration() method sets the current configuration of
+    /// the RTCPeerConnection based on the values included in the specified object.
+    /// This lets you change the ICE servers used by the connection and which transport
+    /// policies to use.
+    ///
+    /// The bundle policy, the rtcp mux policy and the peer identity can not be changed once
+    /// the connection is created, they must keep their values, and the options of the port
+    /// allocator, see `get_configuration`, are ignored. Changing the ICE servers on a live connection is usually
+    /// followed by `restart_ice`.
+    pub fn set_configuration(&self, config: &RTCConfiguration) -> Result<()> {
+        if unsafe { raw::rtc_set_configuration(self.inner.raw, &config.as_raw()) } {
+            Ok(())
+        } else {
+            Err(Error::InvalidState("set configuration failed!".to_string()))
+        }
+    }
+
+    /// Starts gathering candidates now, before the first offer, so they are ready by the time
+    /// the offer or the answer is created, such as while a call rings.
+    ///
+    /// The candidates are gathered by a pool of `size` ICE sessions, one per transport of the
+    /// offer, a single one with the default bundle policy. `on_prewarmed` tells when they are
+    /// all gathered. The pool can no longer change once the local description is set, and
+    /// a size no larger than the current pool, see `ice_candidate_pool_size`, gathers nothing more.
+    ///
+    /// The pooled candidates would be gathered without the candidate filter, so a connection
+    /// with a filter installed does not prewarm.
+    pub fn prewarm(&self, size: u8) -> Result<()> {
+        if unsafe { raw::rtc_prewarm(self.inner.raw, size as c_int) } {
+            Ok(())
+        } else {
+            Err(Error::InvalidState(
+                "the candidate pool can not change once the local description is set \
+                 or while a candidate filter is installed"
+                    .to_string(),
+            ))
+        }
+    }
+
+    /// The WebRTC API's RTCPeerConnection interface offers the restartIce() method to allow
+    /// a web application to easily request that ICE candidate gathering be redone on both ends
+    /// of the connection.
+    ///
+    /// The next offer created by `create_offer` carries new ICE credentials, once it has been
+    /// applied on both ends, the candidates are gathered again with the current configuration.
+    pub fn restart_ice(&self) {
+        unsafe { raw::rtc_restart_ice(self.inner.raw) }
+    }
+
+    /// Replaces the username and credential of the ICE server with the same urls,
+    /// or adds the server when the connection does not know it yet.
+    ///
+    /// The new credentials are used for the allocations made from now on,
+    /// such as the refreshes of the existing TURN allocations.
+    pub fn update_ice_server(&self, ice_server: &RTCIceServer) -> Result<()> {
+        if unsafe { raw::rtc_update_ice_server(self.inner.raw, &ice_server.as_raw()) } {
+            Ok(())
+        } else {
+            Err(Error::InvalidState("update ice server failed!".to_string()))
+        }
+    }
+
+    /// Limits the total outgoing bitrate of the connection,
+    /// the bandwidth estimation never goes beyond the maximum.
+    ///
+    /// The settings replace the previous ones, a limit which is `None` is removed.
+    pub fn set_bitrate(&self, settings: &BitrateSettings) {
+        let mut bitrate = self.inner.bitrate.lock().unwrap();
+        *bitrate = BitrateSettings {
+            start_bitrate: None,
+            ..*settings
+        };
+
+        self.apply_bitrate(settings.min_bitrate, settings.start_bitrate, settings.max_bitrate);
+    }
+
+    /// The limits of the last `set_bitrate`, the start bitrate only applies once, so it is
+    /// not kept.
+    pub fn bitrate(&self) -> BitrateSettings {
+        *self.inner.bitrate.lock().unwrap()
+    }
+
+    /// Probes the bandwidth up to a target bitrate in bits per second, such as after an ICE
+    /// restart or once the link is known to be faster, rather than waiting for the estimate
+    /// to ramp up. The estimate restarts from the target, within the limits of `set_bitrate`,
+    /// and falls back if the probes do not confirm it.
+    ///
+    /// The probes are padding, so a metered link rather suppresses them for every connection
+    /// of its runtime, see `CongestionControlConfig::probing`.
+    pub fn probe_bitrate(&self, target: u32) {
+        let bitrate = self.inner.bitrate.lock().unwrap();
+        let target = target
+            .max(bitrate.min_bitrate.unwrap_or(0))
+            .min(bitrate.max_bitrate.unwrap_or(u32::MAX));
+        self.apply_bitrate(bitrate.min_bitrate, Some(target), bitrate.max_bitrate);
+    }
+
+    fn apply_bitrate(&self, min: Option<u32>, start: Option<u32>, max: Option<u32>) {
+        let bps = |bitrate: Option<u32>| bitrate.map(|b| b.min(c_int::MAX as u32) as c_int).unwrap_or(0);
+        unsafe { raw::rtc_set_bitrate(self.inner.raw, bps(min), bps(start), bps(max)) }
+    }
+}
+
+#[derive(PartialEq, PartialOrd)]
+pub enum CreateSessionDescriptionKind {
+    Offer,
+    Answer,
+}
+
+/// The future of `create_offer` and `create_answer`.
+///
+/// Dropping the future before it completes is safe, the native operation runs to its end
+/// and its result is discarded.
+pub struct CreateSessionDescription {
+    kind: CreateSessionDescriptionKind,
+    peer: RTCPeerConnection,
+    options: raw::RTCOfferAnswerOptions,
+    promise: Option<Arc<Promise<Result<RTCSessionDescription>>>>,
+    timeout: Timeout,
 }
 
 impl CreateSessionDescription {
-    pub fn new(peer: *const raw::RTCPeerConnection, kind: CreateSessionDescriptionKind,) -> Self {
+    pub(crate) fn new(
+        peer: RTCPeerConnection,
+        kind: CreateSessionDescriptionKind,
+        options: raw::RTCOfferAnswerOptions,
+    ) -> Self {
         Self { 
-            waker: Arc::new(AtomicWaker::new()), 
-            desc: Arc::new(AtomicPtr::new(std::ptr::null_mut())), 
-            begin: false,
+            timeout: Timeout::default(),
+            promise: None,
+            options,
             peer, 
             kind,
         }
     }
+
+    /// Fails with `Error::Timeout` when the description is not created within `duration`,
+    /// counted from the first poll. The timer needs a tokio runtime.
+    pub fn timeout(mut self, duration: Duration) -> Self {
+        self.timeout = Timeout::new(duration);
+        self
+    }
 }
 
 impl Future for CreateSessionDescription {
-    type Output = anyhow::Result<RTCSessionDescription>;
+    type Output = Result<RTCSessionDescription>;
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        self.as_ref().waker.register(cx.waker());
-
-        if !self.as_ref().begin {
-            extern "C" fn callback(desc: *const raw::RTCSessionDescription, ctx: *mut c_void) {
-                let mut ctx = unsafe { Box::from_raw(ctx as *mut CreateSessionDescriptionContext) };
-                if desc.is_null() {
-                    (ctx.callback)(desc);
+        if self.promise.is_none() {
+            extern "C" fn callback(
+                desc: *const raw::RTCSessionDescription,
+                error: *const raw::RTCError,
+                ctx: *mut c_void,
+            ) {
+                unsafe {
+                    Promise::resolve(ctx, "createsessiondescription", || {
+                        if desc.is_null() {
+                            Err(Error::from_raw(error))
+                        } else {
+                            let sdesc = RTCSessionDescription::copy_from_raw(desc);
+                            raw::rtc_free(desc);
+                            Ok(sdesc)
+                        }
+                    })
                 }
-                
-                println!("CreateSessionDescription callback");
             }
 
-            let waker = self.as_ref().waker.clone();
-            let desc = self.as_mut().desc.clone();
-            let ctx = Box::new(CreateSessionDescriptionContext {
-                callback: Box::new(move |sdesc| {
-                    desc.store(sdesc as *mut raw::RTCSessionDescription, Ordering::Relaxed);
-                    waker.wake();
-                }),
-            });
-
-            if self.as_ref().kind == CreateSessionDescriptionKind::Offer {
-                println!("CreateSessionDescription offer");
-                unsafe {
-                    raw::rtc_create_offer(
-                        self.as_ref().peer,
-                        Box::into_raw(ctx) as *mut c_void,
-                        callback,
-                    )
-                };
+            let promise = Promise::new(self.peer.inner.observer.clone());
+            let ctx = promise.as_ctx();
+            if self.kind == CreateSessionDescriptionKind::Offer {
+                unsafe { raw::rtc_create_offer(self.peer.inner.raw, &self.options, ctx, callback) };
             } else {
+                unsafe { raw::rtc_create_answer(self.peer.inner.raw, &self.options, ctx, callback) };
+            }
+
+            self.promise = Some(promise);
+        }
+
+        if let Some(result) = self.promise.as_ref().and_then(|p| ready(p.poll(cx))) {
+            return Poll::Ready(result.and_then(|mut desc| {
+                let sdp = self.peer.inner.observer.filter_sdp(desc.get_sdp()?);
+                desc.sdp = CString::new(sdp)?;
+                Ok(desc)
+            }));
+        }
+
+        self.timeout.poll(cx)
+    }
+}
+
+impl Drop for CreateSessionDescription {
+    fn drop(&mut self) {
+        if let Some(promise) = &self.promise {
+            promise.cancel();
+        }
+    }
+}
+
+#[derive(PartialEq, PartialOrd)]
+pub enum SetSessionDescriptionKind {
+    Local,
+    Remote,
+}
+
+/// The future of `set_local_description` and `set_remote_description`.
+///
+/// Dropping the future before it completes is safe, but the description may still be
+/// applied, as the native operation can not be aborted.
+pub struct SetSessionDescription {
+    kind: SetSessionDescriptionKind,
+    peer: RTCPeerConnection,
+    desc: RTCSessionDescription,
+    promise: Option<Arc<Promise<Result<()>>>>,
+    timeout: Timeout,
+}
+
+impl SetSessionDescription {
+    pub(crate) fn new(
+        peer: RTCPeerConnection,
+        kind: SetSessionDescriptionKind,
+        desc: RTCSessionDescription,
+    ) -> Self {
+        Self {
+            timeout: Timeout::default(),
+            promise: None,
+            desc,
+            peer,
+            kind,
+        }
+    }
+
+    /// Fails with `Error::Timeout` when the description is not applied within `duration`,
+    /// counted from the first poll. The timer needs a tokio runtime.
+    pub fn timeout(mut self, duration: Duration) -> Self {
+        self.timeout = Timeout::new(duration);
+        self
+    }
+}
+
+impl Future for SetSessionDescription {
+    type Output = Result<()>;
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.promise.is_none() {
+            extern "C" fn callback(error: *const raw::RTCError, ctx: *mut c_void) {
                 unsafe {
-                    raw::rtc_create_answer(
-                        self.as_ref().peer,
-                        Box::into_raw(ctx) as *mut c_void,
-                        callback,
-                    )
-                };
-            }
-            
-            self.as_mut().begin = true;
-            Poll::Pending
-        } else {
-            let desc = self.as_ref().desc.load(Ordering::Relaxed);
-            Poll::Ready(if desc.is_null() {
-                Err(anyhow!("create offer failed!"))
+                    Promise::resolve(ctx, "setsessiondescription", || {
+                        if error.is_null() {
+                            Ok(())
+                        } else {
+                            Err(Error::from_raw(error))
+                        }
+                    })
+                }
+            }
+
+            let promise = Promise::<Result<()>>::new(self.peer.inner.observer.clone());
+            if self.kind == SetSessionDescriptionKind::Remote {
+                // the queue is applied even if the future is dropped, the connection is not kept alive for it.
+                let peer = Arc::downgrade(&self.peer.inner);
+                promise.then(move |result| {
+                    if let (Ok(()), Some(inner)) = (result, peer.upgrade()) {
+                        RTCPeerConnection { inner }.add_pending_candidates();
+                    }
+                });
+            }
+
+            let ctx = promise.as_ctx();
+            let desc = self.desc.as_raw();
+            if self.kind == SetSessionDescriptionKind::Local {
+                unsafe { raw::rtc_set_local_description(self.peer.inner.raw, &desc, ctx, callback) };
             } else {
-                Ok(RTCSessionDescription::from_raw(desc))
-            })
+                unsafe { raw::rtc_set_remote_description(self.peer.inner.raw, &desc, ctx, callback) };
+            }
+
+            self.promise = Some(promise);
+        }
+
+        if let Some(result) = self.promise.as_ref().and_then(|p| ready(p.poll(cx))) {
+            return Poll::Ready(result);
+        }
+
+        self.timeout.poll(cx)
+    }
+}
+
+impl Drop for SetSessionDescription {
+    fn drop(&mut self) {
+        if let Some(promise) = &self.promise {
+            promise.cancel();
+        }
+    }
+}
+
+fn ready<T>(poll: Poll<T>) -> Option<T> {
+    match poll {
+        Poll::Ready(value) => Some(value),
+        Poll::Pending => None,
+    }
+}
+
+/// The optional timeout of a future, the timer starts on the first poll.
+#[derive(Default)]
+struct Timeout {
+    duration: Option<Duration>,
+    sleep: Option<Pin<Box<tokio::time::Sleep>>>,
+}
+
+impl Timeout {
+    fn new(duration: Duration) -> Self {
+        Self {
+            duration: Some(duration),
+            sleep: None,
+        }
+    }
+
+    fn poll<T>(&mut self, cx: &mut Context<'_>) -> Poll<Result<T>> {
+        let duration = match self.duration {
+            Some(duration) => duration,
+            None => return Poll::Pending,
+        };
+
+        let sleep = self
+            .sleep
+            .get_or_insert_with(|| Box::pin(tokio::time::sleep(duration)));
+        match sleep.as_mut().poll(cx) {
+            Poll::Ready(()) => Poll::Ready(Err(Error::Timeout)),
+            Poll::Pending => Poll::Pending,
         }
     }
 }
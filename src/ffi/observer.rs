@@ -0,0 +1,451 @@
+use super::{
+    dispatcher,
+    raw,
+    EstablishmentPhase,
+    EstablishmentTimeouts,
+    IceCandidate,
+    MediaStreamTrack,
+    RTCDataChannel,
+};
+use crate::diagnostics::{History, TimelineEvent};
+use crate::ice::Candidate;
+use crate::stats::StatsHistory;
+
+use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use libc::*;
+use std::any::Any;
+use std::ffi::CStr;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use tracing::Span;
+
+pub type CandidateFilter = Box<dyn Fn(&Candidate) -> bool + Send + Sync>;
+
+/// A panic caught in a callback of the native side.
+///
+/// Unwinding across the FFI boundary would abort the process or corrupt the native stack,
+/// so the panic is caught and reported on the error stream of the connection instead.
+#[derive(Clone, Debug)]
+pub struct CallbackPanic {
+    /// The name of the native callback, such as "icecandidate".
+    pub callback: &'static str,
+    pub message: String,
+}
+
+impl CallbackPanic {
+    fn new(callback: &'static str, panic: Box<dyn Any + Send>) -> Self {
+        let message = match panic.downcast::<String>() {
+            Ok(message) => *message,
+            Err(panic) => match panic.downcast::<&str>() {
+                Ok(message) => message.to_string(),
+                Err(_) => "unknown panic".to_string(),
+            },
+        };
+
+        Self { callback, message }
+    }
+}
+
+/// Every event of a connection, in the order they were dispatched by the native side.
+#[derive(Clone)]
+pub enum PeerEvent {
+    IceCandidate(IceCandidate),
+    Track(Arc<MediaStreamTrack>),
+    DataChannel(Arc<RTCDataChannel>),
+    ConnectionState(raw::ConnectionState),
+    NegotiationNeeded,
+    SignalingState(raw::SignalingState),
+    IceConnectionState(raw::IceConnectionState),
+    IceGatheringState(raw::IceGatheringState),
+    /// The names of the network interfaces used for gathering candidates, after one of them appeared or went away.
+    NetworkChanged(Vec<String>),
+    /// The number of candidates gathered by the pool of the connection, see `RTCPeerConnection::prewarm`.
+    Prewarmed(usize),
+    Error(CallbackPanic),
+}
+
+/// A list of event listeners, every emitted value is delivered to all of them.
+pub(crate) struct Subscribers<T> {
+    senders: Mutex<Vec<UnboundedSender<T>>>,
+}
+
+impl<T> Default for Subscribers<T> {
+    fn default() -> Self {
+        Self {
+            senders: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl<T: Clone> Subscribers<T> {
+    pub fn subscribe(&self) -> UnboundedReceiver<T> {
+        let (sender, receiver) = unbounded();
+        self.senders.lock().unwrap().push(sender);
+        receiver
+    }
+
+    /// Listeners whose receiver has been dropped are removed on the way.
+    pub fn emit(&self, value: T) {
+        self.senders
+            .lock()
+            .unwrap()
+            .retain(|sender| sender.unbounded_send(value.clone()).is_ok());
+    }
+}
+
+/// The rust half of the native observer, a pointer to it is handed to the native
+/// peer connection as the context of every event handler.
+pub(crate) struct Observer {
+    pub ice_candidate: Subscribers<IceCandidate>,
+    pub negotiation_needed: Subscribers<()>,
+    pub signaling_state: Subscribers<raw::SignalingState>,
+    pub ice_connection_state: Subscribers<raw::IceConnectionState>,
+    pub ice_gathering_state: Subscribers<raw::IceGatheringState>,
+    pub network_changed: Subscribers<Vec<String>>,
+    pub prewarmed: Subscribers<usize>,
+    pub connection_state: Subscribers<raw::ConnectionState>,
+    pub track: Subscribers<Arc<MediaStreamTrack>>,
+    pub data_channel: Subscribers<Arc<RTCDataChannel>>,
+    pub events: Subscribers<PeerEvent>,
+    pub errors: Subscribers<CallbackPanic>,
+    candidate_filter: Mutex<Option<CandidateFilter>>,
+    // the callbacks of the connection run in its span.
+    pub span: Span,
+    pub timeline: History<TimelineEvent>,
+    // the native reports of the diagnostics, only kept along with the recorded reports.
+    pub stats_history: History<String>,
+    // the opt-in history of the parsed reports, see `RTCPeerConnection::enable_stats_history`.
+    pub recorded_stats: Mutex<Option<StatsHistory>>,
+    // the candidates added before the remote description, applied once it is set.
+    pub pending_candidates: Mutex<Vec<IceCandidate>>,
+    timeouts: EstablishmentTimeouts,
+    // bumped whenever a phase is entered or left, a timeout only fires
+    // if its phase has not moved since it was scheduled.
+    phases: [AtomicU64; 3],
+    connected: Mutex<bool>,
+    timed_out: Mutex<Option<EstablishmentPhase>>,
+}
+
+impl Observer {
+    pub fn new(span: Span, timeouts: EstablishmentTimeouts) -> Self {
+        Self {
+            ice_candidate: Default::default(),
+            negotiation_needed: Default::default(),
+            signaling_state: Default::default(),
+            ice_connection_state: Default::default(),
+            ice_gathering_state: Default::default(),
+            network_changed: Default::default(),
+            prewarmed: Default::default(),
+            connection_state: Default::default(),
+            track: Default::default(),
+            data_channel: Default::default(),
+            events: Default::default(),
+            errors: Default::default(),
+            candidate_filter: Default::default(),
+            span,
+            timeline: History::timeline(),
+            stats_history: History::stats(),
+            recorded_stats: Mutex::new(None),
+            pending_candidates: Mutex::new(Vec::new()),
+            timeouts,
+            phases: Default::default(),
+            connected: Mutex::new(false),
+            timed_out: Mutex::new(None),
+        }
+    }
+
+    /// The observer a native callback is called with, its context is a pointer to
+    /// the observer of the connection, which is alive for the duration of the callback.
+    ///
+    /// # Safety
+    ///
+    /// `ctx` must be the observer handed over to the native side with `Arc::as_ptr`.
+    pub unsafe fn from_ctx(ctx: *mut c_void) -> Arc<Self> {
+        Arc::increment_strong_count(ctx as *const Self);
+        Arc::from_raw(ctx as *const Self)
+    }
+
+    /// Dispatches a callback of the native side, `copy` takes the arguments of the callback
+    /// on the native thread, as they are only valid for the duration of the callback, then
+    /// `f` runs on the dispatcher thread in the span of the connection.
+    /// A panic in either of them is caught and reported on the error stream.
+    pub fn dispatch<T: Send + 'static>(
+        self: &Arc<Self>,
+        callback: &'static str,
+        copy: impl FnOnce() -> T,
+        f: impl FnOnce(&Observer, T) + Send + 'static,
+    ) {
+        let observer = self.clone();
+        match catch_unwind(AssertUnwindSafe(copy)) {
+            Ok(args) => dispatcher::dispatch(move || {
+                let _entered = observer.span.enter();
+                tracing::trace!(callback, "native callback");
+                if let Err(panic) = catch_unwind(AssertUnwindSafe(|| f(&observer, args))) {
+                    observer.report(callback, panic);
+                }
+            }),
+            Err(panic) => dispatcher::dispatch(move || {
+                let _entered = observer.span.enter();
+                observer.report(callback, panic);
+            }),
+        }
+    }
+
+    fn report(&self, callback: &'static str, panic: Box<dyn Any + Send>) {
+        let error = CallbackPanic::new(callback, panic);
+        tracing::error!(callback, message = %error.message, "native callback panicked");
+        self.timeline.record(("error", format!("{}: {}", callback, error.message)));
+
+        // waking the listeners may run foreign code too.
+        let _ = catch_unwind(AssertUnwindSafe(|| {
+            self.errors.emit(error.clone());
+            self.events.emit(PeerEvent::Error(error));
+        }));
+    }
+
+    pub fn timed_out(&self) -> Option<EstablishmentPhase> {
+        *self.timed_out.lock().unwrap()
+    }
+
+    /// Starts the timeout of a phase, if it has one, this runs on the native threads.
+    fn enter_phase(self: &Arc<Self>, phase: EstablishmentPhase) {
+        let generation = self.phases[phase.index()].fetch_add(1, Ordering::SeqCst) + 1;
+        if let Some(timeout) = self.timeouts.get(phase) {
+            let observer = Arc::downgrade(self);
+            dispatcher::dispatch_after(timeout, move || {
+                if let Some(observer) = observer.upgrade() {
+                    if observer.phases[phase.index()].load(Ordering::SeqCst) == generation {
+                        observer.time_out(phase);
+                    }
+                }
+            });
+        }
+    }
+
+    fn leave_phase(&self, phase: EstablishmentPhase) {
+        self.phases[phase.index()].fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Fails the connection, the native connection is left as is, so that it can
+    /// still be restarted or closed.
+    fn time_out(&self, phase: EstablishmentPhase) {
+        // the handshake may complete before ICE reports its connection.
+        if phase == EstablishmentPhase::DtlsHandshake && *self.connected.lock().unwrap() {
+            return;
+        }
+
+        let _entered = self.span.enter();
+        tracing::warn!(%phase, "the establishment of the connection timed out");
+        *self.timed_out.lock().unwrap() = Some(phase);
+        self.timeline.record(("establishmenttimeout", phase.to_string()));
+        let _ = catch_unwind(AssertUnwindSafe(|| {
+            self.connection_state.emit(raw::ConnectionState::Failed);
+            self.events.emit(PeerEvent::ConnectionState(raw::ConnectionState::Failed));
+        }));
+    }
+
+    /// A panicking filter poisons the lock, the filter is still usable after it.
+    fn candidate_filter(&self) -> MutexGuard<'_, Option<CandidateFilter>> {
+        self.candidate_filter.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn set_candidate_filter(&self, filter: Option<CandidateFilter>) {
+        *self.candidate_filter() = filter;
+    }
+
+    /// Candidates that can not be parsed, such as the end-of-candidates marker,
+    /// are always accepted.
+    pub fn accept_candidate(&self, candidate: &str) -> bool {
+        match self.candidate_filter().as_ref() {
+            None => true,
+            Some(filter) => match candidate.parse::<Candidate>() {
+                Ok(candidate) => filter(&candidate),
+                Err(_) => true,
+            },
+        }
+    }
+
+    /// Removes the rejected "a=candidate" lines from a local session description,
+    /// so that candidates gathered before the description was created are filtered too.
+    pub fn filter_sdp(&self, sdp: &str) -> String {
+        if self.candidate_filter().is_none() {
+            return sdp.to_string();
+        }
+
+        sdp.split_inclusive('\n')
+            .filter(|line| match line.trim_end().strip_prefix("a=") {
+                Some(candidate) if candidate.starts_with("candidate:") => {
+                    self.accept_candidate(candidate)
+                }
+                _ => true,
+            })
+            .collect()
+    }
+}
+
+pub(crate) extern "C" fn on_ice_candidate(candidate: *const raw::RTCIceCandidate, ctx: *mut c_void) {
+    let observer = unsafe { Observer::from_ctx(ctx) };
+    observer.dispatch("icecandidate", || IceCandidate::from_raw(candidate), |observer, candidate| {
+        observer.timeline.record(("icecandidate", candidate.candidate.clone()));
+        observer.ice_candidate.emit(candidate.clone());
+        observer.events.emit(PeerEvent::IceCandidate(candidate));
+    });
+}
+
+/// Called by the port allocator on the network thread for every gathered candidate,
+/// a panicking filter rejects the candidate and is reported on the dispatcher thread.
+pub(crate) extern "C" fn on_filter_candidate(candidate: *mut c_char, ctx: *mut c_void) -> bool {
+    let observer = unsafe { Observer::from_ctx(ctx) };
+    let accepted = catch_unwind(AssertUnwindSafe(|| {
+        let candidate = unsafe { CStr::from_ptr(candidate) }.to_string_lossy();
+        observer.accept_candidate(candidate.trim().trim_start_matches("a="))
+    }));
+
+    match accepted {
+        Ok(accepted) => accepted,
+        Err(panic) => {
+            dispatcher::dispatch(move || {
+                let _entered = observer.span.enter();
+                observer.report("candidatefilter", panic);
+            });
+
+            false
+        }
+    }
+}
+
+pub(crate) extern "C" fn on_negotiation_needed(ctx: *mut c_void) {
+    let observer = unsafe { Observer::from_ctx(ctx) };
+    observer.dispatch("negotiationneeded", || (), |observer, _| {
+        observer.timeline.record(("negotiationneeded", String::new()));
+        observer.negotiation_needed.emit(());
+        observer.events.emit(PeerEvent::NegotiationNeeded);
+    });
+}
+
+pub(crate) extern "C" fn on_network_changed(names: *const *const c_char, size: c_int, ctx: *mut c_void) {
+    let observer = unsafe { Observer::from_ctx(ctx) };
+    let copy = || match size {
+        0 => Vec::new(),
+        _ => unsafe { std::slice::from_raw_parts(names, size as usize) }
+            .iter()
+            .map(|name| unsafe { CStr::from_ptr(*name) }.to_string_lossy().into_owned())
+            .collect::<Vec<_>>(),
+    };
+
+    observer.dispatch("networkchanged", copy, |observer, names| {
+        observer.timeline.record(("networkchanged", names.join(",")));
+        observer.network_changed.emit(names.clone());
+        observer.events.emit(PeerEvent::NetworkChanged(names));
+    });
+}
+
+pub(crate) extern "C" fn on_prewarmed(candidates: c_int, ctx: *mut c_void) {
+    let observer = unsafe { Observer::from_ctx(ctx) };
+    observer.dispatch("prewarmed", || candidates.max(0) as usize, |observer, candidates| {
+        observer.timeline.record(("prewarmed", candidates.to_string()));
+        observer.prewarmed.emit(candidates);
+        observer.events.emit(PeerEvent::Prewarmed(candidates));
+    });
+}
+
+pub(crate) extern "C" fn on_signaling_state_change(state: raw::SignalingState, ctx: *mut c_void) {
+    let observer = unsafe { Observer::from_ctx(ctx) };
+    observer.dispatch("signalingstatechange", || state, |observer, state| {
+        observer.timeline.record(("signalingstatechange", format!("{:?}", state)));
+        observer.signaling_state.emit(state);
+        observer.events.emit(PeerEvent::SignalingState(state));
+    });
+}
+
+pub(crate) extern "C" fn on_ice_connection_state_change(
+    state: raw::IceConnectionState,
+    ctx: *mut c_void,
+) {
+    let observer = unsafe { Observer::from_ctx(ctx) };
+    match state {
+        raw::IceConnectionState::Checking => observer.enter_phase(EstablishmentPhase::IceConnection),
+        raw::IceConnectionState::Connected => {
+            observer.leave_phase(EstablishmentPhase::IceConnection);
+            observer.enter_phase(EstablishmentPhase::DtlsHandshake);
+        }
+        // completed follows connected, the handshake is already underway.
+        raw::IceConnectionState::Completed => observer.leave_phase(EstablishmentPhase::IceConnection),
+        _ => {
+            observer.leave_phase(EstablishmentPhase::IceConnection);
+            observer.leave_phase(EstablishmentPhase::DtlsHandshake);
+        }
+    }
+
+    observer.dispatch("iceconnectionstatechange", || state, |observer, state| {
+        observer.timeline.record(("iceconnectionstatechange", format!("{:?}", state)));
+        observer.ice_connection_state.emit(state);
+        observer.events.emit(PeerEvent::IceConnectionState(state));
+    });
+}
+
+pub(crate) extern "C" fn on_ice_gathering_state_change(
+    state: raw::IceGatheringState,
+    ctx: *mut c_void,
+) {
+    let observer = unsafe { Observer::from_ctx(ctx) };
+    match state {
+        raw::IceGatheringState::Gathering => observer.enter_phase(EstablishmentPhase::IceGathering),
+        _ => observer.leave_phase(EstablishmentPhase::IceGathering),
+    }
+
+    observer.dispatch("icegatheringstatechange", || state, |observer, state| {
+        observer.timeline.record(("icegatheringstatechange", format!("{:?}", state)));
+        observer.ice_gathering_state.emit(state);
+        observer.events.emit(PeerEvent::IceGatheringState(state));
+    });
+}
+
+pub(crate) extern "C" fn on_connection_state_change(state: raw::ConnectionState, ctx: *mut c_void) {
+    let observer = unsafe { Observer::from_ctx(ctx) };
+    *observer.connected.lock().unwrap() = state == raw::ConnectionState::Connected;
+    if state != raw::ConnectionState::Checking && state != raw::ConnectionState::New {
+        observer.leave_phase(EstablishmentPhase::DtlsHandshake);
+    }
+
+    observer.dispatch("connectionstatechange", || state, |observer, state| {
+        if state == raw::ConnectionState::Connected {
+            *observer.timed_out.lock().unwrap() = None;
+        }
+
+        observer.timeline.record(("connectionstatechange", format!("{:?}", state)));
+        observer.connection_state.emit(state);
+        observer.events.emit(PeerEvent::ConnectionState(state));
+    });
+}
+
+pub(crate) extern "C" fn on_track(
+    track: *mut raw::MediaStreamTrack,
+    stream_ids: *const *const c_char,
+    stream_ids_size: c_int,
+    ctx: *mut c_void,
+) {
+    let observer = unsafe { Observer::from_ctx(ctx) };
+    let stream_ids = (0..stream_ids_size.max(0) as usize)
+        .map(|i| unsafe { CStr::from_ptr(*stream_ids.add(i)) }.to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+
+    let track = || MediaStreamTrack::from_raw(track).with_stream_ids(stream_ids);
+    observer.dispatch("track", track, |observer, track| {
+        let track = Arc::new(track);
+        observer.timeline.record(("track", format!("{} {}", track.kind(), track.id())));
+        observer.track.emit(track.clone());
+        observer.events.emit(PeerEvent::Track(track));
+    });
+}
+
+pub(crate) extern "C" fn on_data_channel(channel: *mut raw::RTCDataChannel, ctx: *mut c_void) {
+    let observer = unsafe { Observer::from_ctx(ctx) };
+    observer.dispatch("datachannel", || RTCDataChannel::from_raw(channel), |observer, channel| {
+        let channel = Arc::new(channel);
+        observer.timeline.record(("datachannel", channel.label().to_string()));
+        observer.data_channel.emit(channel.clone());
+        observer.events.emit(PeerEvent::DataChannel(channel));
+    });
+}
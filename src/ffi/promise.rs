@@ -0,0 +1,78 @@
+use super::observer::Observer;
+
+use futures::task::AtomicWaker;
+use libc::*;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+type Then<T> = Box<dyn FnOnce(&T) + Send>;
+
+/// The state shared by a future and the native callback that completes it.
+///
+/// The native side holds a reference as the context of the callback, and always invokes
+/// the callback exactly once, even when the operation is abandoned, so the state is freed
+/// whether the future completed, was dropped or timed out.
+pub(crate) struct Promise<T> {
+    observer: Arc<Observer>,
+    waker: AtomicWaker,
+    result: Mutex<Option<T>>,
+    then: Mutex<Option<Then<T>>>,
+}
+
+impl<T> Promise<T> {
+    pub fn new(observer: Arc<Observer>) -> Arc<Self> {
+        Arc::new(Self {
+            waker: AtomicWaker::new(),
+            result: Mutex::new(None),
+            then: Mutex::new(None),
+            observer,
+        })
+    }
+
+    /// A reference handed over to the native side, taken back by `resolve`.
+    pub fn as_ctx(self: &Arc<Self>) -> *mut c_void {
+        Arc::into_raw(self.clone()) as *mut c_void
+    }
+
+    /// Completes the promise from the native callback, `f` copies the result on the native
+    /// thread and the future is woken on the dispatcher thread, `callback` names it in the errors.
+    ///
+    /// # Safety
+    ///
+    /// `ctx` must come from `as_ctx`, and this must be called once per context.
+    pub unsafe fn resolve(ctx: *mut c_void, callback: &'static str, f: impl FnOnce() -> T)
+    where
+        T: Send + 'static,
+    {
+        let promise = Arc::from_raw(ctx as *const Self);
+        let observer = promise.observer.clone();
+        observer.dispatch(callback, f, move |_, result| {
+            if let Some(then) = promise.then.lock().unwrap().take() {
+                then(&result);
+            }
+
+            *promise.result.lock().unwrap() = Some(result);
+            promise.waker.wake();
+        });
+    }
+
+    /// Runs `f` on the dispatcher thread with the result, before the future is woken,
+    /// so it also runs when the future was dropped. It must be set before `as_ctx`.
+    pub fn then(&self, f: impl FnOnce(&T) + Send + 'static) {
+        *self.then.lock().unwrap() = Some(Box::new(f));
+    }
+
+    pub fn poll(&self, cx: &mut Context<'_>) -> Poll<T> {
+        self.waker.register(cx.waker());
+        match self.result.lock().unwrap().take() {
+            Some(result) => Poll::Ready(result),
+            None => Poll::Pending,
+        }
+    }
+
+    /// Releases the waker of a future which will not be polled anymore,
+    /// the result is still freed with the state once the native side is done.
+    pub fn cancel(&self) {
+        self.waker.take();
+    }
+}
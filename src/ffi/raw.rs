@@ -56,6 +56,34 @@ pub enum RtcpMuxPolicy {
     Require,
 }
 
+/// Whether the candidates are gathered once, or gathered again whenever the network interfaces change.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContinualGatheringPolicy {
+    /// The candidates are gathered once, a connection whose interface goes away
+    /// fails unless ICE is restarted. This is the default value.
+    GatherOnce = 1,
+    /// The gathering never ends, the candidates of a new interface are gathered and signaled,
+    /// and the candidates of a removed interface are dropped, so the connection can move
+    /// to another interface, such as from Wi-Fi to cellular, without an ICE restart.
+    GatherContinually,
+}
+
+/// The ICE-TCP host candidates gathered besides the UDP ones, which are the only way to connect
+/// directly when UDP is blocked, at the cost of head of line blocking.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TcpCandidatePolicy {
+    /// Both the active candidates, which connect to the remote peer, and the passive candidates,
+    /// which listen for it, are gathered. This is the default value.
+    Enabled = 1,
+    /// Only the active candidates are gathered, no port is listened on, which suits a peer behind
+    /// a NAT whose passive candidates can not be reached anyway.
+    ActiveOnly,
+    /// No TCP host candidate is gathered, TURN over TCP is not affected.
+    Disabled,
+}
+
 /// RTCIceServer
 ///
 /// An array of RTCIceServer objects, each describing one server which may be used by the ICE agent;
@@ -77,6 +105,30 @@ pub struct RTCIceServer {
     pub username: Option<*const c_char>,
 }
 
+/// The protocol spoken with a proxy server.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub enum ProxyType {
+    /// An HTTP proxy, the connection is tunneled with the CONNECT method.
+    Https = 1,
+    Socks5,
+}
+
+/// RTCProxy
+///
+/// A proxy server the TCP based connections, such as TURN over TCP or TLS,
+/// are established through. UDP traffic never goes through the proxy.
+#[repr(C)]
+pub struct RTCProxy {
+    pub r#type: ProxyType,
+    pub hostname: *const c_char,
+    pub port: c_int,
+    pub username: Option<*const c_char>,
+    pub password: Option<*const c_char>,
+    /// The user agent sent to HTTP proxies.
+    pub user_agent: Option<*const c_char>,
+}
+
 /// RTCPeerConnection
 ///
 /// The RTCPeerConnection is a newly-created RTCPeerConnection,
@@ -99,7 +151,83 @@ pub struct RTCPeerConnectionConfigure {
     /// You may find in some cases that connections can be established more quickly by allowing the ICE agent
     /// to start fetching ICE candidates before you start trying to connect, so that they're already available
     /// for inspection when RTCPeerConnection.setLocalDescription() is called.
-    pub ice_candidate_pool_size: Option<c_int>,
+    pub ice_candidate_pool_size: c_int,
+    /// The range of local UDP and TCP ports the port allocator may bind,
+    /// zero for both bounds means any port.
+    pub port_range_min: c_int,
+    pub port_range_max: c_int,
+    /// The names of the network interfaces used for gathering candidates,
+    /// when set, any other interface is ignored.
+    pub allowed_interfaces: Option<*const *const c_char>,
+    pub allowed_interfaces_size: c_int,
+    /// The names of the network interfaces never used for gathering candidates.
+    pub denied_interfaces: Option<*const *const c_char>,
+    pub denied_interfaces_size: c_int,
+    /// The proxy used for the TCP based connections.
+    pub proxy: Option<*const RTCProxy>,
+    pub continual_gathering_policy: Option<ContinualGatheringPolicy>,
+    /// The interval in milliseconds of the STUN binding requests sent to the STUN servers,
+    /// which keep the NAT bindings of the server reflexive candidates open.
+    pub stun_keepalive_interval: c_int,
+    /// The interval in milliseconds of the connectivity checks on a stable candidate pair,
+    /// which double as the consent freshness checks and keep the NAT bindings of the pair open.
+    pub ice_check_interval: c_int,
+    /// The time in milliseconds without any packet after which a candidate pair is no longer receiving.
+    pub ice_receiving_timeout: c_int,
+    /// The time in milliseconds without an answer to the checks after which a candidate pair
+    /// is considered lost, once every pair is lost the connection fails.
+    /// Zero for any of the intervals and timeouts above keeps the default of libwebrtc.
+    pub ice_inactive_timeout: c_int,
+    pub tcp_candidate_policy: Option<TcpCandidatePolicy>,
+    /// Replaces the addresses of the host candidates by random ".local" names, which are only
+    /// resolved with multicast DNS on the local network, so the private addresses are not exposed
+    /// to the remote peer. The host candidates can then only connect within the local network,
+    /// and a peer which can not resolve the names only connects through the other candidates.
+    pub mdns_obfuscation: bool,
+    /// Ignores the IPv6 addresses of the network interfaces.
+    pub disable_ipv6: bool,
+    /// Ignores the link-local addresses, 169.254.0.0/16 and fe80::/10.
+    pub disable_link_local: bool,
+    /// Ignores the network interfaces which libwebrtc recognizes as VPNs.
+    pub disable_vpn: bool,
+    /// The UDP sockets of the connection, or null for the sockets of the system.
+    /// The factory is copied, the context must outlive the connection.
+    pub socket_factory: Option<*const RTCPacketSocketFactory>,
+}
+
+/// An IP address, such as "192.168.1.2" or "fe80::1", and a port.
+#[repr(C)]
+pub struct RTCSocketAddress {
+    pub ip: *const c_char,
+    pub port: c_int,
+}
+
+pub type RTCPacketSocket = c_void;
+
+/// RTCPacketSocketFactory
+///
+/// The UDP sockets of a connection implemented by the application, such as over a VPN tunnel
+/// or a userspace network stack, every function is called on the network thread. The TCP
+/// sockets are not supported, the TCP candidates and the TURN servers over TCP are not gathered.
+#[repr(C)]
+pub struct RTCPacketSocketFactory {
+    pub ctx: *mut c_void,
+    /// Binds a socket to the address, on a port of the range unless both bounds are zero, and writes
+    /// the address it is bound to, whose ip is valid until the socket is closed. Returns the context
+    /// of the socket, or null. The packets received by the socket are handed over with
+    /// rtc_packet_socket_receive until the socket is closed.
+    pub bind: extern "C" fn(
+        address: *const RTCSocketAddress,
+        min_port: c_int,
+        max_port: c_int,
+        socket: *mut RTCPacketSocket,
+        bound: *mut RTCSocketAddress,
+        ctx: *mut c_void,
+    ) -> *mut c_void,
+    /// Sends a datagram, returns false when it is dropped.
+    pub send_to: extern "C" fn(socket: *mut c_void, data: *const u8, size: usize, to: *const RTCSocketAddress) -> bool,
+    /// Closes the socket, rtc_packet_socket_receive must not be called for it once this returns.
+    pub close: extern "C" fn(socket: *mut c_void),
 }
 
 /// RTCPeerConnection
@@ -109,6 +237,105 @@ pub struct RTCPeerConnectionConfigure {
 /// the connection, and close the connection once it's no longer needed.
 pub type RTCPeerConnection = c_void;
 
+/// The bounds of the buffering of the receivers in milliseconds, carried with every video
+/// frame by the playout-delay header extension, up to 40950.
+#[repr(C)]
+pub struct RTCPlayoutDelay {
+    pub min_ms: c_int,
+    pub max_ms: c_int,
+}
+
+/// RTCRuntimeConfigure
+///
+/// The threads of a runtime, the network thread is always dedicated.
+#[repr(C)]
+pub struct RTCRuntimeConfigure {
+    /// The number of dedicated threads between 1 and 3, with a single thread every task runs on
+    /// the network thread, with two the signaling thread is shared with the worker thread.
+    pub threads: c_int,
+    /// The threads are named after the prefix followed by their role, such as "webrtc-network",
+    /// the prefix defaults to "webrtc" when it is null.
+    pub thread_name_prefix: Option<*const c_char>,
+    /// The video encoders implemented by the application, or null.
+    /// The formats are copied, the context must outlive the runtime.
+    pub video_encoder_factory: Option<*const RTCVideoEncoderFactory>,
+    /// The video decoders implemented by the application, or null.
+    /// The formats are copied, the context must outlive the runtime.
+    pub video_decoder_factory: Option<*const RTCVideoDecoderFactory>,
+    /// The field trials of the factory, such as "WebRTC-Video-Pacing/factor:1.1/", or null.
+    pub field_trials: Option<*const c_char>,
+    /// Creates the factory without the audio and video engines, the connections of the runtime
+    /// only carry data channels, and the codec factories are ignored.
+    pub data_only: bool,
+    /// The playout delay of the frames of every video sender of the runtime, or null.
+    pub playout_delay: Option<*const RTCPlayoutDelay>,
+}
+
+/// A video format, the name of a codec such as "H265" and its format specific parameters.
+#[repr(C)]
+pub struct RTCVideoFormat {
+    pub name: *const c_char,
+    /// The parameters of the "a=fmtp" attribute, such as "profile-id=1", or null.
+    pub parameters: Option<*const c_char>,
+}
+
+/// RTCVideoEncoderFactory
+///
+/// Video encoders implemented by the application, every function is called with the context
+/// returned by create, on the encoder queue of the stream. The formats are offered along with
+/// the builtin ones, and take precedence over a builtin format with the same name.
+#[repr(C)]
+pub struct RTCVideoEncoderFactory {
+    pub formats: *const RTCVideoFormat,
+    pub formats_size: usize,
+    pub ctx: *mut c_void,
+    /// Creates an encoder for one of the formats, returns its context or null.
+    pub create: extern "C" fn(format: *const RTCVideoFormat, ctx: *mut c_void) -> *mut c_void,
+    /// Prepares the encoder for the resolution, the bitrate in bits per second and the frame rate.
+    pub init: extern "C" fn(
+        encoder: *mut c_void,
+        width: c_int,
+        height: c_int,
+        max_bitrate: c_int,
+        max_frame_rate: c_int,
+    ) -> bool,
+    /// Encodes a frame, the callback is called synchronously for the encoded frame, if any.
+    pub encode: extern "C" fn(
+        encoder: *mut c_void,
+        frame: *const RTCVideoFrame,
+        keyframe: bool,
+        ctx: *mut c_void,
+        callback: extern "C" fn(*const RTCEncodedFrame, *mut c_void),
+    ) -> bool,
+    /// Updates the target bitrate in bits per second and the frame rate.
+    pub set_rates: extern "C" fn(encoder: *mut c_void, bitrate: c_int, frame_rate: c_double),
+    pub release: extern "C" fn(encoder: *mut c_void),
+}
+
+/// RTCVideoDecoderFactory
+///
+/// Video decoders implemented by the application, every function is called with the context
+/// returned by create, on the decoder thread of the stream.
+#[repr(C)]
+pub struct RTCVideoDecoderFactory {
+    pub formats: *const RTCVideoFormat,
+    pub formats_size: usize,
+    pub ctx: *mut c_void,
+    /// Creates a decoder for one of the formats, returns its context or null.
+    pub create: extern "C" fn(format: *const RTCVideoFormat, ctx: *mut c_void) -> *mut c_void,
+    /// Decodes a frame, the callback is called synchronously for the decoded frame, if any.
+    pub decode: extern "C" fn(
+        decoder: *mut c_void,
+        frame: *const RTCEncodedFrame,
+        ctx: *mut c_void,
+        callback: extern "C" fn(*const RTCVideoFrame, *mut c_void),
+    ) -> bool,
+    pub release: extern "C" fn(decoder: *mut c_void),
+}
+
+/// The threads and the factory shared by the connections created on it.
+pub type RTCRuntime = c_void;
+
 /// RTCIceCandidate
 ///
 /// The RTCIceCandidate interface¡ªpart of the WebRTC API¡ªrepresents a candidate Interactive Connectivity
@@ -138,6 +365,17 @@ pub struct RTCIceCandidate {
     pub sdp_mline_index: c_int,
 }
 
+/// RTCError
+///
+/// An error reported by the native side, it is only valid for the duration of the callback it is passed to.
+#[repr(C)]
+pub struct RTCError {
+    /// The value of webrtc::RTCErrorType, such as 5 for a syntax error or 6 for an invalid state.
+    pub code: c_int,
+    /// A human readable description of the error.
+    pub message: *const c_char,
+}
+
 /// MediaStreamTrack
 ///
 /// The MediaStreamTrack interface represents a single media track within a stream;
@@ -179,10 +417,269 @@ pub struct MediaStreamTrack {
     pub frame_rate: c_int,
 }
 
+/// RTCVideoFrame
+///
+/// A decoded video frame in the I420 format, the planes are only valid for the duration
+/// of the handler it is passed to.
+#[repr(C)]
+pub struct RTCVideoFrame {
+    pub width: c_int,
+    pub height: c_int,
+    /// The render time of the frame, in microseconds of the monotonic clock of libwebrtc.
+    pub timestamp_us: i64,
+    /// The clockwise rotation to apply before rendering, in degrees.
+    pub rotation: c_int,
+    pub data_y: *const u8,
+    pub stride_y: c_int,
+    pub data_u: *const u8,
+    pub stride_u: c_int,
+    pub data_v: *const u8,
+    pub stride_v: c_int,
+}
+
+/// RTCAudioFrame
+///
+/// 10ms of interleaved 16 bits samples, the samples are only valid for the duration
+/// of the handler it is passed to.
 #[repr(C)]
-pub struct MediaStreamTrackFrame {
-    pub buf: *const c_char,
-    pub len: u64,
+pub struct RTCAudioFrame {
+    pub data: *const i16,
+    pub sample_rate: c_int,
+    pub channels: c_int,
+    /// The number of samples per channel.
+    pub frames: c_int,
+    /// The time the frame was delivered, in microseconds of the monotonic clock of libwebrtc.
+    pub timestamp_us: i64,
+}
+
+/// RTCEncodedFrame
+///
+/// An encoded frame, the data is only valid for the duration of the callback it is passed to.
+#[repr(C)]
+pub struct RTCEncodedFrame {
+    pub data: *const u8,
+    pub size: usize,
+    /// The timestamp of the video frame it was encoded from,
+    /// or the time of the first sample since the audio encoder was created.
+    pub timestamp_us: i64,
+    pub keyframe: bool,
+}
+
+/// A sink attached to a track.
+pub type RTCSink = c_void;
+
+/// An observer of the events of a track.
+pub type RTCTrackObserver = c_void;
+
+/// An event of a track received from the remote peer.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrackEvent {
+    /// The source of the track is temporarily unable to provide media, such as when
+    /// the remote peer stops sending.
+    Mute = 1,
+    /// The source of the track provides media again.
+    Unmute,
+    /// The track will never provide media again, such as when its transceiver is stopped.
+    Ended,
+}
+
+/// The DTLS transport of a media id, along with its ICE transport.
+pub type RTCTransport = c_void;
+
+/// An observer of the events of a transport.
+pub type RTCTransportObserver = c_void;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DtlsTransportState {
+    /// The DTLS handshake has not started yet.
+    New = 1,
+    /// The DTLS handshake is underway.
+    Connecting,
+    /// The handshake has completed and the certificate of the remote peer matches its fingerprint.
+    Connected,
+    /// The transport has been closed, such as when a close alert was received.
+    Closed,
+    /// The transport failed, such as when the handshake failed or the certificate of the remote peer
+    /// does not match its fingerprint.
+    Failed,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IceTransportState {
+    New = 1,
+    Checking,
+    Connected,
+    Completed,
+    Disconnected,
+    Failed,
+    Closed,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransportError {
+    /// The DTLS handshake failed, such as when a fatal alert was received or the remote peer
+    /// did not answer the handshake.
+    HandshakeFailed = 1,
+    /// The DTLS handshake failed because the peers have no cipher suite in common.
+    IncompatibleCipherSuite,
+    /// The DTLS transport failed after the handshake, such as when the certificate of the remote
+    /// peer does not match the fingerprint of its description.
+    DtlsFailed,
+}
+
+/// The direction of a packet tapped on a transceiver.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RtpDirection {
+    /// The packet was received from the remote peer.
+    Incoming = 1,
+    /// The packet is sent to the remote peer.
+    Outgoing,
+}
+
+/// RTCRtpPacket
+///
+/// A serialized rtp or rtcp packet, the data is only valid for the duration of the handler it is passed to.
+#[repr(C)]
+pub struct RTCRtpPacket {
+    pub direction: RtpDirection,
+    pub rtcp: bool,
+    pub data: *const u8,
+    pub size: usize,
+}
+
+/// A tap on the transport of a transceiver.
+pub type RTCRtpTap = c_void;
+
+/// The severity of the messages logged by libwebrtc.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogSeverity {
+    /// The debugging messages, including the sensitive ones.
+    Verbose = 1,
+    Info,
+    Warning,
+    Error,
+    /// No message is logged.
+    None,
+}
+
+/// The codec of an encoded frame, which tells how much of the frame its packetizer reads.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrameCodec {
+    /// The audio codecs and the video codecs packetized with the generic packetizer,
+    /// whose payload is opaque to the packetizer.
+    Generic = 1,
+    Vp8,
+    Vp9,
+    Av1,
+    H264,
+    /// A video codec this library does not know the payload format of.
+    Other,
+}
+
+/// RTCTransformableFrame
+///
+/// An encoded frame of a sender before packetization, or of a receiver after depacketization,
+/// the data is only valid for the duration of the handler it is passed to.
+#[repr(C)]
+pub struct RTCTransformableFrame {
+    pub kind: MediaType,
+    /// Outgoing for the frames of a sender, incoming for the frames of a receiver.
+    pub direction: RtpDirection,
+    pub ssrc: u32,
+    pub payload_type: u8,
+    /// The rtp timestamp of the frame, in the clock rate of its payload.
+    pub timestamp: u32,
+    /// Always false for audio frames.
+    pub keyframe: bool,
+    pub codec: FrameCodec,
+    pub data: *const u8,
+    pub size: usize,
+    /// The native frame, only used by rtc_transformable_frame_set_data.
+    pub frame: *mut c_void,
+}
+
+/// A transform registered on a sender or a receiver.
+pub type RTCFrameTransform = c_void;
+
+/// A transceiver of a connection.
+pub type RTCRtpTransceiver = c_void;
+
+/// The sender of a transceiver.
+pub type RTCRtpSender = c_void;
+
+/// The receiver of a transceiver.
+pub type RTCRtpReceiver = c_void;
+
+/// A codec supported by the senders or the receivers of a runtime.
+#[repr(C)]
+pub struct RTCRtpCodecCapability {
+    /// The kind and the name of the codec, such as "video/AV1" or "audio/opus".
+    pub mime_type: *const c_char,
+    /// The clock rate of the rtp timestamps, zero when unset.
+    pub clock_rate: c_int,
+    /// The number of audio channels, zero for the video codecs.
+    pub channels: c_int,
+    /// The format specific parameters of the codec, such as "minptime=10;useinbandfec=1",
+    /// null when there is none.
+    pub sdp_fmtp_line: *const c_char,
+}
+
+/// An rtp header extension supported by the senders or the receivers of a runtime.
+#[repr(C)]
+pub struct RTCRtpHeaderExtensionCapability {
+    pub uri: *const c_char,
+    /// The id offered for the extension, zero when unset.
+    pub preferred_id: c_int,
+}
+
+#[repr(C)]
+pub struct RTCRtpCapabilities {
+    pub codecs: *const RTCRtpCodecCapability,
+    pub codecs_size: usize,
+    pub header_extensions: *const RTCRtpHeaderExtensionCapability,
+    pub header_extensions_size: usize,
+}
+
+/// The preferred direction of a transceiver, or the direction negotiated for it.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransceiverDirection {
+    SendRecv = 1,
+    SendOnly,
+    RecvOnly,
+    Inactive,
+    /// The transceiver is stopped, it neither sends nor receives and will never be used again.
+    Stopped,
+}
+
+pub type RTCVideoEncoder = c_void;
+
+pub type RTCAudioEncoder = c_void;
+
+/// RTCOfferAnswerOptions
+///
+/// The options of createOffer() and createAnswer(), the legacy offerToReceive options only apply
+/// to offers, a value of -1 leaves the decision to the transceivers of the connection.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct RTCOfferAnswerOptions {
+    /// To restart ICE on an active connection, set this to true. This will cause the returned offer
+    /// to have different credentials than those already in place.
+    pub ice_restart: bool,
+    /// Enables voice activity detection, which lets the audio codecs skip the silences.
+    /// The default is true.
+    pub voice_activity_detection: bool,
+    /// Whether the remote peer is offered to send audio, regardless of the local audio tracks.
+    pub offer_to_receive_audio: c_int,
+    /// Whether the remote peer is offered to send video, regardless of the local video tracks.
+    pub offer_to_receive_video: c_int,
 }
 
 /// An enum describing the session description's type.
@@ -221,12 +718,105 @@ pub struct RTCSessionDescription {
 /// (the actual limit may vary from browser to browser).
 #[repr(C)]
 pub struct RTCDataChannel {
-    pub id: *const c_char,
+    /// Returns an ID number (between 0 and 65,534) which uniquely identifies the RTCDataChannel.
+    pub id: c_int,
+    /// Returns a string containing a name describing the data channel.
+    /// These labels are not required to be unique.
     pub label: *const c_char,
+    /// Whether the messages are delivered in the order they were sent.
+    pub ordered: bool,
+    /// Whether the messages are retransmitted until they are delivered,
+    /// rather than within a limited number of retransmissions or a limited time.
+    pub reliable: bool,
 }
 
+/// The options of a data channel opened by the local peer.
 #[repr(C)]
-#[derive(Clone, Copy, Debug)]
+pub struct RTCDataChannelInit {
+    pub ordered: bool,
+    /// The time in milliseconds during which a message is retransmitted, -1 for no limit.
+    pub max_retransmit_time: c_int,
+    /// The number of times a message is retransmitted, -1 for no limit.
+    pub max_retransmits: c_int,
+    pub priority: Priority,
+}
+
+/// What a video sender gives up when the CPU is overused or the bandwidth is too low to encode
+/// the frames at their resolution and frame rate.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DegradationPreference {
+    /// The sender does not adapt, the frames are encoded as they are written, even when the
+    /// CPU is overused.
+    Disabled = 1,
+    MaintainFramerate,
+    MaintainResolution,
+    Balanced,
+}
+
+/// The libwebrtc the library was built against, as given to the build by the LIBWEBRTC_MILESTONE
+/// and LIBWEBRTC_COMMIT definitions.
+#[repr(C)]
+pub struct RTCVersion {
+    /// The milestone of the branch, such as 96, zero when it is not known.
+    pub milestone: c_int,
+    /// The commit hash, an empty string when it is not known, the string is static.
+    pub commit: *const c_char,
+}
+
+/// The limits of the encodings of a sender, they are lowered further by the bandwidth estimation
+/// and the CPU adaptation of libwebrtc.
+#[repr(C)]
+#[derive(Default)]
+pub struct RTCEncodingParameters {
+    /// The bitrate of an encoding, in bits per second.
+    pub max_bitrate_bps: c_int,
+    /// The frame rate of an encoding, the frames written above it are dropped.
+    pub max_framerate: c_double,
+    /// The factor the width and the height of the frames are divided by before they are encoded.
+    pub scale_resolution_down_by: c_double,
+}
+
+/// The SCTP transport carrying the data channels of a connection.
+#[repr(C)]
+#[derive(Default)]
+pub struct RTCSctpTransport {
+    /// The largest message the data channels send, the smaller of the local limit and the limit
+    /// the remote peer announced in its description.
+    pub max_message_size: c_double,
+    /// The number of data channels which can be open at once, as negotiated when the association
+    /// was established.
+    pub max_channels: c_int,
+}
+
+/// The priority of a sender or a data channel, which the bandwidth allocator favors when the link
+/// is constrained, and which sets the DSCP marking of its packets when it is enabled.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    VeryLow = 1,
+    #[default]
+    Low,
+    Medium,
+    High,
+}
+
+/// The state of the underlying data connection of a data channel.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DataChannelState {
+    /// The user agent is attempting to establish the underlying data transport.
+    Connecting = 1,
+    /// The underlying data transport is established and communication is possible.
+    Open,
+    /// The process of closing down the underlying data transport has started.
+    Closing,
+    /// The underlying data transport has been closed or could not be established.
+    Closed,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ConnectionState {
     /// At least one of the connection's ICE transports (RTCIceTransport or RTCDtlsTransport objects)
     /// is in the new state, and none of them are in one of the following states: connecting, checking,
@@ -248,13 +838,154 @@ pub enum ConnectionState {
     Failed,
 }
 
-#[link(name = "rtc_wrapper")]
-extern "C" {
+/// Describes the state of the signaling process on the local end of the connection
+/// while connecting or reconnecting to another peer.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignalingState {
+    /// There is no ongoing exchange of offer and answer underway.
+    Stable = 1,
+    /// The local peer has called RTCPeerConnection.setLocalDescription(),
+    /// passing in SDP representing an offer.
+    HaveLocalOffer,
+    /// The offer sent by the remote peer has been applied and an answer has been created
+    /// and applied by calling RTCPeerConnection.setLocalDescription() with a provisional answer.
+    HaveLocalPrAnswer,
+    /// The remote peer has created an offer and used the signaling server to deliver it
+    /// to the local peer, which has set the offer as the remote description by calling
+    /// RTCPeerConnection.setRemoteDescription().
+    HaveRemoteOffer,
+    /// A provisional answer has been received and successfully applied in response to
+    /// an offer previously sent and established by calling setLocalDescription().
+    HaveRemotePrAnswer,
+    /// The RTCPeerConnection has been closed.
+    Closed,
+}
+
+/// Describes the state of the ICE agent associated with the connection.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IceConnectionState {
+    /// The ICE agent is gathering addresses or is waiting to be given remote candidates.
+    New = 1,
+    /// The ICE agent has been given one or more remote candidates and is checking pairs of
+    /// local and remote candidates against one another to try to find a compatible match.
+    Checking,
+    /// A usable pairing of local and remote candidates has been found for all components of
+    /// the connection, and the connection has been established.
+    Connected,
+    /// The ICE agent has finished gathering candidates, has checked all pairs against one
+    /// another, and has found a connection for all components.
+    Completed,
+    /// The ICE candidate has checked all candidates pairs against one another and has failed
+    /// to find compatible matches for all components of the connection.
+    Failed,
+    /// Checks to ensure that components are still connected failed for at least one component.
+    Disconnected,
+    /// The ICE agent for this RTCPeerConnection has shut down and is no longer handling requests.
+    Closed,
+}
+
+/// Describes the ICE gathering state of the connection.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IceGatheringState {
+    /// The peer connection was just created and hasn't done any networking yet.
+    New = 1,
+    /// The ICE agent is in the process of gathering candidates for the connection.
+    Gathering,
+    /// The ICE agent has finished gathering candidates.
+    Complete,
+}
+
+/// The kind of media carried by a sender, receiver or track.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MediaType {
+    Audio = 1,
+    Video,
+}
+
+/// Declares the functions of the native library, linked against it by default, or looked up
+/// in the library loaded at runtime with the `dlopen` feature, in which case a function called
+/// before the library is loaded aborts the process.
+macro_rules! native_library {
+    ($($(#[$meta:meta])* pub fn $name:ident($($arg:ident: $ty:ty),* $(,)?) $(-> $ret:ty)?;)*) => {
+        // optional fields and arguments rely on the null pointer and the zero discriminant
+        // being the representation of `None`, which is what the native side checks for.
+        #[cfg(not(feature = "dlopen"))]
+        #[allow(improper_ctypes)]
+        #[link(name = "rtc_wrapper")]
+        extern "C" {
+            $($(#[$meta])* pub fn $name($($arg: $ty),*) $(-> $ret)?;)*
+        }
+
+        /// The functions of the loaded library, kept alongside it.
+        #[cfg(feature = "dlopen")]
+        #[allow(improper_ctypes_definitions)]
+        pub(crate) struct Library {
+            $($name: unsafe extern "C" fn($($ty),*) $(-> $ret)?,)*
+            _library: libloading::Library,
+        }
+
+        #[cfg(feature = "dlopen")]
+        impl Library {
+            /// Looks every function up, fails on the first one missing, such as with a library
+            /// older than the crate.
+            pub(crate) fn new(library: libloading::Library) -> Result<Self, libloading::Error> {
+                unsafe {
+                    Ok(Self {
+                        $($name: *library.get(concat!(stringify!($name), "\0").as_bytes())?,)*
+                        _library: library,
+                    })
+                }
+            }
+        }
+
+        $(
+            #[cfg(feature = "dlopen")]
+            #[allow(improper_ctypes_definitions, clippy::missing_safety_doc)]
+            $(#[$meta])*
+            pub unsafe extern "C" fn $name($($arg: $ty),*) $(-> $ret)? {
+                (library().$name)($($arg),*)
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "dlopen")]
+pub(crate) static LIBRARY: std::sync::OnceLock<Library> = std::sync::OnceLock::new();
+
+#[cfg(feature = "dlopen")]
+fn library() -> &'static Library {
+    LIBRARY.get().expect("the rtc_wrapper library is not loaded, see webrtc::load_library")
+}
+
+native_library! {
+    /// Returns a newly-created runtime, which owns the threads and the factory shared by
+    /// the connections created on it, or null when the factory could not be created.
+    pub fn create_rtc_runtime(config: *const RTCRuntimeConfigure) -> *const RTCRuntime;
+    /// Frees a runtime created by create_rtc_runtime, every connection created on it must be freed.
+    pub fn rtc_free_runtime(runtime: *const RTCRuntime);
+    /// Fills the version of libwebrtc the library was built against.
+    pub fn rtc_get_version(version: *mut RTCVersion);
+    /// Returns the resident memory of the process in bytes, or -1 where the system does not
+    /// report it.
+    pub fn rtc_get_resident_memory() -> i64;
     /// Returns a newly-created RTCPeerConnection, which represents a
     /// connection between the local device and a remote peer.
     pub fn create_rtc_peer_connection(
+        runtime: *const RTCRuntime,
         config: *const RTCPeerConnectionConfigure,
     ) -> *const RTCPeerConnection;
+    /// Hands a datagram received by a socket of an RTCPacketSocketFactory over to the connection,
+    /// from any thread, the data is copied.
+    pub fn rtc_packet_socket_receive(
+        socket: *mut RTCPacketSocket,
+        data: *const u8,
+        size: usize,
+        from: *const RTCSocketAddress,
+    );
     /// When a web site or app using RTCPeerConnection receives a new ICE candidate from the remote peer
     /// over its signaling channel, it delivers the newly-received candidate to the browser's ICE agent by
     /// calling RTCPeerConnection.addIceCandidate(). This adds this new remote candidate to the RTCPeerConnection's
@@ -271,23 +1002,162 @@ extern "C" {
     /// During negotiation, your app will likely receive many candidates which you'll deliver to
     /// the ICE agent in this way, allowing it to build up a list of potential connection methods.
     /// This is covered in more detail in the articles WebRTC connectivity and Signaling and video calling.
+    ///
+    /// Returns false when the candidate can not be parsed, or can not be added, such as before
+    /// the remote description is set.
     pub fn rtc_add_ice_candidate(
         peer: *const RTCPeerConnection,
         icecandidate: *const RTCIceCandidate,
+    ) -> bool;
+    /// Adds a candidate as rtc_add_ice_candidate does, the callback is called once the candidate
+    /// is added, with a null error, or with the reason it was rejected. The candidate is copied.
+    pub fn rtc_add_ice_candidate_async(
+        peer: *const RTCPeerConnection,
+        icecandidate: *const RTCIceCandidate,
+        ctx: *mut c_void,
+        callback: extern "C" fn(*const RTCError, *mut c_void),
     );
 
-    pub fn media_stream_track_write_frame(
+    /// Attaches a sink to a video track, the handler receives every decoded frame on the
+    /// thread delivering it until the sink is removed. Returns null if the track is not a video track.
+    pub fn media_stream_track_add_video_sink(
         track: *const MediaStreamTrack,
-        frame: *const MediaStreamTrackFrame,
-    );
-
-    pub fn media_stream_track_on_frame(
+        ctx: *mut c_void,
+        handler: extern "C" fn(*const RTCVideoFrame, *mut c_void),
+    ) -> *const RTCSink;
+    /// Attaches a sink to an audio track, the handler receives every 10ms of audio on the
+    /// thread delivering it until the sink is removed. Returns null if the track is not an audio track.
+    pub fn media_stream_track_add_audio_sink(
         track: *const MediaStreamTrack,
-        callback: extern "C" fn(MediaStreamTrackFrame),
-    );
+        ctx: *mut c_void,
+        handler: extern "C" fn(*const RTCAudioFrame, *mut c_void),
+    ) -> *const RTCSink;
+    /// Detaches and frees a sink, the handler is no longer called once this returns.
+    pub fn media_stream_track_remove_sink(sink: *const RTCSink);
+    /// Enables or disables a track, a disabled video track sends or renders black frames
+    /// and a disabled audio track silence.
+    pub fn media_stream_track_set_enabled(track: *const MediaStreamTrack, enabled: bool);
+    /// Whether the track is enabled, the enabled field is only the state when the track was handed over.
+    pub fn media_stream_track_get_enabled(track: *const MediaStreamTrack) -> bool;
+    /// Observes a track, the handler is called on the signaling thread when the track is muted,
+    /// unmuted or ended, until the observer is removed.
+    pub fn media_stream_track_add_observer(
+        track: *const MediaStreamTrack,
+        ctx: *mut c_void,
+        handler: extern "C" fn(TrackEvent, *mut c_void),
+    ) -> *const RTCTrackObserver;
+    /// Removes and frees an observer, the handler is no longer called once this returns.
+    /// The RTCDtlsTransport of a media id, shared by the media sections bundled with it, along with
+    /// its RTCIceTransport. Returns null when the media id has no transport, such as before the
+    /// descriptions are set.
+    pub fn rtc_get_transport(peer: *const RTCPeerConnection, mid: *const c_char) -> *const RTCTransport;
+    pub fn rtc_free_transport(transport: *const RTCTransport);
+    /// The state of the DTLS transport.
+    pub fn rtc_get_dtls_transport_state(transport: *const RTCTransport) -> DtlsTransportState;
+    /// The state of the ICE transport underneath the DTLS transport.
+    pub fn rtc_get_ice_transport_state(transport: *const RTCTransport) -> IceTransportState;
+    /// Observes a transport, the handlers are called on the network thread when the state of the DTLS
+    /// or of the ICE transport changes, and when the DTLS transport fails, before the failed state,
+    /// until the observer is removed.
+    pub fn rtc_transport_add_observer(
+        transport: *const RTCTransport,
+        ctx: *mut c_void,
+        on_dtls_state_change: extern "C" fn(DtlsTransportState, *mut c_void),
+        on_ice_state_change: extern "C" fn(IceTransportState, *mut c_void),
+        on_error: extern "C" fn(TransportError, *mut c_void),
+    ) -> *const RTCTransportObserver;
+    /// Removes and frees an observer, the handlers are no longer called once this returns.
+    pub fn rtc_transport_remove_observer(observer: *const RTCTransportObserver);
+    pub fn media_stream_track_remove_observer(observer: *const RTCTrackObserver);
+    /// Creates a VP8 encoder targeting the bitrate in bits per second.
+    pub fn rtc_create_video_encoder(bitrate: c_int, frame_rate: c_int) -> *const RTCVideoEncoder;
+    /// Encodes a frame, the callback is called synchronously for the encoded frame, if any.
+    pub fn rtc_video_encoder_encode(
+        encoder: *const RTCVideoEncoder,
+        frame: *const RTCVideoFrame,
+        keyframe: bool,
+        ctx: *mut c_void,
+        callback: extern "C" fn(*const RTCEncodedFrame, *mut c_void),
+    ) -> bool;
+    pub fn rtc_free_video_encoder(encoder: *const RTCVideoEncoder);
+    /// Creates an Opus encoder targeting the bitrate in bits per second, the encoded packets
+    /// are 20ms long and sampled at 48kHz whatever the rate of the input.
+    pub fn rtc_create_audio_encoder(channels: c_int, bitrate: c_int) -> *const RTCAudioEncoder;
+    /// Encodes a frame, the callback is called synchronously for every packet completed by the frame.
+    /// Returns false when the frame does not have the channels of the encoder.
+    pub fn rtc_audio_encoder_encode(
+        encoder: *const RTCAudioEncoder,
+        frame: *const RTCAudioFrame,
+        ctx: *mut c_void,
+        callback: extern "C" fn(*const RTCEncodedFrame, *mut c_void),
+    ) -> bool;
+    pub fn rtc_free_audio_encoder(encoder: *const RTCAudioEncoder);
+    /// Creates a local video track whose frames are written by media_stream_track_write_video_frame,
+    /// the track must be released with rtc_free_media_stream_track.
+    pub fn rtc_create_video_track(runtime: *const RTCRuntime, id: *const c_char) -> *mut MediaStreamTrack;
+    /// Creates a local audio track whose frames are written by media_stream_track_write_audio_frame,
+    /// the track must be released with rtc_free_media_stream_track.
+    pub fn rtc_create_audio_track(runtime: *const RTCRuntime, id: *const c_char) -> *mut MediaStreamTrack;
+    /// Delivers a frame to the senders of a local video track, the frame is copied and stamped
+    /// with the current time. Returns false if the track is not a local video track.
+    pub fn media_stream_track_write_video_frame(
+        track: *const MediaStreamTrack,
+        frame: *const RTCVideoFrame,
+    ) -> bool;
+    /// Delivers 10ms of audio to the senders of a local audio track.
+    /// Returns false if the track is not a local audio track.
+    pub fn media_stream_track_write_audio_frame(
+        track: *const MediaStreamTrack,
+        frame: *const RTCAudioFrame,
+    ) -> bool;
     /// The RTCPeerConnection method addTrack() adds a new media track to the set of tracks
-    /// which will be transmitted to the other peer.
-    pub fn rtc_add_track(peer: *const RTCPeerConnection, track: *const MediaStreamTrack);
+    /// which will be transmitted to the other peer, as part of the streams with the given ids,
+    /// the remote peer groups the tracks of a stream, such as the audio and the video of a participant.
+    pub fn rtc_add_track(
+        peer: *const RTCPeerConnection,
+        track: *const MediaStreamTrack,
+        stream_ids: *const *const c_char,
+        stream_ids_size: c_int,
+    ) -> bool;
+    /// Attaches a tap to the transceiver with the given mid, the handler receives the rtp and rtcp packets
+    /// of the transceiver on the network thread until the tap is removed. When intercepting, the outgoing
+    /// rtp packets are not sent and the incoming rtp packets are not decoded, rtcp always flows.
+    /// Returns null if there is no negotiated transceiver with this mid.
+    pub fn rtc_add_rtp_tap(
+        peer: *const RTCPeerConnection,
+        mid: *const c_char,
+        intercept: bool,
+        ctx: *mut c_void,
+        handler: extern "C" fn(*const RTCRtpPacket, *mut c_void),
+    ) -> *const RTCRtpTap;
+    /// Sends an rtp or rtcp packet on the transport of the tapped transceiver, the packet is sent as is,
+    /// apart from the srtp protection. Returns false if the transport is gone.
+    pub fn rtc_rtp_tap_send(
+        tap: *const RTCRtpTap,
+        data: *const u8,
+        size: usize,
+        rtcp: bool,
+    ) -> bool;
+    /// Detaches and frees a tap, the handler is no longer called once this returns.
+    pub fn rtc_remove_rtp_tap(tap: *const RTCRtpTap);
+    /// Replaces the payload of a frame being transformed, the data is copied.
+    pub fn rtc_transformable_frame_set_data(
+        frame: *const RTCTransformableFrame,
+        data: *const u8,
+        size: usize,
+    );
+    /// Registers a transform on the sender (outgoing) or the receiver (incoming) of the transceiver
+    /// with the given mid, the handler is called for every encoded frame on the thread delivering it,
+    /// and the frame is dropped when it returns false. Returns null if there is no transceiver with this mid.
+    pub fn rtc_add_frame_transform(
+        peer: *const RTCPeerConnection,
+        mid: *const c_char,
+        direction: RtpDirection,
+        ctx: *mut c_void,
+        handler: extern "C" fn(*const RTCTransformableFrame, *mut c_void) -> bool,
+    ) -> *const RTCFrameTransform;
+    /// Removes the handler of a transform, the frames pass through unchanged once this returns.
+    pub fn rtc_remove_frame_transform(transform: *const RTCFrameTransform);
     /// The RTCPeerConnection.close() method closes the current peer connection.
     ///
     /// Calling this method terminates the RTCPeerConnection's ICE agent, ending any ongoing ICE processing
@@ -295,15 +1165,25 @@ extern "C" {
     /// including TURN permissions. All RTCRtpSender objects are considered to be stopped once this
     /// returns (they may still be in the process of stopping, but for all intents and purposes, they're stopped).
     pub fn rtc_close(peer: *const RTCPeerConnection);
+    /// Stops sending on every RTCRtpSender of the connection by detaching its track,
+    /// the transceivers are kept so no renegotiation is needed.
+    pub fn rtc_stop_senders(peer: *const RTCPeerConnection);
+    /// Closes every RTCDataChannel the remote peer has opened on the connection.
+    pub fn rtc_close_data_channels(peer: *const RTCPeerConnection);
+    /// Frees a connection created by create_rtc_peer_connection, the connection must be closed.
+    pub fn rtc_free_peer_connection(peer: *const RTCPeerConnection);
     /// The createAnswer() method on the RTCPeerConnection interface creates an SDP answer to an offer received
     /// from a remote peer during the offer/answer negotiation of a WebRTC connection. The answer contains
     /// information about any media already attached to the session, codecs and options supported by the browser,
     /// and any ICE candidates already gathered. The answer is delivered to the returned Promise, and should
     /// then be sent to the source of the offer to continue the negotiation process.
+    ///
+    /// The description handed to the callback belongs to the callback, it must be released with rtc_free.
     pub fn rtc_create_answer(
         peer: *const RTCPeerConnection,
+        options: *const RTCOfferAnswerOptions,
         ctx: *mut c_void,
-        callback: extern "C" fn(*const RTCSessionDescription, *mut c_void),
+        callback: extern "C" fn(*const RTCSessionDescription, *const RTCError, *mut c_void),
     );
     /// The createOffer() method of the RTCPeerConnection interface initiates the creation of an SDP offer for
     /// the purpose of starting a new WebRTC connection to a remote peer. The SDP offer includes information
@@ -311,10 +1191,13 @@ extern "C" {
     /// by the browser, and any candidates already gathered by the ICE agent, for the purpose of being sent
     /// over the signaling channel to a potential peer to request a connection or to update the configuration
     /// of an existing connection.
+    ///
+    /// The description handed to the callback belongs to the callback, it must be released with rtc_free.
     pub fn rtc_create_offer(
         peer: *const RTCPeerConnection,
+        options: *const RTCOfferAnswerOptions,
         ctx: *mut c_void,
-        callback: extern "C" fn(*const RTCSessionDescription, *mut c_void),
+        callback: extern "C" fn(*const RTCSessionDescription, *const RTCError, *mut c_void),
     );
     /// The RTCPeerConnection method setLocalDescription() changes the local description associated with
     /// the connection. This description specifies the properties of the local end of the connection,
@@ -329,7 +1212,8 @@ extern "C" {
     pub fn rtc_set_local_description(
         peer: *const RTCPeerConnection,
         desc: *const RTCSessionDescription,
-        callback: extern "C" fn(c_int),
+        ctx: *mut c_void,
+        callback: extern "C" fn(*const RTCError, *mut c_void),
     );
     /// The RTCPeerConnection method setRemoteDescription() sets the specified session description
     /// as the remote peer's current offer or answer. The description specifies the properties
@@ -349,7 +1233,8 @@ extern "C" {
     pub fn rtc_set_remote_description(
         peer: *const RTCPeerConnection,
         desc: *const RTCSessionDescription,
-        callback: extern "C" fn(c_int),
+        ctx: *mut c_void,
+        callback: extern "C" fn(*const RTCError, *mut c_void),
     );
     /// The connectionstatechange event is sent to the onconnectionstatechange event handler on
     /// an RTCPeerConnection object after a new track has been added to an RTCRtpReceiver which
@@ -357,15 +1242,417 @@ extern "C" {
     /// and is one of the string values: new, connecting, connected, disconnected, failed, or closed.
     pub fn rtc_on_connectionstatechange(
         peer: *const RTCPeerConnection,
-        callback: extern "C" fn(ConnectionState),
+        ctx: *mut c_void,
+        handler: extern "C" fn(ConnectionState, *mut c_void),
     );
     /// A datachannel event is sent to an RTCPeerConnection instance when an RTCDataChannel has
     /// been added to the connection, as a result of the remote peer calling
     /// RTCPeerConnection.createDataChannel().
+    ///
+    /// The handler owns the channel, which must be released with rtc_free_data_channel.
     pub fn rtc_on_datachannel(
         peer: *const RTCPeerConnection,
-        callback: extern "C" fn(RTCDataChannel),
+        ctx: *mut c_void,
+        handler: extern "C" fn(*mut RTCDataChannel, *mut c_void),
+    );
+    /// Releases a data channel handed over by the datachannel event.
+    pub fn rtc_free_data_channel(channel: *mut RTCDataChannel);
+    /// The read-only RTCDataChannel property readyState returns an enum of type RTCDataChannelState
+    /// which indicates the state of the data channel's underlying data connection.
+    pub fn rtc_get_data_channel_state(channel: *const RTCDataChannel) -> DataChannelState;
+    /// The read-only RTCDataChannel property bufferedAmount returns the number of bytes of data
+    /// currently queued to be sent over the data channel.
+    pub fn rtc_get_data_channel_buffered_amount(channel: *const RTCDataChannel) -> u64;
+    /// The priority of a data channel, as chosen by the peer which opened it.
+    pub fn rtc_get_data_channel_priority(channel: *const RTCDataChannel) -> Priority;
+    /// The createDataChannel() method on the RTCPeerConnection interface creates a new channel linked
+    /// with the remote peer, over which any kind of data may be transmitted. Returns null on failure,
+    /// in which case the error is written, its message must be released with rtc_free_string.
+    pub fn rtc_create_data_channel(
+        peer: *const RTCPeerConnection,
+        label: *const c_char,
+        init: *const RTCDataChannelInit,
+        error: *mut RTCError,
+    ) -> *mut RTCDataChannel;
+    /// Registers the handlers of the events of a data channel, replacing the previous ones. The messages
+    /// received before the handlers are registered are queued and delivered once they are.
+    /// The handlers are called on the signaling thread, the data of a message is only valid for the duration of the handler.
+    pub fn rtc_set_data_channel_observer(
+        channel: *const RTCDataChannel,
+        ctx: *mut c_void,
+        on_message: extern "C" fn(*const u8, usize, bool, *mut c_void),
+        on_state_change: extern "C" fn(DataChannelState, *mut c_void),
+        on_buffered_amount_change: extern "C" fn(u64, *mut c_void),
+    );
+    /// Removes the handlers of a data channel, no handler is called once it returns.
+    pub fn rtc_remove_data_channel_observer(channel: *const RTCDataChannel);
+    /// The send() method of the RTCDataChannel interface sends data across the data channel to the remote peer.
+    /// Returns false when the channel is not open or its send buffer is full.
+    pub fn rtc_send_data_channel_message(
+        channel: *const RTCDataChannel,
+        data: *const u8,
+        size: usize,
+        binary: bool,
+    ) -> bool;
+    /// The RTCDataChannel.close() method closes the RTCDataChannel. Either peer is permitted to call
+    /// this method to initiate closure of the channel.
+    pub fn rtc_close_data_channel(channel: *const RTCDataChannel);
+    /// The track event is sent to the ontrack event handler on RTCPeerConnections after a new track
+    /// has been added to an RTCRtpReceiver which is part of the connection.
+    ///
+    /// The handler owns the track, which must be released with rtc_free_media_stream_track,
+    /// the ids of the remote streams of the track are only valid during the call.
+    pub fn rtc_on_track(
+        peer: *const RTCPeerConnection,
+        ctx: *mut c_void,
+        handler: extern "C" fn(*mut MediaStreamTrack, *const *const c_char, c_int, *mut c_void),
+    );
+    /// Releases a track handed over by the track event.
+    pub fn rtc_free_media_stream_track(track: *mut MediaStreamTrack);
+
+    /// An icecandidate event is sent to an RTCPeerConnection when an RTCIceCandidate has been
+    /// identified and added to the local peer by a call to RTCPeerConnection.setLocalDescription().
+    ///
+    /// The candidate is only valid for the duration of the handler.
+    pub fn rtc_on_icecandidate(
+        peer: *const RTCPeerConnection,
+        ctx: *mut c_void,
+        handler: extern "C" fn(*const RTCIceCandidate, *mut c_void),
+    );
+
+    /// A negotiationneeded event is sent to the RTCPeerConnection when a change occurs which
+    /// requires session negotiation. This negotiation should be carried out as the offerer,
+    /// because some session changes cannot be negotiated as the answerer.
+    pub fn rtc_on_negotiationneeded(
+        peer: *const RTCPeerConnection,
+        ctx: *mut c_void,
+        handler: extern "C" fn(*mut c_void),
+    );
+
+    /// A networkchanged event is sent when a network interface used for gathering candidates appears
+    /// or goes away, with the names of the current interfaces. The handler is called on the network thread,
+    /// the names are only valid for the duration of the handler.
+    pub fn rtc_on_networkchanged(
+        peer: *const RTCPeerConnection,
+        ctx: *mut c_void,
+        handler: extern "C" fn(*const *const c_char, c_int, *mut c_void),
+    );
+
+    /// Starts gathering the candidates of a pool of ICE sessions, as the ice_candidate_pool_size of the
+    /// configuration does, before the first offer. Returns false once the local description is set,
+    /// the pool can no longer change, or while a candidate filter is set.
+    pub fn rtc_prewarm(peer: *const RTCPeerConnection, size: c_int) -> bool;
+
+    /// Sets the filter deciding which gathered local candidates are used, a rejected candidate is
+    /// neither signaled nor used for the connectivity checks, null removes the filter. The filter is
+    /// called on the network thread, and the gathering of every connection of the runtime waits
+    /// for it. The candidate pool is emptied, and no candidate is pooled while it is set.
+    pub fn rtc_set_candidate_filter(
+        peer: *const RTCPeerConnection,
+        ctx: *mut c_void,
+        filter: Option<extern "C" fn(*mut c_char, *mut c_void) -> bool>,
+    );
+
+    /// A prewarmed event is sent once the sessions of the pool have gathered every candidate, with the
+    /// number of candidates gathered. The handler is called on the network thread.
+    pub fn rtc_on_prewarmed(
+        peer: *const RTCPeerConnection,
+        ctx: *mut c_void,
+        handler: extern "C" fn(c_int, *mut c_void),
+    );
+
+    /// A signalingstatechange event is sent to an RTCPeerConnection to notify it that its
+    /// signaling state has changed as a result of a call to setLocalDescription()
+    /// or setRemoteDescription().
+    pub fn rtc_on_signalingstatechange(
+        peer: *const RTCPeerConnection,
+        ctx: *mut c_void,
+        handler: extern "C" fn(SignalingState, *mut c_void),
+    );
+    /// An iceconnectionstatechange event is sent to an RTCPeerConnection object each time
+    /// the ICE connection state changes during the negotiation process.
+    pub fn rtc_on_iceconnectionstatechange(
+        peer: *const RTCPeerConnection,
+        ctx: *mut c_void,
+        handler: extern "C" fn(IceConnectionState, *mut c_void),
+    );
+    /// The icegatheringstatechange event is sent to the RTCPeerConnection when the state
+    /// of the ICE candidate gathering process changes.
+    pub fn rtc_on_icegatheringstatechange(
+        peer: *const RTCPeerConnection,
+        ctx: *mut c_void,
+        handler: extern "C" fn(IceGatheringState, *mut c_void),
     );
+    /// The connectionState read-only property of the RTCPeerConnection interface indicates
+    /// the current state of the peer connection.
+    pub fn rtc_get_connection_state(peer: *const RTCPeerConnection) -> ConnectionState;
+    /// The iceConnectionState read-only property of the RTCPeerConnection interface returns a string
+    /// which state of the ICE agent associated with the RTCPeerConnection.
+    pub fn rtc_get_ice_connection_state(peer: *const RTCPeerConnection) -> IceConnectionState;
+    /// The iceGatheringState read-only property of the RTCPeerConnection interface returns a string
+    /// that describes the overall ICE gathering state for this connection.
+    pub fn rtc_get_ice_gathering_state(peer: *const RTCPeerConnection) -> IceGatheringState;
+    /// The signalingState read-only property of the RTCPeerConnection interface returns a string value
+    /// describing the state of the signaling process on the local end of the connection
+    /// while connecting or reconnecting to another peer.
+    pub fn rtc_get_signaling_state(peer: *const RTCPeerConnection) -> SignalingState;
+    /// The read-only property RTCPeerConnection.localDescription returns an RTCSessionDescription
+    /// describing the session for the local end of the connection. If it has not yet been set, this is null.
+    ///
+    /// It is the pending description when there is one, the current description otherwise.
+    ///
+    /// The description must be released with rtc_free.
+    pub fn rtc_get_local_description(
+        peer: *const RTCPeerConnection,
+    ) -> *const RTCSessionDescription;
+    /// The read-only property RTCPeerConnection.remoteDescription returns a RTCSessionDescription
+    /// describing the session (which includes the configuration and media information) for the remote
+    /// end of the connection. If this hasn't been set yet, this is null.
+    ///
+    /// It is the pending description when there is one, the current description otherwise.
+    ///
+    /// The description must be released with rtc_free.
+    pub fn rtc_get_remote_description(
+        peer: *const RTCPeerConnection,
+    ) -> *const RTCSessionDescription;
+    /// The read-only property RTCPeerConnection.currentLocalDescription returns an RTCSessionDescription
+    /// object describing the local end of the connection as it was most recently successfully negotiated
+    /// since the last time the RTCPeerConnection finished negotiating and connecting to a remote peer.
+    ///
+    /// The description must be released with rtc_free.
+    pub fn rtc_get_current_local_description(
+        peer: *const RTCPeerConnection,
+    ) -> *const RTCSessionDescription;
+    /// The read-only property RTCPeerConnection.currentRemoteDescription returns an RTCSessionDescription
+    /// object describing the remote end of the connection as it was most recently successfully negotiated
+    /// since the last time the RTCPeerConnection finished negotiating and connecting to a remote peer.
+    ///
+    /// The description must be released with rtc_free.
+    pub fn rtc_get_current_remote_description(
+        peer: *const RTCPeerConnection,
+    ) -> *const RTCSessionDescription;
+    /// The read-only property RTCPeerConnection.pendingLocalDescription returns an RTCSessionDescription
+    /// object describing a pending configuration change for the local end of the connection.
+    /// This does not describe the connection as it currently stands, but as it may exist in the near future.
+    ///
+    /// The description must be released with rtc_free.
+    pub fn rtc_get_pending_local_description(
+        peer: *const RTCPeerConnection,
+    ) -> *const RTCSessionDescription;
+    /// The read-only property RTCPeerConnection.pendingRemoteDescription returns an RTCSessionDescription
+    /// object describing a pending configuration change for the remote end of the connection.
+    /// This does not describe the connection as it currently stands, but as it may exist in the near future.
+    ///
+    /// The description must be released with rtc_free.
+    pub fn rtc_get_pending_remote_description(
+        peer: *const RTCPeerConnection,
+    ) -> *const RTCSessionDescription;
 
     pub fn rtc_free(desc: *const RTCSessionDescription);
+    /// The RTCPeerConnection method getStats() returns a promise which resolves with data
+    /// providing statistics about either the overall connection or about the specified MediaStreamTrack.
+    ///
+    /// The report is delivered as the JSON serialization of the native RTCStatsReport,
+    /// the string is only valid for the duration of the callback.
+    pub fn rtc_get_stats(
+        peer: *const RTCPeerConnection,
+        ctx: *mut c_void,
+        callback: extern "C" fn(*const c_char, *mut c_void),
+    );
+    /// Sets the active flag of every encoding of the senders carrying the given kind of media.
+    /// An inactive encoding is not sent, but the sender and its track stay attached,
+    /// so no renegotiation is required to resume it.
+    pub fn rtc_set_senders_active(peer: *const RTCPeerConnection, kind: MediaType, active: bool);
+    /// The RTCPeerConnection.getConfiguration() method returns an object which indicates the current
+    /// configuration of the RTCPeerConnection on which the method is called.
+    ///
+    /// The returned configuration is owned by the native side and released with rtc_free_configuration.
+    pub fn rtc_get_configuration(peer: *const RTCPeerConnection) -> *const RTCPeerConnectionConfigure;
+    pub fn rtc_free_configuration(config: *const RTCPeerConnectionConfigure);
+    /// The RTCPeerConnection.setConfiguration() method sets the current configuration of
+    /// the RTCPeerConnection based on the values included in the specified object.
+    /// Returns false when the configuration is rejected, such as when trying to change
+    /// a property which can not be changed after the connection is created.
+    pub fn rtc_set_configuration(
+        peer: *const RTCPeerConnection,
+        config: *const RTCPeerConnectionConfigure,
+    ) -> bool;
+    /// The WebRTC API's RTCPeerConnection interface offers the restartIce() method to allow
+    /// a web application to easily request that ICE candidate gathering be redone on both ends
+    /// of the connection.
+    pub fn rtc_restart_ice(peer: *const RTCPeerConnection);
+    /// The RTCPeerConnection.sctp read-only property returns the RTCSctpTransport of the connection.
+    /// Returns false while there is no SCTP transport, such as before a data channel is negotiated,
+    /// and leaves the zero of a property which is not known yet, such as before the association is established.
+    pub fn rtc_get_sctp_transport(peer: *const RTCPeerConnection, transport: *mut RTCSctpTransport) -> bool;
+    /// Replaces the username and credential of the configured ICE server with the same urls,
+    /// the server is appended to the configuration when there is none.
+    /// Returns false when the native configuration could not be updated.
+    pub fn rtc_update_ice_server(peer: *const RTCPeerConnection, server: *const RTCIceServer) -> bool;
+    /// Sets the bitrate limits of the bandwidth estimation of the connection, in bits per second,
+    /// the limits apply to the total outgoing bitrate of all senders.
+    /// A value of zero leaves the corresponding limit unchanged.
+    pub fn rtc_set_bitrate(
+        peer: *const RTCPeerConnection,
+        min_bitrate_bps: c_int,
+        start_bitrate_bps: c_int,
+        max_bitrate_bps: c_int,
+    );
+    /// Forwards the messages of libwebrtc at or above the severity to the handler instead of stderr,
+    /// the handler is called on the thread logging the message and replaces any previous handler.
+    pub fn rtc_set_log_handler(
+        severity: LogSeverity,
+        ctx: *mut c_void,
+        handler: extern "C" fn(LogSeverity, *const c_char, *mut c_void),
+    );
+    /// Changes the minimum severity of the messages logged by libwebrtc.
+    pub fn rtc_set_log_severity(severity: LogSeverity);
+    /// Returns the transceivers of the connection, the size of the array is written to size.
+    /// Every handle must be released with rtc_free_transceiver, and the array with rtc_free_handles.
+    pub fn rtc_get_transceivers(
+        peer: *const RTCPeerConnection,
+        size: *mut usize,
+    ) -> *mut *mut RTCRtpTransceiver;
+    /// Adds a transceiver without a track, such as to receive a kind of media without sending it,
+    /// which fires the negotiationneeded event. Returns null on failure, in which case the error is
+    /// written, its message must be released with rtc_free_string. The handle must be released with
+    /// rtc_free_transceiver.
+    pub fn rtc_add_transceiver(
+        peer: *const RTCPeerConnection,
+        kind: MediaType,
+        direction: TransceiverDirection,
+        error: *mut RTCError,
+    ) -> *mut RTCRtpTransceiver;
+    /// Returns the senders of the connection, released as the transceivers are.
+    pub fn rtc_get_senders(peer: *const RTCPeerConnection, size: *mut usize) -> *mut *mut RTCRtpSender;
+    /// Returns the receivers of the connection, released as the transceivers are.
+    pub fn rtc_get_receivers(peer: *const RTCPeerConnection, size: *mut usize) -> *mut *mut RTCRtpReceiver;
+    /// Frees an array of handles, the handles themselves are not released.
+    pub fn rtc_free_handles(handles: *mut c_void);
+    /// Returns the media id of a transceiver, or null until it is negotiated.
+    /// The string must be released with rtc_free_string.
+    pub fn rtc_transceiver_get_mid(transceiver: *const RTCRtpTransceiver) -> *mut c_char;
+    pub fn rtc_transceiver_get_kind(transceiver: *const RTCRtpTransceiver) -> MediaType;
+    /// The preferred direction of a transceiver, as used in the next offers and answers.
+    pub fn rtc_transceiver_get_direction(transceiver: *const RTCRtpTransceiver) -> TransceiverDirection;
+    /// The direction negotiated for a transceiver, or zero until it is negotiated.
+    pub fn rtc_transceiver_get_current_direction(
+        transceiver: *const RTCRtpTransceiver,
+    ) -> Option<TransceiverDirection>;
+    pub fn rtc_transceiver_get_sender(transceiver: *const RTCRtpTransceiver) -> *mut RTCRtpSender;
+    pub fn rtc_transceiver_get_receiver(transceiver: *const RTCRtpTransceiver) -> *mut RTCRtpReceiver;
+    /// Changes the preferred direction of a transceiver, which fires the negotiationneeded event,
+    /// the direction is used by the media once it is negotiated. On failure the error is written,
+    /// its message must be released with rtc_free_string.
+    pub fn rtc_transceiver_set_direction(
+        transceiver: *const RTCRtpTransceiver,
+        direction: TransceiverDirection,
+        error: *mut RTCError,
+    ) -> bool;
+    /// Stops a transceiver, its sender stops sending and its receiver ends its track at once,
+    /// and the media section is rejected by the next negotiation. On failure the error is written,
+    /// its message must be released with rtc_free_string.
+    pub fn rtc_transceiver_stop(transceiver: *const RTCRtpTransceiver, error: *mut RTCError) -> bool;
+    /// Offers an rtp header extension which is supported but not offered by default, such as
+    /// abs-capture-time, in the next negotiations of a transceiver. On failure the error is written,
+    /// its message must be released with rtc_free_string.
+    pub fn rtc_transceiver_enable_header_extension(
+        transceiver: *const RTCRtpTransceiver,
+        uri: *const c_char,
+        error: *mut RTCError,
+    ) -> bool;
+    pub fn rtc_free_transceiver(transceiver: *const RTCRtpTransceiver);
+    /// Returns the id of a sender, which must be released with rtc_free_string.
+    pub fn rtc_sender_get_id(sender: *const RTCRtpSender) -> *mut c_char;
+    pub fn rtc_sender_get_kind(sender: *const RTCRtpSender) -> MediaType;
+    /// Returns the track sent by a sender, or null if it sends nothing.
+    /// The track must be released with rtc_free_media_stream_track.
+    pub fn rtc_sender_get_track(sender: *const RTCRtpSender) -> *mut MediaStreamTrack;
+    /// Returns the ssrcs of the encodings of a sender, one per simulcast layer, the retransmission
+    /// ssrcs are not included. The size of the array is written to size, it must be released
+    /// with rtc_free_ssrcs.
+    pub fn rtc_sender_get_ssrcs(sender: *const RTCRtpSender, size: *mut usize) -> *mut u32;
+    /// The priority of the encodings of a sender, low by default.
+    pub fn rtc_sender_get_priority(sender: *const RTCRtpSender) -> Priority;
+    /// Sets the priority of every encoding of a sender, both its share of the bandwidth and the DSCP
+    /// marking of its packets. On failure the error is written, its message must be released
+    /// with rtc_free_string.
+    pub fn rtc_sender_set_priority(
+        sender: *const RTCRtpSender,
+        priority: Priority,
+        error: *mut RTCError,
+    ) -> bool;
+    /// Reads the degradation preference of a sender, returns false when it is not set, in which case
+    /// libwebrtc maintains the frame rate of a camera and the resolution of a screen.
+    pub fn rtc_sender_get_degradation_preference(
+        sender: *const RTCRtpSender,
+        preference: *mut DegradationPreference,
+    ) -> bool;
+    /// Sets the degradation preference of a sender, which turns the CPU overuse detection of its
+    /// encoder off when it is disabled. On failure the error is written, its message must be released
+    /// with rtc_free_string.
+    pub fn rtc_sender_set_degradation_preference(
+        sender: *const RTCRtpSender,
+        preference: DegradationPreference,
+        error: *mut RTCError,
+    ) -> bool;
+    /// Reads the limits of the first encoding of a sender, leaves the zero of a limit which is not set.
+    pub fn rtc_sender_get_encoding_parameters(
+        sender: *const RTCRtpSender,
+        parameters: *mut RTCEncodingParameters,
+    );
+    /// Sets the limits of every encoding of a sender, a value of zero leaves the corresponding limit
+    /// unchanged. The encoder is reconfigured without a renegotiation. On failure the error is written,
+    /// its message must be released with rtc_free_string.
+    pub fn rtc_sender_set_encoding_parameters(
+        sender: *const RTCRtpSender,
+        parameters: *const RTCEncodingParameters,
+        error: *mut RTCError,
+    ) -> bool;
+    /// Returns the first stream id of a sender, which is the sync group the remote peer plays its
+    /// track in sync with, or null when the sender is part of no stream. It must be released with
+    /// rtc_free_string.
+    pub fn rtc_sender_get_sync_group(sender: *const RTCRtpSender) -> *mut c_char;
+    /// Replaces the streams of a sender, which fires the negotiationneeded event, the remote peer
+    /// plays the tracks of the same first stream in sync once it is negotiated.
+    pub fn rtc_sender_set_streams(
+        sender: *const RTCRtpSender,
+        stream_ids: *const *const c_char,
+        stream_ids_size: c_int,
+    );
+    pub fn rtc_free_sender(sender: *const RTCRtpSender);
+    /// Returns the id of a receiver, which must be released with rtc_free_string.
+    pub fn rtc_receiver_get_id(receiver: *const RTCRtpReceiver) -> *mut c_char;
+    pub fn rtc_receiver_get_kind(receiver: *const RTCRtpReceiver) -> MediaType;
+    /// Returns the track of a receiver, which must be released with rtc_free_media_stream_track.
+    pub fn rtc_receiver_get_track(receiver: *const RTCRtpReceiver) -> *mut MediaStreamTrack;
+    /// Returns the first stream id of a receiver, from the remote description, which is the sync
+    /// group its track is played in sync with, or null when it is part of no stream. It must be
+    /// released with rtc_free_string.
+    pub fn rtc_receiver_get_sync_group(receiver: *const RTCRtpReceiver) -> *mut c_char;
+    /// Returns the ssrcs signaled for a receiver, followed by those of the packets it recently
+    /// received. The size of the array is written to size, it must be released with rtc_free_ssrcs.
+    pub fn rtc_receiver_get_ssrcs(receiver: *const RTCRtpReceiver, size: *mut usize) -> *mut u32;
+    /// Returns the estimated time in milliseconds between the capture of the latest packet of an ssrc
+    /// of a receiver and its reception, from its abs-capture-time header extension, or -1 when the
+    /// extension is not received or the clock of the sender is not estimated yet.
+    pub fn rtc_receiver_get_capture_delay(receiver: *const RTCRtpReceiver, ssrc: u32) -> i64;
+    pub fn rtc_free_receiver(receiver: *const RTCRtpReceiver);
+    /// Frees a string returned by the native side.
+    pub fn rtc_free_string(string: *mut c_char);
+    /// Frees an array of ssrcs returned by the native side.
+    pub fn rtc_free_ssrcs(ssrcs: *mut u32);
+    /// Returns the codecs and header extensions the senders of the runtime can send for the kind of
+    /// media, which must be released with rtc_free_rtp_capabilities.
+    pub fn rtc_get_rtp_sender_capabilities(
+        runtime: *const RTCRuntime,
+        kind: MediaType,
+    ) -> *const RTCRtpCapabilities;
+    /// Returns the codecs and header extensions the receivers of the runtime can receive for the kind of
+    /// media, which must be released with rtc_free_rtp_capabilities.
+    pub fn rtc_get_rtp_receiver_capabilities(
+        runtime: *const RTCRuntime,
+        kind: MediaType,
+    ) -> *const RTCRtpCapabilities;
+    pub fn rtc_free_rtp_capabilities(capabilities: *const RTCRtpCapabilities);
 }
@@ -0,0 +1,171 @@
+use super::observer::Subscribers;
+use super::{raw, PeerConnection, RtpDirection};
+use crate::{Error, Result};
+
+use libc::*;
+use std::ffi::CString;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::Arc;
+use futures::Stream;
+
+/// Whether the packets of the native stack still flow through a tap.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RtpTapMode {
+    /// The packets are copied to the tap and handled as usual.
+    Observe,
+    /// The outgoing rtp packets are not sent and the incoming rtp packets are not decoded,
+    /// the application sends its own packets with `RtpTap::send_rtp`. The rtcp packets
+    /// are still handled natively, the tap only observes them.
+    Intercept,
+}
+
+/// A serialized rtp or rtcp packet, as sent or received on the transport, without the srtp protection.
+#[derive(Clone, Debug)]
+pub struct RtpPacket {
+    pub direction: RtpDirection,
+    pub rtcp: bool,
+    pub data: Vec<u8>,
+}
+
+impl RtpPacket {
+    fn from_raw(raw: *const raw::RTCRtpPacket) -> Self {
+        let raw = unsafe { &*raw };
+        Self {
+            direction: raw.direction,
+            rtcp: raw.rtcp,
+            data: unsafe { std::slice::from_raw_parts(raw.data, raw.size) }.to_vec(),
+        }
+    }
+
+    /// The payload type of an rtp packet, or the packet type of an rtcp packet.
+    pub fn payload_type(&self) -> Option<u8> {
+        match self.rtcp {
+            true => self.data.get(1).copied(),
+            false => self.data.get(1).map(|byte| byte & 0x7f),
+        }
+    }
+
+    /// The sequence number of an rtp packet.
+    pub fn sequence_number(&self) -> Option<u16> {
+        self.rtp_header()
+            .map(|header| u16::from_be_bytes([header[2], header[3]]))
+    }
+
+    /// The timestamp of an rtp packet, in the clock rate of its payload.
+    pub fn timestamp(&self) -> Option<u32> {
+        self.rtp_header()
+            .map(|header| u32::from_be_bytes([header[4], header[5], header[6], header[7]]))
+    }
+
+    /// The ssrc of an rtp packet, or of the sender of an rtcp packet.
+    pub fn ssrc(&self) -> Option<u32> {
+        let offset = if self.rtcp { 4 } else { 8 };
+        self.data
+            .get(offset..offset + 4)
+            .map(|ssrc| u32::from_be_bytes([ssrc[0], ssrc[1], ssrc[2], ssrc[3]]))
+    }
+
+    fn rtp_header(&self) -> Option<&[u8]> {
+        self.data.get(..12).filter(|_| !self.rtcp)
+    }
+}
+
+// a panic can not unwind into the native side, and the listeners are
+// woken on the network thread, so it only loses the packet.
+extern "C" fn on_rtp_packet(packet: *const raw::RTCRtpPacket, ctx: *mut c_void) {
+    let packets = unsafe { &*(ctx as *const Subscribers<RtpPacket>) };
+    let _ = catch_unwind(AssertUnwindSafe(|| packets.emit(RtpPacket::from_raw(packet))));
+}
+
+/// RtpTap
+///
+/// The rtp and rtcp packets of a transceiver, the packetization and the srtp
+/// protection are handled natively. The tap is detached when it is dropped.
+pub struct RtpTap {
+    raw: *const raw::RTCRtpTap,
+    packets: *mut Subscribers<RtpPacket>,
+    mid: String,
+    // the transport of the transceiver belongs to the connection.
+    _peer: Arc<PeerConnection>,
+}
+
+// the native tap marshals every call to the network thread,
+// and the subscribers are thread safe.
+unsafe impl Send for RtpTap {}
+unsafe impl Sync for RtpTap {}
+
+impl RtpTap {
+    pub(crate) fn new(peer: Arc<PeerConnection>, mid: &str, mode: RtpTapMode) -> Result<Self> {
+        let c_mid = CString::new(mid)?;
+        let packets = Box::into_raw(Box::<Subscribers<RtpPacket>>::default());
+        let raw = unsafe {
+            raw::rtc_add_rtp_tap(
+                peer.raw,
+                c_mid.as_ptr(),
+                mode == RtpTapMode::Intercept,
+                packets as *mut c_void,
+                on_rtp_packet,
+            )
+        };
+
+        if raw.is_null() {
+            drop(unsafe { Box::from_raw(packets) });
+            return Err(Error::InvalidState(format!(
+                "there is no negotiated transceiver with the mid {}",
+                mid
+            )));
+        }
+
+        Ok(Self {
+            raw,
+            packets,
+            mid: mid.to_string(),
+            _peer: peer,
+        })
+    }
+
+    /// The media id of the tapped transceiver.
+    pub fn mid(&self) -> &str {
+        &self.mid
+    }
+
+    /// The packets of the transceiver, in both directions, from the moment of the call.
+    pub fn packets(&self) -> impl Stream<Item = RtpPacket> {
+        unsafe { &*self.packets }.subscribe()
+    }
+
+    /// Sends an rtp packet to the remote peer, the ssrc and payload type
+    /// should be the ones negotiated for the transceiver.
+    pub fn send_rtp(&self, packet: &[u8]) -> Result<()> {
+        if packet.len() < 12 || packet[0] >> 6 != 2 {
+            return Err(Error::InvalidState("not an rtp packet!".to_string()));
+        }
+
+        self.send(packet, false)
+    }
+
+    /// Sends an rtcp packet to the remote peer, such as a picture loss indication.
+    pub fn send_rtcp(&self, packet: &[u8]) -> Result<()> {
+        if packet.len() < 8 || packet[0] >> 6 != 2 {
+            return Err(Error::InvalidState("not an rtcp packet!".to_string()));
+        }
+
+        self.send(packet, true)
+    }
+
+    fn send(&self, packet: &[u8], rtcp: bool) -> Result<()> {
+        if unsafe { raw::rtc_rtp_tap_send(self.raw, packet.as_ptr(), packet.len(), rtcp) } {
+            Ok(())
+        } else {
+            Err(Error::InvalidState("send packet failed!".to_string()))
+        }
+    }
+}
+
+impl Drop for RtpTap {
+    fn drop(&mut self) {
+        // the native side no longer calls the handler once the tap is removed.
+        unsafe { raw::rtc_remove_rtp_tap(self.raw) };
+        drop(unsafe { Box::from_raw(self.packets) });
+    }
+}
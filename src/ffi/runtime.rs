@@ -0,0 +1,337 @@
+use super::codec::ExternalFactories;
+use super::{
+    raw,
+    BitrateSettings,
+    ExternalVideoDecoderFactory,
+    ExternalVideoEncoderFactory,
+    MediaStreamTrack,
+    RTCConfiguration,
+    RTCPeerConnection,
+};
+use crate::congestion::CongestionControlConfig;
+use crate::field_trials::FieldTrials;
+use crate::{Error, Result};
+
+use std::ffi::CString;
+use std::sync::atomic::AtomicUsize;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub(crate) struct Runtime {
+    pub raw: *const raw::RTCRuntime,
+    /// The bounds of the estimate applied to the connections when they are created.
+    pub bitrate: Option<BitrateSettings>,
+    /// The runtime has no media engine, see `WebRtcRuntimeBuilder::data_only`.
+    pub data_only: bool,
+    /// The dedicated threads of the runtime.
+    pub threads: usize,
+    /// The connections of the runtime which are not dropped yet.
+    pub connections: AtomicUsize,
+    /// The resident memory of the process once the runtime was created.
+    pub baseline_memory: Option<u64>,
+    // dropped after the native runtime, whose factories call them.
+    _factories: ExternalFactories,
+}
+
+// the factory and its threads are only used by the native connections,
+// whose methods are marshalled to the signaling thread.
+unsafe impl Send for Runtime {}
+unsafe impl Sync for Runtime {}
+
+impl Drop for Runtime {
+    fn drop(&mut self) {
+        unsafe { raw::rtc_free_runtime(self.raw) }
+    }
+}
+
+/// WebRtcRuntime
+///
+/// The native factory and the signaling, worker and network threads it runs on.
+///
+/// A runtime is shared by every connection created on it, so a server with many peers
+/// only runs one set of threads. The handle is cheap to clone, and every connection
+/// holds a clone, so the threads are stopped once the runtime and all its connections
+/// are dropped.
+#[derive(Clone)]
+pub struct WebRtcRuntime {
+    pub(crate) inner: Arc<Runtime>,
+}
+
+static SHARED: Mutex<Option<WebRtcRuntime>> = Mutex::new(None);
+
+#[cfg(feature = "dlopen")]
+static LOADING: Mutex<()> = Mutex::new(());
+
+/// Loads the rtc_wrapper library from a path, such as a copy shipped next to the executable,
+/// with the `dlopen` feature, so that an application runs without WebRTC support when the
+/// library is missing instead of failing to start.
+///
+/// The library is loaded once for the lifetime of the process, a later call does nothing.
+/// A runtime built before any call loads the library from the search path of the system.
+#[cfg(feature = "dlopen")]
+pub fn load_library(path: impl AsRef<std::ffi::OsStr>) -> Result<()> {
+    let _loading = LOADING.lock().unwrap_or_else(|e| e.into_inner());
+    if raw::LIBRARY.get().is_some() {
+        return Ok(());
+    }
+
+    let library = unsafe { libloading::Library::new(path.as_ref()) }
+        .map_err(|e| Error::Library(e.to_string()))?;
+    let library = raw::Library::new(library).map_err(|e| Error::Library(e.to_string()))?;
+    let _ = raw::LIBRARY.set(library);
+    Ok(())
+}
+
+/// Whether the library is loaded, with the `dlopen` feature.
+#[cfg(feature = "dlopen")]
+pub fn is_library_loaded() -> bool {
+    raw::LIBRARY.get().is_some()
+}
+
+impl WebRtcRuntime {
+    /// Creates a runtime with dedicated signaling, worker and network threads.
+    pub fn new() -> Result<Self> {
+        WebRtcRuntimeBuilder::new().build()
+    }
+
+    pub fn builder() -> WebRtcRuntimeBuilder {
+        WebRtcRuntimeBuilder::new()
+    }
+
+    /// The runtime used by `RTCPeerConnection::new`, created on the first call and
+    /// kept for the lifetime of the process.
+    pub fn shared() -> Result<Self> {
+        let mut shared = SHARED.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(runtime) = shared.as_ref() {
+            return Ok(runtime.clone());
+        }
+
+        let runtime = Self::new()?;
+        *shared = Some(runtime.clone());
+        Ok(runtime)
+    }
+
+    /// Whether the connections of the runtime only carry data channels.
+    pub fn is_data_only(&self) -> bool {
+        self.inner.data_only
+    }
+
+    /// Creates a connection running on the threads of this runtime.
+    pub fn create_peer_connection(&self, config: &RTCConfiguration) -> Result<RTCPeerConnection> {
+        RTCPeerConnection::with_runtime(self, config)
+    }
+
+    /// Creates a local video track, the frames written to it are sent by every
+    /// connection it is added to.
+    pub fn create_video_track(&self, id: &str) -> Result<MediaStreamTrack> {
+        self.check_media()?;
+        let id = CString::new(id)?;
+        let raw = unsafe { raw::rtc_create_video_track(self.inner.raw, id.as_ptr()) };
+        Ok(MediaStreamTrack::from_local(raw, self.clone()))
+    }
+
+    /// Creates a local audio track, the frames written to it are sent by every
+    /// connection it is added to.
+    pub fn create_audio_track(&self, id: &str) -> Result<MediaStreamTrack> {
+        self.check_media()?;
+        let id = CString::new(id)?;
+        let raw = unsafe { raw::rtc_create_audio_track(self.inner.raw, id.as_ptr()) };
+        Ok(MediaStreamTrack::from_local(raw, self.clone()))
+    }
+
+    pub(crate) fn check_media(&self) -> Result<()> {
+        if self.inner.data_only {
+            Err(Error::InvalidState("the runtime only carries data channels".to_string()))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// PlayoutDelay
+///
+/// The bounds of the time the receivers buffer the frames before rendering them, carried
+/// with every frame by the playout-delay header extension. A receiver which does not
+/// negotiate the extension keeps its own buffering.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PlayoutDelay {
+    pub min: Duration,
+    pub max: Duration,
+}
+
+impl PlayoutDelay {
+    /// The uri of the header extension, which the capabilities of the video senders list
+    /// when it is supported.
+    pub const URI: &'static str = "http://www.webrtc.org/experiments/rtp-hdrext/playout-delay";
+
+    /// The largest delay the extension carries, it counts in steps of 10 milliseconds on 12 bits.
+    pub const MAX: Duration = Duration::from_millis(40950);
+
+    pub fn new(min: Duration, max: Duration) -> Self {
+        Self { min, max }
+    }
+
+    /// The frames are rendered as soon as they are decoded, as for cloud gaming and remote
+    /// desktop, at the cost of more freezes on a jittery network.
+    pub fn minimal() -> Self {
+        Self::new(Duration::ZERO, Duration::ZERO)
+    }
+}
+
+/// Builds a WebRtcRuntime.
+pub struct WebRtcRuntimeBuilder {
+    threads: usize,
+    thread_name_prefix: Option<String>,
+    factories: ExternalFactories,
+    congestion_control: CongestionControlConfig,
+    field_trials: FieldTrials,
+    data_only: bool,
+    playout_delay: Option<PlayoutDelay>,
+}
+
+impl Default for WebRtcRuntimeBuilder {
+    fn default() -> Self {
+        Self {
+            threads: Self::MAX_THREADS,
+            thread_name_prefix: None,
+            factories: ExternalFactories::default(),
+            congestion_control: CongestionControlConfig::default(),
+            field_trials: FieldTrials::default(),
+            data_only: false,
+            playout_delay: None,
+        }
+    }
+}
+
+impl WebRtcRuntimeBuilder {
+    /// The network, worker and signaling threads.
+    pub const MAX_THREADS: usize = 3;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of dedicated threads, the network thread is always dedicated,
+    /// with two threads the signaling thread is shared with the worker thread,
+    /// with one every task runs on the network thread.
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /// The threads are named after the prefix followed by their role,
+    /// such as `webrtc-network`, the prefix defaults to `webrtc`.
+    pub fn thread_name_prefix(mut self, prefix: &str) -> Self {
+        self.thread_name_prefix = Some(prefix.to_string());
+        self
+    }
+
+    /// Registers video encoders implemented by the application, their formats are
+    /// offered along with the builtin ones and take precedence over them.
+    pub fn video_encoder_factory(mut self, factory: impl ExternalVideoEncoderFactory + 'static) -> Self {
+        self.factories.set_encoder_factory(factory);
+        self
+    }
+
+    /// Registers video decoders implemented by the application, their formats are
+    /// offered along with the builtin ones and take precedence over them.
+    pub fn video_decoder_factory(mut self, factory: impl ExternalVideoDecoderFactory + 'static) -> Self {
+        self.factories.set_decoder_factory(factory);
+        self
+    }
+
+    /// Tunes the congestion controller and the pacer of every connection of the runtime.
+    pub fn congestion_control(mut self, config: CongestionControlConfig) -> Self {
+        self.congestion_control = config;
+        self
+    }
+
+    /// The field trials of the runtime, they take precedence over the trials of the
+    /// congestion control configuration, and only apply to this runtime. A trial refused by
+    /// `FieldTrials` fails `build` with `Error::InvalidConfiguration`.
+    pub fn field_trials(mut self, trials: FieldTrials) -> Self {
+        self.field_trials = trials;
+        self
+    }
+
+    /// Creates the runtime without the audio and video engines, for connections which only
+    /// carry data channels, such as for signaling or a data mesh, which saves the memory and
+    /// the startup of the engines on every connection. The runtime then refuses to create
+    /// tracks and the connections to add transceivers.
+    pub fn data_only(mut self, data_only: bool) -> Self {
+        self.data_only = data_only;
+        self
+    }
+
+    /// Asks the receivers of every video sender of the runtime to buffer the frames within
+    /// the bounds of the delay, the extension is offered when
+    /// `RtpSender::capabilities(MediaType::Video)?.has_header_extension(PlayoutDelay::URI)`.
+    pub fn playout_delay(mut self, delay: PlayoutDelay) -> Self {
+        self.playout_delay = Some(delay);
+        self
+    }
+
+    pub fn build(self) -> Result<WebRtcRuntime> {
+        #[cfg(feature = "dlopen")]
+        load_library(libloading::library_filename("rtc_wrapper"))?;
+
+        if !(1..=Self::MAX_THREADS).contains(&self.threads) {
+            return Err(Error::InvalidConfiguration(format!(
+                "a runtime runs on 1 to {} threads, not {}",
+                Self::MAX_THREADS,
+                self.threads
+            )));
+        }
+
+        if let Some(delay) = self.playout_delay {
+            if delay.min > delay.max || delay.max > PlayoutDelay::MAX {
+                return Err(Error::InvalidConfiguration(format!(
+                    "a playout delay is between 0 and {:?}, not {:?} to {:?}",
+                    PlayoutDelay::MAX,
+                    delay.min,
+                    delay.max
+                )));
+            }
+        }
+
+        let playout_delay = self.playout_delay.map(|delay| raw::RTCPlayoutDelay {
+            min_ms: delay.min.as_millis() as i32,
+            max_ms: delay.max.as_millis() as i32,
+        });
+
+        let prefix = self.thread_name_prefix.map(CString::new).transpose()?;
+        let mut field_trials = self.congestion_control.field_trials();
+        field_trials.extend(self.field_trials);
+        field_trials.validate()?;
+        let field_trials = CString::new(field_trials.to_string())?;
+        let raw = self.factories.with_raw(|encoder, decoder| {
+            let config = raw::RTCRuntimeConfigure {
+                threads: self.threads as i32,
+                thread_name_prefix: prefix.as_ref().map(|p| p.as_ptr()),
+                video_encoder_factory: encoder.map(|f| f as *const _),
+                video_decoder_factory: decoder.map(|f| f as *const _),
+                field_trials: Some(field_trials.as_ptr()),
+                data_only: self.data_only,
+                playout_delay: playout_delay.as_ref().map(|d| d as *const _),
+            };
+
+            unsafe { raw::create_rtc_runtime(&config) }
+        })?;
+
+        if raw.is_null() {
+            return Err(Error::Closed);
+        }
+
+        Ok(WebRtcRuntime {
+            inner: Arc::new(Runtime {
+                raw,
+                bitrate: self.congestion_control.bitrate_settings(),
+                data_only: self.data_only,
+                threads: self.threads,
+                connections: AtomicUsize::new(0),
+                baseline_memory: crate::stats::resident_memory(),
+                _factories: self.factories,
+            }),
+        })
+    }
+}
@@ -0,0 +1,255 @@
+use super::raw;
+
+use libc::*;
+use std::ffi::{CStr, CString};
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr::null_mut;
+use std::slice;
+use std::sync::{Arc, Mutex};
+
+/// A UDP socket of a transport provided by the application.
+pub trait PacketSocket: Send {
+    /// The address the socket is bound to, which the host candidate of the socket carries.
+    fn local_addr(&self) -> SocketAddr;
+
+    /// Sends a datagram, an error drops it, as a full buffer of a UDP socket would.
+    fn send_to(&mut self, data: &[u8], to: SocketAddr) -> io::Result<()>;
+}
+
+/// Creates the UDP sockets of a connection, so that its ICE traffic goes through a transport
+/// provided by the application, such as a VPN tunnel, a userspace network stack or a link
+/// simulator dropping and delaying the packets of a test.
+///
+/// The sockets are bound on the addresses of the network interfaces the connection gathers
+/// on, the socket may be bound elsewhere, its `local_addr` is then the address of its host
+/// candidate. The TCP sockets are not supported, the connection gathers no TCP candidate
+/// and reaches the TURN servers over UDP only. The methods are called on the network thread
+/// of the runtime, they must not block.
+///
+/// ```no_run
+/// use std::io;
+/// use std::net::{SocketAddr, UdpSocket};
+/// use std::time::Duration;
+/// use webrtc::{PacketReceiver, PacketSocket, PacketSocketFactory, RTCConfiguration};
+///
+/// /// Drops one packet out of twenty.
+/// struct Lossy;
+///
+/// struct LossySocket {
+///     socket: UdpSocket,
+///     sent: u64,
+/// }
+///
+/// impl PacketSocket for LossySocket {
+///     fn local_addr(&self) -> SocketAddr {
+///         self.socket.local_addr().unwrap()
+///     }
+///
+///     fn send_to(&mut self, data: &[u8], to: SocketAddr) -> io::Result<()> {
+///         self.sent += 1;
+///         if self.sent % 20 != 0 {
+///             self.socket.send_to(data, to)?;
+///         }
+///
+///         Ok(())
+///     }
+/// }
+///
+/// impl PacketSocketFactory for Lossy {
+///     fn bind(
+///         &self,
+///         address: SocketAddr,
+///         _ports: Option<(u16, u16)>,
+///         receiver: PacketReceiver,
+///     ) -> io::Result<Box<dyn PacketSocket>> {
+///         let socket = UdpSocket::bind(address)?;
+///         let reader = socket.try_clone()?;
+///         reader.set_read_timeout(Some(Duration::from_millis(100)))?;
+///         std::thread::spawn(move || {
+///             let mut buf = [0; 2048];
+///             while !receiver.is_closed() {
+///                 if let Ok((size, from)) = reader.recv_from(&mut buf) {
+///                     receiver.receive(&buf[..size], from);
+///                 }
+///             }
+///         });
+///
+///         Ok(Box::new(LossySocket { socket, sent: 0 }))
+///     }
+/// }
+///
+/// let mut config = RTCConfiguration::default();
+/// config.set_socket_factory(Lossy);
+/// let peer = webrtc::RTCPeerConnection::new(&config)?;
+/// # Ok::<(), webrtc::Error>(())
+/// ```
+pub trait PacketSocketFactory: Send + Sync {
+    /// Binds a socket to an address, on a port of the range when there is one, see
+    /// `RTCConfiguration::set_port_range`. The packets the socket receives are handed over
+    /// to the receiver until the socket is dropped.
+    fn bind(
+        &self,
+        address: SocketAddr,
+        ports: Option<(u16, u16)>,
+        receiver: PacketReceiver,
+    ) -> io::Result<Box<dyn PacketSocket>>;
+}
+
+struct RawSocket(*mut raw::RTCPacketSocket);
+
+// the native socket posts the received packets to its network thread.
+unsafe impl Send for RawSocket {}
+
+/// Hands the packets received by a socket over to its connection, from any thread.
+#[derive(Clone)]
+pub struct PacketReceiver {
+    socket: Arc<Mutex<Option<RawSocket>>>,
+}
+
+impl PacketReceiver {
+    /// Hands a datagram over to the connection, returns false once the socket is closed.
+    pub fn receive(&self, data: &[u8], from: SocketAddr) -> bool {
+        let socket = self.socket.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(socket) = socket.as_ref() else {
+            return false;
+        };
+
+        // an address is never formatted with a nul byte.
+        let ip = CString::new(from.ip().to_string()).unwrap();
+        let from = raw::RTCSocketAddress {
+            ip: ip.as_ptr(),
+            port: from.port() as c_int,
+        };
+
+        unsafe { raw::rtc_packet_socket_receive(socket.0, data.as_ptr(), data.len(), &from) };
+        true
+    }
+
+    /// Whether the connection has closed the socket, such as when the candidate is pruned.
+    pub fn is_closed(&self) -> bool {
+        self.socket.lock().unwrap_or_else(|e| e.into_inner()).is_none()
+    }
+
+    // holds the lock, so a packet being handed over completes before the native socket goes.
+    fn close(&self) {
+        self.socket.lock().unwrap_or_else(|e| e.into_inner()).take();
+    }
+}
+
+type Factory = Box<dyn PacketSocketFactory>;
+
+struct Socket {
+    socket: Box<dyn PacketSocket>,
+    receiver: PacketReceiver,
+    // the native socket copies the bound address once it is bound.
+    local_ip: CString,
+}
+
+fn from_raw(address: *const raw::RTCSocketAddress) -> Option<SocketAddr> {
+    let address = unsafe { &*address };
+    if address.ip.is_null() {
+        return None;
+    }
+
+    let ip: IpAddr = unsafe { CStr::from_ptr(address.ip) }.to_str().ok()?.parse().ok()?;
+    Some(SocketAddr::new(ip, address.port as u16))
+}
+
+// the functions below are called by the native factory on the network thread, a panic can
+// not unwind into the native side, so a panicking socket fails the call instead.
+
+extern "C" fn bind(
+    address: *const raw::RTCSocketAddress,
+    min_port: c_int,
+    max_port: c_int,
+    socket: *mut raw::RTCPacketSocket,
+    bound: *mut raw::RTCSocketAddress,
+    ctx: *mut c_void,
+) -> *mut c_void {
+    let factory = unsafe { &*(ctx as *const Factory) };
+    let Some(address) = from_raw(address) else {
+        return null_mut();
+    };
+
+    let ports = (min_port > 0 || max_port > 0).then_some((min_port as u16, max_port as u16));
+    let receiver = PacketReceiver {
+        socket: Arc::new(Mutex::new(Some(RawSocket(socket)))),
+    };
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let socket = factory.bind(address, ports, receiver.clone())?;
+        let local = socket.local_addr();
+        Ok::<_, io::Error>((socket, local))
+    }));
+
+    let (socket, local) = match result {
+        Ok(Ok(socket)) => socket,
+        Ok(Err(e)) => {
+            tracing::warn!("failed to bind a packet socket on {}: {}", address, e);
+            receiver.close();
+            return null_mut();
+        }
+        Err(_) => {
+            receiver.close();
+            return null_mut();
+        }
+    };
+
+    let socket = Box::new(Socket {
+        local_ip: CString::new(local.ip().to_string()).unwrap(),
+        socket,
+        receiver,
+    });
+
+    unsafe {
+        *bound = raw::RTCSocketAddress {
+            ip: socket.local_ip.as_ptr(),
+            port: local.port() as c_int,
+        };
+    }
+
+    Box::into_raw(socket) as *mut c_void
+}
+
+extern "C" fn send_to(
+    socket: *mut c_void,
+    data: *const u8,
+    size: usize,
+    to: *const raw::RTCSocketAddress,
+) -> bool {
+    let socket = unsafe { &mut *(socket as *mut Socket) };
+    let Some(to) = from_raw(to) else {
+        return false;
+    };
+
+    let data = unsafe { slice::from_raw_parts(data, size) };
+    matches!(catch_unwind(AssertUnwindSafe(|| socket.socket.send_to(data, to))), Ok(Ok(())))
+}
+
+extern "C" fn close(socket: *mut c_void) {
+    let socket = unsafe { Box::from_raw(socket as *mut Socket) };
+    socket.receiver.close();
+    let _ = catch_unwind(AssertUnwindSafe(|| drop(socket)));
+}
+
+/// The factory of a configuration, it is called by the native factory until the connection
+/// is freed, so the connections created with it hold it.
+#[derive(Clone)]
+pub(crate) struct ExternalSocketFactory(Arc<Factory>);
+
+impl ExternalSocketFactory {
+    pub fn new(factory: impl PacketSocketFactory + 'static) -> Self {
+        Self(Arc::new(Box::new(factory)))
+    }
+
+    pub fn as_raw(&self) -> raw::RTCPacketSocketFactory {
+        raw::RTCPacketSocketFactory {
+            ctx: self.0.as_ref() as *const Factory as *mut c_void,
+            bind,
+            send_to,
+            close,
+        }
+    }
+}
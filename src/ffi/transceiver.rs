@@ -0,0 +1,472 @@
+use super::{
+    raw,
+    DegradationPreference,
+    MediaStreamTrack,
+    MediaType,
+    PeerConnection,
+    Priority,
+    TransceiverDirection,
+    WebRtcRuntime,
+};
+use crate::{Error, Result};
+
+use libc::*;
+use std::ffi::{CStr, CString};
+use std::slice;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Takes the ownership of a string returned by the native side.
+fn take_string(raw: *mut c_char) -> Option<String> {
+    if raw.is_null() {
+        return None;
+    }
+
+    let string = unsafe { CStr::from_ptr(raw) }.to_string_lossy().into_owned();
+    unsafe { raw::rtc_free_string(raw) };
+    Some(string)
+}
+
+/// Takes the ownership of an array of ssrcs returned by the native side.
+fn take_ssrcs(get: impl FnOnce(*mut usize) -> *mut u32) -> Vec<u32> {
+    let mut size = 0;
+    let ssrcs = get(&mut size);
+    let taken = unsafe { slice::from_raw_parts(ssrcs, size) }.to_vec();
+    unsafe { raw::rtc_free_ssrcs(ssrcs) };
+    taken
+}
+
+fn to_string(raw: *const c_char) -> Option<String> {
+    (!raw.is_null()).then(|| unsafe { CStr::from_ptr(raw) }.to_string_lossy().into_owned())
+}
+
+/// A codec supported by the senders or the receivers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RtpCodecCapability {
+    /// The kind and the name of the codec, such as "video/AV1" or "audio/opus".
+    pub mime_type: String,
+    pub clock_rate: Option<u32>,
+    /// The number of channels of an audio codec.
+    pub channels: Option<u32>,
+    /// The format specific parameters of the codec, such as "minptime=10;useinbandfec=1".
+    pub sdp_fmtp_line: Option<String>,
+}
+
+/// An rtp header extension supported by the senders or the receivers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RtpHeaderExtensionCapability {
+    pub uri: String,
+    /// The id offered for the extension.
+    pub preferred_id: Option<u16>,
+}
+
+/// The codecs and the header extensions supported for a kind of media,
+/// they depend on the build of libwebrtc rather than on a connection.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RtpCapabilities {
+    pub codecs: Vec<RtpCodecCapability>,
+    pub header_extensions: Vec<RtpHeaderExtensionCapability>,
+}
+
+impl RtpCapabilities {
+    /// Takes the ownership of the capabilities returned by the native side.
+    fn take(raw: *const raw::RTCRtpCapabilities) -> Self {
+        let capabilities = unsafe { &*raw };
+        let codecs = unsafe { slice::from_raw_parts(capabilities.codecs, capabilities.codecs_size) };
+        let header_extensions = unsafe {
+            slice::from_raw_parts(capabilities.header_extensions, capabilities.header_extensions_size)
+        };
+
+        let positive = |value: c_int| (value > 0).then_some(value as u32);
+        let taken = Self {
+            codecs: codecs
+                .iter()
+                .map(|codec| RtpCodecCapability {
+                    mime_type: to_string(codec.mime_type).unwrap_or_default(),
+                    clock_rate: positive(codec.clock_rate),
+                    channels: positive(codec.channels),
+                    sdp_fmtp_line: to_string(codec.sdp_fmtp_line),
+                })
+                .collect(),
+            header_extensions: header_extensions
+                .iter()
+                .map(|extension| RtpHeaderExtensionCapability {
+                    uri: to_string(extension.uri).unwrap_or_default(),
+                    preferred_id: positive(extension.preferred_id).map(|id| id as u16),
+                })
+                .collect(),
+        };
+
+        unsafe { raw::rtc_free_rtp_capabilities(raw) };
+        taken
+    }
+
+    /// Whether a codec is supported, the mime type is compared case insensitively,
+    /// so "video/av1" matches "video/AV1".
+    pub fn has_codec(&self, mime_type: &str) -> bool {
+        self.codecs
+            .iter()
+            .any(|codec| codec.mime_type.eq_ignore_ascii_case(mime_type))
+    }
+
+    /// Whether an rtp header extension is supported.
+    pub fn has_header_extension(&self, uri: &str) -> bool {
+        self.header_extensions.iter().any(|extension| extension.uri == uri)
+    }
+}
+
+/// Calls a native function reporting its failure in an error, whose message is then released.
+pub(crate) fn check(f: impl FnOnce(*mut raw::RTCError) -> bool) -> Result<()> {
+    let mut error = raw::RTCError {
+        code: 0,
+        message: std::ptr::null(),
+    };
+
+    if f(&mut error) {
+        return Ok(());
+    }
+
+    let result = Error::from_raw(&error);
+    unsafe { raw::rtc_free_string(error.message as *mut c_char) };
+    Err(result)
+}
+
+/// Takes the ownership of an array of handles returned by the native side.
+pub(crate) fn take_handles<T, H>(
+    peer: &Arc<PeerConnection>,
+    get: unsafe extern "C" fn(*const raw::RTCPeerConnection, *mut usize) -> *mut *mut T,
+    wrap: impl Fn(*mut T, Arc<PeerConnection>) -> H,
+) -> Vec<H> {
+    let mut size = 0;
+    let handles = unsafe { get(peer.raw, &mut size) };
+    let wrapped = unsafe { slice::from_raw_parts(handles, size) }
+        .iter()
+        .map(|handle| wrap(*handle, peer.clone()))
+        .collect();
+
+    unsafe { raw::rtc_free_handles(handles as *mut c_void) };
+    wrapped
+}
+
+/// RtpTransceiver
+///
+/// A pair of a sender and a receiver sharing a media section of the session description.
+/// The handle reads the current state of the native transceiver, so it reflects
+/// the renegotiations that happened after it was taken.
+pub struct RtpTransceiver {
+    raw: *mut raw::RTCRtpTransceiver,
+    // the transceiver belongs to the connection.
+    peer: Arc<PeerConnection>,
+}
+
+// the native transceiver is a proxy marshalling every call to the signaling thread.
+unsafe impl Send for RtpTransceiver {}
+unsafe impl Sync for RtpTransceiver {}
+
+impl RtpTransceiver {
+    /// The uri of the header extension carrying the capture time of the frames, which libwebrtc
+    /// supports but does not offer by default, see `enable_header_extension`.
+    pub const ABS_CAPTURE_TIME: &'static str = "http://www.webrtc.org/experiments/rtp-hdrext/abs-capture-time";
+
+    pub(crate) fn from_raw(raw: *mut raw::RTCRtpTransceiver, peer: Arc<PeerConnection>) -> Self {
+        Self { raw, peer }
+    }
+
+    pub(crate) fn add(peer: &Arc<PeerConnection>, kind: MediaType, direction: TransceiverDirection) -> Result<Self> {
+        let mut transceiver = std::ptr::null_mut();
+        check(|error| {
+            transceiver = unsafe { raw::rtc_add_transceiver(peer.raw, kind, direction, error) };
+            !transceiver.is_null()
+        })?;
+
+        Ok(Self::from_raw(transceiver, peer.clone()))
+    }
+
+    /// The media id of the media section of the transceiver, None until it is negotiated.
+    pub fn mid(&self) -> Option<String> {
+        take_string(unsafe { raw::rtc_transceiver_get_mid(self.raw) })
+    }
+
+    pub fn kind(&self) -> MediaType {
+        unsafe { raw::rtc_transceiver_get_kind(self.raw) }
+    }
+
+    /// The preferred direction of the transceiver, as used in the next offers and answers.
+    pub fn direction(&self) -> TransceiverDirection {
+        unsafe { raw::rtc_transceiver_get_direction(self.raw) }
+    }
+
+    /// The direction negotiated for the transceiver, None until it is negotiated.
+    pub fn current_direction(&self) -> Option<TransceiverDirection> {
+        unsafe { raw::rtc_transceiver_get_current_direction(self.raw) }
+    }
+
+    /// Changes the preferred direction of the transceiver, such as from sendonly to inactive
+    /// to pause a video without removing its track. The change fires the negotiationneeded
+    /// event and applies to the media once it is negotiated.
+    ///
+    /// A transceiver can not be given the stopped direction, it is stopped with `stop`.
+    pub fn set_direction(&self, direction: TransceiverDirection) -> Result<()> {
+        check(|error| unsafe { raw::rtc_transceiver_set_direction(self.raw, direction, error) })
+    }
+
+    /// Stops the transceiver for good, its sender stops sending and the track of its receiver
+    /// ends at once, and its media section is rejected by the next negotiation.
+    /// Stopping a stopped transceiver does nothing.
+    pub fn stop(&self) -> Result<()> {
+        check(|error| unsafe { raw::rtc_transceiver_stop(self.raw, error) })
+    }
+
+    /// Offers a header extension which is supported but not offered by default, such as
+    /// `RtpTransceiver::ABS_CAPTURE_TIME`, in the next negotiations of the transceiver.
+    /// An answer only accepts the extension when the answerer enabled it as well, before
+    /// creating the answer.
+    pub fn enable_header_extension(&self, uri: &str) -> Result<()> {
+        let uri = CString::new(uri)?;
+        check(|error| unsafe { raw::rtc_transceiver_enable_header_extension(self.raw, uri.as_ptr(), error) })
+    }
+
+    pub fn sender(&self) -> RtpSender {
+        RtpSender::from_raw(unsafe { raw::rtc_transceiver_get_sender(self.raw) }, self.peer.clone())
+    }
+
+    pub fn receiver(&self) -> RtpReceiver {
+        RtpReceiver::from_raw(unsafe { raw::rtc_transceiver_get_receiver(self.raw) }, self.peer.clone())
+    }
+}
+
+impl Drop for RtpTransceiver {
+    fn drop(&mut self) {
+        unsafe { raw::rtc_free_transceiver(self.raw) }
+    }
+}
+
+/// The limits of the encodings of a sender, see `RtpSender::set_encoding_parameters`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct EncodingParameters {
+    /// The bitrate, in bits per second.
+    pub max_bitrate: Option<u32>,
+    /// The frame rate, the frames written above it are dropped.
+    pub max_framerate: Option<f64>,
+    /// The factor the width and the height of the frames are divided by, at least 1.
+    pub scale_resolution_down_by: Option<f64>,
+}
+
+/// RtpSender
+///
+/// The sending half of a transceiver, which encodes and sends a track.
+pub struct RtpSender {
+    raw: *mut raw::RTCRtpSender,
+    _peer: Arc<PeerConnection>,
+}
+
+// the native sender is a proxy marshalling every call to the signaling thread.
+unsafe impl Send for RtpSender {}
+unsafe impl Sync for RtpSender {}
+
+impl RtpSender {
+    /// The codecs and the header extensions the senders can send for a kind of media, so that
+    /// an application can check that a codec such as AV1 is available before negotiating.
+    ///
+    /// The capabilities are read from the shared runtime, every runtime supports the same ones.
+    pub fn capabilities(kind: MediaType) -> Result<RtpCapabilities> {
+        let runtime = WebRtcRuntime::shared()?;
+        let raw = unsafe { raw::rtc_get_rtp_sender_capabilities(runtime.inner.raw, kind) };
+        Ok(RtpCapabilities::take(raw))
+    }
+
+    pub(crate) fn from_raw(raw: *mut raw::RTCRtpSender, peer: Arc<PeerConnection>) -> Self {
+        Self { raw, _peer: peer }
+    }
+
+    pub fn id(&self) -> String {
+        take_string(unsafe { raw::rtc_sender_get_id(self.raw) }).unwrap_or_default()
+    }
+
+    pub fn kind(&self) -> MediaType {
+        unsafe { raw::rtc_sender_get_kind(self.raw) }
+    }
+
+    /// The ssrcs the sender sends with, one per simulcast layer, empty until the sender
+    /// is negotiated. They are the ssrcs of the outbound-rtp stats of the sender.
+    pub fn ssrcs(&self) -> Vec<u32> {
+        take_ssrcs(|size| unsafe { raw::rtc_sender_get_ssrcs(self.raw, size) })
+    }
+
+    /// The priority of the sender, low by default.
+    pub fn priority(&self) -> Priority {
+        unsafe { raw::rtc_sender_get_priority(self.raw) }
+    }
+
+    /// Sets the priority of every encoding of the sender, a higher priority gets a larger share
+    /// of the bandwidth when the link is constrained, twice the share of the priority below it,
+    /// such as the audio and the video of the active speaker. It also sets the DSCP marking of
+    /// the packets, which libwebrtc only applies when DSCP is enabled.
+    ///
+    /// The sender must have been negotiated, the priority of a sender without encodings is ignored.
+    pub fn set_priority(&self, priority: Priority) -> Result<()> {
+        check(|error| unsafe { raw::rtc_sender_set_priority(self.raw, priority, error) })
+    }
+
+    /// The degradation preference of the sender, None until one is set, in which case libwebrtc
+    /// maintains the frame rate of a camera and the resolution of a screen.
+    pub fn degradation_preference(&self) -> Option<DegradationPreference> {
+        let mut preference = DegradationPreference::Balanced;
+        unsafe { raw::rtc_sender_get_degradation_preference(self.raw, &mut preference) }
+            .then_some(preference)
+    }
+
+    /// Sets what the sender gives up when the CPU is overused or the bandwidth is too low.
+    ///
+    /// `Disabled` turns the CPU overuse detection of the encoder off, which suits a server
+    /// with a known load that would otherwise scale its streams down during a load spike,
+    /// while a laptop keeps it on to stay responsive. It also stops the scaling driven by
+    /// the bandwidth, the bitrate then drops at a constant resolution and frame rate.
+    ///
+    /// The thresholds of the overuse detection are internal to the encoders of libwebrtc,
+    /// they are not configurable through its API.
+    pub fn set_degradation_preference(&self, preference: DegradationPreference) -> Result<()> {
+        check(|error| unsafe { raw::rtc_sender_set_degradation_preference(self.raw, preference, error) })
+    }
+
+    /// The limits of the encoding of the sender, the first one when it sends simulcast.
+    pub fn encoding_parameters(&self) -> EncodingParameters {
+        let mut parameters = raw::RTCEncodingParameters::default();
+        unsafe { raw::rtc_sender_get_encoding_parameters(self.raw, &mut parameters) };
+        EncodingParameters {
+            max_bitrate: (parameters.max_bitrate_bps > 0).then_some(parameters.max_bitrate_bps as u32),
+            max_framerate: (parameters.max_framerate > 0.0).then_some(parameters.max_framerate),
+            scale_resolution_down_by: (parameters.scale_resolution_down_by > 0.0)
+                .then_some(parameters.scale_resolution_down_by),
+        }
+    }
+
+    /// The first stream of the sender, the remote peer plays the audio and the video tracks
+    /// of the same sync group in sync, None when the sender is part of no stream.
+    pub fn sync_group(&self) -> Option<String> {
+        take_string(unsafe { raw::rtc_sender_get_sync_group(self.raw) })
+    }
+
+    /// Replaces the streams of the sender, which fires the negotiationneeded event, so an
+    /// audio and a video track are lip-synced by the remote peer once they share their first
+    /// stream, or are played as soon as they are received, for the lowest latency, with
+    /// distinct streams or none.
+    pub fn set_streams(&self, stream_ids: &[&str]) -> Result<()> {
+        let stream_ids = stream_ids
+            .iter()
+            .map(|id| CString::new(*id))
+            .collect::<Result<Vec<_>, _>>()?;
+        let raw_stream_ids = stream_ids.iter().map(|id| id.as_ptr()).collect::<Vec<_>>();
+        unsafe { raw::rtc_sender_set_streams(self.raw, raw_stream_ids.as_ptr(), raw_stream_ids.len() as c_int) };
+        Ok(())
+    }
+
+    /// Sets the limits of every encoding of the sender while it is sending, such as a lower
+    /// frame rate and bitrate when the bandwidth drops, the encoder is reconfigured without
+    /// a renegotiation. A limit which is None is left unchanged.
+    ///
+    /// The resolution follows the frames written to the track, a source adapting to a resized
+    /// window just writes frames of the new size, `scale_resolution_down_by` scales them down
+    /// before they are encoded.
+    ///
+    /// The sender must have been negotiated, the limits of a sender without encodings are ignored.
+    pub fn set_encoding_parameters(&self, parameters: &EncodingParameters) -> Result<()> {
+        let max_bitrate = parameters.max_bitrate.map(|bitrate| bitrate.min(i32::MAX as u32));
+        let raw = raw::RTCEncodingParameters {
+            max_bitrate_bps: max_bitrate.unwrap_or(0) as i32,
+            max_framerate: parameters.max_framerate.unwrap_or(0.0),
+            scale_resolution_down_by: parameters.scale_resolution_down_by.unwrap_or(0.0),
+        };
+
+        if raw.scale_resolution_down_by != 0.0 && raw.scale_resolution_down_by < 1.0 {
+            return Err(Error::InvalidConfiguration(
+                "the resolution can only be scaled down, by a factor of at least 1".to_string(),
+            ));
+        }
+
+        check(|error| unsafe { raw::rtc_sender_set_encoding_parameters(self.raw, &raw, error) })
+    }
+
+    /// The track sent by the sender, None if it sends nothing.
+    pub fn track(&self) -> Option<MediaStreamTrack> {
+        let track = unsafe { raw::rtc_sender_get_track(self.raw) };
+        (!track.is_null()).then(|| MediaStreamTrack::from_raw(track))
+    }
+}
+
+impl Drop for RtpSender {
+    fn drop(&mut self) {
+        unsafe { raw::rtc_free_sender(self.raw) }
+    }
+}
+
+/// RtpReceiver
+///
+/// The receiving half of a transceiver, which receives and decodes a track.
+pub struct RtpReceiver {
+    raw: *mut raw::RTCRtpReceiver,
+    _peer: Arc<PeerConnection>,
+}
+
+// the native receiver is a proxy marshalling every call to the signaling thread.
+unsafe impl Send for RtpReceiver {}
+unsafe impl Sync for RtpReceiver {}
+
+impl RtpReceiver {
+    /// The codecs and the header extensions the receivers can receive for a kind of media.
+    ///
+    /// The capabilities are read from the shared runtime, every runtime supports the same ones.
+    pub fn capabilities(kind: MediaType) -> Result<RtpCapabilities> {
+        let runtime = WebRtcRuntime::shared()?;
+        let raw = unsafe { raw::rtc_get_rtp_receiver_capabilities(runtime.inner.raw, kind) };
+        Ok(RtpCapabilities::take(raw))
+    }
+
+    pub(crate) fn from_raw(raw: *mut raw::RTCRtpReceiver, peer: Arc<PeerConnection>) -> Self {
+        Self { raw, _peer: peer }
+    }
+
+    pub fn id(&self) -> String {
+        take_string(unsafe { raw::rtc_receiver_get_id(self.raw) }).unwrap_or_default()
+    }
+
+    pub fn kind(&self) -> MediaType {
+        unsafe { raw::rtc_receiver_get_kind(self.raw) }
+    }
+
+    /// The ssrcs the receiver receives, the signaled ones followed by those of the
+    /// packets received in the last seconds, so an unsignaled stream is only known
+    /// once its packets arrive.
+    pub fn ssrcs(&self) -> Vec<u32> {
+        take_ssrcs(|size| unsafe { raw::rtc_receiver_get_ssrcs(self.raw, size) })
+    }
+
+    /// The estimated time between the capture of the latest packet of an ssrc of the receiver
+    /// and its reception, None unless the abs-capture-time header extension is negotiated,
+    /// see `RtpTransceiver::ABS_CAPTURE_TIME`, and the clock of the sender is estimated.
+    pub fn capture_delay(&self, ssrc: u32) -> Option<Duration> {
+        let delay = unsafe { raw::rtc_receiver_get_capture_delay(self.raw, ssrc) };
+        u64::try_from(delay).ok().map(Duration::from_millis)
+    }
+
+    /// The first stream of the receiver, from the remote description, its track is played in
+    /// sync with the other tracks of the sync group, None when it is part of no stream.
+    ///
+    /// The group is chosen by the remote sender, a receiver is taken out of it, or moved to
+    /// another one, by rewriting its media section of the remote description before it is set,
+    /// see `MediaSection::set_sync_group`.
+    pub fn sync_group(&self) -> Option<String> {
+        take_string(unsafe { raw::rtc_receiver_get_sync_group(self.raw) })
+    }
+
+    /// The track of the receiver, a receiver always has one, even before anything is received.
+    pub fn track(&self) -> MediaStreamTrack {
+        MediaStreamTrack::from_raw(unsafe { raw::rtc_receiver_get_track(self.raw) })
+    }
+}
+
+impl Drop for RtpReceiver {
+    fn drop(&mut self) {
+        unsafe { raw::rtc_free_receiver(self.raw) }
+    }
+}
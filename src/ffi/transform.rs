@@ -0,0 +1,124 @@
+use super::{raw, FrameCodec, MediaType, PeerConnection, RtpDirection};
+use crate::{Error, Result};
+
+use libc::*;
+use std::ffi::CString;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::Arc;
+
+type TransformHandler = Box<dyn FnMut(&mut EncodedFrame) -> bool + Send>;
+
+/// An encoded frame going through a transform, with the metadata of its rtp packets.
+///
+/// The payload is the encoded frame as produced by the encoder, or as expected by the
+/// decoder, the changes made to it are what is packetized or decoded.
+#[derive(Clone, Debug)]
+pub struct EncodedFrame {
+    pub kind: MediaType,
+    /// Outgoing for the frames of a sender, incoming for the frames of a receiver.
+    pub direction: RtpDirection,
+    pub ssrc: u32,
+    pub payload_type: u8,
+    /// The rtp timestamp of the frame, in the clock rate of its payload.
+    pub timestamp: u32,
+    /// Always false for audio frames.
+    pub keyframe: bool,
+    pub codec: FrameCodec,
+    pub data: Vec<u8>,
+}
+
+impl EncodedFrame {
+    fn from_raw(raw: &raw::RTCTransformableFrame) -> Self {
+        Self {
+            kind: raw.kind,
+            direction: raw.direction,
+            ssrc: raw.ssrc,
+            payload_type: raw.payload_type,
+            timestamp: raw.timestamp,
+            keyframe: raw.keyframe,
+            codec: raw.codec,
+            data: unsafe { std::slice::from_raw_parts(raw.data, raw.size) }.to_vec(),
+        }
+    }
+}
+
+// a panic can not unwind into the native side, a panicking
+// handler drops the frame rather than letting it through untransformed.
+extern "C" fn on_transform(frame: *const raw::RTCTransformableFrame, ctx: *mut c_void) -> bool {
+    let handler = unsafe { &mut *(ctx as *mut TransformHandler) };
+    let raw_frame = unsafe { &*frame };
+    catch_unwind(AssertUnwindSafe(|| {
+        let mut encoded = EncodedFrame::from_raw(raw_frame);
+        if !handler(&mut encoded) {
+            return false;
+        }
+
+        let unchanged = unsafe { std::slice::from_raw_parts(raw_frame.data, raw_frame.size) };
+        if encoded.data != unchanged {
+            unsafe {
+                raw::rtc_transformable_frame_set_data(frame, encoded.data.as_ptr(), encoded.data.len())
+            };
+        }
+
+        true
+    }))
+    .unwrap_or(false)
+}
+
+/// FrameTransform
+///
+/// A transform registered on the sender or the receiver of a transceiver, the handler
+/// is removed when it is dropped, and the frames then pass through unchanged.
+pub struct FrameTransform {
+    raw: *const raw::RTCFrameTransform,
+    handler: *mut TransformHandler,
+    // the sender and the receiver belong to the connection.
+    _peer: Arc<PeerConnection>,
+}
+
+// the handler is Send, and the native transform can be removed from any thread.
+unsafe impl Send for FrameTransform {}
+unsafe impl Sync for FrameTransform {}
+
+impl FrameTransform {
+    pub(crate) fn new(
+        peer: Arc<PeerConnection>,
+        mid: &str,
+        direction: RtpDirection,
+        handler: impl FnMut(&mut EncodedFrame) -> bool + Send + 'static,
+    ) -> Result<Self> {
+        let c_mid = CString::new(mid)?;
+        let handler: *mut TransformHandler = Box::into_raw(Box::new(Box::new(handler)));
+        let raw = unsafe {
+            raw::rtc_add_frame_transform(
+                peer.raw,
+                c_mid.as_ptr(),
+                direction,
+                handler as *mut c_void,
+                on_transform,
+            )
+        };
+
+        if raw.is_null() {
+            drop(unsafe { Box::from_raw(handler) });
+            return Err(Error::InvalidState(format!(
+                "there is no transceiver with the mid {}",
+                mid
+            )));
+        }
+
+        Ok(Self {
+            raw,
+            handler,
+            _peer: peer,
+        })
+    }
+}
+
+impl Drop for FrameTransform {
+    fn drop(&mut self) {
+        // the native side no longer calls the handler once it is removed.
+        unsafe { raw::rtc_remove_frame_transform(self.raw) };
+        drop(unsafe { Box::from_raw(self.handler) });
+    }
+}
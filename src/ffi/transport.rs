@@ -0,0 +1,172 @@
+use super::observer::Subscribers;
+use super::{dispatcher, raw, DtlsTransportState, IceTransportState, TransportError, WebRtcRuntime};
+
+use futures::{Stream, StreamExt};
+use libc::*;
+use std::fmt;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::Arc;
+
+/// An event of a DTLS transport or of its ICE transport.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransportEvent {
+    DtlsStateChange(DtlsTransportState),
+    IceStateChange(IceTransportState),
+    /// The DTLS transport failed, it is followed by the failed state.
+    Error(TransportError),
+}
+
+impl fmt::Display for TransportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::HandshakeFailed => "the dtls handshake failed",
+            Self::IncompatibleCipherSuite => "the peers have no dtls cipher suite in common",
+            Self::DtlsFailed => "the dtls transport failed",
+        })
+    }
+}
+
+impl std::error::Error for TransportError {}
+
+fn subscribers_from_ctx(ctx: *mut c_void) -> Arc<Subscribers<TransportEvent>> {
+    unsafe {
+        Arc::increment_strong_count(ctx as *const Subscribers<TransportEvent>);
+        Arc::from_raw(ctx as *const Subscribers<TransportEvent>)
+    }
+}
+
+// called on the network thread, the listeners are woken on the dispatcher thread.
+fn emit(event: TransportEvent, ctx: *mut c_void) {
+    let events = subscribers_from_ctx(ctx);
+    dispatcher::dispatch(move || {
+        let _ = catch_unwind(AssertUnwindSafe(|| events.emit(event)));
+    });
+}
+
+extern "C" fn on_dtls_state_change(state: DtlsTransportState, ctx: *mut c_void) {
+    emit(TransportEvent::DtlsStateChange(state), ctx)
+}
+
+extern "C" fn on_ice_state_change(state: IceTransportState, ctx: *mut c_void) {
+    emit(TransportEvent::IceStateChange(state), ctx)
+}
+
+extern "C" fn on_error(error: TransportError, ctx: *mut c_void) {
+    emit(TransportEvent::Error(error), ctx)
+}
+
+/// The native transport and its observer, removed when it is dropped.
+struct Transport {
+    raw: *const raw::RTCTransport,
+    observer: *const raw::RTCTransportObserver,
+    events: Arc<Subscribers<TransportEvent>>,
+    // the native transport calls into the network thread of the runtime.
+    _runtime: WebRtcRuntime,
+}
+
+// the native transport is reference counted, and only read on the network thread.
+unsafe impl Send for Transport {}
+unsafe impl Sync for Transport {}
+
+impl Drop for Transport {
+    fn drop(&mut self) {
+        unsafe {
+            raw::rtc_transport_remove_observer(self.observer);
+            raw::rtc_free_transport(self.raw);
+        }
+    }
+}
+
+/// RTCDtlsTransport
+///
+/// The DTLS transport of a media id, shared by the media sections bundled with it. The
+/// connection state of the connection aggregates the state of its transports, a transport
+/// tells an ICE failure, where no candidate pair works, from a DTLS failure, where the
+/// network works but the handshake or the certificate of the remote peer does not.
+#[derive(Clone)]
+pub struct RTCDtlsTransport {
+    inner: Arc<Transport>,
+}
+
+impl RTCDtlsTransport {
+    /// Takes the ownership of a transport, and observes it until it is dropped.
+    pub(crate) fn from_raw(raw: *const raw::RTCTransport, runtime: WebRtcRuntime) -> Self {
+        let events = Arc::new(Subscribers::default());
+        let observer = unsafe {
+            raw::rtc_transport_add_observer(
+                raw,
+                Arc::as_ptr(&events) as *mut c_void,
+                on_dtls_state_change,
+                on_ice_state_change,
+                on_error,
+            )
+        };
+
+        Self {
+            inner: Arc::new(Transport {
+                _runtime: runtime,
+                observer,
+                events,
+                raw,
+            }),
+        }
+    }
+
+    pub fn state(&self) -> DtlsTransportState {
+        unsafe { raw::rtc_get_dtls_transport_state(self.inner.raw) }
+    }
+
+    /// The ICE transport carrying the DTLS packets.
+    pub fn ice_transport(&self) -> RTCIceTransport {
+        RTCIceTransport {
+            inner: self.inner.clone(),
+        }
+    }
+
+    /// Every event of the DTLS transport and of its ICE transport, in the order they happened.
+    pub fn events(&self) -> impl Stream<Item = TransportEvent> {
+        self.inner.events.subscribe()
+    }
+
+    pub fn on_state_change(&self) -> impl Stream<Item = DtlsTransportState> {
+        self.events().filter_map(|event| async move {
+            match event {
+                TransportEvent::DtlsStateChange(state) => Some(state),
+                _ => None,
+            }
+        })
+    }
+
+    /// The reason the transport failed, such as a fatal alert received during the handshake.
+    pub fn on_error(&self) -> impl Stream<Item = TransportError> {
+        self.events().filter_map(|event| async move {
+            match event {
+                TransportEvent::Error(error) => Some(error),
+                _ => None,
+            }
+        })
+    }
+}
+
+/// RTCIceTransport
+///
+/// The ICE transport underneath a DTLS transport, see `RTCDtlsTransport::ice_transport`.
+#[derive(Clone)]
+pub struct RTCIceTransport {
+    inner: Arc<Transport>,
+}
+
+impl RTCIceTransport {
+    pub fn state(&self) -> IceTransportState {
+        unsafe { raw::rtc_get_ice_transport_state(self.inner.raw) }
+    }
+
+    pub fn on_state_change(&self) -> impl Stream<Item = IceTransportState> {
+        self.inner.events.subscribe().filter_map(|event| async move {
+            match event {
+                TransportEvent::IceStateChange(state) => Some(state),
+                _ => None,
+            }
+        })
+    }
+}
@@ -0,0 +1,172 @@
+use crate::{Error, Result};
+
+use std::fmt;
+use std::str::FromStr;
+
+/// The field trials of a runtime, the mechanism of libwebrtc to toggle its experimental
+/// behaviors, such as `WebRTC-Video-Pacing/factor:1.1/`.
+///
+/// Every trial is a name and a group, which is either `Enabled`, `Disabled`, or a list of
+/// parameters. A trial set twice keeps its latest group. The names are not checked against
+/// a list of known trials, an unknown trial is ignored by libwebrtc, but a trial which can not
+/// be written in the format of libwebrtc, such as a name with a slash, is not set and fails
+/// `validate`, and so the runtime built with it.
+///
+/// ```no_run
+/// use webrtc::field_trials::FieldTrials;
+///
+/// let trials = FieldTrials::new()
+///     .enable("WebRTC-Bwe-LossBasedBweV2")
+///     .parameters("WebRTC-Video-Pacing", &[("factor", "1.1")]);
+/// assert_eq!(
+///     trials.to_string(),
+///     "WebRTC-Bwe-LossBasedBweV2/Enabled/WebRTC-Video-Pacing/factor:1.1/"
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FieldTrials {
+    trials: Vec<(String, String)>,
+    // the first trial refused by the builder methods, reported by `validate`.
+    invalid: Option<String>,
+}
+
+impl FieldTrials {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn enable(self, name: &str) -> Self {
+        self.group(name, "Enabled")
+    }
+
+    pub fn disable(self, name: &str) -> Self {
+        self.group(name, "Disabled")
+    }
+
+    /// Sets a trial to a list of parameters, such as `factor:1.1,max_delay:100ms`,
+    /// the keys and the values can not hold a slash, a comma or a colon.
+    pub fn parameters(mut self, name: &str, parameters: &[(&str, &str)]) -> Self {
+        let separator = |value: &str| value.contains(['/', ',', ':']);
+        if let Some((key, value)) = parameters
+            .iter()
+            .find(|(key, value)| key.is_empty() || separator(key) || separator(value))
+        {
+            self.refuse(format!("the parameter {:?}:{:?} of the trial {:?} is invalid", key, value, name));
+            return self;
+        }
+
+        let group = parameters
+            .iter()
+            .map(|(key, value)| format!("{}:{}", key, value))
+            .collect::<Vec<_>>()
+            .join(",");
+        self.group(name, &group)
+    }
+
+    /// Sets the group of a trial as is, the name and the group can neither be empty
+    /// nor hold a slash.
+    pub fn group(mut self, name: &str, group: &str) -> Self {
+        let invalid = |value: &str| value.is_empty() || value.contains('/');
+        if invalid(name) || invalid(group) {
+            self.refuse(format!("the trial {:?} of group {:?} is invalid", name, group));
+            return self;
+        }
+
+        self.insert(name.to_string(), group.to_string());
+        self
+    }
+
+    /// Fails with the first trial which was refused by the builder methods.
+    pub fn validate(&self) -> Result<()> {
+        match &self.invalid {
+            Some(reason) => Err(Error::InvalidConfiguration(format!("invalid field trials: {}", reason))),
+            None => Ok(()),
+        }
+    }
+
+    /// The escape hatch for the trials which are not covered by the other methods,
+    /// the string is parsed and its trials are set in order.
+    pub fn raw(mut self, trials: &str) -> Result<Self> {
+        self.extend(trials.parse()?);
+        Ok(self)
+    }
+
+    /// Sets the trials of another set, which take precedence over the existing ones.
+    pub fn extend(&mut self, other: FieldTrials) {
+        for (name, group) in other.trials {
+            self.insert(name, group);
+        }
+
+        if let Some(reason) = other.invalid {
+            self.refuse(reason);
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.trials
+            .iter()
+            .find(|(trial, _)| trial == name)
+            .map(|(_, group)| group.as_str())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.trials.is_empty()
+    }
+
+    fn refuse(&mut self, reason: String) {
+        self.invalid.get_or_insert(reason);
+    }
+
+    fn insert(&mut self, name: String, group: String) {
+        match self.trials.iter_mut().find(|(trial, _)| *trial == name) {
+            Some(trial) => trial.1 = group,
+            None => self.trials.push((name, group)),
+        }
+    }
+}
+
+impl FromStr for FieldTrials {
+    type Err = Error;
+
+    /// Parses the `Name/Group/Name/Group/` format of libwebrtc.
+    fn from_str(value: &str) -> Result<Self> {
+        let invalid = |reason: &str| {
+            Error::InvalidConfiguration(format!("invalid field trials {:?}: {}", value, reason))
+        };
+
+        let trimmed = value.trim();
+        if trimmed.is_empty() {
+            return Ok(Self::new());
+        }
+
+        let parts = trimmed
+            .strip_suffix('/')
+            .ok_or_else(|| invalid("the trials must end with a slash"))?
+            .split('/')
+            .collect::<Vec<_>>();
+        if parts.len() % 2 != 0 {
+            return Err(invalid("a trial has no group"));
+        }
+
+        let mut trials = Self::new();
+        for pair in parts.chunks(2) {
+            if pair[0].is_empty() || pair[1].is_empty() {
+                return Err(invalid("a trial has an empty name or group"));
+            }
+
+            trials.insert(pair[0].to_string(), pair[1].to_string());
+        }
+
+        Ok(trials)
+    }
+}
+
+impl fmt::Display for FieldTrials {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, group) in &self.trials {
+            write!(f, "{}/{}/", name, group)?;
+        }
+
+        Ok(())
+    }
+}
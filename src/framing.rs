@@ -0,0 +1,358 @@
+use crate::{DataChannelIo, DataChannelMessages, Error, RTCDataChannel, RTCSctpTransport, Result};
+
+use futures::StreamExt;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// The versions of the framing protocol, the peers speak the highest version both support.
+const MIN_VERSION: u8 = 1;
+const VERSION: u8 = 1;
+
+/// `[version, HELLO] + the max message size of the sender as a big endian u64`,
+/// the first frame sent by both peers.
+const HELLO: u8 = 0;
+
+/// `[version, START] + the size of the message as a big endian u64 + the first chunk`.
+const START: u8 = 1;
+
+/// `[version, CONTINUE] + the next chunk`.
+const CONTINUE: u8 = 2;
+
+const START_HEADER_SIZE: usize = 10;
+const CONTINUE_HEADER_SIZE: usize = 2;
+
+/// The most memory reserved for a message before its frames arrive, the size of a message
+/// is announced by the remote peer, so a larger message grows as its frames are received.
+const MAX_INITIAL_CAPACITY: u64 = 1024 * 1024;
+
+/// A data channel carrying messages of any size.
+///
+/// The SCTP transport limits the size of a message, usually to 256 KiB, and some
+/// implementations only receive 16 KiB, so a larger message is split into frames of
+/// `DataChannelIo::MAX_MESSAGE_SIZE` bytes which are reassembled by the remote peer,
+/// or of the limit of the transport, see `FramedDataChannel::sctp`.
+/// The peers first exchange the version of the protocol and the largest message they
+/// accept, a message beyond the limit of the remote peer is refused before it is sent.
+///
+/// Both ends of the channel must use the framing layer, and the channel must be ordered
+/// and reliable. The frames of a message are sent back to back, so a `send` which is
+/// cancelled midway, or a frame the remote peer refuses, leaves the channel unusable.
+pub struct FramedDataChannel {
+    channel: Arc<RTCDataChannel>,
+    // the frames of a message are read back to back.
+    messages: Mutex<DataChannelMessages>,
+    // the sends are serialized so the frames of two messages never interleave.
+    sending: Mutex<()>,
+    version: u8,
+    max_message_size: u64,
+    remote_max_message_size: u64,
+    frame_size: usize,
+}
+
+impl FramedDataChannel {
+    /// The default of the largest message received.
+    pub const DEFAULT_MAX_MESSAGE_SIZE: u64 = 64 * 1024 * 1024;
+
+    /// Exchanges the version of the protocol and the largest message accepted by each peer,
+    /// which waits for the channel to open and for the remote peer to negotiate as well.
+    ///
+    /// The channel is read by the framing layer from then on, see `RTCDataChannel::messages`.
+    pub async fn negotiate(channel: Arc<RTCDataChannel>, max_message_size: u64) -> Result<Self> {
+        if !channel.ordered() || !channel.reliable() {
+            return Err(Error::InvalidState(format!(
+                "data channel {} is not ordered and reliable",
+                channel.label()
+            )));
+        }
+
+        if max_message_size == 0 {
+            return Err(Error::InvalidConfiguration(
+                "the max message size must not be zero".to_string(),
+            ));
+        }
+
+        let mut messages = channel.clone().messages()?;
+        channel.writable(DataChannelIo::HIGH_WATER_MARK).await?;
+        channel.send(&encode_hello(max_message_size), true)?;
+
+        let frame = messages.next().await.ok_or(Error::Closed)?.data;
+        let (version, remote_max_message_size) = decode_hello(&frame)?;
+        Ok(Self {
+            messages: Mutex::new(messages),
+            sending: Mutex::new(()),
+            frame_size: DataChannelIo::MAX_MESSAGE_SIZE,
+            remote_max_message_size,
+            channel,
+            max_message_size,
+            version,
+        })
+    }
+
+    /// Sizes the frames to the largest message of the SCTP transport, as negotiated by both
+    /// peers, see `RTCPeerConnection::sctp`. The frames keep the `DataChannelIo::MAX_MESSAGE_SIZE`
+    /// every implementation receives while the transport does not know its limit.
+    pub fn sctp(mut self, transport: &RTCSctpTransport) -> Self {
+        self.frame_size = frame_size(transport);
+        self
+    }
+
+    /// The version of the protocol both peers speak.
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// The largest message received.
+    pub fn max_message_size(&self) -> u64 {
+        self.max_message_size
+    }
+
+    /// The largest message sent, as accepted by the remote peer.
+    pub fn remote_max_message_size(&self) -> u64 {
+        self.remote_max_message_size
+    }
+
+    /// The largest frame sent, headers included.
+    pub fn frame_size(&self) -> usize {
+        self.frame_size
+    }
+
+    /// Sends a message, which waits while the send buffer of the channel is over
+    /// `DataChannelIo::HIGH_WATER_MARK`, and for the messages sent before it.
+    pub async fn send(&self, data: &[u8]) -> Result<()> {
+        if data.len() as u64 > self.remote_max_message_size {
+            return Err(Error::InvalidFrame(format!(
+                "the message of {} bytes exceeds the {} bytes accepted by the remote peer",
+                data.len(),
+                self.remote_max_message_size
+            )));
+        }
+
+        let _sending = self.sending.lock().await;
+        for frame in encode(self.version, data, self.frame_size) {
+            self.channel.writable(DataChannelIo::HIGH_WATER_MARK).await?;
+            self.channel.send(&frame, true)?;
+        }
+
+        Ok(())
+    }
+
+    /// Receives the next message, None once the channel is closed.
+    pub async fn recv(&self) -> Result<Option<Vec<u8>>> {
+        let mut messages = self.messages.lock().await;
+        let Some(frame) = messages.next().await else {
+            return Ok(None);
+        };
+
+        let mut message = Reassembly::start(self.version, &frame.data, self.max_message_size)?;
+        while !message.is_complete() {
+            let frame = messages.next().await.ok_or(Error::Closed)?;
+            message.push(&frame.data)?;
+        }
+
+        message.finish().map(Some)
+    }
+
+    /// The channel the messages are framed on.
+    pub fn channel(&self) -> &Arc<RTCDataChannel> {
+        &self.channel
+    }
+}
+
+fn frame_size(transport: &RTCSctpTransport) -> usize {
+    transport
+        .max_message_size
+        .filter(|size| *size > START_HEADER_SIZE as u64)
+        .map(|size| size.min(usize::MAX as u64) as usize)
+        .unwrap_or(DataChannelIo::MAX_MESSAGE_SIZE)
+}
+
+fn encode_hello(max_message_size: u64) -> Vec<u8> {
+    let mut hello = vec![VERSION, HELLO];
+    hello.extend_from_slice(&max_message_size.to_be_bytes());
+    hello
+}
+
+/// The version both peers speak and the largest message accepted by the remote peer.
+fn decode_hello(frame: &[u8]) -> Result<(u8, u64)> {
+    if frame.len() != START_HEADER_SIZE || frame[1] != HELLO {
+        return Err(Error::InvalidFrame("the first frame is not a hello".to_string()));
+    }
+
+    let version = frame[0].min(VERSION);
+    if version < MIN_VERSION {
+        return Err(Error::InvalidFrame(format!(
+            "the remote peer speaks version {}, which is not supported",
+            frame[0]
+        )));
+    }
+
+    Ok((version, read_u64(&frame[2..])))
+}
+
+/// The frames of a message, of at most `frame_size` bytes, which must be larger than
+/// the header of the first frame.
+fn encode(version: u8, data: &[u8], frame_size: usize) -> impl Iterator<Item = Vec<u8>> + '_ {
+    let first = data.len().min(frame_size - START_HEADER_SIZE);
+    let mut start = Vec::with_capacity(START_HEADER_SIZE + first);
+    start.extend_from_slice(&[version, START]);
+    start.extend_from_slice(&(data.len() as u64).to_be_bytes());
+    start.extend_from_slice(&data[..first]);
+
+    let rest = data[first..].chunks(frame_size - CONTINUE_HEADER_SIZE).map(move |chunk| {
+        let mut frame = Vec::with_capacity(CONTINUE_HEADER_SIZE + chunk.len());
+        frame.extend_from_slice(&[version, CONTINUE]);
+        frame.extend_from_slice(chunk);
+        frame
+    });
+
+    std::iter::once(start).chain(rest)
+}
+
+/// A message reassembled from its frames.
+struct Reassembly {
+    version: u8,
+    size: u64,
+    message: Vec<u8>,
+}
+
+impl Reassembly {
+    fn start(version: u8, frame: &[u8], max_message_size: u64) -> Result<Self> {
+        let frame = check(version, frame, START, START_HEADER_SIZE)?;
+        let size = read_u64(&frame[2..]);
+        if size > max_message_size {
+            return Err(Error::InvalidFrame(format!(
+                "the message of {} bytes exceeds the max message size of {} bytes",
+                size, max_message_size
+            )));
+        }
+
+        let mut message = Vec::with_capacity(size.min(MAX_INITIAL_CAPACITY) as usize);
+        message.extend_from_slice(&frame[START_HEADER_SIZE..]);
+        Ok(Self {
+            version,
+            size,
+            message,
+        })
+    }
+
+    fn push(&mut self, frame: &[u8]) -> Result<()> {
+        let frame = check(self.version, frame, CONTINUE, CONTINUE_HEADER_SIZE)?;
+        self.message.extend_from_slice(&frame[CONTINUE_HEADER_SIZE..]);
+        Ok(())
+    }
+
+    fn is_complete(&self) -> bool {
+        self.message.len() as u64 >= self.size
+    }
+
+    fn finish(self) -> Result<Vec<u8>> {
+        if self.message.len() as u64 != self.size {
+            return Err(Error::InvalidFrame(format!(
+                "the message of {} bytes has {} bytes",
+                self.size,
+                self.message.len()
+            )));
+        }
+
+        Ok(self.message)
+    }
+}
+
+fn check(version: u8, frame: &[u8], kind: u8, header_size: usize) -> Result<&[u8]> {
+    if frame.len() < header_size || frame[0] != version || frame[1] != kind {
+        return Err(Error::InvalidFrame(format!(
+            "expected a frame of kind {} of version {}",
+            kind, version
+        )));
+    }
+
+    Ok(frame)
+}
+
+fn read_u64(bytes: &[u8]) -> u64 {
+    let mut buf = [0; 8];
+    buf.copy_from_slice(&bytes[..8]);
+    u64::from_be_bytes(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(version: u8, frames: &[Vec<u8>], max_message_size: u64) -> Result<Vec<u8>> {
+        let mut frames = frames.iter();
+        let mut message = Reassembly::start(version, frames.next().unwrap(), max_message_size)?;
+        while !message.is_complete() {
+            message.push(frames.next().ok_or(Error::Closed)?)?;
+        }
+
+        assert!(frames.next().is_none());
+        message.finish()
+    }
+
+    #[test]
+    fn hello_round_trip() {
+        let hello = encode_hello(FramedDataChannel::DEFAULT_MAX_MESSAGE_SIZE);
+        assert_eq!(hello.len(), START_HEADER_SIZE);
+        assert_eq!(
+            decode_hello(&hello).unwrap(),
+            (VERSION, FramedDataChannel::DEFAULT_MAX_MESSAGE_SIZE)
+        );
+
+        // a newer peer speaks the version of the older one.
+        let mut newer = hello.clone();
+        newer[0] = VERSION + 1;
+        assert_eq!(decode_hello(&newer).unwrap().0, VERSION);
+
+        let mut older = hello.clone();
+        older[0] = MIN_VERSION - 1;
+        assert!(decode_hello(&older).is_err());
+
+        assert!(decode_hello(&hello[..START_HEADER_SIZE - 1]).is_err());
+        assert!(decode_hello(&encode(VERSION, &[0; 8], 64).next().unwrap()[..START_HEADER_SIZE]).is_err());
+    }
+
+    #[test]
+    fn frames_round_trip() {
+        let frame_size = 64;
+        let data = (0..1000).map(|i| i as u8).collect::<Vec<_>>();
+        for len in [0, 1, frame_size - START_HEADER_SIZE, frame_size - START_HEADER_SIZE + 1, 1000] {
+            let frames = encode(VERSION, &data[..len], frame_size).collect::<Vec<_>>();
+            assert!(frames.iter().all(|frame| frame.len() <= frame_size));
+            assert_eq!(decode(VERSION, &frames, 1000).unwrap(), &data[..len]);
+        }
+
+        let frames = encode(VERSION, &data, DataChannelIo::MAX_MESSAGE_SIZE).collect::<Vec<_>>();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(decode(VERSION, &frames, 1000).unwrap(), data);
+    }
+
+    #[test]
+    fn invalid_frames() {
+        let data = [7; 100];
+        let frames = encode(VERSION, &data, 32).collect::<Vec<_>>();
+        assert!(Reassembly::start(VERSION, &frames[0], 99).is_err());
+        assert!(Reassembly::start(VERSION, &frames[1], 100).is_err());
+        assert!(Reassembly::start(VERSION + 1, &frames[0], 100).is_err());
+
+        // a continuation overrunning the announced size.
+        let mut message = Reassembly::start(VERSION, &frames[0], 100).unwrap();
+        let mut overrun = frames[1].clone();
+        overrun.extend_from_slice(&[0; 100]);
+        message.push(&overrun).unwrap();
+        assert!(message.is_complete());
+        assert!(message.finish().is_err());
+    }
+
+    #[test]
+    fn frame_size_of_the_transport() {
+        let transport = |max_message_size| RTCSctpTransport {
+            max_message_size,
+            max_channels: None,
+        };
+
+        assert_eq!(frame_size(&transport(None)), DataChannelIo::MAX_MESSAGE_SIZE);
+        assert_eq!(frame_size(&transport(Some(START_HEADER_SIZE as u64))), DataChannelIo::MAX_MESSAGE_SIZE);
+        assert_eq!(frame_size(&transport(Some(256 * 1024))), 256 * 1024);
+    }
+}
@@ -0,0 +1,197 @@
+use crate::{BitrateSettings, RTCPeerConnection};
+
+use futures::future::join_all;
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+/// When a connection uses more than this share of its cap,
+/// it is assumed to want more than it is given.
+const SATURATION: f64 = 0.9;
+
+/// How much a saturated connection asks for beyond its current cap,
+/// this lets the caps grow back when other connections leave.
+const HEADROOM: f64 = 1.5;
+
+/// Splits a budget between connections with a weighted max-min fair allocation.
+///
+/// Every connection receives a share of the budget proportional to its weight, connections
+/// demanding less than their share get their demand, and the leftover is redistributed
+/// between the other connections. The result is in the order of `demands`.
+pub fn allocate(budget: u64, demands: &[(u64, u32)]) -> Vec<u64> {
+    let mut allocations = vec![0; demands.len()];
+    let mut pending = (0..demands.len()).collect::<Vec<_>>();
+    let mut remaining = budget;
+
+    while !pending.is_empty() {
+        // in u128, the product of a budget and a weight does not fit in u64.
+        let weights = pending.iter().map(|i| demands[*i].1.max(1) as u128).sum::<u128>();
+        let share = |i: usize| (remaining as u128 * demands[i].1.max(1) as u128 / weights) as u64;
+
+        // connections which are satisfied with less than their share are settled first,
+        // when none of them is, the remaining budget is split by weight and we are done.
+        let (satisfied, unsatisfied): (Vec<_>, Vec<_>) =
+            pending.iter().partition(|i| demands[**i].0 <= share(**i));
+        if satisfied.is_empty() {
+            for i in &unsatisfied {
+                allocations[*i] = share(*i);
+            }
+
+            break;
+        }
+
+        for i in satisfied {
+            allocations[i] = demands[i].0;
+            remaining -= demands[i].0;
+        }
+
+        pending = unsatisfied;
+    }
+
+    allocations
+}
+
+struct Governed {
+    peer: RTCPeerConnection,
+    weight: u32,
+    cap: Option<u64>,
+}
+
+struct State {
+    budget: u64,
+    min_bitrate: u64,
+    interval: Duration,
+    connections: HashMap<u64, Governed>,
+    next_id: u64,
+}
+
+/// Enforces a global outbound bandwidth budget over many connections of the process.
+///
+/// The governor periodically reads the bandwidth estimation of every connection and caps
+/// each one with `RTCPeerConnection::set_bitrate`, so that the sum of the caps stays within
+/// the budget and no connection can starve the others on a constrained uplink.
+/// Caps never go below the minimum bitrate, which takes precedence over the budget when
+/// there are too many connections. The governor owns the maximum bitrate of its connections,
+/// their minimum bitrate is left as the application set it.
+///
+/// The governor is `Send + Sync` and can be shared behind an `Arc`, so connections can be
+/// added and removed while `run` is rebalancing them.
+pub struct BandwidthGovernor {
+    state: Mutex<State>,
+}
+
+impl BandwidthGovernor {
+    /// Creates a governor with a total outbound budget in bits per second.
+    pub fn new(budget: u64) -> Self {
+        Self {
+            state: Mutex::new(State {
+                budget,
+                min_bitrate: 30_000,
+                interval: Duration::from_secs(2),
+                connections: HashMap::new(),
+                next_id: 0,
+            }),
+        }
+    }
+
+    /// The floor of every cap in bits per second, enough to keep the audio alive by default.
+    pub fn set_min_bitrate(&self, min_bitrate: u64) {
+        self.state().min_bitrate = min_bitrate;
+    }
+
+    /// The interval at which `run` rebalances the caps, from its next start.
+    pub fn set_interval(&self, interval: Duration) {
+        self.state().interval = interval;
+    }
+
+    pub fn set_budget(&self, budget: u64) {
+        self.state().budget = budget;
+    }
+
+    /// Puts a connection under the governor, its share of the budget is proportional to
+    /// its weight. Returns the id used to remove it.
+    pub fn add(&self, peer: RTCPeerConnection, weight: u32) -> u64 {
+        let mut state = self.state();
+        let id = state.next_id;
+        state.next_id += 1;
+        state.connections.insert(id, Governed {
+            peer,
+            weight,
+            cap: None,
+        });
+
+        id
+    }
+
+    /// Releases a connection, it keeps its last cap.
+    pub fn remove(&self, id: u64) -> Option<RTCPeerConnection> {
+        self.state().connections.remove(&id).map(|governed| governed.peer)
+    }
+
+    /// Samples the stats of every connection at once and applies new caps,
+    /// a connection whose stats can not be read is treated as having no estimation.
+    pub async fn rebalance(&self) {
+        let peers = self
+            .state()
+            .connections
+            .iter()
+            .map(|(id, governed)| (*id, governed.peer.clone()))
+            .collect::<Vec<_>>();
+        let reports = join_all(peers.iter().map(|(_, peer)| peer.get_stats())).await;
+
+        // the connections removed while the stats were read are skipped.
+        let mut state = self.state();
+        let mut governed = Vec::with_capacity(peers.len());
+        let mut demands = Vec::with_capacity(peers.len());
+        for ((id, _), report) in peers.iter().zip(reports) {
+            let cap = match state.connections.get(id) {
+                Some(connection) => connection.cap,
+                None => continue,
+            };
+
+            let available = report
+                .ok()
+                .as_ref()
+                .and_then(|report| report.selected_candidate_pair())
+                .and_then(|pair| pair.available_outgoing_bitrate)
+                .map(|bitrate| bitrate as u64);
+
+            // without an estimation the connection may use whatever it is given.
+            let demand = match (available, cap) {
+                (None, _) => state.budget,
+                (Some(available), Some(cap)) if available as f64 >= cap as f64 * SATURATION => {
+                    (cap as f64 * HEADROOM) as u64
+                }
+                (Some(available), _) => available,
+            };
+
+            governed.push(*id);
+            demands.push((demand, state.connections[id].weight));
+        }
+
+        let min_bitrate = state.min_bitrate;
+        for (id, allocation) in governed.iter().zip(allocate(state.budget, &demands)) {
+            let cap = allocation.max(min_bitrate);
+            if let Some(governed) = state.connections.get_mut(id) {
+                governed.cap = Some(cap);
+                governed.peer.set_bitrate(&BitrateSettings {
+                    max_bitrate: Some(cap.min(u32::MAX as u64) as u32),
+                    ..governed.peer.bitrate()
+                });
+            }
+        }
+    }
+
+    /// Rebalances the caps at every interval, forever.
+    pub async fn run(&self) {
+        let mut interval = tokio::time::interval(self.state().interval);
+        loop {
+            interval.tick().await;
+            self.rebalance().await;
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,312 @@
+use crate::{
+    Error,
+    RTCIceServer,
+    RTCIceServerBuilder,
+    RTCPeerConnection,
+    RTCSessionDescription,
+    Result,
+};
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// How the descriptions are carried in the bodies of the requests.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BodyFormat {
+    /// The description as it is, with the `application/sdp` content type, as WHEP servers
+    /// and most cameras expect it.
+    #[default]
+    Sdp,
+    /// A JSON object with the `type` and `sdp` members, as the API of go2rtc also accepts.
+    Json,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonDescription {
+    #[serde(rename = "type")]
+    kind: String,
+    sdp: String,
+}
+
+/// An offer and answer exchange in a single HTTP request, as embedded devices such as
+/// doorbells, cameras and go2rtc expose it.
+///
+/// The offer is posted once its candidates are gathered, and the response carries the
+/// answer, so there is no candidate to trickle afterwards. Only plain http is supported,
+/// as the devices are usually reached on the local network.
+///
+/// ```no_run
+/// use webrtc::http::HttpSignaling;
+///
+/// # async fn example(peer: &webrtc::RTCPeerConnection) -> webrtc::Result<()> {
+/// let signaling = HttpSignaling::new("http://192.168.1.10:1984/api/webrtc?src=doorbell")?;
+/// signaling.connect(peer).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct HttpSignaling {
+    host: String,
+    port: u16,
+    path: String,
+    format: BodyFormat,
+    headers: Vec<(String, String)>,
+    timeout: Duration,
+}
+
+impl HttpSignaling {
+    /// Takes the url of the endpoint, such as `http://192.168.1.10:1984/api/webrtc?src=camera`.
+    pub fn new(url: &str) -> Result<Self> {
+        let invalid = || Error::InvalidConfiguration(format!("not an http url: {}", url));
+        let rest = url.strip_prefix("http://").ok_or_else(invalid)?;
+        let (authority, path) = match rest.find('/') {
+            Some(index) => rest.split_at(index),
+            None => (rest, "/"),
+        };
+
+        // the brackets of an IPv6 address are kept, they are part of the Host header.
+        let (host, port) = match authority.rfind(':') {
+            Some(index) if !authority[index..].contains(']') => {
+                let port = authority[index + 1..].parse().map_err(|_| invalid())?;
+                (&authority[..index], port)
+            }
+            _ => (authority, 80),
+        };
+
+        if host.is_empty() {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            host: host.to_string(),
+            path: path.to_string(),
+            format: BodyFormat::default(),
+            headers: Vec::new(),
+            timeout: Duration::from_secs(10),
+            port,
+        })
+    }
+
+    pub fn format(mut self, format: BodyFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Adds a header to every request, such as the `Authorization` of the device.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// The time a request may take, 10 seconds by default.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The ICE servers the endpoint advertises in the `Link` headers of its response to
+    /// an OPTIONS request, as WHIP and WHEP servers do, empty when it advertises none.
+    pub async fn ice_servers(&self) -> Result<Vec<RTCIceServer>> {
+        let response = self.request("OPTIONS", None).await?;
+        response
+            .headers("link")
+            .flat_map(links)
+            .filter(|link| link.param("rel").as_deref() == Some("ice-server"))
+            .map(|link| {
+                let mut builder = RTCIceServerBuilder::new().url(&link.url);
+                if let Some(username) = link.param("username") {
+                    builder = builder.username(&username);
+                }
+
+                if let Some(credential) = link.param("credential") {
+                    builder = builder.credential(&credential);
+                }
+
+                builder.build()
+            })
+            .collect()
+    }
+
+    /// Posts an offer and returns the answer of the endpoint.
+    pub async fn exchange(&self, offer: &str) -> Result<String> {
+        let body = match self.format {
+            BodyFormat::Sdp => ("application/sdp", offer.to_string()),
+            BodyFormat::Json => (
+                "application/json",
+                serde_json::to_string(&JsonDescription {
+                    kind: "offer".to_string(),
+                    sdp: offer.to_string(),
+                })?,
+            ),
+        };
+
+        let response = self.request("POST", Some((body.0, &body.1))).await?;
+        let body = String::from_utf8(response.body).map_err(|e| Error::Http(e.to_string()))?;
+        match self.format {
+            BodyFormat::Sdp => Ok(body),
+            BodyFormat::Json => {
+                let answer: JsonDescription = serde_json::from_str(&body)?;
+                if answer.kind != "answer" {
+                    return Err(Error::Http(format!("expected an answer, got an {}", answer.kind)));
+                }
+
+                Ok(answer.sdp)
+            }
+        }
+    }
+
+    /// Negotiates a connection with the endpoint, the offer is created and set, posted once
+    /// its candidates are gathered, and the answer of the endpoint is set.
+    pub async fn connect(&self, peer: &RTCPeerConnection) -> Result<()> {
+        let offer = peer.create_offer().await?;
+        peer.set_local_description(offer).await?;
+        peer.ice_gathering_complete().await?;
+
+        let offer = peer
+            .local_description()
+            .ok_or_else(|| Error::InvalidState("the local description is not set".to_string()))?;
+        let answer = self.exchange(offer.get_sdp()?).await?;
+        let answer = RTCSessionDescription::answer(&answer)?;
+        peer.set_remote_description(answer).await
+    }
+
+    async fn request(&self, method: &str, body: Option<(&str, &str)>) -> Result<Response> {
+        tokio::time::timeout(self.timeout, self.send(method, body))
+            .await
+            .map_err(|_| Error::Timeout)?
+    }
+
+    async fn send(&self, method: &str, body: Option<(&str, &str)>) -> Result<Response> {
+        let mut request = format!("{} {} HTTP/1.1\r\nHost: {}", method, self.path, self.host);
+        if self.port != 80 {
+            request.push_str(&format!(":{}", self.port));
+        }
+
+        request.push_str("\r\nConnection: close\r\n");
+        for (name, value) in &self.headers {
+            request.push_str(&format!("{}: {}\r\n", name, value));
+        }
+
+        if let Some((content_type, body)) = body {
+            request.push_str(&format!("Content-Type: {}\r\n", content_type));
+            request.push_str(&format!("Content-Length: {}\r\n\r\n{}", body.len(), body));
+        } else {
+            request.push_str("\r\n");
+        }
+
+        let host = self.host.trim_start_matches('[').trim_end_matches(']');
+        let mut stream = TcpStream::connect((host, self.port)).await?;
+        stream.write_all(request.as_bytes()).await?;
+
+        // the connection is closed by the endpoint once the response is sent.
+        let mut data = Vec::new();
+        stream.read_to_end(&mut data).await?;
+
+        let response = Response::parse(&data)?;
+        if !(200..300).contains(&response.status) {
+            return Err(Error::Http(format!("{} {} returned {}", method, self.path, response.status)));
+        }
+
+        Ok(response)
+    }
+}
+
+struct Response {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn parse(data: &[u8]) -> Result<Self> {
+        let malformed = || Error::Http("malformed response".to_string());
+        let end = data.windows(4).position(|w| w == b"\r\n\r\n").ok_or_else(malformed)?;
+        let head = std::str::from_utf8(&data[..end])?;
+        let mut lines = head.split("\r\n");
+        let status = lines
+            .next()
+            .and_then(|line| line.split(' ').nth(1))
+            .and_then(|status| status.parse().ok())
+            .ok_or_else(malformed)?;
+
+        let headers = lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+            .collect::<Vec<_>>();
+
+        let mut body = data[end + 4..].to_vec();
+        let chunked = headers
+            .iter()
+            .any(|(name, value)| name == "transfer-encoding" && value.eq_ignore_ascii_case("chunked"));
+        if chunked {
+            body = dechunk(&body).ok_or_else(malformed)?;
+        }
+
+        Ok(Self { status, headers, body })
+    }
+
+    fn headers<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> {
+        self.headers
+            .iter()
+            .filter(move |(header, _)| header == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+fn dechunk(mut data: &[u8]) -> Option<Vec<u8>> {
+    let mut body = Vec::new();
+    loop {
+        let end = data.windows(2).position(|w| w == b"\r\n")?;
+        let size = std::str::from_utf8(&data[..end]).ok()?;
+        let size = usize::from_str_radix(size.split(';').next()?.trim(), 16).ok()?;
+        if size == 0 {
+            return Some(body);
+        }
+
+        data = data.get(end + 2..)?;
+        body.extend_from_slice(data.get(..size)?);
+        data = data.get(size + 2..)?;
+    }
+}
+
+/// A link of a `Link` header, such as `<stun:stun.example.net>; rel="ice-server"`.
+struct Link {
+    url: String,
+    params: Vec<(String, String)>,
+}
+
+impl Link {
+    fn param(&self, name: &str) -> Option<String> {
+        self.params
+            .iter()
+            .find(|(param, _)| param.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.clone())
+    }
+}
+
+/// The links of a header, which separates them by commas.
+fn links(header: &str) -> Vec<Link> {
+    let mut links = Vec::new();
+    for part in header.split(',') {
+        let part = part.trim();
+        if let Some(rest) = part.strip_prefix('<') {
+            let Some((url, params)) = rest.split_once('>') else {
+                continue;
+            };
+
+            let params = params
+                .split(';')
+                .filter_map(|param| param.split_once('='))
+                .map(|(name, value)| (name.trim().to_string(), value.trim().trim_matches('"').to_string()))
+                .collect();
+            links.push(Link {
+                url: url.to_string(),
+                params,
+            });
+        }
+    }
+
+    links
+}
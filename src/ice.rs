@@ -0,0 +1,622 @@
+use crate::{Error, Result};
+
+use serde::Deserialize;
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+/// The type of an ICE candidate, which describes how the address was obtained.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CandidateType {
+    /// The candidate is a host candidate, the address is the actual IP address of the remote peer.
+    Host,
+    /// The candidate is a server reflexive candidate, the address indicates an intermediary
+    /// address assigned by the STUN server to represent the candidate's peer anonymously.
+    Srflx,
+    /// The candidate is a peer reflexive candidate, the address is an intermediary address
+    /// assigned by the STUN server to represent the candidate's peer anonymously.
+    Prflx,
+    /// The candidate is a relay candidate, obtained from a TURN server.
+    Relay,
+}
+
+impl fmt::Display for CandidateType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Host => "host",
+            Self::Srflx => "srflx",
+            Self::Prflx => "prflx",
+            Self::Relay => "relay",
+        })
+    }
+}
+
+impl FromStr for CandidateType {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "host" => Self::Host,
+            "srflx" => Self::Srflx,
+            "prflx" => Self::Prflx,
+            "relay" => Self::Relay,
+            _ => return Err(Error::InvalidCandidate(format!("unknown candidate type: {}", s))),
+        })
+    }
+}
+
+/// The transport protocol of an ICE candidate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Protocol {
+    Udp,
+    Tcp,
+}
+
+impl fmt::Display for Protocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Udp => "udp",
+            Self::Tcp => "tcp",
+        })
+    }
+}
+
+impl FromStr for Protocol {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        // the transport is case insensitive, and is commonly upper-cased by browsers.
+        Ok(match s.to_ascii_lowercase().as_str() {
+            "udp" => Self::Udp,
+            "tcp" => Self::Tcp,
+            _ => return Err(Error::InvalidCandidate(format!("unknown candidate protocol: {}", s))),
+        })
+    }
+}
+
+/// The transport between a peer and its TURN server, the relayed traffic itself is always UDP.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RelayProtocol {
+    Udp,
+    Tcp,
+    /// TLS over TCP, which passes through firewalls only allowing HTTPS.
+    Tls,
+}
+
+impl fmt::Display for RelayProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Udp => "udp",
+            Self::Tcp => "tcp",
+            Self::Tls => "tls",
+        })
+    }
+}
+
+impl FromStr for RelayProtocol {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s.to_ascii_lowercase().as_str() {
+            "udp" => Self::Udp,
+            "tcp" => Self::Tcp,
+            "tls" => Self::Tls,
+            _ => return Err(Error::InvalidCandidate(format!("unknown relay protocol: {}", s))),
+        })
+    }
+}
+
+/// Candidate
+///
+/// A structured view of the SDP "candidate" attribute, as carried by the candidate string of
+/// an RTCIceCandidate:
+///
+/// `candidate:<foundation> <component> <protocol> <priority> <address> <port> typ <type>
+/// [raddr <related address>] [rport <related port>] *(<extension name> <extension value>)`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Candidate {
+    /// An identifier which is the same for any candidates of the same type, base address,
+    /// server and protocol.
+    pub foundation: String,
+    /// The component of the candidate, 1 for RTP and 2 for RTCP.
+    pub component: u16,
+    pub protocol: Protocol,
+    pub priority: u32,
+    /// The address of the candidate, usually an IP address,
+    /// but it can also be a mDNS hostname ending with ".local".
+    pub address: String,
+    pub port: u16,
+    pub kind: CandidateType,
+    /// For reflexive and relay candidates, the address of the host candidate they derive from.
+    pub related_address: Option<String>,
+    pub related_port: Option<u16>,
+    /// Extension attributes such as "tcptype", "generation" or "ufrag", in order of appearance.
+    pub extensions: Vec<(String, String)>,
+}
+
+impl Candidate {
+    /// The IP address of the candidate, none if the address is a hostname.
+    pub fn ip(&self) -> Option<IpAddr> {
+        self.address.parse().ok()
+    }
+
+    /// The protocol between the TURN server and the peer which gathered a relay candidate,
+    /// none for the other candidates.
+    ///
+    /// The protocol is not part of the candidate attribute, it is read from the type preference
+    /// of the priority, which libwebrtc and the browsers lower from UDP to TCP to TLS, so it is
+    /// none for a candidate whose priority is computed otherwise.
+    pub fn relay_protocol(&self) -> Option<RelayProtocol> {
+        if self.kind != CandidateType::Relay {
+            return None;
+        }
+
+        match self.priority >> 24 {
+            2 => Some(RelayProtocol::Udp),
+            1 => Some(RelayProtocol::Tcp),
+            0 => Some(RelayProtocol::Tls),
+            _ => None,
+        }
+    }
+
+    /// The value of an extension attribute.
+    pub fn extension(&self, name: &str) -> Option<&str> {
+        self.extensions
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+impl FromStr for Candidate {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let s = s.strip_prefix("a=").unwrap_or(s);
+        let s = s.strip_prefix("candidate:").unwrap_or(s);
+
+        let mut fields = s.split_ascii_whitespace();
+        let mut next = |name: &str| {
+            fields
+                .next()
+                .ok_or_else(|| Error::InvalidCandidate(format!("candidate is missing the {}", name)))
+        };
+
+        let number = |name: &str, value: &str| {
+            Error::InvalidCandidate(format!("candidate {} is not a number: {}", name, value))
+        };
+
+        let foundation = next("foundation")?.to_string();
+        let component = next("component")?;
+        let component = component.parse().map_err(|_| number("component", component))?;
+        let protocol = next("protocol")?.parse()?;
+        let priority = next("priority")?;
+        let priority = priority.parse().map_err(|_| number("priority", priority))?;
+        let address = next("address")?.to_string();
+        let port = next("port")?;
+        let port = port.parse().map_err(|_| number("port", port))?;
+        if next("type")? != "typ" {
+            return Err(Error::InvalidCandidate("candidate is missing the typ keyword".to_string()));
+        }
+
+        let kind = next("type")?.parse()?;
+        let mut candidate = Self {
+            foundation,
+            component,
+            protocol,
+            priority,
+            address,
+            port,
+            kind,
+            related_address: None,
+            related_port: None,
+            extensions: Vec::new(),
+        };
+
+        while let Some(key) = fields.next() {
+            let value = fields
+                .next()
+                .ok_or_else(|| {
+                    Error::InvalidCandidate(format!("candidate attribute {} has no value", key))
+                })?;
+            match key {
+                "raddr" => candidate.related_address = Some(value.to_string()),
+                "rport" => {
+                    candidate.related_port = Some(value.parse().map_err(|_| number("rport", value))?)
+                }
+                _ => candidate
+                    .extensions
+                    .push((key.to_string(), value.to_string())),
+            }
+        }
+
+        Ok(candidate)
+    }
+}
+
+impl fmt::Display for Candidate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "candidate:{} {} {} {} {} {} typ {}",
+            self.foundation,
+            self.component,
+            self.protocol,
+            self.priority,
+            self.address,
+            self.port,
+            self.kind
+        )?;
+
+        if let Some(address) = &self.related_address {
+            write!(f, " raddr {}", address)?;
+        }
+
+        if let Some(port) = self.related_port {
+            write!(f, " rport {}", port)?;
+        }
+
+        for (key, value) in &self.extensions {
+            write!(f, " {} {}", key, value)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The scheme of an ICE server url.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IceServerScheme {
+    Stun,
+    /// STUN over TLS.
+    Stuns,
+    Turn,
+    /// TURN over TLS.
+    Turns,
+}
+
+impl IceServerScheme {
+    pub fn is_turn(&self) -> bool {
+        matches!(self, Self::Turn | Self::Turns)
+    }
+
+    /// The port used when the url has none, 3478 for plain and 5349 for TLS servers.
+    pub fn default_port(&self) -> u16 {
+        match self {
+            Self::Stun | Self::Turn => 3478,
+            Self::Stuns | Self::Turns => 5349,
+        }
+    }
+}
+
+impl fmt::Display for IceServerScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Stun => "stun",
+            Self::Stuns => "stuns",
+            Self::Turn => "turn",
+            Self::Turns => "turns",
+        })
+    }
+}
+
+impl FromStr for IceServerScheme {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s.to_ascii_lowercase().as_str() {
+            "stun" => Self::Stun,
+            "stuns" => Self::Stuns,
+            "turn" => Self::Turn,
+            "turns" => Self::Turns,
+            _ => return Err(Error::InvalidUrl(format!("unknown scheme: {}", s))),
+        })
+    }
+}
+
+/// IceServerUrl
+///
+/// A STUN or TURN server url, as defined by RFC 7064 and RFC 7065:
+///
+/// `<scheme>:<host>[:<port>][?transport=<udp|tcp>]`
+///
+/// The host is a hostname, an IPv4 address, or an IPv6 address in brackets.
+/// Only TURN urls may carry a transport.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IceServerUrl {
+    pub scheme: IceServerScheme,
+    pub host: String,
+    pub port: Option<u16>,
+    pub transport: Option<Protocol>,
+}
+
+impl IceServerUrl {
+    /// A TURN url reaching the server over the given protocol, such as
+    /// "turns:turn.example.com:443" for TLS.
+    pub fn turn(host: &str, port: Option<u16>, protocol: RelayProtocol) -> Self {
+        let (scheme, transport) = match protocol {
+            RelayProtocol::Udp => (IceServerScheme::Turn, Some(Protocol::Udp)),
+            RelayProtocol::Tcp => (IceServerScheme::Turn, Some(Protocol::Tcp)),
+            RelayProtocol::Tls => (IceServerScheme::Turns, None),
+        };
+
+        Self {
+            scheme,
+            host: host.to_string(),
+            port,
+            transport,
+        }
+    }
+
+    /// The protocol a TURN url reaches its server over, none for a STUN url. A "turns" url
+    /// is TLS, and a "turn" url without a transport is UDP, as libwebrtc defaults to it.
+    pub fn relay_protocol(&self) -> Option<RelayProtocol> {
+        match (self.scheme, self.transport) {
+            (IceServerScheme::Turns, _) => Some(RelayProtocol::Tls),
+            (IceServerScheme::Turn, Some(Protocol::Tcp)) => Some(RelayProtocol::Tcp),
+            (IceServerScheme::Turn, _) => Some(RelayProtocol::Udp),
+            _ => None,
+        }
+    }
+
+    /// The port of the url, or the default port of its scheme.
+    pub fn port_or_default(&self) -> u16 {
+        self.port.unwrap_or_else(|| self.scheme.default_port())
+    }
+}
+
+impl FromStr for IceServerUrl {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = |reason: &str| Error::InvalidUrl(format!("{}: {}", reason, s));
+        let (scheme, rest) = s.trim().split_once(':').ok_or_else(|| invalid("missing scheme"))?;
+        let scheme: IceServerScheme = scheme.parse()?;
+
+        let (address, query) = match rest.split_once('?') {
+            Some((address, query)) => (address, Some(query)),
+            None => (rest, None),
+        };
+
+        // an IPv6 host is in brackets, the port follows the closing bracket.
+        let (host, port) = if let Some(address) = address.strip_prefix('[') {
+            let (host, port) = address.split_once(']').ok_or_else(|| invalid("unclosed bracket"))?;
+            match port {
+                "" => (host, None),
+                port => (host, Some(port.strip_prefix(':').ok_or_else(|| invalid("malformed port"))?)),
+            }
+        } else {
+            match address.split_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (address, None),
+            }
+        };
+
+        if host.is_empty() || host.contains(|c: char| c.is_whitespace() || c == '/' || c == '@') {
+            return Err(invalid("malformed host"));
+        }
+
+        let port = match port {
+            Some(port) => match port.parse::<u16>() {
+                Ok(port) if port > 0 => Some(port),
+                _ => return Err(invalid("malformed port")),
+            },
+            None => None,
+        };
+
+        let mut transport = None;
+        for param in query.into_iter().flat_map(|query| query.split('&')) {
+            match param.split_once('=') {
+                Some(("transport", value)) if scheme.is_turn() => {
+                    transport = Some(value.parse().map_err(|_| invalid("unknown transport"))?);
+                }
+                _ => return Err(invalid("unexpected query parameter")),
+            }
+        }
+
+        Ok(Self {
+            scheme,
+            host: host.to_string(),
+            port,
+            transport,
+        })
+    }
+}
+
+impl fmt::Display for IceServerUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:", self.scheme)?;
+        if self.host.contains(':') {
+            write!(f, "[{}]", self.host)?;
+        } else {
+            f.write_str(&self.host)?;
+        }
+
+        if let Some(port) = self.port {
+            write!(f, ":{}", port)?;
+        }
+
+        if let Some(transport) = self.transport {
+            write!(f, "?transport={}", transport)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn host_candidates() {
+        let candidate: Candidate =
+            "a=candidate:842163049 1 udp 2122260223 192.168.1.2 54321 typ host generation 0 ufrag EsAw"
+                .parse()
+                .unwrap();
+
+        assert_eq!(candidate.foundation, "842163049");
+        assert_eq!(candidate.component, 1);
+        assert_eq!(candidate.protocol, Protocol::Udp);
+        assert_eq!(candidate.priority, 2122260223);
+        assert_eq!(candidate.ip(), Some("192.168.1.2".parse().unwrap()));
+        assert_eq!(candidate.port, 54321);
+        assert_eq!(candidate.kind, CandidateType::Host);
+        assert_eq!(candidate.related_address, None);
+        assert_eq!(candidate.related_port, None);
+        assert_eq!(candidate.extension("generation"), Some("0"));
+        assert_eq!(candidate.extension("ufrag"), Some("EsAw"));
+        assert_eq!(candidate.relay_protocol(), None);
+
+        let candidate: Candidate =
+            "candidate:1 1 TCP 1518280447 4f3a1c2e-0d5b-4e8a-9c7f-2b6d8e1a3f90.local 9 typ host tcptype active"
+                .parse()
+                .unwrap();
+
+        assert_eq!(candidate.protocol, Protocol::Tcp);
+        assert_eq!(candidate.ip(), None);
+        assert_eq!(candidate.extension("tcptype"), Some("active"));
+        assert_eq!(candidate.extension("generation"), None);
+    }
+
+    #[test]
+    fn reflexive_and_relay_candidates() {
+        let candidate: Candidate =
+            "candidate:3 1 udp 1686052607 203.0.113.7 61000 typ srflx raddr 192.168.1.2 rport 54321 generation 0"
+                .parse()
+                .unwrap();
+
+        assert_eq!(candidate.kind, CandidateType::Srflx);
+        assert_eq!(candidate.related_address.as_deref(), Some("192.168.1.2"));
+        assert_eq!(candidate.related_port, Some(54321));
+        assert_eq!(candidate.extensions, vec![("generation".to_string(), "0".to_string())]);
+        assert_eq!(candidate.relay_protocol(), None);
+
+        for (priority, protocol) in [
+            (41885439, Some(RelayProtocol::Udp)),
+            (25108223, Some(RelayProtocol::Tcp)),
+            (8331007, Some(RelayProtocol::Tls)),
+            (2122260223, None),
+        ] {
+            let candidate: Candidate = format!(
+                "candidate:4 1 udp {} 198.51.100.9 3478 typ relay raddr 203.0.113.7 rport 61000",
+                priority
+            )
+            .parse()
+            .unwrap();
+
+            assert_eq!(candidate.kind, CandidateType::Relay);
+            assert_eq!(candidate.relay_protocol(), protocol);
+        }
+    }
+
+    #[test]
+    fn invalid_candidates() {
+        for candidate in [
+            "",
+            "candidate:1 1 udp 2122260223 192.168.1.2 54321 host",
+            "candidate:1 1 udp 2122260223 192.168.1.2 54321",
+            "candidate:1 1 udp 2122260223 192.168.1.2 54321 typ",
+            "candidate:1 1 udp 2122260223 192.168.1.2 54321 typ local",
+            "candidate:1 1 sctp 2122260223 192.168.1.2 54321 typ host",
+            "candidate:1 x udp 2122260223 192.168.1.2 54321 typ host",
+            "candidate:1 1 udp 2122260223 192.168.1.2 65536 typ host",
+            "candidate:1 1 udp 1686052607 203.0.113.7 61000 typ srflx rport x",
+            "candidate:1 1 udp 2122260223 192.168.1.2 54321 typ host generation",
+        ] {
+            assert!(matches!(candidate.parse::<Candidate>(), Err(Error::InvalidCandidate(_))), "{}", candidate);
+        }
+
+        match "candidate:1 1 udp 2122260223 192.168.1.2 54321 host".parse::<Candidate>() {
+            Err(Error::InvalidCandidate(reason)) => assert_eq!(reason, "candidate is missing the typ keyword"),
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn candidates_round_trip() {
+        for candidate in [
+            "candidate:842163049 1 udp 2122260223 192.168.1.2 54321 typ host generation 0 ufrag EsAw",
+            "candidate:1 2 tcp 1518280447 192.168.1.2 9 typ host tcptype passive",
+            "candidate:3 1 udp 1686052607 203.0.113.7 61000 typ srflx raddr 192.168.1.2 rport 54321",
+            "candidate:4 1 udp 41885439 2001:db8::1 3478 typ relay raddr 203.0.113.7 rport 61000 generation 0",
+        ] {
+            let parsed: Candidate = candidate.parse().unwrap();
+            assert_eq!(parsed.to_string(), candidate);
+            assert_eq!(parsed.to_string().parse::<Candidate>().unwrap(), parsed);
+        }
+
+        // the protocol is written in lowercase, whatever case it was read in.
+        let parsed: Candidate = "a=candidate:1 1 UDP 2122260223 192.168.1.2 54321 typ host".parse().unwrap();
+        assert_eq!(parsed.to_string(), "candidate:1 1 udp 2122260223 192.168.1.2 54321 typ host");
+    }
+
+    #[test]
+    fn ice_server_urls() {
+        let url: IceServerUrl = "stun:stun.example.com".parse().unwrap();
+        assert_eq!(url.scheme, IceServerScheme::Stun);
+        assert_eq!(url.host, "stun.example.com");
+        assert_eq!(url.port, None);
+        assert_eq!(url.port_or_default(), 3478);
+        assert_eq!(url.relay_protocol(), None);
+
+        let url: IceServerUrl = "turn:192.0.2.1:3479?transport=tcp".parse().unwrap();
+        assert_eq!(url.scheme, IceServerScheme::Turn);
+        assert_eq!(url.host, "192.0.2.1");
+        assert_eq!(url.port, Some(3479));
+        assert_eq!(url.transport, Some(Protocol::Tcp));
+        assert_eq!(url.relay_protocol(), Some(RelayProtocol::Tcp));
+
+        let url: IceServerUrl = "turns:[2001:db8::1]:443".parse().unwrap();
+        assert_eq!(url.scheme, IceServerScheme::Turns);
+        assert_eq!(url.host, "2001:db8::1");
+        assert_eq!(url.port, Some(443));
+        assert_eq!(url.relay_protocol(), Some(RelayProtocol::Tls));
+
+        let url: IceServerUrl = "turns:turn.example.com".parse().unwrap();
+        assert_eq!(url.port_or_default(), 5349);
+
+        // libwebrtc relays over UDP when a turn url has no transport.
+        let url: IceServerUrl = "turn:[2001:db8::1]".parse().unwrap();
+        assert_eq!(url.port, None);
+        assert_eq!(url.relay_protocol(), Some(RelayProtocol::Udp));
+
+        for url in [
+            "stun.example.com",
+            "http:stun.example.com",
+            "stun:",
+            "stun:stun.example.com:0",
+            "stun:stun.example.com:65536",
+            "stun:stun.example.com:port",
+            "stun:user@stun.example.com",
+            "stun:stun.example.com?transport=udp",
+            "turn:[2001:db8::1:3478",
+            "turn:[2001:db8::1]3478",
+            "turn:turn.example.com?transport=sctp",
+            "turn:turn.example.com?ttl=1",
+        ] {
+            assert!(matches!(url.parse::<IceServerUrl>(), Err(Error::InvalidUrl(_))), "{}", url);
+        }
+    }
+
+    #[test]
+    fn ice_server_urls_round_trip() {
+        for url in [
+            "stun:stun.example.com",
+            "stun:stun.example.com:19302",
+            "turn:192.0.2.1:3479?transport=tcp",
+            "turn:turn.example.com?transport=udp",
+            "turns:[2001:db8::1]:443",
+        ] {
+            let parsed: IceServerUrl = url.parse().unwrap();
+            assert_eq!(parsed.to_string(), url);
+        }
+
+        for (protocol, url) in [
+            (RelayProtocol::Udp, "turn:turn.example.com:3478?transport=udp"),
+            (RelayProtocol::Tcp, "turn:turn.example.com:3478?transport=tcp"),
+            (RelayProtocol::Tls, "turns:turn.example.com:3478"),
+        ] {
+            let built = IceServerUrl::turn("turn.example.com", Some(3478), protocol);
+            assert_eq!(built.to_string(), url);
+            assert_eq!(built.relay_protocol(), Some(protocol));
+            assert_eq!(url.parse::<IceServerUrl>().unwrap(), built);
+        }
+    }
+}
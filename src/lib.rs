@@ -0,0 +1,35 @@
+mod diagnostics;
+mod error;
+mod ffi;
+mod version;
+pub mod congestion;
+#[cfg(feature = "e2ee")]
+pub mod e2ee;
+pub mod field_trials;
+pub mod framing;
+pub mod governor;
+pub mod http;
+pub mod ice;
+pub mod logging;
+pub mod manager;
+pub mod media;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod peer;
+pub mod policy;
+pub mod probe;
+pub mod quality;
+pub mod recording;
+pub mod resilience;
+pub mod sdp;
+pub mod stats;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "transfer")]
+pub mod transfer;
+pub mod turn;
+pub mod typed;
+
+pub use error::{Error, Result};
+pub use ffi::*;
+pub use version::{version, Version};
@@ -0,0 +1,38 @@
+use crate::ffi::raw;
+use crate::LogSeverity;
+
+use libc::*;
+use std::ffi::CStr;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use tracing::Level;
+
+// the messages end with a new line, and a panicking subscriber
+// can not unwind into the native side, so it only loses the message.
+extern "C" fn on_log(severity: LogSeverity, message: *const c_char, _ctx: *mut c_void) {
+    let message = unsafe { CStr::from_ptr(message) }.to_string_lossy();
+    let message = message.trim_end();
+    let _ = catch_unwind(AssertUnwindSafe(|| match severity {
+        LogSeverity::Verbose => tracing::event!(target: "webrtc::native", Level::DEBUG, "{}", message),
+        LogSeverity::Info => tracing::event!(target: "webrtc::native", Level::INFO, "{}", message),
+        LogSeverity::Warning => tracing::event!(target: "webrtc::native", Level::WARN, "{}", message),
+        LogSeverity::Error | LogSeverity::None => {
+            tracing::event!(target: "webrtc::native", Level::ERROR, "{}", message)
+        }
+    }));
+}
+
+/// Routes the messages of libwebrtc at or above the severity through tracing,
+/// instead of printing them to stderr.
+///
+/// The messages are events of the `webrtc::native` target, emitted on the thread logging
+/// them, so they are in the span of a connection when logged by a call made in it, see
+/// `RTCPeerConnection::span`. The verbose messages are debug events.
+pub fn init_tracing(severity: LogSeverity) {
+    unsafe { raw::rtc_set_log_handler(severity, std::ptr::null_mut(), on_log) }
+}
+
+/// Changes the minimum severity of the messages logged by libwebrtc,
+/// whether they are routed through tracing or printed to stderr.
+pub fn set_log_severity(severity: LogSeverity) {
+    unsafe { raw::rtc_set_log_severity(severity) }
+}
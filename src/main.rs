@@ -1,6 +1,4 @@
-mod ffi;
-
-use ffi::*;
+use webrtc::*;
 
 #[tokio::main]
 async fn main() {
@@ -0,0 +1,141 @@
+use crate::ffi::observer::Subscribers;
+use crate::{PeerEvent, RTCConfiguration, RTCPeerConnection, Result, WebRtcRuntime};
+
+use futures::future::join_all;
+use futures::{Stream, StreamExt};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio::task::JoinHandle;
+
+struct Managed {
+    peer: RTCPeerConnection,
+    // forwards the events of the connection to the stream of the manager.
+    forward: JoinHandle<()>,
+}
+
+/// Owns many connections created on one runtime and tracks them by id.
+///
+/// The events of every connection are multiplexed into one stream labeled with the id of
+/// the connection, so a server can drive all of its peers from a single loop. The manager
+/// is `Send + Sync` and can be shared behind an `Arc`, its methods must be called within
+/// a tokio runtime.
+pub struct PeerManager {
+    runtime: WebRtcRuntime,
+    peers: Mutex<HashMap<u64, Managed>>,
+    events: Arc<Subscribers<(u64, PeerEvent)>>,
+    next_id: AtomicU64,
+}
+
+impl PeerManager {
+    /// Creates a manager with a runtime of its own.
+    pub fn new() -> Result<Self> {
+        Ok(Self::with_runtime(WebRtcRuntime::new()?))
+    }
+
+    pub fn with_runtime(runtime: WebRtcRuntime) -> Self {
+        Self {
+            runtime,
+            peers: Mutex::new(HashMap::new()),
+            events: Arc::new(Subscribers::default()),
+            next_id: AtomicU64::new(0),
+        }
+    }
+
+    pub fn runtime(&self) -> &WebRtcRuntime {
+        &self.runtime
+    }
+
+    /// Creates a connection on the runtime of the manager and starts tracking it.
+    /// Returns the id of the connection along with a handle to it.
+    pub fn create(&self, config: &RTCConfiguration) -> Result<(u64, RTCPeerConnection)> {
+        let peer = self.runtime.create_peer_connection(config)?;
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+
+        // the stream only ends once the connection is dropped,
+        // so the task is aborted when the connection is removed.
+        let mut events = peer.events();
+        let subscribers = self.events.clone();
+        let forward = tokio::spawn(async move {
+            while let Some(event) = events.next().await {
+                subscribers.emit((id, event));
+            }
+        });
+
+        self.peers().insert(id, Managed {
+            peer: peer.clone(),
+            forward,
+        });
+
+        Ok((id, peer))
+    }
+
+    pub fn get(&self, id: u64) -> Option<RTCPeerConnection> {
+        self.peers().get(&id).map(|managed| managed.peer.clone())
+    }
+
+    /// Stops tracking a connection, its events are no longer part of the stream of the
+    /// manager, and the connection is closed once the returned handle is dropped.
+    pub fn remove(&self, id: u64) -> Option<RTCPeerConnection> {
+        self.peers().remove(&id).map(|managed| {
+            managed.forward.abort();
+            managed.peer
+        })
+    }
+
+    pub fn ids(&self) -> Vec<u64> {
+        self.peers().keys().copied().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.peers().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.peers().is_empty()
+    }
+
+    /// The events of every tracked connection, labeled with the id of the connection.
+    pub fn events(&self) -> impl Stream<Item = (u64, PeerEvent)> {
+        self.events.subscribe()
+    }
+
+    /// Removes a connection and closes it gracefully, see `RTCPeerConnection::close`.
+    /// Returns false when no connection has this id.
+    pub async fn close(&self, id: u64) -> Result<bool> {
+        match self.remove(id) {
+            Some(peer) => peer.close().await.map(|_| true),
+            None => Ok(false),
+        }
+    }
+
+    /// Removes every connection and closes them concurrently, all of them are closed
+    /// even when some fail, and the first failure is returned.
+    pub async fn close_all(&self) -> Result<()> {
+        let peers = self
+            .peers()
+            .drain()
+            .map(|(_, managed)| {
+                managed.forward.abort();
+                managed.peer
+            })
+            .collect::<Vec<_>>();
+
+        join_all(peers.into_iter().map(|peer| peer.close()))
+            .await
+            .into_iter()
+            .collect()
+    }
+
+    fn peers(&self) -> MutexGuard<'_, HashMap<u64, Managed>> {
+        self.peers.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for PeerManager {
+    fn drop(&mut self) {
+        for managed in self.peers().values() {
+            managed.forward.abort();
+        }
+    }
+}
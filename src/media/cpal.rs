@@ -0,0 +1,83 @@
+use super::playout::AudioPlayout;
+use crate::{Error, Result};
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SizedSample};
+use std::sync::Arc;
+
+fn device_error(e: impl std::fmt::Display) -> Error {
+    Error::AudioDevice(e.to_string())
+}
+
+/// Plays a playout buffer on an output device, until it is dropped.
+///
+/// ```no_run
+/// use webrtc::media::cpal::Speaker;
+/// use webrtc::media::playout::AudioPlayout;
+///
+/// # fn example(track: &webrtc::MediaStreamTrack) -> webrtc::Result<()> {
+/// let playout = AudioPlayout::attach(track, Default::default()).unwrap();
+/// let _speaker = Speaker::play(playout.into())?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Speaker {
+    // the stream is not Send on every host, so the speaker stays on the thread which opened it.
+    _stream: cpal::Stream,
+    playout: Arc<AudioPlayout>,
+}
+
+impl Speaker {
+    /// Plays on the default output device of the default host.
+    pub fn play(playout: Arc<AudioPlayout>) -> Result<Self> {
+        let device = cpal::default_host()
+            .default_output_device()
+            .ok_or_else(|| Error::AudioDevice("there is no output device".to_string()))?;
+        Self::play_on(&device, playout)
+    }
+
+    /// Plays on a device in its default configuration.
+    pub fn play_on(device: &cpal::Device, playout: Arc<AudioPlayout>) -> Result<Self> {
+        let supported = device.default_output_config().map_err(device_error)?;
+        let config = supported.config();
+        let stream = match supported.sample_format() {
+            cpal::SampleFormat::F32 => build::<f32>(device, &config, playout.clone()),
+            cpal::SampleFormat::I16 => build::<i16>(device, &config, playout.clone()),
+            cpal::SampleFormat::U16 => build::<u16>(device, &config, playout.clone()),
+            cpal::SampleFormat::I32 => build::<i32>(device, &config, playout.clone()),
+            format => Err(Error::AudioDevice(format!("{} samples are not supported", format))),
+        }?;
+
+        stream.play().map_err(device_error)?;
+        Ok(Self {
+            _stream: stream,
+            playout,
+        })
+    }
+
+    pub fn playout(&self) -> &Arc<AudioPlayout> {
+        &self.playout
+    }
+}
+
+fn build<T>(device: &cpal::Device, config: &cpal::StreamConfig, playout: Arc<AudioPlayout>) -> Result<cpal::Stream>
+where
+    T: SizedSample + FromSample<f32>,
+{
+    let (sample_rate, channels) = (config.sample_rate.0, config.channels);
+    let mut pulled = Vec::new();
+    device
+        .build_output_stream(
+            config,
+            move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+                pulled.resize(data.len(), 0.0);
+                playout.pull(&mut pulled, sample_rate, channels);
+                for (sample, value) in data.iter_mut().zip(&pulled) {
+                    *sample = T::from_sample(*value);
+                }
+            },
+            |e| tracing::warn!("audio output error: {}", e),
+            None,
+        )
+        .map_err(device_error)
+}
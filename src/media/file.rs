@@ -0,0 +1,461 @@
+use crate::{AudioFrame, Error, MediaStreamTrack, Result, VideoFrame};
+
+use ffmpeg_next as ffmpeg;
+use ffmpeg::format::{sample, Pixel, Sample as SampleFormat};
+use ffmpeg::software::{resampling, scaling};
+use ffmpeg::{codec, decoder, frame, media, rescale, ChannelLayout, Rescale};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::sync_channel;
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// The audio is resampled to the rate of Opus, whatever the rate of the file.
+const SAMPLE_RATE: u32 = 48000;
+
+/// The samples of 10ms of audio, the size of the frames of an audio track.
+const FRAMES_PER_CHUNK: usize = SAMPLE_RATE as usize / 100;
+
+fn decoding(e: ffmpeg::Error) -> Error {
+    Error::Decoding(e.to_string())
+}
+
+/// The options of a file source.
+#[derive(Clone, Debug, Default)]
+pub struct FileSourceOptions {
+    /// Plays the file again from the start once it has ended, until the source is stopped.
+    pub looping: bool,
+}
+
+struct VideoStream {
+    index: usize,
+    time_base: ffmpeg::Rational,
+    decoder: decoder::Video,
+    scaler: Option<scaling::Context>,
+    track: Arc<MediaStreamTrack>,
+}
+
+impl VideoStream {
+    /// Converts a decoded frame to I420, the scaler follows the format of the frames.
+    fn convert(&mut self, decoded: &frame::Video) -> Result<VideoFrame> {
+        let (width, height) = (decoded.width(), decoded.height());
+        let outdated = self.scaler.as_ref().map(|s| {
+            let input = s.input();
+            input.format != decoded.format() || input.width != width || input.height != height
+        });
+
+        if outdated.unwrap_or(true) {
+            self.scaler = Some(
+                scaling::Context::get(
+                    decoded.format(),
+                    width,
+                    height,
+                    Pixel::YUV420P,
+                    width,
+                    height,
+                    scaling::Flags::BILINEAR,
+                )
+                .map_err(decoding)?,
+            );
+        }
+
+        let mut i420 = frame::Video::empty();
+        if let Some(scaler) = self.scaler.as_mut() {
+            scaler.run(decoded, &mut i420).map_err(decoding)?;
+        }
+
+        let mut data = Vec::new();
+        for plane in 0..3 {
+            let (stride, width) = (i420.stride(plane), i420.plane_width(plane) as usize);
+            for row in i420.data(plane).chunks(stride).take(i420.plane_height(plane) as usize) {
+                data.extend_from_slice(&row[..width]);
+            }
+        }
+
+        Ok(VideoFrame {
+            timestamp_us: 0,
+            rotation: 0,
+            width,
+            height,
+            data,
+        })
+    }
+}
+
+struct AudioStream {
+    index: usize,
+    time_base: ffmpeg::Rational,
+    decoder: decoder::Audio,
+    resampler: resampling::Context,
+    channels: u32,
+    /// The samples left over by the last 10ms chunk.
+    pending: Vec<i16>,
+    /// The time of the first pending sample, relative to the start of the file.
+    pending_us: Option<i64>,
+    track: Arc<MediaStreamTrack>,
+}
+
+impl AudioStream {
+    /// Resamples a decoded frame to interleaved 16 bits samples at 48kHz,
+    /// and splits them into frames of 10ms with their presentation time.
+    fn convert(&mut self, decoded: &frame::Audio, pts_us: i64) -> Result<Vec<(i64, AudioFrame)>> {
+        let mut resampled = frame::Audio::empty();
+        self.resampler.run(decoded, &mut resampled).map_err(decoding)?;
+
+        let len = resampled.samples() * self.channels as usize * 2;
+        self.pending_us.get_or_insert(pts_us);
+        self.pending.extend(
+            resampled.data(0)[..len]
+                .chunks_exact(2)
+                .map(|sample| i16::from_ne_bytes([sample[0], sample[1]])),
+        );
+
+        let chunk = FRAMES_PER_CHUNK * self.channels as usize;
+        let mut frames = Vec::new();
+        while self.pending.len() >= chunk {
+            let start_us = self.pending_us.unwrap_or(pts_us);
+            self.pending_us = Some(start_us + 10_000);
+            frames.push((start_us, AudioFrame {
+                data: self.pending.drain(..chunk).collect(),
+                sample_rate: SAMPLE_RATE,
+                channels: self.channels,
+                timestamp_us: 0,
+            }));
+        }
+
+        Ok(frames)
+    }
+}
+
+/// Decodes the file and writes its frames to the tracks at the pace of their timestamps.
+struct Player {
+    input: ffmpeg::format::context::Input,
+    video: Option<VideoStream>,
+    audio: Option<AudioStream>,
+    options: FileSourceOptions,
+    stopped: Arc<AtomicBool>,
+    start: Instant,
+    /// The timestamp of the first frame of the file, which is played at `start`.
+    base_us: Option<i64>,
+    /// The duration of the loops already played.
+    offset_us: i64,
+    /// The end of the last frame written, the duration of a loop.
+    end_us: i64,
+}
+
+impl Player {
+    fn open(
+        path: &Path,
+        video: Option<Arc<MediaStreamTrack>>,
+        audio: Option<Arc<MediaStreamTrack>>,
+        options: FileSourceOptions,
+        stopped: Arc<AtomicBool>,
+    ) -> Result<Self> {
+        ffmpeg::init().map_err(decoding)?;
+        let input = ffmpeg::format::input(path).map_err(decoding)?;
+
+        let video = match (video, input.streams().best(media::Type::Video)) {
+            (Some(track), Some(stream)) => {
+                let context = codec::Context::from_parameters(stream.parameters()).map_err(decoding)?;
+                Some(VideoStream {
+                    decoder: context.decoder().video().map_err(decoding)?,
+                    time_base: stream.time_base(),
+                    index: stream.index(),
+                    scaler: None,
+                    track,
+                })
+            }
+            _ => None,
+        };
+
+        let audio = match (audio, input.streams().best(media::Type::Audio)) {
+            (Some(track), Some(stream)) => {
+                let context = codec::Context::from_parameters(stream.parameters()).map_err(decoding)?;
+                let decoder = context.decoder().audio().map_err(decoding)?;
+
+                // the tracks are mono or stereo, a multichannel file is downmixed.
+                let channels = decoder.channels().clamp(1, 2) as u32;
+                let resampler = resampling::Context::get(
+                    decoder.format(),
+                    decoder.channel_layout(),
+                    decoder.rate(),
+                    SampleFormat::I16(sample::Type::Packed),
+                    ChannelLayout::default(channels as i32),
+                    SAMPLE_RATE,
+                )
+                .map_err(decoding)?;
+
+                Some(AudioStream {
+                    time_base: stream.time_base(),
+                    index: stream.index(),
+                    pending: Vec::new(),
+                    pending_us: None,
+                    resampler,
+                    channels,
+                    decoder,
+                    track,
+                })
+            }
+            _ => None,
+        };
+
+        if video.is_none() && audio.is_none() {
+            return Err(Error::InvalidConfiguration(format!(
+                "{} has no stream for the given tracks",
+                path.display()
+            )));
+        }
+
+        Ok(Self {
+            start: Instant::now(),
+            base_us: None,
+            offset_us: 0,
+            end_us: 0,
+            options,
+            stopped,
+            input,
+            video,
+            audio,
+        })
+    }
+
+    fn run(mut self) -> Result<()> {
+        self.start = Instant::now();
+        loop {
+            let mut packets = Vec::new();
+            for (stream, packet) in self.input.packets() {
+                packets.push((stream.index(), packet));
+                // the packets are read by batches, so the iterator borrowing the input
+                // does not outlive the decoding of the batch.
+                if packets.len() == 16 {
+                    break;
+                }
+            }
+
+            if packets.is_empty() {
+                self.drain()?;
+                if !self.options.looping || self.stopped.load(Ordering::Relaxed) {
+                    return Ok(());
+                }
+
+                self.rewind()?;
+                continue;
+            }
+
+            for (index, packet) in packets {
+                if self.stopped.load(Ordering::Relaxed) {
+                    return Ok(());
+                }
+
+                if let Some(video) = self.video.as_mut().filter(|v| v.index == index) {
+                    video.decoder.send_packet(&packet).map_err(decoding)?;
+                    self.receive_video()?;
+                } else if let Some(audio) = self.audio.as_mut().filter(|a| a.index == index) {
+                    audio.decoder.send_packet(&packet).map_err(decoding)?;
+                    self.receive_audio()?;
+                }
+            }
+        }
+    }
+
+    /// Writes the frames still buffered by the decoders at the end of the file.
+    fn drain(&mut self) -> Result<()> {
+        if let Some(video) = self.video.as_mut() {
+            video.decoder.send_eof().map_err(decoding)?;
+            self.receive_video()?;
+        }
+
+        if let Some(audio) = self.audio.as_mut() {
+            audio.decoder.send_eof().map_err(decoding)?;
+            self.receive_audio()?;
+        }
+
+        Ok(())
+    }
+
+    /// Seeks back to the start of the file, the timestamps of the next loop
+    /// follow the end of this one.
+    fn rewind(&mut self) -> Result<()> {
+        self.input.seek(0, ..).map_err(decoding)?;
+        if let Some(video) = self.video.as_mut() {
+            video.decoder.flush();
+        }
+
+        if let Some(audio) = self.audio.as_mut() {
+            audio.decoder.flush();
+            audio.pending.clear();
+            audio.pending_us = None;
+        }
+
+        self.offset_us = self.end_us;
+        Ok(())
+    }
+
+    fn receive_video(&mut self) -> Result<()> {
+        let mut decoded = frame::Video::empty();
+        loop {
+            let video = match self.video.as_mut() {
+                Some(video) => video,
+                None => return Ok(()),
+            };
+
+            if video.decoder.receive_frame(&mut decoded).is_err() {
+                return Ok(());
+            }
+
+            let pts_us = decoded
+                .timestamp()
+                .map(|pts| pts.rescale(video.time_base, rescale::TIME_BASE))
+                .unwrap_or(0);
+            let frame = video.convert(&decoded)?;
+            let track = video.track.clone();
+            if self.wait(pts_us) {
+                track.write_video_frame(&frame)?;
+            }
+        }
+    }
+
+    fn receive_audio(&mut self) -> Result<()> {
+        let mut decoded = frame::Audio::empty();
+        loop {
+            let audio = match self.audio.as_mut() {
+                Some(audio) => audio,
+                None => return Ok(()),
+            };
+
+            if audio.decoder.receive_frame(&mut decoded).is_err() {
+                return Ok(());
+            }
+
+            let pts_us = decoded
+                .timestamp()
+                .map(|pts| pts.rescale(audio.time_base, rescale::TIME_BASE))
+                .unwrap_or(0);
+            let frames = audio.convert(&decoded, pts_us)?;
+            let track = audio.track.clone();
+            for (pts_us, frame) in frames {
+                if self.wait(pts_us) {
+                    track.write_audio_frame(&frame)?;
+                }
+            }
+        }
+    }
+
+    /// Sleeps until the time of a frame, returns false if the source has been stopped.
+    fn wait(&mut self, pts_us: i64) -> bool {
+        let base_us = *self.base_us.get_or_insert(pts_us);
+        let elapsed_us = (pts_us - base_us).max(0);
+        self.end_us = self.end_us.max(self.offset_us + elapsed_us);
+
+        let deadline = self.start + Duration::from_micros((self.offset_us + elapsed_us) as u64);
+        while !self.stopped.load(Ordering::Relaxed) {
+            match deadline.checked_duration_since(Instant::now()) {
+                Some(timeout) if !timeout.is_zero() => thread::park_timeout(timeout),
+                _ => return true,
+            }
+        }
+
+        false
+    }
+}
+
+/// Plays a local media file into tracks at real-time pace.
+///
+/// The best video and audio streams of the file are decoded with ffmpeg on a thread of
+/// the source, the video is converted to I420 and the audio resampled to 48kHz, and
+/// every frame is written to its track when its timestamp is due, so the remote peer
+/// receives the file as if it was captured live. This is meant for load testing and
+/// demo bots, any container and codec ffmpeg can decode is supported.
+///
+/// ```no_run
+/// use webrtc::media::file::{FileSource, FileSourceOptions};
+/// use webrtc::WebRtcRuntime;
+/// use std::sync::Arc;
+///
+/// # fn play(runtime: &WebRtcRuntime, peer: &webrtc::RTCPeerConnection) -> webrtc::Result<()> {
+/// let video = Arc::new(runtime.create_video_track("video")?);
+/// let audio = Arc::new(runtime.create_audio_track("audio")?);
+/// peer.add_track(&video, "bot")?;
+/// peer.add_track(&audio, "bot")?;
+///
+/// let options = FileSourceOptions { looping: true };
+/// let source = FileSource::open("demo.mp4", Some(video), Some(audio), options)?;
+/// // ...
+/// source.stop()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct FileSource {
+    stopped: Arc<AtomicBool>,
+    thread: Option<JoinHandle<Result<()>>>,
+}
+
+impl FileSource {
+    /// Opens the file and starts playing it, the streams of the file without a track are
+    /// skipped. Fails if the file can not be opened, or has no stream for the tracks.
+    pub fn open(
+        path: impl AsRef<Path>,
+        video: Option<Arc<MediaStreamTrack>>,
+        audio: Option<Arc<MediaStreamTrack>>,
+        options: FileSourceOptions,
+    ) -> Result<Self> {
+        let path: PathBuf = path.as_ref().to_path_buf();
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        // the scalers of ffmpeg can not be sent to another thread,
+        // so the file is opened on the thread playing it.
+        let (sender, receiver) = sync_channel(1);
+        let thread_stopped = stopped.clone();
+        let thread = thread::spawn(move || {
+            match Player::open(&path, video, audio, options, thread_stopped) {
+                Ok(player) => {
+                    let _ = sender.send(Ok(()));
+                    player.run()
+                }
+                Err(e) => {
+                    let _ = sender.send(Err(e));
+                    Ok(())
+                }
+            }
+        });
+
+        receiver.recv().unwrap_or_else(|_| {
+            Err(Error::Decoding("the playing thread panicked".to_string()))
+        })?;
+
+        Ok(Self {
+            thread: Some(thread),
+            stopped,
+        })
+    }
+
+    /// True once the whole file has been played, or playing it has failed.
+    pub fn is_finished(&self) -> bool {
+        self.thread.as_ref().map(|t| t.is_finished()).unwrap_or(true)
+    }
+
+    /// Stops playing the file, returns the first error met while playing it.
+    pub fn stop(mut self) -> Result<()> {
+        self.finish()
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.stopped.store(true, Ordering::Relaxed);
+        match self.thread.take() {
+            Some(thread) => {
+                thread.thread().unpark();
+                thread.join().unwrap_or_else(|_| {
+                    Err(Error::Decoding("the playing thread panicked".to_string()))
+                })
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for FileSource {
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
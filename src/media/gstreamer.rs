@@ -0,0 +1,284 @@
+use crate::{AudioFrame, Error, MediaSink, MediaStreamTrack, Result, VideoFrame};
+
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+use gstreamer_audio as gst_audio;
+use gstreamer_video as gst_video;
+use gst_video::prelude::*;
+use std::sync::Arc;
+
+fn conversion(e: impl std::fmt::Display) -> Error {
+    Error::Conversion(e.to_string())
+}
+
+/// The caps of the video frames, raw I420 of any size.
+pub fn video_caps() -> gst::Caps {
+    gst_video::VideoCapsBuilder::new()
+        .format(gst_video::VideoFormat::I420)
+        .build()
+}
+
+/// The caps of the audio frames, interleaved 16 bits samples of any rate, in mono or stereo.
+pub fn audio_caps() -> gst::Caps {
+    gst_audio::AudioCapsBuilder::new_interleaved()
+        .format(gst_audio::AUDIO_FORMAT_S16)
+        .channels_range(1..=2)
+        .build()
+}
+
+/// Copies a frame into a buffer of raw I420 laid out as described by `info`,
+/// which is the default layout for the size of the frame when `None`.
+pub fn video_frame_to_buffer(
+    frame: &VideoFrame,
+    info: Option<&gst_video::VideoInfo>,
+) -> Result<gst::Buffer> {
+    let default_info;
+    let info = match info {
+        Some(info) => info,
+        None => {
+            default_info = gst_video::VideoInfo::builder(
+                gst_video::VideoFormat::I420,
+                frame.width,
+                frame.height,
+            )
+            .build()
+            .map_err(conversion)?;
+            &default_info
+        }
+    };
+
+    if info.format() != gst_video::VideoFormat::I420
+        || info.width() != frame.width
+        || info.height() != frame.height
+    {
+        return Err(Error::Conversion(format!(
+            "a frame of {}x{} can not be written as {:?}",
+            frame.width, frame.height, info
+        )));
+    }
+
+    let buffer = gst::Buffer::with_size(info.size()).map_err(conversion)?;
+    let mut mapped = gst_video::VideoFrame::from_buffer_writable(buffer, info)
+        .map_err(|_| Error::Conversion("the buffer can not be mapped".to_string()))?;
+
+    for (plane, data) in [frame.y(), frame.u(), frame.v()].into_iter().enumerate() {
+        let width = mapped.comp_width(plane as u32) as usize;
+        let stride = mapped.plane_stride()[plane] as usize;
+        let dest = mapped.plane_data_mut(plane as u32).map_err(conversion)?;
+        for (src, dest) in data.chunks(width).zip(dest.chunks_mut(stride)) {
+            dest[..width].copy_from_slice(src);
+        }
+    }
+
+    Ok(mapped.into_buffer())
+}
+
+/// Copies a sample of raw I420 into a frame, the strides of the sample are removed.
+pub fn video_frame_from_sample(sample: &gst::SampleRef) -> Result<VideoFrame> {
+    let caps = sample.caps().ok_or_else(|| conversion("the sample has no caps"))?;
+    let buffer = sample.buffer_owned().ok_or_else(|| conversion("the sample has no buffer"))?;
+    let info = gst_video::VideoInfo::from_caps(caps).map_err(conversion)?;
+    if info.format() != gst_video::VideoFormat::I420 {
+        return Err(Error::Conversion(format!("{:?} is not I420", info.format())));
+    }
+
+    let mapped = gst_video::VideoFrame::from_buffer_readable(buffer, &info)
+        .map_err(|_| Error::Conversion("the buffer can not be mapped".to_string()))?;
+
+    let mut data = Vec::with_capacity(info.size());
+    for plane in 0..3 {
+        let width = mapped.comp_width(plane) as usize;
+        let height = mapped.comp_height(plane) as usize;
+        let stride = mapped.plane_stride()[plane as usize] as usize;
+        let src = mapped.plane_data(plane).map_err(conversion)?;
+        for row in src.chunks(stride).take(height) {
+            data.extend_from_slice(&row[..width]);
+        }
+    }
+
+    Ok(VideoFrame {
+        width: info.width(),
+        height: info.height(),
+        timestamp_us: buffer_timestamp_us(mapped.buffer()),
+        rotation: 0,
+        data,
+    })
+}
+
+/// Copies the samples of a frame into a buffer of interleaved 16 bits samples.
+pub fn audio_frame_to_buffer(frame: &AudioFrame) -> gst::Buffer {
+    let bytes = frame
+        .data
+        .iter()
+        .flat_map(|sample| sample.to_ne_bytes())
+        .collect::<Vec<_>>();
+    gst::Buffer::from_mut_slice(bytes)
+}
+
+/// Copies a sample of interleaved 16 bits samples into a frame, the frame holds all
+/// the samples of the buffer, which is not necessarily 10ms of audio.
+pub fn audio_frame_from_sample(sample: &gst::SampleRef) -> Result<AudioFrame> {
+    let caps = sample.caps().ok_or_else(|| conversion("the sample has no caps"))?;
+    let buffer = sample.buffer().ok_or_else(|| conversion("the sample has no buffer"))?;
+    let info = gst_audio::AudioInfo::from_caps(caps).map_err(conversion)?;
+    if info.format() != gst_audio::AUDIO_FORMAT_S16 || info.layout() != gst_audio::AudioLayout::Interleaved {
+        return Err(Error::Conversion(format!(
+            "{:?} is not interleaved 16 bits samples",
+            info.format()
+        )));
+    }
+
+    let mapped = buffer.map_readable().map_err(conversion)?;
+    Ok(AudioFrame {
+        sample_rate: info.rate(),
+        channels: info.channels(),
+        timestamp_us: buffer_timestamp_us(buffer),
+        data: mapped
+            .chunks_exact(2)
+            .map(|sample| i16::from_ne_bytes([sample[0], sample[1]]))
+            .collect(),
+    })
+}
+
+fn buffer_timestamp_us(buffer: &gst::BufferRef) -> i64 {
+    buffer.pts().map(|pts| pts.useconds() as i64).unwrap_or(0)
+}
+
+/// Publishes the samples reaching an appsink on a local video track.
+///
+/// The caps of the appsink are restricted to `video_caps`, so a `videoconvert` is usually
+/// placed before it. The frames are written as they are received, the pipeline paces them.
+pub fn publish_video(appsink: &gst_app::AppSink, track: Arc<MediaStreamTrack>) {
+    appsink.set_caps(Some(&video_caps()));
+    appsink.set_callbacks(
+        gst_app::AppSinkCallbacks::builder()
+            .new_sample(move |appsink| {
+                let sample = appsink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+                let frame = video_frame_from_sample(&sample).map_err(|_| gst::FlowError::Error)?;
+                track.write_video_frame(&frame).map_err(|_| gst::FlowError::Error)?;
+                Ok(gst::FlowSuccess::Ok)
+            })
+            .build(),
+    );
+}
+
+/// Publishes the samples reaching an appsink on a local audio track.
+///
+/// The caps of the appsink are restricted to `audio_caps`, so an `audioconvert` is usually
+/// placed before it. The samples are split into the 10ms frames expected by the track.
+pub fn publish_audio(appsink: &gst_app::AppSink, track: Arc<MediaStreamTrack>) {
+    let mut pending: Option<AudioFrame> = None;
+    appsink.set_caps(Some(&audio_caps()));
+    appsink.set_callbacks(
+        gst_app::AppSinkCallbacks::builder()
+            .new_sample(move |appsink| {
+                let sample = appsink.pull_sample().map_err(|_| gst::FlowError::Eos)?;
+                let frame = audio_frame_from_sample(&sample).map_err(|_| gst::FlowError::Error)?;
+
+                // a change of format drops the samples left over from the previous one.
+                let same_format = |p: &&mut AudioFrame| {
+                    p.sample_rate == frame.sample_rate && p.channels == frame.channels
+                };
+                if let Some(pending) = pending.as_mut().filter(same_format) {
+                    pending.data.extend(frame.data);
+                } else {
+                    pending = Some(frame);
+                }
+
+                if let Some(pending) = pending.as_mut() {
+                    let chunk = (pending.sample_rate / 100 * pending.channels) as usize;
+                    while chunk > 0 && pending.data.len() >= chunk {
+                        let frame = AudioFrame {
+                            data: pending.data.drain(..chunk).collect(),
+                            ..*pending
+                        };
+
+                        track.write_audio_frame(&frame).map_err(|_| gst::FlowError::Error)?;
+                    }
+                }
+
+                Ok(gst::FlowSuccess::Ok)
+            })
+            .build(),
+    );
+}
+
+/// Pushes the frames of a received track into an appsrc, until it is dropped.
+///
+/// The appsrc is configured as a live source timestamping the buffers as they are
+/// pushed, its caps follow the size of the video frames and the format of the audio.
+pub struct AppSrcBridge {
+    _sink: MediaSink,
+}
+
+impl AppSrcBridge {
+    /// Attaches an appsrc to an audio or video track.
+    pub fn new(track: &MediaStreamTrack, appsrc: gst_app::AppSrc) -> Result<Self> {
+        appsrc.set_format(gst::Format::Time);
+        appsrc.set_is_live(true);
+        appsrc.set_do_timestamp(true);
+
+        let video_src = appsrc.clone();
+        let mut video_info: Option<gst_video::VideoInfo> = None;
+        let sink = track.add_video_sink(move |frame| {
+            let outdated = video_info
+                .as_ref()
+                .map(|info| info.width() != frame.width || info.height() != frame.height)
+                .unwrap_or(true);
+            if outdated {
+                let info = gst_video::VideoInfo::builder(
+                    gst_video::VideoFormat::I420,
+                    frame.width,
+                    frame.height,
+                )
+                .build();
+                if let Ok(caps) = info.as_ref().map(|info| info.to_caps()) {
+                    video_src.set_caps(caps.ok().as_ref());
+                }
+
+                video_info = info.ok();
+            }
+
+            if let Ok(buffer) = video_frame_to_buffer(&frame, video_info.as_ref()) {
+                let _ = video_src.push_buffer(buffer);
+            }
+        });
+
+        let sink = match sink {
+            Some(sink) => sink,
+            None => {
+                let mut audio_info: Option<gst_audio::AudioInfo> = None;
+                track
+                    .add_audio_sink(move |frame| {
+                        let outdated = audio_info
+                            .as_ref()
+                            .map(|info| info.rate() != frame.sample_rate || info.channels() != frame.channels)
+                            .unwrap_or(true);
+                        if outdated {
+                            let info = gst_audio::AudioInfo::builder(
+                                gst_audio::AUDIO_FORMAT_S16,
+                                frame.sample_rate,
+                                frame.channels,
+                            )
+                            .build();
+                            if let Ok(caps) = info.as_ref().map(|info| info.to_caps()) {
+                                appsrc.set_caps(caps.ok().as_ref());
+                            }
+
+                            audio_info = info.ok();
+                        }
+
+                        let _ = appsrc.push_buffer(audio_frame_to_buffer(&frame));
+                    })
+                    .ok_or_else(|| {
+                        Error::InvalidConfiguration(format!(
+                            "the track {} is neither an audio nor a video track",
+                            track.id()
+                        ))
+                    })?
+            }
+        };
+
+        Ok(Self { _sink: sink })
+    }
+}
@@ -0,0 +1,13 @@
+#[cfg(feature = "media-cpal")]
+pub mod cpal;
+
+#[cfg(feature = "media-file")]
+pub mod file;
+
+#[cfg(feature = "media-gstreamer")]
+pub mod gstreamer;
+
+pub mod playout;
+
+#[cfg(feature = "media-wgpu")]
+pub mod wgpu;
@@ -0,0 +1,161 @@
+use crate::{AudioFrame, MediaSink, MediaStreamTrack};
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The tuning of the playout buffer.
+#[derive(Clone, Debug)]
+pub struct PlayoutConfig {
+    /// The audio buffered before the playout starts, or starts again after running dry,
+    /// which absorbs the jitter between the delivery of the frames and the device callbacks.
+    pub latency: Duration,
+    /// The most audio buffered, the oldest samples are dropped beyond it so that a stalled
+    /// device does not delay the audio forever.
+    pub max_latency: Duration,
+}
+
+impl Default for PlayoutConfig {
+    fn default() -> Self {
+        Self {
+            latency: Duration::from_millis(40),
+            max_latency: Duration::from_millis(200),
+        }
+    }
+}
+
+struct Buffer {
+    config: PlayoutConfig,
+    sample_rate: u32,
+    channels: usize,
+    samples: VecDeque<i16>,
+    /// The fractional index of the next frame to play, the resampling reads between frames.
+    position: f64,
+    starving: bool,
+}
+
+impl Buffer {
+    fn frames(&self) -> usize {
+        self.samples.len() / self.channels.max(1)
+    }
+
+    fn duration_frames(&self, duration: Duration) -> usize {
+        (duration.as_secs_f64() * self.sample_rate as f64) as usize
+    }
+
+    fn push(&mut self, frame: &AudioFrame) {
+        // a change of format drops the samples of the previous one.
+        if frame.sample_rate != self.sample_rate || frame.channels as usize != self.channels {
+            self.sample_rate = frame.sample_rate;
+            self.channels = frame.channels as usize;
+            self.samples.clear();
+            self.position = 0.0;
+            self.starving = true;
+        }
+
+        self.samples.extend(&frame.data);
+        let excess = self.frames().saturating_sub(self.duration_frames(self.config.max_latency));
+        if excess > 0 {
+            self.samples.drain(..excess * self.channels);
+            self.position = (self.position - excess as f64).max(0.0);
+        }
+    }
+
+    /// A sample of a frame of the buffer, mapped to a channel of the output.
+    fn sample(&self, frame: usize, channel: usize, channels: usize) -> f32 {
+        let base = frame * self.channels;
+        let value = if self.channels == 2 && channels == 1 {
+            (self.samples[base] as i32 + self.samples[base + 1] as i32) / 2
+        } else {
+            self.samples[base + channel.min(self.channels - 1)] as i32
+        };
+
+        value as f32 / 32768.0
+    }
+}
+
+/// Buffers the audio of a remote track for a device, and hands it over resampled to
+/// the rate and the channels of the device when the device asks for it.
+///
+/// The device pulls the audio from its own callback, such as the output stream of cpal,
+/// see the `media-cpal` feature. The audio is resampled by linear interpolation, which
+/// is good enough for speech and for the usual rates of 44.1 and 48 kHz.
+pub struct AudioPlayout {
+    buffer: Arc<Mutex<Buffer>>,
+    _sink: MediaSink,
+}
+
+impl AudioPlayout {
+    /// Attaches a sink to the track, returns None if the track is not an audio track.
+    pub fn attach(track: &MediaStreamTrack, config: PlayoutConfig) -> Option<Self> {
+        let buffer = Arc::new(Mutex::new(Buffer {
+            samples: VecDeque::new(),
+            sample_rate: 0,
+            channels: 0,
+            position: 0.0,
+            starving: true,
+            config,
+        }));
+
+        let frames = buffer.clone();
+        let sink = track.add_audio_sink(move |frame| {
+            frames.lock().unwrap().push(&frame);
+        })?;
+
+        Some(Self { _sink: sink, buffer })
+    }
+
+    /// The audio buffered and not played yet.
+    pub fn buffered(&self) -> Duration {
+        let buffer = self.buffer.lock().unwrap();
+        if buffer.sample_rate == 0 {
+            return Duration::ZERO;
+        }
+
+        let frames = (buffer.frames() as f64 - buffer.position).max(0.0);
+        Duration::from_secs_f64(frames / buffer.sample_rate as f64)
+    }
+
+    /// Fills interleaved samples between -1 and 1 at the rate and with the channels of
+    /// the device, with silence while the buffer runs dry.
+    pub fn pull(&self, out: &mut [f32], sample_rate: u32, channels: u16) {
+        let channels = channels.max(1) as usize;
+        let mut buffer = self.buffer.lock().unwrap();
+        out.fill(0.0);
+
+        if buffer.sample_rate == 0 || sample_rate == 0 {
+            return;
+        }
+
+        if buffer.starving {
+            if buffer.frames() < buffer.duration_frames(buffer.config.latency) {
+                return;
+            }
+
+            buffer.starving = false;
+        }
+
+        let step = buffer.sample_rate as f64 / sample_rate as f64;
+        for frame in out.chunks_mut(channels) {
+            let index = buffer.position as usize;
+            if index + 1 >= buffer.frames() {
+                buffer.starving = true;
+                break;
+            }
+
+            let fraction = (buffer.position - index as f64) as f32;
+            for (channel, sample) in frame.iter_mut().enumerate() {
+                let current = buffer.sample(index, channel, channels);
+                let next = buffer.sample(index + 1, channel, channels);
+                *sample = current + (next - current) * fraction;
+            }
+
+            buffer.position += step;
+        }
+
+        let played = (buffer.position as usize).min(buffer.frames());
+        let drained = played * buffer.channels;
+        buffer.samples.drain(..drained);
+        buffer.position -= played as f64;
+    }
+}
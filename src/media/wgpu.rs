@@ -0,0 +1,289 @@
+use crate::{MediaSink, MediaStreamTrack, VideoFrame};
+
+use std::borrow::Cow;
+use std::sync::{Arc, Mutex};
+
+/// The latest frame of a remote video track, for a render loop which runs at the rate of
+/// the display rather than the rate of the track.
+///
+/// The frames are only kept until they are taken, a frame replaced before the next redraw
+/// is never uploaded.
+pub struct FrameSlot {
+    frame: Arc<Mutex<Option<Arc<VideoFrame>>>>,
+    _sink: MediaSink,
+}
+
+impl FrameSlot {
+    /// Attaches a sink to the track, returns None if the track is not a video track.
+    pub fn attach(track: &MediaStreamTrack) -> Option<Self> {
+        let frame = Arc::new(Mutex::new(None));
+        let latest = frame.clone();
+        let sink = track.add_video_sink(move |frame| {
+            *latest.lock().unwrap() = Some(frame);
+        })?;
+
+        Some(Self { _sink: sink, frame })
+    }
+
+    /// The frame received since the previous call, if any.
+    pub fn take(&self) -> Option<Arc<VideoFrame>> {
+        self.frame.lock().unwrap().take()
+    }
+}
+
+struct Planes {
+    width: u32,
+    height: u32,
+    rotation: u32,
+    textures: [wgpu::Texture; 3],
+    bind_group: wgpu::BindGroup,
+}
+
+/// Renders the video frames with wgpu.
+///
+/// The Y, U and V planes of a frame are uploaded as they are into three single channel
+/// textures, and converted to RGB by the fragment shader, so the only work left to the
+/// CPU is the copy into the staging memory of the queue. The frames of libwebrtc are
+/// decoded into memory, there is no hardware surface to share with the renderer.
+///
+/// ```no_run
+/// use webrtc::media::wgpu::{FrameSlot, VideoRenderer};
+///
+/// # fn example(track: &webrtc::MediaStreamTrack, device: &wgpu::Device, queue: &wgpu::Queue, view: &wgpu::TextureView) {
+/// let slot = FrameSlot::attach(track).unwrap();
+/// let mut renderer = VideoRenderer::new(device, wgpu::TextureFormat::Bgra8Unorm);
+///
+/// // on every redraw.
+/// if let Some(frame) = slot.take() {
+///     renderer.upload(device, queue, &frame);
+/// }
+///
+/// let mut encoder = device.create_command_encoder(&Default::default());
+/// {
+///     let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+///         color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+///             view,
+///             resolve_target: None,
+///             ops: wgpu::Operations::default(),
+///         })],
+///         ..Default::default()
+///     });
+///
+///     renderer.render(&mut pass);
+/// }
+///
+/// queue.submit([encoder.finish()]);
+/// # }
+/// ```
+pub struct VideoRenderer {
+    pipeline: wgpu::RenderPipeline,
+    layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    planes: Option<Planes>,
+}
+
+impl VideoRenderer {
+    /// Creates a renderer drawing into targets of a format.
+    ///
+    /// The shader writes gamma encoded colors, a target of an sRGB format would encode
+    /// them twice, its view should use the non sRGB variant of the format.
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("webrtc video"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("wgpu.wgsl"))),
+        });
+
+        let plane = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("webrtc video"),
+            entries: &[
+                plane(0),
+                plane(1),
+                plane(2),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("webrtc video"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("webrtc video"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                compilation_options: Default::default(),
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                compilation_options: Default::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("webrtc video"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        Self {
+            planes: None,
+            pipeline,
+            layout,
+            sampler,
+        }
+    }
+
+    /// Uploads a frame, the textures are created again when the size of the frames changes,
+    /// an empty frame is ignored.
+    pub fn upload(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, frame: &VideoFrame) {
+        if frame.width == 0 || frame.height == 0 {
+            return;
+        }
+
+        let mut planes = match self.planes.take() {
+            Some(planes) if (planes.width, planes.height) == (frame.width, frame.height) => planes,
+            _ => self.create_planes(device, frame.width, frame.height),
+        };
+
+        planes.rotation = frame.rotation;
+        for (texture, data) in planes.textures.iter().zip([frame.y(), frame.u(), frame.v()]) {
+            let size = texture.size();
+            queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                data,
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(size.width),
+                    rows_per_image: Some(size.height),
+                },
+                size,
+            );
+        }
+
+        self.planes = Some(planes);
+    }
+
+    /// The size of the latest frame, None until a frame is uploaded.
+    pub fn size(&self) -> Option<(u32, u32)> {
+        self.planes.as_ref().map(|planes| (planes.width, planes.height))
+    }
+
+    /// The clockwise rotation of the latest frame in degrees, which the caller applies
+    /// through the viewport or the transform of its surface.
+    pub fn rotation(&self) -> u32 {
+        self.planes.as_ref().map(|planes| planes.rotation).unwrap_or(0)
+    }
+
+    /// The Y, U and V textures of the latest frame, for a renderer with its own pipeline.
+    pub fn textures(&self) -> Option<&[wgpu::Texture; 3]> {
+        self.planes.as_ref().map(|planes| &planes.textures)
+    }
+
+    /// Draws the latest frame over the viewport of the pass, draws nothing until
+    /// a frame is uploaded.
+    pub fn render<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>) {
+        if let Some(planes) = &self.planes {
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &planes.bind_group, &[]);
+            pass.draw(0..3, 0..1);
+        }
+    }
+
+    fn create_planes(&self, device: &wgpu::Device, width: u32, height: u32) -> Planes {
+        let plane = |width: u32, height: u32| {
+            device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("webrtc video plane"),
+                size: wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::R8Unorm,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            })
+        };
+
+        let (chroma_width, chroma_height) = (width.div_ceil(2), height.div_ceil(2));
+        let textures = [
+            plane(width, height),
+            plane(chroma_width, chroma_height),
+            plane(chroma_width, chroma_height),
+        ];
+
+        let views = textures
+            .each_ref()
+            .map(|texture| texture.create_view(&wgpu::TextureViewDescriptor::default()));
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("webrtc video"),
+            layout: &self.layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&views[0]),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&views[1]),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(&views[2]),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+        });
+
+        Planes {
+            rotation: 0,
+            textures,
+            bind_group,
+            width,
+            height,
+        }
+    }
+}
@@ -0,0 +1,28 @@
+@group(0) @binding(0) var y_plane: texture_2d<f32>;
+@group(0) @binding(1) var u_plane: texture_2d<f32>;
+@group(0) @binding(2) var v_plane: texture_2d<f32>;
+@group(0) @binding(3) var planes: sampler;
+
+struct Vertex {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+// a triangle covering the viewport, the vertices are generated from their index.
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> Vertex {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    var out: Vertex;
+    out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+// the limited range of BT.601 to gamma encoded RGB.
+@fragment
+fn fs_main(in: Vertex) -> @location(0) vec4<f32> {
+    let y = (textureSample(y_plane, planes, in.uv).r - 0.0625) * 1.164;
+    let u = textureSample(u_plane, planes, in.uv).r - 0.5;
+    let v = textureSample(v_plane, planes, in.uv).r - 0.5;
+    return vec4<f32>(y + 1.596 * v, y - 0.392 * u - 0.813 * v, y + 2.017 * u, 1.0);
+}
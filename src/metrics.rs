@@ -0,0 +1,299 @@
+use crate::stats::RTCStatsReport;
+use crate::{Error, RTCPeerConnection, Result};
+
+use futures::future::join_all;
+use prometheus::{GaugeVec, IntCounter, IntCounterVec, IntGaugeVec, Opts, Registry};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+/// The states of a candidate pair, every one of them is exported so a pair
+/// leaving a state resets the corresponding gauge.
+const CANDIDATE_PAIR_STATES: [&str; 5] = ["frozen", "waiting", "in-progress", "failed", "succeeded"];
+
+fn registration(e: prometheus::Error) -> Error {
+    Error::InvalidConfiguration(e.to_string())
+}
+
+/// Moves a counter up to a cumulative value read from the stats,
+/// a value going backwards, such as after an ssrc change, is ignored.
+fn advance(counter: IntCounter, value: u64) {
+    let current = counter.get();
+    if value > current {
+        counter.inc_by(value - current);
+    }
+}
+
+struct Exported {
+    peer: RTCPeerConnection,
+    // the kind and ssrc labels of the streams seen so far, removed with the connection.
+    streams: HashSet<(String, String)>,
+}
+
+/// Exports the stats of many connections as prometheus metrics.
+///
+/// Every metric is labelled with the name the connection was added with, the metrics
+/// of the RTP streams are also labelled with their kind and ssrc. The metrics of a
+/// connection are removed from the registry when the connection is removed.
+///
+/// The exporter is `Send + Sync` and can be shared behind an `Arc`, so connections can be
+/// added and removed while `run` is collecting their stats.
+pub struct MetricsExporter {
+    interval: Mutex<Duration>,
+    connections: Mutex<HashMap<String, Exported>>,
+    outgoing_bitrate: GaugeVec,
+    incoming_bitrate: GaugeVec,
+    round_trip_time: GaugeVec,
+    candidate_pairs: IntGaugeVec,
+    bytes_sent: IntCounterVec,
+    bytes_received: IntCounterVec,
+    packets_sent: IntCounterVec,
+    packets_received: IntCounterVec,
+    packets_lost: IntCounterVec,
+    frames_encoded: IntCounterVec,
+    frames_decoded: IntCounterVec,
+}
+
+impl MetricsExporter {
+    /// Creates the metrics and registers them with the registry.
+    pub fn new(registry: &Registry) -> Result<Self> {
+        let peer = &["peer"];
+        let stream = &["peer", "kind", "ssrc"];
+        let exporter = Self {
+            interval: Mutex::new(Duration::from_secs(5)),
+            connections: Mutex::new(HashMap::new()),
+            outgoing_bitrate: GaugeVec::new(
+                Opts::new("webrtc_available_outgoing_bitrate", "The estimated outgoing bandwidth in bits per second."),
+                peer,
+            )
+            .map_err(registration)?,
+            incoming_bitrate: GaugeVec::new(
+                Opts::new("webrtc_available_incoming_bitrate", "The estimated incoming bandwidth in bits per second."),
+                peer,
+            )
+            .map_err(registration)?,
+            round_trip_time: GaugeVec::new(
+                Opts::new("webrtc_round_trip_time_seconds", "The latest round trip time of the selected candidate pair."),
+                peer,
+            )
+            .map_err(registration)?,
+            candidate_pairs: IntGaugeVec::new(
+                Opts::new("webrtc_candidate_pairs", "The number of candidate pairs in each state."),
+                &["peer", "state"],
+            )
+            .map_err(registration)?,
+            bytes_sent: IntCounterVec::new(
+                Opts::new("webrtc_bytes_sent_total", "The bytes sent on the selected candidate pair."),
+                peer,
+            )
+            .map_err(registration)?,
+            bytes_received: IntCounterVec::new(
+                Opts::new("webrtc_bytes_received_total", "The bytes received on the selected candidate pair."),
+                peer,
+            )
+            .map_err(registration)?,
+            packets_sent: IntCounterVec::new(
+                Opts::new("webrtc_rtp_packets_sent_total", "The rtp packets sent by an outgoing stream."),
+                stream,
+            )
+            .map_err(registration)?,
+            packets_received: IntCounterVec::new(
+                Opts::new("webrtc_rtp_packets_received_total", "The rtp packets received by an incoming stream."),
+                stream,
+            )
+            .map_err(registration)?,
+            packets_lost: IntCounterVec::new(
+                Opts::new("webrtc_rtp_packets_lost_total", "The rtp packets lost by an incoming stream."),
+                stream,
+            )
+            .map_err(registration)?,
+            frames_encoded: IntCounterVec::new(
+                Opts::new("webrtc_frames_encoded_total", "The frames encoded by an outgoing video stream."),
+                stream,
+            )
+            .map_err(registration)?,
+            frames_decoded: IntCounterVec::new(
+                Opts::new("webrtc_frames_decoded_total", "The frames decoded by an incoming video stream."),
+                stream,
+            )
+            .map_err(registration)?,
+        };
+
+        registry.register(Box::new(exporter.outgoing_bitrate.clone())).map_err(registration)?;
+        registry.register(Box::new(exporter.incoming_bitrate.clone())).map_err(registration)?;
+        registry.register(Box::new(exporter.round_trip_time.clone())).map_err(registration)?;
+        registry.register(Box::new(exporter.candidate_pairs.clone())).map_err(registration)?;
+        registry.register(Box::new(exporter.bytes_sent.clone())).map_err(registration)?;
+        registry.register(Box::new(exporter.bytes_received.clone())).map_err(registration)?;
+        registry.register(Box::new(exporter.packets_sent.clone())).map_err(registration)?;
+        registry.register(Box::new(exporter.packets_received.clone())).map_err(registration)?;
+        registry.register(Box::new(exporter.packets_lost.clone())).map_err(registration)?;
+        registry.register(Box::new(exporter.frames_encoded.clone())).map_err(registration)?;
+        registry.register(Box::new(exporter.frames_decoded.clone())).map_err(registration)?;
+        Ok(exporter)
+    }
+
+    /// The interval at which `run` collects the stats, from its next start.
+    pub fn set_interval(&self, interval: Duration) {
+        *self.interval.lock().unwrap_or_else(|e| e.into_inner()) = interval;
+    }
+
+    /// Exports the stats of a connection under the given name, a connection
+    /// already added with this name is replaced.
+    pub fn add(&self, name: &str, peer: RTCPeerConnection) {
+        let mut connections = self.connections();
+        if let Some(exported) = connections.remove(name) {
+            self.remove_metrics(name, &exported);
+        }
+
+        connections.insert(name.to_string(), Exported {
+            peer,
+            streams: HashSet::new(),
+        });
+    }
+
+    /// Stops exporting a connection and removes its metrics.
+    pub fn remove(&self, name: &str) -> Option<RTCPeerConnection> {
+        let exported = self.connections().remove(name)?;
+        self.remove_metrics(name, &exported);
+        Some(exported.peer)
+    }
+
+    fn remove_metrics(&self, name: &str, exported: &Exported) {
+        for gauge in [&self.outgoing_bitrate, &self.incoming_bitrate, &self.round_trip_time] {
+            let _ = gauge.remove_label_values(&[name]);
+        }
+
+        for counter in [&self.bytes_sent, &self.bytes_received] {
+            let _ = counter.remove_label_values(&[name]);
+        }
+
+        for state in CANDIDATE_PAIR_STATES {
+            let _ = self.candidate_pairs.remove_label_values(&[name, state]);
+        }
+
+        for (kind, ssrc) in &exported.streams {
+            for counter in [
+                &self.packets_sent,
+                &self.packets_received,
+                &self.packets_lost,
+                &self.frames_encoded,
+                &self.frames_decoded,
+            ] {
+                let _ = counter.remove_label_values(&[name, kind, ssrc]);
+            }
+        }
+    }
+
+    /// Updates the metrics of a connection from one of its reports, this is what
+    /// `collect` does for every connection, exposed for the reports read elsewhere.
+    pub fn observe(&self, name: &str, report: &RTCStatsReport) {
+        let mut connections = self.connections();
+        let streams = match connections.get_mut(name) {
+            Some(exported) => &mut exported.streams,
+            None => return,
+        };
+
+        if let Some(pair) = report.selected_candidate_pair() {
+            if let Some(bitrate) = pair.available_outgoing_bitrate {
+                self.outgoing_bitrate.with_label_values(&[name]).set(bitrate);
+            }
+
+            if let Some(bitrate) = pair.available_incoming_bitrate {
+                self.incoming_bitrate.with_label_values(&[name]).set(bitrate);
+            }
+
+            if let Some(rtt) = pair.current_round_trip_time {
+                self.round_trip_time.with_label_values(&[name]).set(rtt);
+            }
+
+            if let Some(bytes) = pair.bytes_sent {
+                advance(self.bytes_sent.with_label_values(&[name]), bytes);
+            }
+
+            if let Some(bytes) = pair.bytes_received {
+                advance(self.bytes_received.with_label_values(&[name]), bytes);
+            }
+        }
+
+        for state in CANDIDATE_PAIR_STATES {
+            let count = report
+                .candidate_pairs()
+                .filter(|pair| pair.state.as_deref() == Some(state))
+                .count();
+            self.candidate_pairs.with_label_values(&[name, state]).set(count as i64);
+        }
+
+        for rtp in report.outbound_rtp() {
+            let (kind, ssrc) = match (&rtp.kind, rtp.ssrc) {
+                (Some(kind), Some(ssrc)) => (kind.clone(), ssrc.to_string()),
+                _ => continue,
+            };
+
+            let labels = [name, kind.as_str(), ssrc.as_str()];
+            if let Some(packets) = rtp.packets_sent {
+                advance(self.packets_sent.with_label_values(&labels), packets);
+            }
+
+            if let Some(frames) = rtp.frames_encoded {
+                advance(self.frames_encoded.with_label_values(&labels), frames as u64);
+            }
+
+            streams.insert((kind, ssrc));
+        }
+
+        for rtp in report.inbound_rtp() {
+            let (kind, ssrc) = match (&rtp.kind, rtp.ssrc) {
+                (Some(kind), Some(ssrc)) => (kind.clone(), ssrc.to_string()),
+                _ => continue,
+            };
+
+            let labels = [name, kind.as_str(), ssrc.as_str()];
+            if let Some(packets) = rtp.packets_received {
+                advance(self.packets_received.with_label_values(&labels), packets);
+            }
+
+            // the loss is negative while more duplicates than losses were received.
+            if let Some(lost) = rtp.packets_lost {
+                advance(self.packets_lost.with_label_values(&labels), lost.max(0) as u64);
+            }
+
+            if let Some(frames) = rtp.frames_decoded {
+                advance(self.frames_decoded.with_label_values(&labels), frames as u64);
+            }
+
+            streams.insert((kind, ssrc));
+        }
+    }
+
+    /// Reads the stats of every connection at once and updates their metrics as they
+    /// arrive, a connection whose stats can not be read keeps its previous values.
+    pub async fn collect(&self) {
+        let connections = self
+            .connections()
+            .iter()
+            .map(|(name, exported)| (name.clone(), exported.peer.clone()))
+            .collect::<Vec<_>>();
+        join_all(connections.into_iter().map(|(name, peer)| async move {
+            if let Ok(report) = peer.get_stats().await {
+                // a connection removed meanwhile is not observed.
+                self.observe(&name, &report);
+            }
+        }))
+        .await;
+    }
+
+    /// Collects the stats at every interval, forever.
+    pub async fn run(&self) {
+        let interval = *self.interval.lock().unwrap_or_else(|e| e.into_inner());
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            interval.tick().await;
+            self.collect().await;
+        }
+    }
+
+    fn connections(&self) -> MutexGuard<'_, HashMap<String, Exported>> {
+        self.connections.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
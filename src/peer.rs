@@ -0,0 +1,225 @@
+use crate::{
+    IceCandidate,
+    MediaStreamTrack,
+    RTCPeerConnection,
+    RTCSessionDescription,
+    Result,
+    SignalingState,
+};
+
+use futures::stream::BoxStream;
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+
+/// A message exchanged with the remote peer over the signaling channel of the application.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SignalingMessage {
+    Offer { sdp: String },
+    Answer { sdp: String },
+    Candidate {
+        candidate: String,
+        sdp_mid: String,
+        sdp_mline_index: i32,
+    },
+}
+
+/// The signaling channel of a peer, such as a websocket to a signaling server, it only carries
+/// the messages, the peer decides when to send them and how to apply them.
+pub trait Signaling: Send + 'static {
+    fn send(&mut self, message: SignalingMessage) -> impl Future<Output = Result<()>> + Send;
+
+    /// The next message of the remote peer, None once the channel is closed, which stops
+    /// the negotiation of the peer.
+    ///
+    /// The future must be cancel safe, the peer drops it whenever a local event, such as a
+    /// gathered candidate, has to be handled first, and calls `recv` again afterwards. A message
+    /// must not be lost when the future is dropped before completing, as with the `recv` of a
+    /// tokio channel or the `next` of a stream, unlike a read of a length prefixed frame which
+    /// may stop halfway through.
+    fn recv(&mut self) -> impl Future<Output = Result<Option<SignalingMessage>>> + Send;
+}
+
+/// A connection which negotiates by itself over a signaling channel.
+///
+/// The peer sends an offer whenever the connection needs a negotiation, such as when a track
+/// is published, answers the offers of the remote peer, and trickles the candidates both
+/// ways. When both peers send an offer at once, the polite peer drops its own offer and
+/// answers the other, while the impolite peer ignores the offer it received, as in the
+/// perfect negotiation pattern of the specification, so exactly one of the two peers of a
+/// connection must be polite.
+///
+/// ```no_run
+/// use futures::StreamExt;
+/// use webrtc::peer::{Peer, Signaling};
+///
+/// # async fn example(signaling: impl Signaling, camera: webrtc::MediaStreamTrack) -> webrtc::Result<()> {
+/// let peer = Peer::new(webrtc::RTCPeerConnection::new(&Default::default())?, signaling, true);
+/// peer.publish(&camera, "participant")?;
+///
+/// let mut tracks = peer.subscribe();
+/// while let Some(track) = tracks.next().await {
+///     println!("{} of {:?}", track.kind(), track.stream_ids());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct Peer {
+    connection: RTCPeerConnection,
+    task: JoinHandle<()>,
+}
+
+impl Peer {
+    /// Takes the connection over and negotiates it on the tokio runtime until the signaling
+    /// channel is closed or the peer is dropped.
+    pub fn new(connection: RTCPeerConnection, signaling: impl Signaling, polite: bool) -> Self {
+        // subscribed before the task is spawned, the events sent until it first runs are not replayed.
+        let negotiator = Negotiator {
+            negotiation_needed: connection.on_negotiation_needed().boxed(),
+            candidates: connection.on_ice_candidate().boxed(),
+            connection: connection.clone(),
+            offer_needed: false,
+            signaling,
+            polite,
+        };
+
+        Self {
+            task: tokio::spawn(negotiator.run()),
+            connection,
+        }
+    }
+
+    pub fn connection(&self) -> &RTCPeerConnection {
+        &self.connection
+    }
+
+    /// Sends a local track to the remote peer as part of a stream, the connection is
+    /// renegotiated by itself.
+    pub fn publish(&self, track: &MediaStreamTrack, stream_id: &str) -> Result<()> {
+        self.connection.add_track(track, stream_id)
+    }
+
+    /// The tracks of the remote peer, as they are received, see `MediaStreamTrack::stream_ids`
+    /// to group them by participant.
+    pub fn subscribe(&self) -> impl Stream<Item = Arc<MediaStreamTrack>> {
+        self.connection.on_track()
+    }
+
+    /// Whether the peer still negotiates, it stops once the signaling channel is closed.
+    pub fn is_negotiating(&self) -> bool {
+        !self.task.is_finished()
+    }
+
+    /// Stops the negotiation and closes the connection.
+    pub async fn close(self) -> Result<()> {
+        self.task.abort();
+        self.connection.clone().close().await
+    }
+}
+
+impl Drop for Peer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+struct Negotiator<S> {
+    connection: RTCPeerConnection,
+    negotiation_needed: BoxStream<'static, ()>,
+    candidates: BoxStream<'static, IceCandidate>,
+    signaling: S,
+    polite: bool,
+    /// A negotiation was needed while an exchange was underway.
+    offer_needed: bool,
+}
+
+impl<S: Signaling> Negotiator<S> {
+    async fn run(mut self) {
+        loop {
+            let result = tokio::select! {
+                Some(()) = self.negotiation_needed.next() => {
+                    self.offer_needed = true;
+                    self.offer().await
+                }
+                Some(candidate) = self.candidates.next() => {
+                    self.signaling.send(SignalingMessage::Candidate {
+                        candidate: candidate.candidate,
+                        sdp_mid: candidate.sdp_mid,
+                        sdp_mline_index: candidate.sdp_mline_index,
+                    }).await
+                }
+                message = self.signaling.recv() => match message {
+                    Ok(Some(message)) => self.handle(message).await,
+                    Ok(None) => break,
+                    Err(e) => {
+                        tracing::warn!("peer signaling failed: {}", e);
+                        break;
+                    }
+                },
+            };
+
+            if let Err(e) = result {
+                tracing::warn!("peer negotiation failed: {}", e);
+            }
+        }
+    }
+
+    /// Sends an offer if one is needed and no exchange is underway.
+    async fn offer(&mut self) -> Result<()> {
+        if !self.offer_needed || self.connection.signaling_state() != SignalingState::Stable {
+            return Ok(());
+        }
+
+        self.offer_needed = false;
+        let offer = self.connection.create_offer().await?;
+        let sdp = offer.get_sdp()?.to_string();
+        self.connection.set_local_description(offer).await?;
+        self.signaling.send(SignalingMessage::Offer { sdp }).await
+    }
+
+    async fn handle(&mut self, message: SignalingMessage) -> Result<()> {
+        match message {
+            SignalingMessage::Offer { sdp } => {
+                if self.connection.signaling_state() != SignalingState::Stable {
+                    if !self.polite {
+                        return Ok(());
+                    }
+
+                    // the local offer is offered again once the exchange completes.
+                    let rollback = RTCSessionDescription::rollback();
+                    self.connection.set_local_description(rollback).await?;
+                    self.offer_needed = true;
+                }
+
+                let offer = RTCSessionDescription::offer(&sdp)?;
+                self.connection.set_remote_description(offer).await?;
+
+                let answer = self.connection.create_answer().await?;
+                let sdp = answer.get_sdp()?.to_string();
+                self.connection.set_local_description(answer).await?;
+                self.signaling.send(SignalingMessage::Answer { sdp }).await?;
+                self.offer().await
+            }
+            SignalingMessage::Answer { sdp } => {
+                let answer = RTCSessionDescription::answer(&sdp)?;
+                self.connection.set_remote_description(answer).await?;
+                self.offer().await
+            }
+            SignalingMessage::Candidate {
+                candidate,
+                sdp_mid,
+                sdp_mline_index,
+            } => {
+                // queued by the connection until the remote description is set.
+                self.connection.add_ice_candidate(&IceCandidate {
+                    candidate,
+                    sdp_mid,
+                    sdp_mline_index,
+                })
+            }
+        }
+    }
+}
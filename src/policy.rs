@@ -0,0 +1,154 @@
+use crate::stats::RTCStatsReport;
+use crate::{ConnectionState, Error, MediaType, RTCPeerConnection};
+
+use futures::stream::{self, Stream};
+use std::time::{Duration, Instant};
+
+/// Thresholds of the audio-only degradation policy.
+///
+/// When the network stays below the thresholds for `degrade_after`, the outgoing video
+/// is paused so that the remaining bandwidth is left to the audio. Once the network has
+/// been healthy again for `recover_after`, the video is resumed.
+#[derive(Clone, Debug)]
+pub struct DegradationPolicy {
+    /// The available outgoing bitrate, in bits per second, under which the network is
+    /// considered too poor to carry video.
+    pub min_available_outgoing_bitrate: f64,
+    /// The round trip time over which the network is considered too poor to carry video.
+    pub max_round_trip_time: Duration,
+    /// How long the thresholds must be continuously violated before degrading.
+    pub degrade_after: Duration,
+    /// How long the thresholds must be continuously met before recovering.
+    pub recover_after: Duration,
+    /// The interval at which the stats are sampled when the policy is watching a connection.
+    pub interval: Duration,
+}
+
+impl Default for DegradationPolicy {
+    fn default() -> Self {
+        Self {
+            min_available_outgoing_bitrate: 150_000.0,
+            max_round_trip_time: Duration::from_millis(800),
+            degrade_after: Duration::from_secs(5),
+            recover_after: Duration::from_secs(10),
+            interval: Duration::from_secs(1),
+        }
+    }
+}
+
+/// The threshold that caused the policy to degrade the connection.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DegradationReason {
+    /// The available outgoing bitrate, in bits per second.
+    LowBandwidth(f64),
+    HighRoundTripTime(Duration),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PolicyEvent {
+    /// The outgoing video has been paused, only audio is being sent.
+    VideoDisabled(DegradationReason),
+    /// The network recovered and the outgoing video has been resumed.
+    VideoEnabled,
+}
+
+/// The state machine behind the degradation policy.
+///
+/// It can be fed with stats reports by hand through `evaluate`,
+/// or drive a connection by itself through `watch`.
+pub struct PolicyEngine {
+    policy: DegradationPolicy,
+    degraded: bool,
+    bad_since: Option<Instant>,
+    good_since: Option<Instant>,
+}
+
+impl PolicyEngine {
+    pub fn new(policy: DegradationPolicy) -> Self {
+        Self {
+            policy,
+            degraded: false,
+            bad_since: None,
+            good_since: None,
+        }
+    }
+
+    pub fn is_degraded(&self) -> bool {
+        self.degraded
+    }
+
+    /// Checks the selected candidate pair of the report against the thresholds,
+    /// returns an event when the state of the policy changes.
+    ///
+    /// A report without a selected candidate pair does not move the state machine.
+    pub fn evaluate(&mut self, report: &RTCStatsReport, now: Instant) -> Option<PolicyEvent> {
+        let pair = report.selected_candidate_pair()?;
+        let reason = self.check(
+            pair.available_outgoing_bitrate,
+            pair.current_round_trip_time.map(Duration::from_secs_f64),
+        );
+
+        match reason {
+            Some(reason) => {
+                self.good_since = None;
+                let since = *self.bad_since.get_or_insert(now);
+                if !self.degraded && now.duration_since(since) >= self.policy.degrade_after {
+                    self.degraded = true;
+                    return Some(PolicyEvent::VideoDisabled(reason));
+                }
+            }
+            None => {
+                self.bad_since = None;
+                let since = *self.good_since.get_or_insert(now);
+                if self.degraded && now.duration_since(since) >= self.policy.recover_after {
+                    self.degraded = false;
+                    return Some(PolicyEvent::VideoEnabled);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Periodically samples the stats of the connection, pauses and resumes its outgoing
+    /// video according to the policy, and yields every change as an event.
+    ///
+    /// The policy only acts while the stream is being polled, the stream ends when the
+    /// connection is closed, a sample which fails otherwise is skipped.
+    pub fn watch(self, peer: &RTCPeerConnection) -> impl Stream<Item = PolicyEvent> + '_ {
+        let interval = tokio::time::interval(self.policy.interval);
+        stream::unfold((self, interval), move |(mut engine, mut interval)| async move {
+            loop {
+                interval.tick().await;
+                let report = match peer.get_stats().await {
+                    Ok(_) if peer.connection_state() == ConnectionState::Close => return None,
+                    Ok(report) => report,
+                    Err(Error::Closed) => return None,
+                    Err(_) => continue,
+                };
+
+                if let Some(event) = engine.evaluate(&report, Instant::now()) {
+                    let active = event == PolicyEvent::VideoEnabled;
+                    peer.set_senders_active(MediaType::Video, active);
+                    return Some((event, (engine, interval)));
+                }
+            }
+        })
+    }
+
+    fn check(&self, bitrate: Option<f64>, rtt: Option<Duration>) -> Option<DegradationReason> {
+        if let Some(bitrate) = bitrate {
+            if bitrate < self.policy.min_available_outgoing_bitrate {
+                return Some(DegradationReason::LowBandwidth(bitrate));
+            }
+        }
+
+        if let Some(rtt) = rtt {
+            if rtt > self.policy.max_round_trip_time {
+                return Some(DegradationReason::HighRoundTripTime(rtt));
+            }
+        }
+
+        None
+    }
+}
@@ -0,0 +1,137 @@
+use crate::{DataChannelMessages, RTCDataChannel, Result};
+
+use futures::stream::{self, Stream};
+use futures::StreamExt;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time::{self, Instant, MissedTickBehavior};
+
+/// `[PING] + the sequence as a big endian u32 + the clock of the sender in microseconds as a big endian u64`.
+const PING: u8 = 0;
+
+/// `[PONG] + the sequence of the ping + the clock of the receiver in microseconds when the ping arrived`.
+const PONG: u8 = 1;
+
+const PING_SIZE: usize = 13;
+
+/// The pings awaiting their pong, the older ones are considered lost.
+const MAX_PENDING: usize = 64;
+
+/// A round trip of a ping.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LatencySample {
+    pub sequence: u32,
+    /// The time between the ping and its pong, as measured by the monotonic clock.
+    pub rtt: Duration,
+    /// The clock of the remote peer minus the local clock in microseconds, assuming the
+    /// path is symmetric, so its error is at most half the asymmetry of the path.
+    pub clock_offset_micros: i64,
+}
+
+/// Measures the round trip time of a data channel and the offset of the clock of the
+/// remote peer, such as for the netcode of a game which schedules the inputs in the time
+/// of the server.
+///
+/// The time is the one of the application, so it includes the queues of the data channel
+/// on top of the network. An unordered and unreliable channel dedicated to the probe keeps
+/// a lost packet of another message from delaying the pongs. Both ends of the channel run
+/// a probe, as each one answers the pings of the other.
+pub struct LatencyProbe {
+    messages: DataChannelMessages,
+    interval: Duration,
+}
+
+impl LatencyProbe {
+    /// Takes the reader of the channel, see `RTCDataChannel::messages`.
+    pub fn new(channel: Arc<RTCDataChannel>, interval: Duration) -> Result<Self> {
+        Ok(Self {
+            messages: channel.messages()?,
+            interval,
+        })
+    }
+
+    /// Sends a ping every interval, answers the pings of the remote peer, and yields a
+    /// sample for every pong. The pings are only sent while the stream is polled, and
+    /// the stream ends once the channel is closed.
+    pub fn samples(self) -> impl Stream<Item = LatencySample> {
+        let mut interval = time::interval(self.interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let pending = VecDeque::<(u32, Instant)>::new();
+        stream::unfold(
+            (self.messages, interval, pending, 0u32),
+            |(mut messages, mut interval, mut pending, mut sequence)| async move {
+                loop {
+                    tokio::select! {
+                        _ = interval.tick() => {
+                            sequence = sequence.wrapping_add(1);
+                            let mut ping = vec![PING];
+                            ping.extend_from_slice(&sequence.to_be_bytes());
+                            ping.extend_from_slice(&now_micros().to_be_bytes());
+
+                            // a ping which can not be sent yet, such as before the channel is open, is skipped.
+                            if messages.channel().send(&ping, true).is_ok() {
+                                if pending.len() == MAX_PENDING {
+                                    pending.pop_front();
+                                }
+
+                                pending.push_back((sequence, Instant::now()));
+                            }
+                        }
+                        message = messages.next() => {
+                            let message = message?.data;
+                            if message.len() != PING_SIZE {
+                                continue;
+                            }
+
+                            let received = Instant::now();
+                            let remote = read_u64(&message[5..]);
+                            match message[0] {
+                                PING => {
+                                    let mut pong = message;
+                                    pong[0] = PONG;
+                                    pong[5..].copy_from_slice(&now_micros().to_be_bytes());
+                                    let _ = messages.channel().send(&pong, true);
+                                }
+                                PONG => {
+                                    let seq = u32::from_be_bytes([message[1], message[2], message[3], message[4]]);
+                                    let Some(index) = pending.iter().position(|(s, _)| *s == seq) else {
+                                        continue;
+                                    };
+
+                                    let (_, sent) = pending.remove(index).unwrap();
+                                    let rtt = received - sent;
+
+                                    // the remote clock read the ping half a round trip before now.
+                                    let local = now_micros() as i64 - (rtt.as_micros() / 2) as i64;
+                                    let sample = LatencySample {
+                                        clock_offset_micros: remote as i64 - local,
+                                        sequence: seq,
+                                        rtt,
+                                    };
+
+                                    return Some((sample, (messages, interval, pending, sequence)));
+                                }
+                                _ => (),
+                            }
+                        }
+                    }
+                }
+            },
+        )
+    }
+}
+
+fn now_micros() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_micros() as u64)
+        .unwrap_or(0)
+}
+
+fn read_u64(bytes: &[u8]) -> u64 {
+    let mut buf = [0; 8];
+    buf.copy_from_slice(&bytes[..8]);
+    u64::from_be_bytes(buf)
+}
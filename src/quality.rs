@@ -0,0 +1,260 @@
+use crate::stats::RTCStatsReport;
+use crate::RTCPeerConnection;
+
+use futures::stream::{self, Stream};
+use std::time::Duration;
+
+/// The direction of the media a score is about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// The media sent to the remote peer, as reported back by it.
+    Outbound,
+    /// The media received from the remote peer.
+    Inbound,
+}
+
+/// The coarse level of a score, such as the number of bars of a signal indicator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum QualityLevel {
+    Bad,
+    Poor,
+    Fair,
+    Good,
+    Excellent,
+}
+
+impl QualityLevel {
+    /// The level of a mean opinion score.
+    pub fn from_mos(mos: f64) -> Self {
+        match mos {
+            mos if mos >= 4.0 => Self::Excellent,
+            mos if mos >= 3.6 => Self::Good,
+            mos if mos >= 3.1 => Self::Fair,
+            mos if mos >= 2.6 => Self::Poor,
+            _ => Self::Bad,
+        }
+    }
+
+    /// The number of bars, from 0 for `Bad` to 4 for `Excellent`.
+    pub fn bars(self) -> u8 {
+        self as u8
+    }
+}
+
+/// The network conditions of a direction over the interval between two reports.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct NetworkSample {
+    pub round_trip_time: Option<Duration>,
+    /// The fraction of the packets lost, between 0 and 1.
+    pub loss: f64,
+    pub jitter: Duration,
+    /// The number of times the received video froze.
+    pub freezes: u32,
+}
+
+impl NetworkSample {
+    /// A mean opinion score between 1 and 4.5, from the simplified E-model of ITU-T G.107,
+    /// lowered by half a point for every freeze of the video.
+    ///
+    /// The model was made for voice, it ranks the conditions of a video call rather than
+    /// predicting its perceived quality.
+    pub fn mos(&self) -> f64 {
+        let rtt = self.round_trip_time.unwrap_or_default().as_secs_f64() * 1000.0;
+        let latency = rtt / 2.0 + self.jitter.as_secs_f64() * 1000.0 * 2.0 + 10.0;
+        let delay = if latency < 160.0 {
+            latency / 40.0
+        } else {
+            (latency - 120.0) / 10.0
+        };
+
+        let r = (93.2 - delay - self.loss.clamp(0.0, 1.0) * 100.0 * 2.5).clamp(0.0, 100.0);
+        let mos = 1.0 + 0.035 * r + 0.000007 * r * (r - 60.0) * (100.0 - r);
+        (mos - 0.5 * self.freezes as f64).clamp(1.0, 4.5)
+    }
+}
+
+/// The score of a direction, smoothed over the recent reports.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QualityScore {
+    pub mos: f64,
+    pub level: QualityLevel,
+    /// The conditions of the latest report.
+    pub sample: NetworkSample,
+}
+
+/// The level of a direction changed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QualityEvent {
+    pub direction: Direction,
+    pub score: QualityScore,
+    /// The previous level, None for the first score of the direction.
+    pub previous: Option<QualityLevel>,
+}
+
+/// The tuning of the quality monitor.
+#[derive(Clone, Debug)]
+pub struct QualityConfig {
+    /// The weight of the latest report in the smoothed score, between 0 and 1, a lower
+    /// weight keeps a single bad report from flipping the level.
+    pub smoothing: f64,
+    /// The interval at which the stats are sampled when the monitor is watching a connection.
+    pub interval: Duration,
+}
+
+impl Default for QualityConfig {
+    fn default() -> Self {
+        Self {
+            smoothing: 0.3,
+            interval: Duration::from_secs(2),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+struct InboundTotals {
+    lost: i64,
+    received: u64,
+    freezes: u32,
+}
+
+/// Combines the round trip time, the loss, the jitter and the freezes of the stats into a
+/// score per direction, so that an interface shows the quality of a call without deriving
+/// it from the stats itself.
+///
+/// It can be fed with stats reports by hand through `update`, or sample a connection by
+/// itself through `watch`.
+pub struct QualityMonitor {
+    config: QualityConfig,
+    outbound: Option<QualityScore>,
+    inbound: Option<QualityScore>,
+    totals: Option<InboundTotals>,
+}
+
+impl QualityMonitor {
+    pub fn new(config: QualityConfig) -> Self {
+        Self {
+            config,
+            outbound: None,
+            inbound: None,
+            totals: None,
+        }
+    }
+
+    /// The latest score of a direction, None until a report has media in that direction.
+    pub fn score(&self, direction: Direction) -> Option<QualityScore> {
+        match direction {
+            Direction::Outbound => self.outbound,
+            Direction::Inbound => self.inbound,
+        }
+    }
+
+    /// Scores the report, the loss and the freezes of the incoming media are the ones since
+    /// the previous report, returns an event for every direction whose level changed.
+    pub fn update(&mut self, report: &RTCStatsReport) -> Vec<QualityEvent> {
+        let rtt = report
+            .selected_candidate_pair()
+            .and_then(|pair| pair.current_round_trip_time)
+            .map(Duration::from_secs_f64);
+
+        let mut events = Vec::new();
+        if let Some(sample) = outbound_sample(report, rtt) {
+            let previous = self.outbound;
+            self.outbound = Some(self.smooth(previous, sample));
+            events.extend(changed(Direction::Outbound, previous, self.outbound));
+        }
+
+        if let Some(sample) = self.inbound_sample(report, rtt) {
+            let previous = self.inbound;
+            self.inbound = Some(self.smooth(previous, sample));
+            events.extend(changed(Direction::Inbound, previous, self.inbound));
+        }
+
+        events
+    }
+
+    /// Periodically samples the stats of the connection and yields the changes of level.
+    ///
+    /// The monitor only samples while the stream is being polled.
+    pub fn watch(self, peer: &RTCPeerConnection) -> impl Stream<Item = QualityEvent> + '_ {
+        let interval = tokio::time::interval(self.config.interval);
+        let pending = Vec::<QualityEvent>::new().into_iter();
+        stream::unfold((self, interval, pending), move |(mut monitor, mut interval, mut pending)| async move {
+            loop {
+                if let Some(event) = pending.next() {
+                    return Some((event, (monitor, interval, pending)));
+                }
+
+                interval.tick().await;
+                if let Ok(report) = peer.get_stats().await {
+                    pending = monitor.update(&report).into_iter();
+                }
+            }
+        })
+    }
+
+    fn smooth(&self, previous: Option<QualityScore>, sample: NetworkSample) -> QualityScore {
+        let weight = self.config.smoothing.clamp(0.0, 1.0);
+        let mos = match previous {
+            Some(previous) => previous.mos + (sample.mos() - previous.mos) * weight,
+            None => sample.mos(),
+        };
+
+        QualityScore {
+            level: QualityLevel::from_mos(mos),
+            sample,
+            mos,
+        }
+    }
+
+    fn inbound_sample(&mut self, report: &RTCStatsReport, rtt: Option<Duration>) -> Option<NetworkSample> {
+        let mut streams = report.inbound_rtp().peekable();
+        streams.peek()?;
+
+        let mut totals = InboundTotals::default();
+        let mut jitter = 0.0f64;
+        for stream in streams {
+            totals.lost += stream.packets_lost.unwrap_or(0);
+            totals.received += stream.packets_received.unwrap_or(0);
+            totals.freezes += stream.freeze_count.unwrap_or(0);
+            jitter = jitter.max(stream.jitter.unwrap_or(0.0));
+        }
+
+        // the first report is scored on the totals since the start of the streams.
+        let previous = self.totals.replace(totals).unwrap_or_default();
+        let lost = (totals.lost - previous.lost).max(0) as f64;
+        let received = totals.received.saturating_sub(previous.received) as f64;
+        Some(NetworkSample {
+            round_trip_time: rtt,
+            loss: if lost + received > 0.0 { lost / (lost + received) } else { 0.0 },
+            jitter: Duration::from_secs_f64(jitter),
+            freezes: totals.freezes.saturating_sub(previous.freezes),
+        })
+    }
+}
+
+/// The conditions of the outgoing media, from the receiver reports of the remote peer.
+fn outbound_sample(report: &RTCStatsReport, rtt: Option<Duration>) -> Option<NetworkSample> {
+    let mut sample: Option<NetworkSample> = None;
+    for stream in report.remote_inbound_rtp() {
+        let sample = sample.get_or_insert(NetworkSample {
+            round_trip_time: stream.round_trip_time.map(Duration::from_secs_f64).or(rtt),
+            ..NetworkSample::default()
+        });
+
+        // the worst stream drives the score.
+        sample.loss = sample.loss.max(stream.fraction_lost.unwrap_or(0.0));
+        sample.jitter = sample.jitter.max(Duration::from_secs_f64(stream.jitter.unwrap_or(0.0)));
+    }
+
+    sample
+}
+
+fn changed(direction: Direction, previous: Option<QualityScore>, score: Option<QualityScore>) -> Option<QualityEvent> {
+    let score = score?;
+    let previous = previous.map(|previous| previous.level);
+    (previous != Some(score.level)).then_some(QualityEvent {
+        direction,
+        score,
+        previous,
+    })
+}
@@ -0,0 +1,415 @@
+mod mp4;
+mod webm;
+
+use crate::ffi::encoder::{AudioEncoder, EncodedFrame, VideoEncoder};
+use crate::{AudioFrame, Error, MediaSink, MediaStreamTrack, Result, VideoFrame};
+
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, Write};
+use std::path::Path;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// The samples libopus drops at the start of the stream, 6.5ms at 48kHz.
+pub(crate) const OPUS_PRE_SKIP: u16 = 312;
+
+/// The identification header of Opus, as stored in WebM and MP4 files.
+pub(crate) fn opus_head(channels: u32) -> Vec<u8> {
+    let mut head = b"OpusHead".to_vec();
+    head.extend([1, channels as u8]);
+    head.extend(OPUS_PRE_SKIP.to_le_bytes());
+    head.extend(AudioEncoder::SAMPLE_RATE.to_le_bytes());
+    head.extend([0, 0, 0]);
+    head
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TrackFormat {
+    Video { width: u32, height: u32 },
+    Audio { channels: u32 },
+}
+
+/// An encoded frame, timestamped from the start of the recording.
+pub(crate) struct Sample {
+    pub data: Vec<u8>,
+    pub timestamp_us: i64,
+    pub keyframe: bool,
+}
+
+/// A container writer, the tracks are declared once before the first sample.
+pub(crate) trait Muxer: Send {
+    fn start(&mut self, tracks: &[TrackFormat]) -> io::Result<()>;
+    fn write(&mut self, track: usize, sample: &Sample) -> io::Result<()>;
+    fn finish(&mut self) -> io::Result<()>;
+}
+
+/// The container of a recording, the video is encoded with VP8 and the audio with Opus.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Container {
+    WebM,
+    /// Fragmented MP4, the file is playable up to the last fragment if the recording
+    /// is interrupted.
+    Mp4,
+}
+
+impl Container {
+    /// Guesses the container from the extension of a file.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "webm" | "mkv" => Some(Self::WebM),
+            "mp4" | "m4v" | "m4a" => Some(Self::Mp4),
+            _ => None,
+        }
+    }
+}
+
+/// The options of a recording.
+#[derive(Clone, Debug)]
+pub struct RecordingOptions {
+    /// The container of the file, guessed from its extension when `None`.
+    pub container: Option<Container>,
+    /// The target bitrate of the video in bits per second.
+    pub video_bitrate: u32,
+    /// The target bitrate of the audio in bits per second.
+    pub audio_bitrate: u32,
+    pub frame_rate: u32,
+    /// The interval of the keyframes, which are the points a player can seek to.
+    pub keyframe_interval: Duration,
+    /// The header of the file lists the tracks which have received media, it is written once
+    /// every track has, or this long after the first frame. Late tracks are not recorded.
+    pub start_timeout: Duration,
+}
+
+impl Default for RecordingOptions {
+    fn default() -> Self {
+        Self {
+            container: None,
+            video_bitrate: 1_000_000,
+            audio_bitrate: 64_000,
+            frame_rate: 30,
+            keyframe_interval: Duration::from_secs(2),
+            start_timeout: Duration::from_secs(2),
+        }
+    }
+}
+
+enum Message {
+    Video(usize, Arc<VideoFrame>),
+    Audio(usize, Arc<AudioFrame>),
+    Stop,
+}
+
+enum Encoder {
+    Video {
+        encoder: VideoEncoder,
+        last_keyframe_us: Option<i64>,
+    },
+    Audio {
+        encoder: Option<AudioEncoder>,
+        start_us: i64,
+    },
+}
+
+struct TrackState {
+    encoder: Encoder,
+    format: Option<TrackFormat>,
+    /// The index of the track in the file, once the header is written.
+    index: Option<usize>,
+}
+
+/// Encodes and muxes the frames on a thread of its own,
+/// so the threads delivering the frames are never blocked.
+struct Writer {
+    muxer: Box<dyn Muxer>,
+    options: RecordingOptions,
+    tracks: Vec<TrackState>,
+    /// The timestamp of the first frame of any track, the start of the recording.
+    base_us: Option<i64>,
+    first_frame: Option<Instant>,
+    started: bool,
+    pending: Vec<(usize, Sample)>,
+    error: Option<Error>,
+}
+
+impl Writer {
+    fn run(mut self, receiver: Receiver<Message>) -> Result<()> {
+        loop {
+            match receiver.recv_timeout(Duration::from_millis(100)) {
+                Ok(Message::Video(track, frame)) => self.on_video(track, frame),
+                Ok(Message::Audio(track, frame)) => self.on_audio(track, frame),
+                Ok(Message::Stop) | Err(RecvTimeoutError::Disconnected) => break,
+                Err(RecvTimeoutError::Timeout) => (),
+            }
+
+            if let Err(e) = self.try_start() {
+                self.error.get_or_insert(e);
+            }
+
+            if self.error.is_some() {
+                break;
+            }
+        }
+
+        if let Err(e) = self.finish() {
+            self.error.get_or_insert(e);
+        }
+
+        self.error.map(Err).unwrap_or(Ok(()))
+    }
+
+    fn on_video(&mut self, track: usize, frame: Arc<VideoFrame>) {
+        let base_us = *self.base_us.get_or_insert(frame.timestamp_us);
+        self.first_frame.get_or_insert_with(Instant::now);
+
+        let interval = self.options.keyframe_interval.as_micros() as i64;
+        let state = &mut self.tracks[track];
+        if let Encoder::Video { encoder, last_keyframe_us } = &mut state.encoder {
+            let keyframe = last_keyframe_us.map(|last| frame.timestamp_us - last >= interval).unwrap_or(true);
+            if keyframe {
+                *last_keyframe_us = Some(frame.timestamp_us);
+            }
+
+            state.format.get_or_insert(TrackFormat::Video {
+                width: frame.width,
+                height: frame.height,
+            });
+
+            match encoder.encode(&frame, keyframe) {
+                Ok(frames) => self.push(track, frames, base_us),
+                Err(e) => drop(self.error.get_or_insert(e)),
+            }
+        }
+    }
+
+    fn on_audio(&mut self, track: usize, frame: Arc<AudioFrame>) {
+        let base_us = *self.base_us.get_or_insert(frame.timestamp_us);
+        self.first_frame.get_or_insert_with(Instant::now);
+
+        let bitrate = self.options.audio_bitrate;
+        let state = &mut self.tracks[track];
+        if let Encoder::Audio { encoder, start_us } = &mut state.encoder {
+            // the audio is timestamped by the encoder from its first sample,
+            // which keeps the timestamps continuous whatever the delivery jitter.
+            let encoder = encoder.get_or_insert_with(|| {
+                *start_us = frame.timestamp_us - base_us;
+                AudioEncoder::new(frame.channels, bitrate)
+            });
+
+            state.format.get_or_insert(TrackFormat::Audio {
+                channels: encoder.channels(),
+            });
+
+            // a change of channels is not supported by the containers, the frame is dropped.
+            if frame.channels != encoder.channels() {
+                return;
+            }
+
+            let start_us = *start_us;
+            match encoder.encode(&frame) {
+                Ok(frames) => self.push(track, frames, base_us - start_us),
+                Err(e) => drop(self.error.get_or_insert(e)),
+            }
+        }
+    }
+
+    fn push(&mut self, track: usize, frames: Vec<EncodedFrame>, base_us: i64) {
+        for frame in frames {
+            let sample = Sample {
+                timestamp_us: (frame.timestamp_us - base_us).max(0),
+                keyframe: frame.keyframe,
+                data: frame.data,
+            };
+
+            if !self.started {
+                self.pending.push((track, sample));
+            } else if let Some(index) = self.tracks[track].index {
+                if let Err(e) = self.muxer.write(index, &sample) {
+                    self.error.get_or_insert(e.into());
+                }
+            }
+        }
+    }
+
+    /// Writes the header once every track has received media, or after the timeout.
+    fn try_start(&mut self) -> Result<()> {
+        let timed_out = self
+            .first_frame
+            .map(|first| first.elapsed() >= self.options.start_timeout)
+            .unwrap_or(false);
+        if self.started || !(timed_out || self.tracks.iter().all(|t| t.format.is_some())) {
+            return Ok(());
+        }
+
+        self.start()
+    }
+
+    fn start(&mut self) -> Result<()> {
+        self.started = true;
+
+        let mut formats = Vec::new();
+        for state in &mut self.tracks {
+            if let Some(format) = state.format {
+                state.index = Some(formats.len());
+                formats.push(format);
+            }
+        }
+
+        self.muxer.start(&formats)?;
+        for (track, sample) in std::mem::take(&mut self.pending) {
+            if let Some(index) = self.tracks[track].index {
+                self.muxer.write(index, &sample)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if !self.started {
+            self.start()?;
+        }
+
+        Ok(self.muxer.finish()?)
+    }
+}
+
+/// Records audio and video tracks into a WebM or fragmented MP4 file.
+///
+/// The recorder attaches a sink to every track, the frames are encoded with VP8 and Opus
+/// and muxed on a thread of the recorder, timestamped from the first frame received on any
+/// track so that the tracks stay in sync. The file is finished when the recorder is stopped
+/// or dropped.
+///
+/// ```no_run
+/// use webrtc::recording::{Recorder, RecordingOptions};
+///
+/// # fn record(audio: &webrtc::MediaStreamTrack, video: &webrtc::MediaStreamTrack) -> webrtc::Result<()> {
+/// let recorder = Recorder::create("call.webm", &[audio, video], RecordingOptions::default())?;
+/// // ...
+/// recorder.stop()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Recorder {
+    sender: Sender<Message>,
+    sinks: Vec<MediaSink>,
+    thread: Option<JoinHandle<Result<()>>>,
+}
+
+impl Recorder {
+    /// Records the tracks into a file, the container is guessed from the extension of the
+    /// path unless it is given in the options.
+    pub fn create(
+        path: impl AsRef<Path>,
+        tracks: &[&MediaStreamTrack],
+        options: RecordingOptions,
+    ) -> Result<Self> {
+        let path = path.as_ref();
+        let container = options
+            .container
+            .or_else(|| Container::from_path(path))
+            .ok_or_else(|| {
+                Error::InvalidConfiguration(format!(
+                    "the container of {} can not be guessed from its extension",
+                    path.display()
+                ))
+            })?;
+
+        let file = BufWriter::new(File::create(path)?);
+        Self::with_writer(file, container, tracks, options)
+    }
+
+    /// Records the tracks into any seekable writer.
+    pub fn with_writer<W: Write + Seek + Send + 'static>(
+        writer: W,
+        container: Container,
+        tracks: &[&MediaStreamTrack],
+        options: RecordingOptions,
+    ) -> Result<Self> {
+        let muxer: Box<dyn Muxer> = match container {
+            Container::WebM => Box::new(webm::WebM::new(writer)),
+            Container::Mp4 => Box::new(mp4::Mp4::new(writer)),
+        };
+
+        let (sender, receiver) = channel();
+        let mut sinks = Vec::with_capacity(tracks.len());
+        let mut states = Vec::with_capacity(tracks.len());
+        for (i, track) in tracks.iter().enumerate() {
+            let video_sender = sender.clone();
+            let audio_sender = sender.clone();
+            if let Some(sink) = track.add_video_sink(move |frame| {
+                let _ = video_sender.send(Message::Video(i, frame));
+            }) {
+                sinks.push(sink);
+                states.push(TrackState {
+                    encoder: Encoder::Video {
+                        encoder: VideoEncoder::new(options.video_bitrate, options.frame_rate),
+                        last_keyframe_us: None,
+                    },
+                    format: None,
+                    index: None,
+                });
+            } else if let Some(sink) = track.add_audio_sink(move |frame| {
+                let _ = audio_sender.send(Message::Audio(i, frame));
+            }) {
+                sinks.push(sink);
+                states.push(TrackState {
+                    encoder: Encoder::Audio {
+                        encoder: None,
+                        start_us: 0,
+                    },
+                    format: None,
+                    index: None,
+                });
+            } else {
+                return Err(Error::InvalidConfiguration(format!(
+                    "the track {} is neither an audio nor a video track",
+                    track.id()
+                )));
+            }
+        }
+
+        let writer = Writer {
+            tracks: states,
+            base_us: None,
+            first_frame: None,
+            started: false,
+            pending: Vec::new(),
+            error: None,
+            options,
+            muxer,
+        };
+
+        Ok(Self {
+            thread: Some(thread::spawn(move || writer.run(receiver))),
+            sender,
+            sinks,
+        })
+    }
+
+    /// Detaches the recorder from the tracks and finishes the file,
+    /// returns the first error met while recording.
+    pub fn stop(mut self) -> Result<()> {
+        self.finish()
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        // the sinks are removed first, so no frame is sent after the stop message.
+        self.sinks.clear();
+        let _ = self.sender.send(Message::Stop);
+        match self.thread.take() {
+            Some(thread) => thread.join().unwrap_or_else(|_| {
+                Err(Error::Encoding("the recording thread panicked".to_string()))
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
@@ -0,0 +1,323 @@
+use super::{Muxer, Sample, TrackFormat, OPUS_PRE_SKIP};
+
+use std::io::{Result, Write};
+
+/// The clock of the video tracks, as for RTP.
+const VIDEO_TIMESCALE: u32 = 90000;
+
+/// The clock of the audio tracks, Opus always decodes at 48kHz.
+const AUDIO_TIMESCALE: u32 = 48000;
+
+/// Fragments without video keyframes are cut after this long, in microseconds.
+const MAX_FRAGMENT_DURATION: i64 = 1_000_000;
+
+const SAMPLE_IS_SYNC: u32 = 0x0200_0000;
+const SAMPLE_IS_NOT_SYNC: u32 = 0x0101_0000;
+
+const IDENTITY: [u32; 9] = [0x0001_0000, 0, 0, 0, 0x0001_0000, 0, 0, 0, 0x4000_0000];
+
+fn mp4_box(kind: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(body.len() + 8);
+    bytes.extend((body.len() as u32 + 8).to_be_bytes());
+    bytes.extend(kind);
+    bytes.extend_from_slice(body);
+    bytes
+}
+
+fn full_box(kind: &[u8; 4], version: u8, flags: u32, body: &[u8]) -> Vec<u8> {
+    let mut header = flags.to_be_bytes();
+    header[0] = version;
+    mp4_box(kind, &[&header[..], body].concat())
+}
+
+fn u16s(values: &[u16]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_be_bytes()).collect()
+}
+
+fn u32s(values: &[u32]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_be_bytes()).collect()
+}
+
+struct PendingSample {
+    data: Vec<u8>,
+    timestamp_us: i64,
+    keyframe: bool,
+}
+
+struct Track {
+    format: TrackFormat,
+    timescale: u32,
+    /// The decode time of the first sample of the next fragment.
+    decode_time: Option<u64>,
+    /// The duration of a sample is only known once the next one is received.
+    pending: Option<PendingSample>,
+    last_duration: u32,
+    samples: Vec<(Vec<u8>, u32, bool)>,
+}
+
+impl Track {
+    fn ticks(&self, timestamp_us: i64) -> u64 {
+        timestamp_us.max(0) as u64 * self.timescale as u64 / 1_000_000
+    }
+
+    /// Completes the pending sample with the duration until the given time, or with
+    /// the duration of the previous sample at the end of the recording.
+    fn complete(&mut self, next: Option<i64>) {
+        if let Some(sample) = self.pending.take() {
+            let start = self.ticks(sample.timestamp_us);
+            self.decode_time.get_or_insert(start);
+            let duration = match next {
+                Some(next) => self.ticks(next).saturating_sub(start).max(1) as u32,
+                None => self.last_duration,
+            };
+
+            self.last_duration = duration;
+            self.samples.push((sample.data, duration, sample.keyframe));
+        }
+    }
+}
+
+/// A fragmented MP4 muxer of VP8 and Opus.
+///
+/// The movie header does not index any sample, every fragment carries its own samples
+/// so the file is playable up to the last complete fragment even if it is not finished.
+pub(crate) struct Mp4<W> {
+    writer: W,
+    tracks: Vec<Track>,
+    sequence: u32,
+    fragment_start: Option<i64>,
+}
+
+impl<W: Write> Mp4<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            tracks: Vec::new(),
+            sequence: 0,
+            fragment_start: None,
+        }
+    }
+
+    fn has_video(&self) -> bool {
+        self.tracks.iter().any(|t| matches!(t.format, TrackFormat::Video { .. }))
+    }
+
+    fn sample_entry(format: &TrackFormat) -> Vec<u8> {
+        match *format {
+            TrackFormat::Video { width, height } => {
+                let mut body = vec![0; 6];
+                body.extend(u16s(&[1, 0, 0]));
+                body.extend(u32s(&[0, 0, 0]));
+                body.extend(u16s(&[width as u16, height as u16]));
+                body.extend(u32s(&[0x0048_0000, 0x0048_0000, 0]));
+                body.extend(u16s(&[1]));
+                body.extend([0; 32]);
+                body.extend(u16s(&[0x0018, 0xFFFF]));
+
+                // profile 0, level 1.0, 8 bits 4:2:0, bt.709 colors.
+                let vpcc = [0, 10, 0x80, 1, 1, 1, 0, 0];
+                body.extend(full_box(b"vpcC", 1, 0, &vpcc));
+                mp4_box(b"vp08", &body)
+            }
+            TrackFormat::Audio { channels } => {
+                let mut body = vec![0; 6];
+                body.extend(u16s(&[1]));
+                body.extend(u32s(&[0, 0]));
+                body.extend(u16s(&[channels as u16, 16, 0, 0]));
+                body.extend(u32s(&[AUDIO_TIMESCALE << 16]));
+
+                let mut dops = vec![0, channels as u8];
+                dops.extend(OPUS_PRE_SKIP.to_be_bytes());
+                dops.extend(AUDIO_TIMESCALE.to_be_bytes());
+                dops.extend([0, 0, 0]);
+                body.extend(mp4_box(b"dOps", &dops));
+                mp4_box(b"Opus", &body)
+            }
+        }
+    }
+
+    fn trak(id: u32, track: &Track) -> Vec<u8> {
+        let (width, height, volume, handler, name): (u32, u32, u16, &[u8; 4], &[u8]) = match track.format {
+            TrackFormat::Video { width, height } => (width, height, 0, b"vide", b"VideoHandler\0"),
+            TrackFormat::Audio { .. } => (0, 0, 0x0100, b"soun", b"SoundHandler\0"),
+        };
+
+        let mut tkhd = u32s(&[0, 0, id, 0, 0, 0, 0]);
+        tkhd.extend(u16s(&[0, 0, volume, 0]));
+        tkhd.extend(u32s(&IDENTITY));
+        tkhd.extend(u32s(&[width << 16, height << 16]));
+
+        let mut mdhd = u32s(&[0, 0, track.timescale, 0]);
+        mdhd.extend(u16s(&[0x55C4, 0]));
+
+        let mut hdlr = u32s(&[0]);
+        hdlr.extend(handler);
+        hdlr.extend(u32s(&[0, 0, 0]));
+        hdlr.extend(name);
+
+        let media_header = match track.format {
+            TrackFormat::Video { .. } => full_box(b"vmhd", 0, 1, &[0; 8]),
+            TrackFormat::Audio { .. } => full_box(b"smhd", 0, 0, &[0; 4]),
+        };
+
+        let dinf = mp4_box(b"dinf", &full_box(b"dref", 0, 0, &[
+            &u32s(&[1])[..],
+            &full_box(b"url ", 0, 1, &[]),
+        ].concat()));
+
+        let stbl = mp4_box(b"stbl", &[
+            full_box(b"stsd", 0, 0, &[u32s(&[1]), Self::sample_entry(&track.format)].concat()),
+            full_box(b"stts", 0, 0, &u32s(&[0])),
+            full_box(b"stsc", 0, 0, &u32s(&[0])),
+            full_box(b"stsz", 0, 0, &u32s(&[0, 0])),
+            full_box(b"stco", 0, 0, &u32s(&[0])),
+        ].concat());
+
+        mp4_box(b"trak", &[
+            full_box(b"tkhd", 0, 3, &tkhd),
+            mp4_box(b"mdia", &[
+                full_box(b"mdhd", 0, 0, &mdhd),
+                full_box(b"hdlr", 0, 0, &hdlr),
+                mp4_box(b"minf", &[media_header, dinf, stbl].concat()),
+            ].concat()),
+        ].concat())
+    }
+
+    /// Writes the completed samples of every track as one fragment.
+    fn flush_fragment(&mut self) -> Result<()> {
+        if self.tracks.iter().all(|t| t.samples.is_empty()) {
+            return Ok(());
+        }
+
+        self.sequence += 1;
+
+        // the offsets of the samples depend on the size of the moof itself,
+        // which does not depend on the offsets, so it is built twice.
+        let build = |tracks: &[Track], sequence: u32, moof_size: u32| {
+            let mut trafs = Vec::new();
+            let mut offset = moof_size + 8;
+            for (i, track) in tracks.iter().enumerate() {
+                if track.samples.is_empty() {
+                    continue;
+                }
+
+                let mut trun = u32s(&[track.samples.len() as u32, offset]);
+                for (data, duration, keyframe) in &track.samples {
+                    let flags = if *keyframe { SAMPLE_IS_SYNC } else { SAMPLE_IS_NOT_SYNC };
+                    trun.extend(u32s(&[*duration, data.len() as u32, flags]));
+                    offset += data.len() as u32;
+                }
+
+                trafs.push(mp4_box(b"traf", &[
+                    full_box(b"tfhd", 0, 0x020000, &u32s(&[i as u32 + 1])),
+                    full_box(b"tfdt", 1, 0, &track.decode_time.unwrap_or(0).to_be_bytes()),
+                    full_box(b"trun", 0, 0x000701, &trun),
+                ].concat()));
+            }
+
+            mp4_box(b"moof", &[
+                full_box(b"mfhd", 0, 0, &u32s(&[sequence])),
+                trafs.concat(),
+            ].concat())
+        };
+
+        let size = build(&self.tracks, self.sequence, 0).len() as u32;
+        let moof = build(&self.tracks, self.sequence, size);
+
+        let mut mdat = Vec::new();
+        for track in &mut self.tracks {
+            for (data, duration, _) in track.samples.drain(..) {
+                mdat.extend(data);
+                if let Some(decode_time) = track.decode_time.as_mut() {
+                    *decode_time += duration as u64;
+                }
+            }
+        }
+
+        self.writer.write_all(&moof)?;
+        self.writer.write_all(&mp4_box(b"mdat", &mdat))
+    }
+}
+
+impl<W: Write + Send> Muxer for Mp4<W> {
+    fn start(&mut self, tracks: &[TrackFormat]) -> Result<()> {
+        self.tracks = tracks.iter().map(|format| {
+            let (timescale, last_duration) = match format {
+                TrackFormat::Video { .. } => (VIDEO_TIMESCALE, VIDEO_TIMESCALE / 30),
+                TrackFormat::Audio { .. } => (AUDIO_TIMESCALE, AUDIO_TIMESCALE / 50),
+            };
+
+            Track {
+                format: *format,
+                decode_time: None,
+                pending: None,
+                samples: Vec::new(),
+                last_duration,
+                timescale,
+            }
+        }).collect();
+
+        let mut ftyp = b"iso5".to_vec();
+        ftyp.extend(u32s(&[512]));
+        ftyp.extend(b"iso5iso6mp41");
+        self.writer.write_all(&mp4_box(b"ftyp", &ftyp))?;
+
+        let mut mvhd = u32s(&[0, 0, 1000, 0, 0x0001_0000]);
+        mvhd.extend(u16s(&[0x0100, 0]));
+        mvhd.extend(u32s(&[0, 0]));
+        mvhd.extend(u32s(&IDENTITY));
+        mvhd.extend(u32s(&[0; 6]));
+        mvhd.extend(u32s(&[self.tracks.len() as u32 + 1]));
+
+        let traks = self.tracks.iter().enumerate()
+            .map(|(i, track)| Self::trak(i as u32 + 1, track))
+            .collect::<Vec<_>>();
+        let trexs = (0..self.tracks.len())
+            .map(|i| full_box(b"trex", 0, 0, &u32s(&[i as u32 + 1, 1, 0, 0, 0])))
+            .collect::<Vec<_>>();
+
+        self.writer.write_all(&mp4_box(b"moov", &[
+            full_box(b"mvhd", 0, 0, &mvhd),
+            traks.concat(),
+            mp4_box(b"mvex", &trexs.concat()),
+        ].concat()))
+    }
+
+    fn write(&mut self, track: usize, sample: &Sample) -> Result<()> {
+        // fragments start at video keyframes so that every fragment can be decoded on
+        // its own, audio only files are cut by duration.
+        let is_video = matches!(self.tracks[track].format, TrackFormat::Video { .. });
+        let cut = match self.fragment_start {
+            None => false,
+            Some(_) if self.has_video() => is_video && sample.keyframe,
+            Some(start) => sample.timestamp_us - start >= MAX_FRAGMENT_DURATION,
+        };
+
+        // the pending samples of the other tracks are carried over to the next fragment.
+        if cut {
+            self.tracks[track].complete(Some(sample.timestamp_us));
+            self.flush_fragment()?;
+            self.fragment_start = None;
+        }
+
+        self.fragment_start.get_or_insert(sample.timestamp_us);
+        let track = &mut self.tracks[track];
+        track.complete(Some(sample.timestamp_us));
+        track.pending = Some(PendingSample {
+            data: sample.data.clone(),
+            timestamp_us: sample.timestamp_us,
+            keyframe: sample.keyframe,
+        });
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        for track in &mut self.tracks {
+            track.complete(None);
+        }
+
+        self.flush_fragment()?;
+        self.writer.flush()
+    }
+}
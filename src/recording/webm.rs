@@ -0,0 +1,297 @@
+use super::{opus_head, Muxer, Sample, TrackFormat, OPUS_PRE_SKIP};
+
+use std::io::{Result, Seek, SeekFrom, Write};
+
+const EBML: u32 = 0x1A45DFA3;
+const EBML_VERSION: u32 = 0x4286;
+const EBML_READ_VERSION: u32 = 0x42F7;
+const EBML_MAX_ID_LENGTH: u32 = 0x42F2;
+const EBML_MAX_SIZE_LENGTH: u32 = 0x42F3;
+const DOC_TYPE: u32 = 0x4282;
+const DOC_TYPE_VERSION: u32 = 0x4287;
+const DOC_TYPE_READ_VERSION: u32 = 0x4285;
+const SEGMENT: u32 = 0x18538067;
+const SEEK_HEAD: u32 = 0x114D9B74;
+const SEEK: u32 = 0x4DBB;
+const SEEK_ID: u32 = 0x53AB;
+const SEEK_POSITION: u32 = 0x53AC;
+const VOID: u32 = 0xEC;
+const INFO: u32 = 0x1549A966;
+const TIMECODE_SCALE: u32 = 0x2AD7B1;
+const DURATION: u32 = 0x4489;
+const MUXING_APP: u32 = 0x4D80;
+const WRITING_APP: u32 = 0x5741;
+const TRACKS: u32 = 0x1654AE6B;
+const TRACK_ENTRY: u32 = 0xAE;
+const TRACK_NUMBER: u32 = 0xD7;
+const TRACK_UID: u32 = 0x73C5;
+const TRACK_TYPE: u32 = 0x83;
+const CODEC_ID: u32 = 0x86;
+const CODEC_PRIVATE: u32 = 0x63A2;
+const CODEC_DELAY: u32 = 0x56AA;
+const SEEK_PRE_ROLL: u32 = 0x56BB;
+const VIDEO: u32 = 0xE0;
+const PIXEL_WIDTH: u32 = 0xB0;
+const PIXEL_HEIGHT: u32 = 0xBA;
+const AUDIO: u32 = 0xE1;
+const SAMPLING_FREQUENCY: u32 = 0xB5;
+const CHANNELS: u32 = 0x9F;
+const CLUSTER: u32 = 0x1F43B675;
+const TIMECODE: u32 = 0xE7;
+const SIMPLE_BLOCK: u32 = 0xA3;
+const CUES: u32 = 0x1C53BB6B;
+const CUE_POINT: u32 = 0xBB;
+const CUE_TIME: u32 = 0xB3;
+const CUE_TRACK_POSITIONS: u32 = 0xB7;
+const CUE_TRACK: u32 = 0xF7;
+const CUE_CLUSTER_POSITION: u32 = 0xF1;
+
+/// The size of an element whose size is not known yet, patched when the file is finished.
+const UNKNOWN_SIZE: [u8; 8] = [0x01, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF];
+
+/// The space reserved for the seek head, enough for the info, tracks and cues.
+const SEEK_HEAD_SPACE: usize = 96;
+
+/// Clusters without video keyframes are cut after this long, in milliseconds.
+const MAX_CLUSTER_DURATION: i64 = 5000;
+
+/// Writes the size of an element as a variable length integer of the smallest width.
+fn size(value: u64) -> Vec<u8> {
+    let width = (1..=8).find(|width| value < (1 << (7 * width)) - 1).unwrap_or(8);
+    let mut bytes = value.to_be_bytes()[8 - width..].to_vec();
+    bytes[0] |= 1 << (8 - width);
+    bytes
+}
+
+fn id(id: u32) -> Vec<u8> {
+    let bytes = id.to_be_bytes();
+    let skip = bytes.iter().take_while(|b| **b == 0).count();
+    bytes[skip..].to_vec()
+}
+
+fn element(element_id: u32, body: &[u8]) -> Vec<u8> {
+    let mut bytes = id(element_id);
+    bytes.extend(size(body.len() as u64));
+    bytes.extend_from_slice(body);
+    bytes
+}
+
+fn uint(element_id: u32, value: u64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    let skip = bytes.iter().take_while(|b| **b == 0).count().min(7);
+    element(element_id, &bytes[skip..])
+}
+
+fn float(element_id: u32, value: f64) -> Vec<u8> {
+    element(element_id, &value.to_be_bytes())
+}
+
+fn master(element_id: u32, children: &[Vec<u8>]) -> Vec<u8> {
+    element(element_id, &children.concat())
+}
+
+/// A WebM muxer of VP8 and Opus, the timecodes are in milliseconds.
+///
+/// The segment is written with an unknown size and patched when the file is finished,
+/// along with the duration and the seek head pointing to the cues of the keyframes.
+pub(crate) struct WebM<W> {
+    writer: W,
+    segment: u64,
+    duration: u64,
+    info: u64,
+    tracks: u64,
+    has_video: bool,
+    is_video: Vec<bool>,
+    cluster: Vec<u8>,
+    cluster_timecode: Option<i64>,
+    cues: Vec<(i64, u64, usize)>,
+    last_timecode: i64,
+}
+
+impl<W: Write + Seek> WebM<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            segment: 0,
+            duration: 0,
+            info: 0,
+            tracks: 0,
+            has_video: false,
+            is_video: Vec::new(),
+            cluster: Vec::new(),
+            cluster_timecode: None,
+            cues: Vec::new(),
+            last_timecode: 0,
+        }
+    }
+
+    fn position(&mut self) -> Result<u64> {
+        self.writer.stream_position()
+    }
+
+    fn flush_cluster(&mut self) -> Result<()> {
+        if let Some(timecode) = self.cluster_timecode.take() {
+            let mut body = uint(TIMECODE, timecode.max(0) as u64);
+            body.append(&mut self.cluster);
+            self.writer.write_all(&element(CLUSTER, &body))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<W: Write + Seek + Send> Muxer for WebM<W> {
+    fn start(&mut self, tracks: &[TrackFormat]) -> Result<()> {
+        self.writer.write_all(&master(EBML, &[
+            uint(EBML_VERSION, 1),
+            uint(EBML_READ_VERSION, 1),
+            uint(EBML_MAX_ID_LENGTH, 4),
+            uint(EBML_MAX_SIZE_LENGTH, 8),
+            element(DOC_TYPE, b"webm"),
+            uint(DOC_TYPE_VERSION, 4),
+            uint(DOC_TYPE_READ_VERSION, 2),
+        ]))?;
+
+        self.writer.write_all(&id(SEGMENT))?;
+        self.writer.write_all(&UNKNOWN_SIZE)?;
+        self.segment = self.position()?;
+
+        // the seek head is only known at the end, a void element keeps its place.
+        let mut void = id(VOID);
+        void.extend(size((SEEK_HEAD_SPACE - 2) as u64));
+        void.resize(SEEK_HEAD_SPACE, 0);
+        self.writer.write_all(&void)?;
+
+        self.info = self.position()? - self.segment;
+        let info = [
+            float(DURATION, 0.0),
+            uint(TIMECODE_SCALE, 1_000_000),
+            element(MUXING_APP, b"webrtc-rs"),
+            element(WRITING_APP, b"webrtc-rs"),
+        ].concat();
+
+        // the duration comes first, its value follows the headers of both elements.
+        let headers = id(INFO).len() + size(info.len() as u64).len() + id(DURATION).len() + 1;
+        self.duration = self.segment + self.info + headers as u64;
+        self.writer.write_all(&element(INFO, &info))?;
+
+        self.tracks = self.position()? - self.segment;
+        self.is_video = tracks.iter().map(|t| matches!(t, TrackFormat::Video { .. })).collect();
+        self.has_video = self.is_video.contains(&true);
+        let entries = tracks.iter().enumerate().map(|(i, format)| {
+            let mut children = vec![
+                uint(TRACK_NUMBER, i as u64 + 1),
+                uint(TRACK_UID, i as u64 + 1),
+            ];
+
+            match *format {
+                TrackFormat::Video { width, height } => {
+                    children.push(uint(TRACK_TYPE, 1));
+                    children.push(element(CODEC_ID, b"V_VP8"));
+                    children.push(master(VIDEO, &[
+                        uint(PIXEL_WIDTH, width as u64),
+                        uint(PIXEL_HEIGHT, height as u64),
+                    ]));
+                }
+                TrackFormat::Audio { channels } => {
+                    children.push(uint(TRACK_TYPE, 2));
+                    children.push(element(CODEC_ID, b"A_OPUS"));
+                    children.push(element(CODEC_PRIVATE, &opus_head(channels)));
+                    children.push(uint(CODEC_DELAY, OPUS_PRE_SKIP as u64 * 1_000_000_000 / 48000));
+                    children.push(uint(SEEK_PRE_ROLL, 80_000_000));
+                    children.push(master(AUDIO, &[
+                        float(SAMPLING_FREQUENCY, 48000.0),
+                        uint(CHANNELS, channels as u64),
+                    ]));
+                }
+            }
+
+            master(TRACK_ENTRY, &children)
+        }).collect::<Vec<_>>();
+
+        self.writer.write_all(&master(TRACKS, &entries))
+    }
+
+    fn write(&mut self, track: usize, sample: &Sample) -> Result<()> {
+        let timecode = sample.timestamp_us / 1000;
+        let video_keyframe = sample.keyframe && self.is_video[track];
+        self.last_timecode = self.last_timecode.max(timecode);
+
+        // clusters start at video keyframes so that the cues can point to them,
+        // audio only files are cut by duration.
+        let cut = match self.cluster_timecode {
+            None => true,
+            Some(start) => {
+                (self.has_video && video_keyframe)
+                    || (!self.has_video && timecode - start >= MAX_CLUSTER_DURATION)
+                    || timecode - start > i16::MAX as i64
+            }
+        };
+
+        if cut {
+            self.flush_cluster()?;
+            self.cluster_timecode = Some(timecode);
+            if !self.has_video || video_keyframe {
+                let position = self.position()? - self.segment;
+                self.cues.push((timecode, position, track + 1));
+            }
+        }
+
+        let relative = (timecode - self.cluster_timecode.unwrap_or(timecode))
+            .clamp(i16::MIN as i64, i16::MAX as i64) as i16;
+        let mut block = size(track as u64 + 1);
+        block.extend(relative.to_be_bytes());
+        block.push(if sample.keyframe { 0x80 } else { 0 });
+        block.extend_from_slice(&sample.data);
+        self.cluster.extend(element(SIMPLE_BLOCK, &block));
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.flush_cluster()?;
+
+        let cues = self.position()? - self.segment;
+        let points = self.cues.iter().map(|(time, position, track)| {
+            master(CUE_POINT, &[
+                uint(CUE_TIME, (*time).max(0) as u64),
+                master(CUE_TRACK_POSITIONS, &[
+                    uint(CUE_TRACK, *track as u64),
+                    uint(CUE_CLUSTER_POSITION, *position),
+                ]),
+            ])
+        }).collect::<Vec<_>>();
+
+        self.writer.write_all(&master(CUES, &points))?;
+        let end = self.position()?;
+
+        let seek = |target: u32, position: u64| {
+            master(SEEK, &[
+                element(SEEK_ID, &target.to_be_bytes()),
+                element(SEEK_POSITION, &position.to_be_bytes()),
+            ])
+        };
+
+        let mut seek_head = master(SEEK_HEAD, &[
+            seek(INFO, self.info),
+            seek(TRACKS, self.tracks),
+            seek(CUES, cues),
+        ]);
+
+        seek_head.extend(id(VOID));
+        seek_head.extend(size((SEEK_HEAD_SPACE - seek_head.len() - 2) as u64));
+        seek_head.resize(SEEK_HEAD_SPACE, 0);
+        self.writer.seek(SeekFrom::Start(self.segment))?;
+        self.writer.write_all(&seek_head)?;
+
+        self.writer.seek(SeekFrom::Start(self.duration))?;
+        self.writer.write_all(&(self.last_timecode as f64).to_be_bytes())?;
+
+        let mut segment_size = (end - self.segment).to_be_bytes();
+        segment_size[0] = 0x01;
+        self.writer.seek(SeekFrom::Start(self.segment - 8))?;
+        self.writer.write_all(&segment_size)?;
+
+        self.writer.seek(SeekFrom::Start(end))?;
+        self.writer.flush()
+    }
+}
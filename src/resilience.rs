@@ -0,0 +1,198 @@
+use crate::{ConnectionState, Error, RTCPeerConnection, RTCSessionDescription, Result};
+
+use futures::stream::{self, Stream};
+use futures::StreamExt;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// The backoff of the ICE restarts of a connection.
+#[derive(Clone, Debug)]
+pub struct ReconnectPolicy {
+    /// How long a disconnected connection is given to recover by itself before it is restarted,
+    /// a disconnection is often a short loss of connectivity which ICE gets over.
+    pub disconnected_grace: Duration,
+    /// The delay before the first restart, the delay of every restart is the delay
+    /// of the previous one multiplied by the multiplier, up to the maximum delay.
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub multiplier: f64,
+    /// How long a restart is given to reconnect before the next one is scheduled.
+    pub attempt_timeout: Duration,
+    /// The number of restarts before giving up, None to never give up.
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            disconnected_grace: Duration::from_secs(3),
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            multiplier: 2.0,
+            attempt_timeout: Duration::from_secs(10),
+            max_attempts: Some(8),
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// The delay before a restart, the first attempt is 1.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = self.multiplier.max(1.0).powi(attempt.saturating_sub(1) as i32);
+        self.initial_delay.mul_f64(factor).min(self.max_delay)
+    }
+}
+
+/// The lifecycle of a reconnection.
+pub enum ReconnectEvent {
+    /// A restart is scheduled after the delay, the connection may still recover before it.
+    Scheduled { attempt: u32, delay: Duration },
+    /// ICE is being restarted, the offer has been set as the local description and must be
+    /// sent to the remote peer, whose answer is then set as the remote description.
+    Restarting {
+        attempt: u32,
+        offer: RTCSessionDescription,
+    },
+    /// The offer of a restart could not be created or set, the next restart is scheduled.
+    RestartFailed { attempt: u32, error: Error },
+    /// The connection is connected again after the given number of restarts, zero when it
+    /// recovered by itself while a restart was scheduled.
+    Reconnected { attempts: u32 },
+    /// The connection did not reconnect within the attempts of the policy, it is left as is.
+    GaveUp { attempts: u32 },
+}
+
+enum Phase {
+    /// The connection is connected, or has not connected yet.
+    Watching,
+    /// The connection is disconnected and is given the grace period to recover.
+    Grace(Instant),
+    /// The next restart has to be scheduled.
+    Backoff(u32),
+    /// A restart is scheduled at the deadline.
+    Waiting(u32, Instant),
+    Restart(u32),
+    /// A restart has been sent, it is given until the deadline to reconnect.
+    Awaiting(u32, Instant),
+    Done,
+}
+
+/// What happened to the connection while waiting for a deadline.
+enum Wait {
+    Connected,
+    Failed,
+    Closed,
+    Elapsed,
+}
+
+/// The state machine restarting ICE when a connection is lost.
+///
+/// A restart needs a new offer and answer, so only one end of the connection should
+/// run it, usually the one which created the first offer, the other end answers the
+/// offers of the restarts as it answers any other offer.
+pub struct Reconnector {
+    policy: ReconnectPolicy,
+}
+
+impl Reconnector {
+    pub fn new(policy: ReconnectPolicy) -> Self {
+        Self { policy }
+    }
+
+    /// Watches the state of the connection, restarts ICE with an exponential backoff when the
+    /// connection fails or stays disconnected beyond the grace period, and yields the lifecycle
+    /// of every reconnection. The stream ends when the connection is closed or the policy
+    /// gives up.
+    ///
+    /// The reconnector only acts while the stream is being polled.
+    pub fn watch(self, peer: &RTCPeerConnection) -> impl Stream<Item = ReconnectEvent> + '_ {
+        let states = peer.on_connection_state_change().boxed();
+        stream::unfold((self, states, Phase::Watching), move |(this, mut states, mut phase)| async move {
+            loop {
+                let (next, event) = match phase {
+                    Phase::Done => return None,
+                    Phase::Watching => match states.next().await {
+                        Some(ConnectionState::Disconnected) => {
+                            (Phase::Grace(Instant::now() + this.policy.disconnected_grace), None)
+                        }
+                        Some(ConnectionState::Failed) => (Phase::Backoff(1), None),
+                        Some(ConnectionState::Close) | None => (Phase::Done, None),
+                        Some(_) => (Phase::Watching, None),
+                    },
+                    Phase::Grace(deadline) => match wait(&mut states, deadline).await {
+                        Wait::Connected => (Phase::Watching, None),
+                        Wait::Failed | Wait::Elapsed => (Phase::Backoff(1), None),
+                        Wait::Closed => (Phase::Done, None),
+                    },
+                    Phase::Backoff(attempt) => {
+                        if this.policy.max_attempts.is_some_and(|max| attempt > max) {
+                            let attempts = attempt - 1;
+                            (Phase::Done, Some(ReconnectEvent::GaveUp { attempts }))
+                        } else {
+                            let delay = this.policy.delay(attempt);
+                            let event = ReconnectEvent::Scheduled { attempt, delay };
+                            (Phase::Waiting(attempt, Instant::now() + delay), Some(event))
+                        }
+                    }
+                    Phase::Waiting(attempt, deadline) => match wait(&mut states, deadline).await {
+                        Wait::Connected => (Phase::Watching, Some(ReconnectEvent::Reconnected {
+                            attempts: attempt - 1,
+                        })),
+                        // a failure while waiting does not bring the restart forward.
+                        Wait::Failed => (Phase::Waiting(attempt, deadline), None),
+                        Wait::Elapsed => (Phase::Restart(attempt), None),
+                        Wait::Closed => (Phase::Done, None),
+                    },
+                    Phase::Restart(attempt) => match restart(peer).await {
+                        Ok(offer) => {
+                            let deadline = Instant::now() + this.policy.attempt_timeout;
+                            (Phase::Awaiting(attempt, deadline), Some(ReconnectEvent::Restarting {
+                                attempt,
+                                offer,
+                            }))
+                        }
+                        Err(error) => (Phase::Backoff(attempt + 1), Some(ReconnectEvent::RestartFailed {
+                            attempt,
+                            error,
+                        })),
+                    },
+                    Phase::Awaiting(attempt, deadline) => match wait(&mut states, deadline).await {
+                        Wait::Connected => (Phase::Watching, Some(ReconnectEvent::Reconnected {
+                            attempts: attempt,
+                        })),
+                        // the connection stays failed until the answer of the remote peer is set.
+                        Wait::Failed => (Phase::Awaiting(attempt, deadline), None),
+                        Wait::Elapsed => (Phase::Backoff(attempt + 1), None),
+                        Wait::Closed => (Phase::Done, None),
+                    },
+                };
+
+                phase = next;
+                if let Some(event) = event {
+                    return Some((event, (this, states, phase)));
+                }
+            }
+        })
+    }
+}
+
+/// Waits for the connection to connect, fail or close until the deadline.
+async fn wait(states: &mut (impl Stream<Item = ConnectionState> + Unpin), deadline: Instant) -> Wait {
+    loop {
+        match tokio::time::timeout_at(deadline, states.next()).await {
+            Err(_) => return Wait::Elapsed,
+            Ok(Some(ConnectionState::Connected)) => return Wait::Connected,
+            Ok(Some(ConnectionState::Failed)) => return Wait::Failed,
+            Ok(Some(ConnectionState::Close)) | Ok(None) => return Wait::Closed,
+            Ok(Some(_)) => (),
+        }
+    }
+}
+
+async fn restart(peer: &RTCPeerConnection) -> Result<RTCSessionDescription> {
+    peer.restart_ice();
+    let offer = peer.create_offer().await?;
+    let sent = RTCSessionDescription::new(offer.get_type(), offer.get_sdp()?)?;
+    peer.set_local_description(offer).await?;
+    Ok(sent)
+}
@@ -0,0 +1,1013 @@
+use crate::{Error, Result};
+
+use std::fmt;
+use std::str::FromStr;
+
+/// The largest message of a data channel when the description announces no limit, see RFC 8841.
+pub const DEFAULT_MAX_MESSAGE_SIZE: u64 = 64 * 1024;
+
+/// The codecs which only come along another codec, such as the retransmissions, or carry
+/// no media, a section with nothing else can not be received.
+const AUXILIARY_CODECS: [&str; 6] = ["rtx", "red", "ulpfec", "flexfec-03", "telephone-event", "CN"];
+
+/// The names of the static payload types, which may be used without an "a=rtpmap" attribute.
+const STATIC_PAYLOAD_TYPES: [(&str, &str); 3] = [("0", "PCMU"), ("8", "PCMA"), ("9", "G722")];
+
+/// The values of an attribute among the lines of a section or of the session.
+fn attributes<'a>(lines: &'a [String], name: &'a str) -> impl Iterator<Item = &'a str> {
+    lines.iter().filter_map(move |line| {
+        let attribute = line.strip_prefix("a=")?.strip_prefix(name)?;
+        match attribute.strip_prefix(':') {
+            Some(value) => Some(value),
+            None => attribute.is_empty().then_some(""),
+        }
+    })
+}
+
+/// The names without their repetitions, such as the several profiles of H264.
+fn unique(names: Vec<String>) -> Vec<String> {
+    let mut unique = Vec::with_capacity(names.len());
+    for name in names {
+        if !unique.contains(&name) {
+            unique.push(name);
+        }
+    }
+
+    unique
+}
+
+/// A session description split into its session level lines and its media sections.
+///
+/// The lines are kept as they are, so a description is written back unchanged apart
+/// from the edits made to it, which is what the munging helpers rely on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionDescription {
+    /// The lines before the first media section, such as "v=0".
+    pub session: Vec<String>,
+    pub media: Vec<MediaSection>,
+}
+
+/// A media section, from its "m=" line to the next one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MediaSection {
+    /// "audio", "video" or "application".
+    pub kind: String,
+    /// The port, followed by the number of ports when there are several, such as "9".
+    pub port: String,
+    /// The transport protocol, such as "UDP/TLS/RTP/SAVPF".
+    pub protocol: String,
+    /// The payload types of the section, in the order of preference.
+    pub formats: Vec<String>,
+    /// The lines following the "m=" line.
+    pub lines: Vec<String>,
+}
+
+/// A codec of a media section, described by its "a=rtpmap" attribute.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Codec {
+    pub payload_type: String,
+    /// The name of the codec, such as "opus" or "H264".
+    pub name: String,
+    pub clock_rate: u32,
+    /// The number of channels of an audio codec.
+    pub channels: Option<u32>,
+    /// The format specific parameters of the "a=fmtp" attribute, such as "minptime=10;useinbandfec=1".
+    pub parameters: Option<String>,
+}
+
+/// A change of a media section between two descriptions of a connection, see
+/// `SessionDescription::diff`, the sections are named after their mid.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SdpChange {
+    SectionAdded { mid: String, kind: String },
+    /// The section is missing from the new description, which JSEP never does, a section
+    /// which is no longer used is rejected instead.
+    SectionRemoved { mid: String, kind: String },
+    SectionRejected { mid: String },
+    /// The codecs which carry media, in the order of preference, see `MediaSection::media_codecs`.
+    CodecsChanged { mid: String, from: Vec<String>, to: Vec<String> },
+    /// The direction, such as from "sendrecv" to "recvonly" when the remote peer stops sending.
+    DirectionChanged { mid: String, from: String, to: String },
+    /// The ICE credentials changed, the connection gathers and checks its candidates again.
+    IceRestarted { mid: String },
+}
+
+impl fmt::Display for SdpChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SectionAdded { mid, kind } => write!(f, "{} section {} added", kind, mid),
+            Self::SectionRemoved { mid, kind } => write!(f, "{} section {} removed", kind, mid),
+            Self::SectionRejected { mid } => write!(f, "section {} rejected", mid),
+            Self::CodecsChanged { mid, from, to } => {
+                write!(f, "section {} codecs {} -> {}", mid, from.join(","), to.join(","))
+            }
+            Self::DirectionChanged { mid, from, to } => {
+                write!(f, "section {} direction {} -> {}", mid, from, to)
+            }
+            Self::IceRestarted { mid } => write!(f, "section {} ice restarted", mid),
+        }
+    }
+}
+
+/// The settings of the opus encoder, expressed as format parameters of the opus payload types.
+///
+/// The parameters are preferences of the receiving side, libwebrtc configures its opus encoder
+/// from the ones of the remote description. So a receiver wanting stereo applies them to its
+/// local description before it is sent, and a sender forcing them applies them to the remote
+/// description before it is set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OpusConfig {
+    /// Two channels instead of one, required for music.
+    pub stereo: bool,
+    /// Discontinuous transmission, which sends almost nothing during the silences.
+    pub dtx: bool,
+    /// Inband forward error correction, which recovers from a lost packet with the next one.
+    pub fec: bool,
+    /// The target bitrate of the encoder, in bits per second, between 6000 and 510000.
+    pub max_average_bitrate: Option<u32>,
+    /// The duration of the audio in a packet, in milliseconds, such as 20 or 60.
+    pub ptime: Option<u32>,
+}
+
+impl Default for OpusConfig {
+    fn default() -> Self {
+        Self {
+            stereo: false,
+            dtx: false,
+            fec: true,
+            max_average_bitrate: None,
+            ptime: None,
+        }
+    }
+}
+
+impl OpusConfig {
+    /// Stereo at 128 kbps without dtx, which would cut the quiet passages.
+    pub fn music() -> Self {
+        Self {
+            stereo: true,
+            dtx: false,
+            fec: true,
+            max_average_bitrate: Some(128_000),
+            ptime: None,
+        }
+    }
+}
+
+impl FromStr for SessionDescription {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
+        let mut desc = Self {
+            session: Vec::new(),
+            media: Vec::new(),
+        };
+
+        for line in s.lines().map(|line| line.trim_end()).filter(|line| !line.is_empty()) {
+            let bytes = line.as_bytes();
+            if bytes.len() < 2 || !bytes[0].is_ascii_alphabetic() || bytes[1] != b'=' {
+                return Err(Error::InvalidSdp(format!("invalid line: {}", line)));
+            }
+
+            if let Some(media) = line.strip_prefix("m=") {
+                let mut tokens = media.split_whitespace();
+                let mut next = |name: &str| {
+                    tokens
+                        .next()
+                        .map(|token| token.to_string())
+                        .ok_or_else(|| Error::InvalidSdp(format!("media line is missing the {}: {}", name, line)))
+                };
+
+                desc.media.push(MediaSection {
+                    kind: next("media")?,
+                    port: next("port")?,
+                    protocol: next("protocol")?,
+                    formats: tokens.map(|token| token.to_string()).collect(),
+                    lines: Vec::new(),
+                });
+            } else if let Some(section) = desc.media.last_mut() {
+                section.lines.push(line.to_string());
+            } else {
+                desc.session.push(line.to_string());
+            }
+        }
+
+        if desc.session.is_empty() {
+            return Err(Error::InvalidSdp("the description has no session lines".to_string()));
+        }
+
+        Ok(desc)
+    }
+}
+
+impl fmt::Display for SessionDescription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.session {
+            write!(f, "{}\r\n", line)?;
+        }
+
+        for section in &self.media {
+            write!(f, "{}", section)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl SessionDescription {
+    /// The values of a session level attribute, see `MediaSection::attributes`.
+    pub fn attributes<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> {
+        attributes(&self.session, name)
+    }
+
+    /// Checks the description for the mistakes libwebrtc only reports with an opaque error,
+    /// such as a section without a DTLS fingerprint or an answer whose media ids do not
+    /// match the offer, the answer is checked against the offer it answers.
+    pub fn validate(&self, offer: Option<&SessionDescription>) -> Result<()> {
+        let invalid = |reason: String| Err(Error::InvalidSdp(reason));
+        let mut mids = Vec::new();
+        for (index, section) in self.media.iter().enumerate() {
+            let Some(mid) = section.mid() else {
+                return invalid(format!("the {} section {} has no mid", section.kind, index));
+            };
+
+            if mids.contains(&mid) {
+                return invalid(format!("the mid {} is used by two media sections", mid));
+            }
+
+            mids.push(mid);
+            if section.is_rejected() || section.attributes("bundle-only").next().is_some() {
+                continue;
+            }
+
+            let has = |name| section.attributes(name).next().is_some() || self.attributes(name).next().is_some();
+            if !has("fingerprint") {
+                return invalid(format!(
+                    "the {} section {} has no dtls fingerprint, the media must be encrypted with dtls-srtp",
+                    section.kind, mid
+                ));
+            }
+
+            if !has("ice-ufrag") || !has("ice-pwd") {
+                return invalid(format!("the {} section {} has no ice credentials", section.kind, mid));
+            }
+        }
+
+        for group in self.attributes("group") {
+            if let Some(bundle) = group.strip_prefix("BUNDLE") {
+                if let Some(mid) = bundle.split_whitespace().find(|mid| !mids.contains(mid)) {
+                    return invalid(format!("the bundle group refers to the unknown mid {}", mid));
+                }
+            }
+        }
+
+        let Some(offer) = offer else {
+            return Ok(());
+        };
+
+        // the sections of an answer are those of the offer, in the same order.
+        if self.media.len() != offer.media.len() {
+            return invalid(format!(
+                "the answer has {} media sections, the offer has {}",
+                self.media.len(),
+                offer.media.len()
+            ));
+        }
+
+        for (index, (answer, offer)) in self.media.iter().zip(&offer.media).enumerate() {
+            if answer.mid() != offer.mid() {
+                return invalid(format!(
+                    "the media section {} has the mid {} in the answer and {} in the offer",
+                    index,
+                    answer.mid().unwrap_or_default(),
+                    offer.mid().unwrap_or_default()
+                ));
+            }
+
+            if answer.kind != offer.kind {
+                return invalid(format!(
+                    "the media section {} is {} in the answer and {} in the offer",
+                    answer.mid().unwrap_or_default(),
+                    answer.kind,
+                    offer.kind
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The changes of the media sections from this description to a newer one of the same
+    /// connection, such as the description a renegotiation applies, in the order of the
+    /// sections. The sections are matched by their mid, or by their position without one.
+    ///
+    /// ```no_run
+    /// use webrtc::sdp::SessionDescription;
+    ///
+    /// # fn example(current: &str, new: &str) -> webrtc::Result<()> {
+    /// let current: SessionDescription = current.parse()?;
+    /// for change in current.diff(&new.parse()?) {
+    ///     println!("renegotiated: {}", change);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn diff(&self, new: &SessionDescription) -> Vec<SdpChange> {
+        let key = |index: usize, section: &MediaSection| {
+            section.mid().map(|mid| mid.to_string()).unwrap_or_else(|| index.to_string())
+        };
+
+        let old = self
+            .media
+            .iter()
+            .enumerate()
+            .map(|(index, section)| (key(index, section), section))
+            .collect::<Vec<_>>();
+        let mut changes = Vec::new();
+        for (index, section) in new.media.iter().enumerate() {
+            let mid = key(index, section);
+            let Some((_, previous)) = old.iter().find(|(previous, _)| *previous == mid) else {
+                changes.push(SdpChange::SectionAdded {
+                    kind: section.kind.clone(),
+                    mid,
+                });
+
+                continue;
+            };
+
+            if section.is_rejected() {
+                if !previous.is_rejected() {
+                    changes.push(SdpChange::SectionRejected { mid });
+                }
+
+                continue;
+            }
+
+            let (from, to) = (previous.direction(), section.direction());
+            if from != to {
+                changes.push(SdpChange::DirectionChanged {
+                    mid: mid.clone(),
+                    from: from.to_string(),
+                    to: to.to_string(),
+                });
+            }
+
+            let (from, to) = (unique(previous.media_codecs()), unique(section.media_codecs()));
+            if from != to {
+                changes.push(SdpChange::CodecsChanged {
+                    mid: mid.clone(),
+                    from,
+                    to,
+                });
+            }
+
+            let ufrag = |desc: &SessionDescription, section: &MediaSection| {
+                section
+                    .attributes("ice-ufrag")
+                    .next()
+                    .or_else(|| desc.attributes("ice-ufrag").next())
+                    .map(|ufrag| ufrag.to_string())
+            };
+
+            if ufrag(self, previous) != ufrag(new, section) {
+                changes.push(SdpChange::IceRestarted { mid });
+            }
+        }
+
+        for (mid, section) in old {
+            if !new.media.iter().enumerate().any(|(index, new)| key(index, new) == mid) {
+                changes.push(SdpChange::SectionRemoved {
+                    kind: section.kind.clone(),
+                    mid,
+                });
+            }
+        }
+
+        changes
+    }
+
+    /// The media section with the given media id.
+    pub fn section(&self, mid: &str) -> Option<&MediaSection> {
+        self.media.iter().find(|section| section.mid() == Some(mid))
+    }
+
+    pub fn section_mut(&mut self, mid: &str) -> Option<&mut MediaSection> {
+        self.media.iter_mut().find(|section| section.mid() == Some(mid))
+    }
+
+    /// The media sections of a kind, such as "video".
+    pub fn sections_mut<'a>(&'a mut self, kind: &'a str) -> impl Iterator<Item = &'a mut MediaSection> {
+        self.media.iter_mut().filter(move |section| section.kind == kind)
+    }
+
+    /// Sets the bandwidth of every media section of a kind, see `MediaSection::set_bandwidth`.
+    pub fn set_bandwidth(&mut self, kind: &str, kbps: u32) {
+        self.sections_mut(kind).for_each(|section| section.set_bandwidth(kbps));
+    }
+
+    /// Removes a codec from every media section, see `MediaSection::strip_codec`.
+    pub fn strip_codec(&mut self, name: &str) {
+        self.media.iter_mut().for_each(|section| section.strip_codec(name));
+    }
+
+    /// Moves the codecs to the front of every media section of a kind, see `MediaSection::prefer_codecs`.
+    pub fn prefer_codecs(&mut self, kind: &str, names: &[&str]) {
+        self.sections_mut(kind).for_each(|section| section.prefer_codecs(names));
+    }
+
+    /// Configures the opus codec of every audio section, see `MediaSection::set_opus`.
+    pub fn set_opus(&mut self, config: &OpusConfig) {
+        self.sections_mut("audio").for_each(|section| section.set_opus(config));
+    }
+
+    /// Announces the largest message received by the data channels, see `MediaSection::set_max_message_size`.
+    pub fn set_max_message_size(&mut self, size: u64) {
+        self.sections_mut("application")
+            .for_each(|section| section.set_max_message_size(size));
+    }
+
+    /// Forces the profile of every H264 codec, see `MediaSection::set_h264_profile_level_id`.
+    pub fn set_h264_profile_level_id(&mut self, profile_level_id: &str) {
+        self.sections_mut("video")
+            .for_each(|section| section.set_h264_profile_level_id(profile_level_id));
+    }
+}
+
+impl fmt::Display for MediaSection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "m={} {} {}", self.kind, self.port, self.protocol)?;
+        for format in &self.formats {
+            write!(f, " {}", format)?;
+        }
+
+        write!(f, "\r\n")?;
+        for line in &self.lines {
+            write!(f, "{}\r\n", line)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl MediaSection {
+    /// The values of an attribute, such as "0" for "a=mid:0", the value of
+    /// a flag attribute such as "a=rtcp-mux" is empty.
+    pub fn attributes<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> {
+        attributes(&self.lines, name)
+    }
+
+    /// The direction of the section, "sendrecv", "sendonly", "recvonly" or "inactive",
+    /// a section without a direction attribute sends and receives.
+    pub fn direction(&self) -> &str {
+        ["sendrecv", "sendonly", "recvonly", "inactive"]
+            .into_iter()
+            .find(|direction| self.attributes(direction).next().is_some())
+            .unwrap_or("sendrecv")
+    }
+
+    /// Whether the section is rejected or stopped, with a port of zero, a section only
+    /// sent over the transport of its bundle group has a port of zero too but is active.
+    pub fn is_rejected(&self) -> bool {
+        self.port.split('/').next() == Some("0") && self.attributes("bundle-only").next().is_none()
+    }
+
+    /// The names of the codecs which carry media, such as "opus" or "VP8", without the
+    /// retransmission and the error correction codecs, see `codecs`.
+    pub fn media_codecs(&self) -> Vec<String> {
+        let codecs = self.codecs();
+        self.formats
+            .iter()
+            .filter_map(|format| match codecs.iter().find(|codec| codec.payload_type == *format) {
+                Some(codec) => Some(codec.name.clone()),
+                None => STATIC_PAYLOAD_TYPES
+                    .iter()
+                    .find(|(payload_type, _)| payload_type == format)
+                    .map(|(_, name)| name.to_string()),
+            })
+            .filter(|name| !AUXILIARY_CODECS.iter().any(|auxiliary| auxiliary.eq_ignore_ascii_case(name)))
+            .collect()
+    }
+
+    /// The media id of the section.
+    pub fn mid(&self) -> Option<&str> {
+        self.attributes("mid").next()
+    }
+
+    /// The ssrcs of the section, in the order of their "a=ssrc" lines.
+    pub fn ssrcs(&self) -> Vec<u32> {
+        let mut ssrcs = Vec::new();
+        for value in self.attributes("ssrc") {
+            if let Some(Ok(ssrc)) = value.split(' ').next().map(str::parse) {
+                if !ssrcs.contains(&ssrc) {
+                    ssrcs.push(ssrc);
+                }
+            }
+        }
+
+        ssrcs
+    }
+
+    /// The RTCP canonical name of the section, from its "a=ssrc:<ssrc> cname:<cname>" lines.
+    pub fn cname(&self) -> Option<&str> {
+        self.attributes("ssrc")
+            .find_map(|value| value.split_once(' ')?.1.strip_prefix("cname:"))
+    }
+
+    /// The first stream of the section, from its "a=msid:<stream> <track>" attribute, which is
+    /// the sync group the receiver of the section plays its track in, None without a stream.
+    pub fn sync_group(&self) -> Option<&str> {
+        self.attributes("msid")
+            .filter_map(|msid| msid.split(' ').next())
+            .find(|stream| *stream != "-")
+    }
+
+    /// Moves the track of the section to another sync group, or to none for a receiver which
+    /// plays it as soon as it is received, for the lowest latency. It is applied to the remote
+    /// description before it is set, the "a=msid" attributes are replaced by a single one.
+    pub fn set_sync_group(&mut self, group: Option<&str>) {
+        let Some(track) = self
+            .attributes("msid")
+            .find_map(|msid| msid.split_once(' ').map(|(_, track)| track.to_string()))
+        else {
+            return;
+        };
+
+        let msid = format!("{} {}", group.unwrap_or("-"), track);
+        let at = self.lines.iter().position(|line| line.starts_with("a=msid:"));
+        self.lines.retain(|line| !line.starts_with("a=msid:"));
+        self.lines.insert(at.unwrap_or(self.lines.len()), format!("a=msid:{}", msid));
+
+        // the legacy ssrc attributes carry the same stream.
+        for line in &mut self.lines {
+            if let Some((ssrc, _)) = line
+                .strip_prefix("a=ssrc:")
+                .and_then(|rest| rest.split_once(" msid:"))
+            {
+                *line = format!("a=ssrc:{} msid:{}", ssrc, msid);
+            }
+        }
+    }
+
+    /// The codecs of the section, in the order of the payload types of the media line.
+    pub fn codecs(&self) -> Vec<Codec> {
+        let mut codecs = self
+            .attributes("rtpmap")
+            .filter_map(|rtpmap| {
+                let (payload_type, encoding) = rtpmap.split_once(' ')?;
+                let mut encoding = encoding.split('/');
+                let name = encoding.next()?.to_string();
+                let clock_rate = encoding.next()?.parse().ok()?;
+                let channels = encoding.next().and_then(|channels| channels.parse().ok());
+                let parameters = self.parameters(payload_type).map(|parameters| parameters.to_string());
+                Some(Codec {
+                    payload_type: payload_type.to_string(),
+                    name,
+                    clock_rate,
+                    channels,
+                    parameters,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        codecs.sort_by_key(|codec| self.formats.iter().position(|format| *format == codec.payload_type));
+        codecs
+    }
+
+    /// The payload types of a codec, compared case insensitively.
+    pub fn payload_types(&self, name: &str) -> Vec<String> {
+        self.codecs()
+            .into_iter()
+            .filter(|codec| codec.name.eq_ignore_ascii_case(name))
+            .map(|codec| codec.payload_type)
+            .collect()
+    }
+
+    /// The format specific parameters of a payload type.
+    pub fn parameters(&self, payload_type: &str) -> Option<&str> {
+        self.attributes("fmtp").find_map(|fmtp| {
+            let (pt, parameters) = fmtp.split_once(' ')?;
+            (pt == payload_type).then_some(parameters)
+        })
+    }
+
+    /// Sets a format specific parameter of a payload type, such as "stereo=1",
+    /// the "a=fmtp" attribute is added when the payload type has none.
+    pub fn set_parameter(&mut self, payload_type: &str, key: &str, value: &str) {
+        let prefix = format!("a=fmtp:{} ", payload_type);
+        if let Some(line) = self.lines.iter_mut().find(|line| line.starts_with(&prefix)) {
+            let mut parameters = line[prefix.len()..]
+                .split(';')
+                .map(|parameter| parameter.trim().to_string())
+                .filter(|parameter| !parameter.is_empty())
+                .collect::<Vec<_>>();
+            let pair = format!("{}={}", key, value);
+            match parameters
+                .iter_mut()
+                .find(|parameter| parameter.split('=').next() == Some(key))
+            {
+                Some(parameter) => *parameter = pair,
+                None => parameters.push(pair),
+            }
+
+            *line = format!("{}{}", prefix, parameters.join(";"));
+            return;
+        }
+
+        // the fmtp attribute follows the rtpmap attribute of the payload type.
+        let rtpmap = format!("a=rtpmap:{} ", payload_type);
+        let at = self
+            .lines
+            .iter()
+            .position(|line| line.starts_with(&rtpmap))
+            .map(|i| i + 1)
+            .unwrap_or(self.lines.len());
+        self.lines.insert(at, format!("{}{}={}", prefix, key, value));
+    }
+
+    /// Limits the bandwidth of the section, with a "b=AS" line in kilobits per second and
+    /// a "b=TIAS" line in bits per second, which replace the existing bandwidth lines.
+    pub fn set_bandwidth(&mut self, kbps: u32) {
+        self.lines.retain(|line| !line.starts_with("b="));
+
+        // the bandwidth lines follow the title and the connection lines.
+        let at = self
+            .lines
+            .iter()
+            .position(|line| !line.starts_with("i=") && !line.starts_with("c="))
+            .unwrap_or(self.lines.len());
+        self.lines.insert(at, format!("b=TIAS:{}", kbps as u64 * 1000));
+        self.lines.insert(at, format!("b=AS:{}", kbps));
+    }
+
+    /// Removes the payload types from the media line along with their attributes.
+    pub fn remove_payload_types(&mut self, payload_types: &[String]) {
+        self.formats.retain(|format| !payload_types.contains(format));
+        self.lines.retain(|line| {
+            let attribute = ["a=rtpmap:", "a=fmtp:", "a=rtcp-fb:"]
+                .iter()
+                .find_map(|prefix| line.strip_prefix(prefix));
+            match attribute.and_then(|attribute| attribute.split(' ').next()) {
+                Some(payload_type) => !payload_types.iter().any(|pt| pt == payload_type),
+                None => true,
+            }
+        });
+    }
+
+    /// Removes a codec, compared case insensitively, along with the retransmission
+    /// payload types associated with it, such as every "H264" payload type.
+    pub fn strip_codec(&mut self, name: &str) {
+        let mut payload_types = self.payload_types(name);
+        let associated = self
+            .codecs()
+            .into_iter()
+            .filter(|codec| {
+                codec.parameters.as_deref().is_some_and(|parameters| {
+                    parameters
+                        .split(';')
+                        .filter_map(|parameter| parameter.trim().strip_prefix("apt="))
+                        .any(|apt| payload_types.iter().any(|pt| pt == apt))
+                })
+            })
+            .map(|codec| codec.payload_type)
+            .collect::<Vec<_>>();
+
+        payload_types.extend(associated);
+        self.remove_payload_types(&payload_types);
+    }
+
+    /// Moves the payload types of the codecs to the front of the media line, in the order
+    /// of the names, which makes them the preferred codecs of the section.
+    pub fn prefer_codecs(&mut self, names: &[&str]) {
+        let codecs = self.codecs();
+        let rank = |format: &String| {
+            codecs
+                .iter()
+                .find(|codec| codec.payload_type == *format)
+                .and_then(|codec| names.iter().position(|name| codec.name.eq_ignore_ascii_case(name)))
+                .unwrap_or(names.len())
+        };
+
+        // the sort is stable, the other payload types keep their order.
+        self.formats.sort_by_key(rank);
+    }
+
+    /// Forces the "profile-level-id" of every H264 payload type, such as "42e01f"
+    /// for the constrained baseline profile at level 3.1.
+    pub fn set_h264_profile_level_id(&mut self, profile_level_id: &str) {
+        for payload_type in self.payload_types("H264") {
+            self.set_parameter(&payload_type, "profile-level-id", profile_level_id);
+        }
+    }
+
+    /// The largest message received by the data channels of an application section,
+    /// from its "a=max-message-size" attribute, zero when there is no limit.
+    pub fn max_message_size(&self) -> Option<u64> {
+        self.attributes("max-message-size").next()?.trim().parse().ok()
+    }
+
+    /// Replaces the "a=max-message-size" attribute of an application section, zero for no limit.
+    /// It is a limit of the receiving side, so it is applied to the local description before
+    /// it is sent, the remote peer then refuses to send larger messages.
+    pub fn set_max_message_size(&mut self, size: u64) {
+        self.lines.retain(|line| !line.starts_with("a=max-message-size:"));
+        self.lines.push(format!("a=max-message-size:{}", size));
+    }
+
+    /// Configures every opus payload type of the section, the "a=ptime" attribute
+    /// is replaced when the config has a packet duration.
+    pub fn set_opus(&mut self, config: &OpusConfig) {
+        let flag = |enabled: bool| if enabled { "1" } else { "0" };
+        for payload_type in self.payload_types("opus") {
+            self.set_parameter(&payload_type, "stereo", flag(config.stereo));
+            self.set_parameter(&payload_type, "sprop-stereo", flag(config.stereo));
+            self.set_parameter(&payload_type, "usedtx", flag(config.dtx));
+            self.set_parameter(&payload_type, "useinbandfec", flag(config.fec));
+            if let Some(bitrate) = config.max_average_bitrate {
+                self.set_parameter(&payload_type, "maxaveragebitrate", &bitrate.to_string());
+            }
+        }
+
+        if let Some(ptime) = config.ptime {
+            self.lines.retain(|line| !line.starts_with("a=ptime:"));
+            self.lines.push(format!("a=ptime:{}", ptime));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OFFER: &str = "v=0\r
+o=- 4611731400430051336 2 IN IP4 127.0.0.1\r
+s=-\r
+t=0 0\r
+a=group:BUNDLE 0 1 2\r
+a=fingerprint:sha-256 19:E2:1C:3B:4B:9F:81:E6:B8:5C:F4:A5:A8:D8:73:04:BB:05:2F:70:9F:04:A9:0E:05:E9:26:33:E8:70:88:A2\r
+a=ice-ufrag:EsAw\r
+a=ice-pwd:P2uYro0UCOQ4zxjKXaWCBui1\r
+m=audio 9 UDP/TLS/RTP/SAVPF 111 0\r
+c=IN IP4 0.0.0.0\r
+a=mid:0\r
+a=sendrecv\r
+a=msid:stream audio-track\r
+a=rtpmap:111 opus/48000/2\r
+a=fmtp:111 minptime=10;useinbandfec=1\r
+a=rtpmap:0 PCMU/8000\r
+a=ssrc:1001 cname:4TOk42mSjXCkVIa6\r
+a=ssrc:1001 msid:stream audio-track\r
+m=video 9 UDP/TLS/RTP/SAVPF 96 97 102 103\r
+c=IN IP4 0.0.0.0\r
+a=mid:1\r
+a=recvonly\r
+a=rtpmap:96 VP8/90000\r
+a=rtcp-fb:96 nack\r
+a=rtpmap:97 rtx/90000\r
+a=fmtp:97 apt=96\r
+a=rtpmap:102 H264/90000\r
+a=rtcp-fb:102 nack\r
+a=fmtp:102 level-asymmetry-allowed=1;packetization-mode=1;profile-level-id=42001f\r
+a=rtpmap:103 rtx/90000\r
+a=fmtp:103 apt=102\r
+m=application 9 UDP/DTLS/SCTP webrtc-datachannel\r
+c=IN IP4 0.0.0.0\r
+a=mid:2\r
+a=sctp-port:5000\r
+a=max-message-size:262144\r
+";
+
+    fn offer() -> SessionDescription {
+        OFFER.parse().unwrap()
+    }
+
+    fn edit(desc: &str, from: &str, to: &str) -> SessionDescription {
+        assert!(desc.contains(from), "{}", from);
+        desc.replacen(from, to, 1).parse().unwrap()
+    }
+
+    #[test]
+    fn descriptions_round_trip() {
+        let desc = offer();
+        assert_eq!(desc.session.len(), 8);
+        assert_eq!(desc.media.len(), 3);
+        assert_eq!(desc.media[1].kind, "video");
+        assert_eq!(desc.media[1].port, "9");
+        assert_eq!(desc.media[1].protocol, "UDP/TLS/RTP/SAVPF");
+        assert_eq!(desc.media[1].formats, ["96", "97", "102", "103"]);
+        assert_eq!(desc.to_string(), OFFER);
+
+        // the line endings are written as CRLF whatever they were read as.
+        assert_eq!(OFFER.replace("\r\n", "\n").parse::<SessionDescription>().unwrap(), desc);
+
+        for desc in ["", "m=audio 9 UDP/TLS/RTP/SAVPF 111\r\n", "v=0\r\nnot a line\r\n", "v=0\r\nm=audio 9\r\n"] {
+            assert!(matches!(desc.parse::<SessionDescription>(), Err(Error::InvalidSdp(_))), "{:?}", desc);
+        }
+    }
+
+    #[test]
+    fn media_sections() {
+        let desc = offer();
+        let audio = desc.section("0").unwrap();
+        assert_eq!(audio.direction(), "sendrecv");
+        assert!(!audio.is_rejected());
+        assert_eq!(audio.media_codecs(), ["opus", "PCMU"]);
+        assert_eq!(audio.ssrcs(), [1001]);
+        assert_eq!(audio.cname(), Some("4TOk42mSjXCkVIa6"));
+        assert_eq!(audio.sync_group(), Some("stream"));
+        assert_eq!(audio.parameters("111"), Some("minptime=10;useinbandfec=1"));
+
+        let codecs = audio.codecs();
+        assert_eq!(codecs.len(), 2);
+        assert_eq!(codecs[0].name, "opus");
+        assert_eq!(codecs[0].clock_rate, 48000);
+        assert_eq!(codecs[0].channels, Some(2));
+        assert_eq!(codecs[1].parameters, None);
+
+        let video = desc.section("1").unwrap();
+        assert_eq!(video.direction(), "recvonly");
+        assert_eq!(video.media_codecs(), ["VP8", "H264"]);
+        assert_eq!(video.payload_types("h264"), ["102"]);
+        assert_eq!(video.payload_types("rtx"), ["97", "103"]);
+        assert_eq!(video.sync_group(), None);
+
+        let application = desc.section("2").unwrap();
+        assert_eq!(application.max_message_size(), Some(262144));
+        assert!(desc.section("3").is_none());
+
+        let desc = edit(OFFER, "m=video 9", "m=video 0");
+        assert!(desc.section("1").unwrap().is_rejected());
+        let desc = edit(OFFER, "m=video 9 UDP/TLS/RTP/SAVPF 96 97 102 103\r\n", "m=video 0 UDP/TLS/RTP/SAVPF 96 97 102 103\r\na=bundle-only\r\n");
+        assert!(!desc.section("1").unwrap().is_rejected());
+    }
+
+    #[test]
+    fn valid_descriptions() {
+        offer().validate(None).unwrap();
+        offer().validate(Some(&offer())).unwrap();
+
+        // the credentials can be in the sections rather than at the session level.
+        let moved = OFFER
+            .replace("a=ice-ufrag:EsAw\r\n", "")
+            .replace("a=mid:", "a=ice-ufrag:EsAw\r\na=mid:");
+        moved.parse::<SessionDescription>().unwrap().validate(None).unwrap();
+
+        // a rejected or bundle-only section has no transport of its own.
+        let bare = "v=0\r\ns=-\r\nm=audio 0 UDP/TLS/RTP/SAVPF 0\r\na=mid:0\r\nm=video 0 UDP/TLS/RTP/SAVPF 96\r\na=mid:1\r\na=bundle-only\r\n";
+        bare.parse::<SessionDescription>().unwrap().validate(None).unwrap();
+    }
+
+    #[test]
+    fn invalid_descriptions() {
+        let invalid = |desc: SessionDescription, offer: Option<&SessionDescription>| {
+            assert!(matches!(desc.validate(offer), Err(Error::InvalidSdp(_))), "{}", desc);
+        };
+
+        invalid(edit(OFFER, "a=mid:1\r\n", ""), None);
+        invalid(edit(OFFER, "a=mid:1", "a=mid:0"), None);
+        invalid(edit(OFFER, "a=fingerprint:", "a=setup:"), None);
+        invalid(edit(OFFER, "a=ice-pwd:", "a=ice-options:"), None);
+        invalid(edit(OFFER, "a=group:BUNDLE 0 1 2", "a=group:BUNDLE 0 1 3"), None);
+
+        let mut answer = offer();
+        answer.media.pop();
+        invalid(answer, Some(&offer()));
+
+        let mut answer = offer();
+        answer.media.swap(0, 1);
+        invalid(answer, Some(&offer()));
+
+        let answer = edit(OFFER, "m=application 9 UDP/DTLS/SCTP", "m=video 9 UDP/DTLS/SCTP");
+        invalid(answer, Some(&offer()));
+    }
+
+    #[test]
+    fn descriptions_diff() {
+        assert!(offer().diff(&offer()).is_empty());
+
+        let new = edit(OFFER, "a=recvonly", "a=sendrecv");
+        assert_eq!(
+            offer().diff(&new),
+            [SdpChange::DirectionChanged {
+                mid: "1".to_string(),
+                from: "recvonly".to_string(),
+                to: "sendrecv".to_string(),
+            }]
+        );
+
+        // the retransmission codecs are not media codecs.
+        let mut new = offer();
+        new.section_mut("1").unwrap().strip_codec("VP8");
+        assert_eq!(
+            offer().diff(&new),
+            [SdpChange::CodecsChanged {
+                mid: "1".to_string(),
+                from: vec!["VP8".to_string(), "H264".to_string()],
+                to: vec!["H264".to_string()],
+            }]
+        );
+
+        let new = edit(OFFER, "a=ice-ufrag:EsAw", "a=ice-ufrag:Xk9q");
+        assert_eq!(
+            offer().diff(&new),
+            ["0", "1", "2"].map(|mid| SdpChange::IceRestarted { mid: mid.to_string() })
+        );
+
+        let new = edit(OFFER, "m=video 9", "m=video 0");
+        assert_eq!(offer().diff(&new), [SdpChange::SectionRejected { mid: "1".to_string() }]);
+        assert!(new.diff(&new).is_empty());
+
+        let mut new = offer();
+        let mut section = new.media.remove(1);
+        section.lines.retain(|line| !line.starts_with("a=mid:"));
+        section.lines.insert(0, "a=mid:3".to_string());
+        new.media.push(section);
+        assert_eq!(
+            offer().diff(&new),
+            [
+                SdpChange::SectionAdded {
+                    mid: "3".to_string(),
+                    kind: "video".to_string(),
+                },
+                SdpChange::SectionRemoved {
+                    mid: "1".to_string(),
+                    kind: "video".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn munging() {
+        let mut desc = offer();
+        desc.set_bandwidth("video", 500);
+        let video = desc.section("1").unwrap();
+        assert_eq!(&video.lines[..3], ["c=IN IP4 0.0.0.0", "b=AS:500", "b=TIAS:500000"]);
+
+        desc.set_bandwidth("video", 300);
+        assert_eq!(desc.section("1").unwrap().lines.iter().filter(|line| line.starts_with("b=")).count(), 2);
+        assert!(desc.section("0").unwrap().lines.iter().all(|line| !line.starts_with("b=")));
+
+        // the retransmissions of the codec go along with it.
+        let mut desc = offer();
+        desc.strip_codec("h264");
+        let video = desc.section("1").unwrap();
+        assert_eq!(video.formats, ["96", "97"]);
+        assert!(video.lines.iter().all(|line| !line.contains(":102 ") && !line.contains(":103 ")));
+
+        let mut desc = offer();
+        desc.prefer_codecs("video", &["H264", "rtx"]);
+        assert_eq!(desc.section("1").unwrap().formats, ["102", "97", "103", "96"]);
+        desc.prefer_codecs("audio", &["PCMU"]);
+        assert_eq!(desc.section("0").unwrap().formats, ["0", "111"]);
+
+        let mut desc = offer();
+        desc.set_opus(&OpusConfig {
+            ptime: Some(60),
+            ..OpusConfig::music()
+        });
+        let audio = desc.section("0").unwrap();
+        assert_eq!(
+            audio.parameters("111"),
+            Some("minptime=10;useinbandfec=1;stereo=1;sprop-stereo=1;usedtx=0;maxaveragebitrate=128000")
+        );
+        assert_eq!(audio.attributes("ptime").collect::<Vec<_>>(), ["60"]);
+
+        let mut desc = offer();
+        desc.set_max_message_size(0);
+        assert_eq!(desc.section("2").unwrap().max_message_size(), Some(0));
+        assert_eq!(desc.section("2").unwrap().attributes("max-message-size").count(), 1);
+
+        let mut desc = offer();
+        desc.set_h264_profile_level_id("42e01f");
+        assert_eq!(
+            desc.section("1").unwrap().parameters("102"),
+            Some("level-asymmetry-allowed=1;packetization-mode=1;profile-level-id=42e01f")
+        );
+
+        // a payload type without an fmtp attribute gets one after its rtpmap.
+        let mut desc = offer();
+        desc.section_mut("0").unwrap().set_parameter("0", "ptime", "20");
+        let audio = desc.section("0").unwrap();
+        let at = audio.lines.iter().position(|line| line == "a=rtpmap:0 PCMU/8000").unwrap();
+        assert_eq!(audio.lines[at + 1], "a=fmtp:0 ptime=20");
+    }
+
+    #[test]
+    fn sync_groups() {
+        let mut desc = offer();
+        let audio = desc.section_mut("0").unwrap();
+        audio.set_sync_group(None);
+        assert_eq!(audio.sync_group(), None);
+        assert_eq!(audio.attributes("msid").collect::<Vec<_>>(), ["- audio-track"]);
+        assert!(audio.lines.contains(&"a=ssrc:1001 msid:- audio-track".to_string()));
+
+        audio.set_sync_group(Some("other"));
+        assert_eq!(audio.sync_group(), Some("other"));
+        assert!(audio.lines.contains(&"a=ssrc:1001 msid:other audio-track".to_string()));
+
+        // a section without a track is left alone.
+        let video = desc.section_mut("1").unwrap();
+        let lines = video.lines.clone();
+        video.set_sync_group(Some("other"));
+        assert_eq!(video.lines, lines);
+    }
+}
@@ -0,0 +1,678 @@
+use crate::ffi::observer::Observer;
+use crate::ffi::raw;
+use crate::ice::RelayProtocol;
+use crate::{Error, RTCPeerConnection, Result, WebRtcRuntime};
+
+use futures::task::AtomicWaker;
+use libc::*;
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::ffi::CStr;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+use std::task::*;
+use std::time::{Duration, Instant};
+
+/// Statistics of a pair of local and remote ICE candidates.
+///
+/// The selected pair carries the transport level measurements of the connection,
+/// such as the round trip time and the bandwidth estimation.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RTCIceCandidatePairStats {
+    pub id: String,
+    /// The time, in milliseconds, at which the statistics were collected.
+    pub timestamp: f64,
+    pub transport_id: Option<String>,
+    pub local_candidate_id: Option<String>,
+    pub remote_candidate_id: Option<String>,
+    /// The state of the checklist for the candidate pair,
+    /// one of "frozen", "waiting", "in-progress", "failed" or "succeeded".
+    pub state: Option<String>,
+    /// Whether the candidate pair has been nominated for use.
+    pub nominated: Option<bool>,
+    pub bytes_sent: Option<u64>,
+    pub bytes_received: Option<u64>,
+    /// The total time, in seconds, that has elapsed between sending STUN requests and
+    /// receiving the responses to them.
+    pub total_round_trip_time: Option<f64>,
+    /// The latest round trip time measured in seconds.
+    pub current_round_trip_time: Option<f64>,
+    /// The estimated available bandwidth, in bits per second, for outgoing traffic.
+    pub available_outgoing_bitrate: Option<f64>,
+    /// The estimated available bandwidth, in bits per second, for incoming traffic.
+    pub available_incoming_bitrate: Option<f64>,
+}
+
+/// Statistics of a local or remote ICE candidate.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RTCIceCandidateStats {
+    pub id: String,
+    pub timestamp: f64,
+    pub transport_id: Option<String>,
+    pub address: Option<String>,
+    pub port: Option<u16>,
+    /// "udp" or "tcp".
+    pub protocol: Option<String>,
+    /// One of "host", "srflx", "prflx" or "relay".
+    pub candidate_type: Option<String>,
+    pub priority: Option<u32>,
+    /// The url of the STUN or TURN server the local candidate was obtained from.
+    pub url: Option<String>,
+    /// The protocol between the peer and the TURN server, only for the local relay candidates,
+    /// which is how a TLS relay can be told apart from a UDP one.
+    pub relay_protocol: Option<RelayProtocol>,
+}
+
+/// Statistics of an outgoing RTP stream.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RTCOutboundRtpStreamStats {
+    pub id: String,
+    pub timestamp: f64,
+    pub ssrc: Option<u32>,
+    /// "audio" or "video".
+    pub kind: Option<String>,
+    pub packets_sent: Option<u64>,
+    pub bytes_sent: Option<u64>,
+    /// The current target bitrate, in bits per second, of the encoder.
+    pub target_bitrate: Option<f64>,
+    pub frames_encoded: Option<u32>,
+    pub frame_width: Option<u32>,
+    pub frame_height: Option<u32>,
+    pub frames_per_second: Option<f64>,
+    /// What lowers the resolution or the frame rate of the video encoder.
+    pub quality_limitation_reason: Option<QualityLimitationReason>,
+    /// The number of times the resolution changed because of the quality limitation.
+    pub quality_limitation_resolution_changes: Option<u32>,
+    /// The sum of the quantization parameters of the encoded frames, a higher average
+    /// is a coarser picture, the scale depends on the codec.
+    pub qp_sum: Option<u64>,
+    /// The total time, in seconds, spent encoding the frames.
+    pub total_encode_time: Option<f64>,
+}
+
+impl RTCOutboundRtpStreamStats {
+    /// The average quantization parameter of the encoded frames, None for audio or before
+    /// any frame is encoded.
+    pub fn average_qp(&self) -> Option<f64> {
+        let frames = self.frames_encoded.filter(|frames| *frames > 0)?;
+        Some(self.qp_sum? as f64 / frames as f64)
+    }
+
+    /// The average time, in seconds, spent encoding a frame, which approaches the interval
+    /// between the frames when the encoder is CPU-limited.
+    pub fn average_encode_time(&self) -> Option<f64> {
+        let frames = self.frames_encoded.filter(|frames| *frames > 0)?;
+        Some(self.total_encode_time? / frames as f64)
+    }
+}
+
+/// Why the video encoder sends below the resolution or the frame rate of its source.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QualityLimitationReason {
+    /// The encoder is not limited.
+    None,
+    /// The encoder can not keep up with the frames on the CPU.
+    Cpu,
+    /// The estimated bandwidth is too low for the source.
+    Bandwidth,
+    Other,
+}
+
+/// Statistics of an incoming RTP stream.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RTCInboundRtpStreamStats {
+    pub id: String,
+    pub timestamp: f64,
+    pub ssrc: Option<u32>,
+    /// "audio" or "video".
+    pub kind: Option<String>,
+    pub packets_received: Option<u64>,
+    /// The number of packets lost, this may be negative when duplicates were received.
+    pub packets_lost: Option<i64>,
+    pub bytes_received: Option<u64>,
+    /// The packet jitter measured in seconds.
+    pub jitter: Option<f64>,
+    pub frames_decoded: Option<u32>,
+    pub frames_dropped: Option<u32>,
+    pub frame_width: Option<u32>,
+    pub frame_height: Option<u32>,
+    pub frames_per_second: Option<f64>,
+    /// The number of times the video froze, a frame arriving well after the previous one.
+    pub freeze_count: Option<u32>,
+    /// The total duration of the freezes in seconds.
+    pub total_freezes_duration: Option<f64>,
+    /// The number of times the video paused, no frame being rendered for 5 seconds or more,
+    /// such as when the sender stops sending, which is not counted as a freeze.
+    pub pause_count: Option<u32>,
+    /// The total duration of the pauses in seconds.
+    pub total_pauses_duration: Option<f64>,
+    /// The total time, in seconds, the rendered frames were displayed for.
+    pub total_frames_duration: Option<f64>,
+    /// The id of the track of the receiver of the stream.
+    pub track_identifier: Option<String>,
+    /// The total time, in seconds, the emitted samples or frames spent in the jitter buffer.
+    pub jitter_buffer_delay: Option<f64>,
+    pub jitter_buffer_emitted_count: Option<u64>,
+    /// The estimated time, in seconds, between the capture of the latest packet and its
+    /// reception, from the abs-capture-time header extension, see `RtpReceiver::capture_delay`.
+    #[serde(skip)]
+    pub capture_delay: Option<f64>,
+}
+
+impl RTCInboundRtpStreamStats {
+    /// The fraction of the displayed time the video was frozen, between 0 and 1, the pauses
+    /// are not counted, None for audio or before any frame is rendered.
+    pub fn freeze_ratio(&self) -> Option<f64> {
+        let frozen = self.total_freezes_duration?;
+        let displayed = self.total_frames_duration? - self.total_pauses_duration.unwrap_or(0.0);
+        (displayed > 0.0).then(|| (frozen / displayed).min(1.0))
+    }
+
+    /// The estimated time, in seconds, between the capture of the media and its rendering,
+    /// the capture delay followed by the average time in the jitter buffer, None unless the
+    /// abs-capture-time header extension is negotiated.
+    pub fn estimated_capture_to_render(&self) -> Option<f64> {
+        let buffered = match (self.jitter_buffer_delay, self.jitter_buffer_emitted_count) {
+            (Some(delay), Some(count)) if count > 0 => delay / count as f64,
+            _ => 0.0,
+        };
+
+        Some(self.capture_delay? + buffered)
+    }
+}
+
+/// Statistics of an outgoing RTP stream as received by the remote peer,
+/// from the receiver reports it sends back.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RTCRemoteInboundRtpStreamStats {
+    pub id: String,
+    pub timestamp: f64,
+    pub ssrc: Option<u32>,
+    /// "audio" or "video".
+    pub kind: Option<String>,
+    /// The id of the outgoing stream the report is about.
+    pub local_id: Option<String>,
+    pub packets_lost: Option<i64>,
+    /// The fraction of the packets lost since the previous report, between 0 and 1.
+    pub fraction_lost: Option<f64>,
+    /// The packet jitter measured by the remote peer in seconds.
+    pub jitter: Option<f64>,
+    /// The latest round trip time measured from the reports in seconds.
+    pub round_trip_time: Option<f64>,
+    /// The sum of the round trip times measured from the reports in seconds.
+    pub total_round_trip_time: Option<f64>,
+    /// The number of reports a round trip time was measured from.
+    pub round_trip_time_measurements: Option<u64>,
+}
+
+impl RTCRemoteInboundRtpStreamStats {
+    /// The average round trip time, in seconds, over every report of the stream.
+    pub fn average_round_trip_time(&self) -> Option<f64> {
+        let measurements = self.round_trip_time_measurements.filter(|count| *count > 0)?;
+        Some(self.total_round_trip_time? / measurements as f64)
+    }
+}
+
+/// Statistics of a data channel.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RTCDataChannelStats {
+    pub id: String,
+    pub timestamp: f64,
+    pub label: Option<String>,
+    pub protocol: Option<String>,
+    /// The id of the channel, see `RTCDataChannel::id`.
+    pub data_channel_identifier: Option<i32>,
+    /// One of "connecting", "open", "closing" or "closed".
+    pub state: Option<String>,
+    pub messages_sent: Option<u64>,
+    pub bytes_sent: Option<u64>,
+    pub messages_received: Option<u64>,
+    pub bytes_received: Option<u64>,
+}
+
+/// Statistics of the SCTP transport carrying the data channels.
+///
+/// Not every version of libwebrtc reports them, in which case the report has none.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RTCSctpTransportStats {
+    pub id: String,
+    pub timestamp: f64,
+    pub transport_id: Option<String>,
+    /// The latest smoothed round trip time measured in seconds.
+    pub smoothed_round_trip_time: Option<f64>,
+    /// The congestion window, in bytes.
+    pub congestion_window: Option<u32>,
+    /// The receiver window of the remote peer, in bytes.
+    pub receiver_window: Option<u32>,
+    /// The maximum size of an SCTP packet, in bytes.
+    pub mtu: Option<u32>,
+    /// The number of chunks sent which are not acknowledged yet.
+    pub unack_data: Option<u32>,
+}
+
+/// One entry of a stats report, tagged by the `type` member of the native stats object.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum RTCStats {
+    CandidatePair(RTCIceCandidatePairStats),
+    LocalCandidate(RTCIceCandidateStats),
+    RemoteCandidate(RTCIceCandidateStats),
+    OutboundRtp(RTCOutboundRtpStreamStats),
+    InboundRtp(RTCInboundRtpStreamStats),
+    RemoteInboundRtp(RTCRemoteInboundRtpStreamStats),
+    DataChannel(RTCDataChannelStats),
+    SctpTransport(RTCSctpTransportStats),
+    /// Stats types that are not modelled yet.
+    #[serde(other)]
+    Unknown,
+}
+
+/// RTCStatsReport
+///
+/// The RTCStatsReport interface provides a statistics report obtained by calling
+/// the RTCPeerConnection.getStats() method.
+#[derive(Clone, Debug, Default)]
+pub struct RTCStatsReport {
+    pub stats: Vec<RTCStats>,
+}
+
+impl RTCStatsReport {
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(Self {
+            stats: serde_json::from_str(json)?,
+        })
+    }
+
+    /// Reads the capture delays of the incoming streams from the receivers, which the
+    /// native report does not carry.
+    fn fill_capture_delays(&mut self, peer: &RTCPeerConnection) {
+        if !self.stats.iter().any(|stats| matches!(stats, RTCStats::InboundRtp(_))) {
+            return;
+        }
+
+        let receivers = peer.get_receivers();
+        for stats in &mut self.stats {
+            if let RTCStats::InboundRtp(stats) = stats {
+                if let Some(ssrc) = stats.ssrc {
+                    stats.capture_delay = receivers
+                        .iter()
+                        .find_map(|receiver| receiver.capture_delay(ssrc))
+                        .map(|delay| delay.as_secs_f64());
+                }
+            }
+        }
+    }
+
+    pub fn candidate_pairs(&self) -> impl Iterator<Item = &RTCIceCandidatePairStats> {
+        self.stats.iter().filter_map(|s| match s {
+            RTCStats::CandidatePair(pair) => Some(pair),
+            _ => None,
+        })
+    }
+
+    /// The nominated candidate pair whose checks succeeded,
+    /// this is the pair the media is currently flowing over.
+    pub fn selected_candidate_pair(&self) -> Option<&RTCIceCandidatePairStats> {
+        self.candidate_pairs().find(|pair| {
+            pair.nominated == Some(true) && pair.state.as_deref() == Some("succeeded")
+        })
+    }
+
+    pub fn local_candidates(&self) -> impl Iterator<Item = &RTCIceCandidateStats> {
+        self.stats.iter().filter_map(|s| match s {
+            RTCStats::LocalCandidate(candidate) => Some(candidate),
+            _ => None,
+        })
+    }
+
+    pub fn remote_candidates(&self) -> impl Iterator<Item = &RTCIceCandidateStats> {
+        self.stats.iter().filter_map(|s| match s {
+            RTCStats::RemoteCandidate(candidate) => Some(candidate),
+            _ => None,
+        })
+    }
+
+    /// The local candidate of the selected pair, its relay protocol tells
+    /// which transport to the TURN server the media flows over.
+    pub fn selected_local_candidate(&self) -> Option<&RTCIceCandidateStats> {
+        let id = self.selected_candidate_pair()?.local_candidate_id.as_deref()?;
+        self.local_candidates().find(|candidate| candidate.id == id)
+    }
+
+    pub fn outbound_rtp(&self) -> impl Iterator<Item = &RTCOutboundRtpStreamStats> {
+        self.stats.iter().filter_map(|s| match s {
+            RTCStats::OutboundRtp(rtp) => Some(rtp),
+            _ => None,
+        })
+    }
+
+    pub fn inbound_rtp(&self) -> impl Iterator<Item = &RTCInboundRtpStreamStats> {
+        self.stats.iter().filter_map(|s| match s {
+            RTCStats::InboundRtp(rtp) => Some(rtp),
+            _ => None,
+        })
+    }
+
+    pub fn remote_inbound_rtp(&self) -> impl Iterator<Item = &RTCRemoteInboundRtpStreamStats> {
+        self.stats.iter().filter_map(|s| match s {
+            RTCStats::RemoteInboundRtp(rtp) => Some(rtp),
+            _ => None,
+        })
+    }
+
+    /// The reports the remote peer sent back about an outgoing stream, such as the round trip
+    /// time and the loss it sees, None until the first receiver report arrives.
+    pub fn remote_inbound_rtp_for(
+        &self,
+        outbound: &RTCOutboundRtpStreamStats,
+    ) -> Option<&RTCRemoteInboundRtpStreamStats> {
+        self.remote_inbound_rtp()
+            .find(|remote| remote.local_id.as_deref() == Some(outbound.id.as_str()))
+    }
+
+    pub fn data_channels(&self) -> impl Iterator<Item = &RTCDataChannelStats> {
+        self.stats.iter().filter_map(|s| match s {
+            RTCStats::DataChannel(channel) => Some(channel),
+            _ => None,
+        })
+    }
+
+    /// The statistics of the channel with the given id, see `RTCDataChannel::id`.
+    pub fn data_channel(&self, id: i32) -> Option<&RTCDataChannelStats> {
+        self.data_channels()
+            .find(|channel| channel.data_channel_identifier == Some(id))
+    }
+
+    pub fn sctp_transport(&self) -> Option<&RTCSctpTransportStats> {
+        self.stats.iter().find_map(|s| match s {
+            RTCStats::SctpTransport(transport) => Some(transport),
+            _ => None,
+        })
+    }
+}
+
+/// The reports of a connection over a sliding window, such as for a quality report at the
+/// end of a call.
+///
+/// A metric is read from every report by a closure, such as the round trip time of the
+/// selected candidate pair, and aggregated over the reports recorded within a duration:
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// # fn example(peer: &webrtc::RTCPeerConnection) {
+/// if let Some(history) = peer.stats_history() {
+///     let rtt = |report: &webrtc::stats::RTCStatsReport| {
+///         report.selected_candidate_pair()?.current_round_trip_time
+///     };
+///
+///     let p95 = history.percentile(Duration::from_secs(60), 95.0, rtt);
+/// }
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct StatsHistory {
+    window: Duration,
+    reports: VecDeque<(Instant, RTCStatsReport)>,
+}
+
+impl StatsHistory {
+    /// Keeps the reports recorded within the window, the older ones are dropped.
+    pub fn new(window: Duration) -> Self {
+        Self {
+            reports: VecDeque::new(),
+            window,
+        }
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    pub fn record(&mut self, report: RTCStatsReport) {
+        let now = Instant::now();
+        while let Some((recorded, _)) = self.reports.front() {
+            if now.duration_since(*recorded) <= self.window {
+                break;
+            }
+
+            self.reports.pop_front();
+        }
+
+        self.reports.push_back((now, report));
+    }
+
+    /// The reports, from the oldest to the latest, along with the time they were recorded.
+    pub fn reports(&self) -> impl Iterator<Item = &(Instant, RTCStatsReport)> {
+        self.reports.iter()
+    }
+
+    /// The values of a metric in the reports recorded within the duration, in order,
+    /// the reports without the metric are skipped.
+    pub fn values(&self, within: Duration, metric: impl Fn(&RTCStatsReport) -> Option<f64>) -> Vec<f64> {
+        let now = Instant::now();
+        self.reports
+            .iter()
+            .filter(|(recorded, _)| now.duration_since(*recorded) <= within)
+            .filter_map(|(_, report)| metric(report))
+            .collect()
+    }
+
+    pub fn min(&self, within: Duration, metric: impl Fn(&RTCStatsReport) -> Option<f64>) -> Option<f64> {
+        self.values(within, metric).into_iter().reduce(f64::min)
+    }
+
+    pub fn max(&self, within: Duration, metric: impl Fn(&RTCStatsReport) -> Option<f64>) -> Option<f64> {
+        self.values(within, metric).into_iter().reduce(f64::max)
+    }
+
+    pub fn mean(&self, within: Duration, metric: impl Fn(&RTCStatsReport) -> Option<f64>) -> Option<f64> {
+        let values = self.values(within, metric);
+        (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+    }
+
+    /// The nearest rank percentile of a metric, between 0 and 100, such as 95 for the value
+    /// which 95% of the values do not exceed.
+    pub fn percentile(
+        &self,
+        within: Duration,
+        percentile: f64,
+        metric: impl Fn(&RTCStatsReport) -> Option<f64>,
+    ) -> Option<f64> {
+        let mut values = self.values(within, metric);
+        if values.is_empty() {
+            return None;
+        }
+
+        values.sort_by(f64::total_cmp);
+        let rank = (percentile.clamp(0.0, 100.0) / 100.0 * values.len() as f64).ceil() as usize;
+        Some(values[rank.saturating_sub(1)])
+    }
+}
+
+/// The threads and the memory used by a runtime and its connections, such as to tell how
+/// many connections fit on a host.
+///
+/// libwebrtc does not account for its memory, the memory of a connection is estimated from
+/// the growth of the resident memory of the process since the runtime was created, which
+/// includes the memory of the application and of the other runtimes, it is closest when
+/// the connections are the bulk of the process.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ResourceUsage {
+    /// The dedicated threads of the runtime, shared by its connections.
+    pub threads: usize,
+    /// The connections created on the runtime and not dropped yet.
+    pub connections: usize,
+    /// The resident memory of the process in bytes, None where the system does not report it.
+    pub resident_memory: Option<u64>,
+    /// The estimated memory of a connection in bytes, None without any connection.
+    pub memory_per_connection: Option<u64>,
+}
+
+pub(crate) fn resident_memory() -> Option<u64> {
+    u64::try_from(unsafe { raw::rtc_get_resident_memory() }).ok()
+}
+
+impl WebRtcRuntime {
+    pub fn resource_usage(&self) -> ResourceUsage {
+        let connections = self.inner.connections.load(Ordering::Relaxed);
+        let resident_memory = resident_memory();
+        let memory_per_connection = match (resident_memory, self.inner.baseline_memory) {
+            (Some(resident), Some(baseline)) if connections > 0 => {
+                Some(resident.saturating_sub(baseline) / connections as u64)
+            }
+            _ => None,
+        };
+
+        ResourceUsage {
+            threads: self.inner.threads,
+            connections,
+            resident_memory,
+            memory_per_connection,
+        }
+    }
+}
+
+impl RTCPeerConnection {
+    /// The usage of the runtime the connection runs on, see `WebRtcRuntime::resource_usage`.
+    pub fn resource_usage(&self) -> ResourceUsage {
+        self.inner.runtime.resource_usage()
+    }
+
+    pub fn get_stats(&self) -> GetStats {
+        GetStats::new(self.clone())
+    }
+
+    /// Records the reports read with `get_stats` over the window, until it is disabled,
+    /// the reports are only read when the application, or a monitor such as the quality
+    /// monitor, asks for them. Enabling it again replaces the recorded reports.
+    ///
+    /// The latest native reports are also kept for `dump_diagnostics` while it is enabled.
+    pub fn enable_stats_history(&self, window: Duration) {
+        let mut recorded = self.inner.observer.recorded_stats.lock().unwrap();
+        *recorded = Some(StatsHistory::new(window));
+        self.inner.observer.stats_history.clear();
+    }
+
+    pub fn disable_stats_history(&self) {
+        let mut recorded = self.inner.observer.recorded_stats.lock().unwrap();
+        *recorded = None;
+        self.inner.observer.stats_history.clear();
+    }
+
+    /// A copy of the recorded reports, None unless the history is enabled.
+    pub fn stats_history(&self) -> Option<StatsHistory> {
+        self.inner.observer.recorded_stats.lock().unwrap().clone()
+    }
+}
+
+struct GetStatsContext {
+    observer: Arc<Observer>,
+    callback: Box<dyn FnMut(Result<String>) + Send>,
+}
+
+pub struct GetStats {
+    waker: Arc<AtomicWaker>,
+    peer: RTCPeerConnection,
+    report: Arc<Mutex<Option<Result<String>>>>,
+    begin: bool,
+    // the native report, kept for the diagnostics.
+    json: Option<String>,
+    keep_json: bool,
+}
+
+impl GetStats {
+    pub(crate) fn new(peer: RTCPeerConnection) -> Self {
+        Self {
+            waker: Arc::new(AtomicWaker::new()),
+            report: Arc::new(Mutex::new(None)),
+            begin: false,
+            json: None,
+            keep_json: false,
+            peer,
+        }
+    }
+
+    /// Reads the report along with the native JSON it is parsed from.
+    pub(crate) async fn with_json(mut self) -> Result<(RTCStatsReport, String)> {
+        self.keep_json = true;
+        let report = (&mut self).await?;
+        Ok((report, self.json.take().unwrap_or_default()))
+    }
+}
+
+impl Future for GetStats {
+    type Output = Result<RTCStatsReport>;
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.waker.register(cx.waker());
+
+        if !self.begin {
+            extern "C" fn callback(json: *const c_char, ctx: *mut c_void) {
+                let ctx = unsafe { Box::from_raw(ctx as *mut GetStatsContext) };
+                let observer = ctx.observer.clone();
+                let copy = || {
+                    if json.is_null() {
+                        Err(Error::Closed)
+                    } else {
+                        unsafe { CStr::from_ptr(json) }
+                            .to_str()
+                            .map(|s| s.to_string())
+                            .map_err(Error::from)
+                    }
+                };
+
+                observer.dispatch("getstats", copy, move |_, json| {
+                    let mut ctx = ctx;
+                    (ctx.callback)(json);
+                });
+            }
+
+            let waker = self.waker.clone();
+            let report = self.report.clone();
+            let ctx = Box::new(GetStatsContext {
+                observer: self.peer.inner.observer.clone(),
+                callback: Box::new(move |json| {
+                    *report.lock().unwrap() = Some(json);
+                    waker.wake();
+                }),
+            });
+
+            unsafe { raw::rtc_get_stats(self.peer.inner.raw, Box::into_raw(ctx) as *mut c_void, callback) };
+            self.begin = true;
+            return Poll::Pending;
+        }
+
+        let result = self.report.lock().unwrap().take();
+        match result {
+            None => Poll::Pending,
+            Some(Err(e)) => Poll::Ready(Err(e)),
+            Some(Ok(json)) => {
+                let mut report = RTCStatsReport::from_json(&json);
+                if let Ok(report) = &mut report {
+                    report.fill_capture_delays(&self.peer);
+                    let observer = &self.peer.inner.observer;
+                    if let Some(history) = observer.recorded_stats.lock().unwrap().as_mut() {
+                        history.record(report.clone());
+                        observer.stats_history.record(json.clone());
+                    }
+
+                    if self.keep_json {
+                        self.json = Some(json);
+                    }
+                }
+
+                Poll::Ready(report)
+            }
+        }
+    }
+}
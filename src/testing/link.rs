@@ -0,0 +1,316 @@
+use crate::{PacketReceiver, PacketSocket, PacketSocketFactory};
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Condvar, Mutex, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The first port of the sockets bound without a port range.
+const FIRST_PORT: u16 = 40000;
+
+/// The conditions of a simulated link, each socket sends through a link of its own,
+/// so they apply to each direction of a connection on their own.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LinkConditions {
+    /// The share of the packets dropped at random, between 0 and 1.
+    pub loss: f64,
+    /// The one way delay of every packet.
+    pub delay: Duration,
+    /// Every packet is delayed further by a random duration up to the jitter,
+    /// so the packets may arrive out of order.
+    pub jitter: Duration,
+    /// The capacity of the link in bits per second, the packets wait behind the ones
+    /// being sent, None for a link without a cap.
+    pub bandwidth: Option<u64>,
+    /// The longest a packet waits behind the others under the bandwidth cap, a packet
+    /// arriving at a full queue is dropped, as by the buffer of a router.
+    pub queue: Duration,
+}
+
+impl Default for LinkConditions {
+    fn default() -> Self {
+        Self {
+            loss: 0.0,
+            delay: Duration::ZERO,
+            jitter: Duration::ZERO,
+            bandwidth: None,
+            queue: Duration::from_millis(200),
+        }
+    }
+}
+
+/// A packet on its way to a socket.
+struct Delivery {
+    at: Instant,
+    // keeps the packets due at the same instant in order.
+    seq: u64,
+    from: SocketAddr,
+    to: SocketAddr,
+    data: Vec<u8>,
+}
+
+impl PartialEq for Delivery {
+    fn eq(&self, other: &Self) -> bool {
+        (self.at, self.seq) == (other.at, other.seq)
+    }
+}
+
+impl Eq for Delivery {}
+
+impl PartialOrd for Delivery {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Delivery {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.at, self.seq).cmp(&(other.at, other.seq))
+    }
+}
+
+struct State {
+    conditions: LinkConditions,
+    sockets: HashMap<SocketAddr, PacketReceiver>,
+    deliveries: BinaryHeap<Reverse<Delivery>>,
+    next_port: u16,
+    seq: u64,
+    rng: u64,
+}
+
+impl State {
+    /// A uniform number between 0 and 1, xorshift is good enough to drop packets.
+    fn random(&mut self) -> f64 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        (self.rng >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn is_free(&self, address: SocketAddr, port: u16) -> bool {
+        port != 0 && !self.sockets.contains_key(&SocketAddr::new(address.ip(), port))
+    }
+
+    fn allocate_port(&mut self, address: SocketAddr, ports: Option<(u16, u16)>) -> Option<u16> {
+        if address.port() != 0 {
+            return self.is_free(address, address.port()).then_some(address.port());
+        }
+
+        if let Some((min, max)) = ports {
+            return (min..=max).find(|port| self.is_free(address, *port));
+        }
+
+        for _ in 0..=u16::MAX {
+            let port = self.next_port;
+            self.next_port = self.next_port.checked_add(1).unwrap_or(FIRST_PORT);
+            if self.is_free(address, port) {
+                return Some(port);
+            }
+        }
+
+        None
+    }
+}
+
+struct Network {
+    state: Mutex<State>,
+    wakeup: Condvar,
+}
+
+impl Network {
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Delivers the packets once they are due, until the network is dropped.
+    fn run(network: Weak<Network>) {
+        while let Some(network) = network.upgrade() {
+            let mut state = network.state();
+            let now = Instant::now();
+            let mut due = Vec::new();
+            while state.deliveries.peek().is_some_and(|delivery| delivery.0.at <= now) {
+                let Reverse(delivery) = state.deliveries.pop().unwrap();
+                if let Some(receiver) = state.sockets.get(&delivery.to) {
+                    due.push((receiver.clone(), delivery));
+                }
+            }
+
+            if due.is_empty() {
+                // woken up by a new packet, or in a while to notice the network is dropped.
+                let timeout = state
+                    .deliveries
+                    .peek()
+                    .map(|delivery| delivery.0.at - now)
+                    .unwrap_or(Duration::MAX)
+                    .min(Duration::from_millis(100));
+                drop(network.wakeup.wait_timeout(state, timeout));
+                continue;
+            }
+
+            drop(state);
+            for (receiver, delivery) in due {
+                receiver.receive(&delivery.data, delivery.from);
+            }
+        }
+    }
+}
+
+/// A network in the process the connections send their packets through instead of
+/// the sockets of the system, with a loss, a delay, a jitter and a bandwidth cap, so the
+/// adaptation of the bitrate and the recovery of the losses can be tested without root
+/// privileges or a traffic shaper.
+///
+/// The sockets are bound on the addresses of the network interfaces of the machine, but
+/// they only reach the sockets of the same network, the packets to any other address are
+/// dropped, so the connections only connect over their host candidates. The conditions can
+/// be changed while the packets flow, such as to cut the bandwidth in the middle of a call.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use webrtc::testing::{LinkConditions, Loopback, VideoSource};
+///
+/// # async fn example() -> webrtc::Result<()> {
+/// let loopback = Loopback::with_link(LinkConditions {
+///     loss: 0.02,
+///     delay: Duration::from_millis(40),
+///     jitter: Duration::from_millis(10),
+///     bandwidth: Some(1_000_000),
+///     ..Default::default()
+/// })?;
+///
+/// let _source = VideoSource::new(1280, 720, 30).spawn(loopback.video_track("video")?);
+/// loopback.connect(Duration::from_secs(10)).await?;
+///
+/// // the bitrate of the sender drops below the new cap.
+/// let link = loopback.link().unwrap();
+/// link.set_conditions(LinkConditions {
+///     bandwidth: Some(300_000),
+///     ..link.conditions()
+/// });
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct SimulatedNetwork {
+    network: Arc<Network>,
+}
+
+impl SimulatedNetwork {
+    pub fn new(conditions: LinkConditions) -> Self {
+        Self::with_seed(conditions, 0x2545_f491_4f6c_dd1d)
+    }
+
+    /// A network whose random losses and jitter follow from the seed, the same seed
+    /// gives the same losses to the same sequence of packets.
+    pub fn with_seed(conditions: LinkConditions, seed: u64) -> Self {
+        let network = Arc::new(Network {
+            state: Mutex::new(State {
+                sockets: HashMap::new(),
+                deliveries: BinaryHeap::new(),
+                next_port: FIRST_PORT,
+                seq: 0,
+                // xorshift never leaves zero.
+                rng: seed.max(1),
+                conditions,
+            }),
+            wakeup: Condvar::new(),
+        });
+
+        let weak = Arc::downgrade(&network);
+        thread::Builder::new()
+            .name("webrtc-link".to_string())
+            .spawn(move || Network::run(weak))
+            .expect("failed to spawn the link thread");
+        Self { network }
+    }
+
+    pub fn conditions(&self) -> LinkConditions {
+        self.network.state().conditions
+    }
+
+    /// Changes the conditions of the packets sent from now on, the packets on their way
+    /// keep the conditions they were sent with.
+    pub fn set_conditions(&self, conditions: LinkConditions) {
+        self.network.state().conditions = conditions;
+    }
+}
+
+impl PacketSocketFactory for SimulatedNetwork {
+    fn bind(
+        &self,
+        address: SocketAddr,
+        ports: Option<(u16, u16)>,
+        receiver: PacketReceiver,
+    ) -> io::Result<Box<dyn PacketSocket>> {
+        let mut state = self.network.state();
+        let port = state
+            .allocate_port(address, ports)
+            .ok_or_else(|| io::Error::from(io::ErrorKind::AddrInUse))?;
+
+        let address = SocketAddr::new(address.ip(), port);
+        state.sockets.insert(address, receiver);
+        Ok(Box::new(SimulatedSocket {
+            network: self.network.clone(),
+            busy_until: Instant::now(),
+            address,
+        }))
+    }
+}
+
+struct SimulatedSocket {
+    network: Arc<Network>,
+    address: SocketAddr,
+    /// When the link finishes sending the packets queued under the bandwidth cap.
+    busy_until: Instant,
+}
+
+impl PacketSocket for SimulatedSocket {
+    fn local_addr(&self) -> SocketAddr {
+        self.address
+    }
+
+    // a dropped packet is sent successfully, as it is by a UDP socket.
+    fn send_to(&mut self, data: &[u8], to: SocketAddr) -> io::Result<()> {
+        let mut state = self.network.state();
+        let conditions = state.conditions;
+        if !state.sockets.contains_key(&to) || state.random() < conditions.loss {
+            return Ok(());
+        }
+
+        let now = Instant::now();
+        let mut sent = now;
+        if let Some(bandwidth) = conditions.bandwidth {
+            let start = self.busy_until.max(now);
+            if start - now > conditions.queue {
+                return Ok(());
+            }
+
+            let bits = data.len() as f64 * 8.0;
+            self.busy_until = start + Duration::from_secs_f64(bits / bandwidth.max(1) as f64);
+            sent = self.busy_until;
+        }
+
+        let jitter = conditions.jitter.mul_f64(state.random());
+        let seq = state.seq;
+        state.seq += 1;
+        state.deliveries.push(Reverse(Delivery {
+            at: sent + conditions.delay + jitter,
+            from: self.address,
+            data: data.to_vec(),
+            seq,
+            to,
+        }));
+
+        self.network.wakeup.notify_one();
+        Ok(())
+    }
+}
+
+impl Drop for SimulatedSocket {
+    fn drop(&mut self) {
+        self.network.state().sockets.remove(&self.address);
+    }
+}
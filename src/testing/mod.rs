@@ -0,0 +1,362 @@
+mod link;
+
+use crate::{
+    AudioFrame,
+    Error,
+    MediaStreamTrack,
+    RTCConfiguration,
+    RTCPeerConnection,
+    RTCSessionDescription,
+    Result,
+    VideoFrame,
+    WebRtcRuntime,
+};
+
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::{self, MissedTickBehavior};
+
+pub use link::{LinkConditions, SimulatedNetwork};
+
+/// The stream id of the tracks added by a loopback.
+const STREAM_ID: &str = "loopback";
+
+/// Two connections wired together in the same process, for the integration tests of the
+/// applications built on the crate, without a signaling server.
+///
+/// The descriptions are exchanged directly once the gathering is complete, so they carry
+/// every candidate, and the connections reach each other over the host candidates of the
+/// machine, no ICE server is needed.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// use webrtc::testing::{Loopback, VideoSource};
+///
+/// # async fn example() -> webrtc::Result<()> {
+/// let loopback = Loopback::new()?;
+/// let track = loopback.video_track("video")?;
+/// let _source = VideoSource::new(320, 240, 30).spawn(track);
+/// loopback.connect(Duration::from_secs(10)).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Loopback {
+    runtime: WebRtcRuntime,
+    pub offerer: RTCPeerConnection,
+    pub answerer: RTCPeerConnection,
+    link: Option<SimulatedNetwork>,
+}
+
+impl Loopback {
+    /// Two connections of the default configuration on the shared runtime.
+    pub fn new() -> Result<Self> {
+        let config = RTCConfiguration::default();
+        Self::with_configs(&WebRtcRuntime::shared()?, &config, &config)
+    }
+
+    pub fn with_configs(
+        runtime: &WebRtcRuntime,
+        offerer: &RTCConfiguration,
+        answerer: &RTCConfiguration,
+    ) -> Result<Self> {
+        Ok(Self {
+            offerer: runtime.create_peer_connection(offerer)?,
+            answerer: runtime.create_peer_connection(answerer)?,
+            runtime: runtime.clone(),
+            link: None,
+        })
+    }
+
+    /// Two connections of the default configuration on the shared runtime, which send
+    /// their packets to each other through a simulated link, see `SimulatedNetwork`.
+    pub fn with_link(conditions: LinkConditions) -> Result<Self> {
+        let link = SimulatedNetwork::new(conditions);
+        let mut config = RTCConfiguration::default();
+        config.set_socket_factory(link.clone());
+
+        let mut loopback = Self::with_configs(&WebRtcRuntime::shared()?, &config, &config)?;
+        loopback.link = Some(link);
+        Ok(loopback)
+    }
+
+    /// The link between the connections, None unless the loopback was created with one.
+    pub fn link(&self) -> Option<&SimulatedNetwork> {
+        self.link.as_ref()
+    }
+
+    pub fn runtime(&self) -> &WebRtcRuntime {
+        &self.runtime
+    }
+
+    /// Creates a local video track and adds it to the offerer, the frames written
+    /// to it are received by the answerer.
+    pub fn video_track(&self, id: &str) -> Result<Arc<MediaStreamTrack>> {
+        let track = Arc::new(self.runtime.create_video_track(id)?);
+        self.offerer.add_track(&track, STREAM_ID)?;
+        Ok(track)
+    }
+
+    /// Creates a local audio track and adds it to the offerer.
+    pub fn audio_track(&self, id: &str) -> Result<Arc<MediaStreamTrack>> {
+        let track = Arc::new(self.runtime.create_audio_track(id)?);
+        self.offerer.add_track(&track, STREAM_ID)?;
+        Ok(track)
+    }
+
+    /// Negotiates the connections, see `negotiate`.
+    pub async fn negotiate(&self) -> Result<()> {
+        negotiate(&self.offerer, &self.answerer).await
+    }
+
+    /// Negotiates the connections and waits for both of them to be connected.
+    pub async fn connect(&self, timeout: Duration) -> Result<()> {
+        time::timeout(timeout, async {
+            self.negotiate().await?;
+            futures::try_join!(self.offerer.connected(), self.answerer.connected())?;
+            Ok(())
+        })
+        .await
+        .map_err(|_| Error::Timeout)?
+    }
+
+    /// Closes both connections.
+    pub async fn close(self) -> Result<()> {
+        futures::try_join!(self.offerer.close(), self.answerer.close())?;
+        Ok(())
+    }
+}
+
+/// Runs an offer and answer exchange between two connections of the process, such as the
+/// renegotiation after a track is added to a connected loopback.
+pub async fn negotiate(offerer: &RTCPeerConnection, answerer: &RTCPeerConnection) -> Result<()> {
+    let offer = offerer.create_offer().await?;
+    offerer.set_local_description(offer).await?;
+    answerer.set_remote_description(gathered(offerer).await?).await?;
+
+    let answer = answerer.create_answer().await?;
+    answerer.set_local_description(answer).await?;
+    offerer.set_remote_description(gathered(answerer).await?).await?;
+    Ok(())
+}
+
+/// Waits for the gathering to complete, and copies the local description,
+/// which then carries every candidate.
+async fn gathered(peer: &RTCPeerConnection) -> Result<RTCSessionDescription> {
+    peer.ice_gathering_complete().await?;
+    let local = peer
+        .local_description()
+        .ok_or_else(|| Error::InvalidState("the local description is not set".to_string()))?;
+    RTCSessionDescription::new(local.get_type(), local.get_sdp()?)
+}
+
+/// A frame of a single color, such as the mid gray of a luma of 128.
+pub fn solid_video_frame(width: u32, height: u32, luma: u8) -> VideoFrame {
+    let luma_len = width as usize * height as usize;
+    let chroma_len = (width as usize).div_ceil(2) * (height as usize).div_ceil(2);
+    let mut data = vec![luma; luma_len + chroma_len * 2];
+    data[luma_len..].fill(128);
+    VideoFrame {
+        width,
+        height,
+        timestamp_us: 0,
+        rotation: 0,
+        data,
+    }
+}
+
+/// 10ms of silence.
+pub fn silent_audio_frame(sample_rate: u32, channels: u32) -> AudioFrame {
+    AudioFrame {
+        data: vec![0; (sample_rate / 100 * channels) as usize],
+        timestamp_us: 0,
+        sample_rate,
+        channels,
+    }
+}
+
+/// Writes the frames returned by `next`, given the index of the frame, to a local video
+/// track at the frame rate, until the task is aborted or the track refuses a frame.
+pub fn spawn_video(
+    track: Arc<MediaStreamTrack>,
+    fps: u32,
+    mut next: impl FnMut(u64) -> VideoFrame + Send + 'static,
+) -> JoinHandle<()> {
+    let period = Duration::from_secs(1) / fps.max(1);
+    tokio::spawn(async move {
+        let mut interval = time::interval(period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        for index in 0.. {
+            interval.tick().await;
+            if track.write_video_frame(&next(index)).is_err() {
+                break;
+            }
+        }
+    })
+}
+
+/// Writes the 10ms frames returned by `next`, given the index of the frame,
+/// to a local audio track, until the task is aborted or the track refuses a frame.
+pub fn spawn_audio(
+    track: Arc<MediaStreamTrack>,
+    mut next: impl FnMut(u64) -> AudioFrame + Send + 'static,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        // a late frame is followed by a burst, the jitter buffer of the receiver absorbs it.
+        let mut interval = time::interval(Duration::from_millis(10));
+        interval.set_missed_tick_behavior(MissedTickBehavior::Burst);
+        for index in 0.. {
+            interval.tick().await;
+            if track.write_audio_frame(&next(index)).is_err() {
+                break;
+            }
+        }
+    })
+}
+
+/// The 75% color bars, as the Y, U and V of BT.601.
+const BARS: [[u8; 3]; 7] = [
+    [180, 128, 128],
+    [162, 44, 142],
+    [131, 156, 44],
+    [112, 72, 58],
+    [84, 184, 198],
+    [65, 100, 212],
+    [35, 212, 114],
+];
+
+/// A synthetic video source, the color bars with a white box moving across the bottom of
+/// the picture, so that a received frame tells how far the stream went and a frozen stream
+/// shows as a still box.
+///
+/// The frames only depend on their index, two runs send the same pictures.
+#[derive(Clone, Copy, Debug)]
+pub struct VideoSource {
+    pub width: u32,
+    pub height: u32,
+    pub fps: u32,
+}
+
+impl VideoSource {
+    pub fn new(width: u32, height: u32, fps: u32) -> Self {
+        Self { width, height, fps }
+    }
+
+    /// The frame of an index, the box crosses the picture in two seconds.
+    pub fn frame(&self, index: u64) -> VideoFrame {
+        let (width, height) = (self.width as usize, self.height as usize);
+        let (chroma_width, chroma_height) = (width.div_ceil(2), height.div_ceil(2));
+        let mut data = vec![0; width * height + chroma_width * chroma_height * 2];
+
+        let size = (height / 4).max(2);
+        let travel = width.saturating_sub(size) as u64 + 1;
+        let period = self.fps.max(1) as u64 * 2;
+        let left = ((index % period) * travel / period) as usize;
+        let top = height - size;
+
+        let color = |x: usize, y: usize| {
+            if y >= top && x >= left && x < left + size {
+                BARS[0]
+            } else {
+                BARS[x * BARS.len() / width.max(1)]
+            }
+        };
+
+        let (luma, chroma) = data.split_at_mut(width * height);
+        let (u, v) = chroma.split_at_mut(chroma_width * chroma_height);
+        for y in 0..height {
+            for x in 0..width {
+                luma[y * width + x] = color(x, y)[0];
+            }
+        }
+
+        for y in 0..chroma_height {
+            for x in 0..chroma_width {
+                let [_, cb, cr] = color(x * 2, y * 2);
+                u[y * chroma_width + x] = cb;
+                v[y * chroma_width + x] = cr;
+            }
+        }
+
+        VideoFrame {
+            width: self.width,
+            height: self.height,
+            timestamp_us: index as i64 * 1_000_000 / self.fps.max(1) as i64,
+            rotation: 0,
+            data,
+        }
+    }
+
+    /// Writes the frames to a local video track, see `spawn_video`.
+    pub fn spawn(self, track: Arc<MediaStreamTrack>) -> JoinHandle<()> {
+        spawn_video(track, self.fps, move |index| self.frame(index))
+    }
+}
+
+/// The signal of a synthetic audio source.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tone {
+    /// A pure tone of a frequency in hertz.
+    Sine(f64),
+    /// A linear sweep from a frequency to another, restarted every period, which exercises
+    /// the whole band of the codec.
+    Chirp { from: f64, to: f64, period: Duration },
+}
+
+/// A synthetic audio source, a tone on every channel.
+///
+/// As the video source, the frames only depend on their index.
+#[derive(Clone, Copy, Debug)]
+pub struct AudioSource {
+    pub tone: Tone,
+    pub sample_rate: u32,
+    pub channels: u32,
+    /// The peak amplitude, between 0 and 1.
+    pub amplitude: f64,
+}
+
+impl AudioSource {
+    pub fn new(tone: Tone, sample_rate: u32, channels: u32) -> Self {
+        Self {
+            amplitude: 0.5,
+            sample_rate,
+            channels,
+            tone,
+        }
+    }
+
+    /// The 10ms frame of an index.
+    pub fn frame(&self, index: u64) -> AudioFrame {
+        let samples = self.sample_rate as u64 / 100;
+        let rate = self.sample_rate.max(1) as f64;
+        let amplitude = self.amplitude.clamp(0.0, 1.0) * i16::MAX as f64;
+
+        let mut data = Vec::with_capacity((samples * self.channels as u64) as usize);
+        for n in index * samples..(index + 1) * samples {
+            let t = n as f64 / rate;
+            let phase = match self.tone {
+                Tone::Sine(frequency) => frequency * t,
+                Tone::Chirp { from, to, period } => {
+                    let period = period.as_secs_f64().max(1.0 / rate);
+                    let t = t % period;
+                    from * t + (to - from) * t * t / (2.0 * period)
+                }
+            };
+
+            let sample = ((phase * std::f64::consts::TAU).sin() * amplitude) as i16;
+            data.extend(std::iter::repeat_n(sample, self.channels as usize));
+        }
+
+        AudioFrame {
+            timestamp_us: index as i64 * 10_000,
+            sample_rate: self.sample_rate,
+            channels: self.channels,
+            data,
+        }
+    }
+
+    /// Writes the frames to a local audio track, see `spawn_audio`.
+    pub fn spawn(self, track: Arc<MediaStreamTrack>) -> JoinHandle<()> {
+        spawn_audio(track, move |index| self.frame(index))
+    }
+}
@@ -0,0 +1,277 @@
+use crate::framing::FramedDataChannel;
+use crate::{Error, Result};
+
+use futures::stream::{self, Stream};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::{self, SeekFrom};
+use std::path::{Path, PathBuf};
+use tokio::fs::{File, OpenOptions};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use tokio::sync::watch;
+
+/// `[OFFER] + the offer as JSON`, sent by the sender.
+const OFFER: u8 = 0;
+
+/// `[ACCEPT] + the offset to resume at as a big endian u64`, sent by the receiver.
+const ACCEPT: u8 = 1;
+
+/// `[CHUNK] + the next bytes of the file`, sent by the sender.
+const CHUNK: u8 = 2;
+
+/// `[VERIFIED] + 1 when the hash of the received file matches the offer, 0 otherwise`,
+/// sent by the receiver.
+const VERIFIED: u8 = 3;
+
+/// The bytes of the file sent in a message, the framing layer splits it further.
+const CHUNK_SIZE: usize = 256 * 1024;
+
+/// The file a sender offers to the receiver.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileOffer {
+    /// The name of the file, without its directory.
+    pub name: String,
+    pub size: u64,
+    /// The SHA-256 of the file as lowercase hex.
+    pub sha256: String,
+}
+
+/// The progress of a transfer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Progress {
+    /// The bytes of the file the receiver has, including the ones it had before a resumed transfer.
+    pub transferred: u64,
+    pub total: u64,
+}
+
+/// Sends a file over a framed data channel, the other end runs a `FileReceiver`.
+///
+/// A channel carries one transfer at a time. When a transfer is interrupted, such as
+/// by a lost connection, sending the file again resumes it from the bytes the receiver
+/// already has, and the receiver checks the hash of the whole file before it is accepted.
+pub struct FileSender {
+    path: PathBuf,
+    progress: watch::Sender<Progress>,
+}
+
+impl FileSender {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            progress: watch::channel(Progress::default()).0,
+        }
+    }
+
+    /// The progress of the transfer, yielded as it changes, the stream ends with the transfer.
+    pub fn progress(&self) -> impl Stream<Item = Progress> {
+        progress(self.progress.subscribe())
+    }
+
+    /// Sends the file, which waits while the send buffer of the channel is full,
+    /// and returns once the receiver has checked the hash of the file.
+    pub async fn send(self, channel: &FramedDataChannel) -> Result<FileOffer> {
+        let mut file = File::open(&self.path).await?;
+        let offer = FileOffer {
+            name: self
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            size: file.metadata().await?.len(),
+            sha256: hash(&mut file).await?,
+        };
+
+        let mut message = vec![OFFER];
+        message.extend(serde_json::to_vec(&offer).map_err(|e| Error::Codec(e.to_string()))?);
+        channel.send(&message).await?;
+
+        let accept = recv(channel, ACCEPT).await?;
+        let offset = read_u64(&accept)?;
+        if offset > offer.size {
+            return Err(Error::InvalidFrame(format!(
+                "the receiver resumes at {} beyond the size of {}",
+                offset, offer.size
+            )));
+        }
+
+        file.seek(SeekFrom::Start(offset)).await?;
+        let mut transferred = offset;
+        let mut chunk = vec![0; CHUNK_SIZE + 1];
+        chunk[0] = CHUNK;
+        self.progress.send_replace(Progress {
+            total: offer.size,
+            transferred,
+        });
+
+        while transferred < offer.size {
+            let size = file.read(&mut chunk[1..]).await?;
+            if size == 0 {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+            }
+
+            channel.send(&chunk[..size + 1]).await?;
+            transferred += size as u64;
+            self.progress.send_replace(Progress {
+                total: offer.size,
+                transferred,
+            });
+        }
+
+        match recv(channel, VERIFIED).await?.as_slice() {
+            [1] => Ok(offer),
+            _ => Err(integrity_error(&offer)),
+        }
+    }
+}
+
+/// Receives a file sent by a `FileSender` into a path chosen by the application.
+///
+/// A partial file left at the path by an interrupted transfer is resumed. When the
+/// received file does not match the hash of the offer, it is truncated, so sending
+/// it again starts over.
+pub struct FileReceiver {
+    path: PathBuf,
+    progress: watch::Sender<Progress>,
+}
+
+impl FileReceiver {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            progress: watch::channel(Progress::default()).0,
+        }
+    }
+
+    /// The progress of the transfer, yielded as it changes, the stream ends with the transfer.
+    pub fn progress(&self) -> impl Stream<Item = Progress> {
+        progress(self.progress.subscribe())
+    }
+
+    /// Receives the file, and returns its offer once its hash has been checked.
+    pub async fn receive(self, channel: &FramedDataChannel) -> Result<FileOffer> {
+        let offer = recv(channel, OFFER).await?;
+        let offer: FileOffer = serde_json::from_slice(&offer).map_err(|e| Error::Codec(e.to_string()))?;
+
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&self.path)
+            .await?;
+
+        // a file larger than the offer is not a part of it.
+        let mut offset = file.metadata().await?.len();
+        if offset > offer.size {
+            file.set_len(0).await?;
+            offset = 0;
+        }
+
+        // the bytes already received are part of the hash.
+        let mut hasher = Sha256::new();
+        let mut chunk = vec![0; CHUNK_SIZE];
+        let mut remaining = offset;
+        while remaining > 0 {
+            let size = file.read(&mut chunk[..CHUNK_SIZE.min(remaining as usize)]).await?;
+            if size == 0 {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+            }
+
+            hasher.update(&chunk[..size]);
+            remaining -= size as u64;
+        }
+
+        let mut accept = vec![ACCEPT];
+        accept.extend_from_slice(&offset.to_be_bytes());
+        channel.send(&accept).await?;
+
+        let mut transferred = offset;
+        self.progress.send_replace(Progress {
+            total: offer.size,
+            transferred,
+        });
+
+        while transferred < offer.size {
+            let chunk = recv(channel, CHUNK).await?;
+            if transferred + chunk.len() as u64 > offer.size {
+                return Err(Error::InvalidFrame(format!(
+                    "the sender sends beyond the size of {}",
+                    offer.size
+                )));
+            }
+
+            file.write_all(&chunk).await?;
+            hasher.update(&chunk);
+            transferred += chunk.len() as u64;
+            self.progress.send_replace(Progress {
+                total: offer.size,
+                transferred,
+            });
+        }
+
+        file.flush().await?;
+        let verified = hex(&hasher.finalize()) == offer.sha256;
+        if !verified {
+            file.set_len(0).await?;
+        }
+
+        channel.send(&[VERIFIED, verified as u8]).await?;
+        if verified {
+            Ok(offer)
+        } else {
+            Err(integrity_error(&offer))
+        }
+    }
+}
+
+/// Receives the next message of the transfer, which must be of the given kind.
+async fn recv(channel: &FramedDataChannel, kind: u8) -> Result<Vec<u8>> {
+    let mut message = channel.recv().await?.ok_or(Error::Closed)?;
+    if message.first() != Some(&kind) {
+        return Err(Error::InvalidFrame(format!("expected a transfer message of kind {}", kind)));
+    }
+
+    message.remove(0);
+    Ok(message)
+}
+
+fn progress(receiver: watch::Receiver<Progress>) -> impl Stream<Item = Progress> {
+    stream::unfold(receiver, |mut receiver| async move {
+        receiver.changed().await.ok()?;
+        let progress = *receiver.borrow_and_update();
+        Some((progress, receiver))
+    })
+}
+
+async fn hash(file: &mut File) -> Result<String> {
+    let mut hasher = Sha256::new();
+    let mut chunk = vec![0; CHUNK_SIZE];
+    loop {
+        let size = file.read(&mut chunk).await?;
+        if size == 0 {
+            break;
+        }
+
+        hasher.update(&chunk[..size]);
+    }
+
+    Ok(hex(&hasher.finalize()))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn read_u64(bytes: &[u8]) -> Result<u64> {
+    let bytes = <[u8; 8]>::try_from(bytes)
+        .map_err(|_| Error::InvalidFrame("expected a big endian u64".to_string()))?;
+    Ok(u64::from_be_bytes(bytes))
+}
+
+fn integrity_error(offer: &FileOffer) -> Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("the received {} does not match its hash", offer.name),
+    )
+    .into()
+}
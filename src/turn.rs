@@ -0,0 +1,124 @@
+use crate::{Error, RTCIceServer, RTCPeerConnection};
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+/// A time-limited TURN credential, such as the ones issued by the coturn REST API,
+/// where the username embeds the expiry timestamp and the credential is an HMAC of it.
+#[derive(Clone, Debug)]
+pub struct TurnCredential {
+    pub username: String,
+    pub credential: String,
+    pub expires_at: Instant,
+}
+
+impl TurnCredential {
+    /// Creates a credential which is valid for `ttl` from now.
+    pub fn new(username: &str, credential: &str, ttl: Duration) -> Self {
+        Self {
+            username: username.to_string(),
+            credential: credential.to_string(),
+            expires_at: Instant::now() + ttl,
+        }
+    }
+}
+
+/// The credentials of a TURN server, refreshed through a user supplied async callback
+/// before they expire. The errors of the callback are returned as is, so its error type
+/// only has to be convertible from the errors of the crate.
+///
+/// The server returned by `ice_server` goes into the configuration of the connection,
+/// then `keep_fresh` keeps handing new credentials to the connection for its lifetime.
+pub struct TurnCredentials<F> {
+    urls: Vec<String>,
+    refresh: F,
+    margin: Duration,
+    current: Option<TurnCredential>,
+    // when the current credential was received, the start of its lifetime.
+    refreshed_at: Instant,
+}
+
+impl<F, Fut, E> TurnCredentials<F>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<TurnCredential, E>>,
+    E: From<Error>,
+{
+    pub fn new(urls: &[&str], refresh: F) -> Self {
+        Self {
+            urls: urls.iter().map(|url| url.to_string()).collect(),
+            margin: Duration::from_secs(60),
+            current: None,
+            refreshed_at: Instant::now(),
+            refresh,
+        }
+    }
+
+    /// How long before the expiry the credential is refreshed, one minute by default.
+    /// A credential which lives no longer than twice the margin is refreshed halfway
+    /// through its lifetime instead.
+    pub fn set_margin(&mut self, margin: Duration) {
+        self.margin = margin;
+    }
+
+    /// The ICE server with a valid credential, the credential is refreshed first when
+    /// it is missing or about to expire, see `set_margin`.
+    pub async fn ice_server(&mut self) -> Result<RTCIceServer, E> {
+        let expired = match &self.current {
+            Some(current) => Instant::now() >= self.refresh_at(current),
+            None => true,
+        };
+
+        if expired {
+            let credential = (self.refresh)().await?;
+            self.current = Some(self.check(credential)?);
+            self.refreshed_at = Instant::now();
+        }
+
+        Ok(self.as_ice_server()?)
+    }
+
+    /// Refreshes the credential before every expiry and hands it to the connection,
+    /// returns when a refresh or the update of the connection fails, or when the refresh
+    /// returns a credential which has already expired.
+    pub async fn keep_fresh(&mut self, peer: &RTCPeerConnection) -> Result<(), E> {
+        loop {
+            if let Some(current) = &self.current {
+                tokio::time::sleep_until(self.refresh_at(current).into()).await;
+            }
+
+            let credential = (self.refresh)().await?;
+            self.current = Some(self.check(credential)?);
+            self.refreshed_at = Instant::now();
+            peer.update_ice_server(&self.as_ice_server()?)?;
+        }
+    }
+
+    /// When a credential is refreshed, the margin before its expiry, or halfway through
+    /// its lifetime when it lives no longer than twice the margin.
+    fn refresh_at(&self, credential: &TurnCredential) -> Instant {
+        let lifetime = credential.expires_at.saturating_duration_since(self.refreshed_at);
+        self.refreshed_at + lifetime.saturating_sub(self.margin).max(lifetime / 2)
+    }
+
+    fn check(&self, credential: TurnCredential) -> crate::Result<TurnCredential> {
+        if credential.expires_at <= Instant::now() {
+            return Err(Error::InvalidState(
+                "the refreshed TURN credential has already expired".to_string(),
+            ));
+        }
+
+        Ok(credential)
+    }
+
+    fn as_ice_server(&self) -> crate::Result<RTCIceServer> {
+        let mut server = RTCIceServer::default();
+        server.set_urls(&self.urls.iter().map(|url| url.as_str()).collect::<Vec<_>>())?;
+        if let Some(current) = &self.current {
+            server.set_username(&current.username)?;
+            server.set_credential(&current.credential)?;
+        }
+
+        Ok(server)
+    }
+}
@@ -0,0 +1,134 @@
+use crate::framing::FramedDataChannel;
+use crate::{Error, RTCDataChannel, Result};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// The encoding of the messages of a typed data channel, both ends of the channel
+/// must use the same codec.
+pub trait Codec: Send + Sync {
+    fn encode<T: Serialize>(&self, value: &T) -> Result<Vec<u8>>;
+    fn decode<T: DeserializeOwned>(&self, data: &[u8]) -> Result<T>;
+}
+
+/// JSON, readable when the messages are inspected, at the cost of their size.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Json;
+
+impl Codec for Json {
+    fn encode<T: Serialize>(&self, value: &T) -> Result<Vec<u8>> {
+        serde_json::to_vec(value).map_err(|e| Error::Codec(e.to_string()))
+    }
+
+    fn decode<T: DeserializeOwned>(&self, data: &[u8]) -> Result<T> {
+        serde_json::from_slice(data).map_err(|e| Error::Codec(e.to_string()))
+    }
+}
+
+/// bincode, compact and fast, but only understood by the implementations of bincode.
+#[cfg(feature = "bincode")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Bincode;
+
+#[cfg(feature = "bincode")]
+impl Codec for Bincode {
+    fn encode<T: Serialize>(&self, value: &T) -> Result<Vec<u8>> {
+        bincode::serialize(value).map_err(|e| Error::Codec(e.to_string()))
+    }
+
+    fn decode<T: DeserializeOwned>(&self, data: &[u8]) -> Result<T> {
+        bincode::deserialize(data).map_err(|e| Error::Codec(e.to_string()))
+    }
+}
+
+/// CBOR, a compact binary encoding with implementations in most languages.
+#[cfg(feature = "cbor")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Cbor;
+
+#[cfg(feature = "cbor")]
+impl Codec for Cbor {
+    fn encode<T: Serialize>(&self, value: &T) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        ciborium::into_writer(value, &mut data).map_err(|e| Error::Codec(e.to_string()))?;
+        Ok(data)
+    }
+
+    fn decode<T: DeserializeOwned>(&self, data: &[u8]) -> Result<T> {
+        ciborium::from_reader(data).map_err(|e| Error::Codec(e.to_string()))
+    }
+}
+
+/// A data channel carrying the values of a type, such as the control messages of an
+/// application, encoded by a codec and framed so a value may exceed the max message size
+/// of the transport, see `FramedDataChannel`.
+///
+/// ```no_run
+/// use serde::{Deserialize, Serialize};
+/// use webrtc::typed::{Json, TypedDataChannel};
+///
+/// #[derive(Serialize, Deserialize)]
+/// enum Control {
+///     Mute(bool),
+///     Resize { width: u32, height: u32 },
+/// }
+///
+/// # async fn example(channel: std::sync::Arc<webrtc::RTCDataChannel>) -> webrtc::Result<()> {
+/// let channel = TypedDataChannel::<Control, _>::negotiate(channel, Json).await?;
+/// channel.send(&Control::Mute(true)).await?;
+/// while let Some(control) = channel.recv().await? {
+///     // ...
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct TypedDataChannel<T, C = Json> {
+    framed: FramedDataChannel,
+    codec: C,
+    _value: PhantomData<fn() -> T>,
+}
+
+impl<T, C> TypedDataChannel<T, C>
+where
+    T: Serialize + DeserializeOwned,
+    C: Codec,
+{
+    /// Negotiates the framing layer on the channel, see `FramedDataChannel::negotiate`.
+    pub async fn negotiate(channel: Arc<RTCDataChannel>, codec: C) -> Result<Self> {
+        let framed =
+            FramedDataChannel::negotiate(channel, FramedDataChannel::DEFAULT_MAX_MESSAGE_SIZE).await?;
+        Ok(Self::new(framed, codec))
+    }
+
+    /// Types a channel on which the framing layer is already negotiated.
+    pub fn new(framed: FramedDataChannel, codec: C) -> Self {
+        Self {
+            _value: PhantomData,
+            framed,
+            codec,
+        }
+    }
+
+    pub async fn send(&self, value: &T) -> Result<()> {
+        self.framed.send(&self.codec.encode(value)?).await
+    }
+
+    /// Receives the next value, None once the channel is closed. A value which can
+    /// not be decoded is an error, the values after it are still received.
+    pub async fn recv(&self) -> Result<Option<T>> {
+        match self.framed.recv().await? {
+            Some(data) => self.codec.decode(&data).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    pub fn framed(&self) -> &FramedDataChannel {
+        &self.framed
+    }
+
+    pub fn into_framed(self) -> FramedDataChannel {
+        self.framed
+    }
+}
@@ -0,0 +1,122 @@
+use crate::ffi::raw;
+use crate::{MediaType, Result, RtpSender, WebRtcRuntime};
+
+use std::ffi::CStr;
+use std::fmt;
+
+/// The crate features enabled in this build.
+const FEATURES: &[(&str, bool)] = &[
+    ("dlopen", cfg!(feature = "dlopen")),
+    ("vendored", cfg!(feature = "vendored")),
+    ("media-file", cfg!(feature = "media-file")),
+    ("media-gstreamer", cfg!(feature = "media-gstreamer")),
+    ("media-cpal", cfg!(feature = "media-cpal")),
+    ("media-wgpu", cfg!(feature = "media-wgpu")),
+    ("e2ee", cfg!(feature = "e2ee")),
+    ("metrics", cfg!(feature = "metrics")),
+    ("transfer", cfg!(feature = "transfer")),
+    ("bincode", cfg!(feature = "bincode")),
+    ("cbor", cfg!(feature = "cbor")),
+    ("image", cfg!(feature = "image")),
+];
+
+/// The build of the crate and of the native library, to branch on the capabilities of
+/// libwebrtc and to include in bug reports, its Display is a short summary of one line
+/// per field.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Version {
+    /// The version of the crate.
+    pub crate_version: &'static str,
+    /// The milestone of the branch of libwebrtc, such as 96, None when the native library
+    /// was built without it.
+    pub milestone: Option<u32>,
+    /// The commit of libwebrtc, None when the native library was built without it.
+    pub commit: Option<String>,
+    /// The crate features enabled in this build.
+    pub features: Vec<&'static str>,
+    /// The names of the video codecs the senders can encode, such as "VP8" or "H264",
+    /// in the order of preference of libwebrtc.
+    pub video_codecs: Vec<String>,
+    /// The names of the audio codecs the senders can encode, such as "opus".
+    pub audio_codecs: Vec<String>,
+}
+
+/// The version of the crate and of the libwebrtc it runs on, which loads the native library
+/// and creates the shared runtime if they are not yet, see `WebRtcRuntime::shared`.
+///
+/// ```no_run
+/// let version = webrtc::version()?;
+/// if version.video_codecs.iter().any(|codec| codec == "AV1") {
+///     println!("AV1 is available");
+/// }
+///
+/// println!("{}", version);
+/// # Ok::<(), webrtc::Error>(())
+/// ```
+pub fn version() -> Result<Version> {
+    // the runtime loads the library first with the dlopen feature.
+    WebRtcRuntime::shared()?;
+
+    let mut raw = raw::RTCVersion {
+        milestone: 0,
+        commit: std::ptr::null(),
+    };
+
+    unsafe { raw::rtc_get_version(&mut raw) };
+    let commit = if raw.commit.is_null() {
+        None
+    } else {
+        let commit = unsafe { CStr::from_ptr(raw.commit) };
+        Some(commit.to_string_lossy().into_owned()).filter(|commit| !commit.is_empty())
+    };
+
+    Ok(Version {
+        crate_version: env!("CARGO_PKG_VERSION"),
+        milestone: (raw.milestone > 0).then_some(raw.milestone as u32),
+        features: FEATURES
+            .iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(name, _)| *name)
+            .collect(),
+        video_codecs: codecs(MediaType::Video)?,
+        audio_codecs: codecs(MediaType::Audio)?,
+        commit,
+    })
+}
+
+/// The deduplicated names of the codecs of a kind, the capabilities list a codec once
+/// per profile.
+fn codecs(kind: MediaType) -> Result<Vec<String>> {
+    let mut names: Vec<String> = Vec::new();
+    for codec in RtpSender::capabilities(kind)?.codecs {
+        let name = match codec.mime_type.split_once('/') {
+            Some((_, name)) => name.to_string(),
+            None => codec.mime_type,
+        };
+
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+
+    Ok(names)
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "webrtc {}", self.crate_version)?;
+        match self.milestone {
+            Some(milestone) => write!(f, "libwebrtc M{}", milestone)?,
+            None => write!(f, "libwebrtc unknown")?,
+        }
+
+        if let Some(commit) = &self.commit {
+            write!(f, " ({})", commit)?;
+        }
+
+        writeln!(f)?;
+        writeln!(f, "features: {}", self.features.join(", "))?;
+        writeln!(f, "video codecs: {}", self.video_codecs.join(", "))?;
+        write!(f, "audio codecs: {}", self.audio_codecs.join(", "))
+    }
+}